@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some(ast) = zyra::fuzz::fuzz_parse(data) {
+        let _ = zyra::fuzz::fuzz_compile(&ast);
+    }
+});
@@ -0,0 +1,234 @@
+//! Build-time asset processing for `zyra assets`
+//!
+//! `zyra assets atlas sprites/ --out atlas.png` packs every image in a
+//! directory into one texture atlas and writes a manifest next to it
+//! (`atlas.atlas`) with one `name x y width height` line per sprite. A game
+//! then loads a single image instead of one file per sprite:
+//! `load_sprite("atlas.png#player")` cuts `player` out of the atlas.
+//!
+//! A project can also list the assets it ships in zyra.toml
+//! (`assets = ["atlas.png#player", "sounds/"]`); `zyra check` verifies them.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Image file extensions picked up when packing a directory
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "gif", "tga"];
+
+/// Transparent pixels kept between sprites, so scaled or filtered drawing
+/// never picks up a neighbor's edge
+const PADDING: u32 = 1;
+
+/// Where one sprite sits in the atlas
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasEntry {
+    /// Path relative to the packed directory, without extension (`enemies/bat`)
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Manifest path for an atlas image: `atlas.png` -> `atlas.atlas`
+pub fn manifest_path(atlas: &Path) -> PathBuf {
+    atlas.with_extension("atlas")
+}
+
+/// Place rectangles on shelves: tallest first, each row filled left to right
+/// up to a width near the square root of the total area. Returns the atlas
+/// size and the position of every input, in input order
+pub fn pack(sizes: &[(u32, u32)]) -> (u32, u32, Vec<(u32, u32)>) {
+    let area: u64 = sizes
+        .iter()
+        .map(|&(w, h)| (w + PADDING) as u64 * (h + PADDING) as u64)
+        .sum();
+    let widest = sizes.iter().map(|&(w, _)| w).max().unwrap_or(0);
+    let max_width = ((area as f64).sqrt().ceil() as u32).max(widest);
+
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+    let mut positions = vec![(0, 0); sizes.len()];
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    let mut width = 0;
+    for i in order {
+        let (w, h) = sizes[i];
+        if x > 0 && x + w > max_width {
+            y += shelf_height + PADDING;
+            x = 0;
+            shelf_height = 0;
+        }
+        positions[i] = (x, y);
+        width = width.max(x + w);
+        shelf_height = shelf_height.max(h);
+        x += w + PADDING;
+    }
+    (width, y + shelf_height, positions)
+}
+
+/// Image files under `dir`, recursively, in name order
+fn image_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Pack the images under `dir` into `out` and write its manifest
+pub fn build_atlas(dir: &Path, out: &Path) -> io::Result<Vec<AtlasEntry>> {
+    let mut files = image_files(dir)?;
+    // An atlas written into the packed directory by an earlier run isn't a sprite
+    if let Ok(previous) = fs::canonicalize(out) {
+        files.retain(|path| fs::canonicalize(path).ok().as_ref() != Some(&previous));
+    }
+    if files.is_empty() {
+        return Err(invalid(format!("no images found in {}", dir.display())));
+    }
+    let images = files
+        .iter()
+        .map(|path| {
+            image::open(path)
+                .map(|img| img.to_rgba8())
+                .map_err(|e| invalid(format!("{}: {}", path.display(), e)))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let sizes: Vec<(u32, u32)> = images.iter().map(|img| img.dimensions()).collect();
+    let (width, height, positions) = pack(&sizes);
+    let mut atlas = image::RgbaImage::new(width, height);
+    let mut entries = Vec::new();
+    for ((path, img), &(x, y)) in files.iter().zip(&images).zip(&positions) {
+        image::imageops::replace(&mut atlas, img, x as i64, y as i64);
+        let name = path
+            .strip_prefix(dir)
+            .unwrap_or(path)
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/");
+        entries.push(AtlasEntry {
+            name,
+            x,
+            y,
+            width: img.width(),
+            height: img.height(),
+        });
+    }
+
+    atlas
+        .save(out)
+        .map_err(|e| invalid(format!("{}: {}", out.display(), e)))?;
+    let manifest: String = entries
+        .iter()
+        .map(|e| format!("{} {} {} {} {}\n", e.name, e.x, e.y, e.width, e.height))
+        .collect();
+    fs::write(manifest_path(out), manifest)?;
+    Ok(entries)
+}
+
+/// Parse a manifest written by `build_atlas`
+pub fn parse_manifest(text: &str) -> io::Result<Vec<AtlasEntry>> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            // The name comes first and may contain spaces, so split from the right
+            let parts: Vec<&str> = line.trim_end().rsplitn(5, ' ').collect();
+            let number = |i: usize| parts.get(i).and_then(|n| n.parse::<u32>().ok());
+            match (parts.get(4), number(3), number(2), number(1), number(0)) {
+                (Some(name), Some(x), Some(y), Some(width), Some(height)) => Ok(AtlasEntry {
+                    name: name.to_string(),
+                    x,
+                    y,
+                    width,
+                    height,
+                }),
+                _ => Err(invalid(format!("bad atlas manifest line: '{}'", line))),
+            }
+        })
+        .collect()
+}
+
+/// Whether an asset listed in zyra.toml exists under the project root. A
+/// sprite in an atlas (`atlas.png#player`) must be in the atlas manifest
+pub fn asset_exists(root: &Path, asset: &str) -> bool {
+    match asset.split_once('#') {
+        Some((atlas, name)) => fs::read_to_string(manifest_path(&root.join(atlas)))
+            .ok()
+            .and_then(|text| parse_manifest(&text).ok())
+            .is_some_and(|entries| entries.iter().any(|entry| entry.name == name)),
+        None => root.join(asset).exists(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_rectangles_do_not_overlap() {
+        let sizes = [(16, 16), (32, 8), (8, 24), (16, 16), (4, 4)];
+        let (width, height, positions) = pack(&sizes);
+        let rects: Vec<_> = positions
+            .iter()
+            .zip(&sizes)
+            .map(|(&(x, y), &(w, h))| (x, y, x + w, y + h))
+            .collect();
+        for (i, a) in rects.iter().enumerate() {
+            assert!(a.2 <= width && a.3 <= height);
+            for b in &rects[i + 1..] {
+                assert!(a.2 <= b.0 || b.2 <= a.0 || a.3 <= b.1 || b.3 <= a.1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_atlas_manifest_round_trips() {
+        let dir = std::env::temp_dir().join(format!("zyra_atlas_{}", std::process::id()));
+        fs::create_dir_all(dir.join("enemies")).unwrap();
+        image::RgbaImage::from_pixel(3, 2, image::Rgba([255, 0, 0, 255]))
+            .save(dir.join("player.png"))
+            .unwrap();
+        image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 255, 255]))
+            .save(dir.join("enemies").join("bat.png"))
+            .unwrap();
+
+        let out = std::env::temp_dir().join(format!("zyra_atlas_{}.png", std::process::id()));
+        let entries = build_atlas(&dir, &out).unwrap();
+        let manifest = fs::read_to_string(manifest_path(&out)).unwrap();
+        let atlas = image::open(&out).unwrap().to_rgba8();
+        let root = out.parent().unwrap();
+        let atlas_name = out.file_name().unwrap().to_string_lossy().to_string();
+        assert!(asset_exists(root, &format!("{}#enemies/bat", atlas_name)));
+        assert!(!asset_exists(root, &format!("{}#enemies/cat", atlas_name)));
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_file(manifest_path(&out)).unwrap();
+        fs::remove_file(&out).unwrap();
+        assert!(!asset_exists(root, &atlas_name));
+
+        assert_eq!(parse_manifest(&manifest).unwrap(), entries);
+        let bat = entries.iter().find(|e| e.name == "enemies/bat").unwrap();
+        assert_eq!(
+            atlas.get_pixel(bat.x, bat.y),
+            &image::Rgba([0, 0, 255, 255])
+        );
+    }
+}
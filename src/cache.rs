@@ -0,0 +1,154 @@
+//! Build cache for multi-file projects
+//!
+//! `zyra run` stores the compiled bytecode of a project in `.zyra-cache/`,
+//! with a manifest of the content hash of the entry file, every module it
+//! was built from and the project's zyra.toml. A later run whose compile
+//! options and inputs all hash the same loads the cached bytecode instead of
+//! lexing, parsing and compiling again; any edited file invalidates it.
+//! Only builds without warnings are stored, since a cache hit skips the
+//! analysis that would report them.
+//!
+//! Bytecode is cached per program, not per module: the compiler links a
+//! whole program at once, so an edit to one module recompiles all of them.
+//! What is reused per module is the analysis, through the interface files of
+//! `crate::interface`. Each entry and set of options has one cache slot,
+//! which a rebuild overwrites.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::compiler::Bytecode;
+
+/// Directory, relative to the project root, holding cached builds
+pub const CACHE_DIR: &str = ".zyra-cache";
+
+/// 64-bit FNV-1a hash: stable across Rust versions, unlike `DefaultHasher`
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Compiled programs keyed by their entry file and compile options, checked
+/// against the hashes of the files they were built from
+pub struct BuildCache {
+    dir: PathBuf,
+    /// The project's zyra.toml, an input of every build
+    config: PathBuf,
+}
+
+impl BuildCache {
+    /// Cache of the project rooted at `root`
+    pub fn new(root: &Path) -> Self {
+        Self {
+            dir: root.join(CACHE_DIR),
+            config: root.join("zyra.toml"),
+        }
+    }
+
+    /// Cache file name for an entry: anything that changes the bytecode without
+    /// changing a file (`options`, e.g. overflow mode and edition) is part of it
+    fn key(entry: &Path, options: &str) -> String {
+        let mut bytes = entry.to_string_lossy().into_owned().into_bytes();
+        bytes.extend_from_slice(options.as_bytes());
+        bytes.extend_from_slice(env!("CARGO_PKG_VERSION").as_bytes());
+        format!("{:016x}", content_hash(&bytes))
+    }
+
+    /// Cached bytecode for an entry whose source is `source`, if it and every
+    /// module it was built from are unchanged
+    pub fn load(&self, entry: &Path, source: &str, options: &str) -> Option<Bytecode> {
+        let key = Self::key(entry, options);
+        let manifest = fs::read_to_string(self.dir.join(format!("{}.modules", key))).ok()?;
+        for line in manifest.lines() {
+            let (hash, path) = line.split_once(' ')?;
+            let contents = if Path::new(path) == entry {
+                source.as_bytes().to_vec()
+            } else {
+                fs::read(path).ok()?
+            };
+            if format!("{:016x}", content_hash(&contents)) != hash {
+                return None;
+            }
+        }
+        let data = fs::read(self.dir.join(format!("{}.zyc", key))).ok()?;
+        Bytecode::deserialize(&data).ok()
+    }
+
+    /// Store the bytecode built from an entry and the module files it imported
+    pub fn store(
+        &self,
+        entry: &Path,
+        source: &str,
+        options: &str,
+        modules: &[PathBuf],
+        bytecode: &Bytecode,
+    ) -> io::Result<()> {
+        let key = Self::key(entry, options);
+        let mut manifest = format!(
+            "{:016x} {}\n",
+            content_hash(source.as_bytes()),
+            entry.display()
+        );
+        for module in modules.iter().chain([&self.config]) {
+            let contents = fs::read(module)?;
+            manifest.push_str(&format!(
+                "{:016x} {}\n",
+                content_hash(&contents),
+                module.display()
+            ));
+        }
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(format!("{}.zyc", key)), bytecode.serialize())?;
+        // The manifest goes last: it vouches for the bytecode written before it
+        fs::write(self.dir.join(format!("{}.modules", key)), manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edited_module_invalidates_cached_build() {
+        let dir = std::env::temp_dir().join(format!("zyra_cache_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("zyra.toml"), "[build]\nentry = \"main.zr\"\n").unwrap();
+        let module = dir.join("util.zr");
+        fs::write(&module, "pub func one() -> int { 1 }").unwrap();
+
+        let cache = BuildCache::new(&dir);
+        let entry = dir.join("main.zr");
+        let mut bytecode = Bytecode::new();
+        bytecode.source_file = "main.zr".to_string();
+        cache
+            .store(
+                &entry,
+                "source",
+                "wrap",
+                std::slice::from_ref(&module),
+                &bytecode,
+            )
+            .unwrap();
+
+        let hit = cache.load(&entry, "source", "wrap");
+        let other_options = cache.load(&entry, "source", "error");
+        let other_source = cache.load(&entry, "edited", "wrap");
+        fs::write(&module, "pub func one() -> int { 2 }").unwrap();
+        let after_edit = cache.load(&entry, "source", "wrap");
+
+        // A rebuild of the same entry replaces its cached build
+        cache
+            .store(&entry, "edited", "wrap", &[module], &bytecode)
+            .unwrap();
+        let cached_files = fs::read_dir(dir.join(CACHE_DIR)).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(hit.unwrap().source_file, "main.zr");
+        assert!(other_options.is_none());
+        assert!(other_source.is_none());
+        assert!(after_edit.is_none());
+        assert_eq!(cached_files, 2);
+    }
+}
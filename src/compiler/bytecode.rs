@@ -1,7 +1,11 @@
 //! Bytecode definitions for Zyra VM
 
+use super::OverflowMode;
+use crate::semantic::ZyraType;
 use std::fmt;
 
+pub use super::ordered::{OrderedMap, OrderedSet};
+
 /// Bytecode instruction set
 #[derive(Debug, Clone, PartialEq)]
 pub enum Instruction {
@@ -14,6 +18,12 @@ pub enum Instruction {
     /// Store the top of stack into a variable
     StoreVar(String),
 
+    /// Load a `static mut` global's value onto the stack
+    LoadGlobal(String),
+
+    /// Store the top of stack into a `static mut` global
+    StoreGlobal(String),
+
     /// Pop the top value from the stack
     Pop,
 
@@ -24,6 +34,18 @@ pub enum Instruction {
     Div,
     Mod,
     Neg,
+    /// Add/Sub/Mul that fail with a runtime error when an integer result overflows
+    CheckedAdd,
+    CheckedSub,
+    CheckedMul,
+    /// Add/Sub/Mul that clamp integer results to the bounds of their type
+    SaturatingAdd,
+    SaturatingSub,
+    SaturatingMul,
+    /// Give a plain integer on top of the stack the named declared type, so
+    /// arithmetic in that type overflows at its bounds; a value out of range
+    /// is handled as the mode says. Other values are left as they are.
+    Narrow(String, OverflowMode),
 
     // Comparison operations
     Eq,
@@ -38,14 +60,31 @@ pub enum Instruction {
     Or,
     Not,
 
+    // Bitwise operations (integers)
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+
     // Control flow
     Jump(usize),
     JumpIfFalse(usize),
+    /// Jump on the string on top of the stack, which is popped: its case is
+    /// looked up by `str_hash` and confirmed by comparing the strings. Strings
+    /// without a case jump to `default`; other values fall through.
+    /// Cases are (hash, string, address), sorted by hash.
+    SwitchStr {
+        cases: Vec<(u64, String, usize)>,
+        default: usize,
+    },
 
     // Function operations
     Call(String, usize), // function name, arg count
     /// Method call: method name, arg count (receiver is pushed first, then args)
     MethodCall(String, usize),
+    /// Call a closure value: arg count (the closure is pushed first, then args)
+    CallValue(usize),
     Return,
 
     // Memory management instructions (ownership & borrowing)
@@ -63,11 +102,24 @@ pub enum Instruction {
     EndBorrow(String),
 
     // Data structures
-    MakeList(usize),   // Array (fixed size): element count
+    MakeArray(usize),  // Array (fixed size): element count
     MakeVec(usize),    // Vec (dynamic): element count
+    MakeMap(usize),    // Map: entry count (key and value pushed per entry)
+    MakeSet(usize),    // Set: element count
+    MakeTuple(usize),  // Tuple: element count
     MakeObject(usize), // field count
     GetField(String),
     SetField(String),
+    /// Build a struct from field values on the stack; `slots[i]` is the layout index
+    /// of the i-th pushed value, so fields can be evaluated in source order
+    MakeStruct {
+        type_id: u32,
+        slots: Vec<u32>,
+    },
+    /// Read field `idx` of a struct with the given type id: (type id, field index)
+    GetFieldIdx(u32, u32),
+    /// Write field `idx` of a struct: stack [value, obj] like SetField
+    SetFieldIdx(u32, u32),
     GetIndex,
     SetIndex,
 
@@ -86,6 +138,10 @@ pub enum Instruction {
     Dup,
     /// Check if string contains substring: stack [string, substr] => bool
     StrContains,
+    /// Build an enum value from the payload on top of the stack: (type id, variant id)
+    MakeEnum(u32, u32),
+    /// Test whether the top of stack is the given enum variant: (type id, variant id) => bool
+    IsVariant(u32, u32),
 
     // Halt execution
     Halt,
@@ -95,10 +151,11 @@ pub enum Instruction {
     Cast(String),
 
     // Closures
-    /// Create a closure: MakeClosure(function_name, param_count)
+    /// Create a closure on the heap, copying the variables it captures
     MakeClosure {
         func_name: String,
         param_count: usize,
+        captures: Vec<String>,
     },
 }
 
@@ -133,8 +190,14 @@ pub enum Value {
     Vec(Vec<Value>),
     List(Vec<Value>),  // Legacy
     Array(Vec<Value>), // Fixed size (runtime representation same as Vec)
-
-    Object(std::collections::HashMap<String, Value>),
+    /// Tuple `(a, b)`; stored inline like Array
+    Tuple(Vec<Value>),
+
+    Object(OrderedMap<String, Value>),
+    /// Map<K, V> built with `map{...}`; lives on the heap like Vec
+    Map(OrderedMap<MapKey, Value>),
+    /// Set<T> built with `set{...}`; elements are hashed like map keys
+    Set(OrderedSet<MapKey>),
     Function {
         name: String,
         params: Vec<String>,
@@ -159,13 +222,77 @@ pub enum Value {
     /// The usize is the HeapId for lookup in the VM's heap
     Ref(usize),
 
-    /// Closure value with function name and captured environment
+    /// Closure value with function name and captured environment: the
+    /// variables it uses from where it was created, as (name, value) pairs.
+    /// Closures live on the heap, so the environment is shared by every copy
+    /// of the closure and persists between calls
     Closure {
         func_name: String,
         param_count: usize,
+        env: Vec<(String, Value)>,
+    },
+
+    /// Enum variant with integer tags into `Bytecode::enums` and an optional payload
+    Enum {
+        type_id: u32,
+        variant_id: u32,
+        payload: Option<Box<Value>>,
+    },
+
+    /// Struct with fields in layout order; `type_id` indexes `Bytecode::struct_names`
+    Struct {
+        type_id: u32,
+        fields: Vec<Value>,
     },
 }
 
+/// Key of a runtime Map or element of a Set: the kinds of values that can be hashed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Int(i64),
+    Bool(bool),
+    Char(char),
+    String(String),
+}
+
+impl MapKey {
+    /// The key for a value, or None if the value can't be a key (e.g. a float)
+    pub fn from_value(value: &Value) -> Option<MapKey> {
+        match value {
+            Value::Int(n) | Value::I64(n) => Some(MapKey::Int(*n)),
+            Value::I32(n) => Some(MapKey::Int(*n as i64)),
+            Value::I8(n) => Some(MapKey::Int(*n as i64)),
+            Value::U8(n) => Some(MapKey::Int(*n as i64)),
+            Value::U32(n) => Some(MapKey::Int(*n as i64)),
+            Value::U64(n) => Some(MapKey::Int(*n as i64)),
+            Value::Bool(b) => Some(MapKey::Bool(*b)),
+            Value::Char(c) => Some(MapKey::Char(*c)),
+            Value::String(s) => Some(MapKey::String(s.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            MapKey::Int(n) => Value::Int(*n),
+            MapKey::Bool(b) => Value::Bool(*b),
+            MapKey::Char(c) => Value::Char(*c),
+            MapKey::String(s) => Value::String(s.clone()),
+        }
+    }
+}
+
+impl fmt::Display for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapKey::Int(n) => write!(f, "{}", n),
+            MapKey::Bool(b) => write!(f, "{}", b),
+            MapKey::Char(c) => write!(f, "{}", c),
+            MapKey::String(s) => f.write_str(s),
+        }
+    }
+}
+
 /// Window state for game module
 #[derive(Debug, Clone, PartialEq)]
 pub struct WindowState {
@@ -195,7 +322,10 @@ impl Value {
             Value::Vec(_) => "Vec",
             Value::List(_) => "List",
             Value::Array(_) => "Array",
+            Value::Tuple(_) => "Tuple",
             Value::Object(_) => "Object",
+            Value::Map(_) => "Map",
+            Value::Set(_) => "Set",
             Value::Function { .. } => "Function",
             Value::None => "None",
             Value::Some(_) => "Some",
@@ -205,6 +335,8 @@ impl Value {
             Value::Window(_) => "Window",
             Value::Ref(_) => "Ref",
             Value::Closure { .. } => "Closure",
+            Value::Enum { .. } => "Enum",
+            Value::Struct { .. } => "Struct",
         }
     }
 
@@ -225,6 +357,8 @@ impl Value {
             Value::List(l) => !l.is_empty(),
             Value::Vec(l) => !l.is_empty(),
             Value::Array(l) => !l.is_empty(),
+            Value::Map(m) => !m.is_empty(),
+            Value::Set(s) => !s.is_empty(),
             Value::None => false,
             _ => true,
         }
@@ -288,10 +422,177 @@ impl Value {
     pub fn err(value: Value) -> Value {
         Value::Err(Box::new(value))
     }
+
+    /// Whether the value nests more than `MAX_VALUE_DEPTH` levels deep.
+    /// Dropping and cloning a value recurse into it, so values that hold
+    /// others inline (Some/Ok/Err, enum payloads, tuples, arrays) are checked
+    /// when built; what lives on the heap is behind a `Ref` and not counted.
+    pub fn exceeds_max_depth(&self) -> bool {
+        let mut pending = vec![(self, 0)];
+        while let Some((value, depth)) = pending.pop() {
+            if depth > MAX_VALUE_DEPTH {
+                return true;
+            }
+            let depth = depth + 1;
+            match value {
+                Value::Vec(items)
+                | Value::List(items)
+                | Value::Array(items)
+                | Value::Tuple(items)
+                | Value::Struct { fields: items, .. } => {
+                    pending.extend(items.iter().map(|item| (item, depth)))
+                }
+                Value::Object(fields) => pending.extend(fields.values().map(|v| (v, depth))),
+                Value::Map(entries) => pending.extend(entries.values().map(|v| (v, depth))),
+                Value::Closure { env, .. } => {
+                    pending.extend(env.iter().map(|(_, value)| (value, depth)))
+                }
+                Value::Some(inner) | Value::Ok(inner) | Value::Err(inner) => {
+                    pending.push((inner, depth))
+                }
+                Value::Enum {
+                    payload: Some(payload),
+                    ..
+                } => pending.push((payload, depth)),
+                _ => {}
+            }
+        }
+        false
+    }
+}
+
+/// Deepest nesting the VM prints, compares or converts. Deeper parts of a value
+/// are cut off or reported as errors instead of overflowing the host stack.
+pub const MAX_VALUE_DEPTH: usize = 1000;
+
+/// Deepest nesting accepted in a constant read back from a .zyc file
+const MAX_CONSTANT_DEPTH: usize = 128;
+
+/// Pending output while printing a value
+enum Piece<'a> {
+    Value(&'a Value, usize),
+    Key(&'a str),
+    MapKey(&'a MapKey),
+    Text(&'static str),
 }
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Nested values are printed from a worklist (pushed in reverse order)
+        // rather than by recursing, so deep values cannot overflow the stack
+        let mut pending = vec![Piece::Value(self, 0)];
+        while let Some(piece) = pending.pop() {
+            let (value, depth) = match piece {
+                Piece::Value(value, depth) => (value, depth),
+                Piece::Key(key) => {
+                    write!(f, "{}: ", key)?;
+                    continue;
+                }
+                Piece::MapKey(key) => {
+                    write!(f, "{}: ", key)?;
+                    continue;
+                }
+                Piece::Text(text) => {
+                    f.write_str(text)?;
+                    continue;
+                }
+            };
+            if depth > MAX_VALUE_DEPTH {
+                f.write_str("...")?;
+                continue;
+            }
+            let depth = depth + 1;
+            match value {
+                Value::Vec(items) | Value::List(items) | Value::Array(items) => {
+                    write!(f, "[")?;
+                    pending.push(Piece::Text("]"));
+                    for (i, item) in items.iter().enumerate().rev() {
+                        pending.push(Piece::Value(item, depth));
+                        if i > 0 {
+                            pending.push(Piece::Text(", "));
+                        }
+                    }
+                }
+                // (a, b), with a trailing comma for one element: (a,)
+                Value::Tuple(items) => {
+                    write!(f, "(")?;
+                    pending.push(Piece::Text(if items.len() == 1 { ",)" } else { ")" }));
+                    for (i, item) in items.iter().enumerate().rev() {
+                        pending.push(Piece::Value(item, depth));
+                        if i > 0 {
+                            pending.push(Piece::Text(", "));
+                        }
+                    }
+                }
+                Value::Object(fields) => {
+                    write!(f, "{{")?;
+                    pending.push(Piece::Text("}"));
+                    let fields: Vec<_> = fields.iter().collect();
+                    for (i, (k, v)) in fields.into_iter().enumerate().rev() {
+                        pending.push(Piece::Value(v, depth));
+                        pending.push(Piece::Key(k));
+                        if i > 0 {
+                            pending.push(Piece::Text(", "));
+                        }
+                    }
+                }
+                Value::Map(entries) => {
+                    write!(f, "{{")?;
+                    pending.push(Piece::Text("}"));
+                    let entries: Vec<_> = entries.iter().collect();
+                    for (i, (k, v)) in entries.into_iter().enumerate().rev() {
+                        pending.push(Piece::Value(v, depth));
+                        pending.push(Piece::MapKey(k));
+                        if i > 0 {
+                            pending.push(Piece::Text(", "));
+                        }
+                    }
+                }
+                Value::Set(elements) => {
+                    let elements: Vec<String> = elements.iter().map(MapKey::to_string).collect();
+                    write!(f, "{{{}}}", elements.join(", "))?;
+                }
+                Value::Some(inner) | Value::Ok(inner) | Value::Err(inner) => {
+                    let name = match value {
+                        Value::Some(_) => "Some",
+                        Value::Ok(_) => "Ok",
+                        _ => "Err",
+                    };
+                    write!(f, "{}(", name)?;
+                    pending.push(Piece::Text(")"));
+                    pending.push(Piece::Value(inner, depth));
+                }
+                // Names live in Bytecode::enums; the VM expands enums before printing
+                Value::Enum {
+                    type_id,
+                    variant_id,
+                    payload,
+                } => match payload {
+                    Some(inner) => {
+                        write!(f, "<Enum#{}.{}(", type_id, variant_id)?;
+                        pending.push(Piece::Text(")>"));
+                        pending.push(Piece::Value(inner, depth));
+                    }
+                    None => write!(f, "<Enum#{}.{}>", type_id, variant_id)?,
+                },
+                Value::Struct { type_id, fields } => {
+                    write!(f, "<Struct#{} {{", type_id)?;
+                    pending.push(Piece::Text(" }>"));
+                    for (i, field) in fields.iter().enumerate().rev() {
+                        pending.push(Piece::Value(field, depth));
+                        pending.push(Piece::Text(if i > 0 { ", " } else { " " }));
+                    }
+                }
+                other => other.fmt_scalar(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Value {
+    /// Print a value that holds no nested values
+    fn fmt_scalar(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Value::I8(n) => write!(f, "{}", n),
             Value::I32(n) => write!(f, "{}", n),
@@ -306,31 +607,8 @@ impl fmt::Display for Value {
             Value::Bool(b) => write!(f, "{}", b),
             Value::Char(c) => write!(f, "{}", c),
             Value::String(s) => write!(f, "{}", s),
-            Value::Vec(items) | Value::List(items) | Value::Array(items) => {
-                write!(f, "[")?;
-                for (i, item) in items.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, ", ")?;
-                    }
-                    write!(f, "{}", item)?;
-                }
-                write!(f, "]")
-            }
-            Value::Object(fields) => {
-                write!(f, "{{")?;
-                for (i, (k, v)) in fields.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, ", ")?;
-                    }
-                    write!(f, "{}: {}", k, v)?;
-                }
-                write!(f, "}}")
-            }
             Value::Function { name, .. } => write!(f, "<function {}>", name),
             Value::None => write!(f, "None"),
-            Value::Some(inner) => write!(f, "Some({})", inner),
-            Value::Ok(inner) => write!(f, "Ok({})", inner),
-            Value::Err(inner) => write!(f, "Err({})", inner),
             Value::Reference { name, mutable } => {
                 write!(f, "&{}{}", if *mutable { "mut " } else { "" }, name)
             }
@@ -345,18 +623,80 @@ impl fmt::Display for Value {
             Value::Closure {
                 func_name,
                 param_count,
+                ..
             } => {
                 write!(f, "<Closure {} ({} params)>", func_name, param_count)
             }
+            // Compound values are printed by Display
+            _ => write!(f, "{}", self),
         }
     }
 }
 
+/// Hash `SwitchStr` cases are keyed by; stable, so it can be serialized
+pub fn str_hash(s: &str) -> u64 {
+    crate::cache::content_hash(s.as_bytes())
+}
+
+/// Magic bytes at the start of every .zyc file
+pub const BYTECODE_MAGIC: &[u8; 4] = b"ZYRA";
+
+/// .zyc format version; bump whenever the serialized layout changes
+pub const BYTECODE_FORMAT_VERSION: u16 = 11;
+
+/// Version of the compiler writing .zyc files (recorded in the header)
+pub const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Deduplicated constants collected while serializing
+#[derive(Default)]
+struct ConstantPool {
+    bytes: Vec<u8>,
+    count: u32,
+    index: std::collections::HashMap<Vec<u8>, u32>,
+}
+
+impl ConstantPool {
+    /// Add a constant (if new) and return its pool index
+    fn insert(&mut self, value: &Value) -> u32 {
+        let mut encoded = Vec::new();
+        Bytecode::serialize_value(&mut encoded, value);
+        if let Some(&index) = self.index.get(&encoded) {
+            return index;
+        }
+        let index = self.count;
+        self.bytes.extend_from_slice(&encoded);
+        self.index.insert(encoded, index);
+        self.count += 1;
+        index
+    }
+}
+
+/// FNV-1a hash used as the .zyc body checksum
+fn checksum(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |hash: u32, &byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
 /// Compiled bytecode program
 #[derive(Debug, Clone)]
 pub struct Bytecode {
     pub instructions: Vec<Instruction>,
     pub functions: std::collections::HashMap<String, FunctionDef>,
+    /// Struct field layouts by type name, for runtime schema checks (serde::validate)
+    pub structs: std::collections::HashMap<String, Vec<(String, ZyraType)>>,
+    /// Struct names sorted by name; `Value::Struct` type ids index into this list
+    pub struct_names: Vec<String>,
+    /// Source line of each instruction (0 when unknown), for runtime error locations
+    pub lines: Vec<usize>,
+    /// Path of the source file the bytecode was compiled from
+    pub source_file: String,
+    /// Enum names with their variant names; `Value::Enum` tags index into this table
+    pub enums: Vec<(String, Vec<String>)>,
+    /// `static mut` globals with their initial values, in declaration order
+    pub globals: Vec<(String, Value)>,
+    /// Line recorded for instructions emitted from now on
+    current_line: usize,
 }
 
 /// Function definition in bytecode
@@ -373,15 +713,80 @@ impl Bytecode {
         Self {
             instructions: Vec::new(),
             functions: std::collections::HashMap::new(),
+            structs: std::collections::HashMap::new(),
+            struct_names: Vec::new(),
+            lines: Vec::new(),
+            source_file: String::new(),
+            enums: Vec::new(),
+            globals: Vec::new(),
+            current_line: 0,
         }
     }
 
     pub fn emit(&mut self, instruction: Instruction) -> usize {
         let addr = self.instructions.len();
         self.instructions.push(instruction);
+        self.lines.push(self.current_line);
         addr
     }
 
+    /// Set the source line for following instructions and return the previous one
+    pub fn set_line(&mut self, line: usize) -> usize {
+        std::mem::replace(&mut self.current_line, line)
+    }
+
+    /// Record a struct layout. Type ids follow name order, so they match the
+    /// order layouts are serialized in; register every struct before using ids.
+    pub fn register_struct(&mut self, name: String, fields: Vec<(String, ZyraType)>) {
+        if self.structs.insert(name.clone(), fields).is_none() {
+            let at = self.struct_names.partition_point(|n| *n < name);
+            self.struct_names.insert(at, name);
+        }
+    }
+
+    /// Type id of a registered struct
+    pub fn struct_id(&self, name: &str) -> Option<u32> {
+        self.struct_names
+            .binary_search_by(|n| n.as_str().cmp(name))
+            .ok()
+            .map(|id| id as u32)
+    }
+
+    /// Name and field layout of the struct with the given type id
+    pub fn struct_layout(&self, type_id: u32) -> Option<(&str, &[(String, ZyraType)])> {
+        let name = self.struct_names.get(type_id as usize)?;
+        let fields = self.structs.get(name)?;
+        Some((name, fields))
+    }
+
+    /// Layout index of `field` in the struct with the given type id
+    pub fn field_index(&self, type_id: u32, field: &str) -> Option<u32> {
+        let (_, fields) = self.struct_layout(type_id)?;
+        fields
+            .iter()
+            .position(|(f, _)| f == field)
+            .map(|i| i as u32)
+    }
+
+    /// Integer tags for `Enum::Variant`, if the enum is registered
+    pub fn enum_tag(&self, enum_name: &str, variant: &str) -> Option<(u32, u32)> {
+        let type_id = self.enums.iter().position(|(name, _)| name == enum_name)?;
+        let variant_id = self.enums[type_id].1.iter().position(|v| v == variant)?;
+        Some((type_id as u32, variant_id as u32))
+    }
+
+    /// `Enum::Variant` name for a pair of enum tags
+    pub fn variant_name(&self, type_id: u32, variant_id: u32) -> Option<String> {
+        let (name, variants) = self.enums.get(type_id as usize)?;
+        let variant = variants.get(variant_id as usize)?;
+        Some(format!("{}::{}", name, variant))
+    }
+
+    /// Source line of the instruction at `addr`, if known
+    pub fn line_at(&self, addr: usize) -> Option<usize> {
+        self.lines.get(addr).copied().filter(|&line| line > 0)
+    }
+
     pub fn current_address(&self) -> usize {
         self.instructions.len()
     }
@@ -395,48 +800,140 @@ impl Bytecode {
     }
 
     /// Serialize bytecode to bytes for .zyc file format
+    ///
+    /// Layout (all integers little-endian):
+    /// - magic `ZYRA`, format version (u16), compiler version (u8 length + UTF-8)
+    /// - checksum (u32, FNV-1a of the body), body length (u32)
+    /// - constant pool size, function count, instruction count (u32 each)
+    /// - body: constant pool, functions, instructions, struct layouts,
+    ///   source file name and line table (runs of `start address, line`),
+    ///   enum table (names and variant names, in tag order)
+    ///
+    /// The magic, format version, and compiler version stay at the same offsets in
+    /// every format version so incompatible files can always be identified.
     pub fn serialize(&self) -> Vec<u8> {
-        let mut output = Vec::new();
+        // Constants are stored once in the pool; LoadConst refers to them by index
+        let mut pool = ConstantPool::default();
+        let mut code = Vec::new();
+        for instr in &self.instructions {
+            Self::serialize_instruction(&mut code, instr, &mut pool);
+        }
 
-        // Magic number "ZYRA"
-        output.extend_from_slice(b"ZYRA");
+        let mut body = Vec::new();
+        body.extend_from_slice(&pool.bytes);
 
-        // Version (2 bytes)
-        output.push(1);
-        output.push(0);
+        // Functions sorted by name for stable output
+        let mut functions: Vec<_> = self.functions.iter().collect();
+        functions.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, func_def) in &functions {
+            Self::serialize_string(&mut body, name);
+            body.extend_from_slice(&(func_def.params.len() as u32).to_le_bytes());
+            for param in &func_def.params {
+                Self::serialize_string(&mut body, param);
+            }
+            body.extend_from_slice(&(func_def.start_address as u32).to_le_bytes());
+            body.extend_from_slice(&(func_def.end_address as u32).to_le_bytes());
+        }
 
-        // Instruction count (4 bytes, little-endian)
-        let count = self.instructions.len() as u32;
-        output.extend_from_slice(&count.to_le_bytes());
+        body.extend_from_slice(&code);
 
-        // Function count (4 bytes, little-endian)
-        let func_count = self.functions.len() as u32;
-        output.extend_from_slice(&func_count.to_le_bytes());
+        // Struct layouts, sorted for stable output
+        let mut structs: Vec<_> = self.structs.iter().collect();
+        structs.sort_by(|a, b| a.0.cmp(b.0));
+        body.extend_from_slice(&(structs.len() as u32).to_le_bytes());
+        for (name, fields) in structs {
+            Self::serialize_string(&mut body, name);
+            body.extend_from_slice(&(fields.len() as u32).to_le_bytes());
+            for (field, ty) in fields {
+                Self::serialize_string(&mut body, field);
+                Self::serialize_type(&mut body, ty);
+            }
+        }
 
-        // Serialize each function definition
-        for (name, func_def) in &self.functions {
-            Self::serialize_string(&mut output, name);
-            output.extend_from_slice(&(func_def.params.len() as u32).to_le_bytes());
-            for param in &func_def.params {
-                Self::serialize_string(&mut output, param);
+        // Line table: consecutive instructions on the same line share one run
+        Self::serialize_string(&mut body, &self.source_file);
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for (addr, &line) in self.lines.iter().enumerate() {
+            if runs.last().map(|&(_, l)| l) != Some(line) {
+                runs.push((addr, line));
             }
-            output.extend_from_slice(&(func_def.start_address as u32).to_le_bytes());
-            output.extend_from_slice(&(func_def.end_address as u32).to_le_bytes());
+        }
+        body.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (addr, line) in runs {
+            body.extend_from_slice(&(addr as u32).to_le_bytes());
+            body.extend_from_slice(&(line as u32).to_le_bytes());
         }
 
-        // Serialize each instruction
-        for instr in &self.instructions {
-            Self::serialize_instruction(&mut output, instr);
+        body.extend_from_slice(&(self.enums.len() as u32).to_le_bytes());
+        for (name, variants) in &self.enums {
+            Self::serialize_string(&mut body, name);
+            body.extend_from_slice(&(variants.len() as u32).to_le_bytes());
+            for variant in variants {
+                Self::serialize_string(&mut body, variant);
+            }
+        }
+
+        body.extend_from_slice(&(self.globals.len() as u32).to_le_bytes());
+        for (name, value) in &self.globals {
+            Self::serialize_string(&mut body, name);
+            Self::serialize_value(&mut body, value);
         }
 
+        let mut output = Vec::with_capacity(body.len() + 40);
+        output.extend_from_slice(BYTECODE_MAGIC);
+        output.extend_from_slice(&BYTECODE_FORMAT_VERSION.to_le_bytes());
+        output.push(COMPILER_VERSION.len() as u8);
+        output.extend_from_slice(COMPILER_VERSION.as_bytes());
+        output.extend_from_slice(&checksum(&body).to_le_bytes());
+        output.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        output.extend_from_slice(&pool.count.to_le_bytes());
+        output.extend_from_slice(&(functions.len() as u32).to_le_bytes());
+        output.extend_from_slice(&(self.instructions.len() as u32).to_le_bytes());
+        output.extend_from_slice(&body);
         output
     }
 
-    fn serialize_instruction(output: &mut Vec<u8>, instr: &Instruction) {
+    /// Serialize a struct field type; types without a runtime check become Unknown
+    fn serialize_type(output: &mut Vec<u8>, ty: &ZyraType) {
+        match ty {
+            ZyraType::I8 => output.push(0x01),
+            ZyraType::I32 => output.push(0x02),
+            ZyraType::I64 => output.push(0x03),
+            ZyraType::U8 => output.push(0x04),
+            ZyraType::U32 => output.push(0x05),
+            ZyraType::U64 => output.push(0x06),
+            ZyraType::F32 => output.push(0x07),
+            ZyraType::F64 => output.push(0x08),
+            ZyraType::Bool => output.push(0x09),
+            ZyraType::Char => output.push(0x0A),
+            ZyraType::String => output.push(0x0B),
+            ZyraType::Vec(inner) => {
+                output.push(0x0C);
+                Self::serialize_type(output, inner);
+            }
+            ZyraType::Array { elem, size } => {
+                output.push(0x0D);
+                Self::serialize_type(output, elem);
+                output.extend_from_slice(&(*size as u32).to_le_bytes());
+            }
+            ZyraType::Struct(name) => {
+                output.push(0x0E);
+                Self::serialize_string(output, name);
+            }
+            ZyraType::Enum(name) => {
+                output.push(0x0F);
+                Self::serialize_string(output, name);
+            }
+            ZyraType::Reference { inner, .. } => Self::serialize_type(output, inner),
+            _ => output.push(0x00),
+        }
+    }
+
+    fn serialize_instruction(output: &mut Vec<u8>, instr: &Instruction, pool: &mut ConstantPool) {
         match instr {
             Instruction::LoadConst(value) => {
                 output.push(0x01);
-                Self::serialize_value(output, value);
+                output.extend_from_slice(&pool.insert(value).to_le_bytes());
             }
             Instruction::LoadVar(name) => {
                 output.push(0x02);
@@ -446,6 +943,14 @@ impl Bytecode {
                 output.push(0x03);
                 Self::serialize_string(output, name);
             }
+            Instruction::LoadGlobal(name) => {
+                output.push(0x7e);
+                Self::serialize_string(output, name);
+            }
+            Instruction::StoreGlobal(name) => {
+                output.push(0x7f);
+                Self::serialize_string(output, name);
+            }
             Instruction::Pop => output.push(0x04),
             Instruction::Add => output.push(0x10),
             Instruction::Sub => output.push(0x11),
@@ -453,6 +958,21 @@ impl Bytecode {
             Instruction::Div => output.push(0x13),
             Instruction::Mod => output.push(0x14),
             Instruction::Neg => output.push(0x15),
+            Instruction::CheckedAdd => output.push(0x16),
+            Instruction::CheckedSub => output.push(0x17),
+            Instruction::CheckedMul => output.push(0x18),
+            Instruction::SaturatingAdd => output.push(0x19),
+            Instruction::SaturatingSub => output.push(0x1A),
+            Instruction::SaturatingMul => output.push(0x1B),
+            Instruction::Narrow(type_name, mode) => {
+                output.push(0x1C);
+                output.push(match mode {
+                    OverflowMode::Error => 0,
+                    OverflowMode::Wrap => 1,
+                    OverflowMode::Saturate => 2,
+                });
+                Self::serialize_string(output, type_name);
+            }
             Instruction::Eq => output.push(0x20),
             Instruction::Neq => output.push(0x21),
             Instruction::Lt => output.push(0x22),
@@ -462,6 +982,11 @@ impl Bytecode {
             Instruction::And => output.push(0x30),
             Instruction::Or => output.push(0x31),
             Instruction::Not => output.push(0x32),
+            Instruction::BitAnd => output.push(0x33),
+            Instruction::BitOr => output.push(0x34),
+            Instruction::BitXor => output.push(0x35),
+            Instruction::Shl => output.push(0x36),
+            Instruction::Shr => output.push(0x37),
             Instruction::Jump(addr) => {
                 output.push(0x40);
                 output.extend_from_slice(&(*addr as u32).to_le_bytes());
@@ -470,6 +995,16 @@ impl Bytecode {
                 output.push(0x41);
                 output.extend_from_slice(&(*addr as u32).to_le_bytes());
             }
+            Instruction::SwitchStr { cases, default } => {
+                output.push(0x42);
+                output.extend_from_slice(&(cases.len() as u32).to_le_bytes());
+                for (hash, case, addr) in cases {
+                    output.extend_from_slice(&hash.to_le_bytes());
+                    Self::serialize_string(output, case);
+                    output.extend_from_slice(&(*addr as u32).to_le_bytes());
+                }
+                output.extend_from_slice(&(*default as u32).to_le_bytes());
+            }
             Instruction::Call(name, argc) => {
                 output.push(0x50);
                 Self::serialize_string(output, name);
@@ -480,6 +1015,10 @@ impl Bytecode {
                 Self::serialize_string(output, method_name);
                 output.extend_from_slice(&(*argc as u32).to_le_bytes());
             }
+            Instruction::CallValue(argc) => {
+                output.push(0x7c);
+                output.extend_from_slice(&(*argc as u32).to_le_bytes());
+            }
             Instruction::Return => output.push(0x51),
             Instruction::Alloc => output.push(0x60),
             Instruction::Move(from, to) => {
@@ -503,7 +1042,7 @@ impl Bytecode {
                 output.push(0x65);
                 Self::serialize_string(output, name);
             }
-            Instruction::MakeList(count) => {
+            Instruction::MakeArray(count) => {
                 output.push(0x70);
                 output.extend_from_slice(&(*count as u32).to_le_bytes());
             }
@@ -511,6 +1050,18 @@ impl Bytecode {
                 output.push(0x76);
                 output.extend_from_slice(&(*count as u32).to_le_bytes());
             }
+            Instruction::MakeMap(count) => {
+                output.push(0x7a);
+                output.extend_from_slice(&(*count as u32).to_le_bytes());
+            }
+            Instruction::MakeSet(count) => {
+                output.push(0x7b);
+                output.extend_from_slice(&(*count as u32).to_le_bytes());
+            }
+            Instruction::MakeTuple(count) => {
+                output.push(0x7d);
+                output.extend_from_slice(&(*count as u32).to_le_bytes());
+            }
             Instruction::MakeObject(count) => {
                 output.push(0x71);
                 output.extend_from_slice(&(*count as u32).to_le_bytes());
@@ -523,6 +1074,24 @@ impl Bytecode {
                 output.push(0x73);
                 Self::serialize_string(output, name);
             }
+            Instruction::MakeStruct { type_id, slots } => {
+                output.push(0x77);
+                output.extend_from_slice(&type_id.to_le_bytes());
+                output.extend_from_slice(&(slots.len() as u32).to_le_bytes());
+                for slot in slots {
+                    output.extend_from_slice(&slot.to_le_bytes());
+                }
+            }
+            Instruction::GetFieldIdx(type_id, idx) => {
+                output.push(0x78);
+                output.extend_from_slice(&type_id.to_le_bytes());
+                output.extend_from_slice(&idx.to_le_bytes());
+            }
+            Instruction::SetFieldIdx(type_id, idx) => {
+                output.push(0x79);
+                output.extend_from_slice(&type_id.to_le_bytes());
+                output.extend_from_slice(&idx.to_le_bytes());
+            }
             Instruction::GetIndex => output.push(0x74),
             Instruction::SetIndex => output.push(0x75),
             Instruction::EnterScope => output.push(0x80),
@@ -531,6 +1100,16 @@ impl Bytecode {
             Instruction::Nop => output.push(0xFE),
             Instruction::Dup => output.push(0xA0),
             Instruction::StrContains => output.push(0xA1),
+            Instruction::MakeEnum(type_id, variant_id) => {
+                output.push(0xA4);
+                output.extend_from_slice(&type_id.to_le_bytes());
+                output.extend_from_slice(&variant_id.to_le_bytes());
+            }
+            Instruction::IsVariant(type_id, variant_id) => {
+                output.push(0xA5);
+                output.extend_from_slice(&type_id.to_le_bytes());
+                output.extend_from_slice(&variant_id.to_le_bytes());
+            }
             Instruction::Halt => output.push(0xFF),
             Instruction::Cast(type_name) => {
                 output.push(0xA2);
@@ -539,10 +1118,15 @@ impl Bytecode {
             Instruction::MakeClosure {
                 func_name,
                 param_count,
+                captures,
             } => {
                 output.push(0xA3);
                 Self::serialize_string(output, func_name);
                 output.extend_from_slice(&(*param_count as u32).to_le_bytes());
+                output.extend_from_slice(&(captures.len() as u32).to_le_bytes());
+                for name in captures {
+                    Self::serialize_string(output, name);
+                }
             }
         }
     }
@@ -598,6 +1182,22 @@ impl Bytecode {
                 }
                 output.extend_from_slice(&(*address as u32).to_le_bytes());
             }
+            Value::Enum {
+                type_id,
+                variant_id,
+                payload,
+            } => {
+                output.push(0x09);
+                output.extend_from_slice(&type_id.to_le_bytes());
+                output.extend_from_slice(&variant_id.to_le_bytes());
+                match payload {
+                    Some(inner) => {
+                        output.push(1);
+                        Self::serialize_value(output, inner);
+                    }
+                    None => output.push(0),
+                }
+            }
             // Complex types - serialize as None for now
             _ => output.push(0x00),
         }
@@ -610,88 +1210,229 @@ impl Bytecode {
     }
 
     /// Deserialize bytecode from bytes
+    /// Rejects files with a bad magic number, a different format version, a checksum
+    /// mismatch, or leftover bytes.
     pub fn deserialize(data: &[u8]) -> Result<Self, String> {
-        if data.len() < 14 {
-            return Err("Invalid bytecode: too short".to_string());
+        if data.len() < 6 || &data[0..4] != BYTECODE_MAGIC {
+            return Err("Invalid bytecode: not a Zyra .zyc file (bad magic number)".to_string());
         }
 
-        // Check magic
-        if &data[0..4] != b"ZYRA" {
-            return Err("Invalid bytecode: bad magic number".to_string());
+        let version = u16::from_le_bytes([data[4], data[5]]);
+        let built_by = if version >= 2 {
+            data.get(6)
+                .and_then(|&len| data.get(7..7 + len as usize))
+                .and_then(|v| std::str::from_utf8(v).ok())
+                .map(|v| format!("Zyra {}", v))
+        } else {
+            None
         }
-
-        // Check version (little-endian to match serialization)
-        let version = data[4] as u16 | (data[5] as u16) << 8;
-        if version != 1 {
-            return Err(format!("Unsupported bytecode version: {}", version));
+        .unwrap_or_else(|| "an older Zyra compiler".to_string());
+        if version != BYTECODE_FORMAT_VERSION {
+            return Err(format!(
+                "Incompatible bytecode: built by {} (format {}), but Zyra {} runs format {}. \
+                 Recompile the source with `zyra compile`",
+                built_by, version, COMPILER_VERSION, BYTECODE_FORMAT_VERSION
+            ));
         }
 
-        // Read instruction count
-        let instr_count = u32::from_le_bytes([data[6], data[7], data[8], data[9]]) as usize;
-
-        // Read function count
-        let func_count = u32::from_le_bytes([data[10], data[11], data[12], data[13]]) as usize;
+        let version_len = *data
+            .get(6)
+            .ok_or("Invalid bytecode: file truncated after the format version")?;
+        let mut pos = 7 + version_len as usize;
+        let (expected_checksum, new_pos) = Self::deserialize_u32(data, pos)?;
+        let (body_len, new_pos) = Self::deserialize_u32(data, new_pos)?;
+        let (const_count, new_pos) = Self::deserialize_u32(data, new_pos)?;
+        let (func_count, new_pos) = Self::deserialize_u32(data, new_pos)?;
+        let (instr_count, new_pos) = Self::deserialize_u32(data, new_pos)?;
+        pos = new_pos;
+
+        if data.len() - pos != body_len as usize {
+            return Err(format!(
+                "Invalid bytecode: expected {} bytes of code, found {} (file truncated or corrupted)",
+                body_len,
+                data.len() - pos
+            ));
+        }
+        if checksum(&data[pos..]) != expected_checksum {
+            return Err("Invalid bytecode: checksum mismatch (file corrupted)".to_string());
+        }
 
         let mut bytecode = Bytecode::new();
-        let mut pos = 14;
+
+        // Read constant pool
+        let mut constants = Vec::new();
+        for _ in 0..const_count {
+            let (value, new_pos) = Self::deserialize_value(data, pos)?;
+            constants.push(value);
+            pos = new_pos;
+        }
 
         // Read function definitions
         for _ in 0..func_count {
             let (name, new_pos) = Self::deserialize_string(data, pos)?;
+            let (param_count, new_pos) = Self::deserialize_u32(data, new_pos)?;
             pos = new_pos;
 
-            if pos + 4 > data.len() {
-                return Err("Unexpected end".to_string());
-            }
-            let param_count =
-                u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
-                    as usize;
-            pos += 4;
-
-            let mut params = Vec::with_capacity(param_count);
+            let mut params = Vec::new();
             for _ in 0..param_count {
                 let (param, new_pos) = Self::deserialize_string(data, pos)?;
                 params.push(param);
                 pos = new_pos;
             }
 
-            if pos + 8 > data.len() {
-                return Err("Unexpected end".to_string());
-            }
-            let start_address =
-                u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
-                    as usize;
-            pos += 4;
-            let end_address =
-                u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
-                    as usize;
-            pos += 4;
+            let (start_address, new_pos) = Self::deserialize_u32(data, pos)?;
+            let (end_address, new_pos) = Self::deserialize_u32(data, new_pos)?;
+            pos = new_pos;
 
             bytecode.functions.insert(
                 name.clone(),
                 FunctionDef {
                     name,
                     params,
-                    start_address,
-                    end_address,
+                    start_address: start_address as usize,
+                    end_address: end_address as usize,
                 },
             );
         }
 
         // Read instructions
         for _ in 0..instr_count {
-            if pos >= data.len() {
-                return Err("Unexpected end of bytecode".to_string());
-            }
-            let (instr, new_pos) = Self::deserialize_instruction(data, pos)?;
+            let (instr, new_pos) = Self::deserialize_instruction(data, pos, &constants)?;
             bytecode.emit(instr);
             pos = new_pos;
         }
 
+        // Read struct layouts
+        let (struct_count, new_pos) = Self::deserialize_u32(data, pos)?;
+        pos = new_pos;
+        for _ in 0..struct_count {
+            let (name, new_pos) = Self::deserialize_string(data, pos)?;
+            let (field_count, new_pos) = Self::deserialize_u32(data, new_pos)?;
+            pos = new_pos;
+            let mut fields = Vec::new();
+            for _ in 0..field_count {
+                let (field, new_pos) = Self::deserialize_string(data, pos)?;
+                let (ty, new_pos) = Self::deserialize_type(data, new_pos)?;
+                fields.push((field, ty));
+                pos = new_pos;
+            }
+            bytecode.register_struct(name, fields);
+        }
+
+        // Read line table
+        let (source_file, new_pos) = Self::deserialize_string(data, pos)?;
+        let (run_count, new_pos) = Self::deserialize_u32(data, new_pos)?;
+        pos = new_pos;
+        bytecode.source_file = source_file;
+        let mut runs = Vec::new();
+        for _ in 0..run_count {
+            let (addr, new_pos) = Self::deserialize_u32(data, pos)?;
+            let (line, new_pos) = Self::deserialize_u32(data, new_pos)?;
+            runs.push((addr as usize, line as usize));
+            pos = new_pos;
+        }
+        for (i, &(start, line)) in runs.iter().enumerate() {
+            let end = runs
+                .get(i + 1)
+                .map_or(bytecode.lines.len(), |&(next, _)| next);
+            if start > end || end > bytecode.lines.len() {
+                return Err("Invalid bytecode: line table out of range".to_string());
+            }
+            bytecode.lines[start..end].fill(line);
+        }
+
+        // Read enum table
+        let (enum_count, new_pos) = Self::deserialize_u32(data, pos)?;
+        pos = new_pos;
+        for _ in 0..enum_count {
+            let (name, new_pos) = Self::deserialize_string(data, pos)?;
+            let (variant_count, new_pos) = Self::deserialize_u32(data, new_pos)?;
+            pos = new_pos;
+            let mut variants = Vec::new();
+            for _ in 0..variant_count {
+                let (variant, new_pos) = Self::deserialize_string(data, pos)?;
+                variants.push(variant);
+                pos = new_pos;
+            }
+            bytecode.enums.push((name, variants));
+        }
+
+        // Read globals table
+        let (global_count, new_pos) = Self::deserialize_u32(data, pos)?;
+        pos = new_pos;
+        for _ in 0..global_count {
+            let (name, new_pos) = Self::deserialize_string(data, pos)?;
+            let (value, new_pos) = Self::deserialize_value(data, new_pos)?;
+            bytecode.globals.push((name, value));
+            pos = new_pos;
+        }
+
+        if pos != data.len() {
+            return Err(format!(
+                "Invalid bytecode: {} unexpected trailing bytes",
+                data.len() - pos
+            ));
+        }
+
         Ok(bytecode)
     }
 
-    fn deserialize_instruction(data: &[u8], pos: usize) -> Result<(Instruction, usize), String> {
+    fn deserialize_type(data: &[u8], pos: usize) -> Result<(ZyraType, usize), String> {
+        let tag = *data.get(pos).ok_or("Unexpected end of bytecode")?;
+        let pos = pos + 1;
+        let ty = match tag {
+            0x00 => ZyraType::Unknown,
+            0x01 => ZyraType::I8,
+            0x02 => ZyraType::I32,
+            0x03 => ZyraType::I64,
+            0x04 => ZyraType::U8,
+            0x05 => ZyraType::U32,
+            0x06 => ZyraType::U64,
+            0x07 => ZyraType::F32,
+            0x08 => ZyraType::F64,
+            0x09 => ZyraType::Bool,
+            0x0A => ZyraType::Char,
+            0x0B => ZyraType::String,
+            0x0C => {
+                let (inner, pos) = Self::deserialize_type(data, pos)?;
+                return Ok((ZyraType::Vec(Box::new(inner)), pos));
+            }
+            0x0D => {
+                let (elem, pos) = Self::deserialize_type(data, pos)?;
+                let (size, pos) = Self::deserialize_u32(data, pos)?;
+                let ty = ZyraType::Array {
+                    elem: Box::new(elem),
+                    size: size as usize,
+                };
+                return Ok((ty, pos));
+            }
+            0x0E | 0x0F => {
+                let (name, pos) = Self::deserialize_string(data, pos)?;
+                let ty = if tag == 0x0E {
+                    ZyraType::Struct(name)
+                } else {
+                    ZyraType::Enum(name)
+                };
+                return Ok((ty, pos));
+            }
+            _ => return Err(format!("Unknown type tag: 0x{:02X}", tag)),
+        };
+        Ok((ty, pos))
+    }
+
+    fn deserialize_u32(data: &[u8], pos: usize) -> Result<(u32, usize), String> {
+        if pos + 4 > data.len() {
+            return Err("Unexpected end of bytecode".to_string());
+        }
+        let n = u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]);
+        Ok((n, pos + 4))
+    }
+
+    fn deserialize_instruction(
+        data: &[u8],
+        pos: usize,
+        constants: &[Value],
+    ) -> Result<(Instruction, usize), String> {
         if pos >= data.len() {
             return Err("Unexpected end of bytecode".to_string());
         }
@@ -701,9 +1442,12 @@ impl Bytecode {
 
         let instr = match opcode {
             0x01 => {
-                let (value, new_pos) = Self::deserialize_value(data, pos)?;
+                let (index, new_pos) = Self::deserialize_u32(data, pos)?;
                 pos = new_pos;
-                Instruction::LoadConst(value)
+                let value = constants
+                    .get(index as usize)
+                    .ok_or_else(|| format!("Invalid constant index: {}", index))?;
+                Instruction::LoadConst(value.clone())
             }
             0x02 => {
                 let (name, new_pos) = Self::deserialize_string(data, pos)?;
@@ -715,6 +1459,16 @@ impl Bytecode {
                 pos = new_pos;
                 Instruction::StoreVar(name)
             }
+            0x7e => {
+                let (name, new_pos) = Self::deserialize_string(data, pos)?;
+                pos = new_pos;
+                Instruction::LoadGlobal(name)
+            }
+            0x7f => {
+                let (name, new_pos) = Self::deserialize_string(data, pos)?;
+                pos = new_pos;
+                Instruction::StoreGlobal(name)
+            }
             0x04 => Instruction::Pop,
             0x10 => Instruction::Add,
             0x11 => Instruction::Sub,
@@ -722,6 +1476,23 @@ impl Bytecode {
             0x13 => Instruction::Div,
             0x14 => Instruction::Mod,
             0x15 => Instruction::Neg,
+            0x16 => Instruction::CheckedAdd,
+            0x17 => Instruction::CheckedSub,
+            0x18 => Instruction::CheckedMul,
+            0x19 => Instruction::SaturatingAdd,
+            0x1A => Instruction::SaturatingSub,
+            0x1B => Instruction::SaturatingMul,
+            0x1C => {
+                let mode = match data.get(pos) {
+                    Some(0) => OverflowMode::Error,
+                    Some(1) => OverflowMode::Wrap,
+                    Some(2) => OverflowMode::Saturate,
+                    _ => return Err("Invalid overflow mode in bytecode".to_string()),
+                };
+                let (type_name, new_pos) = Self::deserialize_string(data, pos + 1)?;
+                pos = new_pos;
+                Instruction::Narrow(type_name, mode)
+            }
             0x20 => Instruction::Eq,
             0x21 => Instruction::Neq,
             0x22 => Instruction::Lt,
@@ -731,6 +1502,11 @@ impl Bytecode {
             0x30 => Instruction::And,
             0x31 => Instruction::Or,
             0x32 => Instruction::Not,
+            0x33 => Instruction::BitAnd,
+            0x34 => Instruction::BitOr,
+            0x35 => Instruction::BitXor,
+            0x36 => Instruction::Shl,
+            0x37 => Instruction::Shr,
             0x40 => {
                 if pos + 4 > data.len() {
                     return Err("Unexpected end".to_string());
@@ -751,6 +1527,25 @@ impl Bytecode {
                 pos += 4;
                 Instruction::JumpIfFalse(addr)
             }
+            0x42 => {
+                let (count, new_pos) = Self::deserialize_u32(data, pos)?;
+                pos = new_pos;
+                let mut cases = Vec::new();
+                for _ in 0..count {
+                    let hash = data.get(pos..pos + 8).ok_or("Unexpected end of bytecode")?;
+                    let hash = u64::from_le_bytes(hash.try_into().unwrap());
+                    let (case, new_pos) = Self::deserialize_string(data, pos + 8)?;
+                    let (addr, new_pos) = Self::deserialize_u32(data, new_pos)?;
+                    cases.push((hash, case, addr as usize));
+                    pos = new_pos;
+                }
+                let (default, new_pos) = Self::deserialize_u32(data, pos)?;
+                pos = new_pos;
+                Instruction::SwitchStr {
+                    cases,
+                    default: default as usize,
+                }
+            }
             0x50 => {
                 let (name, new_pos) = Self::deserialize_string(data, pos)?;
                 pos = new_pos;
@@ -811,7 +1606,7 @@ impl Bytecode {
                     u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
                         as usize;
                 pos += 4;
-                Instruction::MakeList(count)
+                Instruction::MakeArray(count)
             }
             0x71 => {
                 if pos + 4 > data.len() {
@@ -845,6 +1640,58 @@ impl Bytecode {
                 pos += 4;
                 Instruction::MakeVec(count)
             }
+            0x7a => {
+                if pos + 4 > data.len() {
+                    return Err("Unexpected end".to_string());
+                }
+                let count =
+                    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+                        as usize;
+                pos += 4;
+                Instruction::MakeMap(count)
+            }
+            0x7c => {
+                let (argc, new_pos) = Self::deserialize_u32(data, pos)?;
+                pos = new_pos;
+                Instruction::CallValue(argc as usize)
+            }
+            0x7b => {
+                if pos + 4 > data.len() {
+                    return Err("Unexpected end".to_string());
+                }
+                let count =
+                    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+                        as usize;
+                pos += 4;
+                Instruction::MakeSet(count)
+            }
+            0x7d => {
+                let (count, new_pos) = Self::deserialize_u32(data, pos)?;
+                pos = new_pos;
+                Instruction::MakeTuple(count as usize)
+            }
+            0x77 => {
+                let (type_id, new_pos) = Self::deserialize_u32(data, pos)?;
+                let (count, new_pos) = Self::deserialize_u32(data, new_pos)?;
+                pos = new_pos;
+                let mut slots = Vec::new();
+                for _ in 0..count {
+                    let (slot, new_pos) = Self::deserialize_u32(data, pos)?;
+                    slots.push(slot);
+                    pos = new_pos;
+                }
+                Instruction::MakeStruct { type_id, slots }
+            }
+            0x78 | 0x79 => {
+                let (type_id, new_pos) = Self::deserialize_u32(data, pos)?;
+                let (idx, new_pos) = Self::deserialize_u32(data, new_pos)?;
+                pos = new_pos;
+                if opcode == 0x78 {
+                    Instruction::GetFieldIdx(type_id, idx)
+                } else {
+                    Instruction::SetFieldIdx(type_id, idx)
+                }
+            }
             0x80 => Instruction::EnterScope,
             0x81 => Instruction::ExitScope,
             0x90 => Instruction::Print,
@@ -860,13 +1707,29 @@ impl Bytecode {
             0xA3 => {
                 let (func_name, new_pos) = Self::deserialize_string(data, pos)?;
                 pos = new_pos;
-                let param_count =
-                    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
-                        as usize;
-                pos += 4;
+                let (param_count, new_pos) = Self::deserialize_u32(data, pos)?;
+                let (count, new_pos) = Self::deserialize_u32(data, new_pos)?;
+                pos = new_pos;
+                let mut captures = Vec::new();
+                for _ in 0..count {
+                    let (name, new_pos) = Self::deserialize_string(data, pos)?;
+                    captures.push(name);
+                    pos = new_pos;
+                }
                 Instruction::MakeClosure {
                     func_name,
-                    param_count,
+                    param_count: param_count as usize,
+                    captures,
+                }
+            }
+            0xA4 | 0xA5 => {
+                let (type_id, new_pos) = Self::deserialize_u32(data, pos)?;
+                let (variant_id, new_pos) = Self::deserialize_u32(data, new_pos)?;
+                pos = new_pos;
+                if opcode == 0xA4 {
+                    Instruction::MakeEnum(type_id, variant_id)
+                } else {
+                    Instruction::IsVariant(type_id, variant_id)
                 }
             }
             _ => return Err(format!("Unknown opcode: 0x{:02X}", opcode)),
@@ -876,6 +1739,20 @@ impl Bytecode {
     }
 
     fn deserialize_value(data: &[u8], pos: usize) -> Result<(Value, usize), String> {
+        Self::deserialize_nested_value(data, pos, 0)
+    }
+
+    /// Decode a value `depth` levels inside a constant. Constants come from
+    /// literals, so anything nested past `MAX_CONSTANT_DEPTH` is a corrupt file
+    /// and is rejected rather than overflowing the stack.
+    fn deserialize_nested_value(
+        data: &[u8],
+        pos: usize,
+        depth: usize,
+    ) -> Result<(Value, usize), String> {
+        if depth > MAX_CONSTANT_DEPTH {
+            return Err("Invalid bytecode: constant nested too deeply".to_string());
+        }
         if pos >= data.len() {
             return Err("Unexpected end of bytecode".to_string());
         }
@@ -969,12 +1846,30 @@ impl Bytecode {
                 pos += 4;
                 let mut items = Vec::with_capacity(count);
                 for _ in 0..count {
-                    let (item, new_pos) = Self::deserialize_value(data, pos)?;
+                    let (item, new_pos) = Self::deserialize_nested_value(data, pos, depth + 1)?;
                     items.push(item);
                     pos = new_pos;
                 }
                 Value::Vec(items)
             }
+            0x09 => {
+                let (type_id, new_pos) = Self::deserialize_u32(data, pos)?;
+                let (variant_id, new_pos) = Self::deserialize_u32(data, new_pos)?;
+                let has_payload = *data.get(new_pos).ok_or("Unexpected end")? != 0;
+                pos = new_pos + 1;
+                let payload = if has_payload {
+                    let (inner, new_pos) = Self::deserialize_nested_value(data, pos, depth + 1)?;
+                    pos = new_pos;
+                    Some(Box::new(inner))
+                } else {
+                    None
+                };
+                Value::Enum {
+                    type_id,
+                    variant_id,
+                    payload,
+                }
+            }
             0x10 => {
                 let (name, new_pos) = Self::deserialize_string(data, pos)?;
                 pos = new_pos;
@@ -1031,3 +1926,119 @@ impl Default for Bytecode {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let mut bytecode = Bytecode::new();
+        bytecode.emit(Instruction::LoadConst(Value::String("hi".to_string())));
+        bytecode.emit(Instruction::Call("println".to_string(), 1));
+        bytecode.emit(Instruction::LoadConst(Value::String("hi".to_string())));
+        bytecode.emit(Instruction::MakeStruct {
+            type_id: 0,
+            slots: vec![1, 0],
+        });
+        bytecode.emit(Instruction::GetFieldIdx(0, 1));
+        bytecode.emit(Instruction::MakeClosure {
+            func_name: "__closure_0".to_string(),
+            param_count: 1,
+            captures: vec!["offset".to_string(), "scale".to_string()],
+        });
+        bytecode.emit(Instruction::CallValue(1));
+        bytecode.emit(Instruction::Halt);
+        bytecode.register_struct(
+            "Player".to_string(),
+            vec![
+                ("name".to_string(), ZyraType::String),
+                ("pos".to_string(), ZyraType::Vec(Box::new(ZyraType::F64))),
+            ],
+        );
+
+        let data = bytecode.serialize();
+        // Repeated constants share one pool entry
+        let pool_size_at = 4 + 2 + 1 + COMPILER_VERSION.len() + 8;
+        assert_eq!(data[pool_size_at], 1);
+
+        let loaded = Bytecode::deserialize(&data).unwrap();
+        assert_eq!(loaded.instructions, bytecode.instructions);
+        assert_eq!(loaded.structs, bytecode.structs);
+        assert_eq!(loaded.struct_id("Player"), Some(0));
+        assert_eq!(loaded.field_index(0, "pos"), Some(1));
+    }
+
+    #[test]
+    fn test_display_cuts_off_deep_nesting() {
+        let mut value = Value::Int(1);
+        for _ in 0..MAX_VALUE_DEPTH + 10 {
+            value = Value::Vec(vec![value]);
+        }
+        let text = value.to_string();
+        assert!(text.starts_with("[[["));
+        assert!(text.contains("[...]"));
+        assert!(!text.contains('1'));
+
+        let shallow = Value::Vec(vec![Value::some(Value::Int(1)), Value::Int(2)]);
+        assert_eq!(shallow.to_string(), "[Some(1), 2]");
+    }
+
+    #[test]
+    fn test_line_table_round_trip() {
+        let mut bytecode = Bytecode::new();
+        bytecode.source_file = "game.zr".to_string();
+        bytecode.set_line(3);
+        bytecode.emit(Instruction::LoadConst(Value::Int(1)));
+        bytecode.emit(Instruction::LoadConst(Value::Int(0)));
+        bytecode.set_line(4);
+        bytecode.emit(Instruction::Div);
+
+        let loaded = Bytecode::deserialize(&bytecode.serialize()).unwrap();
+        assert_eq!(loaded.source_file, "game.zr");
+        assert_eq!(loaded.lines, vec![3, 3, 4]);
+        assert_eq!(loaded.line_at(2), Some(4));
+    }
+
+    #[test]
+    fn test_deserialize_rejects_bad_input() {
+        let data = Bytecode::new().serialize();
+        assert!(Bytecode::deserialize(b"ZYRA").is_err());
+        assert!(Bytecode::deserialize(&data[..6]).is_err());
+        assert!(Bytecode::deserialize(&[b"NOPE", &data[4..]].concat()).is_err());
+        assert!(Bytecode::deserialize(&[&data[..], &[0]].concat()).is_err());
+
+        let mut corrupted = data.clone();
+        *corrupted.last_mut().unwrap() ^= 0xFF;
+        let err = Bytecode::deserialize(&corrupted).unwrap_err();
+        assert!(err.contains("checksum"), "{}", err);
+    }
+
+    #[test]
+    fn test_deserialize_rejects_deeply_nested_constants() {
+        let mut value = Value::Int(1);
+        for _ in 0..MAX_CONSTANT_DEPTH + 10 {
+            value = Value::Vec(vec![value]);
+        }
+        let mut bytecode = Bytecode::new();
+        bytecode.emit(Instruction::LoadConst(value));
+        let err = Bytecode::deserialize(&bytecode.serialize()).unwrap_err();
+        assert!(err.contains("nested too deeply"), "{}", err);
+    }
+
+    #[test]
+    fn test_deserialize_reports_incompatible_version() {
+        let old = [b"ZYRA".as_slice(), &[1, 0], &[0; 8]].concat();
+        let err = Bytecode::deserialize(&old).unwrap_err();
+        assert!(err.contains("an older Zyra compiler (format 1)"), "{}", err);
+
+        let mut newer = Bytecode::new().serialize();
+        newer[4] = 12;
+        let err = Bytecode::deserialize(&newer).unwrap_err();
+        assert!(
+            err.contains(&format!("Zyra {} (format 12)", COMPILER_VERSION)),
+            "{}",
+            err
+        );
+    }
+}
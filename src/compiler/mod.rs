@@ -3,46 +3,259 @@
 //! Compiles AST to stack-based bytecode
 
 pub mod bytecode;
+pub mod ordered;
 
 pub use bytecode::{Bytecode, FunctionDef, Instruction, Value, WindowState};
 
-use crate::error::{ZyraError, ZyraResult};
+use crate::error::{SourceLocation, ZyraError, ZyraResult};
 use crate::parser::ast::*;
-use std::collections::HashSet;
+use crate::semantic::ZyraType;
+use std::collections::{HashMap, HashSet};
+
+/// What integer `+`, `-` and `*` do when the result does not fit its type.
+/// Unless zyra.toml says otherwise, debug builds fail and release builds wrap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowMode {
+    /// Fail with an `OverflowError` at the operation's line
+    #[default]
+    Error,
+    /// Wrap around (two's complement)
+    Wrap,
+    /// Clamp to the type's minimum or maximum
+    Saturate,
+}
+
+impl OverflowMode {
+    /// Parse a zyra.toml `overflow` setting
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(OverflowMode::Error),
+            "wrap" => Some(OverflowMode::Wrap),
+            "saturate" => Some(OverflowMode::Saturate),
+            _ => None,
+        }
+    }
+
+    /// Behavior of a build whose profile doesn't set `overflow`
+    pub fn for_profile(release: bool) -> Self {
+        if release {
+            OverflowMode::Wrap
+        } else {
+            OverflowMode::Error
+        }
+    }
+}
+
+/// Jump bookkeeping for an enclosing loop, used by break/continue
+struct LoopContext {
+    label: Option<String>,
+    /// Scope depth at the loop boundary; break/continue exit every scope above it
+    scope_depth: usize,
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+/// One step from a match scrutinee to the part a nested pattern looks at
+enum PatternStep {
+    /// A struct field, or `_data` of an enum variant
+    Field(String),
+    /// An element of a tuple
+    Index(i64),
+}
 
 /// Bytecode compiler
 pub struct Compiler {
     bytecode: Bytecode,
-    loop_starts: Vec<usize>,
-    loop_ends: Vec<Vec<usize>>,
+    loops: Vec<LoopContext>,
+    /// Number of scopes currently entered by loops and blocks
+    scope_depth: usize,
     /// Tracks which methods/functions are actually called (for dead code elimination)
     used_methods: HashSet<String>,
+    /// Trait definitions by name, used to compile default methods into each impl
+    traits: HashMap<String, Vec<TraitMethod>>,
+    /// Struct type of local variables in the function being compiled, where it is
+    /// statically known; field access on them compiles to indexed instructions
+    local_types: HashMap<String, String>,
+    /// Declared sized integer type of local variables, which literals assigned
+    /// to them take
+    local_ints: HashMap<String, Type>,
+    /// Type the literals of the next expression compiled take, from its
+    /// context; see `compile_with_hint`
+    literal_hint: Option<Type>,
+    /// Declared return type of the function being compiled
+    return_type: Option<Type>,
+    /// Parameters of the functions and methods, by bytecode name
+    signatures: HashMap<String, Vec<Parameter>>,
+    /// Declared field types of the structs
+    struct_fields: HashMap<String, Vec<(String, Type)>>,
+    /// Target type of the impl block being compiled, the type of `self`
+    impl_type: Option<String>,
+    /// Arithmetic instructions emitted for integer overflow
+    overflow: OverflowMode,
+    /// Items merged in from local modules
+    imported: ModuleItems,
+    /// Module whose code is being compiled, for calls between its functions
+    current_module: Option<String>,
+    /// Folded values of the top-level constants, keyed like functions
+    constants: HashMap<String, Value>,
+    /// Names of the `static mut` globals, keyed like constants
+    statics: std::collections::HashSet<String>,
 }
 
 impl Compiler {
     pub fn new() -> Self {
         Self {
             bytecode: Bytecode::new(),
-            loop_starts: Vec::new(),
-            loop_ends: Vec::new(),
+            loops: Vec::new(),
+            scope_depth: 0,
             used_methods: HashSet::new(),
+            traits: HashMap::new(),
+            local_types: HashMap::new(),
+            local_ints: HashMap::new(),
+            literal_hint: None,
+            return_type: None,
+            signatures: HashMap::new(),
+            struct_fields: HashMap::new(),
+            impl_type: None,
+            overflow: OverflowMode::default(),
+            imported: ModuleItems::default(),
+            current_module: None,
+            constants: HashMap::new(),
+            statics: std::collections::HashSet::new(),
         }
     }
 
+    /// Use the given overflow behavior for `+`, `-` and `*`
+    pub fn with_overflow(mut self, overflow: OverflowMode) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
     /// Compile a program to bytecode
     pub fn compile(&mut self, program: &Program) -> ZyraResult<Bytecode> {
         // Pass 0: Collect used method/function names for dead code elimination
         self.collect_used_methods(&program.statements);
+        self.imported = program.imported.clone();
+
+        // Record struct layouts so type names can be used as runtime schemas
+        for stmt in &program.statements {
+            if let Statement::Struct { name, fields, .. } = stmt {
+                let layout = fields
+                    .iter()
+                    .map(|f| (f.name.clone(), ZyraType::from_ast_type(&f.field_type)))
+                    .collect();
+                self.bytecode.register_struct(name.clone(), layout);
+                let declared = fields
+                    .iter()
+                    .map(|f| (f.name.clone(), f.field_type.clone()))
+                    .collect();
+                self.struct_fields.insert(name.clone(), declared);
+            }
+        }
+
+        // Record parameter types so literal arguments take the type of their parameter
+        for stmt in &program.statements {
+            match stmt {
+                Statement::Function { name, params, .. } => {
+                    self.signatures.insert(name.clone(), params.clone());
+                }
+                Statement::Impl {
+                    target_type,
+                    trait_name: None,
+                    methods,
+                    ..
+                } => {
+                    for method in methods {
+                        if let Statement::Function { name, params, .. } = method.as_ref() {
+                            let key = format!("{}::{}", target_type, name);
+                            self.signatures.insert(key, params.clone());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Number enums and their variants so values carry integer tags
+        for stmt in &program.statements {
+            if let Statement::Enum { name, variants, .. } = stmt {
+                let names = variants.iter().map(|v| v.name.clone()).collect();
+                self.bytecode.enums.push((name.clone(), names));
+            }
+        }
+
+        // Record trait definitions so impls can pick up default methods
+        for stmt in &program.statements {
+            if let Statement::Trait { name, methods, .. } = stmt {
+                self.traits.insert(name.clone(), methods.clone());
+            }
+        }
+
+        // Fold constants so every use of one loads its value, and the
+        // initial values of globals for the VM's globals table
+        for stmt in &program.statements {
+            if let Statement::Const {
+                name,
+                type_annotation,
+                value,
+                ..
+            }
+            | Statement::Static {
+                name,
+                type_annotation,
+                value,
+                ..
+            } = stmt
+            {
+                self.current_module = self.imported.get(name).map(|item| item.module.clone());
+                // Folded in the declared type; a result that still doesn't fit
+                // it (say from plain-int constants) is an error, not truncated
+                let folded = match (
+                    self.fold_constant(value, type_annotation.as_ref())?,
+                    type_annotation,
+                ) {
+                    (Value::Int(n), Some(ty)) if Self::sized_int(n, ty).is_some() => Value::Int(n)
+                        .narrow(
+                            &ZyraType::from_ast_type(ty).display_name(),
+                            OverflowMode::Error,
+                        )
+                        .map_err(|message| {
+                            ZyraError::new(
+                                "CompileError",
+                                &message,
+                                Some(SourceLocation::from_span(&value.span())),
+                            )
+                        })?,
+                    (folded, _) => folded,
+                };
+                if matches!(stmt, Statement::Static { .. }) {
+                    self.statics.insert(name.clone());
+                    self.bytecode.globals.push((name.clone(), folded));
+                } else {
+                    self.constants.insert(name.clone(), folded);
+                }
+            }
+        }
+        self.current_module = None;
 
         // First pass: compile function definitions
         for stmt in &program.statements {
             if let Statement::Function {
-                name, params, body, ..
+                name,
+                params,
+                return_type,
+                body,
+                span,
+                ..
             } = stmt
             {
-                self.compile_function(name, params, body)?;
+                self.current_module = self.imported.get(name).map(|item| item.module.clone());
+                self.with_line(span.line, |c| {
+                    c.compile_function(name, params, return_type.as_ref(), body)
+                })?;
             }
         }
+        self.current_module = None;
 
         // Second pass: compile top-level statements
         for stmt in &program.statements {
@@ -71,7 +284,9 @@ impl Compiler {
 
     fn collect_from_statement(&mut self, stmt: &Statement) {
         match stmt {
-            Statement::Let { value, .. } => self.collect_from_expression(value),
+            Statement::Let { value, .. } | Statement::LetPattern { value, .. } => {
+                self.collect_from_expression(value)
+            }
             Statement::Function { body, .. } => {
                 for s in &body.statements {
                     self.collect_from_statement(s);
@@ -124,11 +339,46 @@ impl Compiler {
                     self.collect_from_expression(expr);
                 }
             }
+            Statement::IfLet {
+                value,
+                then_block,
+                else_block,
+                ..
+            } => {
+                self.collect_from_expression(value);
+                for block in std::iter::once(then_block).chain(else_block) {
+                    for s in &block.statements {
+                        self.collect_from_statement(s);
+                    }
+                    if let Some(expr) = &block.expression {
+                        self.collect_from_expression(expr);
+                    }
+                }
+            }
+            Statement::WhileLet { value, body, .. } => {
+                self.collect_from_expression(value);
+                for s in &body.statements {
+                    self.collect_from_statement(s);
+                }
+                if let Some(expr) = &body.expression {
+                    self.collect_from_expression(expr);
+                }
+            }
             Statement::Impl { methods, .. } => {
                 for method in methods {
                     self.collect_from_statement(method);
                 }
             }
+            Statement::Trait { methods, .. } => {
+                for body in methods.iter().filter_map(|m| m.default_impl.as_ref()) {
+                    for s in &body.statements {
+                        self.collect_from_statement(s);
+                    }
+                    if let Some(expr) = &body.expression {
+                        self.collect_from_expression(expr);
+                    }
+                }
+            }
             Statement::Block(block) => {
                 for s in &block.statements {
                     self.collect_from_statement(s);
@@ -163,6 +413,8 @@ impl Compiler {
                     self.used_methods.insert(field.clone());
                     // Recurse into the object
                     self.collect_from_expression(object);
+                } else {
+                    self.collect_from_expression(callee);
                 }
                 // Recurse into arguments
                 for arg in arguments {
@@ -177,7 +429,8 @@ impl Compiler {
             Expression::Unary { operand, .. } => {
                 self.collect_from_expression(operand);
             }
-            Expression::Assignment { value, .. } => {
+            Expression::Assignment { target, value, .. } => {
+                self.collect_from_expression(target);
                 self.collect_from_expression(value);
             }
             Expression::If {
@@ -208,7 +461,18 @@ impl Compiler {
                     self.collect_from_expression(field_expr);
                 }
             }
-            Expression::List { elements, .. } => {
+            Expression::List { elements, .. } | Expression::Tuple { elements, .. } => {
+                for elem in elements {
+                    self.collect_from_expression(elem);
+                }
+            }
+            Expression::MapLiteral { entries, .. } => {
+                for (key, value) in entries {
+                    self.collect_from_expression(key);
+                    self.collect_from_expression(value);
+                }
+            }
+            Expression::SetLiteral { elements, .. } => {
                 for elem in elements {
                     self.collect_from_expression(elem);
                 }
@@ -217,23 +481,440 @@ impl Compiler {
                 self.collect_from_expression(object);
                 self.collect_from_expression(index);
             }
-            Expression::FieldAccess { object, .. } => {
+            Expression::FieldAccess { object, .. } | Expression::TupleIndex { object, .. } => {
                 self.collect_from_expression(object);
             }
             Expression::Reference { value, .. } | Expression::Dereference { value, .. } => {
                 self.collect_from_expression(value);
             }
+            Expression::Closure { body, .. } => {
+                for s in &body.statements {
+                    self.collect_from_statement(s);
+                }
+                if let Some(expr) = &body.expression {
+                    self.collect_from_expression(expr);
+                }
+            }
             _ => {}
         }
     }
 
+    /// std::mem drop/take/swap write back to the variables they are given,
+    /// so they compile to loads and stores instead of a plain call.
+    /// Returns false when the call is not one of these intrinsics.
+    fn compile_mem_intrinsic(&mut self, name: &str, arguments: &[Expression]) -> ZyraResult<bool> {
+        let short = name
+            .strip_prefix("std::mem::")
+            .or_else(|| name.strip_prefix("mem::"))
+            .unwrap_or(name);
+        if !matches!(short, "drop" | "take" | "swap") || self.bytecode.functions.contains_key(name)
+        {
+            return Ok(false);
+        }
+
+        // Both `x` and `&mut x` name the variable `x`
+        let places: Vec<&str> = arguments
+            .iter()
+            .filter_map(|arg| match arg {
+                Expression::Identifier { name, .. } => Some(name.as_str()),
+                Expression::Reference { value, .. } => match value.as_ref() {
+                    Expression::Identifier { name, .. } => Some(name.as_str()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        if places.len() != arguments.len() {
+            return Ok(false);
+        }
+
+        match (short, places.as_slice()) {
+            ("drop", [place]) => {
+                // Release the value now and clear the variable so its heap slot is freed
+                self.bytecode.emit(Instruction::LoadVar(place.to_string()));
+                self.bytecode.emit(Instruction::Call(name.to_string(), 1));
+                self.bytecode.emit(Instruction::LoadConst(Value::None));
+                self.bytecode.emit(Instruction::StoreVar(place.to_string()));
+            }
+            ("take", [place]) => {
+                // Keep the old value on the stack and store its default in its place
+                self.bytecode.emit(Instruction::LoadVar(place.to_string()));
+                self.bytecode.emit(Instruction::Dup);
+                self.bytecode.emit(Instruction::Call(name.to_string(), 1));
+                self.bytecode.emit(Instruction::StoreVar(place.to_string()));
+            }
+            ("swap", [a, b]) => {
+                self.bytecode.emit(Instruction::LoadVar(a.to_string()));
+                self.bytecode.emit(Instruction::LoadVar(b.to_string()));
+                self.bytecode.emit(Instruction::StoreVar(a.to_string()));
+                self.bytecode.emit(Instruction::StoreVar(b.to_string()));
+                self.bytecode.emit(Instruction::LoadConst(Value::None));
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Runtime value of an integer constant of a sized type, e.g. `Value::U8`
+    /// for `255u8`; the analyzer has checked that it fits. `Int` constants
+    /// stay plain ints.
+    fn sized_int(value: i64, ty: &Type) -> Option<Value> {
+        if let Type::Int = ty {
+            return None;
+        }
+        if let Type::Named(name) = ty {
+            if name == "int" || name == "Int" {
+                return None;
+            }
+        }
+        match ZyraType::from_ast_type(ty) {
+            ZyraType::I8 => Some(Value::I8(value as i8)),
+            ZyraType::I32 => Some(Value::I32(value as i32)),
+            ZyraType::I64 => Some(Value::I64(value)),
+            ZyraType::U8 => Some(Value::U8(value as u8)),
+            ZyraType::U32 => Some(Value::U32(value as u32)),
+            ZyraType::U64 => Some(Value::U64(value as u64)),
+            _ => None,
+        }
+    }
+
+    /// Key of a top-level item; inside a module, a bare name is one of the
+    /// module's own items
+    fn item_key(&self, name: &str) -> String {
+        match &self.current_module {
+            Some(module) if !name.contains("::") => format!("{}::{}", module, name),
+            _ => name.to_string(),
+        }
+    }
+
+    /// Folded value of the constant a name refers to
+    fn constant(&self, name: &str) -> Option<&Value> {
+        self.constants.get(&self.item_key(name))
+    }
+
+    /// Globals-table name of the `static mut` global a name refers to
+    fn global_name(&self, name: &str) -> Option<String> {
+        Some(self.item_key(name)).filter(|key| self.statics.contains(key))
+    }
+
+    /// Work out a constant's value. The analyzer has checked that it only
+    /// uses literals, operators, casts and earlier constants; an overflow or a
+    /// division by zero is reported here rather than when the program runs.
+    /// Integer literals take the type `hint`, as they do in compiled code.
+    fn fold_constant(&self, expr: &Expression, hint: Option<&Type>) -> ZyraResult<Value> {
+        let error = |message: &str| {
+            ZyraError::new(
+                "CompileError",
+                message,
+                Some(SourceLocation::from_span(&expr.span())),
+            )
+        };
+        match expr {
+            Expression::Int { value, suffix, .. } => Ok(suffix
+                .as_ref()
+                .or(hint)
+                .and_then(|ty| Self::sized_int(*value, ty))
+                .unwrap_or(Value::Int(*value))),
+            Expression::Float { value, .. } => Ok(Value::Float(*value)),
+            Expression::Bool { value, .. } => Ok(Value::Bool(*value)),
+            Expression::Char { value, .. } => Ok(Value::Char(*value)),
+            Expression::String { value, .. } => Ok(Value::String(value.clone())),
+            Expression::Grouped { inner, .. } => self.fold_constant(inner, hint),
+            Expression::Cast {
+                expr: inner,
+                target_type,
+                ..
+            } => self
+                .fold_constant(inner, None)?
+                .cast(&ZyraType::from_ast_type(target_type).display_name())
+                .map_err(|message| error(&message)),
+            Expression::Identifier { name, .. } => self
+                .constant(name)
+                .cloned()
+                .ok_or_else(|| error(&format!("'{}' is not a constant", name))),
+            Expression::EnumVariant { .. } => expr
+                .qualified_name()
+                .and_then(|name| self.constants.get(&name).cloned())
+                .ok_or_else(|| error("Constant values must be known at compile time")),
+            Expression::Unary {
+                operator: UnaryOp::Negate,
+                operand,
+                ..
+            } => {
+                // `-128i8` fits an i8 even though 128 doesn't
+                if let Expression::Int { value, suffix, .. } = operand.as_ref() {
+                    let ty = suffix.as_ref().or(hint);
+                    if let Some(constant) =
+                        ty.and_then(|ty| Self::sized_int(value.wrapping_neg(), ty))
+                    {
+                        return Ok(constant);
+                    }
+                }
+                let value = self.fold_constant(operand, hint)?;
+                value
+                    .neg()
+                    .ok_or_else(|| error(&format!("Cannot negate {}", value.type_name())))
+            }
+            Expression::Unary {
+                operator: UnaryOp::Not,
+                operand,
+                ..
+            } => Ok(self.fold_constant(operand, hint)?.not()),
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let (left_hint, right_hint) = match operator {
+                    BinaryOp::Add
+                    | BinaryOp::Subtract
+                    | BinaryOp::Multiply
+                    | BinaryOp::Divide
+                    | BinaryOp::Modulo
+                    | BinaryOp::BitAnd
+                    | BinaryOp::BitOr
+                    | BinaryOp::BitXor => (hint, hint),
+                    BinaryOp::ShiftLeft | BinaryOp::ShiftRight => (hint, None),
+                    _ => (None, None),
+                };
+                let (a, b) = (
+                    self.fold_constant(left, left_hint)?,
+                    self.fold_constant(right, right_hint)?,
+                );
+                // The shift amount doesn't lend its type to the shifted value
+                let (a, b) = match operator {
+                    BinaryOp::ShiftLeft | BinaryOp::ShiftRight => (a, b),
+                    _ => Value::unify_numeric(a, b),
+                };
+                let result = match operator {
+                    BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply => {
+                        let op = operator.as_str().chars().next().unwrap_or('+');
+                        match a.int_arith(op, &b, OverflowMode::Error) {
+                            Some(result) => Some(result.map_err(|message| error(&message))?),
+                            None => match operator {
+                                BinaryOp::Add => a.add(&b),
+                                BinaryOp::Subtract => a.sub(&b),
+                                _ => a.mul(&b),
+                            },
+                        }
+                    }
+                    BinaryOp::Divide | BinaryOp::Modulo
+                        if matches!(
+                            b,
+                            Value::Int(0)
+                                | Value::I8(0)
+                                | Value::I32(0)
+                                | Value::I64(0)
+                                | Value::U8(0)
+                                | Value::U32(0)
+                                | Value::U64(0)
+                        ) =>
+                    {
+                        return Err(error("Division by zero in a constant"));
+                    }
+                    BinaryOp::Divide => a.div(&b),
+                    BinaryOp::Modulo => a.modulo(&b),
+                    BinaryOp::Equal => Some(a.eq(&b)),
+                    BinaryOp::NotEqual => Some(a.eq(&b).not()),
+                    BinaryOp::Less => a.lt(&b),
+                    BinaryOp::LessEqual => a.lte(&b),
+                    BinaryOp::Greater => a.gt(&b),
+                    BinaryOp::GreaterEqual => a.gte(&b),
+                    BinaryOp::And => Some(Value::Bool(a.is_truthy() && b.is_truthy())),
+                    BinaryOp::Or => Some(Value::Bool(a.is_truthy() || b.is_truthy())),
+                    BinaryOp::BitAnd
+                    | BinaryOp::BitOr
+                    | BinaryOp::BitXor
+                    | BinaryOp::ShiftLeft
+                    | BinaryOp::ShiftRight => a
+                        .bitwise(operator.as_str(), &b)
+                        .transpose()
+                        .map_err(|message| error(&message))?,
+                };
+                result.ok_or_else(|| {
+                    error(&format!(
+                        "Cannot apply '{}' to {} and {}",
+                        operator.as_str(),
+                        a.type_name(),
+                        b.type_name()
+                    ))
+                })
+            }
+            _ => Err(error("Constant values must be known at compile time")),
+        }
+    }
+
+    /// Registered struct named by a type annotation, looking through references
+    fn struct_type_name(&self, ty: &Type) -> Option<String> {
+        match ty {
+            Type::Named(name) if self.bytecode.structs.contains_key(name) => Some(name.clone()),
+            Type::Reference { inner, .. } | Type::LifetimeAnnotated { inner, .. } => {
+                self.struct_type_name(inner)
+            }
+            _ => None,
+        }
+    }
+
+    /// Struct type an expression evaluates to, when the compiler can tell
+    fn static_struct_type(&self, expr: &Expression) -> Option<String> {
+        match expr {
+            Expression::Identifier { name, .. } => self.local_types.get(name).cloned(),
+            Expression::StructInit { name, .. } if self.bytecode.structs.contains_key(name) => {
+                Some(name.clone())
+            }
+            Expression::FieldAccess { object, field, .. } => {
+                let owner = self.static_struct_type(object)?;
+                let layout = self.bytecode.structs.get(&owner)?;
+                match &layout.iter().find(|(name, _)| name == field)?.1 {
+                    ZyraType::Struct(name) if self.bytecode.structs.contains_key(name) => {
+                        Some(name.clone())
+                    }
+                    _ => None,
+                }
+            }
+            Expression::Reference { value, .. } | Expression::Dereference { value, .. } => {
+                self.static_struct_type(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Record (or forget) the struct type of a local variable
+    fn set_local_type(&mut self, name: &str, struct_type: Option<String>) {
+        match struct_type {
+            Some(ty) => self.local_types.insert(name.to_string(), ty),
+            None => self.local_types.remove(name),
+        };
+    }
+
+    /// Record (or forget) the declared integer type of a local variable
+    fn set_local_int(&mut self, name: &str, declared: Option<&Type>) {
+        match declared.filter(|ty| Self::sized_int(0, ty).is_some()) {
+            Some(ty) => self.local_ints.insert(name.to_string(), ty.clone()),
+            None => self.local_ints.remove(name),
+        };
+    }
+
+    /// Declared type of an assignment target, when the compiler can tell
+    fn place_type(&self, place: &Expression) -> Option<Type> {
+        match place {
+            Expression::Identifier { name, .. } => self.local_ints.get(name).cloned(),
+            Expression::FieldAccess { object, field, .. } => {
+                let fields = self.struct_fields.get(&self.static_struct_type(object)?)?;
+                let (_, ty) = fields.iter().find(|(name, _)| name == field)?;
+                Some(ty.clone())
+            }
+            Expression::Grouped { inner, .. } => self.place_type(inner),
+            _ => None,
+        }
+    }
+
+    /// Type id and field index for `object.field` when the object's struct type is known
+    fn field_slot(&self, object: &Expression, field: &str) -> Option<(u32, u32)> {
+        let type_id = self.bytecode.struct_id(&self.static_struct_type(object)?)?;
+        Some((type_id, self.bytecode.field_index(type_id, field)?))
+    }
+
+    /// Store the value on top of the stack into a place such as
+    /// `player.inventory[3].count`. Inline values (arrays, objects) are
+    /// copied when loaded, so each updated container is written back to
+    /// its own place in turn, up to the variable at the root.
+    fn compile_place_store(&mut self, place: &Expression) -> ZyraResult<()> {
+        match place {
+            Expression::Identifier { name, .. } => {
+                let store = match self.global_name(name) {
+                    Some(global) => Instruction::StoreGlobal(global),
+                    None => Instruction::StoreVar(name.clone()),
+                };
+                self.bytecode.emit(store);
+            }
+            Expression::FieldAccess { object, field, .. } => {
+                let slot = self.field_slot(object, field);
+                self.compile_expression(object)?;
+                match slot {
+                    Some((type_id, idx)) => {
+                        self.bytecode.emit(Instruction::SetFieldIdx(type_id, idx))
+                    }
+                    None => self.bytecode.emit(Instruction::SetField(field.clone())),
+                };
+                self.compile_place_store(object)?;
+            }
+            Expression::Index { object, index, .. } => {
+                self.compile_expression(object)?;
+                self.compile_expression(index)?;
+                self.bytecode.emit(Instruction::SetIndex);
+                self.compile_place_store(object)?;
+            }
+            Expression::Grouped { inner, .. } => self.compile_place_store(inner)?,
+            // A temporary such as a call result: heap values were updated in
+            // place, and there is nowhere to write an inline one back
+            _ => {
+                self.bytecode.emit(Instruction::Pop);
+            }
+        }
+        Ok(())
+    }
+
+    /// A copy of a place whose index expressions, and a root that isn't a
+    /// variable such as a call, are replaced by hidden variables holding
+    /// their values
+    fn hoist_place(&mut self, place: &Expression) -> ZyraResult<Expression> {
+        Ok(match place {
+            Expression::Identifier { .. } => place.clone(),
+            Expression::FieldAccess {
+                object,
+                field,
+                span,
+            } => Expression::FieldAccess {
+                object: Box::new(self.hoist_place(object)?),
+                field: field.clone(),
+                span: *span,
+            },
+            Expression::Index {
+                object,
+                index,
+                span,
+            } => {
+                let object = self.hoist_place(object)?;
+                let index = match index.as_ref() {
+                    Expression::Int { .. } | Expression::Identifier { .. } => {
+                        index.as_ref().clone()
+                    }
+                    _ => self.hoist_value(index)?,
+                };
+                Expression::Index {
+                    object: Box::new(object),
+                    index: Box::new(index),
+                    span: *span,
+                }
+            }
+            Expression::Grouped { inner, .. } => self.hoist_place(inner)?,
+            _ => self.hoist_value(place)?,
+        })
+    }
+
+    fn hoist_value(&mut self, expr: &Expression) -> ZyraResult<Expression> {
+        let name = format!("__place_{}", self.bytecode.current_address());
+        self.compile_expression(expr)?;
+        self.bytecode.emit(Instruction::StoreVar(name.clone()));
+        Ok(Expression::Identifier {
+            name,
+            span: expr.span(),
+        })
+    }
+
     fn compile_function(
         &mut self,
         name: &str,
         params: &[Parameter],
+        return_type: Option<&Type>,
         body: &Block,
     ) -> ZyraResult<()> {
         let start_address = self.bytecode.current_address();
+        let outer_types = std::mem::take(&mut self.local_types);
+        let outer_ints = std::mem::take(&mut self.local_ints);
+        let outer_return = std::mem::replace(&mut self.return_type, return_type.cloned());
 
         // Enter function scope
         self.bytecode.emit(Instruction::EnterScope);
@@ -253,11 +934,29 @@ impl Compiler {
             } else {
                 param.name.clone()
             };
+            let struct_type = if var_name == "self" {
+                self.impl_type.clone()
+            } else {
+                self.struct_type_name(&param.param_type)
+            };
+            self.set_local_type(&var_name, struct_type);
+            self.set_local_int(&var_name, Some(&param.param_type));
             self.bytecode.emit(Instruction::StoreVar(var_name));
         }
 
-        // Compile body
-        self.compile_block(body)?;
+        // Compile body; its trailing expression is the return value
+        let result = body
+            .statements
+            .iter()
+            .try_for_each(|stmt| self.compile_statement(stmt))
+            .and_then(|_| match &body.expression {
+                Some(expr) => self.compile_with_hint(expr, return_type),
+                None => Ok(()),
+            });
+        self.local_types = outer_types;
+        self.local_ints = outer_ints;
+        self.return_type = outer_return;
+        result?;
 
         // Implicit return if no explicit return
         self.bytecode.emit(Instruction::Return);
@@ -278,16 +977,102 @@ impl Compiler {
         Ok(())
     }
 
+    fn enter_scope(&mut self) {
+        self.bytecode.emit(Instruction::EnterScope);
+        self.scope_depth += 1;
+    }
+
+    fn exit_scope(&mut self) {
+        self.bytecode.emit(Instruction::ExitScope);
+        self.scope_depth -= 1;
+    }
+
+    fn push_loop(&mut self, label: &Option<String>) {
+        self.loops.push(LoopContext {
+            label: label.clone(),
+            scope_depth: self.scope_depth,
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        });
+    }
+
+    /// Patch the break/continue jumps of the innermost loop
+    fn pop_loop(&mut self, continue_target: usize, break_target: usize) {
+        if let Some(ctx) = self.loops.pop() {
+            for addr in ctx.continues {
+                self.bytecode.patch_jump(addr, continue_target);
+            }
+            for addr in ctx.breaks {
+                self.bytecode.patch_jump(addr, break_target);
+            }
+        }
+    }
+
+    /// Find the loop targeted by break/continue (innermost if unlabeled)
+    fn find_loop(&self, label: Option<&str>) -> Option<usize> {
+        match label {
+            None => self.loops.len().checked_sub(1),
+            Some(name) => self
+                .loops
+                .iter()
+                .rposition(|ctx| ctx.label.as_deref() == Some(name)),
+        }
+    }
+
+    /// Run `f` with instructions attributed to `line`, then restore the outer line.
+    /// Synthesized nodes without a span (line 0) keep the outer line.
+    fn with_line<T>(&mut self, line: usize, f: impl FnOnce(&mut Self) -> T) -> T {
+        if line == 0 {
+            return f(self);
+        }
+        let outer = self.bytecode.set_line(line);
+        let result = f(self);
+        self.bytecode.set_line(outer);
+        result
+    }
+
     fn compile_statement(&mut self, stmt: &Statement) -> ZyraResult<()> {
+        self.with_line(stmt.span().line, |c| c.compile_statement_kind(stmt))
+    }
+
+    fn compile_statement_kind(&mut self, stmt: &Statement) -> ZyraResult<()> {
         match stmt {
-            Statement::Let { name, value, .. } => {
-                self.compile_expression(value)?;
+            Statement::Let {
+                name,
+                type_annotation,
+                value,
+                ..
+            } => {
+                let struct_type = match type_annotation {
+                    Some(ty) => self.struct_type_name(ty),
+                    None => self.static_struct_type(value),
+                };
+                // `let x: u8 = 5` stores a u8; unannotated literals stay plain ints
+                self.compile_with_hint(value, type_annotation.as_ref())?;
+                self.set_local_type(name, struct_type);
+                self.set_local_int(name, type_annotation.as_ref());
                 self.bytecode.emit(Instruction::StoreVar(name.clone()));
                 Ok(())
             }
 
-            Statement::Function { .. } => {
-                // Functions are compiled in the first pass
+            // let (a, b) = value binds each name from the value, which the
+            // pattern always matches
+            Statement::LetPattern { pattern, value, .. } => {
+                self.compile_expression(value)?;
+                let value_var = format!("__let_pattern_{}", self.bytecode.current_address());
+                self.bytecode.emit(Instruction::StoreVar(value_var.clone()));
+                let mut names = Vec::new();
+                pattern.bound_names(&mut names);
+                for name in &names {
+                    self.set_local_type(name, None);
+                    self.set_local_int(name, None);
+                }
+                self.compile_pattern_bindings(pattern, &value_var, &mut Vec::new());
+                Ok(())
+            }
+
+            Statement::Function { .. } | Statement::Const { .. } | Statement::Static { .. } => {
+                // Functions are compiled and constants and globals folded before the other statements
                 Ok(())
             }
 
@@ -311,7 +1096,8 @@ impl Compiler {
 
             Statement::Return { value, .. } => {
                 if let Some(expr) = value {
-                    self.compile_expression(expr)?;
+                    let return_type = self.return_type.clone();
+                    self.compile_with_hint(expr, return_type.as_ref())?;
                 } else {
                     self.bytecode.emit(Instruction::LoadConst(Value::None));
                 }
@@ -357,11 +1143,13 @@ impl Compiler {
             }
 
             Statement::While {
-                condition, body, ..
+                label,
+                condition,
+                body,
+                ..
             } => {
                 let loop_start = self.bytecode.current_address();
-                self.loop_starts.push(loop_start);
-                self.loop_ends.push(Vec::new());
+                self.push_loop(label);
 
                 // Compile condition
                 self.compile_expression(condition)?;
@@ -370,58 +1158,210 @@ impl Compiler {
                 let jump_to_end = self.bytecode.emit(Instruction::JumpIfFalse(0));
 
                 // Compile body
-                self.bytecode.emit(Instruction::EnterScope);
+                self.enter_scope();
+                self.compile_block(body)?;
+                self.exit_scope();
+
+                // Jump back to start
+                self.bytecode.emit(Instruction::Jump(loop_start));
+
+                // Patch jump to end
+                let loop_end = self.bytecode.current_address();
+                self.bytecode.patch_jump(jump_to_end, loop_end);
+
+                // Patch any break/continue statements
+                self.pop_loop(loop_start, loop_end);
+
+                Ok(())
+            }
+
+            // if let desugars to a two-arm match: the pattern runs the then block,
+            // anything else runs the else block
+            Statement::IfLet {
+                pattern,
+                value,
+                then_block,
+                else_block,
+                ..
+            } => {
+                self.compile_expression(value)?;
+                let scrutinee_var =
+                    format!("__if_let_scrutinee_{}", self.bytecode.current_address());
+                self.bytecode
+                    .emit(Instruction::StoreVar(scrutinee_var.clone()));
+
+                let mut jumps_to_else = Vec::new();
+                self.compile_pattern_test(
+                    pattern,
+                    &scrutinee_var,
+                    &mut Vec::new(),
+                    &mut jumps_to_else,
+                );
+
+                // Pattern bindings live only in the then block
+                self.enter_scope();
+                self.compile_pattern_bindings(pattern, &scrutinee_var, &mut Vec::new());
+                self.compile_block(then_block)?;
+                self.exit_scope();
+
+                let jump_over_else = else_block
+                    .as_ref()
+                    .map(|_| self.bytecode.emit(Instruction::Jump(0)));
+                let else_start = self.bytecode.current_address();
+                for jump in jumps_to_else {
+                    self.bytecode.patch_jump(jump, else_start);
+                }
+                if let (Some(else_blk), Some(jump_over_else)) = (else_block, jump_over_else) {
+                    self.compile_block(else_blk)?;
+                    let end = self.bytecode.current_address();
+                    self.bytecode.patch_jump(jump_over_else, end);
+                }
+
+                Ok(())
+            }
+
+            // while let re-evaluates the value each iteration and stops at the
+            // first value that doesn't match the pattern
+            Statement::WhileLet {
+                label,
+                pattern,
+                value,
+                body,
+                ..
+            } => {
+                let loop_start = self.bytecode.current_address();
+                self.push_loop(label);
+
+                let scrutinee_var = format!("__while_let_scrutinee_{}", loop_start);
+                self.compile_expression(value)?;
+                self.bytecode
+                    .emit(Instruction::StoreVar(scrutinee_var.clone()));
+                let mut jumps_to_end = Vec::new();
+                self.compile_pattern_test(
+                    pattern,
+                    &scrutinee_var,
+                    &mut Vec::new(),
+                    &mut jumps_to_end,
+                );
+
+                self.enter_scope();
+                self.compile_pattern_bindings(pattern, &scrutinee_var, &mut Vec::new());
                 self.compile_block(body)?;
-                self.bytecode.emit(Instruction::ExitScope);
+                self.exit_scope();
 
-                // Jump back to start
                 self.bytecode.emit(Instruction::Jump(loop_start));
 
-                // Patch jump to end
                 let loop_end = self.bytecode.current_address();
-                self.bytecode.patch_jump(jump_to_end, loop_end);
-
-                // Patch any break statements
-                let breaks = self.loop_ends.pop().unwrap();
-                for addr in breaks {
-                    self.bytecode.patch_jump(addr, loop_end);
+                for jump in jumps_to_end {
+                    self.bytecode.patch_jump(jump, loop_end);
                 }
-                self.loop_starts.pop();
+                self.pop_loop(loop_start, loop_end);
 
                 Ok(())
             }
 
             Statement::For {
+                label,
                 variable,
                 start,
                 end,
                 inclusive,
+                step,
+                reverse,
                 body,
                 ..
             } => {
-                self.bytecode.emit(Instruction::EnterScope);
+                self.enter_scope();
+
+                // Hidden bound and step variables are per nesting level, so an
+                // inner loop doesn't overwrite the outer loop's
+                let depth = self.loops.len();
+                let end_var = format!("__loop_end{}", depth);
+                let step_var = format!("__loop_step{}", depth);
+
+                // A reversed range runs from its last value down to its start, inclusive
+                let range_inclusive = *inclusive;
+                let (first, bound, inclusive) = if *reverse {
+                    (end, start, true)
+                } else {
+                    (start, end, *inclusive)
+                };
 
                 // Initialize loop variable
-                self.compile_expression(start)?;
+                self.compile_expression(first)?;
+                if *reverse && !range_inclusive {
+                    self.bytecode.emit(Instruction::LoadConst(Value::Int(1)));
+                    self.bytecode.emit(Instruction::Sub);
+                }
                 self.bytecode.emit(Instruction::StoreVar(variable.clone()));
 
                 // Compile end value
-                self.compile_expression(end)?;
-                self.bytecode
-                    .emit(Instruction::StoreVar("__loop_end".to_string()));
+                self.compile_expression(bound)?;
+                self.bytecode.emit(Instruction::StoreVar(end_var.clone()));
+
+                // The step's sign picks the comparison; a literal step (or none)
+                // decides it here, anything else is checked on every iteration
+                let sign = match step {
+                    Some(step) => step.constant_int().map(i64::signum),
+                    None => Some(1),
+                };
+                let sign = sign.map(|sign| if *reverse { -sign } else { sign });
+                match step {
+                    Some(step) => self.compile_expression(step)?,
+                    None => {
+                        self.bytecode.emit(Instruction::LoadConst(Value::Int(1)));
+                    }
+                }
+                if *reverse {
+                    self.bytecode.emit(Instruction::Neg);
+                }
+                self.bytecode.emit(Instruction::StoreVar(step_var.clone()));
 
                 let loop_start = self.bytecode.current_address();
-                self.loop_starts.push(loop_start);
-                self.loop_ends.push(Vec::new());
-
-                // Check condition: variable < end (or <= for inclusive)
-                self.bytecode.emit(Instruction::LoadVar(variable.clone()));
-                self.bytecode
-                    .emit(Instruction::LoadVar("__loop_end".to_string()));
-                if *inclusive {
-                    self.bytecode.emit(Instruction::Lte);
-                } else {
-                    self.bytecode.emit(Instruction::Lt);
+                self.push_loop(label);
+
+                // Check condition: variable < end counting up, > end counting
+                // down (<= and >= for inclusive ranges). A zero step runs no iterations
+                let compare = |bytecode: &mut Bytecode, up: bool| {
+                    bytecode.emit(Instruction::LoadVar(variable.clone()));
+                    bytecode.emit(Instruction::LoadVar(end_var.clone()));
+                    bytecode.emit(match (up, inclusive) {
+                        (true, false) => Instruction::Lt,
+                        (true, true) => Instruction::Lte,
+                        (false, false) => Instruction::Gt,
+                        (false, true) => Instruction::Gte,
+                    });
+                };
+                match sign {
+                    Some(1) => compare(&mut self.bytecode, true),
+                    Some(-1) => compare(&mut self.bytecode, false),
+                    Some(_) => {
+                        self.bytecode
+                            .emit(Instruction::LoadConst(Value::Bool(false)));
+                    }
+                    None => {
+                        let sign_test = |bytecode: &mut Bytecode, op: Instruction| {
+                            bytecode.emit(Instruction::LoadVar(step_var.clone()));
+                            bytecode.emit(Instruction::LoadConst(Value::Int(0)));
+                            bytecode.emit(op);
+                            bytecode.emit(Instruction::JumpIfFalse(0))
+                        };
+                        let not_up = sign_test(&mut self.bytecode, Instruction::Gt);
+                        compare(&mut self.bytecode, true);
+                        let up_done = self.bytecode.emit(Instruction::Jump(0));
+                        let down = self.bytecode.current_address();
+                        self.bytecode.patch_jump(not_up, down);
+                        let not_down = sign_test(&mut self.bytecode, Instruction::Lt);
+                        compare(&mut self.bytecode, false);
+                        let down_done = self.bytecode.emit(Instruction::Jump(0));
+                        let zero = self.bytecode.current_address();
+                        self.bytecode.patch_jump(not_down, zero);
+                        self.bytecode
+                            .emit(Instruction::LoadConst(Value::Bool(false)));
+                        let decided = self.bytecode.current_address();
+                        self.bytecode.patch_jump(up_done, decided);
+                        self.bytecode.patch_jump(down_done, decided);
+                    }
                 }
 
                 let jump_to_end = self.bytecode.emit(Instruction::JumpIfFalse(0));
@@ -429,9 +1369,10 @@ impl Compiler {
                 // Compile body
                 self.compile_block(body)?;
 
-                // Increment loop variable
+                // Advance loop variable by the step (continue jumps here)
+                let loop_next = self.bytecode.current_address();
                 self.bytecode.emit(Instruction::LoadVar(variable.clone()));
-                self.bytecode.emit(Instruction::LoadConst(Value::Int(1)));
+                self.bytecode.emit(Instruction::LoadVar(step_var));
                 self.bytecode.emit(Instruction::Add);
                 self.bytecode.emit(Instruction::StoreVar(variable.clone()));
 
@@ -442,21 +1383,44 @@ impl Compiler {
                 let loop_end = self.bytecode.current_address();
                 self.bytecode.patch_jump(jump_to_end, loop_end);
 
-                let breaks = self.loop_ends.pop().unwrap();
-                for addr in breaks {
-                    self.bytecode.patch_jump(addr, loop_end);
-                }
-                self.loop_starts.pop();
+                self.pop_loop(loop_next, loop_end);
+
+                self.exit_scope();
 
-                self.bytecode.emit(Instruction::ExitScope);
+                Ok(())
+            }
 
+            Statement::Break { label, span } | Statement::Continue { label, span } => {
+                let is_break = matches!(stmt, Statement::Break { .. });
+                let index = self.find_loop(label.as_deref()).ok_or_else(|| {
+                    let keyword = if is_break { "break" } else { "continue" };
+                    ZyraError::new(
+                        "CompileError",
+                        &match label {
+                            Some(name) => format!("Undeclared loop label '{}", name),
+                            None => format!("'{}' outside of a loop", keyword),
+                        },
+                        Some(SourceLocation::from_span(span)),
+                    )
+                })?;
+
+                // Leave every scope entered inside the target loop before jumping
+                for _ in self.loops[index].scope_depth..self.scope_depth {
+                    self.bytecode.emit(Instruction::ExitScope);
+                }
+                let jump = self.bytecode.emit(Instruction::Jump(0));
+                if is_break {
+                    self.loops[index].breaks.push(jump);
+                } else {
+                    self.loops[index].continues.push(jump);
+                }
                 Ok(())
             }
 
             Statement::Block(block) => {
-                self.bytecode.emit(Instruction::EnterScope);
+                self.enter_scope();
                 self.compile_block(block)?;
-                self.bytecode.emit(Instruction::ExitScope);
+                self.exit_scope();
                 Ok(())
             }
 
@@ -479,10 +1443,27 @@ impl Compiler {
                 ..
             } => {
                 // Compile impl methods as functions with namespaced names
+                self.impl_type = Some(target_type.clone());
+                let is_own_type = self.bytecode.struct_id(target_type).is_some()
+                    || self
+                        .bytecode
+                        .enums
+                        .iter()
+                        .any(|(name, _)| name == target_type);
+                if !is_own_type {
+                    self.current_module = self
+                        .imported
+                        .find_type(target_type)
+                        .map(|item| item.module.clone());
+                }
                 for method in methods {
                     // Extract function name and compile with prefixed name
                     if let Statement::Function {
-                        name, params, body, ..
+                        name,
+                        params,
+                        return_type,
+                        body,
+                        ..
                     } = method.as_ref()
                     {
                         // Generate method name based on impl type:
@@ -515,13 +1496,62 @@ impl Compiler {
                             }));
 
                         if is_used {
-                            self.compile_function(&prefixed_name, params, body)?;
+                            self.compile_function(
+                                &prefixed_name,
+                                params,
+                                return_type.as_ref(),
+                                body,
+                            )?;
                         }
                         // If not used, skip compilation (dead code elimination)
                     } else {
                         self.compile_statement(method)?;
                     }
                 }
+
+                // Default methods the impl doesn't override are compiled once per
+                // implementing type, so dispatch is the same as for overrides
+                if let Some(trait_n) = trait_name {
+                    let overridden: HashSet<&str> = methods
+                        .iter()
+                        .filter_map(|m| match m.as_ref() {
+                            Statement::Function { name, .. } => Some(name.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    let defaults: Vec<TraitMethod> = self
+                        .traits
+                        .get(trait_n)
+                        .map(|trait_methods| {
+                            trait_methods
+                                .iter()
+                                .filter(|m| {
+                                    m.default_impl.is_some()
+                                        && !overridden.contains(m.name.as_str())
+                                })
+                                .cloned()
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    for default in defaults {
+                        let prefixed_name =
+                            format!("<{} as {}>::{}", trait_n, target_type, default.name);
+                        if self.used_methods.contains(&default.name)
+                            || self.used_methods.contains(&prefixed_name)
+                        {
+                            if let Some(body) = &default.default_impl {
+                                self.compile_function(
+                                    &prefixed_name,
+                                    &default.params,
+                                    default.return_type.as_ref(),
+                                    body,
+                                )?;
+                            }
+                        }
+                    }
+                }
+                self.impl_type = None;
+                self.current_module = None;
                 Ok(())
             }
 
@@ -545,10 +1575,42 @@ impl Compiler {
     }
 
     fn compile_expression(&mut self, expr: &Expression) -> ZyraResult<()> {
-        match expr {
-            Expression::Int { value, .. } => {
+        let hint = self.literal_hint.take();
+        self.with_line(expr.span().line, |c| c.compile_expression_kind(expr, hint))
+    }
+
+    /// Compile an expression whose integer literals take the type `hint`, so
+    /// `add(10)` passes a u8 to a u8 parameter just like the analyzer typed it
+    fn compile_with_hint(&mut self, expr: &Expression, hint: Option<&Type>) -> ZyraResult<()> {
+        self.literal_hint = hint.cloned();
+        self.compile_expression(expr)
+    }
+
+    /// Compile an operand of arithmetic whose result has the declared type
+    /// `hint`. A plain int operand, such as a variable holding an unannotated
+    /// literal, is narrowed to that type so `let w: i32 = x + x` overflows at
+    /// the i32 bounds the analyzer typed it with.
+    fn compile_operand(&mut self, expr: &Expression, hint: Option<&Type>) -> ZyraResult<()> {
+        self.compile_with_hint(expr, hint)?;
+        if let Some(ty) = hint.filter(|ty| Self::sized_int(0, ty).is_some()) {
+            if !matches!(expr, Expression::Int { .. }) {
+                let type_name = ZyraType::from_ast_type(ty).display_name();
                 self.bytecode
-                    .emit(Instruction::LoadConst(Value::Int(*value)));
+                    .emit(Instruction::Narrow(type_name, self.overflow));
+            }
+        }
+        Ok(())
+    }
+
+    fn compile_expression_kind(&mut self, expr: &Expression, hint: Option<Type>) -> ZyraResult<()> {
+        match expr {
+            Expression::Int { value, suffix, .. } => {
+                let constant = suffix
+                    .as_ref()
+                    .or(hint.as_ref())
+                    .and_then(|ty| Self::sized_int(*value, ty))
+                    .unwrap_or(Value::Int(*value));
+                self.bytecode.emit(Instruction::LoadConst(constant));
                 Ok(())
             }
 
@@ -577,7 +1639,19 @@ impl Compiler {
             }
 
             Expression::Identifier { name, .. } => {
-                self.bytecode.emit(Instruction::LoadVar(name.clone()));
+                // A bare struct name evaluates to its type name (e.g. validate(data, Player))
+                if name == "None" {
+                    self.bytecode.emit(Instruction::LoadConst(Value::None));
+                } else if self.bytecode.structs.contains_key(name) {
+                    self.bytecode
+                        .emit(Instruction::LoadConst(Value::String(name.clone())));
+                } else if let Some(value) = self.constant(name) {
+                    self.bytecode.emit(Instruction::LoadConst(value.clone()));
+                } else if let Some(global) = self.global_name(name) {
+                    self.bytecode.emit(Instruction::LoadGlobal(global));
+                } else {
+                    self.bytecode.emit(Instruction::LoadVar(name.clone()));
+                }
                 Ok(())
             }
 
@@ -587,23 +1661,50 @@ impl Compiler {
                 right,
                 ..
             } => {
-                self.compile_expression(left)?;
-                self.compile_expression(right)?;
-
-                let instruction = match operator {
-                    BinaryOp::Add => Instruction::Add,
-                    BinaryOp::Subtract => Instruction::Sub,
-                    BinaryOp::Multiply => Instruction::Mul,
-                    BinaryOp::Divide => Instruction::Div,
-                    BinaryOp::Modulo => Instruction::Mod,
-                    BinaryOp::Equal => Instruction::Eq,
-                    BinaryOp::NotEqual => Instruction::Neq,
-                    BinaryOp::Less => Instruction::Lt,
-                    BinaryOp::LessEqual => Instruction::Lte,
-                    BinaryOp::Greater => Instruction::Gt,
-                    BinaryOp::GreaterEqual => Instruction::Gte,
-                    BinaryOp::And => Instruction::And,
-                    BinaryOp::Or => Instruction::Or,
+                // Arithmetic passes the type its result should have on to its
+                // operands; the shift amount's type is independent
+                let (left_hint, right_hint) = match operator {
+                    BinaryOp::Add
+                    | BinaryOp::Subtract
+                    | BinaryOp::Multiply
+                    | BinaryOp::Divide
+                    | BinaryOp::Modulo
+                    | BinaryOp::BitAnd
+                    | BinaryOp::BitOr
+                    | BinaryOp::BitXor => (hint.as_ref(), hint.as_ref()),
+                    BinaryOp::ShiftLeft | BinaryOp::ShiftRight => (hint.as_ref(), None),
+                    _ => (None, None),
+                };
+                self.compile_operand(left, left_hint)?;
+                self.compile_operand(right, right_hint)?;
+
+                let instruction = match (operator, self.overflow) {
+                    (BinaryOp::Add, OverflowMode::Error) => Instruction::CheckedAdd,
+                    (BinaryOp::Subtract, OverflowMode::Error) => Instruction::CheckedSub,
+                    (BinaryOp::Multiply, OverflowMode::Error) => Instruction::CheckedMul,
+                    (BinaryOp::Add, OverflowMode::Saturate) => Instruction::SaturatingAdd,
+                    (BinaryOp::Subtract, OverflowMode::Saturate) => Instruction::SaturatingSub,
+                    (BinaryOp::Multiply, OverflowMode::Saturate) => Instruction::SaturatingMul,
+                    (operator, _) => match operator {
+                        BinaryOp::Add => Instruction::Add,
+                        BinaryOp::Subtract => Instruction::Sub,
+                        BinaryOp::Multiply => Instruction::Mul,
+                        BinaryOp::Divide => Instruction::Div,
+                        BinaryOp::Modulo => Instruction::Mod,
+                        BinaryOp::Equal => Instruction::Eq,
+                        BinaryOp::NotEqual => Instruction::Neq,
+                        BinaryOp::Less => Instruction::Lt,
+                        BinaryOp::LessEqual => Instruction::Lte,
+                        BinaryOp::Greater => Instruction::Gt,
+                        BinaryOp::GreaterEqual => Instruction::Gte,
+                        BinaryOp::And => Instruction::And,
+                        BinaryOp::Or => Instruction::Or,
+                        BinaryOp::BitAnd => Instruction::BitAnd,
+                        BinaryOp::BitOr => Instruction::BitOr,
+                        BinaryOp::BitXor => Instruction::BitXor,
+                        BinaryOp::ShiftLeft => Instruction::Shl,
+                        BinaryOp::ShiftRight => Instruction::Shr,
+                    },
                 };
 
                 self.bytecode.emit(instruction);
@@ -613,7 +1714,21 @@ impl Compiler {
             Expression::Unary {
                 operator, operand, ..
             } => {
-                self.compile_expression(operand)?;
+                // `-128i8` is a constant; 128 on its own doesn't fit an i8
+                if let (UnaryOp::Negate, Expression::Int { value, suffix, .. }) =
+                    (operator, operand.as_ref())
+                {
+                    if let Some(constant) = suffix
+                        .as_ref()
+                        .or(hint.as_ref())
+                        .and_then(|ty| Self::sized_int(value.wrapping_neg(), ty))
+                    {
+                        self.bytecode.emit(Instruction::LoadConst(constant));
+                        return Ok(());
+                    }
+                }
+                let operand_hint = hint.as_ref().filter(|_| *operator == UnaryOp::Negate);
+                self.compile_with_hint(operand, operand_hint)?;
 
                 let instruction = match operator {
                     UnaryOp::Negate => Instruction::Neg,
@@ -624,113 +1739,43 @@ impl Compiler {
                 Ok(())
             }
 
-            Expression::Assignment { target, value, .. } => {
-                self.compile_expression(value)?;
-
-                match target.as_ref() {
-                    Expression::Identifier { name, .. } => {
-                        self.bytecode.emit(Instruction::StoreVar(name.clone()));
-                    }
-                    Expression::FieldAccess { object, field, .. } => {
-                        self.compile_expression(object)?;
-                        self.bytecode.emit(Instruction::SetField(field.clone()));
-                    }
-                    Expression::Index { object, index, .. } => {
-                        // For nested index assignment like `matrix[0][0] = 10`:
-                        // We need to:
-                        // 1. Collect all indices from innermost to outermost
-                        // 2. Load the root variable
-                        // 3. For each level except the last: GetIndex to navigate deeper
-                        // 4. SetIndex with the value at the deepest level
-                        // 5. Propagate changes back up by SetIndex at each level
-                        // 6. StoreVar back to root
-
-                        // Collect indices from outermost to innermost
-                        fn collect_indices(
-                            expr: &Expression,
-                            indices: &mut Vec<Expression>,
-                        ) -> Option<String> {
-                            match expr {
-                                Expression::Identifier { name, .. } => Some(name.clone()),
-                                Expression::Index { object, index, .. } => {
-                                    indices.push((**index).clone());
-                                    collect_indices(object, indices)
-                                }
-                                _ => None,
-                            }
-                        }
-
-                        let mut indices = vec![(**index).clone()];
-                        let root_name = collect_indices(object, &mut indices);
-
-                        // Reverse to get from root to deepest
-                        indices.reverse();
-
-                        if let Some(root) = root_name {
-                            // Value is already on stack (compiled before target)
-
-                            if indices.len() == 1 {
-                                // Simple case: arr[i] = value
-                                // Stack: [value]
-                                // Need: [value, arr, i] for SetIndex
-                                self.bytecode.emit(Instruction::LoadVar(root.clone()));
-                                self.compile_expression(&indices[0])?;
-                                self.bytecode.emit(Instruction::SetIndex);
-                                self.bytecode.emit(Instruction::StoreVar(root));
-                            } else {
-                                // Nested case: matrix[i][j] = value (or deeper)
-                                // Stack: [value]
-
-                                // Load root and navigate to second-to-last level
-                                self.bytecode.emit(Instruction::LoadVar(root.clone()));
-                                for idx_expr in &indices[..indices.len() - 1] {
-                                    self.compile_expression(idx_expr)?;
-                                    self.bytecode.emit(Instruction::GetIndex);
-                                }
-                                // Stack: [value, inner_array]
-
-                                // Now set at the deepest level
-                                // We need: [value, inner_array, deepest_index]
-                                // But value is at bottom, inner_array is at top
-                                // We need to reorder: compile index, swap, then SetIndex
-                                self.compile_expression(&indices[indices.len() - 1])?;
-                                // Stack: [value, inner_array, deepest_index]
-                                // But SetIndex expects [value, obj, idx] in order: pop idx, pop obj, pop value
-                                // Our stack: bottom->[value], [inner_array], [deepest_index]<-top
-                                // This is: idx at top, obj below, value at bottom - correct order!
-                                self.bytecode.emit(Instruction::SetIndex);
-                                // Stack: [modified_inner_array]
-
-                                // Now propagate back up - for each level from second-deepest back to root
-                                // We need to: load parent, swap with modified child, set child at index, store
-                                // This is complex - for now let's handle 2-level nesting
-                                // For matrix[i][j], after modifying row, we need to set it back
-
-                                // Load root again, set the modified inner at first index
-                                self.bytecode.emit(Instruction::LoadVar(root.clone()));
-                                // Stack: [modified_inner, matrix]
-                                // We need [modified_inner, matrix, first_index] then swap/reorder for SetIndex
-                                self.compile_expression(&indices[0])?;
-                                // Stack: [modified_inner, matrix, first_index]
-                                // SetIndex pops: idx, obj, value -> gives us modified obj
-                                // But our stack has modified_inner at bottom, not at "value" position
-
-                                // We need to restructure: SetIndex wants [value_to_set, container, index]
-                                // We have [modified_inner, matrix, first_index]
-                                // This is already correct order!
-                                self.bytecode.emit(Instruction::SetIndex);
-                                // Stack: [modified_matrix]
-
-                                self.bytecode.emit(Instruction::StoreVar(root));
-                            }
-                        }
+            Expression::Assignment {
+                target,
+                operator,
+                value,
+                span,
+            } => {
+                if !matches!(
+                    target.ungrouped(),
+                    Expression::Identifier { .. }
+                        | Expression::FieldAccess { .. }
+                        | Expression::Index { .. }
+                ) {
+                    return Err(ZyraError::runtime_error("Invalid assignment target"));
+                }
+                match operator {
+                    Some(op) => {
+                        // `place op= value` loads and stores the place, so what
+                        // could have side effects in it is evaluated first, once
+                        let place = self.hoist_place(target)?;
+                        let place_type = self.place_type(&place);
+                        self.compile_with_hint(
+                            &Expression::Binary {
+                                left: Box::new(place.clone()),
+                                operator: *op,
+                                right: value.clone(),
+                                span: *span,
+                            },
+                            place_type.as_ref(),
+                        )?;
+                        self.compile_place_store(&place)
                     }
-                    _ => {
-                        return Err(ZyraError::runtime_error("Invalid assignment target"));
+                    None => {
+                        let place_type = self.place_type(target);
+                        self.compile_with_hint(value, place_type.as_ref())?;
+                        self.compile_place_store(target)
                     }
                 }
-
-                Ok(())
             }
 
             Expression::Call {
@@ -738,27 +1783,55 @@ impl Compiler {
             } => {
                 // Get function name and handle method calls specially
                 match callee.as_ref() {
+                    Expression::Identifier { name, .. }
+                        if self.compile_mem_intrinsic(name, arguments)? => {}
                     Expression::Identifier { name, .. } => {
+                        // Inside a module, bare names call the module's own functions
+                        let target = self
+                            .current_module
+                            .as_ref()
+                            .filter(|_| !name.contains("::"))
+                            .and_then(|module| self.imported.sibling(module, name))
+                            .unwrap_or_else(|| name.clone());
                         // Regular function call: compile arguments then call
-                        for arg in arguments {
-                            self.compile_expression(arg)?;
+                        let params = self.signatures.get(&target).cloned().unwrap_or_default();
+                        for (i, arg) in arguments.iter().enumerate() {
+                            let param_type = params.get(i).map(|p| &p.param_type);
+                            self.compile_with_hint(arg, param_type)?;
                         }
                         self.bytecode
-                            .emit(Instruction::Call(name.clone(), arguments.len()));
+                            .emit(Instruction::Call(target, arguments.len()));
                     }
                     Expression::FieldAccess { object, field, .. } => {
                         // Method call: push receiver FIRST, then arguments
                         // VM expects: [receiver, arg1, arg2, ...] on stack
+                        let params: Vec<Parameter> = self
+                            .static_struct_type(object)
+                            .and_then(|ty| self.signatures.get(&format!("{}::{}", ty, field)))
+                            .map(|params| {
+                                params
+                                    .iter()
+                                    .filter(|p| !p.name.ends_with("self"))
+                                    .cloned()
+                                    .collect()
+                            })
+                            .unwrap_or_default();
                         self.compile_expression(object)?;
-                        for arg in arguments {
-                            self.compile_expression(arg)?;
+                        for (i, arg) in arguments.iter().enumerate() {
+                            let param_type = params.get(i).map(|p| &p.param_type);
+                            self.compile_with_hint(arg, param_type)?;
                         }
                         // Emit MethodCall with method name and arg count (not including receiver)
                         self.bytecode
                             .emit(Instruction::MethodCall(field.clone(), arguments.len()));
                     }
                     _ => {
-                        return Err(ZyraError::runtime_error("Invalid call target"));
+                        // Calling a computed value: make_adder(1)(2)
+                        self.compile_expression(callee)?;
+                        for arg in arguments {
+                            self.compile_expression(arg)?;
+                        }
+                        self.bytecode.emit(Instruction::CallValue(arguments.len()));
                     }
                 }
 
@@ -766,8 +1839,14 @@ impl Compiler {
             }
 
             Expression::FieldAccess { object, field, .. } => {
+                let slot = self.field_slot(object, field);
                 self.compile_expression(object)?;
-                self.bytecode.emit(Instruction::GetField(field.clone()));
+                match slot {
+                    Some((type_id, idx)) => {
+                        self.bytecode.emit(Instruction::GetFieldIdx(type_id, idx))
+                    }
+                    None => self.bytecode.emit(Instruction::GetField(field.clone())),
+                };
                 Ok(())
             }
 
@@ -780,22 +1859,66 @@ impl Compiler {
 
             Expression::List { elements, .. } => {
                 // Array literal [a, b, c] - compiles to Value::Array
+                let elem_hint = match hint {
+                    Some(Type::Array { elem, .. } | Type::Vec(elem) | Type::List(elem)) => {
+                        Some(*elem)
+                    }
+                    _ => None,
+                };
+                for elem in elements {
+                    self.compile_with_hint(elem, elem_hint.as_ref())?;
+                }
+                self.bytecode.emit(Instruction::MakeArray(elements.len()));
+                Ok(())
+            }
+
+            Expression::Tuple { elements, .. } => {
                 for elem in elements {
                     self.compile_expression(elem)?;
                 }
-                self.bytecode.emit(Instruction::MakeList(elements.len()));
+                self.bytecode.emit(Instruction::MakeTuple(elements.len()));
+                Ok(())
+            }
+
+            // pair.0 reads the element like an index
+            Expression::TupleIndex { object, index, .. } => {
+                self.compile_expression(object)?;
+                self.bytecode
+                    .emit(Instruction::LoadConst(Value::Int(*index as i64)));
+                self.bytecode.emit(Instruction::GetIndex);
                 Ok(())
             }
 
             Expression::VecLiteral { elements, .. } => {
                 // Vec literal vec[a, b, c] - compiles to Value::Vec
+                let elem_hint = match hint {
+                    Some(Type::Vec(elem) | Type::List(elem)) => Some(*elem),
+                    _ => None,
+                };
                 for elem in elements {
-                    self.compile_expression(elem)?;
+                    self.compile_with_hint(elem, elem_hint.as_ref())?;
                 }
                 self.bytecode.emit(Instruction::MakeVec(elements.len()));
                 Ok(())
             }
 
+            Expression::MapLiteral { entries, .. } => {
+                for (key, value) in entries {
+                    self.compile_expression(key)?;
+                    self.compile_expression(value)?;
+                }
+                self.bytecode.emit(Instruction::MakeMap(entries.len()));
+                Ok(())
+            }
+
+            Expression::SetLiteral { elements, .. } => {
+                for elem in elements {
+                    self.compile_expression(elem)?;
+                }
+                self.bytecode.emit(Instruction::MakeSet(elements.len()));
+                Ok(())
+            }
+
             Expression::Object { fields, .. } => {
                 for (name, value) in fields {
                     self.bytecode
@@ -814,10 +1937,11 @@ impl Compiler {
                 // Auto-dereference: references load the actual value
                 // The borrow checking is done at compile-time in semantic analysis
                 // At runtime, references behave like the value they point to
-                if let Expression::Identifier { name, .. } = value.as_ref() {
-                    self.bytecode.emit(Instruction::LoadVar(name.clone()));
-                } else {
-                    self.compile_expression(value)?;
+                match value.as_ref() {
+                    Expression::Identifier { name, .. } if self.constant(name).is_none() => {
+                        self.bytecode.emit(Instruction::LoadVar(name.clone()));
+                    }
+                    _ => self.compile_expression(value)?,
                 }
                 Ok(())
             }
@@ -835,7 +1959,7 @@ impl Compiler {
                 Ok(())
             }
 
-            Expression::Grouped { inner, .. } => self.compile_expression(inner),
+            Expression::Grouped { inner, .. } => self.compile_with_hint(inner, hint.as_ref()),
 
             // If expression - returns value from branch
             Expression::If {
@@ -878,6 +2002,29 @@ impl Compiler {
 
             // Struct instantiation: StructName { field: value, ... }
             Expression::StructInit { name, fields, .. } => {
+                // Registered structs store their fields in layout order
+                let slots: Option<Vec<u32>> = self.bytecode.struct_id(name).and_then(|id| {
+                    fields
+                        .iter()
+                        .map(|(field_name, _)| self.bytecode.field_index(id, field_name))
+                        .collect()
+                });
+                let declared = self.struct_fields.get(name).cloned().unwrap_or_default();
+                let field_type = |field: &str| {
+                    declared
+                        .iter()
+                        .find(|(name, _)| name == field)
+                        .map(|(_, ty)| ty.clone())
+                };
+                if let (Some(type_id), Some(slots)) = (self.bytecode.struct_id(name), slots) {
+                    for (field_name, field_value) in fields {
+                        self.compile_with_hint(field_value, field_type(field_name).as_ref())?;
+                    }
+                    self.bytecode
+                        .emit(Instruction::MakeStruct { type_id, slots });
+                    return Ok(());
+                }
+
                 // Create an Object value with _type field for struct name
                 // VM pops: value first, then key. So push: key first, then value
                 for (field_name, field_value) in fields {
@@ -899,14 +2046,43 @@ impl Compiler {
             }
 
             // Enum variant: EnumName::Variant or EnumName::Variant(data)
+            // A module's constant, `util::MAX`
+            Expression::EnumVariant { .. }
+                if expr
+                    .qualified_name()
+                    .is_some_and(|name| self.constants.contains_key(&name)) =>
+            {
+                let name = expr.qualified_name().unwrap_or_default();
+                self.bytecode
+                    .emit(Instruction::LoadConst(self.constants[&name].clone()));
+                Ok(())
+            }
+
             Expression::EnumVariant {
                 enum_name,
                 variant,
                 data,
                 ..
             } => {
-                // Create object with _type = "EnumName::Variant" and optional _data field
-                // Push variant info
+                if let Some((type_id, variant_id)) = self.bytecode.enum_tag(enum_name, variant) {
+                    match data {
+                        Some(data_expr) => {
+                            self.compile_expression(data_expr)?;
+                            self.bytecode
+                                .emit(Instruction::MakeEnum(type_id, variant_id));
+                        }
+                        None => {
+                            self.bytecode.emit(Instruction::LoadConst(Value::Enum {
+                                type_id,
+                                variant_id,
+                                payload: None,
+                            }));
+                        }
+                    }
+                    return Ok(());
+                }
+
+                // Unregistered enum: object with _type = "EnumName::Variant" and optional _data
                 self.bytecode
                     .emit(Instruction::LoadConst(Value::String("_type".to_string())));
                 self.bytecode
@@ -930,70 +2106,68 @@ impl Compiler {
             }
 
             // Match expression: match scrutinee { pattern => body, ... }
+            // Arms are tried in order. Each pattern test reloads the scrutinee and
+            // walks to the part it checks, so a failed test can jump to the next
+            // arm with nothing left on the stack
             Expression::Match {
-                scrutinee,
-                arms,
-                span,
+                scrutinee, arms, ..
             } => {
-                // Phase 1: Compile scrutinee - leaves value on stack
                 self.compile_expression(scrutinee)?;
-
-                // Store scrutinee in a temp variable for pattern matching
                 let scrutinee_var =
                     format!("__match_scrutinee_{}", self.bytecode.current_address());
                 self.bytecode
                     .emit(Instruction::StoreVar(scrutinee_var.clone()));
 
-                // Track jump addresses for patching
-                let mut end_jumps: Vec<usize> = Vec::new();
-
-                // Compile each arm as a chained conditional
-                for (_i, arm) in arms.iter().enumerate() {
-                    // Load scrutinee for pattern check
+                // Two or more string arms dispatch through a jump table
+                let string_arms = arms
+                    .iter()
+                    .filter(|arm| Self::string_pattern(&arm.pattern).is_some())
+                    .count();
+                let switch = if string_arms >= 2 {
                     self.bytecode
                         .emit(Instruction::LoadVar(scrutinee_var.clone()));
+                    Some(self.bytecode.emit(Instruction::Nop))
+                } else {
+                    None
+                };
 
-                    // Compile pattern matching check
-                    self.compile_pattern_check(&arm.pattern)?;
+                let mut end_jumps = Vec::new();
+                let mut arm_starts = Vec::new();
+                for arm in arms {
+                    arm_starts.push(self.bytecode.current_address());
+                    let mut fails = Vec::new();
+                    let mut path = Vec::new();
+                    self.compile_pattern_test(&arm.pattern, &scrutinee_var, &mut path, &mut fails);
 
-                    // If guard present, add guard check
+                    // Bindings come before the guard, which may use them
+                    self.compile_pattern_bindings(&arm.pattern, &scrutinee_var, &mut path);
                     if let Some(ref guard) = arm.guard {
-                        // Only check guard if pattern matched
-                        let skip_guard = self.bytecode.emit(Instruction::JumpIfFalse(0));
                         self.compile_expression(guard)?;
-                        // Combine pattern match and guard result (already on stack from guard)
-                        let guard_end = self.bytecode.current_address();
-                        self.bytecode.patch_jump(skip_guard, guard_end);
+                        fails.push(self.bytecode.emit(Instruction::JumpIfFalse(0)));
                     }
 
-                    // Jump to next arm if pattern doesn't match
-                    let jump_to_next = self.bytecode.emit(Instruction::JumpIfFalse(0));
-
-                    // Pattern matched - bind variables and compile body
-                    self.bytecode
-                        .emit(Instruction::LoadVar(scrutinee_var.clone()));
-                    self.compile_pattern_bindings(&arm.pattern)?;
                     self.compile_expression(&arm.body)?;
+                    end_jumps.push(self.bytecode.emit(Instruction::Jump(0)));
 
-                    // Jump to end after successful match
-                    let jump_to_end = self.bytecode.emit(Instruction::Jump(0));
-                    end_jumps.push(jump_to_end);
-
-                    // Patch jump to next arm
-                    let next_arm_addr = self.bytecode.current_address();
-                    self.bytecode.patch_jump(jump_to_next, next_arm_addr);
+                    let next_arm = self.bytecode.current_address();
+                    for jump in fails {
+                        self.bytecode.patch_jump(jump, next_arm);
+                    }
                 }
 
-                // If no arm matched, push error value (match exhaustiveness should prevent this)
+                // No arm matched (exhaustiveness checking should prevent this)
+                let no_match = self.bytecode.current_address();
                 self.bytecode.emit(Instruction::LoadConst(Value::None));
 
-                // Patch all end jumps to here
-                let end_addr = self.bytecode.current_address();
-                for jump in end_jumps {
-                    self.bytecode.patch_jump(jump, end_addr);
+                if let Some(switch) = switch {
+                    self.bytecode.instructions[switch] =
+                        Self::string_switch(arms, &arm_starts, no_match);
                 }
 
-                let _ = span; // Mark span as used
+                let end = self.bytecode.current_address();
+                for jump in end_jumps {
+                    self.bytecode.patch_jump(jump, end);
+                }
                 Ok(())
             }
 
@@ -1029,20 +2203,30 @@ impl Compiler {
                 // 2. Record function start
                 let func_start = self.bytecode.current_address();
 
-                // 3. Enter scope and store parameters
+                // 3. Enter scope and store parameters, first argument on top
+                // (break/continue cannot cross into the enclosing function's loops)
+                let outer_loops = std::mem::take(&mut self.loops);
+                let outer_types = std::mem::take(&mut self.local_types);
+                let outer_ints = std::mem::take(&mut self.local_ints);
+                let outer_return = self.return_type.take();
                 self.bytecode.emit(Instruction::EnterScope);
-
-                // Store params in reverse order (they're on stack from caller)
-                for param in params.iter().rev() {
+                for param in params {
                     self.bytecode
                         .emit(Instruction::StoreVar(param.name.clone()));
                 }
 
-                // 4. Compile closure body
-                self.compile_expression(body)?;
+                // 4. Compile closure body; a body without a trailing expression returns None
+                let result = self.compile_block(body);
+                self.loops = outer_loops;
+                self.local_types = outer_types;
+                self.local_ints = outer_ints;
+                self.return_type = outer_return;
+                result?;
+                if body.expression.is_none() {
+                    self.bytecode.emit(Instruction::LoadConst(Value::None));
+                }
 
-                // 5. Return (exit scope and return to caller)
-                self.bytecode.emit(Instruction::ExitScope);
+                // 5. Return to caller (Return drops the closure's scopes)
                 self.bytecode.emit(Instruction::Return);
 
                 // 6. Record function end
@@ -1062,10 +2246,17 @@ impl Compiler {
                     },
                 );
 
-                // 9. Emit MakeClosure instruction to create the closure value
+                // 9. Emit MakeClosure with every name the body uses besides its
+                // parameters; the VM captures those that are variables
+                let captures = body
+                    .identifiers()
+                    .into_iter()
+                    .filter(|name| params.iter().all(|p| &p.name != name))
+                    .collect();
                 self.bytecode.emit(Instruction::MakeClosure {
                     func_name,
                     param_count: params.len(),
+                    captures,
                 });
 
                 Ok(())
@@ -1073,121 +2264,287 @@ impl Compiler {
         }
     }
 
-    /// Compile pattern matching check - leaves bool on stack
-    fn compile_pattern_check(&mut self, pattern: &crate::parser::ast::Pattern) -> ZyraResult<()> {
+    /// The strings a match arm's pattern matches, when it's a string literal
+    /// or an or-pattern of them
+    fn string_pattern(pattern: &crate::parser::ast::Pattern) -> Option<Vec<&str>> {
+        use crate::parser::ast::{LiteralPattern, Pattern};
+        pattern
+            .alternatives()
+            .iter()
+            .map(|alternative| match alternative {
+                Pattern::Literal {
+                    value: LiteralPattern::String(s),
+                    ..
+                } => Some(s.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Jump table for a match on strings. A string jumps to the first arm that
+    /// could match it, whose own test then runs as usual, so arm order and
+    /// guards keep their meaning: the first arm with that literal, or an
+    /// earlier arm that isn't a string literal.
+    fn string_switch(arms: &[MatchArm], arm_starts: &[usize], no_match: usize) -> Instruction {
+        let first_other = arms
+            .iter()
+            .position(|arm| Self::string_pattern(&arm.pattern).is_none());
+        let default = first_other.map_or(no_match, |i| arm_starts[i]);
+
+        let mut cases: Vec<(u64, String, usize)> = Vec::new();
+        for (i, arm) in arms.iter().enumerate() {
+            for s in Self::string_pattern(&arm.pattern).unwrap_or_default() {
+                if cases.iter().all(|(_, case, _)| case != s) {
+                    let target = arm_starts[first_other.map_or(i, |other| other.min(i))];
+                    cases.push((bytecode::str_hash(s), s.to_string(), target));
+                }
+            }
+        }
+        cases.sort_by_key(|(hash, _, _)| *hash);
+        Instruction::SwitchStr { cases, default }
+    }
+
+    /// Push the part of a match scrutinee a pattern looks at
+    fn load_pattern_path(&mut self, scrutinee_var: &str, path: &[PatternStep]) {
+        self.bytecode
+            .emit(Instruction::LoadVar(scrutinee_var.to_string()));
+        for step in path {
+            match step {
+                PatternStep::Field(name) => {
+                    self.bytecode.emit(Instruction::GetField(name.clone()));
+                }
+                PatternStep::Index(i) => {
+                    self.bytecode.emit(Instruction::LoadConst(Value::Int(*i)));
+                    self.bytecode.emit(Instruction::GetIndex);
+                }
+            }
+        }
+    }
+
+    /// Emit the tests a value must pass to match a pattern, outermost first.
+    /// Each failing test jumps to an address collected in `fails`
+    fn compile_pattern_test(
+        &mut self,
+        pattern: &crate::parser::ast::Pattern,
+        scrutinee_var: &str,
+        path: &mut Vec<PatternStep>,
+        fails: &mut Vec<usize>,
+    ) {
         use crate::parser::ast::Pattern;
         match pattern {
-            Pattern::Wildcard { .. } => {
-                // Wildcard always matches
-                self.bytecode.emit(Instruction::Pop); // Remove scrutinee
-                self.bytecode
-                    .emit(Instruction::LoadConst(Value::Bool(true)));
+            // Bindings and wildcards match anything
+            Pattern::Wildcard { .. } | Pattern::Identifier { .. } | Pattern::RefBinding { .. } => {
+                return
             }
-            Pattern::Identifier { .. } => {
-                // Simple binding always matches
-                self.bytecode.emit(Instruction::Pop);
+            Pattern::Literal { value, .. } => {
+                self.load_pattern_path(scrutinee_var, path);
                 self.bytecode
-                    .emit(Instruction::LoadConst(Value::Bool(true)));
+                    .emit(Instruction::LoadConst(Self::literal_pattern_value(value)));
+                self.bytecode.emit(Instruction::Eq);
             }
-            Pattern::RefBinding { .. } => {
-                // Ref binding always matches
-                self.bytecode.emit(Instruction::Pop);
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+                ..
+            } => {
+                self.load_pattern_path(scrutinee_var, path);
                 self.bytecode
-                    .emit(Instruction::LoadConst(Value::Bool(true)));
+                    .emit(Instruction::LoadConst(Self::literal_pattern_value(start)));
+                self.bytecode.emit(Instruction::Gte);
+                fails.push(self.bytecode.emit(Instruction::JumpIfFalse(0)));
+                self.load_pattern_path(scrutinee_var, path);
+                self.bytecode
+                    .emit(Instruction::LoadConst(Self::literal_pattern_value(end)));
+                self.bytecode.emit(if *inclusive {
+                    Instruction::Lte
+                } else {
+                    Instruction::Lt
+                });
             }
-            Pattern::Literal { value, .. } => {
-                // Compare with literal
-                use crate::parser::ast::LiteralPattern;
-                match value {
-                    LiteralPattern::Int(n) => {
-                        self.bytecode.emit(Instruction::LoadConst(Value::Int(*n)));
-                    }
-                    LiteralPattern::Float(f) => {
-                        self.bytecode.emit(Instruction::LoadConst(Value::Float(*f)));
-                    }
-                    LiteralPattern::Bool(b) => {
-                        self.bytecode.emit(Instruction::LoadConst(Value::Bool(*b)));
-                    }
-                    LiteralPattern::Char(c) => {
-                        self.bytecode.emit(Instruction::LoadConst(Value::Char(*c)));
-                    }
-                    LiteralPattern::String(s) => {
-                        self.bytecode
-                            .emit(Instruction::LoadConst(Value::String(s.clone())));
+            // Each alternative but the last jumps on to the next when it fails
+            Pattern::Or { alternatives, .. } => {
+                let mut matched = Vec::new();
+                let (last, rest) = alternatives.split_last().expect("or-pattern alternatives");
+                for alternative in rest {
+                    let mut alternative_fails = Vec::new();
+                    self.compile_pattern_test(
+                        alternative,
+                        scrutinee_var,
+                        path,
+                        &mut alternative_fails,
+                    );
+                    matched.push(self.bytecode.emit(Instruction::Jump(0)));
+                    let next = self.bytecode.current_address();
+                    for jump in alternative_fails {
+                        self.bytecode.patch_jump(jump, next);
                     }
                 }
-                self.bytecode.emit(Instruction::Eq);
+                self.compile_pattern_test(last, scrutinee_var, path, fails);
+                let end = self.bytecode.current_address();
+                for jump in matched {
+                    self.bytecode.patch_jump(jump, end);
+                }
+                return;
             }
-            Pattern::Variant { variant, inner, .. } => {
-                // Check if scrutinee._type ends with variant name
-                self.bytecode
-                    .emit(Instruction::GetField("_type".to_string()));
-                self.bytecode
-                    .emit(Instruction::LoadConst(Value::String(variant.clone())));
-                self.bytecode.emit(Instruction::StrContains);
-                // TODO: Check inner pattern if present
-                let _ = inner;
+            Pattern::Variant {
+                enum_name,
+                variant,
+                inner,
+                ..
+            } => {
+                self.load_pattern_path(scrutinee_var, path);
+                if let Some((type_id, variant_id)) = self.variant_tag(enum_name.as_deref(), variant)
+                {
+                    // Registered enums compare integer tags
+                    self.bytecode
+                        .emit(Instruction::IsVariant(type_id, variant_id));
+                } else {
+                    // Option, Result and stdlib enums carry a "Enum::Variant" tag
+                    self.bytecode
+                        .emit(Instruction::GetField("_type".to_string()));
+                    self.bytecode
+                        .emit(Instruction::LoadConst(Value::String(format!(
+                            "::{}",
+                            variant
+                        ))));
+                    self.bytecode.emit(Instruction::StrContains);
+                }
+                fails.push(self.bytecode.emit(Instruction::JumpIfFalse(0)));
+                if let Some(inner) = inner {
+                    path.push(PatternStep::Field("_data".to_string()));
+                    self.compile_pattern_test(inner, scrutinee_var, path, fails);
+                    path.pop();
+                }
+                return;
             }
-            Pattern::Struct { type_name, .. } => {
-                // Check if scrutinee._type matches struct type
+            Pattern::Struct {
+                type_name, fields, ..
+            } => {
+                self.load_pattern_path(scrutinee_var, path);
                 self.bytecode
                     .emit(Instruction::GetField("_type".to_string()));
                 self.bytecode
                     .emit(Instruction::LoadConst(Value::String(type_name.clone())));
                 self.bytecode.emit(Instruction::Eq);
+                fails.push(self.bytecode.emit(Instruction::JumpIfFalse(0)));
+                for field in fields {
+                    path.push(PatternStep::Field(field.field_name.clone()));
+                    self.compile_pattern_test(&field.pattern, scrutinee_var, path, fails);
+                    path.pop();
+                }
+                return;
             }
-            Pattern::Tuple { .. } => {
-                // TODO: Tuple pattern matching
-                self.bytecode.emit(Instruction::Pop);
+            Pattern::Tuple { elements, .. } => {
+                self.load_pattern_path(scrutinee_var, path);
+                self.bytecode.emit(Instruction::Call("len".to_string(), 1));
                 self.bytecode
-                    .emit(Instruction::LoadConst(Value::Bool(true)));
+                    .emit(Instruction::LoadConst(Value::Int(elements.len() as i64)));
+                self.bytecode.emit(Instruction::Eq);
+                fails.push(self.bytecode.emit(Instruction::JumpIfFalse(0)));
+                for (i, element) in elements.iter().enumerate() {
+                    path.push(PatternStep::Index(i as i64));
+                    self.compile_pattern_test(element, scrutinee_var, path, fails);
+                    path.pop();
+                }
+                return;
             }
         }
-        Ok(())
+        fails.push(self.bytecode.emit(Instruction::JumpIfFalse(0)));
+    }
+
+    /// The constant a literal pattern compares against
+    fn literal_pattern_value(value: &crate::parser::ast::LiteralPattern) -> Value {
+        use crate::parser::ast::LiteralPattern;
+        match value {
+            LiteralPattern::Int(n) => Value::Int(*n),
+            LiteralPattern::Float(f) => Value::Float(*f),
+            LiteralPattern::Bool(b) => Value::Bool(*b),
+            LiteralPattern::Char(c) => Value::Char(*c),
+            LiteralPattern::String(s) => Value::String(s.clone()),
+        }
     }
 
-    /// Compile pattern variable bindings
+    /// Tags for a variant pattern; an unqualified variant must name exactly one enum
+    fn variant_tag(&self, enum_name: Option<&str>, variant: &str) -> Option<(u32, u32)> {
+        if let Some(enum_name) = enum_name {
+            return self.bytecode.enum_tag(enum_name, variant);
+        }
+        let mut owners = self
+            .bytecode
+            .enums
+            .iter()
+            .filter(|(_, variants)| variants.iter().any(|v| v == variant));
+        match (owners.next(), owners.next()) {
+            (Some((name, _)), None) => self.bytecode.enum_tag(name, variant),
+            _ => None,
+        }
+    }
+
+    /// Store every name a matched pattern binds
     fn compile_pattern_bindings(
         &mut self,
         pattern: &crate::parser::ast::Pattern,
-    ) -> ZyraResult<()> {
+        scrutinee_var: &str,
+        path: &mut Vec<PatternStep>,
+    ) {
         use crate::parser::ast::Pattern;
         match pattern {
-            Pattern::Identifier { name, .. } => {
-                // Bind the value to the variable name
-                self.bytecode.emit(Instruction::StoreVar(name.clone()));
-            }
-            Pattern::RefBinding { name, .. } => {
-                // Bind as reference (same as regular for now)
+            Pattern::Identifier { name, .. } | Pattern::RefBinding { name, .. } => {
+                self.load_pattern_path(scrutinee_var, path);
                 self.bytecode.emit(Instruction::StoreVar(name.clone()));
             }
             Pattern::Struct { fields, .. } => {
-                // Extract and bind each field
                 for field in fields {
-                    // Duplicate scrutinee for each field
-                    self.bytecode.emit(Instruction::Dup);
-                    self.bytecode
-                        .emit(Instruction::GetField(field.field_name.clone()));
-                    self.compile_pattern_bindings(&field.pattern)?;
+                    path.push(PatternStep::Field(field.field_name.clone()));
+                    self.compile_pattern_bindings(&field.pattern, scrutinee_var, path);
+                    path.pop();
                 }
-                self.bytecode.emit(Instruction::Pop); // Remove final scrutinee copy
             }
-            Pattern::Variant { inner, .. } => {
-                if let Some(inner_pattern) = inner {
-                    // Extract _data and bind
-                    self.bytecode
-                        .emit(Instruction::GetField("_data".to_string()));
-                    self.compile_pattern_bindings(inner_pattern)?;
-                } else {
-                    self.bytecode.emit(Instruction::Pop);
+            Pattern::Variant {
+                inner: Some(inner), ..
+            } => {
+                path.push(PatternStep::Field("_data".to_string()));
+                self.compile_pattern_bindings(inner, scrutinee_var, path);
+                path.pop();
+            }
+            Pattern::Tuple { elements, .. } => {
+                for (i, element) in elements.iter().enumerate() {
+                    path.push(PatternStep::Index(i as i64));
+                    self.compile_pattern_bindings(element, scrutinee_var, path);
+                    path.pop();
                 }
             }
-            _ => {
-                // Wildcard, Literal, Tuple - no bindings
-                self.bytecode.emit(Instruction::Pop);
+            // Bind from whichever alternative matched, testing them again in order
+            Pattern::Or { alternatives, .. } => {
+                let mut names = Vec::new();
+                pattern.bound_names(&mut names);
+                if names.is_empty() {
+                    return;
+                }
+                let mut bound = Vec::new();
+                let (last, rest) = alternatives.split_last().expect("or-pattern alternatives");
+                for alternative in rest {
+                    let mut fails = Vec::new();
+                    self.compile_pattern_test(alternative, scrutinee_var, path, &mut fails);
+                    self.compile_pattern_bindings(alternative, scrutinee_var, path);
+                    bound.push(self.bytecode.emit(Instruction::Jump(0)));
+                    let next = self.bytecode.current_address();
+                    for jump in fails {
+                        self.bytecode.patch_jump(jump, next);
+                    }
+                }
+                self.compile_pattern_bindings(last, scrutinee_var, path);
+                let end = self.bytecode.current_address();
+                for jump in bound {
+                    self.bytecode.patch_jump(jump, end);
+                }
             }
+            Pattern::Wildcard { .. }
+            | Pattern::Literal { .. }
+            | Pattern::Range { .. }
+            | Pattern::Variant { .. } => {}
         }
-        Ok(())
     }
 }
 
@@ -1196,3 +2553,108 @@ impl Default for Compiler {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    const SHAPES: &str = "
+        trait Shape {
+            func area(&self) -> i32;
+            func describe(&self) -> String { \"a shape\" }
+        }
+        struct Square { side: i32 }
+        struct Circle { r: i32 }
+        impl Shape for Square {
+            func area(&self) -> i32 { self.side * self.side }
+            func describe(&self) -> String { \"a square\" }
+        }
+        impl Shape for Circle {
+            func area(&self) -> i32 { 3 * self.r * self.r }
+        }
+        func main() {
+            let c = Circle { r: 2 };
+            println(c.describe());
+        }
+    ";
+
+    fn compile(source: &str) -> Bytecode {
+        let tokens = Lexer::new(source, "test.zr").tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        Compiler::new().compile(&program).unwrap()
+    }
+
+    #[test]
+    fn test_trait_default_compiled_per_type() {
+        let bytecode = compile(SHAPES);
+        assert!(bytecode
+            .functions
+            .contains_key("<Shape as Circle>::describe"));
+    }
+
+    #[test]
+    fn test_trait_override_replaces_default() {
+        let bytecode = compile(SHAPES);
+        let square = &bytecode.functions["<Shape as Square>::describe"];
+        let circle = &bytecode.functions["<Shape as Circle>::describe"];
+        assert_ne!(square.start_address, circle.start_address);
+    }
+
+    #[test]
+    fn test_enum_variants_use_integer_tags() {
+        let bytecode = compile(
+            "enum Dir { North, East(i32) }
+            func main() {
+                let d = Dir::East(3);
+                match d { Dir::North => 0, Dir::East(x) => x, }
+            }",
+        );
+        assert_eq!(bytecode.variant_name(0, 1).as_deref(), Some("Dir::East"));
+        assert!(bytecode.instructions.contains(&Instruction::MakeEnum(0, 1)));
+        assert!(bytecode
+            .instructions
+            .contains(&Instruction::IsVariant(0, 0)));
+    }
+
+    #[test]
+    fn test_overflow_mode_selects_instructions() {
+        let tokens = Lexer::new("func main() { let x = 1 + 2 * 3; }", "test.zr")
+            .tokenize()
+            .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let bytecode = Compiler::new()
+            .with_overflow(OverflowMode::Saturate)
+            .compile(&program)
+            .unwrap();
+        assert!(bytecode.instructions.contains(&Instruction::SaturatingAdd));
+        assert!(bytecode.instructions.contains(&Instruction::SaturatingMul));
+        assert!(!bytecode.instructions.contains(&Instruction::Add));
+    }
+
+    #[test]
+    fn test_typed_struct_fields_use_indices() {
+        let bytecode = compile(
+            "struct Point { x: i32, y: i32 }
+            func main() {
+                let mut p = Point { y: 2, x: 1 };
+                p.y = p.x;
+            }",
+        );
+        assert!(bytecode.instructions.contains(&Instruction::MakeStruct {
+            type_id: 0,
+            slots: vec![1, 0],
+        }));
+        assert!(bytecode
+            .instructions
+            .contains(&Instruction::GetFieldIdx(0, 0)));
+        assert!(bytecode
+            .instructions
+            .contains(&Instruction::SetFieldIdx(0, 1)));
+        assert!(!bytecode
+            .instructions
+            .iter()
+            .any(|i| matches!(i, Instruction::GetField(_) | Instruction::SetField(_))));
+    }
+}
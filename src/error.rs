@@ -1,8 +1,16 @@
 //! Zyra Error Handling
-//! 
+//!
 //! Provides human-readable error messages with source locations and suggestions.
+//! `render` quotes the lines an error points at from its source file, with the
+//! span underlined, a line of context around it, and labeled secondary spans.
 
 use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use crate::lexer::Span;
+
+/// Errors a check collects before it stops, unless `--max-errors` says otherwise
+pub const DEFAULT_MAX_ERRORS: usize = 20;
 
 /// Source location for error reporting
 #[derive(Debug, Clone)]
@@ -10,6 +18,8 @@ pub struct SourceLocation {
     pub file: String,
     pub line: usize,
     pub column: usize,
+    /// Characters the problem spans from `column` (0 = unknown)
+    pub length: usize,
     pub snippet: Option<String>,
 }
 
@@ -19,93 +29,521 @@ impl SourceLocation {
             file: file.to_string(),
             line,
             column,
+            length: 0,
             snippet: None,
         }
     }
-    
+
+    /// Location of a span of the file being checked
+    pub fn from_span(span: &Span) -> Self {
+        Self {
+            length: span.end.saturating_sub(span.start),
+            ..Self::new("", span.line, span.column)
+        }
+    }
+
     pub fn with_snippet(mut self, snippet: &str) -> Self {
         self.snippet = Some(snippet.to_string());
         self
     }
 }
 
+/// A secondary span of an error, such as where a value was moved
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub location: SourceLocation,
+    pub message: String,
+}
+
+/// One frame of a runtime backtrace: the function and where it was executing
+#[derive(Debug, Clone)]
+pub struct TraceFrame {
+    pub function: String,
+    pub location: Option<SourceLocation>,
+}
+
+/// A machine-applicable edit: replace the source characters `start..end`
+/// (the positions of a `Span`) with `replacement`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+impl Edit {
+    pub fn insert(at: usize, text: &str) -> Self {
+        Self {
+            start: at,
+            end: at,
+            replacement: text.to_string(),
+        }
+    }
+
+    /// Apply edits that don't overlap to a source text
+    pub fn apply_all(source: &str, edits: &[Edit]) -> String {
+        let mut chars: Vec<char> = source.chars().collect();
+        let mut edits: Vec<&Edit> = edits.iter().collect();
+        // Back to front, so earlier positions stay valid
+        edits.sort_by_key(|edit| std::cmp::Reverse((edit.start, edit.end)));
+        for edit in edits {
+            let end = edit.end.min(chars.len());
+            let start = edit.start.min(end);
+            chars.splice(start..end, edit.replacement.chars());
+        }
+        chars.into_iter().collect()
+    }
+}
+
 /// Main error type for Zyra
+/// Boxed, so a `ZyraResult` stays small however much an error carries; its
+/// fields are reached through `Deref`
+#[derive(Debug, Clone)]
+pub struct ZyraError(Box<ErrorInfo>);
+
+/// What a `ZyraError` reports
 #[derive(Debug, Clone)]
-pub struct ZyraError {
+pub struct ErrorInfo {
     pub kind: String,
     pub message: String,
     pub location: Option<SourceLocation>,
     pub suggestion: Option<String>,
+    /// Edits that together apply the suggestion, used by `zyra fix`
+    pub fix: Vec<Edit>,
+    /// Zyra-level backtrace of a runtime error, innermost frame first
+    pub trace: Vec<TraceFrame>,
+    /// Other places involved, shown next to the error's own
+    pub labels: Vec<Label>,
+    /// Further errors found in the same pass, printed after this one
+    pub others: Vec<ZyraError>,
+}
+
+impl Deref for ZyraError {
+    type Target = ErrorInfo;
+
+    fn deref(&self) -> &ErrorInfo {
+        &self.0
+    }
+}
+
+impl DerefMut for ZyraError {
+    fn deref_mut(&mut self) -> &mut ErrorInfo {
+        &mut self.0
+    }
 }
 
 impl ZyraError {
     pub fn new(kind: &str, message: &str, location: Option<SourceLocation>) -> Self {
-        Self {
+        Self(Box::new(ErrorInfo {
             kind: kind.to_string(),
             message: message.to_string(),
             location,
             suggestion: None,
+            fix: Vec::new(),
+            trace: Vec::new(),
+            labels: Vec::new(),
+            others: Vec::new(),
+        }))
+    }
+
+    pub fn with_label(mut self, location: SourceLocation, message: &str) -> Self {
+        self.labels.push(Label {
+            location,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    /// Name `file` as the file of the locations that don't have one, which
+    /// the analyzer leaves empty
+    pub fn in_file(mut self, file: &str) -> Self {
+        let error = &mut *self.0;
+        let locations = error
+            .location
+            .iter_mut()
+            .chain(error.labels.iter_mut().map(|label| &mut label.location));
+        for location in locations {
+            if location.file.is_empty() {
+                location.file = file.to_string();
+            }
+        }
+        error.others = std::mem::take(&mut error.others)
+            .into_iter()
+            .map(|other| other.in_file(file))
+            .collect();
+        self
+    }
+
+    /// The error as `Display` shows it, with the lines it points at quoted
+    /// from the source `sources` gives for their file
+    pub fn render(&self, sources: &dyn Fn(&str) -> Option<String>) -> String {
+        let mut out = String::new();
+        for (i, error) in self.all().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            let source = error.location.as_ref().and_then(|loc| sources(&loc.file));
+            // Writing to a String can't fail
+            let _ = error.write(&mut out, source.as_deref());
+        }
+        out
+    }
+
+    /// One error reporting all of `errors`, the first in front; None if empty
+    pub fn combine(mut errors: Vec<ZyraError>) -> Option<Self> {
+        if errors.is_empty() {
+            return None;
         }
+        let mut first = errors.remove(0);
+        first.others.extend(errors);
+        Some(first)
     }
-    
+
+    /// This error followed by the others reported with it
+    pub fn all(&self) -> impl Iterator<Item = &ZyraError> {
+        std::iter::once(self).chain(&self.others)
+    }
+
     pub fn with_suggestion(mut self, suggestion: &str) -> Self {
         self.suggestion = Some(suggestion.to_string());
         self
     }
-    
+
+    pub fn with_fix(mut self, fix: Vec<Edit>) -> Self {
+        self.fix = fix;
+        self
+    }
+
     // Common error constructors
     pub fn syntax_error(message: &str, location: SourceLocation) -> Self {
         Self::new("SyntaxError", message, Some(location))
     }
-    
+
     pub fn type_error(message: &str, location: Option<SourceLocation>) -> Self {
         Self::new("TypeError", message, location)
     }
-    
+
     pub fn name_error(message: &str, location: Option<SourceLocation>) -> Self {
         Self::new("NameError", message, location)
     }
-    
+
     pub fn ownership_error(message: &str, location: Option<SourceLocation>) -> Self {
         Self::new("OwnershipError", message, location)
     }
-    
+
+    /// Closes a list of errors cut short at `max_errors`
+    pub fn too_many_errors(max_errors: usize) -> Self {
+        Self::new(
+            "TooManyErrors",
+            &format!("Stopped after {} errors", max_errors),
+            None,
+        )
+        .with_suggestion("Fix these first, or pass --max-errors <n> to see more")
+    }
+
     pub fn runtime_error(message: &str) -> Self {
         Self::new("RuntimeError", message, None)
     }
+
+    pub fn overflow_error(message: &str) -> Self {
+        Self::new("OverflowError", message, None)
+    }
 }
 
-impl fmt::Display for ZyraError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl ZyraError {
+    /// Write this error alone, quoting its lines from `source` when given
+    fn write<W: fmt::Write>(&self, f: &mut W, source: Option<&str>) -> fmt::Result {
         // Error header
         writeln!(f, "\x1b[1;31merror[{}]\x1b[0m: {}", self.kind, self.message)?;
-        
+
         // Location info
         if let Some(ref loc) = self.location {
-            writeln!(f, "  \x1b[1;34m-->\x1b[0m {}:{}:{}", loc.file, loc.line, loc.column)?;
-            
-            // Code snippet
-            if let Some(ref snippet) = loc.snippet {
-                writeln!(f, "   \x1b[1;34m|\x1b[0m")?;
-                writeln!(f, " \x1b[1;34m{:3} |\x1b[0m {}", loc.line, snippet)?;
-                
-                // Underline the error position
-                let padding = " ".repeat(loc.column + 4);
-                writeln!(f, "   \x1b[1;34m|\x1b[0m {}\x1b[1;31m^\x1b[0m", padding)?;
+            // Runtime locations come from the line table and carry no column
+            if loc.column > 0 {
+                writeln!(
+                    f,
+                    "  \x1b[1;34m-->\x1b[0m {}:{}:{}",
+                    loc.file, loc.line, loc.column
+                )?;
+            } else {
+                writeln!(f, "  \x1b[1;34m-->\x1b[0m {}:{}", loc.file, loc.line)?;
+            }
+
+            if let Some(source) = source {
+                write_snippet(f, source, loc, "\x1b[1;31m", &self.labels)?;
+            } else {
+                // Code snippet
+                if let Some(ref snippet) = loc.snippet {
+                    writeln!(f, "   \x1b[1;34m|\x1b[0m")?;
+                    writeln!(f, " \x1b[1;34m{:3} |\x1b[0m {}", loc.line, snippet)?;
+
+                    // Underline the error position
+                    let padding = " ".repeat(loc.column + 4);
+                    writeln!(f, "   \x1b[1;34m|\x1b[0m {}\x1b[1;31m^\x1b[0m", padding)?;
+                }
+                for label in &self.labels {
+                    writeln!(
+                        f,
+                        "   \x1b[1;34m=\x1b[0m note: {} (line {})",
+                        label.message, label.location.line
+                    )?;
+                }
             }
         }
-        
+
+        if !self.trace.is_empty() {
+            writeln!(
+                f,
+                "   \x1b[1;34m=\x1b[0m backtrace (most recent call first):"
+            )?;
+            for (i, frame) in self.trace.iter().enumerate() {
+                match frame.location {
+                    Some(ref loc) => writeln!(
+                        f,
+                        "     {:>2}: {} at {}:{}",
+                        i, frame.function, loc.file, loc.line
+                    )?,
+                    None => writeln!(f, "     {:>2}: {}", i, frame.function)?,
+                }
+            }
+        }
+
         // Suggestion
         if let Some(ref suggestion) = self.suggestion {
             writeln!(f)?;
             writeln!(f, "\x1b[1;32mhelp\x1b[0m: {}", suggestion)?;
         }
-        
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ZyraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.all().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            error.write(f, None)?;
+        }
         Ok(())
     }
 }
 
+/// Quote the source lines `primary` and `labels` point at, with a line of
+/// context around the primary one. Its span is underlined with `^` in
+/// `color`, each label's with `-` and the label's message.
+fn write_snippet<W: fmt::Write>(
+    f: &mut W,
+    source: &str,
+    primary: &SourceLocation,
+    color: &str,
+    labels: &[Label],
+) -> fmt::Result {
+    let lines: Vec<&str> = source.lines().collect();
+    let exists = |line: usize| line >= 1 && line <= lines.len();
+    if !exists(primary.line) {
+        return Ok(());
+    }
+    let mut marks = vec![(primary, '^', "", color)];
+    marks.extend(
+        labels
+            .iter()
+            .filter(|label| exists(label.location.line))
+            .map(|label| (&label.location, '-', label.message.as_str(), "\x1b[1;34m")),
+    );
+
+    let mut shown: Vec<usize> = marks.iter().map(|(loc, ..)| loc.line).collect();
+    shown.extend(
+        [primary.line - 1, primary.line + 1]
+            .into_iter()
+            .filter(|&l| exists(l)),
+    );
+    shown.sort_unstable();
+    shown.dedup();
+
+    let width = shown.last().map_or(1, |line| line.to_string().len());
+    let gutter = " ".repeat(width);
+    writeln!(f, "{} \x1b[1;34m|\x1b[0m", gutter)?;
+    let mut previous: Option<usize> = None;
+    for line in shown {
+        if previous.is_some_and(|previous| line > previous + 1) {
+            writeln!(f, "\x1b[1;34m...\x1b[0m")?;
+        }
+        previous = Some(line);
+        let text = lines[line - 1];
+        writeln!(f, "\x1b[1;34m{:>width$} |\x1b[0m {}", line, text)?;
+        for (loc, mark, message, color) in marks.iter().filter(|(loc, ..)| loc.line == line) {
+            let (start, length) = underline(text, loc.column, loc.length);
+            // Tabs stay tabs so the marks line up under the code
+            let indent: String = text
+                .chars()
+                .take(start)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let marks = mark.to_string().repeat(length);
+            let separator = if message.is_empty() { "" } else { " " };
+            writeln!(
+                f,
+                "{} \x1b[1;34m|\x1b[0m {}{}{}{}{}\x1b[0m",
+                gutter, indent, color, marks, separator, message
+            )?;
+        }
+    }
+    writeln!(f, "{} \x1b[1;34m|\x1b[0m", gutter)
+}
+
+/// Character offset and width of the underline of a span on `text`. A span
+/// without a column, like a runtime error's, covers the code of the line.
+fn underline(text: &str, column: usize, length: usize) -> (usize, usize) {
+    let chars = text.chars().count();
+    if column == 0 {
+        let start = text.chars().take_while(|c| c.is_whitespace()).count();
+        let end = chars - text.chars().rev().take_while(|c| c.is_whitespace()).count();
+        return (start, end.saturating_sub(start).max(1));
+    }
+    let start = (column - 1).min(chars);
+    (start, length.min(chars - start).max(1))
+}
+
 impl std::error::Error for ZyraError {}
 
+/// How seriously a warning is taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Reported, but the program still builds
+    Warning,
+    /// Fails the build, as with `--deny-warnings`
+    Error,
+}
+
+/// A likely mistake that doesn't stop compilation, found by a named lint
+#[derive(Debug, Clone)]
+pub struct Warning {
+    /// Lint that found it, e.g. `float_eq`
+    pub lint: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<SourceLocation>,
+    pub suggestion: Option<String>,
+}
+
+impl Warning {
+    pub fn new(lint: &'static str, message: &str, location: Option<SourceLocation>) -> Self {
+        Self {
+            lint,
+            severity: Severity::Warning,
+            message: message.to_string(),
+            location,
+            suggestion: None,
+        }
+    }
+
+    pub fn with_suggestion(mut self, suggestion: &str) -> Self {
+        self.suggestion = Some(suggestion.to_string());
+        self
+    }
+}
+
+impl Warning {
+    /// The warning as `Display` shows it, with its line quoted from the
+    /// source `sources` gives for its file
+    pub fn render(&self, sources: &dyn Fn(&str) -> Option<String>) -> String {
+        let mut out = String::new();
+        let source = self.location.as_ref().and_then(|loc| sources(&loc.file));
+        // Writing to a String can't fail
+        let _ = self.write(&mut out, source.as_deref());
+        out
+    }
+
+    fn write<W: fmt::Write>(&self, f: &mut W, source: Option<&str>) -> fmt::Result {
+        let color = match self.severity {
+            Severity::Warning => {
+                writeln!(
+                    f,
+                    "\x1b[1;33mwarning[{}]\x1b[0m: {}",
+                    self.lint, self.message
+                )?;
+                "\x1b[1;33m"
+            }
+            Severity::Error => {
+                writeln!(f, "\x1b[1;31merror[{}]\x1b[0m: {}", self.lint, self.message)?;
+                "\x1b[1;31m"
+            }
+        };
+        if let Some(ref loc) = self.location {
+            writeln!(
+                f,
+                "  \x1b[1;34m-->\x1b[0m {}:{}:{}",
+                loc.file, loc.line, loc.column
+            )?;
+            if let Some(source) = source {
+                write_snippet(f, source, loc, color, &[])?;
+            }
+        }
+        if let Some(ref suggestion) = self.suggestion {
+            writeln!(f, "\x1b[1;32mhelp\x1b[0m: {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, None)
+    }
+}
+
 /// Result type alias for Zyra operations
 pub type ZyraResult<T> = Result<T, ZyraError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rendered text without the color codes
+    fn plain(text: &str) -> String {
+        let mut out = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_render_underlines_the_span_and_labels() {
+        let source = "func main() {\n    let v = vec[1];\n    let w = v;\n    println(v);\n}\n";
+        let error = ZyraError::ownership_error("'v' was moved", {
+            let mut location = SourceLocation::new("", 4, 13);
+            location.length = 1;
+            Some(location)
+        })
+        .with_label(SourceLocation::new("", 2, 0), "v is created here")
+        .in_file("main.zr");
+        let rendered = plain(&error.render(&|file| {
+            assert_eq!(file, "main.zr");
+            Some(source.to_string())
+        }));
+        let expected = [
+            "error[OwnershipError]: 'v' was moved",
+            "  --> main.zr:4:13",
+            "  |",
+            "2 |     let v = vec[1];",
+            "  |     --------------- v is created here",
+            "3 |     let w = v;",
+            "4 |     println(v);",
+            "  |             ^",
+            "5 | }",
+            "  |",
+        ];
+        assert_eq!(rendered.lines().collect::<Vec<_>>(), expected);
+
+        // Without its source, the label becomes a note
+        assert!(plain(&error.to_string()).contains("= note: v is created here (line 2)"));
+    }
+}
@@ -0,0 +1,88 @@
+//! Entry points for fuzzing the compiler pipeline (`--features fuzz`)
+//!
+//! Each stage accepts arbitrary input and reports rejected input through its
+//! return value, so any panic a fuzzer finds is a real bug. The cargo-fuzz
+//! targets in `fuzz/` call these; `fuzz/seeds` holds a starting corpus.
+
+use crate::compiler::{Bytecode, Compiler};
+use crate::lexer::{Lexer, Token};
+use crate::parser::{Parser, Program};
+use crate::semantic::SemanticAnalyzer;
+
+/// Source file name reported in errors for fuzz input
+const FUZZ_FILE: &str = "fuzz.zr";
+
+/// Tokenize arbitrary bytes; invalid UTF-8 is replaced, not rejected
+pub fn fuzz_lex(data: &[u8]) -> Option<Vec<Token>> {
+    let source = String::from_utf8_lossy(data);
+    Lexer::new(&source, FUZZ_FILE).tokenize().ok()
+}
+
+/// Tokenize and parse arbitrary bytes
+pub fn fuzz_parse(data: &[u8]) -> Option<Program> {
+    let tokens = fuzz_lex(data)?;
+    Parser::new(tokens).parse().ok()
+}
+
+/// Analyze and compile a parsed program
+pub fn fuzz_compile(ast: &Program) -> Option<Bytecode> {
+    SemanticAnalyzer::new().analyze(ast).ok()?;
+    Compiler::new().compile(ast).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejected_input_does_not_panic() {
+        let mut inputs: Vec<Vec<u8>> = [
+            &b"\xff\xfe func"[..],
+            b"func main( {",
+            b"\"unterminated",
+            b"func main() { let x = ; }",
+            b"",
+        ]
+        .iter()
+        .map(|input| input.to_vec())
+        .collect();
+        // Nesting far past the parser's limit
+        let deep = |open: &str, inner: &str, close: &str| {
+            format!("{}{}{}", open.repeat(500), inner, close.repeat(500))
+        };
+        for source in [
+            format!("func main() {{ let x = {}; }}", deep("(", "1", ")")),
+            format!("func main() {{ let x = {}; }}", deep("- ", "1", "")),
+            format!("func main() {{ let x = {}; }}", deep("[", "1", "]")),
+            format!("func main() {{ {} }}", deep("if true { ", "", " }")),
+            format!("func f(v: {}) {{ }}", deep("Vec<", "int", ">")),
+            format!(
+                "func f(v: int) {{ match v {{ {} => 1, _ => 0, }} }}",
+                deep("Some(", "x", ")")
+            ),
+        ] {
+            inputs.push(source.into_bytes());
+        }
+
+        // A debug build takes far more stack per level than a release one;
+        // give the pipeline as much as the main thread gets
+        std::thread::Builder::new()
+            .stack_size(8 << 20)
+            .spawn(move || {
+                for input in &inputs {
+                    if let Some(ast) = fuzz_parse(input) {
+                        fuzz_compile(&ast);
+                    }
+                }
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_seed_programs_pass_every_stage() {
+        let ast = fuzz_parse(b"func main() { let x = 1 + 2; println(x); }").unwrap();
+        assert!(fuzz_compile(&ast).is_some());
+    }
+}
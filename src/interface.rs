@@ -0,0 +1,229 @@
+//! Module interface files
+//!
+//! Once a program's modules pass analysis, `zyra check` and `zyra run` write
+//! the declarations of each imported module (function signatures, structs and
+//! enums) to `.zyra-cache/<module>.zyi`, together with a hash of the source
+//! they were taken from and of the other modules loaded with it. The
+//! interface is Zyra syntax with empty function bodies, so it is read back
+//! with the regular parser. While a module's hash still matches, importers
+//! take its items as checked and the analyzer skips them instead of
+//! re-analyzing the module source; editing any module checks them all again,
+//! as a module's code may depend on the items of another.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::cache::content_hash;
+use crate::lexer::Lexer;
+use crate::parser::ast::{Block, Program, Statement};
+use crate::parser::Parser;
+
+/// File extension of interface files
+pub const INTERFACE_EXT: &str = "zyi";
+
+/// Header line every interface file starts with, followed by the source hash
+const HEADER: &str = "// zyra interface";
+
+/// Declarations a module exports to the modules importing it
+#[derive(Debug, Clone)]
+pub struct ModuleInterface {
+    /// Name the module's items are prefixed with by the importer, e.g. `util`
+    pub module: String,
+    /// Hash of the module source (and compiler version) the interface
+    /// describes, combined by the resolver with those of the other modules
+    pub source_hash: u64,
+    /// Functions with their bodies left out, structs and enums
+    pub items: Vec<Statement>,
+    /// Names of the items declared `pub`
+    pub exports: Vec<String>,
+}
+
+impl ModuleInterface {
+    /// Hash an interface is keyed by: interfaces don't outlive a compiler upgrade
+    pub fn source_hash(source: &str, options: &str) -> u64 {
+        let mut bytes = env!("CARGO_PKG_VERSION").as_bytes().to_vec();
+        bytes.extend_from_slice(options.as_bytes());
+        bytes.extend_from_slice(source.as_bytes());
+        content_hash(&bytes)
+    }
+
+    /// Interface of a parsed module, before its items are prefixed
+    pub fn from_program(module: &str, source_hash: u64, program: &Program) -> Self {
+        let items = program
+            .statements
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Statement::Function {
+                    name,
+                    lifetimes,
+                    params,
+                    return_type,
+                    body,
+                    span,
+                } => Some(Statement::Function {
+                    name: name.clone(),
+                    lifetimes: lifetimes.clone(),
+                    params: params.clone(),
+                    return_type: return_type.clone(),
+                    body: Block {
+                        statements: vec![],
+                        expression: None,
+                        span: body.span,
+                    },
+                    span: *span,
+                }),
+                Statement::Struct { .. } | Statement::Enum { .. } => Some(stmt.clone()),
+                _ => None,
+            })
+            .collect();
+        Self {
+            module: module.to_string(),
+            source_hash,
+            items,
+            exports: program.exports.clone(),
+        }
+    }
+
+    /// Names of the items as the importing program sees them, e.g. `util::double`
+    pub fn item_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.items.iter().filter_map(move |item| match item {
+            Statement::Function { name, .. }
+            | Statement::Struct { name, .. }
+            | Statement::Enum { name, .. } => Some(format!("{}::{}", self.module, name)),
+            _ => None,
+        })
+    }
+
+    /// The interface file's contents
+    pub fn to_source(&self) -> String {
+        let mut out = format!("{} {:016x}\n", HEADER, self.source_hash);
+        for item in &self.items {
+            let (Statement::Function { name, .. }
+            | Statement::Struct { name, .. }
+            | Statement::Enum { name, .. }) = item
+            else {
+                continue;
+            };
+            if self.exports.contains(name) {
+                out.push_str("pub ");
+            }
+            match item {
+                Statement::Function {
+                    lifetimes,
+                    params,
+                    return_type,
+                    ..
+                } => {
+                    out.push_str("func ");
+                    out.push_str(name);
+                    if !lifetimes.is_empty() {
+                        let lifetimes: Vec<String> =
+                            lifetimes.iter().map(|lt| format!("'{}", lt)).collect();
+                        out.push_str(&format!("<{}>", lifetimes.join(", ")));
+                    }
+                    let params: Vec<String> = params
+                        .iter()
+                        .map(|p| format!("{}: {}", p.name, p.param_type.as_str()))
+                        .collect();
+                    out.push_str(&format!("({})", params.join(", ")));
+                    if let Some(return_type) = return_type {
+                        out.push_str(&format!(" -> {}", return_type.as_str()));
+                    }
+                    out.push_str(" {}\n");
+                }
+                Statement::Struct { fields, .. } => {
+                    let fields: Vec<String> = fields
+                        .iter()
+                        .map(|f| format!("{}: {}", f.name, f.field_type.as_str()))
+                        .collect();
+                    out.push_str(&format!("struct {} {{ {} }}\n", name, fields.join(", ")));
+                }
+                Statement::Enum { variants, .. } => {
+                    let variants: Vec<String> = variants
+                        .iter()
+                        .map(|v| match &v.data {
+                            Some(types) => {
+                                let types: Vec<String> = types.iter().map(|t| t.as_str()).collect();
+                                format!("{}({})", v.name, types.join(", "))
+                            }
+                            None => v.name.clone(),
+                        })
+                        .collect();
+                    out.push_str(&format!("enum {} {{ {} }}\n", name, variants.join(", ")));
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    /// Read an interface back; None unless it was written for `source_hash`
+    pub fn parse(module: &str, source_hash: u64, text: &str) -> Option<Self> {
+        let (header, declarations) = text.split_once('\n')?;
+        let hash = header.strip_prefix(HEADER)?.trim();
+        if u64::from_str_radix(hash, 16).ok()? != source_hash {
+            return None;
+        }
+        let tokens = Lexer::new(declarations, module).tokenize().ok()?;
+        let program = Parser::new(tokens).parse().ok()?;
+        Some(Self::from_program(module, source_hash, &program))
+    }
+
+    /// Load the interface stored at `path`, if it is up to date
+    pub fn load(path: &Path, module: &str, source_hash: u64) -> Option<Self> {
+        Self::parse(module, source_hash, &fs::read_to_string(path).ok()?)
+    }
+
+    /// Write the interface to `path`, creating its directory
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_source())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_module(source: &str) -> Program {
+        let tokens = Lexer::new(source, "util.zr").tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    #[test]
+    fn test_interface_round_trips_declarations() {
+        let source = "pub func scale<'a>(v: &'a Vec<f64>, by: f64) -> Option<i64> { None }\n\
+                      func helper() { println(1); }\n\
+                      pub struct Point { x: f64, y: Map<String, i32> }\n\
+                      enum Shape { Circle(f64), Rect(f64, f64), Empty }";
+        let hash = ModuleInterface::source_hash(source, "");
+        let interface = ModuleInterface::from_program("util", hash, &parse_module(source));
+        let text = interface.to_source();
+
+        assert!(text.contains("pub func scale<'a>(v: &'a Vec<f64>, by: f64) -> Option<i64> {}"));
+        assert!(text.contains("\nfunc helper() {}"));
+        assert!(text.contains("pub struct Point { x: f64, y: Map<String, i32> }"));
+        assert!(text.contains("enum Shape { Circle(f64), Rect(f64, f64), Empty }"));
+
+        let read = ModuleInterface::parse("util", hash, &text).unwrap();
+        assert_eq!(read.to_source(), text);
+        assert_eq!(
+            read.item_names().collect::<Vec<_>>(),
+            ["util::scale", "util::helper", "util::Point", "util::Shape"]
+        );
+    }
+
+    #[test]
+    fn test_stale_interface_is_not_loaded() {
+        let source = "pub func one() -> int { 1 }";
+        let hash = ModuleInterface::source_hash(source, "");
+        let text = ModuleInterface::from_program("util", hash, &parse_module(source)).to_source();
+
+        let edited = ModuleInterface::source_hash("pub func one() -> int { 2 }", "");
+        assert!(ModuleInterface::parse("util", edited, &text).is_none());
+        assert!(ModuleInterface::parse("util", hash, "garbage").is_none());
+    }
+}
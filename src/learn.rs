@@ -0,0 +1,225 @@
+//! Interactive tutorial for `zyra learn`
+//!
+//! Each lesson is a small exercise: the learner edits a starter file, and
+//! `check` runs it in a sandboxed VM (output captured, instruction count
+//! limited) and compares what it printed with the expected output.
+//! Completed lessons are checkpointed in a progress file, so the tutorial
+//! resumes where the learner stopped.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::compiler::{Bytecode, Compiler};
+use crate::error::{ZyraError, ZyraResult};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::semantic::SemanticAnalyzer;
+use crate::stdlib::io::capture;
+use crate::vm::VM;
+
+/// One tutorial exercise
+pub struct Lesson {
+    /// Also the exercise file name (`<id>.zr`)
+    pub id: &'static str,
+    pub title: &'static str,
+    pub instructions: &'static str,
+    pub starter: &'static str,
+    /// A passing answer, used to test the lesson itself
+    pub solution: &'static str,
+    /// What the program must print
+    pub expected: &'static str,
+}
+
+pub const LESSONS: &[Lesson] = &[
+    Lesson {
+        id: "01_hello",
+        title: "Hello, Zyra",
+        instructions: "Every program starts in `func main()`. Use println to print exactly:\n  Hello, Zyra!",
+        starter: "// Lesson 1: print a greeting\n\nfunc main() {\n    // your code here\n}\n",
+        solution: "func main() {\n    println(\"Hello, Zyra!\");\n}\n",
+        expected: "Hello, Zyra!\n",
+    },
+    Lesson {
+        id: "02_variables",
+        title: "Variables",
+        instructions: "Variables are declared with `let` and can be put into strings with ${...}.\nSet `name` to \"Ada\" and `age` to 36, then print:\n  Ada is 36",
+        starter: "// Lesson 2: variables and string interpolation\n\nfunc main() {\n    let name = \"\";\n    let age = 0;\n    println(\"${name} is ${age}\");\n}\n",
+        solution: "func main() {\n    let name = \"Ada\";\n    let age = 36;\n    println(\"${name} is ${age}\");\n}\n",
+        expected: "Ada is 36\n",
+    },
+    Lesson {
+        id: "03_loops",
+        title: "Loops",
+        instructions: "`for i in a..b` counts from a up to, but not including, b.\nPrint the numbers 1 to 5, one per line.",
+        starter: "// Lesson 3: for loops\n\nfunc main() {\n    for i in 0..0 {\n        println(i);\n    }\n}\n",
+        solution: "func main() {\n    for i in 1..6 {\n        println(i);\n    }\n}\n",
+        expected: "1\n2\n3\n4\n5\n",
+    },
+    Lesson {
+        id: "04_functions",
+        title: "Functions",
+        instructions: "Functions declare their parameter and return types.\nFinish `square` so that the program prints:\n  49",
+        starter: "// Lesson 4: functions\n\nfunc square(n: int) -> int {\n    return 0;\n}\n\nfunc main() {\n    println(square(7));\n}\n",
+        solution: "func square(n: int) -> int {\n    return n * n;\n}\n\nfunc main() {\n    println(square(7));\n}\n",
+        expected: "49\n",
+    },
+    Lesson {
+        id: "05_structs",
+        title: "Structs and methods",
+        instructions: "Structs group fields; methods live in an `impl` block and take `self`.\nImplement `sum` so that the program prints:\n  7",
+        starter: "// Lesson 5: structs and methods\n\nstruct Point {\n    x: int,\n    y: int,\n}\n\nimpl Point {\n    func sum(self) -> int {\n        return 0;\n    }\n}\n\nfunc main() {\n    let p = Point { x: 3, y: 4 };\n    println(p.sum());\n}\n",
+        solution: "struct Point {\n    x: int,\n    y: int,\n}\n\nimpl Point {\n    func sum(self) -> int {\n        return self.x + self.y;\n    }\n}\n\nfunc main() {\n    let p = Point { x: 3, y: 4 };\n    println(p.sum());\n}\n",
+        expected: "7\n",
+    },
+];
+
+/// Instructions an exercise may run before it's stopped
+const STEP_LIMIT: u64 = 1_000_000;
+
+/// Result of checking a learner's solution
+pub enum Outcome {
+    Passed,
+    /// The program ran but printed something else
+    WrongOutput {
+        expected: String,
+        actual: String,
+    },
+    /// The program didn't compile or stopped with an error
+    Failed(ZyraError),
+}
+
+fn compile(source: &str, path: &str) -> ZyraResult<Bytecode> {
+    let tokens = Lexer::new(source, path).tokenize()?;
+    let ast = Parser::new(tokens).parse()?;
+    SemanticAnalyzer::new().analyze(&ast)?;
+    let mut bytecode = Compiler::new().compile(&ast)?;
+    bytecode.source_file = path.to_string();
+    Ok(bytecode)
+}
+
+/// Compile and run a program in a fresh VM, returning what it printed
+pub fn run_captured(source: &str, path: &str) -> (ZyraResult<()>, String) {
+    let bytecode = match compile(source, path) {
+        Ok(bytecode) => bytecode,
+        Err(e) => return (Err(e), String::new()),
+    };
+
+    let mut vm = VM::new().with_step_limit(STEP_LIMIT);
+    let (result, output) = capture(|| vm.run(&bytecode));
+    (result.map(|_| ()), output)
+}
+
+/// Run a solution and compare its output with the lesson's, ignoring trailing whitespace
+pub fn check(lesson: &Lesson, source: &str, path: &str) -> Outcome {
+    let (result, output) = run_captured(source, path);
+    if let Err(e) = result {
+        return Outcome::Failed(e);
+    }
+    let normalize = |text: &str| -> String {
+        let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+        lines.join("\n").trim_end().to_string()
+    };
+    if normalize(&output) == normalize(lesson.expected) {
+        Outcome::Passed
+    } else {
+        Outcome::WrongOutput {
+            expected: lesson.expected.to_string(),
+            actual: output,
+        }
+    }
+}
+
+/// Completed lessons, stored one id per line in `<dir>/progress.txt`
+pub struct Progress {
+    dir: PathBuf,
+    completed: Vec<String>,
+}
+
+impl Progress {
+    /// Load the checkpoint from a tutorial directory; a missing file means a fresh start
+    pub fn load(dir: &Path) -> Self {
+        let completed = fs::read_to_string(dir.join("progress.txt"))
+            .map(|text| text.lines().map(|l| l.trim().to_string()).collect())
+            .unwrap_or_default();
+        Self {
+            dir: dir.to_path_buf(),
+            completed,
+        }
+    }
+
+    pub fn is_done(&self, lesson: &Lesson) -> bool {
+        self.completed.iter().any(|id| id == lesson.id)
+    }
+
+    /// The first lesson not completed yet, or None when the tutorial is finished
+    pub fn current(&self) -> Option<&'static Lesson> {
+        LESSONS.iter().find(|lesson| !self.is_done(lesson))
+    }
+
+    /// Where the learner writes a lesson's solution
+    pub fn exercise_path(&self, lesson: &Lesson) -> PathBuf {
+        self.dir.join(format!("{}.zr", lesson.id))
+    }
+
+    /// Create the exercise file from the starter code unless it already exists
+    pub fn prepare(&self, lesson: &Lesson) -> io::Result<PathBuf> {
+        let path = self.exercise_path(lesson);
+        if !path.exists() {
+            fs::create_dir_all(&self.dir)?;
+            fs::write(&path, lesson.starter)?;
+        }
+        Ok(path)
+    }
+
+    /// Record a lesson as completed
+    pub fn complete(&mut self, lesson: &Lesson) -> io::Result<()> {
+        if !self.is_done(lesson) {
+            self.completed.push(lesson.id.to_string());
+        }
+        self.save()
+    }
+
+    /// Forget all progress; exercise files are kept
+    pub fn reset(&mut self) -> io::Result<()> {
+        self.completed.clear();
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut text = self.completed.join("\n");
+        text.push('\n');
+        fs::write(self.dir.join("progress.txt"), text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lesson_solutions_pass_and_starters_do_not() {
+        for lesson in LESSONS {
+            assert!(
+                matches!(
+                    check(lesson, lesson.solution, "solution.zr"),
+                    Outcome::Passed
+                ),
+                "solution of {} fails",
+                lesson.id
+            );
+            assert!(
+                !matches!(check(lesson, lesson.starter, "starter.zr"), Outcome::Passed),
+                "starter of {} already passes",
+                lesson.id
+            );
+        }
+    }
+
+    #[test]
+    fn test_endless_loop_is_stopped() {
+        let (result, _) = run_captured("func main() { while true { } }", "loop.zr");
+        assert!(result.unwrap_err().message.contains("endless loop"));
+    }
+}
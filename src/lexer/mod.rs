@@ -8,9 +8,11 @@ pub use token::{Span, Token, TokenKind};
 
 use crate::error::{SourceLocation, ZyraError, ZyraResult};
 
+/// Type suffixes an integer literal may carry, e.g. `255u8`
+pub const INT_SUFFIXES: &[&str] = &["i8", "i32", "i64", "u8", "u32", "u64"];
+
 /// Lexer for Zyra source code
-pub struct Lexer<'a> {
-    source: &'a str,
+pub struct Lexer {
     chars: Vec<char>,
     filename: String,
 
@@ -23,10 +25,9 @@ pub struct Lexer<'a> {
     start_column: usize,
 }
 
-impl<'a> Lexer<'a> {
-    pub fn new(source: &'a str, filename: &str) -> Self {
+impl Lexer {
+    pub fn new(source: &str, filename: &str) -> Self {
         Self {
-            source,
             chars: source.chars().collect(),
             filename: filename.to_string(),
             pos: 0,
@@ -158,6 +159,8 @@ impl<'a> Lexer<'a> {
             '<' => {
                 if self.match_char('=') {
                     TokenKind::LessEqual
+                } else if self.match_char('<') {
+                    TokenKind::ShiftLeft
                 } else {
                     TokenKind::Less
                 }
@@ -165,10 +168,14 @@ impl<'a> Lexer<'a> {
             '>' => {
                 if self.match_char('=') {
                     TokenKind::GreaterEqual
+                } else if self.match_char('>') {
+                    // The parser splits this again where it closes two generics
+                    TokenKind::ShiftRight
                 } else {
                     TokenKind::Greater
                 }
             }
+            '^' => TokenKind::Caret,
             '&' => {
                 if self.match_char('&') {
                     TokenKind::And
@@ -187,9 +194,17 @@ impl<'a> Lexer<'a> {
             // Lifetime or Char
             '\'' => self.scan_lifetime_or_char()?,
 
+            // Raw text block: """..."""
+            '"' if self.peek() == '"' && self.peek_next() == '"' => self.scan_text_block()?,
+
             // String literal
             '"' => self.scan_string()?,
 
+            // Raw string: r"..." or r#"..."#
+            'r' if self.peek() == '"' || (self.peek() == '#' && self.raw_string_follows()) => {
+                self.scan_raw_string()?
+            }
+
             // Number
             '0'..='9' => self.scan_number(c)?,
 
@@ -208,7 +223,8 @@ impl<'a> Lexer<'a> {
             }
         };
 
-        let lexeme = self.source[self.start..self.pos].to_string();
+        // Positions count chars, not bytes, so non-ASCII text can't split a slice
+        let lexeme: String = self.chars[self.start..self.pos].iter().collect();
         let span = Span::new(self.start, self.pos, self.start_line, self.start_column);
 
         Ok(Token::new(kind, span, lexeme))
@@ -223,17 +239,7 @@ impl<'a> Lexer<'a> {
 
         // Scan char/lifetime content
         let first_char = if self.peek() == '\\' {
-            self.advance(); // consume \
-            match self.advance() {
-                'n' => '\n',
-                't' => '\t',
-                'r' => '\r',
-                '\\' => '\\',
-                '\'' => '\'',
-                '"' => '"',
-                '0' => '\0',
-                c => return Err(self.error(&format!("Invalid escape sequence '\\{}'", c))),
-            }
+            self.scan_escape()?
         } else {
             self.advance()
         };
@@ -333,37 +339,8 @@ impl<'a> Lexer<'a> {
             }
 
             if self.peek() == '\\' {
-                self.advance();
-                match self.peek() {
-                    'n' => {
-                        self.advance();
-                        current_literal.push('\n');
-                    }
-                    't' => {
-                        self.advance();
-                        current_literal.push('\t');
-                    }
-                    'r' => {
-                        self.advance();
-                        current_literal.push('\r');
-                    }
-                    '\\' => {
-                        self.advance();
-                        current_literal.push('\\');
-                    }
-                    '"' => {
-                        self.advance();
-                        current_literal.push('"');
-                    }
-                    '$' => {
-                        // Escape $ sign
-                        self.advance();
-                        current_literal.push('$');
-                    }
-                    c => {
-                        return Err(self.error(&format!("Invalid escape sequence '\\{}'", c)));
-                    }
-                }
+                let c = self.scan_escape()?;
+                current_literal.push(c);
             } else {
                 current_literal.push(self.advance());
             }
@@ -385,40 +362,253 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn scan_number(&mut self, first: char) -> ZyraResult<TokenKind> {
-        let mut num_str = String::from(first);
-        let mut is_float = false;
+    /// Scan an escape sequence in a string or char literal, starting at its
+    /// backslash. Errors point at the escape itself, not the whole literal
+    fn scan_escape(&mut self) -> ZyraResult<char> {
+        let (at, line, column) = (self.pos, self.line, self.column);
+        self.advance(); // Consume \
+        let c = match self.peek() {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '\'' => '\'',
+            '"' => '"',
+            // Escape $ so "\${" isn't read as interpolation
+            '$' => '$',
+            'x' => {
+                self.advance();
+                let digits = self.take_hex_digits(2);
+                return match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if digits.len() == 2 && byte <= 0x7F => Ok(byte as char),
+                    _ => Err(self.escape_error(
+                        &format!(
+                            "Invalid escape '\\x{}': expected two hex digits up to 7F",
+                            digits
+                        ),
+                        at,
+                        (line, column),
+                    )),
+                };
+            }
+            'u' => {
+                self.advance();
+                if !self.match_char('{') {
+                    return Err(self.escape_error(
+                        "Invalid unicode escape: expected '{' after '\\u', as in '\\u{1F600}'",
+                        at,
+                        (line, column),
+                    ));
+                }
+                let digits = self.take_hex_digits(6);
+                if digits.is_empty() || !self.match_char('}') {
+                    return Err(self.escape_error(
+                        "Invalid unicode escape: expected 1 to 6 hex digits and a closing '}'",
+                        at,
+                        (line, column),
+                    ));
+                }
+                return u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| {
+                        self.escape_error(
+                            &format!("'\\u{{{}}}' is not a Unicode character", digits),
+                            at,
+                            (line, column),
+                        )
+                    });
+            }
+            '\0' | '\n' => {
+                return Err(self.escape_error("Expected an escape after '\\'", at, (line, column)))
+            }
+            other => {
+                self.advance();
+                return Err(self.escape_error(
+                    &format!("Invalid escape sequence '\\{}'", other),
+                    at,
+                    (line, column),
+                ));
+            }
+        };
+        self.advance();
+        Ok(c)
+    }
 
-        // Check for hex literal (0x or 0X prefix)
-        if first == '0' && (self.peek() == 'x' || self.peek() == 'X') {
-            self.advance(); // Consume 'x' or 'X'
-            let mut hex_str = String::new();
+    /// Up to `max` hex digits, consumed
+    fn take_hex_digits(&mut self, max: usize) -> String {
+        let mut digits = String::new();
+        while digits.len() < max && self.peek().is_ascii_hexdigit() {
+            digits.push(self.advance());
+        }
+        digits
+    }
 
-            while self.peek().is_ascii_hexdigit() {
-                hex_str.push(self.advance());
+    /// Error underlining an escape sequence from its backslash (at `at`) to
+    /// the current position
+    fn escape_error(&self, message: &str, at: usize, (line, column): (usize, usize)) -> ZyraError {
+        let mut location =
+            SourceLocation::new(&self.filename, line, column).with_snippet(&self.line_text(at));
+        location.length = self.pos - at;
+        ZyraError::syntax_error(message, location).with_suggestion(
+            "Escapes are \\n \\t \\r \\0 \\\\ \\' \\\" \\$, \\x41 for ASCII and \\u{1F600} for any character; use a raw string r\"...\" to keep backslashes",
+        )
+    }
+
+    /// Scan a raw text block after its first quote: `"""` ... `"""`, which
+    /// can hold quotes and backslashes as they are. A newline straight after
+    /// the opening quotes is dropped, and when the closing quotes are on a
+    /// line of their own, their indentation is taken off every line, so
+    /// ASCII art can be indented with the code around it
+    fn scan_text_block(&mut self) -> ZyraResult<TokenKind> {
+        self.advance();
+        self.advance(); // Consume the rest of the opening quotes
+        self.match_char('\r');
+        let mut line_start = self.match_char('\n');
+        if line_start {
+            self.line += 1;
+            self.column = 1;
+        }
+        let closing_indent = self.text_block_indent();
+        let indent = closing_indent.unwrap_or(0);
+
+        let mut value = String::new();
+        while !(self.peek() == '"' && self.peek_next() == '"' && self.peek_at(2) == '"') {
+            if self.is_at_end() {
+                return Err(self.error("Unterminated text block: expected closing \"\"\""));
+            }
+            if line_start {
+                // Skip the block's indentation, but no more of the line
+                for _ in 0..indent {
+                    if !matches!(self.peek(), ' ' | '\t') {
+                        break;
+                    }
+                    self.advance();
+                }
+                line_start = false;
+                continue;
+            }
+            if self.peek() == '\n' {
+                self.line += 1;
+                self.column = 0;
+                line_start = true;
             }
+            value.push(self.advance());
+        }
+        for _ in 0..3 {
+            self.advance(); // Consume closing quotes
+        }
 
-            if hex_str.is_empty() {
-                return Err(self.error("Expected hex digits after '0x'"));
+        // The closing quotes' own line ends the text, not a line of it
+        if closing_indent.is_some() && value.ends_with('\n') {
+            value.pop();
+            if value.ends_with('\r') {
+                value.pop();
             }
+        }
+        Ok(TokenKind::String(value))
+    }
 
-            let value = i64::from_str_radix(&hex_str, 16)
-                .map_err(|_| self.error(&format!("Invalid hex literal '0x{}'", hex_str)))?;
-            return Ok(TokenKind::Int(value));
+    /// Indentation of a text block's closing quotes, when they are alone on
+    /// their line (None otherwise, so no indentation is removed)
+    fn text_block_indent(&self) -> Option<usize> {
+        let end = (self.pos..self.chars.len())
+            .find(|&i| self.chars[i..].starts_with(&['"', '"', '"']))?;
+        let line_start = self.chars[..end].iter().rposition(|&c| c == '\n')? + 1;
+        let indent = &self.chars[line_start..end];
+        indent
+            .iter()
+            .all(|c| matches!(c, ' ' | '\t'))
+            .then_some(indent.len())
+    }
+
+    /// Whether `r` followed by hashes opens a raw string (`r#"`), rather
+    /// than being an identifier
+    fn raw_string_follows(&self) -> bool {
+        self.chars[self.pos..]
+            .iter()
+            .find(|&&c| c != '#')
+            .is_some_and(|&c| c == '"')
+    }
+
+    /// Scan a raw string after its `r`: no escapes or interpolation, and it
+    /// can span lines. `r#"..."#` can hold quotes; it ends at a quote followed
+    /// by as many hashes as it started with
+    fn scan_raw_string(&mut self) -> ZyraResult<TokenKind> {
+        let mut hashes = 0;
+        while self.match_char('#') {
+            hashes += 1;
+        }
+        self.advance(); // Consume opening quote
+
+        let mut value = String::new();
+        loop {
+            if self.is_at_end() {
+                return Err(self.error("Unterminated raw string literal"));
+            }
+            if self.peek() == '"' && (1..=hashes).all(|i| self.peek_at(i) == '#') {
+                break;
+            }
+            if self.peek() == '\n' {
+                self.line += 1;
+                self.column = 0;
+            }
+            value.push(self.advance());
         }
+        for _ in 0..=hashes {
+            self.advance(); // Consume closing quote and hashes
+        }
+        Ok(TokenKind::String(value))
+    }
+
+    fn scan_number(&mut self, first: char) -> ZyraResult<TokenKind> {
+        let mut num_str = String::from(first);
+        let mut is_float = false;
+
+        // Hex, octal and binary literals: 0xFF, 0o755, 0b1010
+        let radix = match self.peek() {
+            'x' | 'X' => Some((16, "hex")),
+            'o' | 'O' => Some((8, "octal")),
+            'b' | 'B' => Some((2, "binary")),
+            _ => None,
+        };
+        if let (Some((radix, name)), '0') = (radix, first) {
+            let prefix = self.advance();
+            let mut digits = String::new();
+            self.scan_digits(radix, &mut digits);
+
+            if self.peek().is_ascii_digit() {
+                return Err(self.error(&format!(
+                    "Invalid digit '{}' in {} literal",
+                    self.peek(),
+                    name
+                )));
+            }
+            if digits.is_empty() {
+                return Err(self.error(&format!("Expected {} digits after '0{}'", name, prefix)));
+            }
 
-        while self.peek().is_ascii_digit() {
-            num_str.push(self.advance());
+            let value = i64::from_str_radix(&digits, radix).map_err(|_| {
+                self.error(&format!(
+                    "Literal '0{}{}' is too large for an integer",
+                    prefix, digits
+                ))
+            })?;
+            return self.int_suffix(value);
         }
 
-        // Check for decimal part
-        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+        self.scan_digits(10, &mut num_str);
+
+        // Check for decimal part. A number right after a field access dot is
+        // a tuple index, so `pair.0.1` is two indices rather than a float
+        let tuple_index = self.start > 0
+            && self.chars[self.start - 1] == '.'
+            && (self.start < 2 || self.chars[self.start - 2] != '.');
+        if !tuple_index && self.peek() == '.' && self.peek_next().is_ascii_digit() {
             is_float = true;
             num_str.push(self.advance()); // Consume '.'
-
-            while self.peek().is_ascii_digit() {
-                num_str.push(self.advance());
-            }
+            self.scan_digits(10, &mut num_str);
         }
 
         if is_float {
@@ -430,7 +620,41 @@ impl<'a> Lexer<'a> {
             let value: i64 = num_str
                 .parse()
                 .map_err(|_| self.error(&format!("Invalid integer literal '{}'", num_str)))?;
-            Ok(TokenKind::Int(value))
+            self.int_suffix(value)
+        }
+    }
+
+    /// Read digits in `radix` into `digits`, skipping `_` separators as in
+    /// `1_000_000`
+    fn scan_digits(&mut self, radix: u32, digits: &mut String) {
+        loop {
+            let c = self.peek();
+            if c.is_digit(radix) {
+                digits.push(self.advance());
+            } else if c == '_' {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Read the type suffix of an integer literal, if it has one: `255u8`
+    fn int_suffix(&mut self, value: i64) -> ZyraResult<TokenKind> {
+        if !matches!(self.peek(), 'i' | 'u') {
+            return Ok(TokenKind::Int(value));
+        }
+        let mut suffix = String::new();
+        while self.peek().is_alphanumeric() || self.peek() == '_' {
+            suffix.push(self.advance());
+        }
+        match INT_SUFFIXES.iter().find(|known| **known == suffix) {
+            Some(known) => Ok(TokenKind::SuffixedInt(value, known)),
+            None => Err(self.error(&format!(
+                "Invalid suffix '{}' on integer literal, expected one of {}",
+                suffix,
+                INT_SUFFIXES.join(", ")
+            ))),
         }
     }
 
@@ -507,11 +731,11 @@ impl<'a> Lexer<'a> {
     }
 
     fn peek_next(&self) -> char {
-        if self.pos + 1 >= self.chars.len() {
-            '\0'
-        } else {
-            self.chars[self.pos + 1]
-        }
+        self.peek_at(1)
+    }
+
+    fn peek_at(&self, offset: usize) -> char {
+        self.chars.get(self.pos + offset).copied().unwrap_or('\0')
     }
 
     fn advance(&mut self) -> char {
@@ -532,22 +756,27 @@ impl<'a> Lexer<'a> {
     }
 
     fn error(&self, message: &str) -> ZyraError {
-        let line_start = self.source[..self.start]
-            .rfind('\n')
-            .map(|i| i + 1)
-            .unwrap_or(0);
-        let line_end = self.source[self.start..]
-            .find('\n')
-            .map(|i| self.start + i)
-            .unwrap_or(self.source.len());
-        let snippet = &self.source[line_start..line_end];
-
         ZyraError::syntax_error(
             message,
             SourceLocation::new(&self.filename, self.start_line, self.start_column)
-                .with_snippet(snippet),
+                .with_snippet(&self.line_text(self.start)),
         )
     }
+
+    /// Text of the line holding the char at `at`
+    fn line_text(&self, at: usize) -> String {
+        let line_start = self.chars[..at]
+            .iter()
+            .rposition(|&c| c == '\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let line_end = self.chars[at..]
+            .iter()
+            .position(|&c| c == '\n')
+            .map(|i| at + i)
+            .unwrap_or(self.chars.len());
+        self.chars[line_start..line_end].iter().collect()
+    }
 }
 
 #[cfg(test)]
@@ -566,6 +795,35 @@ mod tests {
         assert!(matches!(tokens[4].kind, TokenKind::Semicolon));
     }
 
+    #[test]
+    fn test_integer_suffixes() {
+        let tokens = Lexer::new("255u8 0xFFi64 7", "test.zr").tokenize().unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::SuffixedInt(255, "u8"));
+        assert_eq!(tokens[1].kind, TokenKind::SuffixedInt(255, "i64"));
+        assert_eq!(tokens[2].kind, TokenKind::Int(7));
+
+        let err = Lexer::new("let x = 5usize;", "test.zr")
+            .tokenize()
+            .unwrap_err();
+        assert!(err.message.contains("Invalid suffix 'usize'"));
+    }
+
+    #[test]
+    fn test_radix_literals_and_separators() {
+        let source = "0xFF_80_00 0o755 0b1010_u8 1_000_000 2_500.0_5";
+        let tokens = Lexer::new(source, "test.zr").tokenize().unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::Int(0xFF8000));
+        assert_eq!(tokens[1].kind, TokenKind::Int(0o755));
+        assert_eq!(tokens[2].kind, TokenKind::SuffixedInt(10, "u8"));
+        assert_eq!(tokens[3].kind, TokenKind::Int(1_000_000));
+        assert_eq!(tokens[4].kind, TokenKind::Float(2500.05));
+
+        let err = Lexer::new("0b102", "test.zr").tokenize().unwrap_err();
+        assert!(err.message.contains("Invalid digit '2' in binary literal"));
+        let err = Lexer::new("0o", "test.zr").tokenize().unwrap_err();
+        assert!(err.message.contains("Expected octal digits after '0o'"));
+    }
+
     #[test]
     fn test_string_literal() {
         let mut lexer = Lexer::new("\"hello world\"", "test.zr");
@@ -574,6 +832,53 @@ mod tests {
         assert!(matches!(&tokens[0].kind, TokenKind::String(s) if s == "hello world"));
     }
 
+    #[test]
+    fn test_raw_strings_and_text_blocks() {
+        let source =
+            "r\"C:\\dir\\n\" r#\"a \"quote\"\"# \"\"\"\n    /\\_/\\\n     x \"y\"\n    \"\"\" r";
+        let tokens = Lexer::new(source, "test.zr").tokenize().unwrap();
+
+        assert!(matches!(&tokens[0].kind, TokenKind::String(s) if s == "C:\\dir\\n"));
+        assert!(matches!(&tokens[1].kind, TokenKind::String(s) if s == "a \"quote\""));
+        assert!(matches!(&tokens[2].kind, TokenKind::String(s) if s == "/\\_/\\\n x \"y\""));
+        assert!(matches!(&tokens[3].kind, TokenKind::Identifier(s) if s == "r"));
+        assert_eq!(tokens[3].span.line, 4);
+
+        let err = Lexer::new("\"\"\"open", "test.zr").tokenize().unwrap_err();
+        assert!(err.message.contains("Unterminated text block"));
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        let source = r#""a\tb\\\"\u{1F600}\x41\$" '\u{e9}' '\''"#;
+        let tokens = Lexer::new(source, "test.zr").tokenize().unwrap();
+
+        assert!(matches!(&tokens[0].kind, TokenKind::String(s) if s == "a\tb\\\"😀A$"));
+        assert!(matches!(tokens[1].kind, TokenKind::Char('é')));
+        assert!(matches!(tokens[2].kind, TokenKind::Char('\'')));
+
+        let err = Lexer::new("let s = \"ok\\q\";", "test.zr")
+            .tokenize()
+            .unwrap_err();
+        assert!(err.message.contains("'\\q'"));
+        let location = err.location.as_ref().unwrap();
+        assert_eq!((location.column, location.length), (12, 2));
+
+        let err = Lexer::new("'\\u{D800}'", "test.zr").tokenize().unwrap_err();
+        assert!(err.message.contains("not a Unicode character"));
+    }
+
+    #[test]
+    fn test_non_ascii_text() {
+        let mut lexer = Lexer::new("\"héllo\" 'ö' größe", "test.zr");
+        let tokens = lexer.tokenize().unwrap();
+
+        assert!(matches!(&tokens[0].kind, TokenKind::String(s) if s == "héllo"));
+        assert!(matches!(tokens[1].kind, TokenKind::Char('ö')));
+        assert_eq!(tokens[1].lexeme, "'ö'");
+        assert_eq!(tokens[2].lexeme, "größe");
+    }
+
     #[test]
     fn test_operators() {
         let mut lexer = Lexer::new("+ - * / == != < > <= >= && ||", "test.zr");
@@ -27,6 +27,8 @@ impl Span {
 pub enum TokenKind {
     // Literals
     Int(i64),
+    /// Integer literal with a type suffix, e.g. `255u8`
+    SuffixedInt(i64, &'static str),
     Float(f64),
     String(String),
     Char(char),
@@ -55,10 +57,13 @@ pub enum TokenKind {
     SelfType, // self
     Break,
     Continue,
-    Match, // match keyword for pattern matching
-    Ref,   // ref keyword for ref bindings
-    As,    // as keyword for type casting
-    Move,  // move keyword for closure captures
+    Match,  // match keyword for pattern matching
+    Ref,    // ref keyword for ref bindings
+    As,     // as keyword for type casting
+    Move,   // move keyword for closure captures
+    Pub,    // pub keyword for module exports
+    Const,  // const keyword for compile-time constants
+    Static, // static keyword for `static mut` globals
 
     // Types
     TypeInt,
@@ -87,6 +92,9 @@ pub enum TokenKind {
     And,          // &&
     Or,           // ||
     Not,          // !
+    Caret,        // ^
+    ShiftLeft,    // <<
+    ShiftRight,   // >>
 
     // Compound assignment operators
     PlusAssign,    // +=
@@ -148,6 +156,9 @@ impl TokenKind {
             "ref" => Some(TokenKind::Ref),
             "as" => Some(TokenKind::As),
             "move" => Some(TokenKind::Move),
+            "pub" => Some(TokenKind::Pub),
+            "const" => Some(TokenKind::Const),
+            "static" => Some(TokenKind::Static),
             "Int" => Some(TokenKind::TypeInt),
             "Float" => Some(TokenKind::TypeFloat),
             "Bool" => Some(TokenKind::TypeBool),
@@ -163,6 +174,7 @@ impl fmt::Display for TokenKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             TokenKind::Int(n) => write!(f, "{}", n),
+            TokenKind::SuffixedInt(n, suffix) => write!(f, "{}{}", n, suffix),
             TokenKind::Float(n) => write!(f, "{}", n),
             TokenKind::String(s) => write!(f, "\"{}\"", s),
             TokenKind::Char(c) => write!(f, "'{}'", c),
@@ -202,6 +214,9 @@ impl fmt::Display for TokenKind {
             TokenKind::Ref => write!(f, "ref"),
             TokenKind::As => write!(f, "as"),
             TokenKind::Move => write!(f, "move"),
+            TokenKind::Pub => write!(f, "pub"),
+            TokenKind::Const => write!(f, "const"),
+            TokenKind::Static => write!(f, "static"),
             TokenKind::TypeInt => write!(f, "Int"),
             TokenKind::TypeFloat => write!(f, "Float"),
             TokenKind::TypeBool => write!(f, "Bool"),
@@ -223,6 +238,9 @@ impl fmt::Display for TokenKind {
             TokenKind::And => write!(f, "&&"),
             TokenKind::Or => write!(f, "||"),
             TokenKind::Not => write!(f, "!"),
+            TokenKind::Caret => write!(f, "^"),
+            TokenKind::ShiftLeft => write!(f, "<<"),
+            TokenKind::ShiftRight => write!(f, ">>"),
             TokenKind::PlusAssign => write!(f, "+="),
             TokenKind::MinusAssign => write!(f, "-="),
             TokenKind::StarAssign => write!(f, "*="),
@@ -5,8 +5,14 @@
 //! compile-time memory safety via ownership, borrowing, and lifetime checking.
 //! This design enables fast, predictable, and garbage-collection-free execution.
 
+pub mod assets;
+pub mod cache;
 pub mod compiler;
 pub mod error;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod interface;
+pub mod learn;
 pub mod lexer;
 pub mod parser;
 pub mod resolver;
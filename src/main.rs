@@ -2,34 +2,90 @@
 //!
 //! Usage:
 //!   zyra run <file.zr>     - Run a Zyra program
+//!   zyra run <file.zyc>    - Run a compiled bytecode file
 //!   zyra check <file.zr>   - Check syntax and types without running
+//!   --profile-analysis     - Time the analyzer's passes and items (check)
+//!   zyra fix <file.zr>     - Apply the suggested fixes for type errors
 //!   zyra compile <file.zr> - Compile to bytecode
 //!   zyra build <file.zr>   - Alias for compile
+//!   zyra test <file.zr>    - Run the `test func` functions of a program
+//!   --release              - Use the release profile from zyra.toml (run/compile)
+//!   --deny-warnings        - Fail on lint warnings (check/run/test/compile)
+//!   --max-errors <n>       - Report up to n type errors before stopping (default 20)
+//!   --headless[=frames]    - Simulate game windows: no OS window, fixed delta time
+//!   --assert-no-alloc-in <frame|function> - Report heap allocations made there (run)
+//!   zyra repl              - Start an interactive session
+//!   zyra examples [run|show <name>] - List, run or print a bundled example
+//!   zyra learn [check|list|reset]   - Interactive tutorial
+//!   zyra init <name>       - Create a project (--template game|cli|lib, --git)
 
 use std::env;
 use std::fs;
+use std::io::{self, BufRead, Write};
 use std::path::Path;
 use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 use colored::Colorize;
-use zyra::compiler::Compiler;
-use zyra::error::ZyraError;
-use zyra::lexer::Lexer;
-use zyra::parser::Parser;
+use zyra::assets;
+use zyra::cache::{BuildCache, CACHE_DIR};
+use zyra::compiler::{Bytecode, Compiler, OverflowMode};
+use zyra::error::{Edit, Severity, ZyraError, DEFAULT_MAX_ERRORS};
+use zyra::learn;
+use zyra::lexer::{Lexer, Span, Token};
+use zyra::parser::{Block, Edition, Feature, ModuleItems, Parser, Program, Statement};
 use zyra::resolver::ModuleResolver;
-use zyra::semantic::SemanticAnalyzer;
-use zyra::vm::VM;
+use zyra::semantic::{AnalysisProfile, ArgCheck, Pass, SemanticAnalyzer};
+use zyra::stdlib::capability::Capability;
+use zyra::stdlib::cli;
+use zyra::stdlib::game;
+use zyra::stdlib::io::capture;
+use zyra::vm::alloc_check::Region;
+use zyra::vm::{Value, VM};
 
 /// Project configuration from zyra.toml
 struct ProjectConfig {
+    /// Directory containing zyra.toml
+    root: std::path::PathBuf,
     main: Option<String>,
     output: Option<String>,
+    /// `overflow` from [profile.debug]
+    debug_overflow: Option<OverflowMode>,
+    /// `overflow` from [profile.release]
+    release_overflow: Option<OverflowMode>,
+    /// `edition` from [project]
+    edition: Edition,
+    /// `unstable` from [project]: experimental features the code may use
+    unstable: Vec<Feature>,
+    /// Path dependencies from [dependencies] as (package name, package root)
+    dependencies: Vec<(String, std::path::PathBuf)>,
+    /// `needs` from [project]: the only capabilities the program is granted
+    needs: Option<Vec<Capability>>,
+    /// `assets` from [project], relative to the project root
+    assets: Vec<String>,
+    /// `skip` from [check]: literal argument checks turned off
+    skipped_checks: Vec<ArgCheck>,
 }
 
 /// Configuration validation result
 enum ConfigResult {
     Valid(ProjectConfig),
     InvalidMainEntry(String),
+    InvalidOverflow(String),
+    InvalidEdition(String),
+    /// The `zyra` version requirement isn't met by this build
+    UnsupportedZyra(String),
+    /// A [dependencies] entry that can't be used, with the reason
+    InvalidDependency(String, String),
+    /// A `needs` entry that isn't a capability
+    InvalidCapability(String),
+    /// A [check] `skip` entry that isn't a check
+    InvalidArgCheck(String),
+    /// An `unstable` entry that isn't a feature of this version
+    InvalidFeature(String),
+    /// A [project] key that should be a list of strings, with its value
+    InvalidList(String, String),
     NoConfig,
 }
 
@@ -65,11 +121,120 @@ fn parse_project_config(toml_path: &Path) -> ConfigResult {
     // Simple TOML parsing for main and output
     let mut main: Option<String> = None;
     let mut output: Option<String> = None;
+    let mut debug_overflow: Option<OverflowMode> = None;
+    let mut release_overflow: Option<OverflowMode> = None;
+    let mut edition = Edition::default();
+    let mut unstable = Vec::new();
+    let mut dependencies = Vec::new();
+    let mut needs = None;
+    let mut assets = Vec::new();
+    let mut skipped_checks = Vec::new();
+    let mut section = "";
+    let root = toml_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
 
     for line in content.lines() {
         let line = line.trim();
-        // Parse "main = ..." in [build] section
-        if line.starts_with("main") && !line.starts_with("main_entry") {
+        if line.starts_with('[') {
+            section = line;
+        }
+        // Parse `name = { path = "..." }` in [dependencies] section
+        if section == "[dependencies]" && !line.starts_with('#') {
+            if let Some((name, spec)) = line.split_once('=') {
+                let name = name.trim().to_string();
+                match dependency_path(spec.trim()) {
+                    Ok(path) if root.join(path).join("src").is_dir() => {
+                        dependencies.push((name, root.join(path)));
+                    }
+                    Ok(path) => {
+                        return ConfigResult::InvalidDependency(
+                            name,
+                            format!("no package with a src/ directory at '{}'", path),
+                        )
+                    }
+                    Err(reason) => return ConfigResult::InvalidDependency(name, reason),
+                }
+            }
+            continue;
+        }
+        // Parse "overflow = ..." in [profile.debug] / [profile.release]
+        if line.starts_with("overflow") {
+            if let Some(value) = line.split('=').nth(1) {
+                let val = value.trim().trim_matches('"');
+                let mode = match OverflowMode::parse(val) {
+                    Some(mode) => mode,
+                    None => return ConfigResult::InvalidOverflow(val.to_string()),
+                };
+                match section {
+                    "[profile.debug]" => debug_overflow = Some(mode),
+                    "[profile.release]" => release_overflow = Some(mode),
+                    _ => {}
+                }
+            }
+        }
+        // Parse "edition", "zyra", "needs", "assets" and "unstable" in [project] section
+        if section == "[project]" {
+            if let Some((key, value)) = line.split_once('=') {
+                let val = value.trim().trim_matches('"');
+                match key.trim() {
+                    "edition" => match Edition::parse(val) {
+                        Some(e) => edition = e,
+                        None => return ConfigResult::InvalidEdition(val.to_string()),
+                    },
+                    "zyra" if !zyra_version_satisfies(val) => {
+                        return ConfigResult::UnsupportedZyra(val.to_string())
+                    }
+                    key @ ("needs" | "assets" | "unstable") => {
+                        let Some(items) = string_list(value) else {
+                            return ConfigResult::InvalidList(
+                                key.to_string(),
+                                value.trim().to_string(),
+                            );
+                        };
+                        if key == "assets" {
+                            assets = items;
+                        } else if key == "unstable" {
+                            for item in items {
+                                match Feature::parse(&item) {
+                                    Some(feature) => unstable.push(feature),
+                                    None => return ConfigResult::InvalidFeature(item),
+                                }
+                            }
+                        } else {
+                            let mut granted = Vec::new();
+                            for item in items {
+                                match Capability::parse(&item) {
+                                    Some(capability) => granted.push(capability),
+                                    None => return ConfigResult::InvalidCapability(item),
+                                }
+                            }
+                            needs = Some(granted);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        // Parse "skip" in [check] section
+        if section == "[check]" {
+            if let Some(("skip", value)) = line.split_once('=').map(|(k, v)| (k.trim(), v)) {
+                let Some(items) = string_list(value) else {
+                    return ConfigResult::InvalidList("skip".to_string(), value.trim().to_string());
+                };
+                for item in items {
+                    match ArgCheck::parse(&item) {
+                        Some(check) => skipped_checks.push(check),
+                        None => return ConfigResult::InvalidArgCheck(item),
+                    }
+                }
+            }
+        }
+        // Parse "entry = ..." (or "main = ...") in [build] section
+        if line.starts_with("entry")
+            || (line.starts_with("main") && !line.starts_with("main_entry"))
+        {
             if let Some(value) = line.split('=').nth(1) {
                 let val = value.trim().trim_matches('"').to_string();
                 if !val.is_empty() {
@@ -103,7 +268,173 @@ fn parse_project_config(toml_path: &Path) -> ConfigResult {
         }
     }
 
-    ConfigResult::Valid(ProjectConfig { main, output })
+    ConfigResult::Valid(ProjectConfig {
+        root,
+        main,
+        output,
+        debug_overflow,
+        release_overflow,
+        edition,
+        unstable,
+        dependencies,
+        needs,
+        assets,
+        skipped_checks,
+    })
+}
+
+/// Items of a one-line string array: `["fs", "audio"]`
+fn string_list(value: &str) -> Option<Vec<String>> {
+    let items = value.trim().strip_prefix('[')?.strip_suffix(']')?;
+    items
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            item.strip_prefix('"')
+                .and_then(|item| item.strip_suffix('"'))
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// Path of a dependency written as `{ path = "../mylib" }`; git and registry
+/// dependencies aren't supported yet
+fn dependency_path(spec: &str) -> Result<&str, String> {
+    let fields = match spec.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+        Some(fields) => fields,
+        None => return Err("registry dependencies are not supported yet".to_string()),
+    };
+    let mut path = None;
+    for field in fields.split(',') {
+        if let Some((key, value)) = field.split_once('=') {
+            match key.trim() {
+                "path" => path = Some(value.trim().trim_matches('"')),
+                "git" => return Err("git dependencies are not supported yet".to_string()),
+                _ => {}
+            }
+        }
+    }
+    path.ok_or_else(|| "expected { path = \"...\" }".to_string())
+}
+
+/// Check a `zyra = ">=1.0.2"` requirement against this build's version
+/// A bare version means the same as `>=`; anything unparseable is rejected.
+fn zyra_version_satisfies(requirement: &str) -> bool {
+    let version =
+        |v: &str| -> Option<Vec<u32>> { v.trim().split('.').map(|p| p.parse().ok()).collect() };
+    let (exact, wanted) = match requirement.strip_prefix(">=") {
+        Some(rest) => (false, rest),
+        None => match requirement.strip_prefix('=') {
+            Some(rest) => (true, rest),
+            None => (false, requirement),
+        },
+    };
+    match (version(wanted), version(env!("CARGO_PKG_VERSION"))) {
+        (Some(wanted), Some(current)) if exact => current == wanted,
+        (Some(wanted), Some(current)) => current >= wanted,
+        _ => false,
+    }
+}
+
+/// Edition a source file is parsed with: `edition` in the project's zyra.toml,
+/// or the default edition when there is none
+fn edition_for(path: &str) -> Edition {
+    match find_project_config_for_file(Some(path)) {
+        ConfigResult::Valid(config) => config.edition,
+        _ => Edition::default(),
+    }
+}
+
+/// Unstable features a source file may use: `unstable` in the project's
+/// zyra.toml, or none when there is no project
+fn features_for(path: &str) -> Vec<Feature> {
+    match find_project_config_for_file(Some(path)) {
+        ConfigResult::Valid(config) => config.unstable,
+        _ => Vec::new(),
+    }
+}
+
+/// Module resolver for a source file: imports are looked up next to the file,
+/// then in `src/` of the project the file belongs to, whose build cache
+/// holds the module interfaces
+fn resolver_for(path: &str, edition: Edition) -> ModuleResolver {
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+    let resolver = ModuleResolver::new(base_dir)
+        .with_edition(edition)
+        .with_features(&features_for(path));
+    match find_project_config_for_file(Some(path)) {
+        ConfigResult::Valid(config) => config.dependencies.iter().fold(
+            resolver
+                .with_source_root(&config.root.join("src"))
+                .with_interface_dir(&config.root.join(CACHE_DIR)),
+            |resolver, (name, root)| resolver.with_dependency(name, root),
+        ),
+        _ => resolver,
+    }
+}
+
+/// Build cache of the project a source file belongs to; files outside a
+/// zyra.toml project aren't cached
+fn build_cache_for(path: &str) -> Option<BuildCache> {
+    match find_project_config_for_file(Some(path)) {
+        ConfigResult::Valid(config) => Some(BuildCache::new(&config.root)),
+        _ => None,
+    }
+}
+
+/// VM for a source file's program, granted only the capabilities its
+/// project declares in `needs` (all of them when there is no `needs`)
+fn vm_for(path: &str) -> VM {
+    match find_project_config_for_file(Some(path)) {
+        ConfigResult::Valid(ProjectConfig {
+            needs: Some(needs), ..
+        }) => VM::new().with_capabilities(needs),
+        _ => VM::new(),
+    }
+}
+
+/// Overflow behavior for a source file: the selected profile's setting in the
+/// project's zyra.toml, or failing in debug and wrapping in release builds
+/// when none is configured
+fn overflow_mode_for(path: &str, release: bool) -> OverflowMode {
+    let configured = match find_project_config_for_file(Some(path)) {
+        ConfigResult::Valid(config) if release => config.release_overflow,
+        ConfigResult::Valid(config) => config.debug_overflow,
+        _ => None,
+    };
+    configured.unwrap_or(OverflowMode::for_profile(release))
+}
+
+/// Set by --deny-warnings: lint warnings fail the command
+static DENY_WARNINGS: AtomicBool = AtomicBool::new(false);
+
+/// Set by --max-errors: how many errors the analyzer reports before stopping
+static MAX_ERRORS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_ERRORS);
+
+/// Parser for a source file, with the project's edition and unstable features
+fn parser_for(path: &str, tokens: Vec<Token>, edition: Edition) -> Parser {
+    Parser::new(tokens)
+        .with_edition(edition)
+        .with_features(&features_for(path))
+        .with_max_errors(MAX_ERRORS.load(Ordering::Relaxed))
+}
+
+/// Semantic analyzer for a source file, without the literal argument checks
+/// the project's zyra.toml skips
+fn analyzer_for(path: &str) -> SemanticAnalyzer {
+    let analyzer = match find_project_config_for_file(Some(path)) {
+        ConfigResult::Valid(config) => {
+            SemanticAnalyzer::new().without_arg_checks(&config.skipped_checks)
+        }
+        _ => SemanticAnalyzer::new(),
+    };
+    let analyzer = analyzer.with_max_errors(MAX_ERRORS.load(Ordering::Relaxed));
+    if DENY_WARNINGS.load(Ordering::Relaxed) {
+        analyzer.with_denied_warnings()
+    } else {
+        analyzer
+    }
 }
 
 /// Get the main entry file, either from arg or zyra.toml
@@ -121,10 +452,10 @@ fn get_main_entry(args: &[String], arg_index: usize) -> Option<String> {
         ConfigResult::Valid(config) => {
             if config.main.is_none() {
                 eprintln!(
-                    "{}: main is not specified in zyra.toml",
+                    "{}: entry is not specified in zyra.toml",
                     "ConfigError".red()
                 );
-                eprintln!("  Add [build] section with main = \"main.zr\"");
+                eprintln!("  Add [build] section with entry = \"main.zr\"");
                 return None;
             }
 
@@ -133,10 +464,11 @@ fn get_main_entry(args: &[String], arg_index: usize) -> Option<String> {
                 return Some(file);
             }
 
+            // The entry is relative to the project root, wherever zyra.toml was found
             let entry = config.main.unwrap();
-            let entry_path = Path::new(&entry);
+            let entry_path = config.root.join(&entry);
             if entry_path.exists() {
-                return Some(entry);
+                return Some(entry_path.to_string_lossy().to_string());
             } else {
                 eprintln!("{}: main '{}' not found", "ConfigError".red(), entry);
                 eprintln!("  The file specified in zyra.toml does not exist.");
@@ -152,6 +484,89 @@ fn get_main_entry(args: &[String], arg_index: usize) -> Option<String> {
             eprintln!("  main must end with .zr, .zy, or .za");
             return None;
         }
+        ConfigResult::InvalidOverflow(value) => {
+            eprintln!(
+                "{}: overflow '{}' is not a valid setting",
+                "ConfigError".red(),
+                value
+            );
+            eprintln!("  overflow must be \"error\", \"wrap\" or \"saturate\"");
+            return None;
+        }
+        ConfigResult::InvalidEdition(value) => {
+            let supported: Vec<&str> = Edition::ALL.iter().map(|e| e.as_str()).collect();
+            eprintln!(
+                "{}: edition '{}' is not supported by Zyra {}",
+                "ConfigError".red(),
+                value,
+                env!("CARGO_PKG_VERSION")
+            );
+            eprintln!("  Supported editions: {}", supported.join(", "));
+            eprintln!(
+                "  Upgrade Zyra to use a newer edition, or set edition = \"{}\"",
+                Edition::default().as_str()
+            );
+            return None;
+        }
+        ConfigResult::InvalidDependency(name, reason) => {
+            eprintln!("{}: dependency '{}': {}", "ConfigError".red(), name, reason);
+            eprintln!(
+                "  Dependencies are local packages: {} = {{ path = \"../{}\" }}",
+                name, name
+            );
+            return None;
+        }
+        ConfigResult::InvalidCapability(name) => {
+            let known: Vec<&str> = Capability::ALL.iter().map(|c| c.as_str()).collect();
+            eprintln!(
+                "{}: '{}' in needs is not a capability",
+                "ConfigError".red(),
+                name
+            );
+            eprintln!("  Known capabilities: {}", known.join(", "));
+            return None;
+        }
+        ConfigResult::InvalidArgCheck(name) => {
+            let known: Vec<&str> = ArgCheck::ALL.iter().map(|c| c.as_str()).collect();
+            eprintln!(
+                "{}: '{}' in [check] skip is not a check",
+                "ConfigError".red(),
+                name
+            );
+            eprintln!("  Known checks: {}", known.join(", "));
+            return None;
+        }
+        ConfigResult::InvalidFeature(name) => {
+            let known: Vec<&str> = Feature::ALL.iter().map(|f| f.as_str()).collect();
+            eprintln!(
+                "{}: '{}' in unstable is not a feature of Zyra {}",
+                "ConfigError".red(),
+                name,
+                env!("CARGO_PKG_VERSION")
+            );
+            eprintln!("  Unstable features: {}", known.join(", "));
+            return None;
+        }
+        ConfigResult::InvalidList(key, value) => {
+            eprintln!(
+                "{}: {} = {} is not a list of strings",
+                "ConfigError".red(),
+                key,
+                value
+            );
+            eprintln!("  Write it on one line, e.g. {} = [\"...\", \"...\"]", key);
+            return None;
+        }
+        ConfigResult::UnsupportedZyra(requirement) => {
+            eprintln!(
+                "{}: project requires zyra '{}', but this is Zyra {}",
+                "ConfigError".red(),
+                requirement,
+                env!("CARGO_PKG_VERSION")
+            );
+            eprintln!("  Upgrade Zyra, or change the zyra requirement in zyra.toml");
+            return None;
+        }
         ConfigResult::NoConfig => {
             // No zyra.toml found - allow running explicit file
             if let Some(file) = explicit_file {
@@ -164,6 +579,69 @@ fn get_main_entry(args: &[String], arg_index: usize) -> Option<String> {
 
 fn main() {
     let args: Vec<String> = env::args().collect();
+    // --release selects the release profile and may appear anywhere
+    let release = args.iter().any(|arg| arg == "--release");
+    let args: Vec<String> = args.into_iter().filter(|arg| arg != "--release").collect();
+    // --deny-warnings too: lint warnings become errors (check, run, test, compile)
+    if args.iter().any(|arg| arg == "--deny-warnings") {
+        DENY_WARNINGS.store(true, Ordering::Relaxed);
+    }
+    let args: Vec<String> = args
+        .into_iter()
+        .filter(|arg| arg != "--deny-warnings")
+        .collect();
+    // --headless[=frames] too: game windows are simulated, so games run in CI
+    if let Some(arg) = args.iter().find(|arg| arg.starts_with("--headless")) {
+        let frames = match &arg["--headless".len()..] {
+            "" => Some(game::HEADLESS_FRAME_LIMIT),
+            rest => rest.strip_prefix('=').and_then(|n| n.parse().ok()),
+        };
+        match frames {
+            Some(frames) => game::set_headless(frames),
+            None => {
+                eprintln!(
+                    "{}: expected --headless or --headless=<frames>, got '{}'",
+                    "Error".red(),
+                    arg
+                );
+                process::exit(1);
+            }
+        }
+    }
+    let mut args: Vec<String> = args
+        .into_iter()
+        .filter(|arg| !arg.starts_with("--headless"))
+        .collect();
+    // --assert-no-alloc-in <region> reports heap allocations made in a game
+    // frame or a function
+    let mut alloc_region = None;
+    if let Some(at) = args.iter().position(|arg| arg == "--assert-no-alloc-in") {
+        if at + 1 >= args.len() {
+            eprintln!(
+                "{}: expected --assert-no-alloc-in frame or --assert-no-alloc-in <function>",
+                "Error".red()
+            );
+            process::exit(1);
+        }
+        alloc_region = Some(Region::parse(&args.remove(at + 1)));
+        args.remove(at);
+    }
+    // --max-errors <n> caps the errors one check reports
+    if let Some(at) = args.iter().position(|arg| arg == "--max-errors") {
+        match args.get(at + 1).and_then(|n| n.parse::<usize>().ok()) {
+            Some(max_errors) if max_errors > 0 => {
+                MAX_ERRORS.store(max_errors, Ordering::Relaxed);
+                args.drain(at..at + 2);
+            }
+            _ => {
+                eprintln!(
+                    "{}: expected --max-errors <n> with n at least 1",
+                    "Error".red()
+                );
+                process::exit(1);
+            }
+        }
+    }
 
     if args.len() < 2 {
         print_usage();
@@ -171,22 +649,33 @@ fn main() {
     }
 
     let command = &args[1];
+    // zyra check --profile-analysis times the analyzer's passes
+    let profile_analysis = args.iter().any(|arg| arg == "--profile-analysis");
+    let check_args: Vec<String> = args
+        .iter()
+        .filter(|arg| *arg != "--profile-analysis")
+        .cloned()
+        .collect();
 
     match command.as_str() {
         "run" => match get_main_entry(&args, 2) {
-            Some(file) => run_file(&file),
+            Some(file) => {
+                // The script sees only the arguments after its path
+                cli::set_script_args(&file, args.iter().skip(3).cloned().collect());
+                run_file(&file, release, alloc_region)
+            }
             None => {
                 eprintln!(
                     "{}",
                     "Error: No file specified and no zyra.toml found".red()
                 );
-                eprintln!("Usage: zyra run <file.zr>");
+                eprintln!("Usage: zyra run <file.zr | file.zyc>");
                 eprintln!("  Or create a project with: zyra init <name>");
                 process::exit(1);
             }
         },
-        "check" => match get_main_entry(&args, 2) {
-            Some(file) => check_file(&file),
+        "check" => match get_main_entry(&check_args, 2) {
+            Some(file) => check_file(&file, profile_analysis),
             None => {
                 eprintln!(
                     "{}",
@@ -196,8 +685,19 @@ fn main() {
                 process::exit(1);
             }
         },
+        "fix" => match get_main_entry(&args, 2) {
+            Some(file) => fix_file(&file),
+            None => {
+                eprintln!(
+                    "{}",
+                    "Error: No file specified and no zyra.toml found".red()
+                );
+                eprintln!("Usage: zyra fix <file.zr>");
+                process::exit(1);
+            }
+        },
         "build" | "compile" => match get_main_entry(&args, 2) {
-            Some(file) => build_file(&file),
+            Some(file) => build_file(&file, release),
             None => {
                 eprintln!(
                     "{}",
@@ -207,6 +707,32 @@ fn main() {
                 process::exit(1);
             }
         },
+        "test" => match get_main_entry(&args, 2) {
+            Some(file) => test_file(&file, release),
+            None => {
+                eprintln!(
+                    "{}",
+                    "Error: No file specified and no zyra.toml found".red()
+                );
+                eprintln!("Usage: zyra test <file.zr>");
+                process::exit(1);
+            }
+        },
+        "selftest" => match get_main_entry(&args, 2) {
+            Some(file) => selftest_file(&file, release),
+            None => {
+                eprintln!(
+                    "{}",
+                    "Error: No file specified and no zyra.toml found".red()
+                );
+                eprintln!("Usage: zyra selftest <file.zr>");
+                process::exit(1);
+            }
+        },
+        "repl" => run_repl(),
+        "examples" => examples_command(&args[2..]),
+        "learn" => learn_command(&args[2..]),
+        "assets" => assets_command(&args[2..]),
         "help" | "--help" | "-h" => {
             print_usage();
         }
@@ -214,8 +740,24 @@ fn main() {
             println!("{}", "Zyra Programming Language v1.0.2".cyan().bold());
         }
         "init" => {
-            let name = args.get(2).map(|s| s.as_str()).unwrap_or(".");
-            if let Err(e) = init_project(name) {
+            let mut name = ".";
+            let mut template = "default";
+            let mut git = false;
+            let mut rest = args[2..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--git" => git = true,
+                    "--template" => match rest.next() {
+                        Some(t) => template = t,
+                        None => {
+                            eprintln!("{}: --template needs a name", "Error".red());
+                            process::exit(1);
+                        }
+                    },
+                    other => name = other,
+                }
+            }
+            if let Err(e) = init_project(name, template, git) {
                 eprintln!("{}", e);
                 process::exit(1);
             }
@@ -223,7 +765,7 @@ fn main() {
         _ => {
             // Check if it's a file path (for convenience: `zyra file.zr`)
             if is_zyra_file(command) {
-                run_file(command);
+                run_file(command, release, alloc_region);
             } else {
                 eprintln!("{}: Unknown command '{}'", "Error".red(), command);
                 print_usage();
@@ -238,7 +780,7 @@ fn print_usage() {
     println!();
     println!("{}", "Usage:".yellow().bold());
     println!(
-        "  {} {}     Run a Zyra program",
+        "  {} {}     Run a Zyra program (.zr or compiled .zyc)",
         "zyra run".green(),
         "<file>".white()
     );
@@ -247,6 +789,11 @@ fn print_usage() {
         "zyra check".green(),
         "<file>".white()
     );
+    println!(
+        "  {} {}     Apply suggested fixes for type errors",
+        "zyra fix".green(),
+        "<file>".white()
+    );
     println!(
         "  {} {} Compile to bytecode",
         "zyra compile".green(),
@@ -257,6 +804,34 @@ fn print_usage() {
         "zyra build".green(),
         "<file>".white()
     );
+    println!(
+        "  {} {}    Run test functions",
+        "zyra test".green(),
+        "<file>".white()
+    );
+    println!(
+        "  {} {} Run as compiled and from .zyc, compare output",
+        "zyra selftest".green(),
+        "<file>".white()
+    );
+    println!(
+        "  {}            Start interactive session",
+        "zyra repl".green()
+    );
+    println!(
+        "  {} {} Run a bundled example (list without arguments)",
+        "zyra examples run".green(),
+        "<name>".white()
+    );
+    println!(
+        "  {}           Interactive tutorial (check, list, reset)",
+        "zyra learn".green()
+    );
+    println!(
+        "  {} {} Pack a directory of images into one atlas (--out atlas.png)",
+        "zyra assets atlas".green(),
+        "<dir>".white()
+    );
     println!("  {}           Show this help", "zyra help".green());
     println!("  {}        Show version", "zyra version".green());
     println!(
@@ -264,6 +839,41 @@ fn print_usage() {
         "zyra init".green(),
         "<name>".white()
     );
+    println!(
+        "    {} {}  Start from a template: {}",
+        "--template".green(),
+        "<name>".white(),
+        INIT_TEMPLATES.join(", ")
+    );
+    println!(
+        "    {}              Also create a .gitignore",
+        "--git".green()
+    );
+    println!();
+    println!(
+        "  {}        Use the release profile (run, compile, test)",
+        "--release".green()
+    );
+    println!(
+        "  {}  Fail on lint warnings (check, run, test, compile)",
+        "--deny-warnings".green()
+    );
+    println!(
+        "  {}     Report up to n errors before stopping (default 20)",
+        "--max-errors <n>".green()
+    );
+    println!(
+        "  {} Simulate game windows for CI (always on in zyra test, or ZYRA_HEADLESS=1)",
+        "--headless[=frames]".green()
+    );
+    println!(
+        "  {} Report heap allocations in each game frame or a function (run)",
+        "--assert-no-alloc-in <frame|fn>".green()
+    );
+    println!(
+        "  {} Time each analysis pass and function (check)",
+        "--profile-analysis".green()
+    );
     println!();
     println!("Supported file extensions: {}", ".zr, .zy, .za".cyan());
 }
@@ -306,56 +916,153 @@ fn read_source_file(path: &str) -> Result<String, ZyraError> {
     })
 }
 
-fn run_file(path: &str) {
-    match run_file_internal(path) {
-        Ok(_) => {}
+fn run_file(path: &str, release: bool, alloc_region: Option<Region>) {
+    match run_file_internal(path, release, alloc_region) {
+        Ok(0) => {}
+        Ok(code) => process::exit(code),
         Err(e) => {
-            eprintln!("{}", e);
+            print_error(path, &e);
             process::exit(1);
         }
     }
 }
 
-fn run_file_internal(path: &str) -> Result<(), ZyraError> {
+/// Print an error with the source lines it points at, read from their file.
+/// Locations that don't name a file are in `path`.
+fn print_error(path: &str, error: &ZyraError) {
+    let error = error.clone().in_file(path);
+    eprintln!("{}", error.render(&|file| fs::read_to_string(file).ok()));
+}
+
+/// Print the lint warnings found in a source file, failing if any of them
+/// were denied
+fn report_warnings(path: &str, analyzer: &SemanticAnalyzer) -> Result<(), ZyraError> {
+    let source = fs::read_to_string(path).ok();
+    let mut denied = 0;
+    for warning in analyzer.warnings() {
+        let mut warning = warning.clone();
+        if let Some(location) = &mut warning.location {
+            location.file = path.to_string();
+        }
+        if warning.severity == Severity::Error {
+            denied += 1;
+        }
+        eprint!("{}", warning.render(&|_| source.clone()));
+    }
+    if denied > 0 {
+        return Err(ZyraError::new(
+            "DeniedWarnings",
+            &format!("{} warning(s) denied by --deny-warnings", denied),
+            None,
+        ));
+    }
+    Ok(())
+}
+
+/// VM for `zyra run`, checking allocations when --assert-no-alloc-in is given
+fn run_vm(path: &str, alloc_region: Option<Region>) -> VM {
+    match alloc_region {
+        Some(region) => vm_for(path).with_alloc_check(region),
+        None => vm_for(path),
+    }
+}
+
+/// Execute bytecode and return the exit status the program asked for.
+/// A runtime error fails with that status (or 1), and is only printed
+/// if the program's panic hook didn't already report it. Allocations
+/// found by --assert-no-alloc-in are printed after the run and fail it.
+fn execute(mut vm: VM, bytecode: &zyra::compiler::bytecode::Bytecode) -> Result<i32, ZyraError> {
+    let status = match vm.run(bytecode) {
+        Ok(_) => vm.exit_code(),
+        Err(e) => {
+            if !vm.panic_reported() {
+                print_error(&bytecode.source_file, &e);
+            }
+            if vm.exit_code() != 0 {
+                vm.exit_code()
+            } else {
+                1
+            }
+        }
+    };
+    let allocations = vm.alloc_report(bytecode);
+    for allocation in &allocations {
+        eprintln!("{}", allocation);
+    }
+    Ok(if status == 0 && !allocations.is_empty() {
+        1
+    } else {
+        status
+    })
+}
+
+fn run_file_internal(
+    path: &str,
+    release: bool,
+    alloc_region: Option<Region>,
+) -> Result<i32, ZyraError> {
     // Check if it's a compiled bytecode file
     if path.ends_with(".zyc") {
-        return run_bytecode_file(path);
+        return run_bytecode_file(path, alloc_region);
     }
 
     let source = read_source_file(path)?;
+    let edition = edition_for(path);
+    let overflow = overflow_mode_for(path, release);
+
+    // Unchanged projects run straight from the build cache
+    let cache = build_cache_for(path);
+    let options = format!("{:?} {:?} {:?}", overflow, edition, features_for(path));
+    if let Some(bytecode) = cache
+        .as_ref()
+        .and_then(|cache| cache.load(Path::new(path), &source, &options))
+    {
+        return execute(run_vm(path, alloc_region), &bytecode);
+    }
 
     // Lexical analysis
     let mut lexer = Lexer::new(&source, path);
     let tokens = lexer.tokenize()?;
 
     // Parsing
-    // Parsing
-    let mut parser = Parser::new(tokens);
+    let mut parser = parser_for(path, tokens, edition);
     let mut ast = parser.parse()?;
 
     // Module Resolution
-    let file_path = Path::new(path);
-    let base_dir = file_path.parent().unwrap_or_else(|| Path::new("."));
-    let mut resolver = ModuleResolver::new(base_dir);
+    let mut resolver = resolver_for(path, edition);
     resolver.resolve_imports(&mut ast)?;
 
-    // Semantic analysis
-    let mut analyzer = SemanticAnalyzer::new();
+    // Semantic analysis, skipping modules unchanged since their interface was written
+    let mut analyzer = analyzer_for(path).with_interfaces(resolver.interfaces());
     analyzer.analyze(&ast)?;
+    report_warnings(path, &analyzer)?;
+    // Interfaces that can't be written only cost the next run a re-check
+    let _ = resolver.write_interfaces();
 
     // Compilation
-    let mut compiler = Compiler::new();
-    let bytecode = compiler.compile(&ast)?;
+    let mut compiler = Compiler::new().with_overflow(overflow);
+    let mut bytecode = compiler.compile(&ast)?;
+    bytecode.source_file = path.to_string();
+
+    // A cache that can't be written only costs the next run a recompile.
+    // Builds with warnings aren't cached, so every run reports them again
+    // (and --deny-warnings keeps failing)
+    if let Some(cache) = cache.as_ref().filter(|_| analyzer.warnings().is_empty()) {
+        let _ = cache.store(
+            Path::new(path),
+            &source,
+            &options,
+            resolver.loaded_files(),
+            &bytecode,
+        );
+    }
 
     // Execution
-    let mut vm = VM::new();
-    vm.run(&bytecode)?;
-
-    Ok(())
+    execute(run_vm(path, alloc_region), &bytecode)
 }
 
 /// Run a pre-compiled bytecode file
-fn run_bytecode_file(path: &str) -> Result<(), ZyraError> {
+fn run_bytecode_file(path: &str, alloc_region: Option<Region>) -> Result<i32, ZyraError> {
     use zyra::compiler::bytecode::Bytecode;
 
     // Read bytecode file
@@ -369,17 +1076,14 @@ fn run_bytecode_file(path: &str) -> Result<(), ZyraError> {
 
     // Deserialize bytecode
     let bytecode = Bytecode::deserialize(&data)
-        .map_err(|e| ZyraError::new("BytecodeError", e.as_str(), None))?;
+        .map_err(|e| ZyraError::new("BytecodeError", &format!("{}: {}", path, e), None))?;
 
     // Execute
-    let mut vm = VM::new();
-    vm.run(&bytecode)?;
-
-    Ok(())
+    execute(run_vm(path, alloc_region), &bytecode)
 }
 
-fn check_file(path: &str) {
-    match check_file_internal(path) {
+fn check_file(path: &str, profile: bool) {
+    match check_file_internal(path, profile) {
         Ok(summary) => {
             println!("{}", "═══════════════════════════════════════════".green());
             println!("{}", format!("✓ Check passed: '{}'", path).green().bold());
@@ -395,22 +1099,61 @@ fn check_file(path: &str) {
             println!("  ✓ Ownership checking    - moves tracked");
             println!("  ✓ Borrow checking       - references safe");
             println!("  ✓ Lifetime checking     - no dangling refs");
+            if summary.interface_count > 0 {
+                println!(
+                    "  ✓ Module interfaces     - {} unchanged, not re-checked",
+                    summary.interface_count
+                );
+            }
+            if summary.asset_count > 0 {
+                println!(
+                    "  ✓ Assets                - {} declared, all found",
+                    summary.asset_count
+                );
+            }
+            if let Some(profile) = &summary.profile {
+                print_analysis_profile(profile);
+            }
             println!();
             println!("{}", "No errors found!".green().bold());
         }
         Err(e) => {
-            eprintln!("{}", e);
+            print_error(path, &e);
             process::exit(1);
         }
     }
 }
 
+fn print_analysis_profile(profile: &AnalysisProfile) {
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    println!();
+    println!("{}", "Analysis Profile:".cyan().bold());
+    for pass in Pass::ALL {
+        println!("  {:<22}{:>9.3} ms", pass.as_str(), ms(profile.pass(pass)));
+    }
+    println!("  {:<22}{:>9.3} ms", "total", ms(profile.total()));
+    let slowest = profile.slowest_items(10);
+    if !slowest.is_empty() {
+        println!();
+        println!("{}", "Slowest Items:".cyan().bold());
+        for (name, elapsed) in slowest {
+            println!("  {:<22}{:>9.3} ms", name, ms(elapsed));
+        }
+    }
+}
+
 struct CheckSummary {
     token_count: usize,
     statement_count: usize,
+    /// Assets declared in zyra.toml, all found
+    asset_count: usize,
+    /// Imported modules taken from their interface instead of re-checked
+    interface_count: usize,
+    /// Analyzer timings, with --profile-analysis
+    profile: Option<AnalysisProfile>,
 }
 
-fn check_file_internal(path: &str) -> Result<CheckSummary, ZyraError> {
+fn check_file_internal(path: &str, profile: bool) -> Result<CheckSummary, ZyraError> {
     let source = read_source_file(path)?;
 
     // Lexical analysis
@@ -419,56 +1162,484 @@ fn check_file_internal(path: &str) -> Result<CheckSummary, ZyraError> {
     let token_count = tokens.len();
 
     // Parsing
-    let mut parser = Parser::new(tokens);
-    let ast = parser.parse()?;
+    let edition = edition_for(path);
+    let mut parser = parser_for(path, tokens, edition);
+    let mut ast = parser.parse()?;
     let statement_count = ast.statements.len();
 
-    // Semantic analysis (includes ownership, borrow, and lifetime checking)
-    let mut analyzer = SemanticAnalyzer::new();
+    // Module Resolution, so calls into imported modules are checked too
+    let mut resolver = resolver_for(path, edition);
+    resolver.resolve_imports(&mut ast)?;
+
+    // Semantic analysis (includes ownership, borrow, and lifetime checking);
+    // modules unchanged since their interface was written aren't checked again
+    let interface_count = resolver.interfaces().len();
+    let mut analyzer = analyzer_for(path).with_interfaces(resolver.interfaces());
+    if profile {
+        analyzer = analyzer.with_profiling();
+    }
     analyzer.analyze(&ast)?;
+    report_warnings(path, &analyzer)?;
+    let _ = resolver.write_interfaces();
+
+    // Assets the project declares must all be there
+    let mut asset_count = 0;
+    if let ConfigResult::Valid(config) = find_project_config_for_file(Some(path)) {
+        let missing: Vec<&str> = config
+            .assets
+            .iter()
+            .filter(|asset| !assets::asset_exists(&config.root, asset))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(ZyraError::new(
+                "AssetError",
+                &format!(
+                    "asset(s) listed in zyra.toml not found: {}",
+                    missing.join(", ")
+                ),
+                None,
+            ));
+        }
+        asset_count = config.assets.len();
+    }
 
     Ok(CheckSummary {
         token_count,
         statement_count,
+        asset_count,
+        interface_count,
+        profile: analyzer.profile().cloned(),
     })
 }
 
-fn build_file(path: &str) {
-    match build_file_internal(path) {
-        Ok(output_path) => {
-            println!("✓ Compiled '{}' to '{}'", path, output_path);
+fn fix_file(path: &str) {
+    match fix_file_internal(path) {
+        Ok((fixed, remaining)) => {
+            if fixed > 0 {
+                println!(
+                    "{}",
+                    format!("✓ Applied {} fix(es) to '{}'", fixed, path)
+                        .green()
+                        .bold()
+                );
+            }
+            match remaining {
+                Some(e) => {
+                    print_error(path, &e);
+                    process::exit(1);
+                }
+                None if fixed == 0 => println!("Nothing to fix in '{}'", path),
+                None => {}
+            }
         }
         Err(e) => {
-            eprintln!("{}", e);
+            print_error(path, &e);
             process::exit(1);
         }
     }
 }
 
-fn build_file_internal(path: &str) -> Result<String, ZyraError> {
-    let source = read_source_file(path)?;
+/// Apply the fixes attached to semantic errors one at a time, re-checking
+/// after each, and write the result back. Returns how many were applied
+/// and the errors left that have no fix.
+fn fix_file_internal(path: &str) -> Result<(usize, Option<ZyraError>), ZyraError> {
+    let original = read_source_file(path)?;
+    let mut source = original.clone();
+    let mut fixed = 0;
+
+    let remaining = loop {
+        let errors = match analyze_source(path, &source) {
+            Ok(()) => break None,
+            Err(e) => e,
+        };
+        let Some(error) = errors.all().find(|e| !e.fix.is_empty()) else {
+            break Some(errors);
+        };
+        let candidate = Edit::apply_all(&source, &error.fix);
+        // A fix has to make its error go away; one that doesn't was
+        // aimed at another file, such as an imported module
+        match analyze_source(path, &candidate) {
+            Err(e) if e.kind == "SyntaxError" || e.all().any(|e| same_error(e, error)) => {
+                break Some(errors)
+            }
+            _ => {
+                source = candidate;
+                fixed += 1;
+            }
+        }
+    };
+
+    if source != original {
+        fs::write(path, &source).map_err(|e| {
+            ZyraError::new(
+                "FileError",
+                &format!("Could not write file '{}': {}", path, e),
+                None,
+            )
+        })?;
+    }
+    Ok((fixed, remaining))
+}
+
+/// Whether two errors report the same problem at the same place
+fn same_error(a: &ZyraError, b: &ZyraError) -> bool {
+    let line = |e: &ZyraError| e.location.as_ref().map(|l| l.line);
+    a.message == b.message && line(a) == line(b)
+}
+
+/// Run the front end over a source text, up to semantic analysis
+fn analyze_source(path: &str, source: &str) -> Result<(), ZyraError> {
+    let tokens = Lexer::new(source, path).tokenize()?;
+    let edition = edition_for(path);
+    let mut ast = parser_for(path, tokens, edition).parse()?;
+    resolver_for(path, edition).resolve_imports(&mut ast)?;
+    analyzer_for(path).analyze(&ast)
+}
+
+/// Outcome of one test function
+struct TestOutcome {
+    name: String,
+    duration: Duration,
+    error: Option<ZyraError>,
+}
+
+fn test_file(path: &str, release: bool) {
+    let outcomes = match test_file_internal(path, release) {
+        Ok(outcomes) => outcomes,
+        Err(e) => {
+            print_error(path, &e);
+            process::exit(1);
+        }
+    };
+
+    if outcomes.is_empty() {
+        println!("No tests found in '{}'", path);
+        return;
+    }
+
+    let failed: Vec<&TestOutcome> = outcomes.iter().filter(|t| t.error.is_some()).collect();
+    if !failed.is_empty() {
+        println!();
+        println!("{}", "Failures:".red().bold());
+        for test in &failed {
+            if let Some(e) = &test.error {
+                println!("  {} {}", "---".red(), test.name.white().bold());
+                for line in e.to_string().lines() {
+                    println!("    {}", line);
+                }
+            }
+        }
+    }
+
+    let width = outcomes
+        .iter()
+        .map(|t| t.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Test".len());
+    println!();
+    println!("{}", "Test Summary:".cyan().bold());
+    println!("  {:<width$}  {:<6}  {:>9}", "Test", "Result", "Time");
+    for test in &outcomes {
+        let result = if test.error.is_none() {
+            format!("{:<6}", "ok").green()
+        } else {
+            format!("{:<6}", "FAILED").red()
+        };
+        println!(
+            "  {:<width$}  {}  {:>7.2}ms",
+            test.name,
+            result,
+            test.duration.as_secs_f64() * 1000.0
+        );
+    }
+    println!();
+
+    let passed = outcomes.len() - failed.len();
+    let summary = format!("{} passed; {} failed", passed, failed.len());
+    if failed.is_empty() {
+        println!("{} {}", "test result: ok.".green().bold(), summary);
+    } else {
+        println!("{} {}", "test result: FAILED.".red().bold(), summary);
+        process::exit(1);
+    }
+}
+
+/// Compile a program once and run each of its tests in a fresh VM
+fn test_file_internal(path: &str, release: bool) -> Result<Vec<TestOutcome>, ZyraError> {
+    let source = read_source_file(path)?;
 
     // Lexical analysis
     let mut lexer = Lexer::new(&source, path);
     let tokens = lexer.tokenize()?;
 
     // Parsing
-    let mut parser = Parser::new(tokens);
+    let edition = edition_for(path);
+    let mut parser = parser_for(path, tokens, edition);
+    let mut ast = parser.parse()?;
+
+    // Module Resolution
+    let mut resolver = resolver_for(path, edition);
+    resolver.resolve_imports(&mut ast)?;
+
+    // Test files don't need an entry point of their own
+    if !ast
+        .statements
+        .iter()
+        .any(|stmt| declaration_name(stmt) == Some("main"))
+    {
+        ast.statements.push(Statement::Function {
+            name: "main".to_string(),
+            lifetimes: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            body: Block {
+                statements: Vec::new(),
+                expression: None,
+                span: Span::new(0, 0, 1, 1),
+            },
+            span: Span::new(0, 0, 1, 1),
+        });
+    }
+
+    // Semantic analysis
+    let mut analyzer = analyzer_for(path);
+    analyzer.analyze(&ast)?;
+    report_warnings(path, &analyzer)?;
+
+    // Compilation
+    let mut compiler = Compiler::new().with_overflow(overflow_mode_for(path, release));
+    let mut bytecode = compiler.compile(&ast)?;
+    bytecode.source_file = path.to_string();
+
+    // Tests never open real windows; a game loop in one ends after a fixed
+    // number of simulated frames
+    if !game::is_headless() {
+        game::set_headless(game::HEADLESS_FRAME_LIMIT);
+    }
+
+    let mut outcomes = Vec::new();
+    for name in &ast.tests {
+        print!("test {} ... ", name);
+        let _ = io::stdout().flush();
+
+        let start = Instant::now();
+        let result = match bytecode.functions.get(name) {
+            Some(func) if !func.params.is_empty() => Err(ZyraError::new(
+                "TestError",
+                &format!("Test '{}' must not have parameters", name),
+                None,
+            )),
+            _ => vm_for(path).run_function(&bytecode, name).map(|_| ()),
+        };
+        let duration = start.elapsed();
+
+        match &result {
+            Ok(()) => println!("{}", "ok".green()),
+            Err(_) => println!("{}", "FAILED".red()),
+        }
+        outcomes.push(TestOutcome {
+            name: name.clone(),
+            duration,
+            error: result.err(),
+        });
+    }
+
+    Ok(outcomes)
+}
+
+/// A way of executing compiled bytecode. `zyra selftest` runs a program on
+/// every backend; they must agree, or one of them miscompiles.
+///
+/// There is only one execution backend, the VM: both run it, one on the
+/// compiler's bytecode and one on that bytecode after a trip through the .zyc
+/// format, so a disagreement points at the serializer.
+#[derive(Clone, Copy)]
+enum Backend {
+    /// Bytecode straight from the compiler
+    Interpreter,
+    /// Bytecode written to and read back from the .zyc format
+    BytecodeFile,
+}
+
+impl Backend {
+    const ALL: [Backend; 2] = [Backend::Interpreter, Backend::BytecodeFile];
+
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Interpreter => "interpreter",
+            Backend::BytecodeFile => "bytecode file",
+        }
+    }
+
+    fn prepare(self, bytecode: &Bytecode) -> Result<Bytecode, ZyraError> {
+        match self {
+            Backend::Interpreter => Ok(bytecode.clone()),
+            Backend::BytecodeFile => Bytecode::deserialize(&bytecode.serialize())
+                .map_err(|e| ZyraError::new("BytecodeError", &e, None)),
+        }
+    }
+}
+
+/// What a program did on one backend
+#[derive(PartialEq)]
+struct BackendRun {
+    output: String,
+    /// Error the program stopped with, if any
+    error: Option<String>,
+    exit_code: i32,
+}
+
+fn selftest_file(path: &str, release: bool) {
+    let runs = match selftest_file_internal(path, release) {
+        Ok(runs) => runs,
+        Err(e) => {
+            print_error(path, &e);
+            process::exit(1);
+        }
+    };
+
+    let (reference_backend, reference) = &runs[0];
+    let mut mismatches = 0;
+    for (backend, run) in &runs {
+        if run == reference {
+            println!("{:<16} {}", backend.name(), "ok".green());
+            continue;
+        }
+        mismatches += 1;
+        println!("{:<16} {}", backend.name(), "DIFFERS".red().bold());
+        println!(
+            "{}",
+            difference_report((*reference_backend, reference), (*backend, run))
+        );
+    }
+
+    if mismatches == 0 {
+        println!(
+            "{} {} runs agree",
+            "selftest passed:".green().bold(),
+            runs.len()
+        );
+    } else {
+        println!(
+            "{} {} of {} runs disagree with the {}",
+            "selftest FAILED:".red().bold(),
+            mismatches,
+            runs.len(),
+            reference_backend.name()
+        );
+        process::exit(1);
+    }
+}
+
+/// Where a backend's run first departs from the reference run
+fn difference_report(
+    (reference_backend, reference): (Backend, &BackendRun),
+    (backend, run): (Backend, &BackendRun),
+) -> String {
+    let expected: Vec<&str> = reference
+        .output
+        .lines()
+        .chain(reference.error.as_deref())
+        .collect();
+    let actual: Vec<&str> = run.output.lines().chain(run.error.as_deref()).collect();
+    match (0..expected.len().max(actual.len())).find(|&i| expected.get(i) != actual.get(i)) {
+        Some(line) => format!(
+            "  first difference at line {}:\n    {}: {}\n    {}: {}",
+            line + 1,
+            reference_backend.name(),
+            expected.get(line).unwrap_or(&"<end>"),
+            backend.name(),
+            actual.get(line).unwrap_or(&"<end>")
+        ),
+        None => format!("  exit code {} vs {}", reference.exit_code, run.exit_code),
+    }
+}
+
+/// Compile a program once and run it on every backend with its output captured
+fn selftest_file_internal(
+    path: &str,
+    release: bool,
+) -> Result<Vec<(Backend, BackendRun)>, ZyraError> {
+    let source = read_source_file(path)?;
+
+    // Lexical analysis
+    let mut lexer = Lexer::new(&source, path);
+    let tokens = lexer.tokenize()?;
+
+    // Parsing
+    let edition = edition_for(path);
+    let mut parser = parser_for(path, tokens, edition);
+    let mut ast = parser.parse()?;
+
+    // Module Resolution
+    let mut resolver = resolver_for(path, edition);
+    resolver.resolve_imports(&mut ast)?;
+
+    // Semantic analysis
+    let mut analyzer = analyzer_for(path);
+    analyzer.analyze(&ast)?;
+
+    // Compilation
+    let mut compiler = Compiler::new().with_overflow(overflow_mode_for(path, release));
+    let mut bytecode = compiler.compile(&ast)?;
+    bytecode.source_file = path.to_string();
+
+    let mut runs = Vec::new();
+    for backend in Backend::ALL {
+        let program = backend.prepare(&bytecode)?;
+        let mut vm = VM::new();
+        let (result, output) = capture(|| vm.run(&program));
+        runs.push((
+            backend,
+            BackendRun {
+                output,
+                error: result.err().map(|e| e.message.clone()),
+                exit_code: vm.exit_code(),
+            },
+        ));
+    }
+    Ok(runs)
+}
+
+fn build_file(path: &str, release: bool) {
+    match build_file_internal(path, release) {
+        Ok(output_path) => {
+            println!("✓ Compiled '{}' to '{}'", path, output_path);
+        }
+        Err(e) => {
+            print_error(path, &e);
+            process::exit(1);
+        }
+    }
+}
+
+fn build_file_internal(path: &str, release: bool) -> Result<String, ZyraError> {
+    let source = read_source_file(path)?;
+
+    // Lexical analysis
+    let mut lexer = Lexer::new(&source, path);
+    let tokens = lexer.tokenize()?;
+
+    // Parsing
+    let edition = edition_for(path);
+    let mut parser = parser_for(path, tokens, edition);
     let mut ast = parser.parse()?;
 
     // Module Resolution - merge imported modules
-    let file_path = Path::new(path);
-    let base_dir = file_path.parent().unwrap_or_else(|| Path::new("."));
-    let mut resolver = ModuleResolver::new(base_dir);
+    let mut resolver = resolver_for(path, edition);
     resolver.resolve_imports(&mut ast)?;
 
     // Semantic analysis
-    let mut analyzer = SemanticAnalyzer::new();
+    let mut analyzer = analyzer_for(path);
     analyzer.analyze(&ast)?;
+    report_warnings(path, &analyzer)?;
 
     // Compilation
-    let mut compiler = Compiler::new();
-    let bytecode = compiler.compile(&ast)?;
+    let mut compiler = Compiler::new().with_overflow(overflow_mode_for(path, release));
+    let mut bytecode = compiler.compile(&ast)?;
+    bytecode.source_file = path.to_string();
 
     // Write bytecode to file
     let mut output_path = Path::new(path).with_extension("zyc");
@@ -519,8 +1690,658 @@ fn build_file_internal(path: &str) -> Result<String, ZyraError> {
     Ok(output_str)
 }
 
+/// Example programs bundled into the binary: (name, description, source)
+const EXAMPLES: &[(&str, &str, &str)] = &[
+    (
+        "hello",
+        "The smallest Zyra program",
+        include_str!("../examples/hello.zr"),
+    ),
+    (
+        "fizzbuzz",
+        "Loops and conditions",
+        include_str!("../examples/fizzbuzz.zr"),
+    ),
+    (
+        "shapes",
+        "Structs, methods, traits and enums",
+        include_str!("../examples/shapes.zr"),
+    ),
+    (
+        "ownership",
+        "Moves, borrows and mutable references",
+        include_str!("../examples/ownership.zr"),
+    ),
+    (
+        "pong",
+        "A window, a game loop and keyboard input",
+        include_str!("../examples/pong.zr"),
+    ),
+];
+
+/// `zyra examples`, `zyra examples run <name>` and `zyra examples show <name>`
+fn examples_command(args: &[String]) {
+    let action = args.first().map(String::as_str).unwrap_or("list");
+    let example = match (action, args.get(1)) {
+        ("list", _) => {
+            println!("{}", "Examples:".yellow().bold());
+            for (name, description, _) in EXAMPLES {
+                println!("  {:<10} {}", name.green(), description);
+            }
+            println!();
+            println!("Run one with: {}", "zyra examples run <name>".cyan());
+            println!(
+                "Print its code with: {}",
+                "zyra examples show <name>".cyan()
+            );
+            return;
+        }
+        ("run" | "show", Some(name)) => match EXAMPLES.iter().find(|(n, _, _)| n == name) {
+            Some(example) => example,
+            None => {
+                let names: Vec<&str> = EXAMPLES.iter().map(|(n, _, _)| *n).collect();
+                eprintln!("{}: Unknown example '{}'", "Error".red(), name);
+                eprintln!("  Available examples: {}", names.join(", "));
+                process::exit(1);
+            }
+        },
+        _ => {
+            eprintln!("Usage: zyra examples [run|show <name>]");
+            process::exit(1);
+        }
+    };
+
+    let (name, _, source) = example;
+    if action == "show" {
+        print!("{}", source);
+        return;
+    }
+    match run_example(name, source) {
+        Ok(0) => {}
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Run a bundled example with the default edition and overflow behavior
+fn run_example(name: &str, source: &str) -> Result<i32, ZyraError> {
+    let path = format!("examples/{}.zr", name);
+
+    // Lexical analysis
+    let mut lexer = Lexer::new(source, &path);
+    let tokens = lexer.tokenize()?;
+
+    // Parsing
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse()?;
+
+    // Semantic analysis
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast)?;
+
+    // Compilation
+    let mut compiler = Compiler::new();
+    let mut bytecode = compiler.compile(&ast)?;
+    bytecode.source_file = path;
+
+    // Execution
+    execute(VM::new(), &bytecode)
+}
+
+/// `zyra assets atlas <dir> [--out atlas.png]` packs the images under a
+/// directory into one texture atlas plus its manifest
+fn assets_command(args: &[String]) {
+    let usage = || -> ! {
+        eprintln!("Usage: zyra assets atlas <dir> [--out atlas.png]");
+        process::exit(1);
+    };
+    let mut dir = None;
+    let mut out = "atlas.png";
+    match args.first().map(String::as_str) {
+        Some("atlas") => {
+            let mut rest = args[1..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--out" | "-o" => out = rest.next().unwrap_or_else(|| usage()),
+                    other => dir = Some(other),
+                }
+            }
+        }
+        _ => usage(),
+    }
+    let Some(dir) = dir else { usage() };
+
+    let out = Path::new(out);
+    match assets::build_atlas(Path::new(dir), out) {
+        Ok(entries) => {
+            println!(
+                "{} {} sprites into {} ({})",
+                "Packed".green().bold(),
+                entries.len(),
+                out.display(),
+                assets::manifest_path(out).display()
+            );
+            if let Some(first) = entries.first() {
+                println!(
+                    "Load one with: {}",
+                    format!("load_sprite(\"{}#{}\")", out.display(), first.name).cyan()
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("{}: {}", "Error".red(), e);
+            process::exit(1);
+        }
+    }
+}
+
+/// `zyra learn` shows the current lesson; `check` grades it, `list` shows
+/// progress and `reset` starts over. Exercises live in ./learn/.
+fn learn_command(args: &[String]) {
+    let mut progress = learn::Progress::load(Path::new("learn"));
+    let fail = |e: io::Error| -> ! {
+        eprintln!("{}: {}", "Error".red(), e);
+        process::exit(1);
+    };
+
+    match args.first().map(String::as_str) {
+        None => match progress.current() {
+            Some(lesson) => show_lesson(&progress, lesson),
+            None => println!("{}", "You have finished every lesson!".green().bold()),
+        },
+        Some("list") => {
+            println!("{}", "Lessons:".yellow().bold());
+            for (i, lesson) in learn::LESSONS.iter().enumerate() {
+                let mark = if progress.is_done(lesson) {
+                    "✓".green()
+                } else {
+                    " ".normal()
+                };
+                println!("  {} {}. {}", mark, i + 1, lesson.title);
+            }
+        }
+        Some("reset") => {
+            progress.reset().unwrap_or_else(|e| fail(e));
+            println!("Progress cleared. Your exercise files were kept.");
+        }
+        Some("check") => {
+            let Some(lesson) = progress.current() else {
+                println!("{}", "You have finished every lesson!".green().bold());
+                return;
+            };
+            let path = progress.prepare(lesson).unwrap_or_else(|e| fail(e));
+            let path_str = path.to_string_lossy().to_string();
+            let source = fs::read_to_string(&path).unwrap_or_else(|e| fail(e));
+
+            match learn::check(lesson, &source, &path_str) {
+                learn::Outcome::Passed => {
+                    progress.complete(lesson).unwrap_or_else(|e| fail(e));
+                    println!("{}", format!("✓ {} passed!", lesson.title).green().bold());
+                    println!();
+                    match progress.current() {
+                        Some(next) => show_lesson(&progress, next),
+                        None => println!("{}", "You have finished every lesson!".green().bold()),
+                    }
+                }
+                learn::Outcome::WrongOutput { expected, actual } => {
+                    println!("{}", "✗ Not quite: the output doesn't match.".red().bold());
+                    println!("{}", "Expected:".yellow());
+                    for line in expected.lines() {
+                        println!("  {}", line);
+                    }
+                    println!("{}", "Your program printed:".yellow());
+                    if actual.is_empty() {
+                        println!("  (nothing)");
+                    }
+                    for line in actual.lines() {
+                        println!("  {}", line);
+                    }
+                    process::exit(1);
+                }
+                learn::Outcome::Failed(e) => {
+                    println!("{}", "✗ Your program has an error:".red().bold());
+                    print_error(&path_str, &e);
+                    process::exit(1);
+                }
+            }
+        }
+        Some(other) => {
+            eprintln!("{}: Unknown learn command '{}'", "Error".red(), other);
+            eprintln!("Usage: zyra learn [check|list|reset]");
+            process::exit(1);
+        }
+    }
+}
+
+/// Print a lesson's instructions, creating its exercise file if needed
+fn show_lesson(progress: &learn::Progress, lesson: &learn::Lesson) {
+    let path = match progress.prepare(lesson) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("{}: {}", "Error".red(), e);
+            process::exit(1);
+        }
+    };
+    let number = learn::LESSONS
+        .iter()
+        .position(|l| l.id == lesson.id)
+        .map_or(0, |i| i + 1);
+
+    println!(
+        "{}",
+        format!(
+            "Lesson {}/{}: {}",
+            number,
+            learn::LESSONS.len(),
+            lesson.title
+        )
+        .cyan()
+        .bold()
+    );
+    println!();
+    println!("{}", lesson.instructions);
+    println!();
+    println!("  Edit:  {}", path.display().to_string().cyan());
+    println!("  Then:  {}", "zyra learn check".green());
+}
+
+/// Name of the synthetic function each REPL entry is compiled into
+const REPL_ENTRY: &str = "__repl__";
+
+/// Interactive session state kept alive between REPL entries
+struct ReplSession {
+    resolver: ModuleResolver,
+    analyzer: SemanticAnalyzer,
+    vm: VM,
+    /// Functions, types, and imports entered so far (recompiled with every entry)
+    declarations: Vec<Statement>,
+}
+
+impl ReplSession {
+    fn new() -> Self {
+        Self {
+            resolver: ModuleResolver::new(Path::new(".")),
+            analyzer: SemanticAnalyzer::new(),
+            vm: VM::new(),
+            declarations: Vec::new(),
+        }
+    }
+
+    /// Check, compile, and run one complete entry
+    fn eval(&mut self, source: &str) -> Result<Option<Value>, ZyraError> {
+        let (mut entry, body) = parse_repl_entry(source)?;
+        self.resolver.resolve_imports(&mut entry)?;
+        self.analyzer.analyze_repl(&entry.statements, &body)?;
+
+        // Redefining a function or type replaces the earlier definition
+        for decl in entry.statements {
+            if let Some(name) = declaration_name(&decl).map(str::to_string) {
+                self.declarations
+                    .retain(|d| declaration_name(d) != Some(name.as_str()));
+            }
+            self.declarations.push(decl);
+        }
+
+        let mut program = Program {
+            statements: self.declarations.clone(),
+            tests: Vec::new(),
+            exports: Vec::new(),
+            imported: ModuleItems::default(),
+            module_statements: 0,
+        };
+        program.statements.push(Statement::Function {
+            name: REPL_ENTRY.to_string(),
+            lifetimes: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            span: body.span,
+            body,
+        });
+
+        let mut compiler = Compiler::new();
+        let bytecode = compiler.compile(&program)?;
+        self.vm.run_repl(&bytecode, REPL_ENTRY)
+    }
+}
+
+fn run_repl() {
+    println!("{}", "Zyra Programming Language v1.0.2".cyan().bold());
+    println!(
+        "Type {} for help, {} to exit",
+        ":help".green(),
+        ":quit".green()
+    );
+
+    let mut session = ReplSession::new();
+    let stdin = io::stdin();
+    let mut buffer = String::new();
+
+    loop {
+        let prompt = if buffer.is_empty() { "zyra> " } else { "...> " };
+        print!("{}", prompt.cyan());
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                println!();
+                break;
+            }
+            Ok(_) => {}
+        }
+
+        if buffer.is_empty() {
+            match line.trim() {
+                "" => continue,
+                ":quit" | ":q" | "exit" => break,
+                ":help" => {
+                    print_repl_help();
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        // Keep reading until every brace, paren, and bracket is closed
+        buffer.push_str(&line);
+        if unclosed_delimiters(&buffer) > 0 {
+            continue;
+        }
+
+        let source = std::mem::take(&mut buffer);
+        match session.eval(&source) {
+            Ok(Some(value)) => println!("{}", value),
+            Ok(None) => {}
+            Err(e) => eprintln!("{}", e.in_file("<repl>").render(&|_| Some(source.clone()))),
+        }
+    }
+}
+
+fn print_repl_help() {
+    println!("{}", "REPL commands:".yellow().bold());
+    println!("  {}   Show this help", ":help".green());
+    println!("  {}   Exit the session", ":quit".green());
+    println!();
+    println!("Statements, functions, structs, and imports persist between entries.");
+    println!("An expression without a trailing ';' prints its value.");
+}
+
+/// Split a REPL entry into declarations and a body to execute
+/// The input is parsed as top-level code first; if that fails (e.g. a bare
+/// expression without ';'), it is parsed as a block so the trailing expression is kept.
+fn parse_repl_entry(source: &str) -> Result<(Program, Block), ZyraError> {
+    let parse = |src: &str| -> Result<Program, ZyraError> {
+        let tokens = Lexer::new(src, "<repl>").tokenize()?;
+        Parser::new(tokens).parse()
+    };
+
+    let (statements, expression) = match parse(source) {
+        Ok(program) => (program.statements, None),
+        Err(err) => {
+            // Same line as the input so error line numbers still match
+            let wrapped = format!("func {}() {{ {}\n}}", REPL_ENTRY, source);
+            match parse(&wrapped).map(|mut p| p.statements.pop()) {
+                Ok(Some(Statement::Function { body, .. })) => (body.statements, body.expression),
+                _ => return Err(err),
+            }
+        }
+    };
+
+    let (declarations, statements): (Vec<_>, Vec<_>) =
+        statements.into_iter().partition(is_declaration);
+
+    Ok((
+        Program {
+            statements: declarations,
+            tests: Vec::new(),
+            exports: Vec::new(),
+            imported: ModuleItems::default(),
+            module_statements: 0,
+        },
+        Block {
+            statements,
+            expression,
+            span: Span::new(0, source.len(), 1, 1),
+        },
+    ))
+}
+
+fn is_declaration(stmt: &Statement) -> bool {
+    matches!(
+        stmt,
+        Statement::Function { .. }
+            | Statement::Struct { .. }
+            | Statement::Enum { .. }
+            | Statement::Impl { .. }
+            | Statement::Trait { .. }
+            | Statement::Import { .. }
+    )
+}
+
+fn declaration_name(stmt: &Statement) -> Option<&str> {
+    match stmt {
+        Statement::Function { name, .. }
+        | Statement::Struct { name, .. }
+        | Statement::Enum { name, .. }
+        | Statement::Trait { name, .. } => Some(name),
+        _ => None,
+    }
+}
+
+/// Count delimiters still open in a REPL buffer, ignoring strings, chars, and comments
+fn unclosed_delimiters(source: &str) -> i32 {
+    let chars: Vec<char> = source.chars().collect();
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            // Char literals like '{' or '\n'; lone quotes are lifetimes
+            '\'' if chars.get(i + 2) == Some(&'\'') => i += 2,
+            '\'' if chars.get(i + 1) == Some(&'\\') && chars.get(i + 3) == Some(&'\'') => i += 3,
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    depth
+}
+
+/// Templates accepted by `zyra init --template`
+const INIT_TEMPLATES: &[&str] = &["default", "game", "cli", "lib"];
+
+const DEFAULT_MAIN: &str = r#"// {project} - Main Entry Point
+//
+// Run with: zyra run
+
+func main() {
+    println("Hello, {project}!");
+}
+"#;
+
+const GAME_MAIN: &str = r#"// {project} - Main Entry Point
+//
+// Move the square with the arrow keys, ESC to quit.
+// Run with: zyra run
+
+import std::game;
+import std::time;
+import player;
+
+func main() {
+    let win = Window(800, 600, "{project}");
+    let mut player = Player::new(400, 300);
+
+    while game::is_open() {
+        game::clear();
+
+        if game::key_pressed("Escape") {
+            break;
+        }
+        player.update();
+        player.draw();
+
+        game::draw_text(10, 10, "{project}", 0xFFFFFF, 2);
+        game::display();
+        time::sleep(16);
+    }
+}
+"#;
+
+const GAME_PLAYER: &str = r#"// player.zr - The square controlled by the arrow keys
+
+import std::game;
+
+pub struct Player {
+    x: int,
+    y: int,
+    speed: int,
+}
+
+impl Player {
+    func new(x: int, y: int) -> Player {
+        Player { x: x, y: y, speed: 5 }
+    }
+
+    func update(&mut self) {
+        if game::key_pressed("Left") {
+            self.x = self.x - self.speed;
+        }
+        if game::key_pressed("Right") {
+            self.x = self.x + self.speed;
+        }
+        if game::key_pressed("Up") {
+            self.y = self.y - self.speed;
+        }
+        if game::key_pressed("Down") {
+            self.y = self.y + self.speed;
+        }
+    }
+
+    func draw(&self) {
+        game::draw_rect(self.x, self.y, 32, 32);
+    }
+}
+"#;
+
+const CLI_MAIN: &str = r#"// {project} - Command-line tool
+//
+// Run with: zyra run main.zr --name Zyra --count 2
+// Show the options with: zyra run main.zr --help
+
+import std::cli;
+import std::string;
+
+func greeting(name: string, shout: bool) -> string {
+    if shout {
+        return to_upper("Hello, ${name}!");
+    }
+    return "Hello, ${name}!";
+}
+
+func main() {
+    let args = cli::parse([
+        "--name=World, -n: Who to greet",
+        "--count=1, -c: How many times to greet",
+        "--shout, -s: Print in upper case"
+    ]);
+
+    for i in 0..args.count {
+        println(greeting(args.name, args.shout));
+    }
+}
+"#;
+
+const LIB_MAIN: &str = r#"// {project} - Library demo and tests
+//
+// Run the demo with: zyra run
+// Run the tests with: zyra test
+
+import std::core;
+import geometry;
+
+func main() {
+    let rect = Rect::new(3, 4);
+    println("area: ${rect.area()}");
+    println("perimeter: ${rect.perimeter()}");
+}
+
+test func rect_area() {
+    assert_eq(Rect::new(3, 4).area(), 12);
+}
+
+test func square_perimeter() {
+    assert_eq(geometry::square(5).perimeter(), 20);
+}
+"#;
+
+const LIB_GEOMETRY: &str = r#"// geometry.zr - Reusable shape helpers
+//
+// Import with: import geometry;
+// Only `pub` items can be used from other files.
+
+pub struct Rect {
+    width: int,
+    height: int,
+}
+
+impl Rect {
+    func new(width: int, height: int) -> Rect {
+        Rect { width: width, height: height }
+    }
+
+    func area(&self) -> int {
+        self.width * self.height
+    }
+
+    func perimeter(&self) -> int {
+        2 * (self.width + self.height)
+    }
+}
+
+pub func square(side: int) -> Rect {
+    Rect::new(side, side)
+}
+"#;
+
+/// Starter files of an init template as (path, contents), or None for an unknown template
+fn template_files(template: &str, project_name: &str) -> Option<Vec<(&'static str, String)>> {
+    let files: Vec<(&'static str, &str)> = match template {
+        "default" => vec![("main.zr", DEFAULT_MAIN)],
+        "game" => vec![("main.zr", GAME_MAIN), ("src/player.zr", GAME_PLAYER)],
+        "cli" => vec![("main.zr", CLI_MAIN)],
+        "lib" => vec![("main.zr", LIB_MAIN), ("src/geometry.zr", LIB_GEOMETRY)],
+        _ => return None,
+    };
+    Some(
+        files
+            .into_iter()
+            .map(|(path, contents)| (path, contents.replace("{project}", project_name)))
+            .collect(),
+    )
+}
+
 /// Initialize a new Zyra project
-fn init_project(name: &str) -> Result<(), ZyraError> {
+fn init_project(name: &str, template: &str, git: bool) -> Result<(), ZyraError> {
     use std::path::PathBuf;
 
     let project_dir = if name == "." {
@@ -541,6 +2362,18 @@ fn init_project(name: &str) -> Result<(), ZyraError> {
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| "zyra_project".to_string());
 
+    let files = template_files(template, &project_name).ok_or_else(|| {
+        ZyraError::new(
+            "InitError",
+            &format!(
+                "Unknown template '{}'. Available templates: {}",
+                template,
+                INIT_TEMPLATES.join(", ")
+            ),
+            None,
+        )
+    })?;
+
     // Check if zyra.toml already exists (re-initialization)
     let toml_file = project_dir.join("zyra.toml");
     let is_reinit = toml_file.exists();
@@ -568,23 +2401,31 @@ fn init_project(name: &str) -> Result<(), ZyraError> {
         })?;
     }
 
-    // Only create main.zr if not re-initializing and it doesn't exist
-    let main_file = project_dir.join("main.zr");
-    if !is_reinit && !main_file.exists() {
-        let main_content = format!(
-            r#"// {} - Main Entry Point
-//
-// Run with: zyra run
-
-func main() {{
-    println("Hello, {}!");
-}}
-"#,
-            project_name, project_name
-        );
+    // Only create the template's files if not re-initializing and they don't exist
+    let mut created = Vec::new();
+    if !is_reinit {
+        for (path, contents) in files {
+            let file = project_dir.join(path);
+            if file.exists() {
+                continue;
+            }
+            fs::write(&file, contents).map_err(|e| {
+                ZyraError::new("InitError", &format!("Cannot create {}: {}", path, e), None)
+            })?;
+            created.push(path);
+        }
+    }
 
-        fs::write(&main_file, main_content).map_err(|e| {
-            ZyraError::new("InitError", &format!("Cannot create main.zr: {}", e), None)
+    // --git: keep compiled bytecode out of the repository
+    let gitignore = project_dir.join(".gitignore");
+    let create_gitignore = git && !gitignore.exists();
+    if create_gitignore {
+        fs::write(&gitignore, "# Compiled bytecode\n*.zyc\n.zyra-cache/\n").map_err(|e| {
+            ZyraError::new(
+                "InitError",
+                &format!("Cannot create .gitignore: {}", e),
+                None,
+            )
         })?;
     }
 
@@ -598,14 +2439,31 @@ zyra = ">=1.0.2"
 description = "-"
 authors = "-"
 license = ["MIT"]
+# Capabilities the program may use: "fs", "net", "audio" (all of them without this line)
+# needs = ["fs"]
+# Files `zyra check` makes sure exist; a sprite in an atlas is "atlas.png#player"
+# assets = ["atlas.png"]
+# Unstable syntax the project opts in to, e.g. `loop {{ ... }}`
+# unstable = ["loop"]
 
 [dependencies]
-# Add dependencies here
-# not supported yet
+# Local packages, imported as `import mylib::helpers;`
+# mylib = {{ path = "../mylib" }}
 
 [build]
-main = "main.zr"
+entry = "main.zr"
 output = "./"
+
+# Integer overflow: "error", "wrap" or "saturate" (`--release` uses the release profile)
+[profile.debug]
+overflow = "error"
+
+[profile.release]
+overflow = "wrap"
+
+# Checks of literal arguments `zyra check` can skip: "path", "color", "key"
+# [check]
+# skip = ["path"]
 "#,
         project_name
     );
@@ -625,14 +2483,28 @@ output = "./"
         println!("  Updated:");
         println!("    {} - project configuration", "zyra.toml".cyan());
         println!();
+        if create_gitignore {
+            println!("    {} - ignored files", ".gitignore".cyan());
+        }
+        println!();
         println!("  Note: Existing files were not modified.");
     } else {
         println!("{}", "✓ Zyra project initialized!".green().bold());
         println!();
         println!("  Created:");
-        println!("    {} - project entry point", "main.zr".cyan());
+        for path in &created {
+            let role = if *path == "main.zr" {
+                "project entry point"
+            } else {
+                "module"
+            };
+            println!("    {} - {}", path.cyan(), role);
+        }
         println!("    {} - project configuration", "zyra.toml".cyan());
         println!("    {} - source directory", "src/".cyan());
+        if create_gitignore {
+            println!("    {} - ignored files", ".gitignore".cyan());
+        }
         println!();
         println!("  Get started:");
         println!("    {}", "zyra run".green());
@@ -640,3 +2512,99 @@ output = "./"
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_examples_compile() {
+        for (name, _, source) in EXAMPLES {
+            let tokens = Lexer::new(source, name).tokenize().unwrap();
+            let ast = Parser::new(tokens).parse().unwrap();
+            SemanticAnalyzer::new()
+                .analyze(&ast)
+                .unwrap_or_else(|e| panic!("example '{}': {}", name, e));
+            Compiler::new().compile(&ast).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_unclosed_delimiters() {
+        assert_eq!(unclosed_delimiters("func f() {"), 1);
+        assert_eq!(unclosed_delimiters("let v = vec[(1, 2"), 2);
+        assert_eq!(unclosed_delimiters("println(\"{\");"), 0);
+        assert_eq!(unclosed_delimiters("let c = '{'; let n = '\\n';"), 0);
+        assert_eq!(unclosed_delimiters("func f<'a>(x: &'a Int) {"), 1);
+        assert_eq!(unclosed_delimiters("let x = 1; // {"), 0);
+        assert_eq!(unclosed_delimiters("}"), -1);
+    }
+
+    #[test]
+    fn test_parse_repl_entry_splits_declarations_from_statements() {
+        let (program, body) =
+            parse_repl_entry("func double(n: Int) -> Int { n * 2 }\nstruct P { x: Int }").unwrap();
+        assert_eq!(program.statements.len(), 2);
+        assert!(body.statements.is_empty() && body.expression.is_none());
+
+        // Loose statements and a trailing expression run as the body
+        let (program, body) = parse_repl_entry("let x = 2;\nx * 3").unwrap();
+        assert!(program.statements.is_empty());
+        assert_eq!(body.statements.len(), 1);
+        assert!(body.expression.is_some());
+
+        assert!(parse_repl_entry("let = ;").is_err());
+    }
+
+    #[test]
+    fn test_init_templates_check_cleanly() {
+        for template in INIT_TEMPLATES {
+            let dir =
+                std::env::temp_dir().join(format!("zyra-init-{}-{}", template, process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            init_project(&dir.to_string_lossy(), template, true).unwrap();
+            let entry = dir.join("main.zr");
+            let checked = check_file_internal(&entry.to_string_lossy(), false);
+            let _ = fs::remove_dir_all(&dir);
+            checked.unwrap_or_else(|e| panic!("template '{}': {}", template, e));
+        }
+    }
+
+    #[test]
+    fn test_selftest_reports_the_first_difference() {
+        let run = |output: &str, error: Option<&str>, exit_code| BackendRun {
+            output: output.to_string(),
+            error: error.map(str::to_string),
+            exit_code,
+        };
+        let reference = run("1\n2\n", None, 0);
+        let report = difference_report(
+            (Backend::Interpreter, &reference),
+            (Backend::BytecodeFile, &run("1\n3\n", None, 0)),
+        );
+        assert!(report.contains("first difference at line 2"));
+        assert!(report.contains("interpreter: 2") && report.contains("bytecode file: 3"));
+
+        // An error counts as a line of output after the rest
+        let report = difference_report(
+            (Backend::Interpreter, &reference),
+            (Backend::BytecodeFile, &run("1\n2\n", Some("boom"), 1)),
+        );
+        assert!(report.contains("line 3") && report.contains("interpreter: <end>"));
+
+        let report = difference_report(
+            (Backend::Interpreter, &reference),
+            (Backend::BytecodeFile, &run("1\n2\n", None, 4)),
+        );
+        assert_eq!(report, "  exit code 0 vs 4");
+    }
+
+    #[test]
+    fn test_backends_agree_on_examples() {
+        for path in ["examples/fizzbuzz.zr", "examples/shapes.zr"] {
+            let runs = selftest_file_internal(path, false).unwrap();
+            assert!(!runs[0].1.output.is_empty());
+            assert!(runs.iter().all(|(_, run)| *run == runs[0].1), "{}", path);
+        }
+    }
+}
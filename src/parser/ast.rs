@@ -1,11 +1,68 @@
 //! Abstract Syntax Tree definitions for Zyra
 
+use std::collections::HashMap;
+
 use crate::lexer::Span;
 
 /// A complete Zyra program
 #[derive(Debug, Clone)]
 pub struct Program {
     pub statements: Vec<Statement>,
+    /// Names of functions declared with `test func`
+    pub tests: Vec<String>,
+    /// Names of top-level items declared with `pub`
+    pub exports: Vec<String>,
+    /// Items merged in from local modules by the module resolver
+    pub imported: ModuleItems,
+    /// How many leading statements the module resolver merged in
+    pub module_statements: usize,
+}
+
+/// A function, constant, global, struct or enum merged in from a local module
+#[derive(Debug, Clone)]
+pub struct ModuleItem {
+    /// Module it was declared in (`util` for `import src::util;`)
+    pub module: String,
+    /// Declared with `pub`, so visible to the importing file
+    pub public: bool,
+    /// Struct or enum rather than a function
+    pub is_type: bool,
+}
+
+/// Imported module items keyed by their prefixed name (`util::helper`)
+#[derive(Debug, Clone, Default)]
+pub struct ModuleItems {
+    items: HashMap<String, ModuleItem>,
+}
+
+impl ModuleItems {
+    pub fn insert(&mut self, prefixed_name: String, item: ModuleItem) {
+        self.items.insert(prefixed_name, item);
+    }
+
+    pub fn get(&self, prefixed_name: &str) -> Option<&ModuleItem> {
+        self.items.get(prefixed_name)
+    }
+
+    /// Find a struct or enum by prefixed name, or by the bare name it's
+    /// usually written with (`Rect` for `geometry::Rect`)
+    pub fn find_type(&self, name: &str) -> Option<&ModuleItem> {
+        self.get(name).filter(|item| item.is_type).or_else(|| {
+            self.items
+                .iter()
+                .find(|(key, item)| item.is_type && key.rsplit("::").next() == Some(name))
+                .map(|(_, item)| item)
+        })
+    }
+
+    /// Prefixed name of the function `name` declared in `module`, for calls
+    /// between functions of the same module
+    pub fn sibling(&self, module: &str, name: &str) -> Option<String> {
+        let prefixed = format!("{}::{}", module, name);
+        self.get(&prefixed)
+            .filter(|item| item.module == module && !item.is_type)
+            .map(|_| prefixed)
+    }
 }
 
 /// Statement types
@@ -20,6 +77,24 @@ pub enum Statement {
         span: Span,
     },
 
+    /// Top-level constant: const NAME [: Type] = expr; the value is worked
+    /// out at compile time
+    Const {
+        name: String,
+        type_annotation: Option<Type>,
+        value: Expression,
+        span: Span,
+    },
+
+    /// Mutable global: static mut NAME [: Type] = expr; the initial value is
+    /// worked out at compile time like a constant's
+    Static {
+        name: String,
+        type_annotation: Option<Type>,
+        value: Expression,
+        span: Span,
+    },
+
     /// Function declaration: func name<'a>(params) -> Type { body }
     Function {
         name: String,
@@ -54,23 +129,62 @@ pub enum Statement {
         span: Span,
     },
 
-    /// While loop: while condition { }
+    /// While loop: ['label:] while condition { }
     While {
+        label: Option<String>,
         condition: Expression,
         body: Block,
         span: Span,
     },
 
-    /// For loop: for name in start..end { } or for name in start..=end { }
+    /// Destructuring declaration: let pattern [: Type] = expr; the pattern
+    /// always matches, like `let (x, y) = pair();` or `let Point { x, y } = p;`
+    LetPattern {
+        pattern: Pattern,
+        type_annotation: Option<Type>,
+        value: Expression,
+        span: Span,
+    },
+
+    /// Conditional destructuring: if let pattern = value { } else { }
+    IfLet {
+        pattern: Pattern,
+        value: Expression,
+        then_block: Block,
+        else_block: Option<Block>,
+        span: Span,
+    },
+
+    /// Loop while a pattern matches: ['label:] while let pattern = value { }
+    WhileLet {
+        label: Option<String>,
+        pattern: Pattern,
+        value: Expression,
+        body: Block,
+        span: Span,
+    },
+
+    /// For loop: ['label:] for name in start..end { } or for name in start..=end { }
     For {
+        label: Option<String>,
         variable: String,
         start: Expression,
         end: Expression,
         inclusive: bool,
+        /// `step n`: added to the variable each iteration (default 1)
+        step: Option<Box<Expression>>,
+        /// `(start..end).rev()`: count down from the end of the range
+        reverse: bool,
         body: Block,
         span: Span,
     },
 
+    /// Break out of the innermost loop, or the loop named by 'label
+    Break { label: Option<String>, span: Span },
+
+    /// Skip to the next iteration of the innermost loop, or the loop named by 'label
+    Continue { label: Option<String>, span: Span },
+
     /// Block of statements
     Block(Block),
 
@@ -166,8 +280,12 @@ pub struct TraitMethod {
 /// Expression types
 #[derive(Debug, Clone)]
 pub enum Expression {
-    /// Integer literal
-    Int { value: i64, span: Span },
+    /// Integer literal, with the type of its suffix if it has one: `255u8`
+    Int {
+        value: i64,
+        suffix: Option<Type>,
+        span: Span,
+    },
 
     /// Float literal
     Float { value: f64, span: Span },
@@ -199,9 +317,10 @@ pub enum Expression {
         span: Span,
     },
 
-    /// Assignment: a = b
+    /// Assignment: a = b, or a += b with the operator of a compound assignment
     Assignment {
         target: Box<Expression>,
+        operator: Option<BinaryOp>,
         value: Box<Expression>,
         span: Span,
     },
@@ -220,6 +339,13 @@ pub enum Expression {
         span: Span,
     },
 
+    /// Tuple element access: pair.0
+    TupleIndex {
+        object: Box<Expression>,
+        index: usize,
+        span: Span,
+    },
+
     /// Index access: arr[index]
     Index {
         object: Box<Expression>,
@@ -227,6 +353,12 @@ pub enum Expression {
         span: Span,
     },
 
+    /// Tuple literal: (a, b, c), or (a,) with one element
+    Tuple {
+        elements: Vec<Expression>,
+        span: Span,
+    },
+
     /// List literal (Array): [a, b, c]
     List {
         elements: Vec<Expression>,
@@ -239,6 +371,18 @@ pub enum Expression {
         span: Span,
     },
 
+    /// Map literal: map{key: value, ...}
+    MapLiteral {
+        entries: Vec<(Expression, Expression)>,
+        span: Span,
+    },
+
+    /// Set literal: set{a, b, c}
+    SetLiteral {
+        elements: Vec<Expression>,
+        span: Span,
+    },
+
     /// Object literal: { field: value }
     Object {
         fields: Vec<(String, Expression)>,
@@ -303,15 +447,43 @@ pub enum Expression {
     },
 
     /// Closure expression: |params| body or move |params| body
+    /// (an expression body is a block with only a trailing expression)
     Closure {
         params: Vec<ClosureParam>,
         return_type: Option<Type>,
-        body: Box<Expression>,
+        body: Block,
         capture_mode: CaptureMode,
         span: Span,
     },
 }
 
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Let { span, .. } => *span,
+            Statement::Const { span, .. } => *span,
+            Statement::Static { span, .. } => *span,
+            Statement::Function { span, .. } => *span,
+            Statement::Expression { span, .. } => *span,
+            Statement::Import { span, .. } => *span,
+            Statement::Return { span, .. } => *span,
+            Statement::If { span, .. } => *span,
+            Statement::While { span, .. } => *span,
+            Statement::LetPattern { span, .. } => *span,
+            Statement::IfLet { span, .. } => *span,
+            Statement::WhileLet { span, .. } => *span,
+            Statement::For { span, .. } => *span,
+            Statement::Break { span, .. } => *span,
+            Statement::Continue { span, .. } => *span,
+            Statement::Block(block) => block.span,
+            Statement::Struct { span, .. } => *span,
+            Statement::Enum { span, .. } => *span,
+            Statement::Impl { span, .. } => *span,
+            Statement::Trait { span, .. } => *span,
+        }
+    }
+}
+
 impl Expression {
     pub fn span(&self) -> Span {
         match self {
@@ -326,9 +498,13 @@ impl Expression {
             Expression::Assignment { span, .. } => *span,
             Expression::Call { span, .. } => *span,
             Expression::FieldAccess { span, .. } => *span,
+            Expression::TupleIndex { span, .. } => *span,
             Expression::Index { span, .. } => *span,
+            Expression::Tuple { span, .. } => *span,
             Expression::List { span, .. } => *span,
             Expression::VecLiteral { span, .. } => *span,
+            Expression::MapLiteral { span, .. } => *span,
+            Expression::SetLiteral { span, .. } => *span,
             Expression::Object { span, .. } => *span,
             Expression::Reference { span, .. } => *span,
             Expression::Dereference { span, .. } => *span,
@@ -342,6 +518,216 @@ impl Expression {
             Expression::Closure { span, .. } => *span,
         }
     }
+
+    /// The expression inside any parentheses around this one
+    pub fn ungrouped(&self) -> &Expression {
+        match self {
+            Expression::Grouped { inner, .. } => inner.ungrouped(),
+            _ => self,
+        }
+    }
+
+    /// Whether this is a number literal without a suffix, possibly negated,
+    /// whose type comes from where it is used
+    pub fn is_untyped_number(&self) -> bool {
+        match self {
+            Expression::Int { suffix, .. } => suffix.is_none(),
+            Expression::Float { .. } => true,
+            Expression::Unary {
+                operator: UnaryOp::Negate,
+                operand,
+                ..
+            }
+            | Expression::Grouped { inner: operand, .. } => operand.is_untyped_number(),
+            _ => false,
+        }
+    }
+
+    /// The path of a `module::NAME` expression, which parses like an enum
+    /// variant without data
+    pub fn qualified_name(&self) -> Option<String> {
+        match self {
+            Expression::EnumVariant {
+                enum_name,
+                variant,
+                data: None,
+                ..
+            } => Some(format!("{}::{}", enum_name, variant)),
+            _ => None,
+        }
+    }
+
+    /// The value of an integer known at compile time: a literal, possibly negated
+    pub fn constant_int(&self) -> Option<i64> {
+        match self {
+            Expression::Int { value, .. } => Some(*value),
+            Expression::Unary {
+                operator: UnaryOp::Negate,
+                operand,
+                ..
+            } => operand.constant_int().map(|n| -n),
+            Expression::Grouped { inner, .. } => inner.constant_int(),
+            _ => None,
+        }
+    }
+
+    fn collect_identifiers(&self, names: &mut Vec<String>) {
+        let mut all = |exprs: &mut dyn Iterator<Item = &Expression>| {
+            for expr in exprs {
+                expr.collect_identifiers(names);
+            }
+        };
+        match self {
+            Expression::Identifier { name, .. } => names.push(name.clone()),
+            Expression::Binary { left, right, .. }
+            | Expression::Range {
+                start: left,
+                end: right,
+                ..
+            }
+            | Expression::Assignment {
+                target: left,
+                value: right,
+                ..
+            }
+            | Expression::Index {
+                object: left,
+                index: right,
+                ..
+            } => all(&mut [left, right].into_iter().map(|e| e.as_ref())),
+            Expression::Unary { operand: inner, .. }
+            | Expression::FieldAccess { object: inner, .. }
+            | Expression::TupleIndex { object: inner, .. }
+            | Expression::Reference { value: inner, .. }
+            | Expression::Dereference { value: inner, .. }
+            | Expression::Grouped { inner, .. }
+            | Expression::Cast { expr: inner, .. } => inner.collect_identifiers(names),
+            Expression::Call {
+                callee, arguments, ..
+            } => all(&mut std::iter::once(callee.as_ref()).chain(arguments)),
+            Expression::Tuple { elements, .. }
+            | Expression::List { elements, .. }
+            | Expression::VecLiteral { elements, .. }
+            | Expression::SetLiteral { elements, .. } => all(&mut elements.iter()),
+            Expression::MapLiteral { entries, .. } => {
+                all(&mut entries.iter().flat_map(|(k, v)| [k, v]))
+            }
+            Expression::Object { fields, .. } | Expression::StructInit { fields, .. } => {
+                all(&mut fields.iter().map(|(_, value)| value))
+            }
+            Expression::EnumVariant { data, .. } => all(&mut data.iter().map(|d| d.as_ref())),
+            Expression::If {
+                condition,
+                then_block,
+                else_block,
+                ..
+            } => {
+                condition.collect_identifiers(names);
+                for block in std::iter::once(then_block).chain(else_block) {
+                    block.collect_identifiers(names);
+                }
+            }
+            Expression::Match {
+                scrutinee, arms, ..
+            } => {
+                scrutinee.collect_identifiers(names);
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        guard.collect_identifiers(names);
+                    }
+                    arm.body.collect_identifiers(names);
+                }
+            }
+            Expression::Closure { body, .. } => body.collect_identifiers(names),
+            Expression::Int { .. }
+            | Expression::Float { .. }
+            | Expression::Bool { .. }
+            | Expression::Char { .. }
+            | Expression::String { .. } => {}
+        }
+    }
+}
+
+impl Block {
+    /// Every name used as a variable (or called) anywhere in the block, sorted
+    /// and without duplicates; closures use it to find what they capture
+    pub fn identifiers(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_identifiers(&mut names);
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn collect_identifiers(&self, names: &mut Vec<String>) {
+        for stmt in &self.statements {
+            stmt.collect_identifiers(names);
+        }
+        if let Some(expr) = &self.expression {
+            expr.collect_identifiers(names);
+        }
+    }
+}
+
+impl Statement {
+    fn collect_identifiers(&self, names: &mut Vec<String>) {
+        match self {
+            Statement::Let { value: expr, .. }
+            | Statement::LetPattern { value: expr, .. }
+            | Statement::Expression { expr, .. }
+            | Statement::Return {
+                value: Some(expr), ..
+            } => expr.collect_identifiers(names),
+            Statement::If {
+                condition,
+                then_block,
+                else_block,
+                ..
+            } => {
+                condition.collect_identifiers(names);
+                for block in std::iter::once(then_block).chain(else_block) {
+                    block.collect_identifiers(names);
+                }
+            }
+            Statement::IfLet {
+                value,
+                then_block,
+                else_block,
+                ..
+            } => {
+                value.collect_identifiers(names);
+                for block in std::iter::once(then_block).chain(else_block) {
+                    block.collect_identifiers(names);
+                }
+            }
+            Statement::While {
+                condition: value,
+                body,
+                ..
+            }
+            | Statement::WhileLet { value, body, .. } => {
+                value.collect_identifiers(names);
+                body.collect_identifiers(names);
+            }
+            Statement::For {
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => {
+                start.collect_identifiers(names);
+                end.collect_identifiers(names);
+                if let Some(step) = step {
+                    step.collect_identifiers(names);
+                }
+                body.collect_identifiers(names);
+            }
+            Statement::Block(block) => block.collect_identifiers(names),
+            // Nested items don't see the enclosing function's variables
+            _ => {}
+        }
+    }
 }
 
 // =============================================================================
@@ -385,6 +771,59 @@ pub enum Pattern {
 
     /// Tuple pattern: (a, b, c)
     Tuple { elements: Vec<Pattern>, span: Span },
+
+    /// Range of integers or chars: 1..=9, 'a'..'n'
+    Range {
+        start: LiteralPattern,
+        end: LiteralPattern,
+        inclusive: bool,
+        span: Span,
+    },
+
+    /// Or-pattern, matching when any alternative does: 'a' | 'e' | 'i'
+    Or {
+        alternatives: Vec<Pattern>,
+        span: Span,
+    },
+}
+
+impl Pattern {
+    /// The patterns any of which this one matches: an or-pattern's
+    /// alternatives, else just itself
+    pub fn alternatives(&self) -> &[Pattern] {
+        match self {
+            Pattern::Or { alternatives, .. } => alternatives,
+            other => std::slice::from_ref(other),
+        }
+    }
+
+    /// Every name the pattern binds, in order; an or-pattern's alternatives
+    /// all bind the same ones, so only the first is walked
+    pub fn bound_names(&self, names: &mut Vec<String>) {
+        match self {
+            Pattern::Identifier { name, .. } | Pattern::RefBinding { name, .. } => {
+                names.push(name.clone())
+            }
+            Pattern::Struct { fields, .. } => {
+                for field in fields {
+                    field.pattern.bound_names(names);
+                }
+            }
+            Pattern::Variant {
+                inner: Some(inner), ..
+            } => inner.bound_names(names),
+            Pattern::Tuple { elements, .. } => {
+                for element in elements {
+                    element.bound_names(names);
+                }
+            }
+            Pattern::Or { alternatives, .. } => alternatives[0].bound_names(names),
+            Pattern::Wildcard { .. }
+            | Pattern::Literal { .. }
+            | Pattern::Range { .. }
+            | Pattern::Variant { .. } => {}
+        }
+    }
 }
 
 /// Literal values in patterns
@@ -435,6 +874,13 @@ pub enum BinaryOp {
     // Logical
     And,
     Or,
+
+    // Bitwise (integers only)
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
 }
 
 impl BinaryOp {
@@ -453,6 +899,11 @@ impl BinaryOp {
             BinaryOp::GreaterEqual => ">=",
             BinaryOp::And => "&&",
             BinaryOp::Or => "||",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::ShiftLeft => "<<",
+            BinaryOp::ShiftRight => ">>",
         }
     }
 }
@@ -504,6 +955,21 @@ pub enum Type {
     },
     /// List<T> - legacy alias for Vec
     List(Box<Type>),
+    /// Option<T> - value that may be absent
+    Option(Box<Type>),
+    /// Map<K, V> - hash map
+    Map(Box<Type>, Box<Type>),
+    /// Set<T> - hash set
+    Set(Box<Type>),
+
+    /// (A, B) - tuple
+    Tuple(Vec<Type>),
+
+    /// Fn(A, B) -> R - closure (or function) taking A and B and returning R
+    Function {
+        params: Vec<Type>,
+        return_type: Box<Type>,
+    },
 
     /// Object type (structural)
     Object,
@@ -527,6 +993,32 @@ pub enum Type {
 }
 
 impl Type {
+    /// Whether this is an integer type, including `int` and `u8` written as
+    /// names
+    pub fn is_integer(&self) -> bool {
+        match self {
+            Type::I8 | Type::I32 | Type::I64 | Type::Int | Type::U8 | Type::U32 | Type::U64 => true,
+            Type::Named(name) => matches!(
+                name.as_str(),
+                "int"
+                    | "Int"
+                    | "i8"
+                    | "I8"
+                    | "i32"
+                    | "I32"
+                    | "i64"
+                    | "I64"
+                    | "u8"
+                    | "U8"
+                    | "u32"
+                    | "U32"
+                    | "u64"
+                    | "U64"
+            ),
+            _ => false,
+        }
+    }
+
     pub fn as_str(&self) -> String {
         match self {
             // Signed integers
@@ -552,8 +1044,26 @@ impl Type {
 
             // Collections
             Type::Vec(inner) => format!("Vec<{}>", inner.as_str()),
+            Type::Option(inner) => format!("Option<{}>", inner.as_str()),
             Type::Array { elem, size } => format!("[{}; {}]", elem.as_str(), size),
             Type::List(inner) => format!("List<{}>", inner.as_str()),
+            Type::Map(key, value) => format!("Map<{}, {}>", key.as_str(), value.as_str()),
+            Type::Set(inner) => format!("Set<{}>", inner.as_str()),
+            Type::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(Type::as_str).collect();
+                if elements.len() == 1 {
+                    format!("({},)", elements[0])
+                } else {
+                    format!("({})", elements.join(", "))
+                }
+            }
+            Type::Function {
+                params,
+                return_type,
+            } => {
+                let params: Vec<String> = params.iter().map(Type::as_str).collect();
+                format!("Fn({}) -> {}", params.join(", "), return_type.as_str())
+            }
 
             Type::Object => "Object".to_string(),
             Type::Named(name) => name.clone(),
@@ -6,29 +6,303 @@ pub mod ast;
 
 pub use ast::*;
 
-use crate::error::{SourceLocation, ZyraError, ZyraResult};
+use std::collections::HashMap;
+
+use crate::error::{SourceLocation, ZyraError, ZyraResult, DEFAULT_MAX_ERRORS};
 use crate::lexer::{Span, Token, TokenKind};
 
+/// Deepest nesting of expressions, blocks, types and patterns the parser
+/// accepts. Each level takes tens of kilobytes of stack in a debug build, so
+/// deeper input is rejected rather than overflowing the stack.
+pub const MAX_NESTING: usize = 64;
+
+/// Language edition a project is written against (`edition` in zyra.toml)
+/// Syntax changes that would break existing code are enabled per edition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Edition {
+    #[default]
+    E2025,
+}
+
+impl Edition {
+    /// Every edition this version of Zyra understands, oldest first
+    pub const ALL: &'static [Edition] = &[Edition::E2025];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|e| e.as_str() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Edition::E2025 => "2025",
+        }
+    }
+}
+
+/// Experimental language feature a project opts into (`unstable` in zyra.toml)
+/// Unstable syntax is rejected unless its feature is enabled, so it can still
+/// change before it becomes part of the language without breaking anyone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// `loop { }`: repeat until `break`
+    Loop,
+}
+
+impl Feature {
+    /// Every unstable feature this version of Zyra has
+    pub const ALL: &'static [Feature] = &[Feature::Loop];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|f| f.as_str() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Feature::Loop => "loop",
+        }
+    }
+}
+
 /// Parser for Zyra source code
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    edition: Edition,
+    /// Unstable features the project enabled
+    features: Vec<Feature>,
+    /// Syntax errors skipped past so far
+    errors: Vec<ZyraError>,
+    /// Errors kept before parsing gives up (`--max-errors`)
+    max_errors: usize,
+    /// Values of the integer constants declared so far, for array sizes and
+    /// patterns
+    constants: HashMap<String, i64>,
+    /// Expressions, blocks, types and patterns currently being parsed inside
+    /// one another
+    depth: usize,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            current: 0,
+            edition: Edition::default(),
+            features: Vec::new(),
+            errors: Vec::new(),
+            max_errors: DEFAULT_MAX_ERRORS,
+            constants: HashMap::new(),
+            depth: 0,
+        }
+    }
+
+    /// Parse with the syntax of the given edition
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
+    pub fn edition(&self) -> Edition {
+        self.edition
+    }
+
+    /// Accept the syntax of these unstable features
+    pub fn with_features(mut self, features: &[Feature]) -> Self {
+        self.features.extend_from_slice(features);
+        self
+    }
+
+    /// Stop after this many syntax errors instead of the default 20
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors.max(1);
+        self
     }
 
-    /// Parse the token stream into an AST
+    /// Fail on unstable syntax whose feature the project didn't enable
+    fn require_feature(&self, feature: Feature) -> ZyraResult<()> {
+        if self.features.contains(&feature) {
+            return Ok(());
+        }
+        Err(self
+            .error(&format!("`{}` is an unstable feature", feature.as_str()))
+            .with_suggestion(&format!(
+                "Enable it in zyra.toml: unstable = [\"{}\"] under [project]",
+                feature.as_str()
+            )))
+    }
+
+    /// Whether the next token is the contextual keyword `loop` starting a loop
+    fn at_loop(&self) -> bool {
+        matches!(&self.peek().kind, TokenKind::Identifier(name) if name == "loop")
+            && matches!(
+                self.tokens.get(self.current + 1).map(|t| &t.kind),
+                Some(TokenKind::LeftBrace)
+            )
+    }
+
+    /// Parse the token stream into an AST, failing with every syntax error found
     pub fn parse(&mut self) -> ZyraResult<Program> {
-        let mut statements = Vec::new();
+        let (program, errors) = self.parse_recovering();
+        match ZyraError::combine(errors) {
+            Some(error) => Err(error),
+            None => Ok(program),
+        }
+    }
 
+    /// Parse the token stream, skipping past syntax errors instead of stopping
+    /// at the first. The program holds everything that parsed, so tools can
+    /// work with the rest of a file that's being edited; the errors come in
+    /// source order.
+    pub fn parse_recovering(&mut self) -> (Program, Vec<ZyraError>) {
+        let mut program = Program {
+            statements: Vec::new(),
+            tests: Vec::new(),
+            exports: Vec::new(),
+            imported: ModuleItems::default(),
+            module_statements: 0,
+        };
+        let stopped = self.parse_items(&mut program).err();
+        let mut errors = std::mem::take(&mut self.errors);
+        errors.extend(stopped);
+        (program, errors)
+    }
+
+    fn parse_items(&mut self, program: &mut Program) -> ZyraResult<()> {
         while !self.is_at_end() {
-            statements.push(self.parse_statement()?);
+            let start = self.current;
+            if let Err(error) = self.parse_item(program) {
+                self.recover(error, start, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// One top-level declaration or statement
+    fn parse_item(&mut self, program: &mut Program) -> ZyraResult<()> {
+        // `pub` exports a function, struct, enum or constant to files importing this one
+        if self.check(&TokenKind::Pub) {
+            self.advance();
+            let item = match self.peek().kind {
+                TokenKind::Func => self.parse_function()?,
+                TokenKind::Struct => self.parse_struct()?,
+                TokenKind::Enum => self.parse_enum()?,
+                TokenKind::Const => self.parse_const()?,
+                _ => {
+                    return Err(
+                        self.error("Expected 'func', 'struct', 'enum' or 'const' after 'pub'")
+                    )
+                }
+            };
+            if let Statement::Function { name, .. }
+            | Statement::Struct { name, .. }
+            | Statement::Enum { name, .. }
+            | Statement::Const { name, .. } = &item
+            {
+                program.exports.push(name.clone());
+            }
+            program.statements.push(item);
+            return Ok(());
         }
 
-        Ok(Program { statements })
+        // `test func name() { ... }` declares a test for `zyra test`
+        if matches!(&self.peek().kind, TokenKind::Identifier(word) if word == "test")
+            && matches!(
+                self.tokens.get(self.current + 1).map(|t| &t.kind),
+                Some(TokenKind::Func)
+            )
+        {
+            self.advance();
+            let function = self.parse_function()?;
+            if let Statement::Function { name, .. } = &function {
+                program.tests.push(name.clone());
+            }
+            program.statements.push(function);
+            return Ok(());
+        }
+        if self.check(&TokenKind::Const) {
+            program.statements.push(self.parse_const()?);
+            return Ok(());
+        }
+        if self.check(&TokenKind::Static) {
+            program.statements.push(self.parse_static()?);
+            return Ok(());
+        }
+        program.statements.push(self.parse_statement()?);
+        Ok(())
+    }
+
+    /// Keep a syntax error and skip what's left of the broken item or
+    /// statement that began at token `start`. Fails once --max-errors errors
+    /// were kept, and passes the error on if the limit was already reached.
+    fn recover(&mut self, error: ZyraError, start: usize, top_level: bool) -> ZyraResult<()> {
+        if self.errors.len() >= self.max_errors {
+            return Err(error);
+        }
+        self.errors.push(error);
+        if self.errors.len() >= self.max_errors {
+            return Err(ZyraError::too_many_errors(self.max_errors));
+        }
+        self.synchronize(start, top_level);
+        if self.current == start {
+            self.advance();
+        }
+        Ok(())
+    }
+
+    /// Skip tokens up to where the next statement can start: past a `;`, or
+    /// at a `}` closing the enclosing block or a keyword starting a statement,
+    /// outside the braces the broken code opened. At top level only a
+    /// declaration keyword ends the skip.
+    fn synchronize(&mut self, start: usize, top_level: bool) {
+        let mut depth = self.tokens[start..self.current]
+            .iter()
+            .fold(0usize, |depth, token| match token.kind {
+                TokenKind::LeftBrace => depth + 1,
+                TokenKind::RightBrace => depth.saturating_sub(1),
+                _ => depth,
+            });
+        while !self.is_at_end() {
+            let kind = &self.peek().kind;
+            if depth == 0 {
+                let starts_item = matches!(
+                    kind,
+                    TokenKind::Func
+                        | TokenKind::Struct
+                        | TokenKind::Enum
+                        | TokenKind::Impl
+                        | TokenKind::Trait
+                        | TokenKind::Import
+                        | TokenKind::Pub
+                        | TokenKind::Const
+                        | TokenKind::Static
+                );
+                let starts_statement = matches!(
+                    kind,
+                    TokenKind::Let
+                        | TokenKind::Return
+                        | TokenKind::If
+                        | TokenKind::While
+                        | TokenKind::For
+                        | TokenKind::Break
+                        | TokenKind::Continue
+                );
+                match kind {
+                    TokenKind::Semicolon => {
+                        self.advance();
+                        return;
+                    }
+                    TokenKind::RightBrace if !top_level => return,
+                    _ if starts_item || (starts_statement && !top_level) => return,
+                    _ => {}
+                }
+            }
+            match kind {
+                TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightBrace => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.advance();
+        }
     }
 
     // ===== Statement Parsing =====
@@ -42,10 +316,22 @@ impl Parser {
             TokenKind::If => self.parse_if(),
             TokenKind::While => self.parse_while(),
             TokenKind::For => self.parse_for(),
+            TokenKind::Identifier(_) if self.at_loop() => self.parse_loop(),
+            TokenKind::Lifetime(_) => self.parse_labeled_loop(),
+            TokenKind::Break | TokenKind::Continue => self.parse_break_continue(),
             TokenKind::Struct => self.parse_struct(),
             TokenKind::Enum => self.parse_enum(),
             TokenKind::Impl => self.parse_impl(),
             TokenKind::Trait => self.parse_trait(),
+            TokenKind::Pub => Err(self.error(
+                "'pub' is only allowed on top-level func, struct, enum and const declarations",
+            )),
+            TokenKind::Const => {
+                Err(self.error("'const' is only allowed at the top level, outside functions"))
+            }
+            TokenKind::Static => {
+                Err(self.error("'static mut' is only allowed at the top level, outside functions"))
+            }
             TokenKind::LeftBrace => {
                 let block = self.parse_block()?;
                 Ok(Statement::Block(block))
@@ -57,6 +343,16 @@ impl Parser {
     fn parse_let_statement(&mut self) -> ZyraResult<Statement> {
         let start_span = self.advance().span; // Consume 'let'
 
+        // let (a, b) = ... and let Point { x, y } = ... destructure the value
+        let struct_pattern = matches!(self.peek().kind, TokenKind::Identifier(_))
+            && matches!(
+                self.tokens.get(self.current + 1).map(|t| &t.kind),
+                Some(TokenKind::LeftBrace)
+            );
+        if struct_pattern || self.check(&TokenKind::LeftParen) {
+            return self.parse_let_pattern(start_span);
+        }
+
         // Check for 'mut'
         let mutable = if self.check(&TokenKind::Mut) {
             self.advance();
@@ -101,6 +397,138 @@ impl Parser {
         })
     }
 
+    /// Parse the rest of a destructuring `let pattern [: Type] = value;`
+    fn parse_let_pattern(&mut self, start_span: Span) -> ZyraResult<Statement> {
+        let pattern = self.parse_pattern()?;
+        let type_annotation = if self.check(&TokenKind::Colon) {
+            self.advance();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        self.expect(&TokenKind::Equal, "Expected '=' in let statement")?;
+        let value = self.parse_expression()?;
+        self.expect(&TokenKind::Semicolon, "Expected ';' after let statement")?;
+
+        let span = Span::new(
+            start_span.start,
+            self.previous().span.end,
+            start_span.line,
+            start_span.column,
+        );
+        Ok(Statement::LetPattern {
+            pattern,
+            type_annotation,
+            value,
+            span,
+        })
+    }
+
+    /// Parse `const NAME [: Type] = value;`
+    fn parse_const(&mut self) -> ZyraResult<Statement> {
+        let start_span = self.advance().span; // Consume 'const'
+        let name = self.expect_identifier("Expected constant name after 'const'")?;
+        let (type_annotation, value, span) = self.parse_global_rest(start_span, "const")?;
+        let integer = type_annotation.as_ref().is_none_or(Type::is_integer);
+        if let (true, Some(n)) = (integer, self.const_int(&value)) {
+            self.constants.insert(name.clone(), n);
+        }
+        Ok(Statement::Const {
+            name,
+            type_annotation,
+            value,
+            span,
+        })
+    }
+
+    /// Value of an integer expression made only of literals, constants
+    /// declared above it and arithmetic: `2 * TILE`, `-1`
+    fn const_int(&self, expr: &Expression) -> Option<i64> {
+        match expr {
+            Expression::Int { value, .. } => Some(*value),
+            Expression::Identifier { name, .. } => self.constants.get(name).copied(),
+            Expression::Grouped { inner, .. } => self.const_int(inner),
+            Expression::Unary {
+                operator: UnaryOp::Negate,
+                operand,
+                ..
+            } => self.const_int(operand)?.checked_neg(),
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let (a, b) = (self.const_int(left)?, self.const_int(right)?);
+                match operator {
+                    BinaryOp::Add => a.checked_add(b),
+                    BinaryOp::Subtract => a.checked_sub(b),
+                    BinaryOp::Multiply => a.checked_mul(b),
+                    BinaryOp::Divide => a.checked_div(b),
+                    BinaryOp::Modulo => a.checked_rem(b),
+                    BinaryOp::BitAnd => Some(a & b),
+                    BinaryOp::BitOr => Some(a | b),
+                    BinaryOp::BitXor => Some(a ^ b),
+                    BinaryOp::ShiftLeft => a.checked_shl(u32::try_from(b).ok()?),
+                    BinaryOp::ShiftRight => a.checked_shr(u32::try_from(b).ok()?),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse `static mut NAME [: Type] = value;`; globals are always
+    /// mutable, so the `mut` is required
+    fn parse_static(&mut self) -> ZyraResult<Statement> {
+        let start_span = self.advance().span; // Consume 'static'
+        if !self.check(&TokenKind::Mut) {
+            return Err(self
+                .error("Expected 'mut' after 'static': globals are declared with 'static mut'")
+                .with_suggestion("Use 'const' for a value that never changes"));
+        }
+        self.advance();
+        let name = self.expect_identifier("Expected global name after 'static mut'")?;
+        let (type_annotation, value, span) = self.parse_global_rest(start_span, "static")?;
+        Ok(Statement::Static {
+            name,
+            type_annotation,
+            value,
+            span,
+        })
+    }
+
+    /// The `[: Type] = value;` that ends a const or static declaration
+    fn parse_global_rest(
+        &mut self,
+        start_span: Span,
+        keyword: &str,
+    ) -> ZyraResult<(Option<Type>, Expression, Span)> {
+        let type_annotation = if self.check(&TokenKind::Colon) {
+            self.advance();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        self.expect(
+            &TokenKind::Equal,
+            &format!("Expected '=' in {} declaration", keyword),
+        )?;
+        let value = self.parse_expression()?;
+        self.expect(
+            &TokenKind::Semicolon,
+            &format!("Expected ';' after {} declaration", keyword),
+        )?;
+
+        let span = Span::new(
+            start_span.start,
+            self.previous().span.end,
+            start_span.line,
+            start_span.column,
+        );
+        Ok((type_annotation, value, span))
+    }
+
     fn parse_function(&mut self) -> ZyraResult<Statement> {
         let start_span = self.advance().span; // Consume 'func'
 
@@ -126,10 +554,7 @@ impl Parser {
                 self.advance();
             }
 
-            self.expect(
-                &TokenKind::Greater,
-                "Expected '>' after lifetime parameters",
-            )?;
+            self.expect_closing_angle("Expected '>' after lifetime parameters")?;
             lifetimes
         } else {
             Vec::new()
@@ -218,7 +643,7 @@ impl Parser {
                 } else {
                     return Err(ZyraError::syntax_error(
                         "Expected 'self' after '&' or '&mut' in parameter",
-                        SourceLocation::new("", param_span.line, param_span.column),
+                        SourceLocation::from_span(&param_span),
                     ));
                 }
             } else if self.check(&TokenKind::SelfType) {
@@ -367,6 +792,16 @@ impl Parser {
     fn parse_if(&mut self) -> ZyraResult<Statement> {
         let start_span = self.advance().span; // Consume 'if'
 
+        // 'if let pattern = value' destructures the value instead of testing it
+        let pattern = if self.check(&TokenKind::Let) {
+            self.advance(); // consume 'let'
+            let pattern = self.parse_pattern()?;
+            self.expect(&TokenKind::Equal, "Expected '=' after pattern in 'if let'")?;
+            Some(pattern)
+        } else {
+            None
+        };
+
         let condition = self.parse_expression()?;
         let then_block = self.parse_block()?;
 
@@ -379,7 +814,7 @@ impl Parser {
                 let else_if_stmt = self.parse_if()?;
                 // Extract span from the Statement::If
                 let else_if_span = match &else_if_stmt {
-                    Statement::If { span, .. } => *span,
+                    Statement::If { span, .. } | Statement::IfLet { span, .. } => *span,
                     _ => self.previous().span, // fallback (shouldn't happen)
                 };
                 Some(Block {
@@ -407,6 +842,16 @@ impl Parser {
             start_span.column,
         );
 
+        if let Some(pattern) = pattern {
+            return Ok(Statement::IfLet {
+                pattern,
+                value: condition,
+                then_block,
+                else_block,
+                span,
+            });
+        }
+
         Ok(Statement::If {
             condition,
             then_block,
@@ -418,6 +863,10 @@ impl Parser {
     fn parse_while(&mut self) -> ZyraResult<Statement> {
         let start_span = self.advance().span; // Consume 'while'
 
+        if self.check(&TokenKind::Let) {
+            return self.parse_while_let(start_span);
+        }
+
         let condition = self.parse_expression()?;
         let body = self.parse_block()?;
 
@@ -434,12 +883,74 @@ impl Parser {
         );
 
         Ok(Statement::While {
+            label: None,
             condition,
             body,
             span,
         })
     }
 
+    /// Parse `loop { }`, a `while` whose condition is always true
+    fn parse_loop(&mut self) -> ZyraResult<Statement> {
+        self.require_feature(Feature::Loop)?;
+        let start_span = self.advance().span; // Consume 'loop'
+        let body = self.parse_block()?;
+
+        // Optional semicolon after the loop
+        if self.check(&TokenKind::Semicolon) {
+            self.advance();
+        }
+
+        let span = Span::new(
+            start_span.start,
+            self.previous().span.end,
+            start_span.line,
+            start_span.column,
+        );
+
+        Ok(Statement::While {
+            label: None,
+            condition: Expression::Bool {
+                value: true,
+                span: start_span,
+            },
+            body,
+            span,
+        })
+    }
+
+    /// Parse the rest of `while let pattern = value { }` after 'while'
+    fn parse_while_let(&mut self, start_span: Span) -> ZyraResult<Statement> {
+        self.advance(); // Consume 'let'
+        let pattern = self.parse_pattern()?;
+        self.expect(
+            &TokenKind::Equal,
+            "Expected '=' after pattern in 'while let'",
+        )?;
+        let value = self.parse_expression()?;
+        let body = self.parse_block()?;
+
+        // Optional semicolon after while loop
+        if self.check(&TokenKind::Semicolon) {
+            self.advance();
+        }
+
+        let span = Span::new(
+            start_span.start,
+            self.previous().span.end,
+            start_span.line,
+            start_span.column,
+        );
+
+        Ok(Statement::WhileLet {
+            label: None,
+            pattern,
+            value,
+            body,
+            span,
+        })
+    }
+
     fn parse_for(&mut self) -> ZyraResult<Statement> {
         let start_span = self.advance().span; // Consume 'for'
 
@@ -447,18 +958,48 @@ impl Parser {
 
         self.expect(&TokenKind::In, "Expected 'in' after loop variable")?;
 
-        let start = self.parse_expression()?;
-
-        // Check for .. or ..= (inclusive range)
-        let inclusive = if self.check(&TokenKind::DotDotEq) {
+        // `(start..end).rev()` counts down; anything else starting with '(' is
+        // a grouped start expression, so the parser backs up and reads it again
+        let checkpoint = self.current;
+        let reversed = if self.check(&TokenKind::LeftParen) {
             self.advance();
-            true
+            match self.parse_for_range() {
+                Ok((start, end, inclusive)) if self.check(&TokenKind::RightParen) => {
+                    self.advance();
+                    self.expect(
+                        &TokenKind::Dot,
+                        "Expected '.rev()' after range in parentheses",
+                    )?;
+                    match self.peek().kind.clone() {
+                        TokenKind::Identifier(name) if name == "rev" => self.advance(),
+                        _ => return Err(self.error("Expected '.rev()' after range in parentheses")),
+                    };
+                    self.expect(&TokenKind::LeftParen, "Expected '(' after 'rev'")?;
+                    self.expect(&TokenKind::RightParen, "Expected ')' after 'rev('")?;
+                    Some((start, end, inclusive))
+                }
+                _ => None,
+            }
         } else {
-            self.expect(&TokenKind::DotDot, "Expected '..' or '..=' in range")?;
-            false
+            None
+        };
+        let reverse = reversed.is_some();
+        let (start, end, inclusive) = match reversed {
+            Some(range) => range,
+            None => {
+                self.current = checkpoint;
+                self.parse_for_range()?
+            }
         };
 
-        let end = self.parse_expression()?;
+        // Optional stride: `for i in 0..100 step 5`, `for i in 10..0 step -1`
+        let step = match &self.peek().kind {
+            TokenKind::Identifier(name) if name == "step" => {
+                self.advance();
+                Some(Box::new(self.parse_expression()?))
+            }
+            _ => None,
+        };
 
         let body = self.parse_block()?;
 
@@ -475,15 +1016,97 @@ impl Parser {
         );
 
         Ok(Statement::For {
+            label: None,
             variable,
             start,
             end,
             inclusive,
+            step,
+            reverse,
             body,
             span,
         })
     }
 
+    /// `start..end` or `start..=end`, returning whether the end is included
+    fn parse_for_range(&mut self) -> ZyraResult<(Expression, Expression, bool)> {
+        let start = self.parse_expression()?;
+
+        // Check for .. or ..= (inclusive range)
+        let inclusive = if self.check(&TokenKind::DotDotEq) {
+            self.advance();
+            true
+        } else {
+            self.expect(&TokenKind::DotDot, "Expected '..' or '..=' in range")?;
+            false
+        };
+
+        let end = self.parse_expression()?;
+        Ok((start, end, inclusive))
+    }
+
+    /// Parse `'label: while ...` or `'label: for ...`
+    fn parse_labeled_loop(&mut self) -> ZyraResult<Statement> {
+        let name = match &self.advance().kind {
+            TokenKind::Lifetime(name) => name.clone(),
+            _ => unreachable!("parse_labeled_loop called without a label"),
+        };
+        self.expect(&TokenKind::Colon, "Expected ':' after loop label")?;
+
+        let mut stmt = match self.peek().kind {
+            TokenKind::While => self.parse_while()?,
+            TokenKind::For => self.parse_for()?,
+            TokenKind::Identifier(_) if self.at_loop() => self.parse_loop()?,
+            _ => {
+                return Err(self.error(&format!("Expected 'while' or 'for' after label '{}", name)))
+            }
+        };
+
+        if let Statement::While { label, .. }
+        | Statement::WhileLet { label, .. }
+        | Statement::For { label, .. } = &mut stmt
+        {
+            *label = Some(name);
+        }
+        Ok(stmt)
+    }
+
+    /// Parse `break ['label];` or `continue ['label];`
+    fn parse_break_continue(&mut self) -> ZyraResult<Statement> {
+        let token = self.advance().clone();
+
+        let label = if let TokenKind::Lifetime(name) = &self.peek().kind {
+            let name = name.clone();
+            self.advance();
+            Some(name)
+        } else {
+            None
+        };
+
+        let keyword = if token.kind == TokenKind::Break {
+            "break"
+        } else {
+            "continue"
+        };
+        self.expect(
+            &TokenKind::Semicolon,
+            &format!("Expected ';' after '{}'", keyword),
+        )?;
+
+        let span = Span::new(
+            token.span.start,
+            self.previous().span.end,
+            token.span.line,
+            token.span.column,
+        );
+
+        if token.kind == TokenKind::Break {
+            Ok(Statement::Break { label, span })
+        } else {
+            Ok(Statement::Continue { label, span })
+        }
+    }
+
     fn parse_expression_statement(&mut self) -> ZyraResult<Statement> {
         let start_span = self.peek().span;
         let expr = self.parse_expression()?;
@@ -501,6 +1124,10 @@ impl Parser {
     }
 
     fn parse_block(&mut self) -> ZyraResult<Block> {
+        self.nested(Self::parse_block_unguarded)
+    }
+
+    fn parse_block_unguarded(&mut self) -> ZyraResult<Block> {
         let start_span = self
             .expect(&TokenKind::LeftBrace, "Expected '{' to start block")?
             .span;
@@ -509,75 +1136,9 @@ impl Parser {
         let mut expression = None;
 
         while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
-            // Try to determine if this is a statement or a trailing expression
-            // Check what kind of token we're looking at
-            match self.peek().kind {
-                // These are definitely statements
-                TokenKind::Let
-                | TokenKind::Func
-                | TokenKind::Import
-                | TokenKind::Return
-                | TokenKind::While
-                | TokenKind::For => {
-                    statements.push(self.parse_statement()?);
-                }
-                // If statement - could be trailing expression or statement
-                TokenKind::If => {
-                    let if_stmt = self.parse_if()?;
-
-                    // Check if this if statement should be a trailing expression
-                    // (no semicolon after and followed by closing brace)
-                    if self.check(&TokenKind::RightBrace) {
-                        // Convert if statement to expression for trailing position
-                        if let Statement::If {
-                            condition,
-                            then_block,
-                            else_block,
-                            span,
-                        } = if_stmt
-                        {
-                            expression = Some(Box::new(Expression::If {
-                                condition: Box::new(condition),
-                                then_block,
-                                else_block,
-                                span,
-                            }));
-                        } else {
-                            statements.push(if_stmt);
-                        }
-                    } else {
-                        statements.push(if_stmt);
-                    }
-                }
-                // Block statement
-                TokenKind::LeftBrace => {
-                    let block = self.parse_block()?;
-                    statements.push(Statement::Block(block));
-                }
-                // Everything else might be an expression or expression statement
-                _ => {
-                    let expr_start = self.peek().span;
-                    let expr = self.parse_expression()?;
-
-                    // Check what follows the expression
-                    if self.check(&TokenKind::Semicolon) {
-                        // It's an expression statement with semicolon
-                        self.advance();
-                        let span = Span::new(
-                            expr_start.start,
-                            self.previous().span.end,
-                            expr_start.line,
-                            expr_start.column,
-                        );
-                        statements.push(Statement::Expression { expr, span });
-                    } else if self.check(&TokenKind::RightBrace) {
-                        // It's the trailing expression (no semicolon before closing brace)
-                        expression = Some(Box::new(expr));
-                    } else {
-                        // Unexpected - report error
-                        return Err(self.error("Expected ';' or '}' after expression"));
-                    }
-                }
+            let start = self.current;
+            if let Err(error) = self.parse_block_item(&mut statements, &mut expression) {
+                self.recover(error, start, false)?;
             }
         }
 
@@ -597,10 +1158,178 @@ impl Parser {
         })
     }
 
+    /// One statement of a block, or its trailing expression
+    fn parse_block_item(
+        &mut self,
+        statements: &mut Vec<Statement>,
+        expression: &mut Option<Box<Expression>>,
+    ) -> ZyraResult<()> {
+        // Try to determine if this is a statement or a trailing expression
+        // Check what kind of token we're looking at
+        match self.peek().kind {
+            // These are definitely statements
+            TokenKind::Let
+            | TokenKind::Func
+            | TokenKind::Import
+            | TokenKind::Return
+            | TokenKind::While
+            | TokenKind::For
+            | TokenKind::Lifetime(_)
+            | TokenKind::Break
+            | TokenKind::Continue
+            | TokenKind::Const
+            | TokenKind::Static => {
+                statements.push(self.parse_statement()?);
+            }
+            TokenKind::Identifier(_) if self.at_loop() => {
+                statements.push(self.parse_loop()?);
+            }
+            // If statement - could be trailing expression or statement
+            TokenKind::If => {
+                let if_stmt = self.parse_if()?;
+
+                // Check if this if statement should be a trailing expression
+                // (no semicolon after and followed by closing brace)
+                if self.check(&TokenKind::RightBrace) {
+                    // Convert if statement to expression for trailing position
+                    match Self::trailing_if(if_stmt) {
+                        Ok(if_expr) => *expression = Some(Box::new(if_expr)),
+                        Err(if_stmt) => statements.push(*if_stmt),
+                    }
+                } else {
+                    statements.push(if_stmt);
+                }
+            }
+            // Block statement
+            TokenKind::LeftBrace => {
+                let block = self.parse_block()?;
+                statements.push(Statement::Block(block));
+            }
+            // Everything else might be an expression or expression statement
+            _ => {
+                let expr_start = self.peek().span;
+                let expr = self.parse_expression()?;
+
+                // Check what follows the expression
+                if self.check(&TokenKind::Semicolon) {
+                    // It's an expression statement with semicolon
+                    self.advance();
+                    let span = Span::new(
+                        expr_start.start,
+                        self.previous().span.end,
+                        expr_start.line,
+                        expr_start.column,
+                    );
+                    statements.push(Statement::Expression { expr, span });
+                } else if self.check(&TokenKind::RightBrace) {
+                    // It's the trailing expression (no semicolon before closing brace)
+                    *expression = Some(Box::new(expr));
+                } else {
+                    // Unexpected - report error
+                    return Err(self.error("Expected ';' or '}' after expression"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The rest of an `if` used as a value, after the `if` at `start_span`.
+    /// Its value is the taken branch's, or None when there's no `else`.
+    fn parse_if_expression(&mut self, start_span: Span) -> ZyraResult<Expression> {
+        if self.check(&TokenKind::Let) {
+            return Err(self
+                .error("'if let' can't be used as a value")
+                .with_suggestion("Use a match, or declare the variable before the 'if let'"));
+        }
+        let condition = self.parse_expression()?;
+        let then_block = self.parse_block()?;
+
+        let else_block = if self.check(&TokenKind::Else) {
+            self.advance(); // consume 'else'
+            if self.check(&TokenKind::If) {
+                // `else if` becomes an else block whose value is the inner `if`
+                let if_span = self.advance().span;
+                let inner = self.parse_if_expression(if_span)?;
+                let span = inner.span();
+                Some(Block {
+                    statements: Vec::new(),
+                    expression: Some(Box::new(inner)),
+                    span,
+                })
+            } else {
+                Some(self.parse_block()?)
+            }
+        } else {
+            None
+        };
+
+        let span = Span::new(
+            start_span.start,
+            self.previous().span.end,
+            start_span.line,
+            start_span.column,
+        );
+        Ok(Expression::If {
+            condition: Box::new(condition),
+            then_block,
+            else_block,
+            span,
+        })
+    }
+
+    /// An `if` statement in trailing position as the expression giving its
+    /// block a value, down its `else if` chain; `if let` stays a statement
+    fn trailing_if(stmt: Statement) -> Result<Expression, Box<Statement>> {
+        let Statement::If {
+            condition,
+            then_block,
+            else_block,
+            span,
+        } = stmt
+        else {
+            return Err(Box::new(stmt));
+        };
+        let else_block = else_block.map(|mut block| {
+            // `else if` is parsed into a block holding just the inner `if`
+            let chained = block.expression.is_none()
+                && matches!(
+                    block.statements.as_slice(),
+                    [Statement::If { span, .. }] if *span == block.span
+                );
+            if chained {
+                match Self::trailing_if(block.statements.remove(0)) {
+                    Ok(inner) => block.expression = Some(Box::new(inner)),
+                    Err(inner) => block.statements.push(*inner),
+                }
+            }
+            block
+        });
+        Ok(Expression::If {
+            condition: Box::new(condition),
+            then_block,
+            else_block,
+            span,
+        })
+    }
+
     // ===== Expression Parsing (Pratt Parser style with precedence) =====
 
     fn parse_expression(&mut self) -> ZyraResult<Expression> {
-        self.parse_assignment()
+        self.nested(Self::parse_assignment)
+    }
+
+    /// Run a parse step one nesting level deeper, failing past `MAX_NESTING`
+    fn nested<T>(&mut self, parse: fn(&mut Self) -> ZyraResult<T>) -> ZyraResult<T> {
+        if self.depth >= MAX_NESTING {
+            return Err(self.error(&format!(
+                "Nesting too deep: more than {} levels of expressions, blocks, types or patterns",
+                MAX_NESTING
+            )));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
     }
 
     fn parse_assignment(&mut self) -> ZyraResult<Expression> {
@@ -608,7 +1337,7 @@ impl Parser {
 
         if self.check(&TokenKind::Equal) {
             let start_span = self.advance().span;
-            let value = self.parse_assignment()?;
+            let value = self.nested(Self::parse_assignment)?;
 
             let span = Span::new(
                 expr.span().start,
@@ -619,13 +1348,13 @@ impl Parser {
 
             return Ok(Expression::Assignment {
                 target: Box::new(expr),
+                operator: None,
                 value: Box::new(value),
                 span,
             });
         }
 
         // Compound assignment operators: +=, -=, *=, /=, %=
-        // Desugar x += y to x = x + y
         let compound_op = if self.check(&TokenKind::PlusAssign) {
             Some(BinaryOp::Add)
         } else if self.check(&TokenKind::MinusAssign) {
@@ -642,7 +1371,7 @@ impl Parser {
 
         if let Some(op) = compound_op {
             let start_span = self.advance().span;
-            let right = self.parse_assignment()?;
+            let right = self.nested(Self::parse_assignment)?;
 
             let span = Span::new(
                 expr.span().start,
@@ -651,17 +1380,11 @@ impl Parser {
                 start_span.column,
             );
 
-            // Create: target = target op value
-            let binary_expr = Expression::Binary {
-                operator: op,
-                left: Box::new(expr.clone()),
-                right: Box::new(right),
-                span,
-            };
-
+            // The compiler evaluates the target once: `get_player().hp += 5`
             return Ok(Expression::Assignment {
                 target: Box::new(expr),
-                value: Box::new(binary_expr),
+                operator: Some(op),
+                value: Box::new(right),
                 span,
             });
         }
@@ -748,7 +1471,7 @@ impl Parser {
     }
 
     fn parse_comparison(&mut self) -> ZyraResult<Expression> {
-        let mut left = self.parse_term()?;
+        let mut left = self.parse_bit_or()?;
 
         loop {
             let op = match self.peek().kind {
@@ -760,7 +1483,69 @@ impl Parser {
             };
             self.advance();
 
-            let right = self.parse_term()?;
+            let right = self.parse_bit_or()?;
+            let span = Span::new(
+                left.span().start,
+                right.span().end,
+                left.span().line,
+                left.span().column,
+            );
+
+            left = Expression::Binary {
+                left: Box::new(left),
+                operator: op,
+                right: Box::new(right),
+                span,
+            };
+        }
+
+        Ok(left)
+    }
+
+    // Bitwise operators bind tighter than comparisons, like in Rust:
+    // `|` < `^` < `&` < shifts < `+`/`-`
+
+    fn parse_bit_or(&mut self) -> ZyraResult<Expression> {
+        self.parse_binary_level(Self::parse_bit_xor, |kind| match kind {
+            TokenKind::Pipe => Some(BinaryOp::BitOr),
+            _ => None,
+        })
+    }
+
+    fn parse_bit_xor(&mut self) -> ZyraResult<Expression> {
+        self.parse_binary_level(Self::parse_bit_and, |kind| match kind {
+            TokenKind::Caret => Some(BinaryOp::BitXor),
+            _ => None,
+        })
+    }
+
+    fn parse_bit_and(&mut self) -> ZyraResult<Expression> {
+        self.parse_binary_level(Self::parse_shift, |kind| match kind {
+            TokenKind::Ampersand => Some(BinaryOp::BitAnd),
+            _ => None,
+        })
+    }
+
+    fn parse_shift(&mut self) -> ZyraResult<Expression> {
+        self.parse_binary_level(Self::parse_term, |kind| match kind {
+            TokenKind::ShiftLeft => Some(BinaryOp::ShiftLeft),
+            TokenKind::ShiftRight => Some(BinaryOp::ShiftRight),
+            _ => None,
+        })
+    }
+
+    /// Parse a left-associative level of binary operators: `operand (op operand)*`
+    fn parse_binary_level(
+        &mut self,
+        operand: fn(&mut Self) -> ZyraResult<Expression>,
+        operator: fn(&TokenKind) -> Option<BinaryOp>,
+    ) -> ZyraResult<Expression> {
+        let mut left = operand(self)?;
+
+        while let Some(op) = operator(&self.peek().kind) {
+            self.advance();
+
+            let right = operand(self)?;
             let span = Span::new(
                 left.span().start,
                 right.span().end,
@@ -846,7 +1631,7 @@ impl Parser {
         match self.peek().kind {
             TokenKind::Minus => {
                 self.advance();
-                let operand = self.parse_unary()?;
+                let operand = self.nested(Self::parse_unary)?;
                 let span = Span::new(
                     start_span.start,
                     operand.span().end,
@@ -862,7 +1647,7 @@ impl Parser {
             }
             TokenKind::Not => {
                 self.advance();
-                let operand = self.parse_unary()?;
+                let operand = self.nested(Self::parse_unary)?;
                 let span = Span::new(
                     start_span.start,
                     operand.span().end,
@@ -884,7 +1669,7 @@ impl Parser {
                 } else {
                     false
                 };
-                let value = self.parse_unary()?;
+                let value = self.nested(Self::parse_unary)?;
                 let span = Span::new(
                     start_span.start,
                     value.span().end,
@@ -900,7 +1685,7 @@ impl Parser {
             }
             TokenKind::Star => {
                 self.advance();
-                let value = self.parse_unary()?;
+                let value = self.nested(Self::parse_unary)?;
                 let span = Span::new(
                     start_span.start,
                     value.span().end,
@@ -965,6 +1750,24 @@ impl Parser {
                 };
             } else if self.check(&TokenKind::Dot) {
                 self.advance();
+
+                // Tuple element: pair.0
+                if let TokenKind::Int(index) = self.peek().kind {
+                    self.advance();
+                    let span = Span::new(
+                        expr.span().start,
+                        self.previous().span.end,
+                        expr.span().line,
+                        expr.span().column,
+                    );
+                    expr = Expression::TupleIndex {
+                        object: Box::new(expr),
+                        index: index as usize,
+                        span,
+                    };
+                    continue;
+                }
+
                 let field = self.expect_identifier("Expected field name after '.'")?;
 
                 let span = Span::new(
@@ -1028,7 +1831,16 @@ impl Parser {
         let span = token.span;
 
         match token.kind {
-            TokenKind::Int(value) => Ok(Expression::Int { value, span }),
+            TokenKind::Int(value) => Ok(Expression::Int {
+                value,
+                suffix: None,
+                span,
+            }),
+            TokenKind::SuffixedInt(value, suffix) => Ok(Expression::Int {
+                value,
+                suffix: Some(Type::Named(suffix.to_string())),
+                span,
+            }),
             TokenKind::Float(value) => Ok(Expression::Float { value, span }),
             TokenKind::True => Ok(Expression::Bool { value: true, span }),
             TokenKind::False => Ok(Expression::Bool { value: false, span }),
@@ -1129,6 +1941,56 @@ impl Parser {
                     return Ok(Expression::VecLiteral { elements, span });
                 }
 
+                // Check for map literal: map{"a": 1}. The brace must follow `map`
+                // directly, so a variable named map still works in `if map {`
+                if name == "map"
+                    && self.check(&TokenKind::LeftBrace)
+                    && self.peek().span.start == span.end
+                {
+                    self.advance(); // Consume {
+                    let mut entries = Vec::new();
+
+                    while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+                        let key = self.parse_expression()?;
+                        self.expect(&TokenKind::Colon, "Expected ':' after map key")?;
+                        let value = self.parse_expression()?;
+                        entries.push((key, value));
+                        if !self.check(&TokenKind::Comma) {
+                            break;
+                        }
+                        self.advance();
+                    }
+
+                    self.expect(&TokenKind::RightBrace, "Expected '}' after map entries")?;
+
+                    let end_span = self.previous().span;
+                    let span = Span::new(span.start, end_span.end, span.line, span.column);
+                    return Ok(Expression::MapLiteral { entries, span });
+                }
+
+                // Check for set literal: set{1, 2}, with the same adjacency rule as map{
+                if name == "set"
+                    && self.check(&TokenKind::LeftBrace)
+                    && self.peek().span.start == span.end
+                {
+                    self.advance(); // Consume {
+                    let mut elements = Vec::new();
+
+                    while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+                        elements.push(self.parse_expression()?);
+                        if !self.check(&TokenKind::Comma) {
+                            break;
+                        }
+                        self.advance();
+                    }
+
+                    self.expect(&TokenKind::RightBrace, "Expected '}' after set elements")?;
+
+                    let end_span = self.previous().span;
+                    let span = Span::new(span.start, end_span.end, span.line, span.column);
+                    return Ok(Expression::SetLiteral { elements, span });
+                }
+
                 // Check for qualified path (module::function or module::StructName)
                 let mut full_path = name;
                 while self.check(&TokenKind::ColonColon) {
@@ -1139,7 +2001,7 @@ impl Parser {
                     } else {
                         return Err(ZyraError::syntax_error(
                             "Expected identifier after '::'",
-                            SourceLocation::new("", self.peek().span.line, self.peek().span.column),
+                            SourceLocation::from_span(&self.peek().span),
                         ));
                     }
                 }
@@ -1167,11 +2029,7 @@ impl Parser {
                         } else {
                             return Err(ZyraError::syntax_error(
                                 "Expected field name in struct initializer",
-                                SourceLocation::new(
-                                    "",
-                                    self.peek().span.line,
-                                    self.peek().span.column,
-                                ),
+                                SourceLocation::from_span(&self.peek().span),
                             ));
                         };
 
@@ -1190,11 +2048,7 @@ impl Parser {
                         } else {
                             return Err(ZyraError::syntax_error(
                                 "Expected ':' or ',' after field name in struct initializer",
-                                SourceLocation::new(
-                                    "",
-                                    self.peek().span.line,
-                                    self.peek().span.column,
-                                ),
+                                SourceLocation::from_span(&self.peek().span),
                             ));
                         };
 
@@ -1275,6 +2129,23 @@ impl Parser {
 
             TokenKind::LeftParen => {
                 let inner = self.parse_expression()?;
+
+                // A comma makes it a tuple: (a, b), or (a,) with one element
+                if self.check(&TokenKind::Comma) {
+                    let mut elements = vec![inner];
+                    while self.check(&TokenKind::Comma) {
+                        self.advance();
+                        if self.check(&TokenKind::RightParen) {
+                            break;
+                        }
+                        elements.push(self.parse_expression()?);
+                    }
+                    self.expect(&TokenKind::RightParen, "Expected ')' after tuple elements")?;
+                    let end_span = self.previous().span;
+                    let span = Span::new(span.start, end_span.end, span.line, span.column);
+                    return Ok(Expression::Tuple { elements, span });
+                }
+
                 self.expect(&TokenKind::RightParen, "Expected ')' after expression")?;
 
                 let end_span = self.previous().span;
@@ -1308,6 +2179,9 @@ impl Parser {
             }
 
             // Match expression: match expr { pattern => body, ... }
+            // `if` in value position: `let x = if cond { a } else { b };`
+            TokenKind::If => self.parse_if_expression(span),
+
             TokenKind::Match => {
                 let scrutinee = Box::new(self.parse_expression()?);
                 self.expect(&TokenKind::LeftBrace, "Expected '{' after match expression")?;
@@ -1335,7 +2209,10 @@ impl Parser {
                 })
             }
 
-            _ => Err(self.error(&format!("Unexpected token: {}", token.kind))),
+            _ => Err(ZyraError::syntax_error(
+                &format!("Unexpected token: {}", token.kind),
+                SourceLocation::from_span(&span).with_snippet(&token.lexeme),
+            )),
         }
     }
 
@@ -1373,8 +2250,100 @@ impl Parser {
         })
     }
 
-    /// Parse a pattern
+    /// Parse a pattern, with any `|` alternatives
     fn parse_pattern(&mut self) -> ZyraResult<Pattern> {
+        self.nested(Self::parse_pattern_unguarded)
+    }
+
+    fn parse_pattern_unguarded(&mut self) -> ZyraResult<Pattern> {
+        let span = self.peek().span;
+        let first = self.parse_pattern_alternative()?;
+        if !self.check(&TokenKind::Pipe) {
+            return Ok(first);
+        }
+
+        let mut alternatives = vec![first];
+        while self.check(&TokenKind::Pipe) {
+            self.advance(); // consume '|'
+            alternatives.push(self.parse_pattern_alternative()?);
+        }
+        let end_span = self.previous().span;
+        let span = Span::new(span.start, end_span.end, span.line, span.column);
+        Ok(Pattern::Or { alternatives, span })
+    }
+
+    /// Parse an integer or char literal pattern, or a range starting with
+    /// one: `-1`, `'a'`, `1..=9`, `0..10`
+    fn parse_literal_or_range_pattern(&mut self, start_span: Span) -> ZyraResult<Pattern> {
+        let start = self.parse_range_bound()?;
+        let inclusive = match self.peek().kind {
+            TokenKind::DotDotEq => true,
+            TokenKind::DotDot => false,
+            _ => {
+                let end_span = self.previous().span;
+                return Ok(Pattern::Literal {
+                    value: start,
+                    span: Span::new(
+                        start_span.start,
+                        end_span.end,
+                        start_span.line,
+                        start_span.column,
+                    ),
+                });
+            }
+        };
+        self.advance(); // consume '..' or '..='
+
+        let end = self.parse_range_bound()?;
+        let end_span = self.previous().span;
+        let span = Span::new(
+            start_span.start,
+            end_span.end,
+            start_span.line,
+            start_span.column,
+        );
+        if !matches!(
+            (&start, &end),
+            (LiteralPattern::Int(_), LiteralPattern::Int(_))
+                | (LiteralPattern::Char(_), LiteralPattern::Char(_))
+        ) {
+            return Err(ZyraError::syntax_error(
+                "Range pattern bounds must both be integers or both be chars",
+                SourceLocation::from_span(&span),
+            ));
+        }
+        Ok(Pattern::Range {
+            start,
+            end,
+            inclusive,
+            span,
+        })
+    }
+
+    /// One bound of a range pattern: an integer or integer constant,
+    /// possibly negative, or a char
+    fn parse_range_bound(&mut self) -> ZyraResult<LiteralPattern> {
+        let negative = self.check(&TokenKind::Minus);
+        if negative {
+            self.advance(); // consume '-'
+        }
+        let value = match &self.peek().kind {
+            TokenKind::Int(n) | TokenKind::SuffixedInt(n, _) => {
+                LiteralPattern::Int(if negative { n.wrapping_neg() } else { *n })
+            }
+            TokenKind::Identifier(name) if self.constants.contains_key(name) => {
+                let n = self.constants[name];
+                LiteralPattern::Int(if negative { n.wrapping_neg() } else { n })
+            }
+            TokenKind::Char(c) if !negative => LiteralPattern::Char(*c),
+            _ => return Err(self.error("Expected an integer or char literal in pattern")),
+        };
+        self.advance();
+        Ok(value)
+    }
+
+    /// Parse one pattern of an or-pattern
+    fn parse_pattern_alternative(&mut self) -> ZyraResult<Pattern> {
         let token = self.peek().clone();
         let span = token.span;
 
@@ -1399,15 +2368,23 @@ impl Parser {
                 }
             }
 
-            // Literal patterns
-            TokenKind::Int(n) => {
-                let n = *n;
-                self.advance();
-                Ok(Pattern::Literal {
-                    value: LiteralPattern::Int(n),
-                    span,
-                })
+            // Integer and char literals, which can start a range, and the
+            // integer constants that stand for them
+            TokenKind::Int(_)
+            | TokenKind::SuffixedInt(..)
+            | TokenKind::Char(_)
+            | TokenKind::Minus => self.parse_literal_or_range_pattern(span),
+            TokenKind::Identifier(name)
+                if self.constants.contains_key(name)
+                    && !matches!(
+                        self.tokens.get(self.current + 1).map(|t| &t.kind),
+                        Some(TokenKind::ColonColon | TokenKind::LeftBrace | TokenKind::LeftParen)
+                    ) =>
+            {
+                self.parse_literal_or_range_pattern(span)
             }
+
+            // Other literal patterns
             TokenKind::Float(f) => {
                 let f = *f;
                 self.advance();
@@ -1430,14 +2407,6 @@ impl Parser {
                     span,
                 })
             }
-            TokenKind::Char(c) => {
-                let c = *c;
-                self.advance();
-                Ok(Pattern::Literal {
-                    value: LiteralPattern::Char(c),
-                    span,
-                })
-            }
             TokenKind::String(s) => {
                 let s = s.clone();
                 self.advance();
@@ -1644,6 +2613,10 @@ impl Parser {
     // ===== Type Parsing =====
 
     fn parse_type(&mut self) -> ZyraResult<Type> {
+        self.nested(Self::parse_type_unguarded)
+    }
+
+    fn parse_type_unguarded(&mut self) -> ZyraResult<Type> {
         // Check for lifetime-annotated type ('a Type)
         if let TokenKind::Lifetime(lt) = &self.peek().kind {
             let lifetime = lt.clone();
@@ -1711,7 +2684,7 @@ impl Parser {
                 self.advance();
                 self.expect(&TokenKind::Less, "Expected '<' after 'List'")?;
                 let inner = self.parse_type()?;
-                self.expect(&TokenKind::Greater, "Expected '>' after list type")?;
+                self.expect_closing_angle("Expected '>' after list type")?;
                 Type::List(Box::new(inner))
             }
             TokenKind::Identifier(ref name) => {
@@ -1726,24 +2699,103 @@ impl Parser {
                         // Vec requires generic type argument parsing
                         self.expect(&TokenKind::Less, "Expected '<' after 'Vec'")?;
                         let inner = self.parse_type()?;
-                        self.expect(&TokenKind::Greater, "Expected '>' after vector type")?;
+                        self.expect_closing_angle("Expected '>' after vector type")?;
                         Type::Vec(Box::new(inner))
                     }
+                    "Map" => {
+                        self.expect(&TokenKind::Less, "Expected '<' after 'Map'")?;
+                        let key = self.parse_type()?;
+                        self.expect(
+                            &TokenKind::Comma,
+                            "Expected ',' between map key and value types",
+                        )?;
+                        let value = self.parse_type()?;
+                        self.expect_closing_angle("Expected '>' after map type")?;
+                        Type::Map(Box::new(key), Box::new(value))
+                    }
+                    "Set" => {
+                        self.expect(&TokenKind::Less, "Expected '<' after 'Set'")?;
+                        let inner = self.parse_type()?;
+                        self.expect_closing_angle("Expected '>' after set type")?;
+                        Type::Set(Box::new(inner))
+                    }
+                    "Fn" if self.check(&TokenKind::LeftParen) => {
+                        // Fn(A, B) -> R; the return type defaults to Void
+                        self.advance(); // consume '('
+                        let mut params = Vec::new();
+                        while !self.check(&TokenKind::RightParen) {
+                            params.push(self.parse_type()?);
+                            if !self.check(&TokenKind::Comma) {
+                                break;
+                            }
+                            self.advance();
+                        }
+                        self.expect(&TokenKind::RightParen, "Expected ')' after Fn parameters")?;
+                        let return_type = if self.check(&TokenKind::Arrow) {
+                            self.advance();
+                            self.parse_type()?
+                        } else {
+                            Type::Named("Void".to_string())
+                        };
+                        Type::Function {
+                            params,
+                            return_type: Box::new(return_type),
+                        }
+                    }
+                    "Option" if self.check(&TokenKind::Less) => {
+                        self.advance(); // consume '<'
+                        let inner = self.parse_type()?;
+                        self.expect_closing_angle("Expected '>' after option type")?;
+                        Type::Option(Box::new(inner))
+                    }
                     // All other identifiers (primitives, user types) are Named
                     // Semantic phase's resolve_type_name will resolve them
                     _ => Type::Named(name),
                 }
             }
+            // (A, B) is a tuple, (A,) a tuple of one, (A) just A and () Void
+            TokenKind::LeftParen => {
+                self.advance();
+                let mut elements = Vec::new();
+                let mut trailing_comma = false;
+                while !self.check(&TokenKind::RightParen) {
+                    elements.push(self.parse_type()?);
+                    trailing_comma = self.check(&TokenKind::Comma);
+                    if !trailing_comma {
+                        break;
+                    }
+                    self.advance();
+                }
+                self.expect(&TokenKind::RightParen, "Expected ')' after tuple type")?;
+                match elements.len() {
+                    0 => Type::Named("()".to_string()),
+                    1 if !trailing_comma => elements.remove(0),
+                    _ => Type::Tuple(elements),
+                }
+            }
             TokenKind::LeftBracket => {
                 self.advance();
                 let inner = self.parse_type()?;
                 self.expect(&TokenKind::Semicolon, "Expected ';' in array type")?;
 
-                let size = if let TokenKind::Int(n) = self.peek().kind {
-                    self.advance();
-                    n as usize
-                } else {
-                    return Err(self.error("Expected integer size for array"));
+                let size_expr = self.parse_expression()?;
+                let size = match self.const_int(&size_expr).map(usize::try_from) {
+                    Some(Ok(size)) => size,
+                    Some(Err(_)) => {
+                        return Err(ZyraError::syntax_error(
+                            "Array size can't be negative",
+                            SourceLocation::from_span(&size_expr.span()),
+                        ))
+                    }
+                    None => {
+                        return Err(ZyraError::syntax_error(
+                            "Array size must be an integer known at compile time",
+                            SourceLocation::from_span(&size_expr.span()),
+                        )
+                        .with_suggestion(
+                            "Use a literal or arithmetic on constants declared above it in this file, like [int; 2 * TILE]",
+                        ))
+                    }
                 };
 
                 self.expect(&TokenKind::RightBracket, "Expected ']' after array size")?;
@@ -1804,56 +2856,7 @@ impl Parser {
         // Expect closing |
         self.expect(&TokenKind::Pipe, "Expected '|' after closure parameters")?;
 
-        // Check for optional return type annotation: -> Type
-        let return_type = if self.check(&TokenKind::Arrow) {
-            self.advance(); // consume ->
-            Some(self.parse_type()?)
-        } else {
-            None
-        };
-
-        // Parse body: either { block } or single expression
-        let body = if self.check(&TokenKind::LeftBrace) {
-            // Block body: { statements; optional_expr }
-            let block = self.parse_block()?;
-            if let Some(expression) = block.expression {
-                *expression
-            } else if !block.statements.is_empty() {
-                // If no trailing expr but has statements, wrap in grouped
-                Expression::Grouped {
-                    inner: Box::new(Expression::Int {
-                        value: 0,
-                        span: start_span,
-                    }), // placeholder
-                    span: start_span,
-                }
-            } else {
-                // Empty block
-                Expression::Int {
-                    value: 0,
-                    span: start_span,
-                }
-            }
-        } else {
-            // Single expression body
-            self.parse_expression()?
-        };
-
-        let end_span = body.span();
-        let span = Span::new(
-            start_span.start,
-            end_span.end,
-            start_span.line,
-            start_span.column,
-        );
-
-        Ok(Expression::Closure {
-            params,
-            return_type,
-            body: Box::new(body),
-            capture_mode,
-            span,
-        })
+        self.parse_closure_body_after_params(start_span, capture_mode, params)
     }
 
     /// Parse closure body when params are already known (for empty closures ||)
@@ -1871,37 +2874,22 @@ impl Parser {
             None
         };
 
-        // Parse body: either { block } or single expression
+        // Parse body: either { block } or a single expression, kept as a
+        // block with only a trailing expression
         let body = if self.check(&TokenKind::LeftBrace) {
-            // Block body: { statements; optional_expr }
-            let block = self.parse_block()?;
-            if let Some(expression) = block.expression {
-                *expression
-            } else if !block.statements.is_empty() {
-                // If no trailing expr but has statements, wrap in grouped
-                Expression::Grouped {
-                    inner: Box::new(Expression::Int {
-                        value: 0,
-                        span: start_span,
-                    }),
-                    span: start_span,
-                }
-            } else {
-                // Empty block
-                Expression::Int {
-                    value: 0,
-                    span: start_span,
-                }
-            }
+            self.parse_block()?
         } else {
-            // Single expression body
-            self.parse_expression()?
+            let expression = self.parse_expression()?;
+            Block {
+                statements: Vec::new(),
+                span: expression.span(),
+                expression: Some(Box::new(expression)),
+            }
         };
 
-        let end_span = body.span();
         let span = Span::new(
             start_span.start,
-            end_span.end,
+            body.span.end,
             start_span.line,
             start_span.column,
         );
@@ -1909,7 +2897,7 @@ impl Parser {
         Ok(Expression::Closure {
             params,
             return_type,
-            body: Box::new(body),
+            body,
             capture_mode,
             span,
         })
@@ -1952,6 +2940,19 @@ impl Parser {
         }
     }
 
+    /// Expect the `>` closing a generic type. `>>` closes two, so only its
+    /// first half is consumed: `Vec<Vec<i32>>`
+    fn expect_closing_angle(&mut self, message: &str) -> ZyraResult<()> {
+        if self.check(&TokenKind::ShiftRight) {
+            let token = &mut self.tokens[self.current];
+            token.kind = TokenKind::Greater;
+            token.span.start += 1;
+            token.span.column += 1;
+            return Ok(());
+        }
+        self.expect(&TokenKind::Greater, message).map(|_| ())
+    }
+
     fn expect_identifier(&mut self, message: &str) -> ZyraResult<String> {
         if let TokenKind::Identifier(name) = &self.peek().kind {
             let name = name.clone();
@@ -1966,7 +2967,7 @@ impl Parser {
         let token = self.peek();
         ZyraError::syntax_error(
             message,
-            SourceLocation::new("", token.span.line, token.span.column).with_snippet(&token.lexeme),
+            SourceLocation::from_span(&token.span).with_snippet(&token.lexeme),
         )
     }
 
@@ -2099,6 +3100,10 @@ impl Parser {
             if self.check(&TokenKind::Func) {
                 let method = self.parse_function()?;
                 methods.push(Box::new(method));
+            } else if self.check(&TokenKind::Pub) {
+                return Err(
+                    self.error("Methods are visible wherever their type is; remove 'pub' here")
+                );
             } else {
                 return Err(self.error("Only functions are allowed in impl blocks"));
             }
@@ -2257,4 +3262,336 @@ mod tests {
             panic!("Expected Expression statement");
         }
     }
+
+    #[test]
+    fn test_bitwise_precedence() {
+        let operator_of = |source: &str| match &parse(source).unwrap().statements[0] {
+            Statement::Expression {
+                expr: Expression::Binary { operator, .. },
+                ..
+            } => *operator,
+            _ => panic!("Expected Binary expression"),
+        };
+        // Shifts bind looser than arithmetic, masks looser than shifts,
+        // and comparisons looser than masks
+        assert_eq!(operator_of("1 + 2 << 3;"), BinaryOp::ShiftLeft);
+        assert_eq!(operator_of("a | b & c << 1;"), BinaryOp::BitOr);
+        assert_eq!(operator_of("a ^ b | c;"), BinaryOp::BitOr);
+        assert_eq!(operator_of("flags & 4 != 0;"), BinaryOp::NotEqual);
+
+        // `>>` still closes nested generics
+        assert!(parse("let v: Vec<Vec<i32>> = [];").is_ok());
+        assert!(parse("func f(m: Map<String, Option<i32>>) { }").is_ok());
+    }
+
+    #[test]
+    fn test_labeled_loop() {
+        let program =
+            parse("'outer: while true { for i in 0..3 { break 'outer; continue; } }").unwrap();
+
+        if let Statement::While { label, body, .. } = &program.statements[0] {
+            assert_eq!(label.as_deref(), Some("outer"));
+            if let Statement::For { label, body, .. } = &body.statements[0] {
+                assert!(label.is_none());
+                assert!(matches!(
+                    &body.statements[0],
+                    Statement::Break { label: Some(l), .. } if l == "outer"
+                ));
+                assert!(matches!(
+                    &body.statements[1],
+                    Statement::Continue { label: None, .. }
+                ));
+            } else {
+                panic!("Expected For statement");
+            }
+        } else {
+            panic!("Expected While statement");
+        }
+    }
+
+    #[test]
+    fn test_for_step_and_rev() {
+        let program =
+            parse("for i in (0..=10).rev() step 2 { } for j in 10..0 step -1 { }").unwrap();
+
+        if let Statement::For {
+            inclusive,
+            step,
+            reverse,
+            ..
+        } = &program.statements[0]
+        {
+            assert!(*inclusive && *reverse);
+            assert_eq!(step.as_deref().and_then(Expression::constant_int), Some(2));
+        } else {
+            panic!("Expected For statement");
+        }
+        if let Statement::For { step, reverse, .. } = &program.statements[1] {
+            assert!(!*reverse);
+            assert_eq!(step.as_deref().and_then(Expression::constant_int), Some(-1));
+        } else {
+            panic!("Expected For statement");
+        }
+    }
+
+    #[test]
+    fn test_if_let() {
+        let program = parse("if let Some(x) = find(1) { x; } else { 0; }").unwrap();
+
+        if let Statement::IfLet {
+            pattern,
+            else_block,
+            ..
+        } = &program.statements[0]
+        {
+            assert!(matches!(
+                pattern,
+                Pattern::Variant { variant, inner: Some(_), .. } if variant == "Some"
+            ));
+            assert!(else_block.is_some());
+        } else {
+            panic!("Expected IfLet statement");
+        }
+    }
+
+    #[test]
+    fn test_labeled_while_let() {
+        let program = parse("'next: while let Some(item) = queue.pop() { break 'next; }").unwrap();
+
+        if let Statement::WhileLet { label, value, .. } = &program.statements[0] {
+            assert_eq!(label.as_deref(), Some("next"));
+            assert!(matches!(value, Expression::Call { .. }));
+        } else {
+            panic!("Expected WhileLet statement");
+        }
+    }
+
+    #[test]
+    fn test_test_functions_are_recorded() {
+        let program =
+            parse("test func adds() { }\nfunc test() { }\nfunc main() { test(); }").unwrap();
+
+        assert_eq!(program.tests, vec!["adds".to_string()]);
+        assert_eq!(program.statements.len(), 3);
+    }
+
+    #[test]
+    fn test_pub_items_are_exported() {
+        let program =
+            parse("pub func area() { }\nfunc helper() { }\npub struct Rect { w: int }").unwrap();
+
+        assert_eq!(
+            program.exports,
+            vec!["area".to_string(), "Rect".to_string()]
+        );
+        assert!(parse("func main() { pub let x = 1; }").is_err());
+        assert!(parse("pub impl Rect { }").is_err());
+    }
+
+    #[test]
+    fn test_edition_names() {
+        assert_eq!(Edition::parse("2025"), Some(Edition::E2025));
+        assert_eq!(Edition::parse("2030"), None);
+        let parser = Parser::new(Vec::new()).with_edition(Edition::E2025);
+        assert_eq!(parser.edition().as_str(), "2025");
+    }
+
+    #[test]
+    fn test_trailing_else_if_chain_is_one_expression() {
+        let program = parse(
+            "func grade(n: i64) -> String {\n\
+             if n >= 90 { \"A\" } else if n >= 80 { \"B\" } else { \"F\" }\n}",
+        )
+        .unwrap();
+        let Statement::Function { body, .. } = &program.statements[0] else {
+            panic!("expected grade");
+        };
+        assert!(body.statements.is_empty());
+        let Some(Expression::If {
+            else_block: Some(else_block),
+            ..
+        }) = body.expression.as_deref()
+        else {
+            panic!("expected a trailing if");
+        };
+        // The `else if` is the else block's value, not a statement in it
+        assert!(else_block.statements.is_empty());
+        assert!(matches!(
+            else_block.expression.as_deref(),
+            Some(Expression::If {
+                else_block: Some(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_if_is_an_expression_anywhere() {
+        let program = parse(
+            "func f(n: i64) -> i64 {\n\
+             let x = if n > 0 { 1 } else if n < 0 { 2 } else { 3 };\n\
+             g(if x == 1 { 10 } else { 20 });\n\
+             return if x > 1 { x } else { 0 };\n}",
+        )
+        .unwrap();
+        let Statement::Function { body, .. } = &program.statements[0] else {
+            panic!("expected f");
+        };
+        assert!(matches!(
+            &body.statements[0],
+            Statement::Let {
+                value: Expression::If { .. },
+                ..
+            }
+        ));
+        let Statement::Expression {
+            expr: Expression::Call { arguments, .. },
+            ..
+        } = &body.statements[1]
+        else {
+            panic!("expected a call");
+        };
+        assert!(matches!(arguments[0], Expression::If { .. }));
+        assert!(matches!(
+            &body.statements[2],
+            Statement::Return {
+                value: Some(Expression::If { .. }),
+                ..
+            }
+        ));
+
+        let error = parse("func f() { let x = if let Some(y) = z { y } else { 0 }; }").unwrap_err();
+        assert!(error.message.contains("'if let' can't be used as a value"));
+    }
+
+    #[test]
+    fn test_constants_fold_into_array_sizes_and_patterns() {
+        let program = parse(
+            "const TILE: int = 16;\nconst W: i32 = 2 * TILE - (1 << 2);\n\
+             func f(row: [int; W], d: int) -> int {\n\
+             match d { -TILE..=-1 => -1, 0 | W => 0, _ => 1 }\n}",
+        )
+        .unwrap();
+        let Statement::Function { params, body, .. } = &program.statements[2] else {
+            panic!("expected f");
+        };
+        assert_eq!(params[0].param_type.as_str(), "[int; 28]");
+        let Some(Expression::Match { arms, .. }) = body.expression.as_deref() else {
+            panic!("expected a match");
+        };
+        assert!(matches!(
+            arms[0].pattern,
+            Pattern::Range {
+                start: LiteralPattern::Int(-16),
+                end: LiteralPattern::Int(-1),
+                ..
+            }
+        ));
+        assert!(matches!(
+            arms[1].pattern.alternatives()[1],
+            Pattern::Literal {
+                value: LiteralPattern::Int(28),
+                ..
+            }
+        ));
+
+        let err = parse("func f(a: [int; N]) {}").unwrap_err();
+        assert!(err.message.contains("known at compile time"));
+    }
+
+    #[test]
+    fn test_tuples() {
+        let program = parse(
+            "func f(p: (i64, (bool,))) -> (i64, String) {\n\
+             let (a, mut b) = (p.0, p.1.0);\n\
+             return (a, \"x\");\n}",
+        )
+        .unwrap();
+        let Statement::Function { params, body, .. } = &program.statements[0] else {
+            panic!("expected f");
+        };
+        assert_eq!(params[0].param_type.as_str(), "(i64, (bool,))");
+        let Statement::LetPattern {
+            pattern: Pattern::Tuple { elements, .. },
+            value: Expression::Tuple {
+                elements: values, ..
+            },
+            ..
+        } = &body.statements[0]
+        else {
+            panic!("expected a destructuring let");
+        };
+        assert!(matches!(
+            elements[1],
+            Pattern::Identifier { mutable: true, .. }
+        ));
+        // `p.1.0` is two element accesses, not `p.1` and a float
+        assert!(matches!(
+            &values[1],
+            Expression::TupleIndex { object, index: 0, .. }
+                if matches!(object.as_ref(), Expression::TupleIndex { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parsing_resumes_after_syntax_errors() {
+        let source =
+            "func a() {\n    let x = 1 +;\n    let y = 2\n    let z = Point { x: , y: 2 };\n}\n\
+                      func b(n: ) {\n    let ok = 1;\n}\n\
+                      func main() {\n    a();\n}";
+        let tokens = Lexer::new(source, "test.zr").tokenize().unwrap();
+        let (program, errors) = Parser::new(tokens).parse_recovering();
+        let lines: Vec<usize> = errors
+            .iter()
+            .map(|e| e.location.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, [2, 4, 4, 6]);
+        // Everything around the errors is still there
+        let names: Vec<&str> = program
+            .statements
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Statement::Function { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, ["a", "main"]);
+
+        let err = parse(source).unwrap_err();
+        assert_eq!(err.all().count(), 4);
+    }
+
+    #[test]
+    fn test_unstable_syntax_needs_its_feature() {
+        let source = "func main() { let mut n = 0; loop { break; }\n\
+                      'outer: loop { n += 1; if n > 3 { break 'outer; } } }";
+        let tokens = Lexer::new(source, "test.zr").tokenize().unwrap();
+        let err = Parser::new(tokens.clone()).parse().unwrap_err();
+        assert_eq!(err.message, "`loop` is an unstable feature");
+        assert!(err
+            .suggestion
+            .as_ref()
+            .unwrap()
+            .contains("unstable = [\"loop\"]"));
+
+        let program = Parser::new(tokens)
+            .with_features(&[Feature::Loop])
+            .parse()
+            .unwrap();
+        let Statement::Function { body, .. } = &program.statements[0] else {
+            panic!("expected main");
+        };
+        assert!(matches!(
+            &body.statements[1],
+            Statement::While { label: None, .. }
+        ));
+        assert!(matches!(
+            &body.statements[2],
+            Statement::While { label: Some(label), condition: Expression::Bool { value: true, .. }, .. }
+                if label == "outer"
+        ));
+        // `loop` is still an ordinary name elsewhere
+        parse("func main() { let loop = 1; let n = loop + 1; }").unwrap();
+        assert_eq!(Feature::parse("loop"), Some(Feature::Loop));
+    }
 }
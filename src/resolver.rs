@@ -1,23 +1,54 @@
 //! Module Resolver for Zyra
 //!
 //! Resolves import paths (e.g., `src::ball`) to actual .zr file paths
-//! and loads/parses their content.
+//! and loads/parses their content. Paths are looked up next to the importing
+//! file first, then in the project's source root (`src/`), so `import ball;`
+//! finds `src/ball.zr`. Imports starting with a dependency's name are looked
+//! up in that package instead (`import mylib::helpers;` -> `<mylib>/src/helpers.zr`,
+//! `import mylib;` -> `<mylib>/src/lib.zr`).
+//!
+//! With an interface directory, each loaded module is matched against its
+//! `.zyi` interface file (see `interface.rs`) so the analyzer can skip modules
+//! that haven't changed since they last passed analysis.
 
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::cache::content_hash;
 use crate::error::{ZyraError, ZyraResult};
+use crate::interface::{ModuleInterface, INTERFACE_EXT};
 use crate::lexer::Lexer;
-use crate::parser::ast::{Program, Statement};
-use crate::parser::Parser;
+use crate::parser::ast::{ModuleItem, Program, Statement};
+use crate::parser::{Edition, Feature, Parser};
 
 /// Module resolver for loading .zr files
 pub struct ModuleResolver {
     /// Base directory for resolving imports
     base_dir: PathBuf,
+    /// Project source directory searched when an import isn't found in base_dir
+    source_root: Option<PathBuf>,
+    /// Package roots of path dependencies by package name
+    dependencies: HashMap<String, PathBuf>,
     /// Cache of already loaded modules
     loaded_modules: HashMap<String, Program>,
+    /// Files of the loaded modules, in load order
+    loaded_files: Vec<PathBuf>,
+    /// Edition modules are parsed with
+    edition: Edition,
+    /// Unstable features modules may use
+    features: Vec<Feature>,
+    /// Directory holding the modules' interface files
+    interface_dir: Option<PathBuf>,
+    /// Up-to-date interfaces of loaded modules
+    interfaces: Vec<ModuleInterface>,
+    /// Interfaces of loaded modules without an up-to-date file, with their path
+    pending_interfaces: Vec<(PathBuf, ModuleInterface)>,
+    /// Source hashes of the loaded modules, part of every interface's key
+    source_hashes: Vec<u64>,
+    /// Loaded modules not yet matched against their interface file: the file,
+    /// the module name, its source hash and its program
+    unmatched: Vec<(PathBuf, String, u64, Program)>,
 }
 
 impl ModuleResolver {
@@ -25,18 +56,108 @@ impl ModuleResolver {
     pub fn new(base_dir: &Path) -> Self {
         Self {
             base_dir: base_dir.to_path_buf(),
+            source_root: None,
+            dependencies: HashMap::new(),
             loaded_modules: HashMap::new(),
+            loaded_files: Vec::new(),
+            edition: Edition::default(),
+            features: Vec::new(),
+            interface_dir: None,
+            interfaces: Vec::new(),
+            pending_interfaces: Vec::new(),
+            source_hashes: Vec::new(),
+            unmatched: Vec::new(),
         }
     }
 
+    /// Parse imported modules with the given edition
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
+    /// Let imported modules use these unstable features
+    pub fn with_features(mut self, features: &[Feature]) -> Self {
+        self.features.extend_from_slice(features);
+        self
+    }
+
+    /// Also look up imports in a project's source directory
+    pub fn with_source_root(mut self, source_root: &Path) -> Self {
+        self.source_root = Some(source_root.to_path_buf());
+        self
+    }
+
+    /// Read and write module interface files in `dir`
+    pub fn with_interface_dir(mut self, dir: &Path) -> Self {
+        self.interface_dir = Some(dir.to_path_buf());
+        self
+    }
+
+    /// Resolve imports starting with `name` in the package at `root`
+    pub fn with_dependency(mut self, name: &str, root: &Path) -> Self {
+        self.dependencies
+            .insert(name.to_string(), root.to_path_buf());
+        self
+    }
+
+    /// Files an import path may refer to, in lookup order
+    fn candidate_paths(&self, import_path: &[String]) -> Vec<PathBuf> {
+        if let Some((package, module)) = import_path.split_first() {
+            if let Some(root) = self.dependencies.get(package) {
+                // `import mylib;` loads the package's src/lib.zr
+                if module.is_empty() {
+                    return vec![root.join("src").join("lib.zr")];
+                }
+                let mut path = root.join("src");
+                for segment in module {
+                    path = path.join(segment);
+                }
+                return vec![path.with_extension("zr")];
+            }
+        }
+        std::iter::once(&self.base_dir)
+            .chain(&self.source_root)
+            .map(|dir| {
+                let mut path = dir.clone();
+                for segment in import_path {
+                    path = path.join(segment);
+                }
+                path.with_extension("zr")
+            })
+            .collect()
+    }
+
     /// Resolve an import path to a file path
-    /// Example: ["src", "ball"] -> "src/ball.zr"
+    /// Example: ["src", "ball"] -> "src/ball.zr"; ["ball"] -> "src/ball.zr" with a source root
     pub fn resolve_path(&self, import_path: &[String]) -> PathBuf {
-        let mut path = self.base_dir.clone();
-        for segment in import_path {
-            path = path.join(segment);
+        let candidates = self.candidate_paths(import_path);
+        candidates
+            .iter()
+            .find(|path| path.exists())
+            .unwrap_or(&candidates[0])
+            .clone()
+    }
+
+    /// Source files of every module loaded so far
+    pub fn loaded_files(&self) -> &[PathBuf] {
+        &self.loaded_files
+    }
+
+    /// Interfaces of loaded modules unchanged since they were written
+    pub fn interfaces(&self) -> &[ModuleInterface] {
+        &self.interfaces
+    }
+
+    /// Write the interfaces of loaded modules that have none or a stale one.
+    /// Only call this once the program has passed analysis: an interface
+    /// vouches for its module.
+    pub fn write_interfaces(&mut self) -> std::io::Result<()> {
+        for (path, interface) in std::mem::take(&mut self.pending_interfaces) {
+            interface.store(&path)?;
+            self.interfaces.push(interface);
         }
-        path.with_extension("zr")
+        Ok(())
     }
 
     /// Check if a path is a stdlib import (starts with "std")
@@ -64,11 +185,17 @@ impl ModuleResolver {
 
         // Check if file exists
         if !file_path.exists() {
+            let looked_in: Vec<String> = self
+                .candidate_paths(import_path)
+                .iter()
+                .map(|path| format!("{:?}", path))
+                .collect();
             return Err(ZyraError::new(
                 "ImportError",
                 &format!(
-                    "Module not found: '{}' (looking for {:?})",
-                    module_key, file_path
+                    "Module not found: '{}' (looking for {})",
+                    module_key,
+                    looked_in.join(" or ")
                 ),
                 None,
             ));
@@ -88,11 +215,25 @@ impl ModuleResolver {
         let mut lexer = Lexer::new(&source, &file_str);
         let tokens = lexer.tokenize()?;
 
-        let mut parser = Parser::new(tokens);
+        let mut parser = Parser::new(tokens)
+            .with_edition(self.edition)
+            .with_features(&self.features);
         let program = parser.parse()?;
 
+        if let Some(dir) = &self.interface_dir {
+            let module = import_path.last().cloned().unwrap_or_default();
+            let hash = ModuleInterface::source_hash(
+                &source,
+                &format!("{:?} {:?}", self.edition, self.features),
+            );
+            let path = dir.join(format!("{}.{}", import_path.join("."), INTERFACE_EXT));
+            self.source_hashes.push(hash);
+            self.unmatched.push((path, module, hash, program.clone()));
+        }
+
         // Cache the module
         self.loaded_modules.insert(module_key, program.clone());
+        self.loaded_files.push(file_path);
 
         Ok(Some(program))
     }
@@ -122,11 +263,7 @@ impl ModuleResolver {
                     return Err(ZyraError::new(
                         "ImportError",
                         "Cannot import 'main' - it is the entry point and cannot be imported",
-                        Some(crate::error::SourceLocation::new(
-                            "",
-                            span.line,
-                            span.column,
-                        )),
+                        Some(crate::error::SourceLocation::from_span(span)),
                     ));
                 }
 
@@ -147,6 +284,19 @@ impl ModuleResolver {
                             _ => {
                                 // Add namespace prefix to function and struct names
                                 Self::add_namespace_prefix(&module_name, &mut module_stmt);
+                                if let Some((name, is_type)) = Self::item_name(&module_stmt) {
+                                    let bare_name = name.rsplit("::").next().unwrap_or(name);
+                                    let public =
+                                        module_program.exports.iter().any(|e| e == bare_name);
+                                    program.imported.insert(
+                                        name.to_string(),
+                                        ModuleItem {
+                                            module: module_name.clone(),
+                                            public,
+                                            is_type,
+                                        },
+                                    );
+                                }
                                 imported_statements.push(module_stmt);
                             }
                         }
@@ -158,16 +308,53 @@ impl ModuleResolver {
         // Prepend imported statements to the program
         // (They need to come before the code that uses them)
         let original_statements = std::mem::take(&mut program.statements);
+        program.module_statements = imported_statements.len();
         program.statements = imported_statements;
         program.statements.extend(original_statements);
 
+        self.match_interfaces();
         Ok(())
     }
 
-    /// Add namespace prefix to function and struct names
+    /// Take up the interface files of the modules just loaded. An interface
+    /// is keyed by the sources of all loaded modules, not only its own: a
+    /// module calling into another has to be checked again when that one
+    /// changes.
+    fn match_interfaces(&mut self) {
+        let hashes: Vec<u8> = self
+            .source_hashes
+            .iter()
+            .flat_map(|hash| hash.to_le_bytes())
+            .collect();
+        let modules_hash = content_hash(&hashes);
+        for (path, module, hash, program) in std::mem::take(&mut self.unmatched) {
+            let key = content_hash(&[hash.to_le_bytes(), modules_hash.to_le_bytes()].concat());
+            match ModuleInterface::load(&path, &module, key) {
+                Some(interface) => self.interfaces.push(interface),
+                None => self
+                    .pending_interfaces
+                    .push((path, ModuleInterface::from_program(&module, key, &program))),
+            }
+        }
+    }
+
+    /// Name of a prefixed module item, and whether it is a type
+    fn item_name(stmt: &Statement) -> Option<(&str, bool)> {
+        match stmt {
+            Statement::Function { name, .. }
+            | Statement::Const { name, .. }
+            | Statement::Static { name, .. } => Some((name, false)),
+            Statement::Struct { name, .. } | Statement::Enum { name, .. } => Some((name, true)),
+            _ => None,
+        }
+    }
+
+    /// Add namespace prefix to function, struct, enum, constant and global names
     fn add_namespace_prefix(module_name: &str, stmt: &mut Statement) {
         match stmt {
-            Statement::Function { name, .. } => {
+            Statement::Function { name, .. }
+            | Statement::Const { name, .. }
+            | Statement::Static { name, .. } => {
                 *name = format!("{}::{}", module_name, name);
             }
             Statement::Struct { name, .. } => {
@@ -180,3 +367,153 @@ impl ModuleResolver {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::SemanticAnalyzer;
+
+    fn analyze_with_module(main: &str, module: &str) -> ZyraResult<()> {
+        let dir = std::env::temp_dir().join(format!("zyra_resolver_{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src").join("util.zr"), module).unwrap();
+
+        let tokens = Lexer::new(main, "main.zr").tokenize()?;
+        let mut program = Parser::new(tokens).parse()?;
+        let result = ModuleResolver::new(&dir).resolve_imports(&mut program);
+        fs::remove_dir_all(&dir).unwrap();
+        result?;
+        SemanticAnalyzer::new().analyze(&program)
+    }
+
+    #[test]
+    fn test_private_items_are_hidden_from_importers() {
+        let module = "func helper(x: int) -> int { x + 1 }\n\
+                      pub func double(x: int) -> int { helper(x) * 2 }\n\
+                      struct Secret { v: int }";
+
+        analyze_with_module(
+            "import src::util;\nfunc main() { let n = util::double(1); }",
+            module,
+        )
+        .unwrap();
+
+        let err = analyze_with_module(
+            "import src::util;\nfunc main() { let n = util::helper(1); }",
+            module,
+        )
+        .unwrap_err();
+        assert!(err.message.contains("private to module 'util'"));
+
+        let err = analyze_with_module(
+            "import src::util;\nfunc main() { let s = Secret { v: 1 }; }",
+            module,
+        )
+        .unwrap_err();
+        assert!(err.message.contains("'Secret' is private"));
+    }
+
+    #[test]
+    fn test_imports_fall_back_to_source_root() {
+        let dir = std::env::temp_dir().join(format!("zyra_src_root_{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(
+            dir.join("src").join("player.zr"),
+            "pub func speed() -> int { 5 }",
+        )
+        .unwrap();
+
+        let resolver = ModuleResolver::new(&dir).with_source_root(&dir.join("src"));
+        let path = resolver.resolve_path(&["player".to_string()]);
+        let legacy = resolver.resolve_path(&["src".to_string(), "player".to_string()]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(path, dir.join("src").join("player.zr"));
+        assert_eq!(legacy, path);
+    }
+
+    #[test]
+    fn test_unchanged_modules_are_taken_from_their_interface() {
+        let dir = std::env::temp_dir().join(format!("zyra_interface_{}", std::process::id()));
+        let cache = dir.join(".zyra-cache");
+        fs::create_dir_all(&dir).unwrap();
+        let module = dir.join("util.zr");
+        fs::write(&module, "pub func one() -> int { 1 }").unwrap();
+
+        let interfaces_after = |main: &str| -> usize {
+            let tokens = Lexer::new(main, "main.zr").tokenize().unwrap();
+            let mut program = Parser::new(tokens).parse().unwrap();
+            let mut resolver = ModuleResolver::new(&dir).with_interface_dir(&cache);
+            resolver.resolve_imports(&mut program).unwrap();
+            let reused = resolver.interfaces().len();
+            SemanticAnalyzer::new()
+                .with_interfaces(resolver.interfaces())
+                .analyze(&program)
+                .unwrap();
+            resolver.write_interfaces().unwrap();
+            reused
+        };
+        let main = "import util;\nfunc main() { let n = util::one(); }";
+        let first = interfaces_after(main);
+        let second = interfaces_after(main);
+        fs::write(&module, "pub func one() -> int { 2 }").unwrap();
+        let after_edit = interfaces_after(main);
+        let written = cache.join("util.zyi").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!((first, second, after_edit), (0, 1, 0));
+        assert!(written);
+    }
+
+    #[test]
+    fn test_interfaces_are_checked_again_when_a_dependency_changes() {
+        let dir = std::env::temp_dir().join(format!("zyra_interface_deps_{}", std::process::id()));
+        let cache = dir.join(".zyra-cache");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("a.zr"),
+            "import b;\npub func g() -> int { b::f(41) }",
+        )
+        .unwrap();
+        fs::write(dir.join("b.zr"), "pub func f(x: int) -> int { x }").unwrap();
+
+        let check = || -> ZyraResult<()> {
+            let main = "import a;\nimport b;\nfunc main() { let n = a::g(); }";
+            let mut program = Parser::new(Lexer::new(main, "main.zr").tokenize()?).parse()?;
+            let mut resolver = ModuleResolver::new(&dir).with_interface_dir(&cache);
+            resolver.resolve_imports(&mut program)?;
+            SemanticAnalyzer::new()
+                .with_interfaces(resolver.interfaces())
+                .analyze(&program)?;
+            resolver.write_interfaces().unwrap();
+            Ok(())
+        };
+        let first = check();
+        fs::write(dir.join("b.zr"), "pub func f(x: string) -> int { 1 }").unwrap();
+        let after_edit = check();
+        fs::remove_dir_all(&dir).unwrap();
+
+        first.unwrap();
+        let err = after_edit.unwrap_err();
+        assert!(err.message.contains("expects String"), "{}", err.message);
+    }
+
+    #[test]
+    fn test_dependency_imports_resolve_in_the_package() {
+        let root = PathBuf::from("deps").join("mylib");
+        let resolver = ModuleResolver::new(Path::new("app")).with_dependency("mylib", &root);
+
+        assert_eq!(
+            resolver.resolve_path(&["mylib".to_string(), "helpers".to_string()]),
+            root.join("src").join("helpers.zr")
+        );
+        assert_eq!(
+            resolver.resolve_path(&["mylib".to_string()]),
+            root.join("src").join("lib.zr")
+        );
+        assert_eq!(
+            resolver.resolve_path(&["other".to_string()]),
+            Path::new("app").join("other.zr")
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Compile-time checks of literal arguments
+//!
+//! Some standard library arguments can be wrong in ways their type doesn't
+//! show: a sprite or sound file that isn't there, a malformed color string, a key name
+//! the game module doesn't know. When such an argument is a string literal,
+//! its check runs during analysis, so the mistake is reported by `zyra check`
+//! instead of surfacing as a failed call at runtime. A project can turn
+//! checks off in zyra.toml, e.g. when files are generated while it runs:
+//!
+//! ```toml
+//! [check]
+//! skip = ["path"]
+//! ```
+
+use std::path::Path;
+
+use crate::stdlib::game;
+
+/// A check of literal arguments, named in `[check] skip`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgCheck {
+    /// Assets the program loads must exist: `load_sprite("player.png")`
+    Path,
+    /// Colors must be `#rrggbb` or `#rgb`: `hex_color("#ff8800")`
+    Color,
+    /// Keys must be ones `key_pressed` reports: `key_pressed("Space")`
+    Key,
+}
+
+impl ArgCheck {
+    pub const ALL: &'static [ArgCheck] = &[ArgCheck::Path, ArgCheck::Color, ArgCheck::Key];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|check| check.as_str() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ArgCheck::Path => "path",
+            ArgCheck::Color => "color",
+            ArgCheck::Key => "key",
+        }
+    }
+
+    /// Error kind a failed check is reported as
+    pub fn error_kind(self) -> &'static str {
+        match self {
+            ArgCheck::Path => "FileError",
+            ArgCheck::Color | ArgCheck::Key => "TypeError",
+        }
+    }
+
+    /// Check applying to a standard library function, with the position of
+    /// the argument it looks at
+    pub fn for_function(function: &str) -> Option<(Self, usize)> {
+        let name = function.split("::").last().unwrap_or(function);
+        match name {
+            // Only assets shipped with the game: files read with `read_file`
+            // may be written while the program runs, like a save file
+            "load_sprite" | "set_window_icon" | "set_icon" | "load_sound" | "play_music" => {
+                Some((ArgCheck::Path, 0))
+            }
+            "hex_color" => Some((ArgCheck::Color, 0)),
+            "key_pressed" | "simulate_key" => Some((ArgCheck::Key, 0)),
+            _ => None,
+        }
+    }
+
+    /// What is wrong with a literal argument of `function`, if anything
+    pub fn validate(self, function: &str, value: &str) -> Option<String> {
+        let name = function.split("::").last().unwrap_or(function);
+        match self {
+            ArgCheck::Path => {
+                // An atlas sprite, `atlas.png#player`, is read from the atlas
+                let path = match name {
+                    "load_sprite" => value.rsplit_once('#').map_or(value, |(atlas, _)| atlas),
+                    _ => value,
+                };
+                let is_icon = matches!(name, "set_window_icon" | "set_icon");
+                if is_icon && cfg!(target_os = "windows") && !path.to_lowercase().ends_with(".ico")
+                {
+                    Some(format!(
+                        "{} requires an .ico file on Windows, got '{}'",
+                        name, path
+                    ))
+                } else if !Path::new(path).exists() {
+                    Some(format!("File not found: '{}' (passed to {})", path, name))
+                } else {
+                    None
+                }
+            }
+            ArgCheck::Color => game::parse_color(value)
+                .is_none()
+                .then(|| format!("'{}' is not a color: expected #rrggbb or #rgb", value)),
+            ArgCheck::Key => (!game::is_key_name(value))
+                .then(|| format!("{} does not know the key '{}'", name, value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_arguments_are_validated() {
+        assert_eq!(
+            ArgCheck::for_function("std::game::load_sprite"),
+            Some((ArgCheck::Path, 0))
+        );
+        assert_eq!(ArgCheck::for_function("println"), None);
+        assert_eq!(ArgCheck::for_function("read_file"), None);
+        assert!(ArgCheck::Path
+            .validate("load_sound", "Cargo.toml")
+            .is_none());
+        assert!(ArgCheck::Path
+            .validate("load_sprite", "Cargo.toml#player")
+            .is_none());
+        assert!(ArgCheck::Path
+            .validate("load_sound", "no/such.wav")
+            .is_some());
+        assert!(ArgCheck::Color.validate("hex_color", "#f80").is_none());
+        assert!(ArgCheck::Color.validate("hex_color", "orange").is_some());
+        assert!(ArgCheck::Key.validate("key_pressed", "Space").is_none());
+        assert!(ArgCheck::Key.validate("key_pressed", "space").is_some());
+        assert_eq!(ArgCheck::parse("color"), Some(ArgCheck::Color));
+    }
+}
@@ -0,0 +1,637 @@
+//! Lints over the program's own code, run once the analysis has passed
+//!
+//! They look for code that compiles but is probably not what was meant:
+//! variables that are never read, imports that are never used, statements
+//! after a `return`, `break` or `continue`, and variables that hide one of
+//! an enclosing block. Each finding is a `Warning`; `--deny-warnings` turns
+//! them into errors. Names starting with `_` are never reported as unused.
+
+use std::collections::HashSet;
+
+use super::std_signatures;
+use crate::error::{SourceLocation, Warning};
+use crate::lexer::Span;
+use crate::parser::ast::*;
+
+/// Warnings for the statements of `program` that weren't merged in from
+/// local modules
+pub fn check(program: &Program) -> Vec<Warning> {
+    let own = &program.statements[program.module_statements.min(program.statements.len())..];
+    let mut lints = Lints::default();
+    lints.enter();
+    for stmt in own {
+        lints.statement(stmt);
+    }
+    lints.exit();
+    for stmt in own {
+        if let Statement::Import { path, items, span } = stmt {
+            lints.import(path, items, *span);
+        }
+    }
+    lints.warnings
+}
+
+/// A variable in scope
+struct Binding {
+    name: String,
+    span: Span,
+    used: bool,
+    /// Checked for being read and for hiding another variable; parameters,
+    /// loop variables and pattern bindings aren't
+    linted: bool,
+}
+
+#[derive(Default)]
+struct Lints {
+    scopes: Vec<Vec<Binding>>,
+    /// First scope of the function being walked; shadowing is only
+    /// reported within one function
+    function_base: usize,
+    /// Every name used as a variable, function, method or type, for imports
+    names: HashSet<String>,
+    warnings: Vec<Warning>,
+}
+
+fn location(span: Span) -> Option<SourceLocation> {
+    Some(SourceLocation::from_span(&span))
+}
+
+impl Lints {
+    fn enter(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    fn exit(&mut self) {
+        for binding in self.scopes.pop().unwrap_or_default() {
+            if binding.linted && !binding.used {
+                self.warnings.push(
+                    Warning::new(
+                        "unused_variable",
+                        &format!("Variable `{}` is never read", binding.name),
+                        location(binding.span),
+                    )
+                    .with_suggestion(&format!(
+                        "Remove it, or name it `_{}` if it's meant to be unused",
+                        binding.name
+                    )),
+                );
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &str, span: Span, linted: bool) {
+        if name.starts_with('_') {
+            return;
+        }
+        if linted {
+            let enclosing = self.scopes.len().saturating_sub(1);
+            let outer = self.scopes[self.function_base.min(enclosing)..enclosing]
+                .iter()
+                .rev()
+                .find_map(|scope| scope.iter().rev().find(|b| b.name == name));
+            if let Some(outer) = outer {
+                self.warnings.push(
+                    Warning::new(
+                        "shadowed_variable",
+                        &format!(
+                            "`{}` hides the variable declared on line {}",
+                            name, outer.span.line
+                        ),
+                        location(span),
+                    )
+                    .with_suggestion("Give one of them a different name"),
+                );
+            }
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Binding {
+                name: name.to_string(),
+                span,
+                used: false,
+                linted,
+            });
+        }
+    }
+
+    fn use_name(&mut self, name: &str) {
+        self.names.insert(name.to_string());
+        let binding = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.iter_mut().rev().find(|b| b.name == name));
+        if let Some(binding) = binding {
+            binding.used = true;
+        }
+    }
+
+    fn type_names(&mut self, ty: &Type) {
+        match ty {
+            Type::Named(name) => {
+                self.names.insert(name.clone());
+            }
+            Type::Vec(inner)
+            | Type::List(inner)
+            | Type::Option(inner)
+            | Type::Set(inner)
+            | Type::Array { elem: inner, .. }
+            | Type::Reference { inner, .. }
+            | Type::LifetimeAnnotated { inner, .. } => self.type_names(inner),
+            Type::Map(key, value) => {
+                self.type_names(key);
+                self.type_names(value);
+            }
+            Type::Function {
+                params,
+                return_type,
+            } => {
+                for param in params {
+                    self.type_names(param);
+                }
+                self.type_names(return_type);
+            }
+            _ => {}
+        }
+    }
+
+    fn function(&mut self, params: &[Parameter], return_type: Option<&Type>, body: &Block) {
+        let outer_base = self.function_base;
+        self.function_base = self.scopes.len();
+        self.enter();
+        for param in params {
+            self.type_names(&param.param_type);
+            self.declare(&param.name, param.span, false);
+        }
+        if let Some(return_type) = return_type {
+            self.type_names(return_type);
+        }
+        self.block_contents(body);
+        self.exit();
+        self.function_base = outer_base;
+    }
+
+    fn block(&mut self, block: &Block) {
+        self.enter();
+        self.block_contents(block);
+        self.exit();
+    }
+
+    /// A block's statements in the current scope, noting the first one that
+    /// can't be reached
+    fn block_contents(&mut self, block: &Block) {
+        let mut exit: Option<&Statement> = None;
+        let mut reported = false;
+        for stmt in &block.statements {
+            if let (Some(exit), false) = (exit, reported) {
+                self.unreachable(exit, stmt.span());
+                reported = true;
+            }
+            self.statement(stmt);
+            if exit.is_none() && diverges(stmt) {
+                exit = Some(stmt);
+            }
+        }
+        if let Some(expr) = &block.expression {
+            if let (Some(exit), false) = (exit, reported) {
+                self.unreachable(exit, expr.span());
+            }
+            self.expression(expr);
+        }
+    }
+
+    fn unreachable(&mut self, exit: &Statement, span: Span) {
+        let line = exit.span().line;
+        let after = match exit {
+            Statement::Return { .. } => format!("the `return` on line {}", line),
+            Statement::Break { .. } => format!("the `break` on line {}", line),
+            Statement::Continue { .. } => format!("the `continue` on line {}", line),
+            Statement::If { .. } => format!("the `if` on line {}, whose branches all leave", line),
+            _ => format!("the block on line {}, which always leaves", line),
+        };
+        self.warnings.push(
+            Warning::new(
+                "unreachable_code",
+                &format!("This code never runs: it comes after {}", after),
+                location(span),
+            )
+            .with_suggestion("Remove it, or move it before the statement that leaves"),
+        );
+    }
+
+    fn statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Let {
+                name,
+                type_annotation,
+                value,
+                span,
+                ..
+            } => {
+                self.expression(value);
+                if let Some(ty) = type_annotation {
+                    self.type_names(ty);
+                }
+                // Top-level variables may be read by any function, and games
+                // name their window (`let win = Window(...)`) without using it
+                let linted = self.scopes.len() > 1 && !opens_window(value);
+                self.declare(name, *span, linted);
+            }
+            Statement::Const {
+                type_annotation,
+                value,
+                ..
+            }
+            | Statement::Static {
+                type_annotation,
+                value,
+                ..
+            } => {
+                self.expression(value);
+                if let Some(ty) = type_annotation {
+                    self.type_names(ty);
+                }
+            }
+            Statement::LetPattern {
+                pattern,
+                type_annotation,
+                value,
+                ..
+            } => {
+                self.expression(value);
+                if let Some(ty) = type_annotation {
+                    self.type_names(ty);
+                }
+                self.pattern(pattern);
+            }
+            Statement::Function {
+                params,
+                return_type,
+                body,
+                ..
+            } => self.function(params, return_type.as_ref(), body),
+            Statement::Expression { expr, .. }
+            | Statement::Return {
+                value: Some(expr), ..
+            } => self.expression(expr),
+            Statement::If {
+                condition,
+                then_block,
+                else_block,
+                ..
+            } => {
+                self.expression(condition);
+                self.block(then_block);
+                if let Some(else_block) = else_block {
+                    self.block(else_block);
+                }
+            }
+            Statement::While {
+                condition, body, ..
+            } => {
+                self.expression(condition);
+                self.block(body);
+            }
+            Statement::IfLet {
+                pattern,
+                value,
+                then_block,
+                else_block,
+                ..
+            } => {
+                self.expression(value);
+                self.enter();
+                self.pattern(pattern);
+                self.block(then_block);
+                self.exit();
+                if let Some(else_block) = else_block {
+                    self.block(else_block);
+                }
+            }
+            Statement::WhileLet {
+                pattern,
+                value,
+                body,
+                ..
+            } => {
+                self.expression(value);
+                self.enter();
+                self.pattern(pattern);
+                self.block(body);
+                self.exit();
+            }
+            Statement::For {
+                variable,
+                start,
+                end,
+                step,
+                body,
+                span,
+                ..
+            } => {
+                self.expression(start);
+                self.expression(end);
+                if let Some(step) = step {
+                    self.expression(step);
+                }
+                // A counting loop needn't read its variable: for i in 0..3
+                self.enter();
+                self.declare(variable, *span, false);
+                self.block(body);
+                self.exit();
+            }
+            Statement::Block(block) => self.block(block),
+            Statement::Struct { fields, .. } => {
+                for field in fields {
+                    self.type_names(&field.field_type);
+                }
+            }
+            Statement::Enum { variants, .. } => {
+                for ty in variants.iter().flat_map(|v| v.data.iter().flatten()) {
+                    self.type_names(ty);
+                }
+            }
+            Statement::Impl {
+                target_type,
+                trait_name,
+                methods,
+                ..
+            } => {
+                self.names.insert(target_type.clone());
+                self.names.extend(trait_name.clone());
+                for method in methods {
+                    self.statement(method);
+                }
+            }
+            Statement::Trait { methods, .. } => {
+                for method in methods {
+                    match &method.default_impl {
+                        Some(body) => {
+                            self.function(&method.params, method.return_type.as_ref(), body)
+                        }
+                        None => {
+                            for param in &method.params {
+                                self.type_names(&param.param_type);
+                            }
+                        }
+                    }
+                }
+            }
+            Statement::Import { .. }
+            | Statement::Return { value: None, .. }
+            | Statement::Break { .. }
+            | Statement::Continue { .. } => {}
+        }
+    }
+
+    /// Declare the variables a pattern binds
+    fn pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Identifier { name, span, .. } | Pattern::RefBinding { name, span } => {
+                self.declare(name, *span, false)
+            }
+            Pattern::Struct {
+                type_name, fields, ..
+            } => {
+                self.names.insert(type_name.clone());
+                for field in fields {
+                    self.pattern(&field.pattern);
+                }
+            }
+            Pattern::Variant {
+                enum_name, inner, ..
+            } => {
+                self.names.extend(enum_name.clone());
+                if let Some(inner) = inner {
+                    self.pattern(inner);
+                }
+            }
+            Pattern::Tuple { elements, .. } => {
+                for element in elements {
+                    self.pattern(element);
+                }
+            }
+            Pattern::Or { alternatives, .. } => {
+                for alternative in alternatives {
+                    self.pattern(alternative);
+                }
+            }
+            Pattern::Wildcard { .. } | Pattern::Literal { .. } | Pattern::Range { .. } => {}
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Identifier { name, .. } => self.use_name(name),
+            Expression::Assignment { target, value, .. } => {
+                // Writing a variable doesn't read it
+                match target.as_ref() {
+                    Expression::Identifier { name, .. } => {
+                        self.names.insert(name.clone());
+                    }
+                    target => self.expression(target),
+                }
+                self.expression(value);
+            }
+            Expression::Call {
+                callee, arguments, ..
+            } => {
+                if let Expression::FieldAccess { field, .. } = callee.as_ref() {
+                    self.names.insert(field.clone());
+                }
+                // watch("player.hp") reads a variable by name
+                if let (
+                    Expression::Identifier { name, .. },
+                    Some(Expression::String { value, .. }),
+                ) = (callee.as_ref(), arguments.first())
+                {
+                    if name.rsplit("::").next() == Some("watch") {
+                        let variable = value.split('.').next().unwrap_or(value).trim();
+                        self.use_name(variable);
+                    }
+                }
+                self.expression(callee);
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+            Expression::Binary { left, right, .. }
+            | Expression::Index {
+                object: left,
+                index: right,
+                ..
+            }
+            | Expression::Range {
+                start: left,
+                end: right,
+                ..
+            } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::Unary { operand: inner, .. }
+            | Expression::FieldAccess { object: inner, .. }
+            | Expression::TupleIndex { object: inner, .. }
+            | Expression::Reference { value: inner, .. }
+            | Expression::Dereference { value: inner, .. }
+            | Expression::Grouped { inner, .. } => self.expression(inner),
+            Expression::Cast {
+                expr, target_type, ..
+            } => {
+                self.expression(expr);
+                self.type_names(target_type);
+            }
+            Expression::Tuple { elements, .. }
+            | Expression::List { elements, .. }
+            | Expression::VecLiteral { elements, .. }
+            | Expression::SetLiteral { elements, .. } => {
+                for element in elements {
+                    self.expression(element);
+                }
+            }
+            Expression::MapLiteral { entries, .. } => {
+                for (key, value) in entries {
+                    self.expression(key);
+                    self.expression(value);
+                }
+            }
+            Expression::Object { fields, .. } => {
+                for (_, value) in fields {
+                    self.expression(value);
+                }
+            }
+            Expression::StructInit { name, fields, .. } => {
+                self.names.insert(name.clone());
+                for (_, value) in fields {
+                    self.expression(value);
+                }
+            }
+            Expression::EnumVariant {
+                enum_name, data, ..
+            } => {
+                self.names.insert(enum_name.clone());
+                if let Some(data) = data {
+                    self.expression(data);
+                }
+            }
+            Expression::If {
+                condition,
+                then_block,
+                else_block,
+                ..
+            } => {
+                self.expression(condition);
+                self.block(then_block);
+                if let Some(else_block) = else_block {
+                    self.block(else_block);
+                }
+            }
+            Expression::Match {
+                scrutinee, arms, ..
+            } => {
+                self.expression(scrutinee);
+                for arm in arms {
+                    self.enter();
+                    self.pattern(&arm.pattern);
+                    if let Some(guard) = &arm.guard {
+                        self.expression(guard);
+                    }
+                    self.expression(&arm.body);
+                    self.exit();
+                }
+            }
+            Expression::Closure {
+                params,
+                return_type,
+                body,
+                ..
+            } => {
+                self.enter();
+                for param in params {
+                    if let Some(ty) = &param.param_type {
+                        self.type_names(ty);
+                    }
+                    self.declare(&param.name, param.span, false);
+                }
+                if let Some(return_type) = return_type {
+                    self.type_names(return_type);
+                }
+                self.block(body);
+                self.exit();
+            }
+            Expression::Int { .. }
+            | Expression::Float { .. }
+            | Expression::Bool { .. }
+            | Expression::Char { .. }
+            | Expression::String { .. } => {}
+        }
+    }
+
+    /// Report a standard library import none of whose names are used
+    fn import(&mut self, path: &[String], items: &[String], span: Span) {
+        let module = match path {
+            [std, ..] if std == "std" && path.len() >= 2 => path.join("::"),
+            [legacy] => format!("std::{}", legacy),
+            _ => return,
+        };
+        let functions = std_signatures::module_signatures(&module);
+        if items.is_empty() && functions.is_empty() {
+            // A module without a signature table; there's no telling
+            return;
+        }
+        // `math::sqrt`, `std::math::sqrt`, `game::physics::step`, ...
+        let prefixes: Vec<String> = (0..path.len())
+            .map(|i| format!("{}::", path[i..].join("::")))
+            .chain(std::iter::once(format!("{}::", module)))
+            .collect();
+        let used = self.names.iter().any(|name| {
+            prefixes
+                .iter()
+                .any(|prefix| name.starts_with(prefix.as_str()))
+                || if items.is_empty() {
+                    functions.iter().any(|f| f.name == *name)
+                } else {
+                    items.contains(name)
+                }
+        });
+        if !used {
+            self.warnings.push(
+                Warning::new(
+                    "unused_import",
+                    &format!("`{}` is imported but never used", module),
+                    location(span),
+                )
+                .with_suggestion("Remove the import"),
+            );
+        }
+    }
+}
+
+fn opens_window(value: &Expression) -> bool {
+    let mut value = value;
+    // Builder calls on the window: Window(...).set_title("Pong")
+    while let Expression::Call { callee, .. } = value {
+        match callee.as_ref() {
+            Expression::Identifier { name, .. } => {
+                return matches!(name.rsplit("::").next(), Some("Window" | "HeadlessWindow"))
+            }
+            Expression::FieldAccess { object, .. } => value = object,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Whether control never continues past this statement
+fn diverges(stmt: &Statement) -> bool {
+    let block_diverges = |block: &Block| block.statements.iter().any(diverges);
+    match stmt {
+        Statement::Return { .. } | Statement::Break { .. } | Statement::Continue { .. } => true,
+        Statement::Block(block) => block_diverges(block),
+        Statement::If {
+            then_block,
+            else_block: Some(else_block),
+            ..
+        } => block_diverges(then_block) && block_diverges(else_block),
+        _ => false,
+    }
+}
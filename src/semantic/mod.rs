@@ -2,23 +2,45 @@
 //!
 //! Performs type checking, type inference, and ownership validation
 
+pub mod arg_checks;
 pub mod borrow;
 pub mod lifetime;
+mod lints;
 pub mod ownership;
+pub mod profile;
 pub mod scope;
+mod std_signatures;
 pub mod types;
 
+pub use arg_checks::ArgCheck;
 pub use borrow::{BorrowChecker, BorrowError, BorrowKind};
 pub use lifetime::{LifetimeChecker, LifetimeError};
 pub use ownership::{OwnershipChecker, OwnershipError};
+pub use profile::{AnalysisProfile, Pass};
 pub use scope::{ReferenceInfo, ScopeId, ScopeStack, ValueOrigin, VariableInfo};
 pub use types::ZyraType;
 
 use std::collections::HashMap;
+use std::time::Instant;
 
-use crate::error::{SourceLocation, ZyraError, ZyraResult};
+use crate::error::{
+    Edit, Severity, SourceLocation, Warning, ZyraError, ZyraResult, DEFAULT_MAX_ERRORS,
+};
+use crate::interface::ModuleInterface;
 use crate::parser::ast::*;
 
+/// Call into one of the sub-checkers, timed under `pass` when profiling
+macro_rules! timed {
+    ($analyzer:ident, $pass:ident, $call:expr) => {{
+        let start = $analyzer.profile.as_ref().map(|_| Instant::now());
+        let result = $call;
+        if let (Some(profile), Some(start)) = ($analyzer.profile.as_mut(), start) {
+            profile.add(Pass::$pass, start.elapsed());
+        }
+        result
+    }};
+}
+
 /// Symbol table entry
 #[derive(Debug, Clone)]
 pub struct Symbol {
@@ -44,6 +66,11 @@ pub enum TypeDef {
     },
     /// Enum type with name and variants
     Enum { name: String, variants: Vec<String> },
+    /// Trait with its methods as (name, parameter count, has default body)
+    Trait {
+        name: String,
+        methods: Vec<(String, usize, bool)>,
+    },
 }
 
 impl TypeDef {
@@ -52,6 +79,7 @@ impl TypeDef {
         match self {
             TypeDef::Struct { name, .. } => ZyraType::Struct(name.clone()),
             TypeDef::Enum { name, .. } => ZyraType::Enum(name.clone()),
+            TypeDef::Trait { name, .. } => ZyraType::Struct(name.clone()),
         }
     }
 }
@@ -65,6 +93,8 @@ pub struct SemanticAnalyzer {
     functions: HashMap<String, FunctionSignature>,
     /// Type registry: maps type names to their definitions
     types: HashMap<String, TypeDef>,
+    /// Trait implementations as (trait name, implementing type)
+    trait_impls: std::collections::HashSet<(String, String)>,
     /// Expression type cache: maps expression IDs to their resolved types
     /// Used for type-aware features like DCE and compile-time borrow checking
     expr_types: HashMap<ExprId, ZyraType>,
@@ -76,6 +106,12 @@ pub struct SemanticAnalyzer {
     scope_depth: usize,
     current_function: Option<String>,
     errors: Vec<ZyraError>,
+    /// Likely mistakes found by lints; they don't fail the analysis
+    warnings: Vec<Warning>,
+    /// Report warnings as errors (`--deny-warnings`)
+    deny_warnings: bool,
+    /// Errors collected before the analysis gives up (`--max-errors`)
+    max_errors: usize,
     /// Scope stack for tracking nested scopes with unique IDs
     scope_stack: ScopeStack,
     /// Tracks active references and their origins
@@ -91,10 +127,40 @@ pub struct SemanticAnalyzer {
     module_aliases: HashMap<String, String>,
     /// Tracks if `self` is mutable in current method (None = not in method)
     self_is_mutable: Option<bool>,
+    /// Labels of the enclosing loops, innermost last (None = unlabeled loop)
+    loop_labels: Vec<Option<String>>,
+    /// Closures being analyzed; a `return` inside one leaves the closure, not the function
+    closure_depth: usize,
+    /// Parameter types of the `Fn` a closure literal is passed as, used for the
+    /// closure's unannotated parameters (taken by the next closure analyzed)
+    closure_param_hints: Option<Vec<ZyraType>>,
+    /// Type an unsuffixed number literal takes from its context, e.g. the
+    /// annotation in `let x: u8 = 5` (taken by the next expression analyzed)
+    literal_hint: Option<ZyraType>,
+    /// Call graph: maps a called function to the functions calling it
+    callers: HashMap<String, std::collections::HashSet<String>>,
+    /// Items merged in from local modules, with their visibility
+    imported: ModuleItems,
+    /// Types of the top-level constants, keyed like functions (`util::MAX`
+    /// for a module's)
+    constants: HashMap<String, ZyraType>,
+    /// Types of the `static mut` globals, keyed like constants
+    statics: HashMap<String, ZyraType>,
+    /// (function, global) pairs already warned about, so each function is
+    /// told once per global it uses
+    static_uses: std::collections::HashSet<(String, String)>,
+    /// Module whose code is being analyzed (None = the importing file)
+    current_module: Option<String>,
+    /// Timings, collected when profiling was asked for
+    profile: Option<AnalysisProfile>,
+    /// Items of imported modules with an up-to-date interface, not checked again
+    verified_items: std::collections::HashSet<String>,
+    /// Literal argument checks the project turned off
+    skipped_arg_checks: Vec<ArgCheck>,
 }
 
 /// Function signature for type checking
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FunctionSignature {
     pub name: String,
     pub params: Vec<(String, ZyraType)>,
@@ -110,6 +176,7 @@ impl SemanticAnalyzer {
             symbols: HashMap::new(),
             functions: HashMap::new(),
             types: HashMap::new(),
+            trait_impls: std::collections::HashSet::new(),
             expr_types: HashMap::new(),
             next_expr_id: 0,
             ownership: OwnershipChecker::new(),
@@ -118,6 +185,9 @@ impl SemanticAnalyzer {
             scope_depth: 0,
             current_function: None,
             errors: Vec::new(),
+            warnings: Vec::new(),
+            deny_warnings: false,
+            max_errors: DEFAULT_MAX_ERRORS,
             scope_stack: ScopeStack::new(),
             references: HashMap::new(),
             function_scope: None,
@@ -125,6 +195,19 @@ impl SemanticAnalyzer {
             imported_std_items: HashMap::new(),
             module_aliases: HashMap::new(),
             self_is_mutable: None,
+            loop_labels: Vec::new(),
+            closure_depth: 0,
+            closure_param_hints: None,
+            literal_hint: None,
+            callers: HashMap::new(),
+            imported: ModuleItems::default(),
+            constants: HashMap::new(),
+            statics: HashMap::new(),
+            static_uses: std::collections::HashSet::new(),
+            current_module: None,
+            profile: None,
+            verified_items: std::collections::HashSet::new(),
+            skipped_arg_checks: Vec::new(),
         };
 
         // Register built-in functions
@@ -222,7 +305,21 @@ impl SemanticAnalyzer {
                     ("height".to_string(), ZyraType::I32),
                     ("title".to_string(), ZyraType::String),
                 ],
-                return_type: ZyraType::Object(HashMap::new()),
+                return_type: ZyraType::Struct("Window".to_string()),
+                lifetimes: vec![],
+                has_mut_self: false,
+            },
+        );
+        self.functions.insert(
+            "HeadlessWindow".to_string(),
+            FunctionSignature {
+                name: "HeadlessWindow".to_string(),
+                params: vec![
+                    ("width".to_string(), ZyraType::I32),
+                    ("height".to_string(), ZyraType::I32),
+                    ("title".to_string(), ZyraType::String),
+                ],
+                return_type: ZyraType::Struct("Window".to_string()),
                 lifetimes: vec![],
                 has_mut_self: false,
             },
@@ -235,378 +332,8 @@ impl SemanticAnalyzer {
         module_name: &str,
         specific_imports: Option<&Vec<String>>,
     ) {
-        let functions: Vec<(&str, Vec<(&str, ZyraType)>, ZyraType)> = match module_name {
-            "std::math" => vec![
-                // Basic math - polymorphic (accepts int or float)
-                ("abs", vec![("x", ZyraType::Unknown)], ZyraType::Unknown),
-                ("sqrt", vec![("x", ZyraType::Unknown)], ZyraType::F64),
-                (
-                    "pow",
-                    vec![("base", ZyraType::Unknown), ("exp", ZyraType::Unknown)],
-                    ZyraType::Unknown,
-                ),
-                ("sin", vec![("x", ZyraType::Unknown)], ZyraType::F64),
-                ("cos", vec![("x", ZyraType::Unknown)], ZyraType::F64),
-                ("tan", vec![("x", ZyraType::Unknown)], ZyraType::F64),
-                (
-                    "min",
-                    vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
-                    ZyraType::Unknown,
-                ),
-                (
-                    "max",
-                    vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
-                    ZyraType::Unknown,
-                ),
-                ("floor", vec![("x", ZyraType::Unknown)], ZyraType::I64),
-                ("ceil", vec![("x", ZyraType::Unknown)], ZyraType::I64),
-                ("round", vec![("x", ZyraType::Unknown)], ZyraType::I64),
-                (
-                    "random",
-                    vec![("min", ZyraType::I64), ("max", ZyraType::I64)],
-                    ZyraType::I64,
-                ),
-                (
-                    "lerp",
-                    vec![
-                        ("a", ZyraType::Unknown),
-                        ("b", ZyraType::Unknown),
-                        ("t", ZyraType::Unknown),
-                    ],
-                    ZyraType::F64,
-                ),
-                (
-                    "clamp",
-                    vec![
-                        ("x", ZyraType::Unknown),
-                        ("min", ZyraType::Unknown),
-                        ("max", ZyraType::Unknown),
-                    ],
-                    ZyraType::Unknown,
-                ),
-                ("pi", vec![], ZyraType::F64),
-                ("e", vec![], ZyraType::F64),
-            ],
-            "std::io" => vec![
-                ("print", vec![("value", ZyraType::Unknown)], ZyraType::Void),
-                (
-                    "println",
-                    vec![("value", ZyraType::Unknown)],
-                    ZyraType::Void,
-                ),
-                ("input", vec![], ZyraType::String),
-            ],
-            "std::time" => vec![
-                ("now", vec![], ZyraType::I64),
-                ("now_secs", vec![], ZyraType::F64),
-                ("sleep", vec![("ms", ZyraType::I32)], ZyraType::Void),
-                ("monotonic_ms", vec![], ZyraType::I64),
-                ("instant_now", vec![], ZyraType::I64),
-                (
-                    "instant_elapsed",
-                    vec![("id", ZyraType::I64)],
-                    ZyraType::I64,
-                ),
-                ("delta_time", vec![], ZyraType::F32),
-                ("fps", vec![], ZyraType::F32),
-            ],
-            "std::string" => vec![
-                ("string_len", vec![("s", ZyraType::String)], ZyraType::I32),
-                ("to_upper", vec![("s", ZyraType::String)], ZyraType::String),
-                ("to_lower", vec![("s", ZyraType::String)], ZyraType::String),
-                ("trim", vec![("s", ZyraType::String)], ZyraType::String),
-                (
-                    "contains",
-                    vec![("s", ZyraType::String), ("sub", ZyraType::String)],
-                    ZyraType::Bool,
-                ),
-                (
-                    "replace",
-                    vec![
-                        ("s", ZyraType::String),
-                        ("from", ZyraType::String),
-                        ("to", ZyraType::String),
-                    ],
-                    ZyraType::String,
-                ),
-                (
-                    "split",
-                    vec![("s", ZyraType::String), ("delim", ZyraType::String)],
-                    ZyraType::Vec(Box::new(ZyraType::String)),
-                ),
-                // Numeric parsing
-                ("parse_int", vec![("s", ZyraType::String)], ZyraType::I64),
-                ("parse_float", vec![("s", ZyraType::String)], ZyraType::F64),
-                // String validation
-                ("is_numeric", vec![("s", ZyraType::String)], ZyraType::Bool),
-                ("is_digit", vec![("s", ZyraType::String)], ZyraType::Bool),
-                ("is_alpha", vec![("s", ZyraType::String)], ZyraType::Bool),
-                (
-                    "is_alphanumeric",
-                    vec![("s", ZyraType::String)],
-                    ZyraType::Bool,
-                ),
-                // Type casting functions
-                ("to_i32", vec![("s", ZyraType::String)], ZyraType::I32),
-                ("to_i64", vec![("s", ZyraType::String)], ZyraType::I64),
-                ("to_f32", vec![("s", ZyraType::String)], ZyraType::F32),
-                ("to_f64", vec![("s", ZyraType::String)], ZyraType::F64),
-            ],
-            "std::fs" => vec![
-                (
-                    "read_file",
-                    vec![("path", ZyraType::String)],
-                    ZyraType::String,
-                ),
-                (
-                    "write_file",
-                    vec![("path", ZyraType::String), ("content", ZyraType::String)],
-                    ZyraType::Bool,
-                ),
-                (
-                    "file_exists",
-                    vec![("path", ZyraType::String)],
-                    ZyraType::Bool,
-                ),
-                ("is_file", vec![("path", ZyraType::String)], ZyraType::Bool),
-                ("is_dir", vec![("path", ZyraType::String)], ZyraType::Bool),
-                (
-                    "list_dir",
-                    vec![("path", ZyraType::String)],
-                    ZyraType::Vec(Box::new(ZyraType::String)),
-                ),
-                ("current_dir", vec![], ZyraType::String),
-            ],
-            "std::env" => vec![
-                ("args", vec![], ZyraType::Vec(Box::new(ZyraType::String))),
-                ("args_count", vec![], ZyraType::I64),
-                (
-                    "env_var",
-                    vec![("name", ZyraType::String)],
-                    ZyraType::String,
-                ),
-                ("os_name", vec![], ZyraType::String),
-                ("os_arch", vec![], ZyraType::String),
-                ("is_windows", vec![], ZyraType::Bool),
-                ("is_linux", vec![], ZyraType::Bool),
-                ("temp_dir", vec![], ZyraType::String),
-                ("pid", vec![], ZyraType::I64),
-            ],
-            "std::list" => vec![
-                // LinkedList - memory-safe doubly linked list
-                ("list_new", vec![], ZyraType::I64),
-                (
-                    "list_push_front",
-                    vec![("list", ZyraType::I64), ("value", ZyraType::Unknown)],
-                    ZyraType::Bool,
-                ),
-                (
-                    "list_push_back",
-                    vec![("list", ZyraType::I64), ("value", ZyraType::Unknown)],
-                    ZyraType::Bool,
-                ),
-                (
-                    "list_pop_front",
-                    vec![("list", ZyraType::I64)],
-                    ZyraType::Unknown,
-                ),
-                (
-                    "list_pop_back",
-                    vec![("list", ZyraType::I64)],
-                    ZyraType::Unknown,
-                ),
-                (
-                    "list_get",
-                    vec![("list", ZyraType::I64), ("index", ZyraType::I64)],
-                    ZyraType::Unknown,
-                ),
-                (
-                    "list_set",
-                    vec![
-                        ("list", ZyraType::I64),
-                        ("index", ZyraType::I64),
-                        ("value", ZyraType::Unknown),
-                    ],
-                    ZyraType::Bool,
-                ),
-                ("list_len", vec![("list", ZyraType::I64)], ZyraType::I64),
-                (
-                    "list_is_empty",
-                    vec![("list", ZyraType::I64)],
-                    ZyraType::Bool,
-                ),
-                (
-                    "list_to_array",
-                    vec![("list", ZyraType::I64)],
-                    ZyraType::Vec(Box::new(ZyraType::Unknown)),
-                ),
-                ("list_clear", vec![("list", ZyraType::I64)], ZyraType::Bool),
-                ("list_delete", vec![("list", ZyraType::I64)], ZyraType::Bool),
-            ],
-            "std::process" => vec![("exit", vec![("code", ZyraType::I64)], ZyraType::Void)],
-            "std::thread" => vec![
-                ("thread_sleep", vec![("ms", ZyraType::I64)], ZyraType::Void),
-                ("thread_yield", vec![], ZyraType::Void),
-                ("thread_id", vec![], ZyraType::I64),
-                ("cpu_cores", vec![], ZyraType::I64),
-            ],
-            "std::mem" => vec![
-                ("size_of", vec![("value", ZyraType::Unknown)], ZyraType::I64),
-                (
-                    "type_of",
-                    vec![("value", ZyraType::Unknown)],
-                    ZyraType::String,
-                ),
-            ],
-            "std::core" => vec![
-                (
-                    "assert",
-                    vec![("condition", ZyraType::Bool), ("message", ZyraType::String)],
-                    ZyraType::Void,
-                ),
-                ("panic", vec![("message", ZyraType::String)], ZyraType::Void),
-                (
-                    "is_none",
-                    vec![("value", ZyraType::Unknown)],
-                    ZyraType::Bool,
-                ),
-                (
-                    "is_some",
-                    vec![("value", ZyraType::Unknown)],
-                    ZyraType::Bool,
-                ),
-                (
-                    "unwrap",
-                    vec![("value", ZyraType::Unknown)],
-                    ZyraType::Unknown,
-                ),
-            ],
-            "std::game" => vec![
-                (
-                    "Window",
-                    vec![
-                        ("width", ZyraType::I32),
-                        ("height", ZyraType::I32),
-                        ("title", ZyraType::String),
-                    ],
-                    ZyraType::Object(HashMap::new()),
-                ),
-                ("is_open", vec![], ZyraType::Bool),
-                ("clear", vec![], ZyraType::Void),
-                ("display", vec![], ZyraType::Void),
-                (
-                    "key_pressed",
-                    vec![("key", ZyraType::String)],
-                    ZyraType::Bool,
-                ),
-                (
-                    "draw_rect",
-                    vec![
-                        ("x", ZyraType::I32),
-                        ("y", ZyraType::I32),
-                        ("w", ZyraType::I32),
-                        ("h", ZyraType::I32),
-                    ],
-                    ZyraType::Void,
-                ),
-                (
-                    "draw_rect_color",
-                    vec![
-                        ("x", ZyraType::I32),
-                        ("y", ZyraType::I32),
-                        ("w", ZyraType::I32),
-                        ("h", ZyraType::I32),
-                        ("color", ZyraType::I32),
-                    ],
-                    ZyraType::Void,
-                ),
-                (
-                    "draw_digit",
-                    vec![
-                        ("x", ZyraType::I32),
-                        ("y", ZyraType::I32),
-                        ("digit", ZyraType::I32),
-                        ("color", ZyraType::I32),
-                    ],
-                    ZyraType::I32,
-                ),
-                (
-                    "draw_number",
-                    vec![
-                        ("x", ZyraType::I32),
-                        ("y", ZyraType::I32),
-                        ("num", ZyraType::I32),
-                        ("scale", ZyraType::I32),
-                    ],
-                    ZyraType::Void,
-                ),
-                (
-                    "draw_text_win",
-                    vec![
-                        ("x", ZyraType::I32),
-                        ("y", ZyraType::I32),
-                        ("scale", ZyraType::I32),
-                    ],
-                    ZyraType::Void,
-                ),
-                (
-                    "draw_text_lose",
-                    vec![
-                        ("x", ZyraType::I32),
-                        ("y", ZyraType::I32),
-                        ("scale", ZyraType::I32),
-                    ],
-                    ZyraType::Void,
-                ),
-                // Sprites
-                (
-                    "load_sprite",
-                    vec![("path", ZyraType::String)],
-                    ZyraType::I64,
-                ),
-                (
-                    "draw_sprite",
-                    vec![
-                        ("id", ZyraType::I64),
-                        ("x", ZyraType::I32),
-                        ("y", ZyraType::I32),
-                    ],
-                    ZyraType::Void,
-                ),
-                (
-                    "draw_sprite_scaled",
-                    vec![
-                        ("id", ZyraType::I64),
-                        ("x", ZyraType::I32),
-                        ("y", ZyraType::I32),
-                        ("scale", ZyraType::I32),
-                    ],
-                    ZyraType::Void,
-                ),
-                // Icons
-                (
-                    "set_window_icon",
-                    vec![("path", ZyraType::String)],
-                    ZyraType::Bool,
-                ),
-                ("is_icon_supported", vec![], ZyraType::Bool),
-            ],
-            _ => vec![],
-        };
-
-        for (name, params, return_type) in functions {
-            let param_types: Vec<_> = params
-                .into_iter()
-                .map(|(n, t)| (n.to_string(), t))
-                .collect();
-
-            let sig = FunctionSignature {
-                name: name.to_string(),
-                params: param_types,
-                return_type,
-                lifetimes: vec![],
-                has_mut_self: false,
-            };
+        for sig in std_signatures::module_signatures(module_name) {
+            let name = sig.name.as_str();
 
             // 1. Always register fully qualified name (e.g., std::math::sin)
             let qualified_name = format!("{}::{}", module_name, name);
@@ -617,11 +344,13 @@ impl SemanticAnalyzer {
             // 2. Register short name if specifically requested OR importing entire module
             let should_import = match specific_imports {
                 None => true, // Import all check
-                Some(list) => list.contains(&name.to_string()),
+                Some(list) => list.iter().any(|item| item == name),
             };
 
-            if should_import {
-                self.functions.insert(name.to_string(), sig);
+            // A function the program defines keeps its name; the library's
+            // is still reachable by its qualified name
+            if should_import && !self.is_user_function(name) {
+                self.functions.insert(name.to_string(), sig.clone());
                 // Track that this function came from this module
                 self.imported_std_items
                     .insert(name.to_string(), module_name.to_string());
@@ -629,10 +358,15 @@ impl SemanticAnalyzer {
         }
     }
 
+    /// Whether the program itself defines a function with this name
+    fn is_user_function(&self, name: &str) -> bool {
+        self.functions.contains_key(name) && !self.imported_std_items.contains_key(name)
+    }
+
     /// Check if a stdlib function is available (imported)
     pub fn is_stdlib_function_available(&self, name: &str) -> bool {
-        // Always allow `print`, `println` as builtins
-        if matches!(name, "print" | "println" | "input") {
+        // Always allow `print`, `println` (and their stderr forms) as builtins
+        if matches!(name, "print" | "println" | "eprint" | "eprintln" | "input") {
             return true;
         }
 
@@ -643,7 +377,7 @@ impl SemanticAnalyzer {
     /// Check if a function name is a stdlib function
     pub fn is_stdlib_function(&self, name: &str) -> bool {
         // Builtins always available
-        if matches!(name, "print" | "println" | "input") {
+        if matches!(name, "print" | "println" | "eprint" | "eprintln" | "input") {
             return false; // Not a restricted stdlib function
         }
 
@@ -651,6 +385,8 @@ impl SemanticAnalyzer {
         const STDLIB_FUNCTIONS: &[&str] = &[
             // std::core
             "assert",
+            "assert_eq",
+            "assert_ne",
             "panic",
             "type_of",
             "is_none",
@@ -682,8 +418,43 @@ impl SemanticAnalyzer {
             "log",
             "log10",
             "exp",
+            "noise2d",
+            "octave_noise2d",
+            "wrapping_add",
+            "wrapping_sub",
+            "wrapping_mul",
+            "saturating_add",
+            "saturating_sub",
+            "saturating_mul",
+            "checked_add",
+            "checked_sub",
+            "checked_mul",
+            "vec2",
+            "vec2_add",
+            "vec2_sub",
+            "vec2_scale",
+            "vec2_dot",
+            "vec2_length",
+            "vec2_normalize",
+            "vec2_distance",
+            "vec2_lerp",
+            "vec2_rotate",
+            "vec2_angle",
+            "vec2_from_angle",
+            "vec3",
+            "vec3_add",
+            "vec3_sub",
+            "vec3_scale",
+            "vec3_dot",
+            "vec3_cross",
+            "vec3_length",
+            "vec3_normalize",
+            "vec3_distance",
+            "vec3_lerp",
+            "vec3_rotate",
             // std::string
             "string_len",
+            "byte_len",
             "to_upper",
             "to_lower",
             "trim",
@@ -709,6 +480,13 @@ impl SemanticAnalyzer {
             "now_secs",
             "now_millis",
             "sleep",
+            "sleep_precise",
+            "sleep_until",
+            "perf_counter",
+            "Stopwatch",
+            "stopwatch_start",
+            "stopwatch_lap",
+            "stopwatch_elapsed",
             "monotonic_ms",
             "instant_now",
             "instant_elapsed",
@@ -717,6 +495,8 @@ impl SemanticAnalyzer {
             // std::fs
             "read_file",
             "write_file",
+            "read_file_bytes",
+            "write_file_bytes",
             "append_file",
             "file_exists",
             "delete_file",
@@ -727,6 +507,9 @@ impl SemanticAnalyzer {
             "current_dir",
             // std::env
             "env_var",
+            "env_int",
+            "env_bool",
+            "load_dotenv",
             "set_env_var",
             "args",
             "args_count",
@@ -743,6 +526,8 @@ impl SemanticAnalyzer {
             "exec",
             "shell",
             "spawn",
+            "set_exit_code",
+            "on_panic",
             // std::thread
             "thread_spawn",
             "thread_join",
@@ -757,9 +542,54 @@ impl SemanticAnalyzer {
             "drop",
             "take",
             "swap",
+            "freeze",
+            "is_frozen",
             "replace",
+            // std::ai
+            "astar",
+            "bfs",
+            // std::debug
+            "watch",
+            "dump_watches",
+            // std::test
+            "forall",
+            "gen_int",
+            "gen_string",
+            "gen_vec",
+            // std::i18n
+            "tr",
+            "set_language",
+            "language",
+            // std::serde
+            "to_bytes",
+            "from_bytes",
+            // std::json
+            "json_parse",
+            "json_stringify",
+            // std::random
+            "seed",
+            "random_f32",
+            "random_float",
+            "shuffle",
+            "choice",
+            // std::net
+            "tcp_connect",
+            "tcp_send",
+            "tcp_recv",
+            "tcp_close",
+            "http_get",
+            "net_timeout",
+            // std::audio
+            "load_sound",
+            "play_sound",
+            "play_music",
+            "stop_music",
+            "set_volume",
             // std::game
             "Window",
+            "window_is_open",
+            "window_set_title",
+            "window_set_size",
             "is_open",
             "clear",
             "display",
@@ -769,6 +599,38 @@ impl SemanticAnalyzer {
             "draw_line",
             "draw_text",
             "set_color",
+            "hex_color",
+            "ease",
+            "Tween",
+            "tween_update",
+            "tween_value",
+            "tween_done",
+            "tween_reset",
+            "mouse_x",
+            "mouse_y",
+            "mouse_down",
+            "mouse_clicked",
+            "simulate_key",
+            "simulate_mouse",
+            "simulate_text",
+            "pixel_at",
+            "is_headless",
+            "draw_calls",
+            "HeadlessWindow",
+            "ui_button",
+            "ui_slider",
+            "ui_checkbox",
+            "ui_text_input",
+            "create_canvas",
+            "draw_to",
+            "draw_canvas",
+            "set_camera",
+            "set_zoom",
+            "screen_to_world",
+            "rect_overlaps",
+            "circle_overlaps",
+            "point_in_rect",
+            "game_loop",
         ];
 
         STDLIB_FUNCTIONS.contains(&name)
@@ -778,153 +640,146 @@ impl SemanticAnalyzer {
     pub fn get_stdlib_module_for_function(&self, name: &str) -> Option<&'static str> {
         match name {
             // std::core
-            "assert" | "panic" | "type_of" | "is_none" | "is_some" | "unwrap" | "expect" => {
-                Some("std::core")
-            }
+            "assert" | "assert_eq" | "assert_ne" | "panic" | "type_of" | "is_none" | "is_some"
+            | "unwrap" | "expect" => Some("std::core"),
             // std::math
             "abs" | "sqrt" | "pow" | "sin" | "cos" | "tan" | "asin" | "acos" | "atan" | "atan2"
             | "floor" | "ceil" | "round" | "min" | "max" | "clamp" | "lerp" | "random"
-            | "random_range" | "pi" | "e" | "log" | "log10" | "exp" => Some("std::math"),
+            | "random_range" | "pi" | "e" | "log" | "log10" | "exp" | "noise2d"
+            | "octave_noise2d" | "wrapping_add" | "wrapping_sub" | "wrapping_mul"
+            | "saturating_add" | "saturating_sub" | "saturating_mul" | "checked_add"
+            | "checked_sub" | "checked_mul" => Some("std::math"),
+            // std::math vectors
+            "vec2" | "vec2_add" | "vec2_sub" | "vec2_scale" | "vec2_dot" | "vec2_length"
+            | "vec2_normalize" | "vec2_distance" | "vec2_lerp" | "vec2_rotate" | "vec2_angle"
+            | "vec2_from_angle" | "vec3" | "vec3_add" | "vec3_sub" | "vec3_scale" | "vec3_dot"
+            | "vec3_cross" | "vec3_length" | "vec3_normalize" | "vec3_distance" | "vec3_lerp"
+            | "vec3_rotate" => Some("std::math"),
             // std::string
-            "string_len" | "to_upper" | "to_lower" | "trim" | "trim_start" | "trim_end"
-            | "contains" | "starts_with" | "ends_with" | "replace" | "split" | "join"
-            | "parse_int" | "parse_float" | "char_at" | "substring" => Some("std::string"),
+            "string_len" | "byte_len" | "to_upper" | "to_lower" | "trim" | "trim_start"
+            | "trim_end" | "contains" | "starts_with" | "ends_with" | "replace" | "split"
+            | "join" | "parse_int" | "parse_float" | "char_at" | "substring" => Some("std::string"),
             // std::io
             "read_line" | "write" | "writeln" | "flush" => Some("std::io"),
             // std::time
-            "now" | "now_secs" | "now_millis" | "sleep" | "monotonic_ms" | "instant_now"
-            | "instant_elapsed" | "delta_time" | "fps" => Some("std::time"),
+            "now" | "now_secs" | "now_millis" | "sleep" | "sleep_precise" | "sleep_until"
+            | "perf_counter" | "Stopwatch" | "stopwatch_start" | "stopwatch_lap"
+            | "stopwatch_elapsed" | "monotonic_ms" | "instant_now" | "instant_elapsed"
+            | "delta_time" | "fps" => Some("std::time"),
             // std::fs
-            "read_file" | "write_file" | "append_file" | "file_exists" | "delete_file"
-            | "create_dir" | "list_dir" | "is_file" | "is_dir" | "current_dir" => Some("std::fs"),
+            "read_file" | "write_file" | "read_file_bytes" | "write_file_bytes" | "append_file"
+            | "file_exists" | "delete_file" | "create_dir" | "list_dir" | "is_file" | "is_dir"
+            | "current_dir" => Some("std::fs"),
             // std::env
-            "env_var" | "set_env_var" | "args" | "args_count" | "os_name" | "os_arch"
-            | "is_windows" | "is_linux" | "is_macos" | "home_dir" | "temp_dir" | "pid" => {
-                Some("std::env")
-            }
+            "env_var" | "env_int" | "env_bool" | "load_dotenv" | "set_env_var" | "args"
+            | "args_count" | "os_name" | "os_arch" | "is_windows" | "is_linux" | "is_macos"
+            | "home_dir" | "temp_dir" | "pid" => Some("std::env"),
             // std::process
-            "exit" | "exec" | "shell" | "spawn" => Some("std::process"),
+            "exit" | "exec" | "shell" | "spawn" | "set_exit_code" | "on_panic" => {
+                Some("std::process")
+            }
             // std::thread
             "thread_spawn" | "thread_join" | "thread_sleep" | "thread_yield" | "thread_id"
             | "thread_name" | "cpu_cores" | "thread_park" => Some("std::thread"),
             // std::mem
-            "size_of" | "drop" | "take" | "swap" => Some("std::mem"),
+            "size_of" | "drop" | "take" | "swap" | "freeze" | "is_frozen" => Some("std::mem"),
+            // std::ai
+            "astar" | "bfs" => Some("std::ai"),
+            // std::debug
+            "watch" | "dump_watches" => Some("std::debug"),
+            // std::test
+            "forall" | "gen_int" | "gen_string" | "gen_vec" => Some("std::test"),
+            // std::i18n
+            "tr" | "set_language" | "language" => Some("std::i18n"),
+            // std::serde
+            "to_bytes" | "from_bytes" => Some("std::serde"),
+            // std::json
+            "json_parse" | "json_stringify" => Some("std::json"),
+            // std::random
+            "seed" | "random_f32" | "random_float" | "shuffle" | "choice" => Some("std::random"),
+            // std::net
+            "tcp_connect" | "tcp_send" | "tcp_recv" | "tcp_close" | "http_get" | "net_timeout" => {
+                Some("std::net")
+            }
+            // std::audio
+            "load_sound" | "play_sound" | "play_music" | "stop_music" | "set_volume" => {
+                Some("std::audio")
+            }
             // std::game
-            "Window" | "is_open" | "clear" | "display" | "key_pressed" | "draw_rect"
-            | "draw_circle" | "draw_line" | "draw_text" | "set_color" => Some("std::game"),
+            "Window" | "window_is_open" | "window_set_title" | "window_set_size" | "is_open"
+            | "clear" | "display" | "key_pressed" | "draw_rect" | "draw_circle" | "draw_line"
+            | "draw_text" | "set_color" | "hex_color" | "ease" | "Tween" | "tween_update"
+            | "tween_value" | "tween_done" | "tween_reset" | "mouse_x" | "mouse_y"
+            | "mouse_down" | "mouse_clicked" | "simulate_key" | "simulate_mouse"
+            | "simulate_text" | "pixel_at" | "is_headless" | "draw_calls" | "HeadlessWindow"
+            | "ui_button" | "ui_slider" | "ui_checkbox" | "ui_text_input" | "create_canvas"
+            | "draw_to" | "draw_canvas" | "set_camera" | "set_zoom" | "screen_to_world"
+            | "rect_overlaps" | "circle_overlaps" | "point_in_rect" | "game_loop" => {
+                Some("std::game")
+            }
             _ => None,
         }
     }
 
-    /// Analyze a program
-    pub fn analyze(&mut self, program: &Program) -> ZyraResult<()> {
-        // First pass: collect function signatures
-        for stmt in &program.statements {
-            if let Statement::Function {
-                name,
-                params,
-                return_type,
-                lifetimes,
-                ..
-            } = stmt
-            {
-                let param_types: Vec<_> = params
-                    .iter()
-                    .map(|p| (p.name.clone(), ZyraType::from_ast_type(&p.param_type)))
-                    .collect();
+    /// Time each pass and top-level item while analyzing
+    pub fn with_profiling(mut self) -> Self {
+        self.profile = Some(AnalysisProfile::default());
+        self
+    }
 
-                let ret_type = return_type
-                    .as_ref()
-                    .map(ZyraType::from_ast_type)
-                    .unwrap_or(ZyraType::Void);
+    /// Take the items of modules whose interface is up to date as checked
+    pub fn with_interfaces(mut self, interfaces: &[ModuleInterface]) -> Self {
+        self.verified_items
+            .extend(interfaces.iter().flat_map(ModuleInterface::item_names));
+        self
+    }
 
-                // Detect &mut self: first param named "self" with mutable reference type
-                let has_mut_self = params.first().map_or(false, |first_param| {
-                    first_param.name == "self"
-                        && matches!(
-                            &first_param.param_type,
-                            crate::parser::ast::Type::Reference { mutable: true, .. }
-                        )
-                });
+    /// Don't run these checks of literal arguments
+    pub fn without_arg_checks(mut self, checks: &[ArgCheck]) -> Self {
+        self.skipped_arg_checks.extend_from_slice(checks);
+        self
+    }
 
-                self.functions.insert(
-                    name.clone(),
-                    FunctionSignature {
-                        name: name.clone(),
-                        params: param_types,
-                        return_type: ret_type,
-                        lifetimes: lifetimes.clone(),
-                        has_mut_self,
-                    },
-                );
-            }
-        }
+    /// Give every warning the Error severity, for callers that fail on them
+    pub fn with_denied_warnings(mut self) -> Self {
+        self.deny_warnings = true;
+        self
+    }
 
-        // Second pass: analyze statements
-        // Check for illegal top-level code (executable statements outside functions)
-        for stmt in &program.statements {
-            match stmt {
-                // These are allowed at top level
-                Statement::Function { .. }
-                | Statement::Struct { .. }
-                | Statement::Enum { .. }
-                | Statement::Impl { .. }
-                | Statement::Trait { .. }
-                | Statement::Import { .. } => {}
-
-                // These are NOT allowed at top level
-                Statement::Let { name, span, .. } => {
-                    return Err(ZyraError::new(
-                        "CompileError",
-                        &format!(
-                            "Top-level variable '{}' not allowed. Move it inside 'func main() {{ ... }}'",
-                            name
-                        ),
-                        Some(SourceLocation::new("", span.line, span.column)),
-                    ));
-                }
-                Statement::Expression { span, .. } => {
-                    return Err(ZyraError::new(
-                        "CompileError",
-                        "Top-level expressions not allowed. Move them inside 'func main() { ... }'",
-                        Some(SourceLocation::new("", span.line, span.column)),
-                    ));
-                }
-                Statement::Return { span, .. } => {
-                    return Err(ZyraError::new(
-                        "CompileError",
-                        "Return statement outside of function",
-                        Some(SourceLocation::new("", span.line, span.column)),
-                    ));
-                }
-                Statement::If { span, .. }
-                | Statement::While { span, .. }
-                | Statement::For { span, .. } => {
-                    return Err(ZyraError::new(
-                        "CompileError",
-                        "Control flow statements not allowed at top level. Move them inside 'func main() { ... }'",
-                        Some(SourceLocation::new("", span.line, span.column)),
-                    ));
-                }
-                Statement::Block { .. } => {
-                    return Err(ZyraError::new(
-                        "CompileError",
-                        "Top-level blocks not allowed. Move them inside 'func main() { ... }'",
-                        None,
-                    ));
-                }
-            }
-        }
+    /// Stop after this many errors instead of the default 20
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors.max(1);
+        self
+    }
 
-        // Third pass: analyze statements
-        for stmt in &program.statements {
-            self.analyze_statement(stmt)?;
-        }
+    pub fn profile(&self) -> Option<&AnalysisProfile> {
+        self.profile.as_ref()
+    }
 
-        if !self.errors.is_empty() {
-            return Err(self.errors[0].clone());
+    /// Warnings from the last analysis, in source order
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Analyze a program
+    pub fn analyze(&mut self, program: &Program) -> ZyraResult<()> {
+        self.imported = program.imported.clone();
+
+        // First pass: collect type definitions and function signatures
+        let start = Instant::now();
+        self.collect_type_definitions(&program.statements);
+        self.collect_function_signatures(&program.statements);
+        if let Some(profile) = &mut self.profile {
+            profile.add(Pass::Signatures, start.elapsed());
         }
 
+        // Errors in one item don't stop the others from being checked;
+        // this only fails once --max-errors of them were collected
+        self.errors.clear();
+        let stopped = self.analyze_items(program).err();
+        self.errors.extend(stopped);
+        self.take_errors()?;
+
         // *** MAIN FUNCTION REQUIRED ***
         // Programs must have a main() function as entry point
         if !self.functions.contains_key("main") {
@@ -946,98 +801,1073 @@ impl SemanticAnalyzer {
             }
         }
 
+        // Unused variables and imports, unreachable code, shadowing
+        self.warnings.extend(lints::check(program));
+        self.warnings
+            .sort_by_key(|w| w.location.as_ref().map(|l| (l.line, l.column)));
+        if self.deny_warnings {
+            for warning in &mut self.warnings {
+                warning.severity = Severity::Error;
+            }
+        }
+
         Ok(())
     }
 
-    fn analyze_statement(&mut self, stmt: &Statement) -> ZyraResult<ZyraType> {
-        match stmt {
-            Statement::Let {
-                name,
-                mutable,
-                type_annotation,
-                value,
-                span,
-            } => {
-                // Infer type from value
-                let value_type = self.analyze_expression(value)?;
+    /// Second and third passes: reject illegal top-level code (executable
+    /// statements outside functions), then check every item
+    fn analyze_items(&mut self, program: &Program) -> ZyraResult<()> {
+        for stmt in &program.statements {
+            if let Err(error) = Self::check_top_level(stmt) {
+                self.record_error(error)?;
+            }
+        }
 
-                // Check type annotation matches
-                if let Some(annotation) = type_annotation {
-                    let annotated_type = ZyraType::from_ast_type(annotation);
-                    if !annotated_type.is_compatible(&value_type) {
-                        return Err(ZyraError::type_error(
-                            &format!(
-                                "Type mismatch: expected {}, found {}",
-                                annotated_type.display_name(),
-                                value_type.display_name()
-                            ),
-                            Some(SourceLocation::new("", span.line, span.column)),
-                        ));
-                    }
-                }
+        // Constants and globals come first so functions anywhere in the file can use them
+        let (constants, items): (Vec<_>, Vec<_>) = program
+            .statements
+            .iter()
+            .partition(|stmt| matches!(stmt, Statement::Const { .. } | Statement::Static { .. }));
+        for stmt in constants.into_iter().chain(items) {
+            if self.is_verified(stmt) {
+                continue;
+            }
+            let checkers = self.profile.as_ref().map(AnalysisProfile::checkers);
+            let start = Instant::now();
+            if let Err(error) = self.analyze_statement(stmt) {
+                let recorded = self.record_error(error);
+                self.unwind_to_top_level();
+                recorded?;
+            }
+            if let (Some(profile), Some(checkers)) = (&mut self.profile, checkers) {
+                let checkers = profile.checkers() - checkers;
+                profile.add_item(Self::item_label(stmt), start.elapsed(), checkers);
+            }
+        }
+        Ok(())
+    }
 
-                // Register in symbol table
-                self.symbols.insert(
-                    name.clone(),
-                    Symbol {
-                        name: name.clone(),
-                        symbol_type: value_type.clone(),
-                        mutable: *mutable,
-                        scope_depth: self.scope_depth,
-                        scope_id: self.scope_stack.current(),
-                        origin: ValueOrigin::Local,
-                        decl_line: span.line,
-                    },
-                );
+    /// Keep an error and go on checking. Fails once --max-errors errors
+    /// were kept, and passes the error on if the limit was already reached.
+    fn record_error(&mut self, mut error: ZyraError) -> ZyraResult<()> {
+        if self.errors.len() >= self.max_errors {
+            return Err(error);
+        }
+        // Code of an imported module isn't in the file being checked
+        if let (Some(location), Some(module)) = (&mut error.location, &self.current_module) {
+            if location.file.is_empty() {
+                location.file = format!("{}.zr", module);
+            }
+        }
+        self.errors.push(error);
+        if self.errors.len() < self.max_errors {
+            return Ok(());
+        }
+        Err(ZyraError::too_many_errors(self.max_errors))
+    }
 
-                // Register in ownership checker
-                self.ownership
-                    .define(name, *mutable, span.line)
-                    .map_err(|e| self.ownership_error_to_zyra(e))?;
+    /// Fail with every collected error, the first in front
+    fn take_errors(&mut self) -> ZyraResult<()> {
+        match ZyraError::combine(std::mem::take(&mut self.errors)) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
 
-                // Track ownership/borrow semantics based on expression type:
-                // - Plain identifier: MOVE (ownership transfer)
-                // - &identifier: SHARED BORROW (immutable reference)
-                // - &mut identifier: MUTABLE BORROW (exclusive reference)
-                match value {
-                    // &expr or &mut expr = borrow
-                    Expression::Reference {
-                        mutable: is_mut,
-                        value: inner_value,
-                        ..
-                    } => {
-                        if let Expression::Identifier {
-                            name: source_name, ..
-                        } = inner_value.as_ref()
-                        {
-                            // Get source variable info for reference tracking
-                            let (source_origin, origin_scope) =
-                                if let Some(src_sym) = self.symbols.get(source_name) {
-                                    (src_sym.origin, src_sym.scope_id)
-                                } else {
-                                    (ValueOrigin::Local, self.scope_stack.current())
-                                };
+    /// Leave the scopes a failed check stopped in, back to top level
+    fn unwind_to_top_level(&mut self) {
+        self.unwind_to((0, 0, 0));
+        self.current_function = None;
+        self.current_module = None;
+        self.self_is_mutable = None;
+    }
 
-                            if *is_mut {
-                                // &mut = mutable borrow
-                                if let Err(borrow_err) =
-                                    self.borrow_checker
-                                        .borrow_mutable(source_name, name, span.line)
-                                {
-                                    return Err(ZyraError::ownership_error(
-                                        &format!("{}", borrow_err),
-                                        Some(SourceLocation::new("", span.line, span.column)),
-                                    ));
-                                }
-                            } else {
-                                // & = shared borrow
-                                if let Err(borrow_err) =
-                                    self.borrow_checker
-                                        .borrow_shared(source_name, name, span.line)
-                                {
-                                    return Err(ZyraError::ownership_error(
-                                        &format!("{}", borrow_err),
-                                        Some(SourceLocation::new("", span.line, span.column)),
+    /// Scope depth, loops and closures entered, to unwind to after an error
+    fn checkpoint(&self) -> (usize, usize, usize) {
+        (self.scope_depth, self.loop_labels.len(), self.closure_depth)
+    }
+
+    /// Leave the scopes, loops and closures a failed statement stopped in
+    fn unwind_to(&mut self, (scope_depth, loops, closures): (usize, usize, usize)) {
+        while self.scope_depth > scope_depth {
+            self.exit_scope();
+        }
+        self.loop_labels.truncate(loops);
+        self.closure_depth = closures;
+        self.closure_param_hints = None;
+        self.literal_hint = None;
+    }
+
+    /// Analyze one REPL entry against the session state
+    /// Unlike `analyze`, the body may contain top-level statements and no main() is required.
+    /// Variables, functions, and imports stay registered for later entries.
+    pub fn analyze_repl(&mut self, declarations: &[Statement], body: &Block) -> ZyraResult<()> {
+        self.errors.clear();
+        self.collect_type_definitions(declarations);
+        self.collect_function_signatures(declarations);
+
+        let stopped = self.analyze_repl_entry(declarations, body).err();
+        self.errors.extend(stopped);
+        self.take_errors()
+    }
+
+    fn analyze_repl_entry(&mut self, declarations: &[Statement], body: &Block) -> ZyraResult<()> {
+        for stmt in declarations {
+            if let Err(error) = self.analyze_statement(stmt) {
+                let recorded = self.record_error(error);
+                self.unwind_to_top_level();
+                recorded?;
+            }
+        }
+        self.analyze_block(body)?;
+        Ok(())
+    }
+
+    /// Re-check declarations edited since the last `analyze`, such as one function
+    /// or the statements of one module, without rebuilding the whole analyzer.
+    /// Builtin tables, imports, the type registry and the signatures of untouched
+    /// functions are reused. Returns the callers of functions whose signature
+    /// changed, which need to be re-checked as well.
+    pub fn reanalyze(&mut self, changed: &[Statement]) -> ZyraResult<Vec<String>> {
+        self.errors.clear();
+        for stmt in changed {
+            Self::check_top_level(stmt)?;
+        }
+
+        let previous: Vec<(String, Option<FunctionSignature>)> = changed
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Statement::Function { name, .. } => {
+                    Some((name.clone(), self.functions.get(name).cloned()))
+                }
+                _ => None,
+            })
+            .collect();
+        self.collect_type_definitions(changed);
+        self.collect_function_signatures(changed);
+
+        for (name, _) in &previous {
+            for callers in self.callers.values_mut() {
+                callers.remove(name);
+            }
+        }
+
+        for stmt in changed {
+            if let Err(error) = self.analyze_statement(stmt) {
+                // A failed check can stop inside the function; unwind so the
+                // next edit starts from top level again
+                let recorded = self.record_error(error);
+                self.unwind_to_top_level();
+                if let Err(limit) = recorded {
+                    self.errors.push(limit);
+                    break;
+                }
+            }
+        }
+        self.take_errors()?;
+
+        let mut stale: Vec<String> = previous
+            .iter()
+            .filter(|(name, old)| old.as_ref() != self.functions.get(name))
+            .filter_map(|(name, _)| self.callers.get(name))
+            .flatten()
+            .cloned()
+            .collect();
+        stale.sort();
+        stale.dedup();
+        Ok(stale)
+    }
+
+    /// Whether a statement is an item of a module with an up-to-date interface
+    fn is_verified(&self, stmt: &Statement) -> bool {
+        match stmt {
+            Statement::Function { name, .. }
+            | Statement::Struct { name, .. }
+            | Statement::Enum { name, .. } => self.verified_items.contains(name),
+            _ => false,
+        }
+    }
+
+    /// How a top-level item is named in the analysis profile
+    fn item_label(stmt: &Statement) -> String {
+        match stmt {
+            Statement::Function { name, .. } => format!("func {}", name),
+            Statement::Impl {
+                target_type,
+                trait_name: Some(trait_name),
+                ..
+            } => format!("impl {} for {}", trait_name, target_type),
+            Statement::Impl { target_type, .. } => format!("impl {}", target_type),
+            Statement::Struct { name, .. } => format!("struct {}", name),
+            Statement::Enum { name, .. } => format!("enum {}", name),
+            Statement::Trait { name, .. } => format!("trait {}", name),
+            _ => format!("line {}", stmt.span().line),
+        }
+    }
+
+    /// Reject executable statements outside functions
+    fn check_top_level(stmt: &Statement) -> ZyraResult<()> {
+        match stmt {
+            // These are allowed at top level
+            Statement::Function { .. }
+            | Statement::Const { .. }
+            | Statement::Static { .. }
+            | Statement::Struct { .. }
+            | Statement::Enum { .. }
+            | Statement::Impl { .. }
+            | Statement::Trait { .. }
+            | Statement::Import { .. } => {}
+
+            // These are NOT allowed at top level
+            Statement::LetPattern { span, .. } => {
+                return Err(ZyraError::new(
+                    "CompileError",
+                    "Top-level variables not allowed. Move them inside 'func main() { ... }'",
+                    Some(SourceLocation::from_span(span)),
+                ));
+            }
+            Statement::Let { name, span, .. } => {
+                return Err(ZyraError::new(
+                    "CompileError",
+                    &format!(
+                        "Top-level variable '{}' not allowed. Move it inside 'func main() {{ ... }}'",
+                        name
+                    ),
+                    Some(SourceLocation::from_span(span)),
+                ));
+            }
+            Statement::Expression { span, .. } => {
+                return Err(ZyraError::new(
+                    "CompileError",
+                    "Top-level expressions not allowed. Move them inside 'func main() { ... }'",
+                    Some(SourceLocation::from_span(span)),
+                ));
+            }
+            Statement::Return { span, .. } => {
+                return Err(ZyraError::new(
+                    "CompileError",
+                    "Return statement outside of function",
+                    Some(SourceLocation::from_span(span)),
+                ));
+            }
+            Statement::If { span, .. }
+            | Statement::IfLet { span, .. }
+            | Statement::While { span, .. }
+            | Statement::WhileLet { span, .. }
+            | Statement::For { span, .. }
+            | Statement::Break { span, .. }
+            | Statement::Continue { span, .. } => {
+                return Err(ZyraError::new(
+                    "CompileError",
+                    "Control flow statements not allowed at top level. Move them inside 'func main() { ... }'",
+                    Some(SourceLocation::from_span(span)),
+                ));
+            }
+            Statement::Block { .. } => {
+                return Err(ZyraError::new(
+                    "CompileError",
+                    "Top-level blocks not allowed. Move them inside 'func main() { ... }'",
+                    None,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject a reference to a private item of another module. `path` names a
+    /// type when `is_type` is set, otherwise a function (`util::helper`) or an
+    /// associated function of a type (`Rect::new`)
+    fn check_visibility(
+        &self,
+        path: &str,
+        is_type: bool,
+        span: crate::lexer::Span,
+    ) -> ZyraResult<()> {
+        // The importing file's own types shadow module types of the same name
+        let find_type = |name: &str| {
+            if !name.contains("::") && self.types.contains_key(name) {
+                None
+            } else {
+                self.imported.find_type(name)
+            }
+        };
+        let (name, item) = if is_type {
+            (path, find_type(path))
+        } else if let Some(item) = self.imported.get(path) {
+            (path, Some(item))
+        } else {
+            match path.rsplit_once("::") {
+                Some((type_name, _)) => (type_name, find_type(type_name)),
+                None => (path, None),
+            }
+        };
+        match item {
+            Some(item) if !item.public && self.current_module.as_ref() != Some(&item.module) => {
+                Err(ZyraError::name_error(
+                    &format!("'{}' is private to module '{}'", name, item.module),
+                    Some(SourceLocation::from_span(&span)),
+                )
+                .with_suggestion(&format!(
+                    "Declare it with 'pub' in {}.zr to use it from other files",
+                    item.module
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Register struct and enum definitions in the type registry
+    fn collect_type_definitions(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            let def = match stmt {
+                Statement::Struct { name, fields, .. } => TypeDef::Struct {
+                    name: name.clone(),
+                    fields: fields
+                        .iter()
+                        .map(|f| (f.name.clone(), ZyraType::from_ast_type(&f.field_type)))
+                        .collect(),
+                },
+                Statement::Enum { name, variants, .. } => TypeDef::Enum {
+                    name: name.clone(),
+                    variants: variants.iter().map(|v| v.name.clone()).collect(),
+                },
+                Statement::Trait { name, methods, .. } => TypeDef::Trait {
+                    name: name.clone(),
+                    methods: methods
+                        .iter()
+                        .map(|m| (m.name.clone(), m.params.len(), m.default_impl.is_some()))
+                        .collect(),
+                },
+                Statement::Impl {
+                    target_type,
+                    trait_name: Some(trait_name),
+                    ..
+                } => {
+                    self.trait_impls
+                        .insert((trait_name.clone(), target_type.clone()));
+                    continue;
+                }
+                _ => continue,
+            };
+            let name = match &def {
+                TypeDef::Struct { name, .. }
+                | TypeDef::Enum { name, .. }
+                | TypeDef::Trait { name, .. } => name.clone(),
+            };
+            self.types.insert(name, def);
+        }
+    }
+
+    /// Type-check a built-in Vec method call such as `v.push(x)`. Methods that
+    /// change the Vec need a mutable receiver
+    fn analyze_vec_method(
+        &mut self,
+        elem: &ZyraType,
+        receiver: &Expression,
+        method: &str,
+        arguments: &[Expression],
+        span: crate::lexer::Span,
+    ) -> ZyraResult<ZyraType> {
+        let location = || Some(SourceLocation::from_span(&span));
+        let elem = elem.clone();
+        let index = ZyraType::I64;
+        let (params, return_type, mutates) = match method {
+            "push" => (vec![elem.clone()], ZyraType::Void, true),
+            "pop" => (vec![], ZyraType::Option(Box::new(elem)), true),
+            "insert" => (vec![index, elem.clone()], ZyraType::Void, true),
+            "remove" => (vec![index], elem, true),
+            "clear" | "sort" | "reverse" => (vec![], ZyraType::Void, true),
+            "contains" => (vec![elem], ZyraType::Bool, false),
+            "len" | "length" => (vec![], ZyraType::I32, false),
+            "is_empty" => (vec![], ZyraType::Bool, false),
+            // Closure-taking methods check their closures when they're analyzed
+            "map" | "filter" | "fold" | "forEach" | "find" | "any" | "all" => {
+                for arg in arguments {
+                    self.analyze_expression(arg)?;
+                }
+                return Ok(ZyraType::Unknown);
+            }
+            _ => {
+                return Err(ZyraError::type_error(
+                    &format!("Vec has no method '{}'", method),
+                    location(),
+                )
+                .with_suggestion(
+                    "Vec methods: push, pop, insert, remove, clear, sort, reverse, contains, len, is_empty",
+                ))
+            }
+        };
+
+        self.check_method_args("Vec", method, &params, arguments, span)?;
+        if mutates {
+            self.check_mutating_receiver(receiver, method, span)?;
+        }
+        Ok(return_type)
+    }
+
+    /// Type-check a built-in Map method call such as `m.insert(k, v)`
+    fn analyze_map_method(
+        &mut self,
+        key: &ZyraType,
+        value: &ZyraType,
+        receiver: &Expression,
+        method: &str,
+        arguments: &[Expression],
+        span: crate::lexer::Span,
+    ) -> ZyraResult<ZyraType> {
+        let location = || Some(SourceLocation::from_span(&span));
+        let (key, value) = (key.clone(), value.clone());
+        let (params, return_type, mutates) = match method {
+            "get" => (vec![key], ZyraType::Option(Box::new(value)), false),
+            "insert" => (vec![key, value], ZyraType::Void, true),
+            "remove" => (vec![key], ZyraType::Option(Box::new(value)), true),
+            "contains_key" => (vec![key], ZyraType::Bool, false),
+            "keys" => (vec![], ZyraType::Vec(Box::new(key)), false),
+            "values" => (vec![], ZyraType::Vec(Box::new(value)), false),
+            "len" | "length" => (vec![], ZyraType::I32, false),
+            "is_empty" => (vec![], ZyraType::Bool, false),
+            "clear" => (vec![], ZyraType::Void, true),
+            _ => {
+                return Err(ZyraError::type_error(
+                    &format!("Map has no method '{}'", method),
+                    location(),
+                )
+                .with_suggestion(
+                    "Map methods: get, insert, remove, contains_key, keys, values, len, is_empty, clear",
+                ))
+            }
+        };
+
+        self.check_method_args("Map", method, &params, arguments, span)?;
+        if mutates {
+            self.check_mutating_receiver(receiver, method, span)?;
+        }
+        Ok(return_type)
+    }
+
+    /// Type-check a built-in Set method call such as `s.insert(x)`
+    fn analyze_set_method(
+        &mut self,
+        elem: &ZyraType,
+        receiver: &Expression,
+        method: &str,
+        arguments: &[Expression],
+        span: crate::lexer::Span,
+    ) -> ZyraResult<ZyraType> {
+        let location = || Some(SourceLocation::from_span(&span));
+        let elem = elem.clone();
+        let set = ZyraType::Set(Box::new(elem.clone()));
+        let (params, return_type, mutates) = match method {
+            "insert" | "remove" => (vec![elem], ZyraType::Bool, true),
+            "contains" => (vec![elem], ZyraType::Bool, false),
+            "union" | "intersection" | "difference" => (vec![set.clone()], set, false),
+            "to_vec" => (vec![], ZyraType::Vec(Box::new(elem)), false),
+            "len" | "length" => (vec![], ZyraType::I32, false),
+            "is_empty" => (vec![], ZyraType::Bool, false),
+            "clear" => (vec![], ZyraType::Void, true),
+            _ => {
+                return Err(ZyraError::type_error(
+                    &format!("Set has no method '{}'", method),
+                    location(),
+                )
+                .with_suggestion(
+                    "Set methods: insert, remove, contains, union, intersection, difference, to_vec, len, is_empty, clear",
+                ))
+            }
+        };
+
+        self.check_method_args("Set", method, &params, arguments, span)?;
+        if mutates {
+            self.check_mutating_receiver(receiver, method, span)?;
+        }
+        Ok(return_type)
+    }
+
+    /// Type-check a built-in String method call such as `s.to_upper()`.
+    /// Strings are values, so none of them needs a mutable receiver
+    fn analyze_string_method(
+        &mut self,
+        method: &str,
+        arguments: &[Expression],
+        span: crate::lexer::Span,
+    ) -> ZyraResult<ZyraType> {
+        let string = || ZyraType::String;
+        let strings = || ZyraType::Vec(Box::new(ZyraType::String));
+        let index = ZyraType::I64;
+        let (params, return_type) = match method {
+            "len" | "length" | "byte_len" => (vec![], ZyraType::I32),
+            "index_of" => (vec![string()], ZyraType::I32),
+            "char_at" => (vec![index], ZyraType::Option(Box::new(ZyraType::Char))),
+            "substring" => (vec![index.clone(), index], string()),
+            "is_empty" => (vec![], ZyraType::Bool),
+            "to_upper" | "to_lower" | "trim" | "trim_start" | "trim_end" | "reverse" => {
+                (vec![], string())
+            }
+            "contains" | "starts_with" | "ends_with" => (vec![string()], ZyraType::Bool),
+            "replace" => (vec![string(), string()], string()),
+            "repeat" => (vec![ZyraType::I64], string()),
+            "split" => (vec![string()], strings()),
+            "lines" => (vec![], strings()),
+            "chars" => (vec![], ZyraType::Vec(Box::new(ZyraType::Char))),
+            "parse_int" => (vec![], ZyraType::Option(Box::new(ZyraType::I64))),
+            "parse_float" => (vec![], ZyraType::Option(Box::new(ZyraType::F64))),
+            _ => {
+                return Err(ZyraError::type_error(
+                    &format!("String has no method '{}'", method),
+                    Some(SourceLocation::from_span(&span)),
+                )
+                .with_suggestion(
+                    "String methods: len, byte_len, is_empty, char_at, substring, to_upper, to_lower, trim, trim_start, trim_end, reverse, contains, starts_with, ends_with, index_of, replace, repeat, split, lines, chars, parse_int, parse_float",
+                ))
+            }
+        };
+
+        self.check_method_args("String", method, &params, arguments, span)?;
+        Ok(return_type)
+    }
+
+    /// Run the literal argument check of a standard library call, unless the
+    /// project turned it off or the argument isn't a string literal
+    fn check_literal_args(&self, func_name: &str, arguments: &[Expression]) -> ZyraResult<()> {
+        if !self.imported_std_items.contains_key(func_name) {
+            return Ok(());
+        }
+        let Some((check, index)) = ArgCheck::for_function(func_name) else {
+            return Ok(());
+        };
+        if self.skipped_arg_checks.contains(&check) {
+            return Ok(());
+        }
+        if let Some(Expression::String { value, span }) = arguments.get(index) {
+            if let Some(problem) = check.validate(func_name, value) {
+                return Err(ZyraError::new(
+                    check.error_kind(),
+                    &problem,
+                    Some(SourceLocation::from_span(span)),
+                )
+                .with_suggestion(&format!(
+                    "If the value is right, turn this check off with [check] skip = [\"{}\"] in zyra.toml",
+                    check.as_str()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Type-check a method of a standard library value, such as `t.update(dt)`
+    /// on a Tween. It calls the function named after the type, `tween_update`,
+    /// with the receiver as first argument. None when the type has its own
+    /// definition or the standard library has no such function in scope.
+    fn analyze_builtin_method(
+        &mut self,
+        type_name: &str,
+        method: &str,
+        arguments: &[Expression],
+        span: crate::lexer::Span,
+    ) -> Option<ZyraResult<ZyraType>> {
+        if self.types.contains_key(type_name) {
+            return None;
+        }
+        let sig = self
+            .functions
+            .get(&format!("{}_{}", type_name.to_lowercase(), method))?;
+        let params: Vec<ZyraType> = sig.params.iter().skip(1).map(|(_, t)| t.clone()).collect();
+        let return_type = sig.return_type.clone();
+        Some(
+            self.check_method_args(type_name, method, &params, arguments, span)
+                .map(|_| return_type),
+        )
+    }
+
+    /// Analyze an expression whose number literals take `hint` as their type
+    fn analyze_with_hint(
+        &mut self,
+        expr: &Expression,
+        hint: Option<ZyraType>,
+    ) -> ZyraResult<ZyraType> {
+        self.literal_hint = hint;
+        self.analyze_expression(expr)
+    }
+
+    /// Type of an integer literal: its suffix, else the type its context
+    /// wants, else i32 (i64 when the value doesn't fit an i32)
+    fn int_literal_type(
+        value: i128,
+        suffix: Option<&Type>,
+        hint: Option<ZyraType>,
+        span: crate::lexer::Span,
+    ) -> ZyraResult<ZyraType> {
+        let literal_type = match (suffix, hint) {
+            (Some(suffix), _) => ZyraType::from_ast_type(suffix),
+            (None, Some(hint)) if hint.is_integer() => hint,
+            _ if i32::try_from(value).is_err() => return Ok(ZyraType::I64),
+            _ => return Ok(ZyraType::I32),
+        };
+        match literal_type.int_range() {
+            Some((min, max)) if value < min || value > max => Err(ZyraError::type_error(
+                &format!(
+                    "Literal {} does not fit in {} ({} to {})",
+                    value,
+                    literal_type.display_name(),
+                    min,
+                    max
+                ),
+                Some(SourceLocation::from_span(&span)),
+            )),
+            _ => Ok(literal_type),
+        }
+    }
+
+    /// Analyze the operands of a binary operator. An unsuffixed literal takes
+    /// the type of the other operand, so `x + 1` is a u8 addition when `x` is
+    /// a u8; arithmetic passes the type its result should have on to both.
+    fn analyze_operands(
+        &mut self,
+        operator: &BinaryOp,
+        left: &Expression,
+        right: &Expression,
+        hint: Option<ZyraType>,
+    ) -> ZyraResult<(ZyraType, ZyraType)> {
+        use BinaryOp::*;
+        let (hint, peer) = match operator {
+            Add | Subtract | Multiply | Divide | Modulo | BitAnd | BitOr | BitXor => (hint, true),
+            // The shift amount's type is independent of the shifted value's
+            ShiftLeft | ShiftRight => {
+                return Ok((
+                    self.analyze_with_hint(left, hint)?,
+                    self.analyze_expression(right)?,
+                ))
+            }
+            Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual => (None, true),
+            And | Or => (None, false),
+        };
+        let numeric = |ty: ZyraType| Some(ty).filter(ZyraType::is_numeric);
+        if peer && left.is_untyped_number() && !right.is_untyped_number() {
+            let right_type = self.analyze_with_hint(right, hint)?;
+            let left_type = self.analyze_with_hint(left, numeric(right_type.clone()))?;
+            Ok((left_type, right_type))
+        } else {
+            let left_type = self.analyze_with_hint(left, hint)?;
+            let right_hint = if peer {
+                numeric(left_type.clone())
+            } else {
+                None
+            };
+            Ok((left_type, self.analyze_with_hint(right, right_hint)?))
+        }
+    }
+
+    /// Reject a method the receiver's type can't have: numbers, bools and chars
+    /// have none, and the program's own structs and enums only those of their
+    /// impls and the traits they implement
+    fn check_method_exists(
+        &self,
+        receiver_type: &ZyraType,
+        method: &str,
+        span: crate::lexer::Span,
+    ) -> ZyraResult<()> {
+        let known = match receiver_type {
+            ZyraType::Struct(name) | ZyraType::Enum(name) => {
+                !matches!(
+                    self.types.get(name),
+                    Some(TypeDef::Struct { .. } | TypeDef::Enum { .. })
+                ) || self
+                    .functions
+                    .contains_key(&format!("{}::{}", name, method))
+                    || self.trait_impls.iter().any(|(trait_name, target)| {
+                        target == name
+                            && matches!(
+                                self.types.get(trait_name),
+                                Some(TypeDef::Trait { methods, .. })
+                                    if methods.iter().any(|(m, _, _)| m == method)
+                            )
+                    })
+            }
+            ty => {
+                !(ty.is_integer() || ty.is_float() || matches!(ty, ZyraType::Bool | ZyraType::Char))
+            }
+        };
+        if known {
+            return Ok(());
+        }
+        Err(ZyraError::type_error(
+            &format!(
+                "{} has no method '{}'",
+                receiver_type.display_name(),
+                method
+            ),
+            Some(SourceLocation::from_span(&span)),
+        ))
+    }
+
+    /// Check the arguments of a built-in method against its parameter types;
+    /// integer parameters accept any integer type
+    fn check_method_args(
+        &mut self,
+        type_name: &str,
+        method: &str,
+        params: &[ZyraType],
+        arguments: &[Expression],
+        span: crate::lexer::Span,
+    ) -> ZyraResult<()> {
+        let location = || Some(SourceLocation::from_span(&span));
+        if arguments.len() != params.len() {
+            return Err(ZyraError::type_error(
+                &format!(
+                    "{}::{} expects {} argument(s), found {}",
+                    type_name,
+                    method,
+                    params.len(),
+                    arguments.len()
+                ),
+                location(),
+            ));
+        }
+        for (param, arg) in params.iter().zip(arguments) {
+            let arg_type = self.analyze_with_hint(arg, Some(param.clone()))?;
+            let accepted = if param.is_integer() {
+                arg_type.is_integer() || matches!(arg_type, ZyraType::Unknown)
+            } else {
+                self.type_accepts(param, &arg_type)
+            };
+            if !accepted {
+                return Err(ZyraError::type_error(
+                    &format!(
+                        "{}::{} expects {}, found {}",
+                        type_name,
+                        method,
+                        param.display_name(),
+                        arg_type.display_name()
+                    ),
+                    location(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Map keys and Set elements are hashed, so they must be integers, strings, bools or chars
+    fn check_hashable(ty: &ZyraType, role: &str, span: crate::lexer::Span) -> ZyraResult<()> {
+        if ty.is_integer()
+            || matches!(
+                ty,
+                ZyraType::String | ZyraType::Bool | ZyraType::Char | ZyraType::Unknown
+            )
+        {
+            return Ok(());
+        }
+        Err(ZyraError::type_error(
+            &format!("{} cannot be a {}", ty.display_name(), role),
+            Some(SourceLocation::from_span(&span)),
+        )
+        .with_suggestion("Map keys and Set elements are integers, strings, bools or chars"))
+    }
+
+    /// A method that changes its receiver (`v.push(x)`, `m.insert(k, v)`) needs
+    /// a mutable variable that isn't currently borrowed
+    fn check_mutating_receiver(
+        &self,
+        receiver: &Expression,
+        method: &str,
+        span: crate::lexer::Span,
+    ) -> ZyraResult<()> {
+        let location = || Some(SourceLocation::from_span(&span));
+        if let Expression::Identifier { name, .. } = receiver {
+            if matches!(self.symbols.get(name), Some(symbol) if !symbol.mutable) {
+                return Err(ZyraError::ownership_error(
+                    &format!("Cannot call '{}' on immutable variable '{}'", method, name),
+                    location(),
+                )
+                .with_suggestion(&format!("Declare it with 'let mut {}'", name)));
+            }
+            if let Err(borrow_err) = self.borrow_checker.can_mutate(name, span.line) {
+                return Err(Self::borrow_error_to_zyra(borrow_err, location()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether a value of type `actual` can be used where `expected` is required,
+    /// including a type standing in for a trait it implements
+    fn type_accepts(&self, expected: &ZyraType, actual: &ZyraType) -> bool {
+        if expected.is_compatible(actual) {
+            return true;
+        }
+        let strip = |t: &ZyraType| match t {
+            ZyraType::Reference { inner, .. } => inner.as_ref().clone(),
+            other => other.clone(),
+        };
+        match (strip(expected), strip(actual)) {
+            (ZyraType::Struct(trait_name), ZyraType::Struct(type_name))
+            | (ZyraType::Struct(trait_name), ZyraType::Enum(type_name)) => {
+                self.trait_impls.contains(&(trait_name, type_name))
+            }
+            _ => false,
+        }
+    }
+
+    /// Check a call of a closure value against its parameter types and return
+    /// its result type; values of unknown type are called unchecked
+    fn analyze_closure_call(
+        &mut self,
+        name: &str,
+        callee_type: &ZyraType,
+        arguments: &[Expression],
+        span: crate::lexer::Span,
+    ) -> ZyraResult<ZyraType> {
+        let location = || Some(SourceLocation::from_span(&span));
+        let arg_types = arguments
+            .iter()
+            .map(|arg| self.analyze_expression(arg))
+            .collect::<ZyraResult<Vec<_>>>()?;
+        let (params, return_type) = match callee_type {
+            ZyraType::Closure {
+                params,
+                return_type,
+            } => (params, return_type),
+            ZyraType::Unknown => return Ok(ZyraType::Unknown),
+            other => {
+                return Err(ZyraError::type_error(
+                    &format!("Cannot call a value of type {}", other.display_name()),
+                    location(),
+                ))
+            }
+        };
+        if arg_types.len() != params.len() {
+            return Err(ZyraError::type_error(
+                &format!(
+                    "Closure '{}' expects {} argument(s), got {}",
+                    name,
+                    params.len(),
+                    arg_types.len()
+                ),
+                location(),
+            ));
+        }
+        for (i, (param, arg)) in params.iter().zip(&arg_types).enumerate() {
+            if !self.type_accepts(param, arg) {
+                return Err(ZyraError::type_error(
+                    &format!(
+                        "Closure '{}' argument {} expects {}, got {}",
+                        name,
+                        i + 1,
+                        param.display_name(),
+                        arg.display_name()
+                    ),
+                    location(),
+                ));
+            }
+        }
+        Ok(return_type.as_ref().clone())
+    }
+
+    /// Register the signatures of all functions so calls can precede definitions
+    fn collect_function_signatures(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            // Inherent methods are known as `Type::method`, without their self
+            // parameter, so calls on any receiver expression can be checked
+            if let Statement::Impl {
+                target_type,
+                trait_name: None,
+                methods,
+                ..
+            } = stmt
+            {
+                let self_type = match self.types.get(target_type) {
+                    Some(TypeDef::Enum { .. }) => ZyraType::Enum(target_type.clone()),
+                    _ => ZyraType::Struct(target_type.clone()),
+                };
+                for method in methods {
+                    let Statement::Function {
+                        name,
+                        params,
+                        return_type,
+                        lifetimes,
+                        ..
+                    } = method.as_ref()
+                    else {
+                        continue;
+                    };
+                    if !params.first().is_some_and(|p| p.name.ends_with("self")) {
+                        continue;
+                    }
+                    let own_type = |ty: &crate::parser::ast::Type| match ty {
+                        crate::parser::ast::Type::SelfType => self_type.clone(),
+                        other => ZyraType::from_ast_type(other),
+                    };
+                    let qualified = format!("{}::{}", target_type, name);
+                    self.functions.insert(
+                        qualified.clone(),
+                        FunctionSignature {
+                            name: qualified,
+                            params: params[1..]
+                                .iter()
+                                .map(|p| (p.name.clone(), own_type(&p.param_type)))
+                                .collect(),
+                            return_type: return_type.as_ref().map_or(ZyraType::Void, own_type),
+                            lifetimes: lifetimes.clone(),
+                            // The receiver's borrow ends with the call, so it
+                            // isn't recorded with the borrow checker
+                            has_mut_self: false,
+                        },
+                    );
+                }
+                continue;
+            }
+            if let Statement::Function {
+                name,
+                params,
+                return_type,
+                lifetimes,
+                ..
+            } = stmt
+            {
+                let param_types: Vec<_> = params
+                    .iter()
+                    .map(|p| (p.name.clone(), ZyraType::from_ast_type(&p.param_type)))
+                    .collect();
+
+                let ret_type = return_type
+                    .as_ref()
+                    .map(ZyraType::from_ast_type)
+                    .unwrap_or(ZyraType::Void);
+
+                // Detect &mut self: first param named "self" with mutable reference type
+                let has_mut_self = params.first().is_some_and(|first_param| {
+                    first_param.name == "self"
+                        && matches!(
+                            &first_param.param_type,
+                            crate::parser::ast::Type::Reference { mutable: true, .. }
+                        )
+                });
+
+                self.functions.insert(
+                    name.clone(),
+                    FunctionSignature {
+                        name: name.clone(),
+                        params: param_types,
+                        return_type: ret_type,
+                        lifetimes: lifetimes.clone(),
+                        has_mut_self,
+                    },
+                );
+            }
+        }
+    }
+
+    fn analyze_statement(&mut self, stmt: &Statement) -> ZyraResult<ZyraType> {
+        match stmt {
+            Statement::Let {
+                name,
+                mutable,
+                type_annotation,
+                value,
+                span,
+            } => {
+                self.check_not_global(name, span)?;
+
+                // Infer type from value; number literals take the annotated type
+                let mut value_type = self.analyze_with_hint(
+                    value,
+                    type_annotation.as_ref().map(ZyraType::from_ast_type),
+                )?;
+
+                // Check type annotation matches
+                if let Some(annotation) = type_annotation {
+                    let annotated_type = ZyraType::from_ast_type(annotation);
+                    if !self.type_accepts(&annotated_type, &value_type) {
+                        return Err(Self::with_cast_fix(
+                            ZyraError::type_error(
+                                &format!(
+                                    "Type mismatch: expected {}, found {}",
+                                    annotated_type.display_name(),
+                                    value_type.display_name()
+                                ),
+                                Some(SourceLocation::from_span(span)),
+                            ),
+                            value,
+                            &annotated_type,
+                            &value_type,
+                        ));
+                    }
+                    // The binding has the declared type: `let b: i64 = 10` is an i64
+                    if !matches!(annotated_type, ZyraType::Unknown) {
+                        value_type = annotated_type;
+                    }
+                }
+
+                // Register in symbol table
+                self.symbols.insert(
+                    name.clone(),
+                    Symbol {
+                        name: name.clone(),
+                        symbol_type: value_type.clone(),
+                        mutable: *mutable,
+                        scope_depth: self.scope_depth,
+                        scope_id: self.scope_stack.current(),
+                        origin: ValueOrigin::Local,
+                        decl_line: span.line,
+                    },
+                );
+
+                // Register in ownership checker
+                timed!(
+                    self,
+                    Ownership,
+                    self.ownership.define(name, *mutable, span.line)
+                )
+                .map_err(|e| self.ownership_error_to_zyra(e))?;
+
+                // Track ownership/borrow semantics based on expression type:
+                // - Plain identifier: MOVE (ownership transfer)
+                // - &identifier: SHARED BORROW (immutable reference)
+                // - &mut identifier: MUTABLE BORROW (exclusive reference)
+                match value {
+                    // &expr or &mut expr = borrow
+                    Expression::Reference {
+                        mutable: is_mut,
+                        value: inner_value,
+                        ..
+                    } => {
+                        if let Expression::Identifier {
+                            name: source_name, ..
+                        } = inner_value.as_ref()
+                        {
+                            // Get source variable info for reference tracking
+                            let (source_origin, origin_scope) =
+                                if let Some(src_sym) = self.symbols.get(source_name) {
+                                    (src_sym.origin, src_sym.scope_id)
+                                } else {
+                                    (ValueOrigin::Local, self.scope_stack.current())
+                                };
+
+                            if *is_mut {
+                                // &mut = mutable borrow
+                                if let Err(borrow_err) = timed!(
+                                    self,
+                                    Borrow,
+                                    self.borrow_checker.borrow_mutable(
+                                        source_name,
+                                        name,
+                                        span.line
+                                    )
+                                ) {
+                                    return Err(Self::borrow_error_to_zyra(
+                                        borrow_err,
+                                        Some(SourceLocation::from_span(span)),
+                                    ));
+                                }
+                            } else {
+                                // & = shared borrow
+                                if let Err(borrow_err) = timed!(
+                                    self,
+                                    Borrow,
+                                    self.borrow_checker
+                                        .borrow_shared(source_name, name, span.line)
+                                ) {
+                                    return Err(Self::borrow_error_to_zyra(
+                                        borrow_err,
+                                        Some(SourceLocation::from_span(span)),
                                     ));
                                 }
                             }
@@ -1064,7 +1894,7 @@ impl SemanticAnalyzer {
                                      note: temporary values are dropped at the end of the statement\n\
                                      hint: store the value in a variable first, then borrow that variable"
                                 ),
-                                Some(SourceLocation::new("", span.line, span.column)),
+                                Some(SourceLocation::from_span(span)),
                             ));
                         }
                     }
@@ -1072,13 +1902,15 @@ impl SemanticAnalyzer {
                     Expression::Identifier {
                         name: source_name, ..
                     } => {
-                        if let Err(borrow_err) =
+                        if let Err(borrow_err) = timed!(
+                            self,
+                            Borrow,
                             self.borrow_checker
                                 .record_move(source_name, name, span.line)
-                        {
-                            return Err(ZyraError::ownership_error(
-                                &format!("{}", borrow_err),
-                                Some(SourceLocation::new("", span.line, span.column)),
+                        ) {
+                            return Err(Self::borrow_error_to_zyra(
+                                borrow_err,
+                                Some(SourceLocation::from_span(span)),
                             ));
                         }
                     }
@@ -1089,6 +1921,99 @@ impl SemanticAnalyzer {
                 Ok(ZyraType::Void)
             }
 
+            Statement::LetPattern {
+                pattern,
+                type_annotation,
+                value,
+                span,
+            } => {
+                let annotated_type = type_annotation.as_ref().map(ZyraType::from_ast_type);
+                let mut value_type = self.analyze_with_hint(value, annotated_type.clone())?;
+                if let Some(annotated_type) = annotated_type {
+                    if !self.type_accepts(&annotated_type, &value_type) {
+                        return Err(ZyraError::type_error(
+                            &format!(
+                                "Type mismatch: expected {}, found {}",
+                                annotated_type.display_name(),
+                                value_type.display_name()
+                            ),
+                            Some(SourceLocation::from_span(span)),
+                        ));
+                    }
+                    value_type = annotated_type;
+                }
+
+                if !Self::is_irrefutable(pattern) {
+                    return Err(ZyraError::type_error(
+                        "This pattern can fail to match, so 'let' can't use it",
+                        Some(SourceLocation::from_span(span)),
+                    )
+                    .with_suggestion("Use 'if let' or 'match' to handle values it doesn't match"));
+                }
+
+                // Each bound name is a new variable owning its part of the value
+                self.analyze_pattern_bindings(pattern, &value_type)?;
+
+                let mut names = Vec::new();
+                pattern.bound_names(&mut names);
+                let location = Some(SourceLocation::from_span(span));
+                match value {
+                    // Destructuring `&p` borrows p for every name bound from it;
+                    // `&mut p` is one exclusive borrow, held by the first name
+                    Expression::Reference {
+                        mutable,
+                        value: inner,
+                        ..
+                    } => {
+                        if let Expression::Identifier {
+                            name: source_name, ..
+                        } = inner.as_ref()
+                        {
+                            let borrowers = if *mutable {
+                                &names[..names.len().min(1)]
+                            } else {
+                                &names[..]
+                            };
+                            for borrower in borrowers {
+                                let borrowed = timed!(self, Borrow, {
+                                    if *mutable {
+                                        self.borrow_checker.borrow_mutable(
+                                            source_name,
+                                            borrower,
+                                            span.line,
+                                        )
+                                    } else {
+                                        self.borrow_checker.borrow_shared(
+                                            source_name,
+                                            borrower,
+                                            span.line,
+                                        )
+                                    }
+                                });
+                                borrowed
+                                    .map_err(|e| Self::borrow_error_to_zyra(e, location.clone()))?;
+                            }
+                        }
+                    }
+                    // Destructuring a variable moves it, like `let b = a`
+                    Expression::Identifier {
+                        name: source_name, ..
+                    } if !value_type.is_copy_type() => {
+                        let target = names.first().map_or("_", String::as_str);
+                        timed!(
+                            self,
+                            Borrow,
+                            self.borrow_checker
+                                .record_move(source_name, target, span.line)
+                        )
+                        .map_err(|e| Self::borrow_error_to_zyra(e, location))?;
+                    }
+                    _ => {}
+                }
+
+                Ok(ZyraType::Void)
+            }
+
             Statement::Function {
                 name,
                 lifetimes: lifetime_params,
@@ -1100,10 +2025,14 @@ impl SemanticAnalyzer {
                 // Enter function scope
                 self.enter_scope();
                 self.current_function = Some(name.clone());
+                let outer_module = self.current_module.clone();
+                if let Some(item) = self.imported.get(name) {
+                    self.current_module = Some(item.module.clone());
+                }
 
                 // Declare lifetime parameters
                 for lt in lifetime_params {
-                    self.lifetime_checker.declare_lifetime(lt);
+                    timed!(self, Lifetime, self.lifetime_checker.declare_lifetime(lt));
                 }
 
                 // Register parameters
@@ -1122,6 +2051,7 @@ impl SemanticAnalyzer {
                         param.name.clone()
                     };
                     let is_mutable = param.name.contains("mut");
+                    self.check_not_global(&normalized_name, &param.span)?;
 
                     // Track self mutability for method body analysis
                     if is_self_param {
@@ -1140,13 +2070,19 @@ impl SemanticAnalyzer {
                             decl_line: span.line,
                         },
                     );
-                    self.ownership
-                        .define(&normalized_name, is_mutable, span.line)
-                        .map_err(|e| self.ownership_error_to_zyra(e))?;
+                    timed!(
+                        self,
+                        Ownership,
+                        self.ownership
+                            .define(&normalized_name, is_mutable, span.line)
+                    )
+                    .map_err(|e| self.ownership_error_to_zyra(e))?;
                 }
 
-                // Analyze body
+                // Analyze body (loops of an enclosing scope are not visible to break/continue)
+                let outer_loops = std::mem::take(&mut self.loop_labels);
                 let body_type = self.analyze_block(body)?;
+                self.loop_labels = outer_loops;
 
                 // Check return type (only if body has a trailing expression, not explicit returns)
                 // Functions with explicit `return` statements have Void body type but
@@ -1162,7 +2098,7 @@ impl SemanticAnalyzer {
                                 expected.display_name(),
                                 body_type.display_name()
                             ),
-                            Some(SourceLocation::new("", span.line, span.column)),
+                            Some(SourceLocation::from_span(span)),
                         ));
                     }
                 }
@@ -1174,12 +2110,63 @@ impl SemanticAnalyzer {
                 }
 
                 self.current_function = None;
+                self.current_module = outer_module;
                 self.self_is_mutable = None;
                 self.exit_scope();
 
                 Ok(ZyraType::Void)
             }
 
+            Statement::Const {
+                name,
+                type_annotation,
+                value,
+                span,
+            }
+            | Statement::Static {
+                name,
+                type_annotation,
+                value,
+                span,
+            } => {
+                let outer_module = self.current_module.clone();
+                if let Some(item) = self.imported.get(name) {
+                    self.current_module = Some(item.module.clone());
+                }
+                self.check_constant_expression(value)?;
+
+                let annotated_type = type_annotation.as_ref().map(ZyraType::from_ast_type);
+                let mut value_type = self.analyze_with_hint(value, annotated_type.clone())?;
+                if let Some(annotated_type) = annotated_type {
+                    if !self.type_accepts(&annotated_type, &value_type) {
+                        return Err(Self::with_cast_fix(
+                            ZyraError::type_error(
+                                &format!(
+                                    "Type mismatch: expected {}, found {}",
+                                    annotated_type.display_name(),
+                                    value_type.display_name()
+                                ),
+                                Some(SourceLocation::from_span(span)),
+                            ),
+                            value,
+                            &annotated_type,
+                            &value_type,
+                        ));
+                    }
+                    if !matches!(annotated_type, ZyraType::Unknown) {
+                        value_type = annotated_type;
+                    }
+                }
+
+                if matches!(stmt, Statement::Static { .. }) {
+                    self.statics.insert(name.clone(), value_type);
+                } else {
+                    self.constants.insert(name.clone(), value_type);
+                }
+                self.current_module = outer_module;
+                Ok(ZyraType::Void)
+            }
+
             Statement::Expression { expr, .. } => self.analyze_expression(expr),
 
             Statement::Import {
@@ -1247,7 +2234,8 @@ impl SemanticAnalyzer {
                         Ok(ZyraType::Void)
                     }
                     "game" | "math" | "io" | "time" | "fs" | "env" | "process" | "thread"
-                    | "mem" | "string" | "core" => {
+                    | "mem" | "string" | "core" | "ai" | "i18n" | "serde" | "json" | "random"
+                    | "net" | "audio" | "cli" => {
                         // Legacy single-word modules - convert to std:: form
                         let module_name = format!("std::{}", root);
                         self.imported_std_modules.insert(module_name.clone());
@@ -1268,23 +2256,39 @@ impl SemanticAnalyzer {
 
             Statement::Return { value, span } => {
                 let return_type = if let Some(expr) = value {
-                    self.analyze_expression(expr)?
+                    let expected = self
+                        .current_function
+                        .as_ref()
+                        .filter(|_| self.closure_depth == 0)
+                        .and_then(|func_name| self.functions.get(func_name))
+                        .map(|sig| sig.return_type.clone());
+                    self.analyze_with_hint(expr, expected)?
                 } else {
                     ZyraType::Void
                 };
 
                 // Check against function return type
-                if let Some(ref func_name) = self.current_function {
+                if let Some(func_name) = self
+                    .current_function
+                    .as_ref()
+                    .filter(|_| self.closure_depth == 0)
+                {
                     if let Some(sig) = self.functions.get(func_name) {
                         if !sig.return_type.is_compatible(&return_type) {
-                            return Err(ZyraError::type_error(
+                            let err = ZyraError::type_error(
                                 &format!(
                                     "Return type mismatch: expected {}, found {}",
                                     sig.return_type.display_name(),
                                     return_type.display_name()
                                 ),
-                                Some(SourceLocation::new("", span.line, span.column)),
-                            ));
+                                Some(SourceLocation::from_span(span)),
+                            );
+                            return Err(match value {
+                                Some(expr) => {
+                                    Self::with_cast_fix(err, expr, &sig.return_type, &return_type)
+                                }
+                                None => err,
+                            });
                         }
                     }
                 }
@@ -1303,7 +2307,7 @@ impl SemanticAnalyzer {
                 if !matches!(cond_type, ZyraType::Bool | ZyraType::Unknown) {
                     return Err(ZyraError::type_error(
                         &format!("Condition must be Bool, found {}", cond_type.display_name()),
-                        Some(SourceLocation::new("", span.line, span.column)),
+                        Some(SourceLocation::from_span(span)),
                     ));
                 }
 
@@ -1323,6 +2327,7 @@ impl SemanticAnalyzer {
             }
 
             Statement::While {
+                label,
                 condition,
                 body,
                 span,
@@ -1335,22 +2340,94 @@ impl SemanticAnalyzer {
                             "While condition must be Bool, found {}",
                             cond_type.display_name()
                         ),
-                        Some(SourceLocation::new("", span.line, span.column)),
+                        Some(SourceLocation::from_span(span)),
                     ));
                 }
 
+                self.loop_labels.push(label.clone());
+                self.enter_scope();
+                self.analyze_block(body)?;
+                self.exit_scope();
+                self.loop_labels.pop();
+
+                Ok(ZyraType::Void)
+            }
+
+            Statement::IfLet {
+                pattern,
+                value,
+                then_block,
+                else_block,
+                ..
+            } => {
+                let value_type = self.analyze_expression(value)?;
+
+                // Pattern bindings are only visible in the then block
+                self.enter_scope();
+                self.analyze_pattern_bindings(pattern, &value_type)?;
+                self.analyze_block(then_block)?;
+                self.exit_scope();
+
+                if let Some(else_blk) = else_block {
+                    self.enter_scope();
+                    self.analyze_block(else_blk)?;
+                    self.exit_scope();
+                }
+
+                Ok(ZyraType::Void)
+            }
+
+            Statement::WhileLet {
+                label,
+                pattern,
+                value,
+                body,
+                ..
+            } => {
+                let value_type = self.analyze_expression(value)?;
+
+                self.loop_labels.push(label.clone());
                 self.enter_scope();
+                self.analyze_pattern_bindings(pattern, &value_type)?;
                 self.analyze_block(body)?;
                 self.exit_scope();
+                self.loop_labels.pop();
+
+                Ok(ZyraType::Void)
+            }
 
+            Statement::Break { label, span } | Statement::Continue { label, span } => {
+                let keyword = if matches!(stmt, Statement::Break { .. }) {
+                    "break"
+                } else {
+                    "continue"
+                };
+                let found = match label {
+                    Some(name) => self.loop_labels.iter().any(|l| l.as_ref() == Some(name)),
+                    None => !self.loop_labels.is_empty(),
+                };
+                if !found {
+                    let message = match label {
+                        Some(name) => format!("Use of undeclared loop label '{}", name),
+                        None => format!("'{}' outside of a loop", keyword),
+                    };
+                    return Err(ZyraError::new(
+                        "CompileError",
+                        &message,
+                        Some(SourceLocation::from_span(span)),
+                    ));
+                }
                 Ok(ZyraType::Void)
             }
 
             Statement::For {
+                label,
                 variable,
                 start,
                 end,
                 inclusive: _,
+                step,
+                reverse: _,
                 body,
                 span,
             } => {
@@ -1366,7 +2443,7 @@ impl SemanticAnalyzer {
                             "For loop start must be Int, found {}",
                             start_type.display_name()
                         ),
-                        Some(SourceLocation::new("", span.line, span.column)),
+                        Some(SourceLocation::from_span(span)),
                     ));
                 }
 
@@ -1376,11 +2453,32 @@ impl SemanticAnalyzer {
                             "For loop end must be Int, found {}",
                             end_type.display_name()
                         ),
-                        Some(SourceLocation::new("", span.line, span.column)),
+                        Some(SourceLocation::from_span(span)),
                     ));
                 }
 
+                if let Some(step) = step {
+                    let step_type = self.analyze_expression(step)?;
+                    let location = Some(SourceLocation::from_span(span));
+                    if !matches!(step_type, ZyraType::I32 | ZyraType::I64 | ZyraType::Unknown) {
+                        return Err(ZyraError::type_error(
+                            &format!(
+                                "For loop step must be Int, found {}",
+                                step_type.display_name()
+                            ),
+                            location,
+                        ));
+                    }
+                    if step.constant_int() == Some(0) {
+                        return Err(ZyraError::type_error("For loop step can't be 0", location)
+                            .with_suggestion(
+                                "Use a positive step to count up, or a negative one to count down",
+                            ));
+                    }
+                }
+
                 // Loop variable is in body scope
+                self.check_not_global(variable, span)?;
                 self.enter_scope();
                 self.symbols.insert(
                     variable.clone(),
@@ -1394,11 +2492,16 @@ impl SemanticAnalyzer {
                         decl_line: span.line,
                     },
                 );
-                self.ownership
-                    .define(variable, false, span.line)
-                    .map_err(|e| self.ownership_error_to_zyra(e))?;
-
+                timed!(
+                    self,
+                    Ownership,
+                    self.ownership.define(variable, false, span.line)
+                )
+                .map_err(|e| self.ownership_error_to_zyra(e))?;
+
+                self.loop_labels.push(label.clone());
                 self.analyze_block(body)?;
+                self.loop_labels.pop();
                 self.exit_scope();
 
                 Ok(ZyraType::Void)
@@ -1443,15 +2546,91 @@ impl SemanticAnalyzer {
             }
 
             Statement::Impl {
-                target_type: _target_type,
-                trait_name: _trait_name,
+                target_type,
+                trait_name,
                 methods,
-                span: _span,
+                span,
             } => {
+                // Trait impls must match the trait: no unknown methods, same arity,
+                // and every method without a default body implemented
+                if let Some(trait_n) = trait_name {
+                    let trait_methods = match self.types.get(trait_n) {
+                        Some(TypeDef::Trait { methods, .. }) => methods.clone(),
+                        _ => {
+                            return Err(ZyraError::type_error(
+                                &format!("Trait '{}' is not defined", trait_n),
+                                Some(SourceLocation::from_span(span)),
+                            ))
+                        }
+                    };
+                    for method in methods {
+                        if let Statement::Function {
+                            name, params, span, ..
+                        } = method.as_ref()
+                        {
+                            let location = Some(SourceLocation::from_span(span));
+                            match trait_methods.iter().find(|(n, _, _)| n == name) {
+                                None => {
+                                    return Err(ZyraError::type_error(
+                                        &format!(
+                                            "Method '{}' is not a member of trait '{}'",
+                                            name, trait_n
+                                        ),
+                                        location,
+                                    ))
+                                }
+                                Some((_, count, _)) if *count != params.len() => {
+                                    return Err(ZyraError::type_error(
+                                        &format!(
+                                            "Method '{}' takes {} parameter(s) but trait '{}' declares {}",
+                                            name,
+                                            params.len(),
+                                            trait_n,
+                                            count
+                                        ),
+                                        location,
+                                    ))
+                                }
+                                Some(_) => {}
+                            }
+                        }
+                    }
+                    let missing: Vec<&str> = trait_methods
+                        .iter()
+                        .filter(|(name, _, has_default)| {
+                            !has_default
+                                && !methods.iter().any(|m| {
+                                    matches!(m.as_ref(), Statement::Function { name: n, .. } if n == name)
+                                })
+                        })
+                        .map(|(name, _, _)| name.as_str())
+                        .collect();
+                    if !missing.is_empty() {
+                        return Err(ZyraError::type_error(
+                            &format!(
+                                "impl {} for {} is missing trait method(s): {}",
+                                trait_n,
+                                target_type,
+                                missing.join(", ")
+                            ),
+                            Some(SourceLocation::from_span(span)),
+                        ));
+                    }
+                }
+
+                // Methods of a module's type can use the module's private items
+                let outer_module = self.current_module.clone();
+                if !self.types.contains_key(target_type) {
+                    if let Some(item) = self.imported.find_type(target_type) {
+                        self.current_module = Some(item.module.clone());
+                    }
+                }
+
                 // Analyze impl methods
                 for method in methods {
                     self.analyze_statement(method)?;
                 }
+                self.current_module = outer_module;
                 Ok(ZyraType::Void)
             }
 
@@ -1460,8 +2639,8 @@ impl SemanticAnalyzer {
                 methods,
                 span: _span,
             } => {
-                // Register trait in type system
-                // Validate method signatures
+                // Signatures are registered in collect_type_definitions;
+                // default bodies are checked like ordinary methods
                 for method in methods {
                     for param in &method.params {
                         let _ = ZyraType::from_ast_type(&param.param_type);
@@ -1469,6 +2648,16 @@ impl SemanticAnalyzer {
                     if let Some(ref ret) = method.return_type {
                         let _ = ZyraType::from_ast_type(ret);
                     }
+                    if let Some(body) = &method.default_impl {
+                        self.analyze_statement(&Statement::Function {
+                            name: method.name.clone(),
+                            lifetimes: Vec::new(),
+                            params: method.params.clone(),
+                            return_type: method.return_type.clone(),
+                            body: body.clone(),
+                            span: method.span,
+                        })?;
+                    }
                 }
                 Ok(ZyraType::Void)
             }
@@ -1476,36 +2665,98 @@ impl SemanticAnalyzer {
     }
 
     fn analyze_block(&mut self, block: &Block) -> ZyraResult<ZyraType> {
+        self.analyze_block_with_hint(block, None)
+    }
+
+    /// Analyze a block whose value has a type its context wants, which the
+    /// block's trailing expression takes as its literal hint
+    fn analyze_block_with_hint(
+        &mut self,
+        block: &Block,
+        hint: Option<ZyraType>,
+    ) -> ZyraResult<ZyraType> {
         for stmt in &block.statements {
-            self.analyze_statement(stmt)?;
+            let checkpoint = self.checkpoint();
+            if let Err(error) = self.analyze_statement(stmt) {
+                // Check the statements after it too, with a failed `let`
+                // still declaring its name so its uses aren't errors as well
+                self.unwind_to(checkpoint);
+                self.record_error(error)?;
+                if let Statement::Let {
+                    name,
+                    mutable,
+                    type_annotation,
+                    span,
+                    ..
+                } = stmt
+                {
+                    self.declare_failed_binding(
+                        name,
+                        *mutable,
+                        type_annotation.as_ref(),
+                        span.line,
+                    );
+                }
+            }
         }
 
         if let Some(ref expr) = block.expression {
-            self.analyze_expression(expr)
+            self.analyze_with_hint(expr, hint)
         } else {
             Ok(ZyraType::Void)
         }
     }
 
     fn analyze_expression(&mut self, expr: &Expression) -> ZyraResult<ZyraType> {
+        let hint = self.literal_hint.take();
         match expr {
-            Expression::Int { .. } => Ok(ZyraType::I32), // Default integer literals to i32 (memory efficient)
-            Expression::Float { .. } => Ok(ZyraType::F32), // Default float literals to f32 (memory efficient)
+            Expression::Int {
+                value,
+                suffix,
+                span,
+            } => Self::int_literal_type(*value as i128, suffix.as_ref(), hint, *span),
+            // Float literals default to f32 (memory efficient) unless the context wants f64
+            Expression::Float { .. } => {
+                Ok(hint.filter(ZyraType::is_float).unwrap_or(ZyraType::F32))
+            }
             Expression::Bool { .. } => Ok(ZyraType::Bool),
             Expression::Char { .. } => Ok(ZyraType::Char),
             Expression::String { .. } => Ok(ZyraType::String),
 
             Expression::Identifier { name, span } => {
+                if name == "None" && !self.symbols.contains_key(name) {
+                    return Ok(ZyraType::Option(Box::new(ZyraType::Unknown)));
+                }
+
+                // A bare struct name is a type value (e.g. the schema in validate(data, Player))
+                if !self.symbols.contains_key(name)
+                    && matches!(self.types.get(name), Some(TypeDef::Struct { .. }))
+                {
+                    return Ok(ZyraType::String);
+                }
+
+                // Constants are replaced by their value, so nothing owns or borrows them
+                if let Some(constant_type) = self.constant_type(name).cloned() {
+                    self.check_visibility(name, false, *span)?;
+                    return Ok(constant_type);
+                }
+                // Globals live in the VM's globals table, outside ownership too
+                if let Some(static_type) = self.static_type(name).cloned() {
+                    self.note_static_use(name, span);
+                    return Ok(static_type);
+                }
+
                 // Check ownership
-                self.ownership
-                    .use_binding(name, span.line)
+                timed!(self, Ownership, self.ownership.use_binding(name, span.line))
                     .map_err(|e| self.ownership_error_to_zyra(e))?;
 
                 // Check borrow checker for use-after-move
-                if let Err(borrow_err) = self.borrow_checker.can_use(name, span.line) {
-                    return Err(ZyraError::ownership_error(
-                        &format!("{}", borrow_err),
-                        Some(SourceLocation::new("", span.line, span.column)),
+                if let Err(borrow_err) =
+                    timed!(self, Borrow, self.borrow_checker.can_use(name, span.line))
+                {
+                    return Err(Self::borrow_error_to_zyra(
+                        borrow_err,
+                        Some(SourceLocation::from_span(span)),
                     ));
                 }
 
@@ -1524,8 +2775,7 @@ impl SemanticAnalyzer {
                 right,
                 span,
             } => {
-                let left_type = self.analyze_expression(left)?;
-                let right_type = self.analyze_expression(right)?;
+                let (left_type, right_type) = self.analyze_operands(operator, left, right, hint)?;
 
                 match operator {
                     BinaryOp::Add
@@ -1537,24 +2787,52 @@ impl SemanticAnalyzer {
                             // STRICT TYPE CHECKING: Types must match exactly
                             // No implicit type promotion - use explicit `as` cast
                             if left_type != right_type {
-                                return Err(ZyraError::type_error(
-                                    &format!(
-                                        "Cannot apply '{}' between {} and {} - types must match. Use explicit cast: `value as {}`",
-                                        operator.as_str(),
-                                        left_type.display_name(),
-                                        right_type.display_name(),
-                                        left_type.display_name()
-                                    ),
-                                    Some(SourceLocation::new("", span.line, span.column)),
+                                return Err(Self::operand_mismatch(
+                                    operator,
+                                    (left, &left_type),
+                                    (right, &right_type),
+                                    *span,
                                 ));
                             }
 
+                            if let (BinaryOp::Divide, Some(a), Some(b)) =
+                                (operator, left.constant_int(), right.constant_int())
+                            {
+                                if b != 0 && a % b != 0 {
+                                    self.warnings.push(
+                                        Warning::new(
+                                            "int_division",
+                                            &format!(
+                                                "`{} / {}` is integer division and gives {}",
+                                                a,
+                                                b,
+                                                a / b
+                                            ),
+                                            Some(SourceLocation::from_span(span)),
+                                        )
+                                        .with_suggestion(
+                                            &format!(
+                                                "Write `{}.0 / {}.0` for {}",
+                                                a,
+                                                b,
+                                                a as f64 / b as f64
+                                            ),
+                                        ),
+                                    );
+                                }
+                            }
+
                             // Return the same type
                             Ok(left_type)
                         } else if matches!(operator, BinaryOp::Add)
                             && matches!(left_type, ZyraType::String)
                         {
                             Ok(ZyraType::String)
+                        } else if let Some(vector_type) =
+                            Self::vector_arith_type(operator, &left_type, &right_type)
+                        {
+                            // Built-in Vec2/Vec3 arithmetic: v + v, v - v, v * s, s * v, v / s
+                            Ok(vector_type)
                         } else if matches!(left_type, ZyraType::Unknown)
                             || matches!(right_type, ZyraType::Unknown)
                         {
@@ -1567,27 +2845,83 @@ impl SemanticAnalyzer {
                                     left_type.display_name(),
                                     right_type.display_name()
                                 ),
-                                Some(SourceLocation::new("", span.line, span.column)),
+                                Some(SourceLocation::from_span(span)),
                             ))
                         }
                     }
-                    BinaryOp::Equal
-                    | BinaryOp::NotEqual
-                    | BinaryOp::Less
+                    BinaryOp::Equal | BinaryOp::NotEqual => {
+                        // Rounding makes exact float comparison unreliable: 0.1 + 0.2 != 0.3
+                        if left_type.is_float() && right_type.is_float() {
+                            self.warnings.push(
+                                Warning::new(
+                                    "float_eq",
+                                    &format!(
+                                        "'{}' compares {} values exactly, but rounding errors make them rarely equal",
+                                        operator.as_str(),
+                                        left_type.display_name()
+                                    ),
+                                    Some(SourceLocation::from_span(span)),
+                                )
+                                .with_suggestion("Compare with a tolerance: `abs(a - b) < 0.0001`"),
+                            );
+                        }
+                        Ok(ZyraType::Bool)
+                    }
+                    BinaryOp::Less
                     | BinaryOp::LessEqual
                     | BinaryOp::Greater
                     | BinaryOp::GreaterEqual => Ok(ZyraType::Bool),
+                    BinaryOp::BitAnd
+                    | BinaryOp::BitOr
+                    | BinaryOp::BitXor
+                    | BinaryOp::ShiftLeft
+                    | BinaryOp::ShiftRight => {
+                        for (side, operand_type) in [("Left", &left_type), ("Right", &right_type)] {
+                            if !operand_type.is_integer()
+                                && !matches!(operand_type, ZyraType::Unknown)
+                            {
+                                return Err(ZyraError::type_error(
+                                    &format!(
+                                        "{} side of '{}' must be an integer, found {}",
+                                        side,
+                                        operator.as_str(),
+                                        operand_type.display_name()
+                                    ),
+                                    Some(SourceLocation::from_span(span)),
+                                ));
+                            }
+                        }
+                        // A shift amount may be any integer; `&`, `|` and `^` need matching types
+                        let shift = matches!(operator, BinaryOp::ShiftLeft | BinaryOp::ShiftRight);
+                        if !shift
+                            && left_type != right_type
+                            && left_type.is_integer()
+                            && right_type.is_integer()
+                        {
+                            return Err(Self::operand_mismatch(
+                                operator,
+                                (left, &left_type),
+                                (right, &right_type),
+                                *span,
+                            ));
+                        }
+                        if shift || !matches!(left_type, ZyraType::Unknown) {
+                            Ok(left_type)
+                        } else {
+                            Ok(right_type)
+                        }
+                    }
                     BinaryOp::And | BinaryOp::Or => {
                         if !matches!(left_type, ZyraType::Bool | ZyraType::Unknown) {
                             return Err(ZyraError::type_error(
                                 &format!("Left side of '{}' must be Bool", operator.as_str()),
-                                Some(SourceLocation::new("", span.line, span.column)),
+                                Some(SourceLocation::from_span(span)),
                             ));
                         }
                         if !matches!(right_type, ZyraType::Bool | ZyraType::Unknown) {
                             return Err(ZyraError::type_error(
                                 &format!("Right side of '{}' must be Bool", operator.as_str()),
-                                Some(SourceLocation::new("", span.line, span.column)),
+                                Some(SourceLocation::from_span(span)),
                             ));
                         }
                         Ok(ZyraType::Bool)
@@ -1600,26 +2934,38 @@ impl SemanticAnalyzer {
                 operand,
                 span,
             } => {
-                let operand_type = self.analyze_expression(operand)?;
+                // `-128i8` is in range even though `128i8` is not
+                if let (UnaryOp::Negate, Expression::Int { value, suffix, .. }) =
+                    (operator, operand.as_ref())
+                {
+                    return Self::int_literal_type(-(*value as i128), suffix.as_ref(), hint, *span);
+                }
+                let operand_type = self.analyze_with_hint(operand, hint)?;
 
                 match operator {
                     UnaryOp::Negate => {
-                        if operand_type.is_numeric() || matches!(operand_type, ZyraType::Unknown) {
+                        if operand_type.is_numeric()
+                            || matches!(operand_type, ZyraType::Unknown)
+                            || operand_type.is_vector_type()
+                        {
                             Ok(operand_type)
                         } else {
                             Err(ZyraError::type_error(
                                 &format!("Cannot negate {}", operand_type.display_name()),
-                                Some(SourceLocation::new("", span.line, span.column)),
+                                Some(SourceLocation::from_span(span)),
                             ))
                         }
                     }
                     UnaryOp::Not => {
                         if matches!(operand_type, ZyraType::Bool | ZyraType::Unknown) {
                             Ok(ZyraType::Bool)
+                        } else if operand_type.is_integer() {
+                            // Bitwise NOT, like in Rust
+                            Ok(operand_type)
                         } else {
                             Err(ZyraError::type_error(
                                 &format!("Cannot apply '!' to {}", operand_type.display_name()),
-                                Some(SourceLocation::new("", span.line, span.column)),
+                                Some(SourceLocation::from_span(span)),
                             ))
                         }
                     }
@@ -1628,20 +2974,44 @@ impl SemanticAnalyzer {
 
             Expression::Assignment {
                 target,
+                operator,
                 value,
                 span,
             } => {
                 // Check target is assignable
                 if let Expression::Identifier { name, .. } = target.as_ref() {
-                    self.ownership
-                        .assign(name, span.line)
+                    if self.constant_type(name).is_some() {
+                        return Err(ZyraError::ownership_error(
+                            &format!("Cannot assign to constant '{}'", name),
+                            Some(SourceLocation::from_span(span)),
+                        )
+                        .with_suggestion(&format!(
+                            "Declare a variable with 'let mut' and start it from {}",
+                            name
+                        )));
+                    }
+                    if let Some(static_type) = self.static_type(name).cloned() {
+                        return self.analyze_static_assignment(
+                            name,
+                            static_type,
+                            target,
+                            *operator,
+                            value,
+                            span,
+                        );
+                    }
+                    timed!(self, Ownership, self.ownership.assign(name, span.line))
                         .map_err(|e| self.ownership_error_to_zyra(e))?;
 
                     // Check borrow checker for mutate-while-borrowed
-                    if let Err(borrow_err) = self.borrow_checker.can_mutate(name, span.line) {
-                        return Err(ZyraError::ownership_error(
-                            &format!("{}", borrow_err),
-                            Some(SourceLocation::new("", span.line, span.column)),
+                    if let Err(borrow_err) = timed!(
+                        self,
+                        Borrow,
+                        self.borrow_checker.can_mutate(name, span.line)
+                    ) {
+                        return Err(Self::borrow_error_to_zyra(
+                            borrow_err,
+                            Some(SourceLocation::from_span(span)),
                         ));
                     }
                 } else if let Expression::FieldAccess { object, field, .. } = target.as_ref() {
@@ -1656,7 +3026,7 @@ impl SemanticAnalyzer {
                                             "Cannot mutate field '{}' through immutable &self. Use &mut self instead",
                                             field
                                         ),
-                                        Some(SourceLocation::new("", span.line, span.column)),
+                                        Some(SourceLocation::from_span(span)),
                                     ));
                                 }
                             }
@@ -1669,16 +3039,36 @@ impl SemanticAnalyzer {
                                             "Cannot mutate field '{}' of immutable variable '{}'",
                                             field, name
                                         ),
-                                        Some(SourceLocation::new("", span.line, span.column)),
+                                        Some(SourceLocation::from_span(span)),
                                     ));
                                 }
                             }
                         }
                     }
+                } else if matches!(target.as_ref(), Expression::Index { .. }) && operator.is_none()
+                {
+                    // Bounds-checks constant indices into fixed-size arrays
+                    self.analyze_expression(target)?;
                 }
 
-                let value_type = self.analyze_expression(value)?;
-                Ok(value_type)
+                // `a += b` reads `a` and is typed as `a + b`
+                match operator {
+                    Some(op) => self.analyze_expression(&Expression::Binary {
+                        left: target.clone(),
+                        operator: *op,
+                        right: value.clone(),
+                        span: *span,
+                    }),
+                    None => {
+                        let target_type = match target.as_ref() {
+                            Expression::Identifier { name, .. } => {
+                                self.symbols.get(name).map(|sym| sym.symbol_type.clone())
+                            }
+                            _ => None,
+                        };
+                        self.analyze_with_hint(value, target_type)
+                    }
+                }
             }
 
             Expression::Call {
@@ -1689,8 +3079,25 @@ impl SemanticAnalyzer {
                 // Get function name from callee
                 // For method calls (obj.method), we use the RECEIVER TYPE name, not variable name
                 // Also track receiver variable for &mut self borrow checking
-                let (func_name, receiver_var_for_borrow) = match callee.as_ref() {
-                    Expression::Identifier { name, .. } => (name.clone(), None),
+                let (func_name, receiver_var_for_borrow, is_method) = match callee.as_ref() {
+                    // A variable holding a closure (a local or an `Fn` parameter)
+                    Expression::Identifier { name, .. }
+                        if self.symbols.contains_key(name)
+                            && !self.functions.contains_key(name) =>
+                    {
+                        let callee_type = self.analyze_expression(callee)?;
+                        return self.analyze_closure_call(name, &callee_type, arguments, *span);
+                    }
+                    Expression::Identifier { name, .. } => {
+                        self.check_visibility(name, false, *span)?;
+                        // Inside a module, bare names call the module's own functions
+                        let sibling = self
+                            .current_module
+                            .as_ref()
+                            .filter(|_| !name.contains("::"))
+                            .and_then(|module| self.imported.sibling(module, name));
+                        (sibling.unwrap_or_else(|| name.clone()), None, false)
+                    }
                     Expression::FieldAccess { object, field, .. } => {
                         // Analyze the object to get its type and track it
                         let receiver_type = self.analyze_and_track(object)?;
@@ -1703,6 +3110,29 @@ impl SemanticAnalyzer {
                                 None
                             };
 
+                        if let ZyraType::Vec(elem) = &receiver_type {
+                            return self.analyze_vec_method(elem, object, field, arguments, *span);
+                        }
+                        if let ZyraType::Map { key, value } = &receiver_type {
+                            return self
+                                .analyze_map_method(key, value, object, field, arguments, *span);
+                        }
+                        if let ZyraType::Set(elem) = &receiver_type {
+                            return self.analyze_set_method(elem, object, field, arguments, *span);
+                        }
+                        if matches!(receiver_type, ZyraType::String) {
+                            return self.analyze_string_method(field, arguments, *span);
+                        }
+
+                        if let ZyraType::Struct(type_name) = &receiver_type {
+                            if let Some(result) =
+                                self.analyze_builtin_method(type_name, field, arguments, *span)
+                            {
+                                return result;
+                            }
+                        }
+                        self.check_method_exists(&receiver_type, field, *span)?;
+
                         // Use the type name for method resolution (enables type-aware DCE)
                         let func_name = match &receiver_type {
                             ZyraType::Struct(type_name) => {
@@ -1711,19 +3141,38 @@ impl SemanticAnalyzer {
                             ZyraType::Enum(type_name) => {
                                 format!("{}::{}", type_name, field)
                             }
-                            _ => {
-                                // Static call or unknown type - try variable name as Type name
-                                if let Expression::Identifier { name, .. } = object.as_ref() {
+                            // Static call: the name is a type or module, not a value
+                            _ => match object.as_ref() {
+                                Expression::Identifier { name, .. }
+                                    if !self.symbols.contains_key(name) =>
+                                {
                                     format!("{}::{}", name, field)
-                                } else {
-                                    // Method call on complex expression
-                                    field.clone()
                                 }
-                            }
+                                // A method on a value of unknown type, such as the
+                                // result of another call: it is resolved at runtime and
+                                // must not be mistaken for a free function of that name
+                                _ => {
+                                    for arg in arguments {
+                                        self.analyze_expression(arg)?;
+                                    }
+                                    return Ok(ZyraType::Unknown);
+                                }
+                            },
                         };
-                        (func_name, receiver_var)
+                        let is_method =
+                            matches!(receiver_type, ZyraType::Struct(_) | ZyraType::Enum(_));
+                        (func_name, receiver_var, is_method)
+                    }
+                    // Calling the result of an expression: make_adder(1)(2)
+                    _ => {
+                        let callee_type = self.analyze_expression(callee)?;
+                        return self.analyze_closure_call(
+                            "closure",
+                            &callee_type,
+                            arguments,
+                            *span,
+                        );
                     }
-                    _ => return Ok(ZyraType::Unknown),
                 };
 
                 // *** MODULE ALIAS EXPANSION ***
@@ -1744,20 +3193,29 @@ impl SemanticAnalyzer {
                     func_name
                 };
 
+                if let Some(caller) = &self.current_function {
+                    self.callers
+                        .entry(func_name.clone())
+                        .or_default()
+                        .insert(caller.clone());
+                }
+
                 // *** MAIN FUNCTION PROTECTION ***
                 // main() is the program entry point and cannot be called directly
                 if func_name == "main" {
                     return Err(ZyraError::new(
                         "SemanticError",
                         "Cannot call 'main' - it is the program entry point",
-                        Some(SourceLocation::new("", span.line, span.column)),
+                        Some(SourceLocation::from_span(span)),
                     ));
                 }
 
                 // *** STDLIB IMPORT ENFORCEMENT ***
-                // Check if this is a stdlib function that requires import
+                // Check if this is a stdlib function that requires import;
+                // the program's own functions shadow library names
                 if self.is_stdlib_function(&func_name)
                     && !self.is_stdlib_function_available(&func_name)
+                    && !self.is_user_function(&func_name)
                 {
                     let module = self
                         .get_stdlib_module_for_function(&func_name)
@@ -1768,94 +3226,74 @@ impl SemanticAnalyzer {
                             "Function '{}' requires import. Add: import {};",
                             func_name, module
                         ),
-                        Some(SourceLocation::new("", span.line, span.column)),
+                        Some(SourceLocation::from_span(span)),
                     ));
                 }
 
-                // Check argument types
-                let mut arg_types = Vec::new();
-                for arg in arguments {
-                    arg_types.push(self.analyze_expression(arg)?);
-                }
-
-                // *** COMPILE-TIME ICON FORMAT CHECK ***
-                // On Windows, set_window_icon requires .ico files
-                #[cfg(target_os = "windows")]
+                // *** COMPILE-TIME SCHEMA CHECK ***
+                // serde::validate(value, Schema) needs a struct from the type registry
+                if func_name.rsplit("::").next() == Some("validate")
+                    && self.imported_std_items.get(&func_name).map(String::as_str)
+                        == Some("std::serde")
                 {
-                    let short_name = if let Some(idx) = func_name.rfind("::") {
-                        &func_name[idx + 2..]
-                    } else {
-                        &func_name
-                    };
-
-                    if short_name == "set_window_icon" || short_name == "set_icon" {
-                        // Check if first argument is a string literal
-                        if let Some(first_arg) = arguments.first() {
-                            if let Expression::String { value: path, .. } = first_arg {
-                                // Check 1: Must be .ico on Windows
-                                if !path.to_lowercase().ends_with(".ico") {
-                                    return Err(ZyraError::new(
-                                        "TypeError",
-                                        &format!(
-                                            "set_window_icon requires .ico file on Windows. Got: '{}'. \
-                                             Please convert your icon to .ico format.",
-                                            path
-                                        ),
-                                        Some(SourceLocation::new("", span.line, span.column)),
-                                    ));
-                                }
-
-                                // Check 2: File must exist
-                                if !std::path::Path::new(path).exists() {
-                                    return Err(ZyraError::new(
-                                        "FileError",
-                                        &format!(
-                                            "Icon file not found: '{}'. \
-                                             Please ensure the file exists at the specified path.",
-                                            path
-                                        ),
-                                        Some(SourceLocation::new("", span.line, span.column)),
-                                    ));
-                                }
-                            }
+                    if let Some(Expression::Identifier { name, span }) = arguments.get(1) {
+                        if !matches!(self.types.get(name), Some(TypeDef::Struct { .. })) {
+                            return Err(ZyraError::type_error(
+                                &format!(
+                                    "validate expects a struct type as schema, found '{}'",
+                                    name
+                                ),
+                                Some(SourceLocation::from_span(span)),
+                            ));
                         }
                     }
                 }
 
-                // *** COMPILE-TIME ICON FILE CHECK (non-Windows) ***
-                // On non-Windows, just check if file exists
-                #[cfg(not(target_os = "windows"))]
+                // *** MEMORY INTRINSICS ***
+                // std::mem drop/take/swap act on variables, not just values
+                if matches!(callee.as_ref(), Expression::Identifier { .. })
+                    && self.imported_std_items.get(&func_name).map(String::as_str)
+                        == Some("std::mem")
                 {
-                    let short_name = if let Some(idx) = func_name.rfind("::") {
-                        &func_name[idx + 2..]
-                    } else {
-                        &func_name
-                    };
+                    let short_name = func_name.rsplit("::").next().unwrap_or(&func_name);
+                    if matches!(short_name, "drop" | "take" | "swap" | "weak") {
+                        let short_name = short_name.to_string();
+                        return self.analyze_mem_intrinsic(&short_name, arguments, *span);
+                    }
+                }
 
-                    if short_name == "set_window_icon" || short_name == "set_icon" {
-                        if let Some(first_arg) = arguments.first() {
-                            if let Expression::String { value: path, .. } = first_arg {
-                                if !std::path::Path::new(path).exists() {
-                                    return Err(ZyraError::new(
-                                        "FileError",
-                                        &format!(
-                                            "Icon file not found: '{}'. \
-                                             Please ensure the file exists at the specified path.",
-                                            path
-                                        ),
-                                        Some(SourceLocation::new("", span.line, span.column)),
-                                    ));
-                                }
-                            }
-                        }
+                // Check argument types; a closure passed for an `Fn` parameter
+                // takes its parameter types from it
+                let fn_params: Vec<ZyraType> = self
+                    .functions
+                    .get(&func_name)
+                    .map(|sig| sig.params.iter().map(|(_, ty)| ty.clone()).collect())
+                    .unwrap_or_default();
+                let mut arg_types = Vec::new();
+                for (i, arg) in arguments.iter().enumerate() {
+                    if let (Expression::Closure { .. }, Some(ZyraType::Closure { params, .. })) =
+                        (arg, fn_params.get(i))
+                    {
+                        self.closure_param_hints = Some(params.clone());
                     }
+                    let arg_type = self.analyze_with_hint(arg, fn_params.get(i).cloned());
+                    self.closure_param_hints = None;
+                    arg_types.push(arg_type?);
                 }
 
+                // *** COMPILE-TIME LITERAL ARGUMENT CHECKS ***
+                // Sprite paths, color strings, key names and the like
+                self.check_literal_args(&func_name, arguments)?;
+
                 // Look up function signature
                 // Try full name first (e.g., "paddle::move_up"), then short name (e.g., "move_up")
+                // A method is only looked up on its receiver's type: `p.step()` must not
+                // pick up a free function `step`
                 let sig_option = self.functions.get(&func_name).or_else(|| {
                     // If prefixed lookup fails, try just the function name (after ::)
-                    if let Some(idx) = func_name.rfind("::") {
+                    if is_method {
+                        None
+                    } else if let Some(idx) = func_name.rfind("::") {
                         let short_name = &func_name[idx + 2..];
                         self.functions.get(short_name)
                     } else {
@@ -1872,7 +3310,7 @@ impl SemanticAnalyzer {
                                 sig.params.len(),
                                 arguments.len()
                             ),
-                            Some(SourceLocation::new("", span.line, span.column)),
+                            Some(SourceLocation::from_span(span)),
                         ));
                     }
 
@@ -1881,17 +3319,21 @@ impl SemanticAnalyzer {
                     if sig.has_mut_self {
                         if let Some(ref receiver_var) = receiver_var_for_borrow {
                             // Check if receiver can be mutably borrowed (no active borrows)
-                            if let Err(borrow_err) = self.borrow_checker.borrow_mutable(
-                                receiver_var,
-                                &format!("&mut self in {}", func_name),
-                                span.line,
+                            if let Err(borrow_err) = timed!(
+                                self,
+                                Borrow,
+                                self.borrow_checker.borrow_mutable(
+                                    receiver_var,
+                                    &format!("&mut self in {}", func_name),
+                                    span.line,
+                                )
                             ) {
                                 return Err(ZyraError::ownership_error(
                                     &format!(
                                         "Cannot call method '{}' requiring &mut self: {}",
                                         func_name, borrow_err
                                     ),
-                                    Some(SourceLocation::new("", span.line, span.column)),
+                                    Some(SourceLocation::from_span(span)),
                                 ));
                             }
                         }
@@ -1902,19 +3344,24 @@ impl SemanticAnalyzer {
                         arg_types.iter().zip(sig.params.iter()).enumerate()
                     {
                         // param_type.is_compatible(arg_type) checks if param accepts arg (widening I32->I64)
-                        if !param_type.is_compatible(arg_type)
+                        if !self.type_accepts(param_type, arg_type)
                             && !matches!(arg_type, ZyraType::Unknown)
                             && !matches!(param_type, ZyraType::Unknown)
                         {
-                            return Err(ZyraError::type_error(
-                                &format!(
-                                    "Function '{}' argument {} expects {}, got {}",
-                                    func_name,
-                                    i + 1,
-                                    param_type.display_name(),
-                                    arg_type.display_name()
+                            return Err(Self::with_cast_fix(
+                                ZyraError::type_error(
+                                    &format!(
+                                        "Function '{}' argument {} expects {}, got {}",
+                                        func_name,
+                                        i + 1,
+                                        param_type.display_name(),
+                                        arg_type.display_name()
+                                    ),
+                                    Some(SourceLocation::from_span(span)),
                                 ),
-                                Some(SourceLocation::new("", span.line, span.column)),
+                                &arguments[i],
+                                param_type,
+                                arg_type,
                             ));
                         }
                     }
@@ -1944,14 +3391,42 @@ impl SemanticAnalyzer {
                                 if name != "self" && !is_copy {
                                     // Only Reference types trigger move
                                     // Mark as moved - subsequent use will error
-                                    let _ =
-                                        self.ownership.move_value(name, &func_name, arg_span.line);
+                                    let _ = timed!(
+                                        self,
+                                        Ownership,
+                                        self.ownership.move_value(name, &func_name, arg_span.line)
+                                    );
                                 }
                             }
                         }
                     }
 
+                    // shuffle and choice give back the elements they were given
+                    if self.imported_std_items.get(&func_name).map(String::as_str)
+                        == Some("std::random")
+                    {
+                        let short_name = func_name.rsplit("::").next().unwrap_or(&func_name);
+                        let items = match arg_types.first() {
+                            Some(ZyraType::Reference { inner, .. }) => Some(inner.as_ref()),
+                            other => other,
+                        };
+                        match (short_name, items) {
+                            (
+                                "shuffle",
+                                Some(items @ (ZyraType::Vec(_) | ZyraType::Array { .. })),
+                            ) => return Ok(items.clone()),
+                            (
+                                "choice",
+                                Some(ZyraType::Vec(elem) | ZyraType::Array { elem, .. }),
+                            ) => return Ok(ZyraType::Option(elem.clone())),
+                            _ => {}
+                        }
+                    }
+
                     Ok(sig.return_type.clone())
+                } else if func_name == "Some" && arg_types.len() == 1 {
+                    // Option constructor keeps its payload type for match checks
+                    Ok(ZyraType::Option(Box::new(arg_types[0].clone())))
                 } else {
                     // Allow unknown functions (for flexibility)
                     Ok(ZyraType::Unknown)
@@ -1987,22 +3462,84 @@ impl SemanticAnalyzer {
                 if !idx_type.is_integer() && !matches!(idx_type, ZyraType::Unknown) {
                     return Err(ZyraError::type_error(
                         &format!("Index must be integer, found {}", idx_type.display_name()),
-                        Some(SourceLocation::new("", span.line, span.column)),
+                        Some(SourceLocation::from_span(span)),
                     ));
                 }
 
                 match obj_type {
                     ZyraType::Vec(inner) => Ok(*inner),
-                    ZyraType::Array { elem, .. } => Ok(*elem),
-                    ZyraType::String => Ok(ZyraType::String),
+                    ZyraType::Array { elem, size } => {
+                        // A constant index into an array of known size is checked here
+                        match index.constant_int() {
+                            Some(i) if i < 0 || i as usize >= size => Err(ZyraError::type_error(
+                                &format!(
+                                    "Index {} is out of bounds for array of length {}",
+                                    i, size
+                                ),
+                                Some(SourceLocation::from_span(span)),
+                            )
+                            .with_suggestion(&format!(
+                                "Valid indices are 0 to {}",
+                                size.saturating_sub(1)
+                            ))),
+                            _ => Ok(*elem),
+                        }
+                    }
+                    // A string is indexed by character
+                    ZyraType::String => Ok(ZyraType::Char),
                     ZyraType::Unknown => Ok(ZyraType::Unknown),
                     _ => Err(ZyraError::type_error(
                         &format!("Cannot index {}", obj_type.display_name()),
-                        Some(SourceLocation::new("", span.line, span.column)),
+                        Some(SourceLocation::from_span(span)),
                     )),
                 }
             }
 
+            Expression::Tuple { elements, .. } => {
+                let hints = match hint {
+                    Some(ZyraType::Tuple(types)) if types.len() == elements.len() => types,
+                    _ => vec![ZyraType::Unknown; elements.len()],
+                };
+                let mut types = Vec::new();
+                for (element, hint) in elements.iter().zip(hints) {
+                    types.push(self.analyze_with_hint(element, Some(hint))?);
+                }
+                Ok(ZyraType::Tuple(types))
+            }
+
+            Expression::TupleIndex {
+                object,
+                index,
+                span,
+            } => match self.analyze_expression(object)? {
+                ZyraType::Tuple(mut types) => {
+                    if *index >= types.len() {
+                        return Err(ZyraError::type_error(
+                            &format!(
+                                "Index {} is out of bounds for a tuple of {} element(s)",
+                                index,
+                                types.len()
+                            ),
+                            Some(SourceLocation::from_span(span)),
+                        )
+                        .with_suggestion(&format!(
+                            "Valid indices are 0 to {}",
+                            types.len().saturating_sub(1)
+                        )));
+                    }
+                    Ok(types.swap_remove(*index))
+                }
+                ZyraType::Unknown => Ok(ZyraType::Unknown),
+                other => Err(ZyraError::type_error(
+                    &format!(
+                        "'.{}' reads a tuple element, but this is {}",
+                        index,
+                        other.display_name()
+                    ),
+                    Some(SourceLocation::from_span(span)),
+                )),
+            },
+
             Expression::List { elements, .. } => {
                 // Array literal [a, b, c] - fixed size, inferred as Array type
                 if elements.is_empty() {
@@ -2011,7 +3548,11 @@ impl SemanticAnalyzer {
                         size: 0,
                     })
                 } else {
-                    let first_type = self.analyze_expression(&elements[0])?;
+                    let elem_hint = match hint {
+                        Some(ZyraType::Array { elem, .. } | ZyraType::Vec(elem)) => Some(*elem),
+                        _ => None,
+                    };
+                    let first_type = self.analyze_with_hint(&elements[0], elem_hint)?;
                     Ok(ZyraType::Array {
                         elem: Box::new(first_type),
                         size: elements.len(),
@@ -2024,11 +3565,76 @@ impl SemanticAnalyzer {
                 if elements.is_empty() {
                     Ok(ZyraType::Vec(Box::new(ZyraType::Unknown)))
                 } else {
-                    let first_type = self.analyze_expression(&elements[0])?;
+                    let elem_hint = match hint {
+                        Some(ZyraType::Vec(elem)) => Some(*elem),
+                        _ => None,
+                    };
+                    let first_type = self.analyze_with_hint(&elements[0], elem_hint)?;
                     Ok(ZyraType::Vec(Box::new(first_type)))
                 }
             }
 
+            Expression::MapLiteral { entries, .. } => {
+                // Map literal map{k: v, ...} - key and value types come from the first entry
+                let mut key_type = ZyraType::Unknown;
+                let mut value_type = ZyraType::Unknown;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    let this_key = self.analyze_expression(key)?;
+                    let this_value = self.analyze_expression(value)?;
+                    let span = key.span();
+                    let location = Some(SourceLocation::from_span(&span));
+                    Self::check_hashable(&this_key, "map key", span)?;
+                    if i == 0 {
+                        key_type = this_key;
+                        value_type = this_value;
+                        continue;
+                    }
+                    for (kind, expected, found) in [
+                        ("key", &key_type, &this_key),
+                        ("value", &value_type, &this_value),
+                    ] {
+                        if !expected.is_compatible(found) {
+                            return Err(ZyraError::type_error(
+                                &format!(
+                                    "Map {} has type {}, but the first entry's is {}",
+                                    kind,
+                                    found.display_name(),
+                                    expected.display_name()
+                                ),
+                                location.clone(),
+                            ));
+                        }
+                    }
+                }
+                Ok(ZyraType::Map {
+                    key: Box::new(key_type),
+                    value: Box::new(value_type),
+                })
+            }
+
+            Expression::SetLiteral { elements, .. } => {
+                // Set literal set{a, b} - element type comes from the first element
+                let mut elem_type = ZyraType::Unknown;
+                for (i, elem) in elements.iter().enumerate() {
+                    let this_type = self.analyze_expression(elem)?;
+                    let span = elem.span();
+                    Self::check_hashable(&this_type, "set element", span)?;
+                    if i == 0 {
+                        elem_type = this_type;
+                    } else if !elem_type.is_compatible(&this_type) {
+                        return Err(ZyraError::type_error(
+                            &format!(
+                                "Set element has type {}, but the first element's is {}",
+                                this_type.display_name(),
+                                elem_type.display_name()
+                            ),
+                            Some(SourceLocation::from_span(&span)),
+                        ));
+                    }
+                }
+                Ok(ZyraType::Set(Box::new(elem_type)))
+            }
+
             Expression::Object { fields, .. } => {
                 let mut field_types = HashMap::new();
                 for (name, expr) in fields {
@@ -2045,14 +3651,38 @@ impl SemanticAnalyzer {
             } => {
                 // Check if we can borrow
                 if let Expression::Identifier { name, .. } = value.as_ref() {
-                    if *mutable {
-                        self.ownership
-                            .borrow_mut(name, "ref", span.line)
-                            .map_err(|e| self.ownership_error_to_zyra(e))?;
+                    if self.static_type(name).is_some() {
+                        return Err(ZyraError::ownership_error(
+                            &format!("Cannot borrow the global '{}'", name),
+                            Some(SourceLocation::from_span(span)),
+                        )
+                        .with_suggestion(&format!(
+                            "Copy it into a variable first: `let value = {};`",
+                            name
+                        )));
+                    }
+                    if self.constant_type(name).is_some() {
+                        // A constant is a fresh value at every use; only `&mut` is wrong
+                        if *mutable {
+                            return Err(ZyraError::ownership_error(
+                                &format!("Cannot borrow constant '{}' as mutable", name),
+                                Some(SourceLocation::from_span(span)),
+                            ));
+                        }
+                    } else if *mutable {
+                        timed!(
+                            self,
+                            Ownership,
+                            self.ownership.borrow_mut(name, "ref", span.line)
+                        )
+                        .map_err(|e| self.ownership_error_to_zyra(e))?;
                     } else {
-                        self.ownership
-                            .borrow(name, "ref", span.line)
-                            .map_err(|e| self.ownership_error_to_zyra(e))?;
+                        timed!(
+                            self,
+                            Ownership,
+                            self.ownership.borrow(name, "ref", span.line)
+                        )
+                        .map_err(|e| self.ownership_error_to_zyra(e))?;
                     }
                 }
 
@@ -2078,14 +3708,14 @@ impl SemanticAnalyzer {
                 Ok(ZyraType::Unknown) // Range type
             }
 
-            Expression::Grouped { inner, .. } => self.analyze_expression(inner),
+            Expression::Grouped { inner, .. } => self.analyze_with_hint(inner, hint),
 
             // If expression - analyze branches and determine type
             Expression::If {
                 condition,
                 then_block,
                 else_block,
-                ..
+                span,
             } => {
                 // Condition must be bool
                 let cond_type = self.analyze_expression(condition)?;
@@ -2095,16 +3725,27 @@ impl SemanticAnalyzer {
                 }
 
                 // Analyze then block
-                let then_type = self.analyze_block(then_block)?;
+                let then_type = self.analyze_block_with_hint(then_block, hint.clone())?;
 
                 // Analyze else block if present
                 if let Some(else_blk) = else_block {
-                    let else_type = self.analyze_block(else_blk)?;
-                    // Both branches should return compatible types
+                    let else_type = self.analyze_block_with_hint(else_blk, hint)?;
+                    // Both branches should return compatible types; a branch
+                    // giving no value (a statement, or one that returns) has
+                    // nothing to disagree with
                     if then_type.is_compatible(&else_type) {
                         Ok(then_type)
-                    } else {
+                    } else if then_type == ZyraType::Void || else_type == ZyraType::Void {
                         Ok(ZyraType::Unknown)
+                    } else {
+                        Err(ZyraError::type_error(
+                            &format!(
+                                "If branches have incompatible types: {} and {}",
+                                then_type.display_name(),
+                                else_type.display_name()
+                            ),
+                            Some(SourceLocation::from_span(span)),
+                        ))
                     }
                 } else {
                     Ok(ZyraType::Void)
@@ -2112,19 +3753,42 @@ impl SemanticAnalyzer {
             }
 
             // Struct instantiation: StructName { field: value, ... }
-            Expression::StructInit { name, fields, .. } => {
+            Expression::StructInit { name, fields, span } => {
+                self.check_visibility(name, true, *span)?;
                 // Analyze all field values
-                for (_, field_value) in fields {
-                    self.analyze_expression(field_value)?;
+                for (field_name, field_value) in fields {
+                    let field_type = match self.types.get(name) {
+                        Some(TypeDef::Struct { fields, .. }) => fields
+                            .iter()
+                            .find(|(declared, _)| declared == field_name)
+                            .map(|(_, ty)| ty.clone()),
+                        _ => None,
+                    };
+                    self.analyze_with_hint(field_value, field_type)?;
                 }
                 // Return the struct type
                 Ok(ZyraType::Struct(name.clone()))
             }
 
             // Enum variant: EnumName::Variant
+            // A module's constant, `util::MAX`
+            Expression::EnumVariant { span, .. }
+                if expr
+                    .qualified_name()
+                    .is_some_and(|name| self.constants.contains_key(&name)) =>
+            {
+                let name = expr.qualified_name().unwrap_or_default();
+                self.check_visibility(&name, false, *span)?;
+                Ok(self.constants[&name].clone())
+            }
+
             Expression::EnumVariant {
-                enum_name, data, ..
+                enum_name,
+                data,
+                span,
+                ..
             } => {
+                self.check_visibility(enum_name, true, *span)?;
                 // Analyze data if present
                 if let Some(data_expr) = data {
                     self.analyze_expression(data_expr)?;
@@ -2147,6 +3811,12 @@ impl SemanticAnalyzer {
 
                 // Analyze each arm
                 for arm in arms {
+                    // Enter new scope for pattern bindings
+                    self.enter_scope();
+
+                    // Introduce pattern bindings into scope (guards can use them)
+                    self.analyze_pattern_bindings(&arm.pattern, &scrutinee_type)?;
+
                     // Check guard purity if present
                     if let Some(ref guard) = arm.guard {
                         Self::check_guard_purity(guard)?;
@@ -2155,17 +3825,11 @@ impl SemanticAnalyzer {
                         if !matches!(guard_type, ZyraType::Bool | ZyraType::Unknown) {
                             return Err(ZyraError::type_error(
                                 "Match guard must be a boolean expression",
-                                Some(SourceLocation::new("", span.line, span.column)),
+                                Some(SourceLocation::from_span(span)),
                             ));
                         }
                     }
 
-                    // Enter new scope for pattern bindings
-                    self.enter_scope();
-
-                    // Introduce pattern bindings into scope
-                    self.analyze_pattern_bindings(&arm.pattern, &scrutinee_type)?;
-
                     // Analyze arm body
                     let body_type = self.analyze_expression(&arm.body)?;
                     arm_types.push(body_type);
@@ -2175,7 +3839,7 @@ impl SemanticAnalyzer {
                 }
 
                 // Check exhaustiveness (conservative: require _ or all variants)
-                Self::check_exhaustiveness(arms, &scrutinee_type, *span)?;
+                self.check_exhaustiveness(arms, &scrutinee_type, *span)?;
 
                 // Return type is common type of all arms (or Unknown if mixed)
                 if let Some(first) = arm_types.first() {
@@ -2206,10 +3870,40 @@ impl SemanticAnalyzer {
                             source_type.display_name(),
                             target.display_name()
                         ),
-                        Some(SourceLocation::new("", span.line, span.column)),
+                        Some(SourceLocation::from_span(span)),
                     ));
                 }
 
+                // (a / b) as f64 casts an already truncated quotient; constant
+                // quotients were reported with the division itself
+                if target.is_float() && source_type.is_integer() {
+                    if let Expression::Binary {
+                        operator: BinaryOp::Divide,
+                        left,
+                        right,
+                        ..
+                    } = expr.ungrouped()
+                    {
+                        if left.constant_int().is_none() || right.constant_int().is_none() {
+                            self.warnings.push(
+                            Warning::new(
+                                "int_division",
+                                &format!(
+                                    "Integer division is done before the cast to {}, so the fraction is lost",
+                                    target.display_name()
+                                ),
+                                Some(SourceLocation::from_span(span)),
+                            )
+                            .with_suggestion(&format!(
+                                "Cast the operands instead: `a as {} / b as {}`",
+                                target.display_name(),
+                                target.display_name()
+                            )),
+                        );
+                        }
+                    }
+                }
+
                 // Cast succeeds - return target type
                 Ok(target)
             }
@@ -2230,22 +3924,32 @@ impl SemanticAnalyzer {
                 let param_names: std::collections::HashSet<String> =
                     params.iter().map(|p| p.name.clone()).collect();
 
-                // Increment scope depth for closure
-                self.scope_depth += 1;
+                for param in params {
+                    self.check_not_global(&param.name, &param.span)?;
+                }
+
+                // Parameters live in the closure's own scope
+                let hints = self.closure_param_hints.take().unwrap_or_default();
+                self.enter_scope();
                 let current_scope_id = self.scope_stack.current();
+                let mut shadowed = Vec::new();
 
-                // Register closure parameters in symbol table
+                // Register closure parameters in symbol table. Unannotated ones
+                // take the type of the `Fn` parameter the closure is passed as,
+                // or default to i32
                 let param_types: Vec<ZyraType> = params
                     .iter()
-                    .map(|p| {
+                    .enumerate()
+                    .map(|(i, p)| {
                         let param_type = p
                             .param_type
                             .as_ref()
                             .map(|t| ZyraType::from_ast_type(t))
-                            .unwrap_or(ZyraType::I32); // Default to i32 if not specified
+                            .or_else(|| hints.get(i).cloned())
+                            .unwrap_or(ZyraType::I32);
 
                         // Register parameter in symbol table
-                        self.symbols.insert(
+                        let outer = self.symbols.insert(
                             p.name.clone(),
                             Symbol {
                                 name: p.name.clone(),
@@ -2257,16 +3961,32 @@ impl SemanticAnalyzer {
                                 decl_line: span.line,
                             },
                         );
+                        shadowed.extend(outer);
 
                         // Also register with ownership checker
-                        let _ = self.ownership.define(&p.name, false, span.line);
+                        let _ = timed!(
+                            self,
+                            Ownership,
+                            self.ownership.define(&p.name, false, span.line)
+                        );
 
                         param_type
                     })
                     .collect();
 
-                // Analyze closure body with parameters in scope
-                let body_type = self.analyze_expression(body)?;
+                // Analyze closure body with parameters in scope; break and
+                // continue can't reach the enclosing function's loops
+                let outer_loops = std::mem::take(&mut self.loop_labels);
+                self.closure_depth += 1;
+                let body_type = self.analyze_block(body);
+                self.exit_scope();
+                self.closure_depth -= 1;
+                self.loop_labels = outer_loops;
+                // Variables the parameters shadowed are visible again
+                for symbol in shadowed {
+                    self.symbols.insert(symbol.name.clone(), symbol);
+                }
+                let body_type = body_type?;
 
                 // Detect captured variables: outer scope vars referenced in body
                 let captured_vars =
@@ -2277,43 +3997,43 @@ impl SemanticAnalyzer {
                     match capture_mode {
                         crate::parser::ast::CaptureMode::Move => {
                             // Move semantics: mark variable as moved
-                            let move_result = self.ownership.move_value(
-                                captured_var,
-                                &format!("closure_capture_{}", captured_var),
-                                span.line,
+                            let move_result = timed!(
+                                self,
+                                Ownership,
+                                self.ownership.move_value(
+                                    captured_var,
+                                    &format!("closure_capture_{}", captured_var),
+                                    span.line,
+                                )
                             );
                             if let Err(e) = move_result {
                                 return Err(ZyraError::ownership_error(
                                     &format!("Cannot move '{}' into closure: {}", captured_var, e),
-                                    Some(SourceLocation::new("", span.line, span.column)),
+                                    Some(SourceLocation::from_span(span)),
                                 ));
                             }
                         }
                         crate::parser::ast::CaptureMode::Borrow => {
                             // Borrow semantics: create immutable borrow
-                            let borrow_result = self.ownership.borrow(
-                                captured_var,
-                                &format!("closure_capture_{}", captured_var),
-                                span.line,
+                            let borrow_result = timed!(
+                                self,
+                                Ownership,
+                                self.ownership.borrow(
+                                    captured_var,
+                                    &format!("closure_capture_{}", captured_var),
+                                    span.line,
+                                )
                             );
                             if let Err(e) = borrow_result {
                                 return Err(ZyraError::ownership_error(
                                     &format!("Cannot borrow '{}' in closure: {}", captured_var, e),
-                                    Some(SourceLocation::new("", span.line, span.column)),
+                                    Some(SourceLocation::from_span(span)),
                                 ));
                             }
                         }
                     }
                 }
 
-                // Remove closure params from symbols (they go out of scope)
-                for p in params {
-                    self.symbols.remove(&p.name);
-                }
-
-                // Exit closure scope
-                self.scope_depth -= 1;
-
                 let ret_type = return_type
                     .as_ref()
                     .map(|t| ZyraType::from_ast_type(t))
@@ -2327,18 +4047,107 @@ impl SemanticAnalyzer {
         }
     }
 
+    /// Error for a binary operator applied to two different numeric types, with
+    /// a cast of the side that converts without loss (or the right one) as fix
+    fn operand_mismatch(
+        operator: &BinaryOp,
+        (left, left_type): (&Expression, &ZyraType),
+        (right, right_type): (&Expression, &ZyraType),
+        span: crate::lexer::Span,
+    ) -> ZyraError {
+        let (side, operand, target) = if left_type.widens_to(right_type) {
+            ("left", left, right_type)
+        } else {
+            ("right", right, left_type)
+        };
+        ZyraError::type_error(
+            &format!(
+                "Cannot apply '{}' between {} and {} - types must match",
+                operator.as_str(),
+                left_type.display_name(),
+                right_type.display_name()
+            ),
+            Some(SourceLocation::from_span(&span)),
+        )
+        .with_suggestion(&format!(
+            "Convert the {} operand with `as {}` (`zyra fix` applies this)",
+            side,
+            target.display_name()
+        ))
+        .with_fix(Self::cast_fix(operand, target))
+    }
+
+    /// Attach a cast of a value to the type its context expects as fix, when
+    /// both are numbers: `let x: f64 = count;` becomes `count as f64`
+    fn with_cast_fix(
+        err: ZyraError,
+        value: &Expression,
+        expected: &ZyraType,
+        found: &ZyraType,
+    ) -> ZyraError {
+        if !expected.is_numeric() || !found.is_numeric() {
+            return err;
+        }
+        let lossy = if found.widens_to(expected) {
+            ""
+        } else {
+            ", which may lose precision"
+        };
+        err.with_suggestion(&format!(
+            "Convert the value with `as {}`{} (`zyra fix` applies this)",
+            expected.display_name(),
+            lossy
+        ))
+        .with_fix(Self::cast_fix(value, expected))
+    }
+
+    /// Edits that cast an operand with `as`, parenthesizing it unless it's atomic
+    fn cast_fix(operand: &Expression, target: &ZyraType) -> Vec<Edit> {
+        let span = operand.span();
+        let cast = format!(" as {}", target.display_name());
+        match operand {
+            Expression::Identifier { .. }
+            | Expression::Int { .. }
+            | Expression::Float { .. }
+            | Expression::Call { .. }
+            | Expression::FieldAccess { .. }
+            | Expression::TupleIndex { .. }
+            | Expression::Index { .. }
+            | Expression::Grouped { .. } => vec![Edit::insert(span.end, &cast)],
+            _ => vec![
+                Edit::insert(span.start, "("),
+                Edit::insert(span.end, &format!("){}", cast)),
+            ],
+        }
+    }
+
+    /// Result type of arithmetic on built-in vectors, or None if the operation is invalid
+    fn vector_arith_type(op: &BinaryOp, left: &ZyraType, right: &ZyraType) -> Option<ZyraType> {
+        let scalar = |ty: &ZyraType| ty.is_numeric() || matches!(ty, ZyraType::Unknown);
+        match op {
+            BinaryOp::Add | BinaryOp::Subtract if left.is_vector_type() && left == right => {
+                Some(left.clone())
+            }
+            BinaryOp::Multiply | BinaryOp::Divide if left.is_vector_type() && scalar(right) => {
+                Some(left.clone())
+            }
+            BinaryOp::Multiply if scalar(left) && right.is_vector_type() => Some(right.clone()),
+            _ => None,
+        }
+    }
+
     /// Check that a match guard is pure (no side effects)
     fn check_guard_purity(guard: &Expression) -> ZyraResult<()> {
         match guard {
             // Assignments are side effects
             Expression::Assignment { span, .. } => Err(ZyraError::type_error(
                 "Match guard cannot contain assignment (must be pure)",
-                Some(SourceLocation::new("", span.line, span.column)),
+                Some(SourceLocation::from_span(span)),
             )),
             // Function calls may have side effects - conservative rejection
             Expression::Call { span, .. } => Err(ZyraError::type_error(
                 "Match guard cannot contain function calls (must be pure)",
-                Some(SourceLocation::new("", span.line, span.column)),
+                Some(SourceLocation::from_span(span)),
             )),
             // Binary and unary expressions are pure if operands are pure
             Expression::Binary { left, right, .. } => {
@@ -2366,153 +4175,17 @@ impl SemanticAnalyzer {
         }
     }
 
-    /// Detect variables captured from outer scope in a closure body
+    /// Outer scope variables a closure body uses (its captures), sorted
     fn detect_captured_variables(
         &self,
-        expr: &Expression,
+        body: &Block,
         outer_scope_vars: &std::collections::HashSet<String>,
         param_names: &std::collections::HashSet<String>,
     ) -> Vec<String> {
-        let mut captured = Vec::new();
-        self.collect_variable_refs(expr, outer_scope_vars, param_names, &mut captured);
-        // Remove duplicates
-        captured.sort();
-        captured.dedup();
-        captured
-    }
-
-    /// Recursively collect variable references from an expression
-    fn collect_variable_refs(
-        &self,
-        expr: &Expression,
-        outer_scope_vars: &std::collections::HashSet<String>,
-        param_names: &std::collections::HashSet<String>,
-        captured: &mut Vec<String>,
-    ) {
-        match expr {
-            Expression::Identifier { name, .. } => {
-                // If it's an outer scope var and not a param, it's captured
-                if outer_scope_vars.contains(name) && !param_names.contains(name) {
-                    captured.push(name.clone());
-                }
-            }
-            Expression::Binary { left, right, .. } => {
-                self.collect_variable_refs(left, outer_scope_vars, param_names, captured);
-                self.collect_variable_refs(right, outer_scope_vars, param_names, captured);
-            }
-            Expression::Unary { operand, .. } => {
-                self.collect_variable_refs(operand, outer_scope_vars, param_names, captured);
-            }
-            Expression::Call { arguments, .. } => {
-                for arg in arguments {
-                    self.collect_variable_refs(arg, outer_scope_vars, param_names, captured);
-                }
-            }
-            Expression::FieldAccess { object, .. } => {
-                self.collect_variable_refs(object, outer_scope_vars, param_names, captured);
-            }
-            Expression::Index { object, index, .. } => {
-                self.collect_variable_refs(object, outer_scope_vars, param_names, captured);
-                self.collect_variable_refs(index, outer_scope_vars, param_names, captured);
-            }
-            Expression::Grouped { inner, .. } => {
-                self.collect_variable_refs(inner, outer_scope_vars, param_names, captured);
-            }
-            Expression::List { elements, .. } => {
-                for elem in elements {
-                    self.collect_variable_refs(elem, outer_scope_vars, param_names, captured);
-                }
-            }
-            Expression::Cast { expr, .. } => {
-                self.collect_variable_refs(expr, outer_scope_vars, param_names, captured);
-            }
-            Expression::Closure { body, .. } => {
-                // Nested closures - recursively check but don't capture their params
-                self.collect_variable_refs(body, outer_scope_vars, param_names, captured);
-            }
-            Expression::Reference { value, .. } => {
-                self.collect_variable_refs(value, outer_scope_vars, param_names, captured);
-            }
-            Expression::Dereference { value, .. } => {
-                self.collect_variable_refs(value, outer_scope_vars, param_names, captured);
-            }
-            Expression::Assignment { target, value, .. } => {
-                self.collect_variable_refs(target, outer_scope_vars, param_names, captured);
-                self.collect_variable_refs(value, outer_scope_vars, param_names, captured);
-            }
-            // Literals don't capture
-            Expression::Int { .. }
-            | Expression::Float { .. }
-            | Expression::Bool { .. }
-            | Expression::Char { .. }
-            | Expression::String { .. } => {}
-            // Other expressions - skip for simplicity
-            _ => {}
-        }
-    }
-
-    /// Collect variable refs from a statement
-    #[allow(dead_code)]
-    fn collect_variable_refs_from_stmt(
-        &self,
-        stmt: &Statement,
-        outer_scope_vars: &std::collections::HashSet<String>,
-        param_names: &std::collections::HashSet<String>,
-        captured: &mut Vec<String>,
-    ) {
-        match stmt {
-            Statement::Expression { expr, .. } => {
-                self.collect_variable_refs(expr, outer_scope_vars, param_names, captured);
-            }
-            Statement::Let { value, .. } => {
-                self.collect_variable_refs(value, outer_scope_vars, param_names, captured);
-            }
-            Statement::Return {
-                value: Some(expr), ..
-            } => {
-                self.collect_variable_refs(expr, outer_scope_vars, param_names, captured);
-            }
-            Statement::If {
-                condition,
-                then_block,
-                else_block,
-                ..
-            } => {
-                self.collect_variable_refs(condition, outer_scope_vars, param_names, captured);
-                for s in &then_block.statements {
-                    self.collect_variable_refs_from_stmt(
-                        s,
-                        outer_scope_vars,
-                        param_names,
-                        captured,
-                    );
-                }
-                if let Some(else_blk) = else_block {
-                    for s in &else_blk.statements {
-                        self.collect_variable_refs_from_stmt(
-                            s,
-                            outer_scope_vars,
-                            param_names,
-                            captured,
-                        );
-                    }
-                }
-            }
-            Statement::While {
-                condition, body, ..
-            } => {
-                self.collect_variable_refs(condition, outer_scope_vars, param_names, captured);
-                for s in &body.statements {
-                    self.collect_variable_refs_from_stmt(
-                        s,
-                        outer_scope_vars,
-                        param_names,
-                        captured,
-                    );
-                }
-            }
-            _ => {}
-        }
+        body.identifiers()
+            .into_iter()
+            .filter(|name| outer_scope_vars.contains(name) && !param_names.contains(name))
+            .collect()
     }
 
     /// Introduce pattern bindings into current scope (simplified)
@@ -2521,13 +4194,14 @@ impl SemanticAnalyzer {
         pattern: &crate::parser::ast::Pattern,
         scrutinee_type: &ZyraType,
     ) -> ZyraResult<()> {
-        use crate::parser::ast::Pattern;
+        use crate::parser::ast::{LiteralPattern, Pattern};
         match pattern {
             Pattern::Identifier {
                 name,
                 mutable,
                 span,
             } => {
+                self.check_not_global(name, span)?;
                 // Add to symbol table
                 self.symbols.insert(
                     name.clone(),
@@ -2542,10 +4216,15 @@ impl SemanticAnalyzer {
                     },
                 );
                 // Register with ownership checker
-                let _ = self.ownership.define(name, *mutable, span.line);
+                let _ = timed!(
+                    self,
+                    Ownership,
+                    self.ownership.define(name, *mutable, span.line)
+                );
                 Ok(())
             }
             Pattern::RefBinding { name, span } => {
+                self.check_not_global(name, span)?;
                 // Add to symbol table
                 self.symbols.insert(
                     name.clone(),
@@ -2560,33 +4239,318 @@ impl SemanticAnalyzer {
                     },
                 );
                 // Register with ownership checker (immutable ref binding)
-                let _ = self.ownership.define(name, false, span.line);
+                let _ = timed!(
+                    self,
+                    Ownership,
+                    self.ownership.define(name, false, span.line)
+                );
                 Ok(())
             }
-            Pattern::Struct { fields, .. } => {
+            Pattern::Struct {
+                type_name,
+                fields,
+                span,
+                ..
+            } => {
+                let scrutinee = match scrutinee_type {
+                    ZyraType::Reference { inner, .. } => inner.as_ref(),
+                    other => other,
+                };
+                let declared = match self.types.get(type_name) {
+                    Some(TypeDef::Struct { fields, .. }) => Some(fields.clone()),
+                    _ => None,
+                };
+                if let (Some(_), ZyraType::Struct(actual)) = (&declared, scrutinee) {
+                    if actual != type_name && self.types.contains_key(actual) {
+                        return Err(ZyraError::type_error(
+                            &format!(
+                                "Type mismatch: the pattern matches {}, found {}",
+                                type_name, actual
+                            ),
+                            Some(SourceLocation::from_span(span)),
+                        ));
+                    }
+                }
                 for field in fields {
-                    self.analyze_pattern_bindings(&field.pattern, &ZyraType::Unknown)?;
+                    // Fields of a known struct bind with their declared types
+                    let field_type = match &declared {
+                        Some(declared) => declared
+                            .iter()
+                            .find(|(name, _)| *name == field.field_name)
+                            .map(|(_, ty)| ty.clone())
+                            .ok_or_else(|| {
+                                ZyraError::type_error(
+                                    &format!(
+                                        "'{}' has no field named '{}'",
+                                        type_name, field.field_name
+                                    ),
+                                    Some(SourceLocation::from_span(&field.span)),
+                                )
+                            })?,
+                        None => ZyraType::Unknown,
+                    };
+                    self.analyze_pattern_bindings(&field.pattern, &field_type)?;
                 }
                 Ok(())
             }
-            Pattern::Variant { inner, .. } => {
+            Pattern::Variant {
+                enum_name: None,
+                variant,
+                inner: None,
+                span,
+            } if variant != "None"
+                && !self.types.values().any(|def| {
+                    matches!(def, TypeDef::Enum { variants, .. } if variants.contains(variant))
+                }) =>
+            {
+                // Only integer constants stand for their value in a pattern
+                Err(ZyraError::type_error(
+                    &format!(
+                        "'{}' is not an enum variant or an integer constant, so it can't be used as a pattern",
+                        variant
+                    ),
+                    Some(SourceLocation::from_span(span)),
+                ))
+            }
+            Pattern::Variant { variant, inner, .. } => {
                 if let Some(inner_pattern) = inner {
-                    self.analyze_pattern_bindings(inner_pattern, &ZyraType::Unknown)?;
+                    // Option and Result payloads keep their declared type
+                    let inner_type = match (scrutinee_type, variant.as_str()) {
+                        (ZyraType::Option(inner), "Some") => inner.as_ref().clone(),
+                        (ZyraType::Result { ok_type, .. }, "Ok") => ok_type.as_ref().clone(),
+                        (ZyraType::Result { err_type, .. }, "Err") => err_type.as_ref().clone(),
+                        _ => ZyraType::Unknown,
+                    };
+                    self.analyze_pattern_bindings(inner_pattern, &inner_type)?;
+                }
+                Ok(())
+            }
+            Pattern::Tuple { elements, span } => {
+                let scrutinee_type = match scrutinee_type {
+                    ZyraType::Reference { inner, .. } => inner.as_ref(),
+                    other => other,
+                };
+                let types = match scrutinee_type {
+                    ZyraType::Tuple(types) if types.len() != elements.len() => {
+                        return Err(ZyraError::type_error(
+                            &format!(
+                                "Expected a tuple of {} element(s), found {}",
+                                elements.len(),
+                                scrutinee_type.display_name()
+                            ),
+                            Some(SourceLocation::from_span(span)),
+                        ));
+                    }
+                    ZyraType::Tuple(types) => types.clone(),
+                    _ => vec![ZyraType::Unknown; elements.len()],
+                };
+                for (elem, elem_type) in elements.iter().zip(&types) {
+                    self.analyze_pattern_bindings(elem, elem_type)?;
                 }
                 Ok(())
             }
-            Pattern::Tuple { elements, .. } => {
-                for elem in elements {
-                    self.analyze_pattern_bindings(elem, &ZyraType::Unknown)?;
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+                span,
+            } => {
+                let empty = match (start, end) {
+                    (LiteralPattern::Int(a), LiteralPattern::Int(b)) => {
+                        a > b || (a == b && !inclusive)
+                    }
+                    (LiteralPattern::Char(a), LiteralPattern::Char(b)) => {
+                        a > b || (a == b && !inclusive)
+                    }
+                    _ => false,
+                };
+                if empty {
+                    return Err(ZyraError::type_error(
+                        "This range pattern is empty, so it never matches",
+                        Some(SourceLocation::from_span(span)),
+                    ));
                 }
                 Ok(())
             }
+            Pattern::Or { alternatives, span } => {
+                // Every alternative binds the same names, whichever one matched
+                let names = |pattern: &Pattern| {
+                    let mut names = Vec::new();
+                    pattern.bound_names(&mut names);
+                    names.sort();
+                    names
+                };
+                let first = names(&alternatives[0]);
+                if alternatives[1..].iter().any(|alt| names(alt) != first) {
+                    return Err(ZyraError::type_error(
+                        "Each alternative of an or-pattern must bind the same variables",
+                        Some(SourceLocation::from_span(span)),
+                    ));
+                }
+                self.analyze_pattern_bindings(&alternatives[0], scrutinee_type)
+            }
             Pattern::Wildcard { .. } | Pattern::Literal { .. } => Ok(()),
         }
     }
 
-    /// Check match exhaustiveness (conservative algorithm)
+    /// Key of a top-level item; inside a module, a bare name is one of the
+    /// module's own items
+    fn item_key(&self, name: &str) -> String {
+        match &self.current_module {
+            Some(module) if !name.contains("::") => format!("{}::{}", module, name),
+            _ => name.to_string(),
+        }
+    }
+
+    /// Type of the constant a name refers to
+    fn constant_type(&self, name: &str) -> Option<&ZyraType> {
+        self.constants.get(&self.item_key(name))
+    }
+
+    /// Type of the `static mut` global a name refers to
+    fn static_type(&self, name: &str) -> Option<&ZyraType> {
+        self.statics.get(&self.item_key(name))
+    }
+
+    /// Variables can't take a constant's or global's name, since the
+    /// compiler resolves every use of the name to the top-level item
+    fn check_not_global(&self, name: &str, span: &crate::lexer::Span) -> ZyraResult<()> {
+        let kind = if self.constant_type(name).is_some() {
+            "a constant"
+        } else if self.static_type(name).is_some() {
+            "a global"
+        } else {
+            return Ok(());
+        };
+        Err(ZyraError::name_error(
+            &format!("'{}' is already declared as {}", name, kind),
+            Some(SourceLocation::from_span(span)),
+        )
+        .with_suggestion("Give the variable a different name"))
+    }
+
+    /// Warn the first time a function touches a given `static mut` global:
+    /// state shared that way is easy to change from somewhere unexpected
+    fn note_static_use(&mut self, name: &str, span: &crate::lexer::Span) {
+        let Some(function) = self.current_function.clone() else {
+            return;
+        };
+        if self
+            .static_uses
+            .insert((function.clone(), name.to_string()))
+        {
+            self.warnings.push(
+                Warning::new(
+                    "static_mut",
+                    &format!("`{}` uses the mutable global `{}`", function, name),
+                    Some(SourceLocation::from_span(span)),
+                )
+                .with_suggestion(
+                    "Pass the value in as a parameter where only a few functions need it",
+                ),
+            );
+        }
+    }
+
+    /// `GLOBAL = value` or `GLOBAL op= value`: the value has to fit the
+    /// global's type, and no ownership applies
+    fn analyze_static_assignment(
+        &mut self,
+        name: &str,
+        static_type: ZyraType,
+        target: &Expression,
+        operator: Option<BinaryOp>,
+        value: &Expression,
+        span: &crate::lexer::Span,
+    ) -> ZyraResult<ZyraType> {
+        self.note_static_use(name, &target.span());
+        if let Some(op) = operator {
+            return self.analyze_expression(&Expression::Binary {
+                left: Box::new(target.clone()),
+                operator: op,
+                right: Box::new(value.clone()),
+                span: *span,
+            });
+        }
+        let value_type = self.analyze_with_hint(value, Some(static_type.clone()))?;
+        if !self.type_accepts(&static_type, &value_type) {
+            return Err(Self::with_cast_fix(
+                ZyraError::type_error(
+                    &format!(
+                        "Type mismatch: expected {}, found {}",
+                        static_type.display_name(),
+                        value_type.display_name()
+                    ),
+                    Some(SourceLocation::from_span(span)),
+                ),
+                value,
+                &static_type,
+                &value_type,
+            ));
+        }
+        Ok(value_type)
+    }
+
+    /// Reject a constant's value unless it can be worked out at compile
+    /// time: literals, operators, casts and constants declared before it
+    fn check_constant_expression(&self, expr: &Expression) -> ZyraResult<()> {
+        match expr {
+            Expression::Int { .. }
+            | Expression::Float { .. }
+            | Expression::Bool { .. }
+            | Expression::Char { .. }
+            | Expression::String { .. } => Ok(()),
+            Expression::Grouped { inner, .. }
+            | Expression::Cast { expr: inner, .. }
+            | Expression::Unary { operand: inner, .. } => self.check_constant_expression(inner),
+            Expression::Binary { left, right, .. } => {
+                self.check_constant_expression(left)?;
+                self.check_constant_expression(right)
+            }
+            Expression::Identifier { name, .. } if self.constant_type(name).is_some() => Ok(()),
+            Expression::EnumVariant { .. }
+                if expr
+                    .qualified_name()
+                    .is_some_and(|name| self.constants.contains_key(&name)) =>
+            {
+                Ok(())
+            }
+            _ => {
+                let message = match expr {
+                    Expression::Identifier { name, .. } => {
+                        format!("'{}' is not a constant declared before this one", name)
+                    }
+                    _ => "Constant values must be known at compile time".to_string(),
+                };
+                Err(
+                    ZyraError::type_error(&message, Some(SourceLocation::from_span(&expr.span())))
+                        .with_suggestion(
+                        "A constant can only use literals, operators, casts and earlier constants",
+                    ),
+                )
+            }
+        }
+    }
+
+    /// Whether a pattern matches every value of its type: bindings,
+    /// wildcards, and tuples and structs of them
+    fn is_irrefutable(pattern: &crate::parser::ast::Pattern) -> bool {
+        use crate::parser::ast::Pattern;
+        match pattern {
+            Pattern::Wildcard { .. } | Pattern::Identifier { .. } | Pattern::RefBinding { .. } => {
+                true
+            }
+            Pattern::Tuple { elements, .. } => elements.iter().all(Self::is_irrefutable),
+            Pattern::Struct { fields, .. } => fields
+                .iter()
+                .all(|field| Self::is_irrefutable(&field.pattern)),
+            _ => false,
+        }
+    }
+
+    /// Check match exhaustiveness against the scrutinee's variant set
     fn check_exhaustiveness(
+        &self,
         arms: &[crate::parser::ast::MatchArm],
         scrutinee_type: &ZyraType,
         span: crate::lexer::Span,
@@ -2599,45 +4563,186 @@ impl SemanticAnalyzer {
             if arm.guard.is_some() {
                 return false;
             }
-            matches!(
-                &arm.pattern,
-                Pattern::Wildcard { .. } | Pattern::Identifier { .. }
-            )
+            arm.pattern.alternatives().iter().any(|pattern| {
+                matches!(
+                    pattern,
+                    Pattern::Wildcard { .. } | Pattern::Identifier { .. }
+                )
+            })
         });
 
         if has_wildcard {
             return Ok(()); // Exhaustive via wildcard
         }
 
-        // For enums, check if we have variant patterns
-        // A more complete implementation would count variants against enum definition
-        // For now, we allow enum matching if there are any variant patterns
-        if let ZyraType::Enum(_enum_name) = scrutinee_type {
-            let has_variant_patterns = arms
-                .iter()
-                .any(|arm| matches!(&arm.pattern, Pattern::Variant { .. }));
+        // Enum-like scrutinees are checked against every variant they can hold
+        let scrutinee = match scrutinee_type {
+            ZyraType::Reference { inner, .. } => inner.as_ref(),
+            other => other,
+        };
+        let (enum_name, variants): (&str, Vec<&str>) = match scrutinee {
+            ZyraType::Enum(name) | ZyraType::Struct(name) => match self.types.get(name) {
+                Some(TypeDef::Enum { name, variants }) => {
+                    (name.as_str(), variants.iter().map(String::as_str).collect())
+                }
+                _ => return Ok(()),
+            },
+            ZyraType::Option(_) => ("Option", vec!["Some", "None"]),
+            ZyraType::Result { .. } => ("Result", vec!["Ok", "Err"]),
+            _ => return Ok(()),
+        };
 
-            if has_variant_patterns {
-                // TODO: In the future, check all variants are covered
-                // For now, allow variant patterns (assume programmer covers all cases)
-                return Ok(());
+        // Payload patterns of the unguarded arms for each variant (None for a
+        // bare variant). An or-pattern covers what each of its alternatives covers
+        let mut payloads: HashMap<&str, Vec<Option<&Pattern>>> = HashMap::new();
+        for arm in arms {
+            for pattern in arm.pattern.alternatives() {
+                let Pattern::Variant {
+                    variant,
+                    inner,
+                    span,
+                    ..
+                } = pattern
+                else {
+                    continue;
+                };
+                if !variants.contains(&variant.as_str()) {
+                    return Err(ZyraError::type_error(
+                        &format!("'{}' has no variant named '{}'", enum_name, variant),
+                        Some(SourceLocation::from_span(span)),
+                    ));
+                }
+                // Guarded arms don't cover the variant
+                if arm.guard.is_none() {
+                    payloads
+                        .entry(variant.as_str())
+                        .or_default()
+                        .push(inner.as_deref());
+                }
             }
+        }
 
-            // No variant patterns and no wildcard = definitely non-exhaustive
+        let missing: Vec<&str> = variants
+            .into_iter()
+            .filter(|v| {
+                !payloads
+                    .get(v)
+                    .is_some_and(|payloads| self.payloads_cover(payloads))
+            })
+            .collect();
+        if !missing.is_empty() {
             return Err(ZyraError::type_error(
-                "Non-exhaustive match: add a wildcard `_` pattern or cover all enum variants",
-                Some(SourceLocation::new("", span.line, span.column)),
+                &format!(
+                    "Non-exhaustive match on '{}': missing variant(s) {}. Cover them or add a wildcard `_` pattern",
+                    enum_name,
+                    missing.join(", ")
+                ),
+                Some(SourceLocation::from_span(&span)),
             ));
         }
 
         Ok(())
     }
 
+    /// Whether the payload patterns of a variant's unguarded arms (None for a
+    /// bare variant) together match every payload. Variant patterns count once
+    /// they cover their own enum, so `Some(Shape::Circle(r))` and
+    /// `Some(Shape::Square(_))` cover `Some`.
+    fn payloads_cover(&self, payloads: &[Option<&crate::parser::ast::Pattern>]) -> bool {
+        use crate::parser::ast::Pattern;
+        let patterns: Vec<&Pattern> = payloads
+            .iter()
+            .flatten()
+            .flat_map(|pattern| pattern.alternatives())
+            .collect();
+        if payloads.iter().any(Option::is_none)
+            || patterns.iter().any(|pattern| Self::is_irrefutable(pattern))
+        {
+            return true;
+        }
+        let Some(variants) = patterns
+            .iter()
+            .find_map(|pattern| self.pattern_enum_variants(pattern))
+        else {
+            return false;
+        };
+        variants.iter().all(|variant| {
+            let inner: Vec<Option<&Pattern>> = patterns
+                .iter()
+                .filter_map(|pattern| match pattern {
+                    Pattern::Variant {
+                        variant: name,
+                        inner,
+                        ..
+                    } if name == variant => Some(inner.as_deref()),
+                    _ => None,
+                })
+                .collect();
+            !inner.is_empty() && self.payloads_cover(&inner)
+        })
+    }
+
+    /// Every variant of the enum a variant pattern names
+    fn pattern_enum_variants(&self, pattern: &crate::parser::ast::Pattern) -> Option<Vec<String>> {
+        let crate::parser::ast::Pattern::Variant {
+            enum_name, variant, ..
+        } = pattern
+        else {
+            return None;
+        };
+        let of = |names: &[&str]| Some(names.iter().map(|name| name.to_string()).collect());
+        match (enum_name.as_deref(), variant.as_str()) {
+            (Some("Option"), _) | (None, "Some" | "None") => of(&["Some", "None"]),
+            (Some("Result"), _) | (None, "Ok" | "Err") => of(&["Ok", "Err"]),
+            (Some(name), _) => match self.types.get(name) {
+                Some(TypeDef::Enum { variants, .. }) => Some(variants.clone()),
+                _ => None,
+            },
+            (None, variant) => self.types.values().find_map(|def| match def {
+                TypeDef::Enum { variants, .. } if variants.iter().any(|v| v == variant) => {
+                    Some(variants.clone())
+                }
+                _ => None,
+            }),
+        }
+    }
+
+    /// Bind a name whose `let` failed to check, as its declared type or as
+    /// Unknown, which every use accepts
+    fn declare_failed_binding(
+        &mut self,
+        name: &str,
+        mutable: bool,
+        annotation: Option<&Type>,
+        line: usize,
+    ) {
+        if self
+            .symbols
+            .get(name)
+            .is_some_and(|symbol| symbol.scope_depth == self.scope_depth)
+        {
+            return;
+        }
+        self.symbols.insert(
+            name.to_string(),
+            Symbol {
+                name: name.to_string(),
+                symbol_type: annotation.map_or(ZyraType::Unknown, ZyraType::from_ast_type),
+                mutable,
+                scope_depth: self.scope_depth,
+                scope_id: self.scope_stack.current(),
+                origin: ValueOrigin::Local,
+                decl_line: line,
+            },
+        );
+        let _ = self.ownership.define(name, mutable, line);
+    }
+
     fn enter_scope(&mut self) {
         self.scope_depth += 1;
         self.scope_stack.enter();
-        self.ownership.enter_scope();
-        self.borrow_checker.enter_scope();
+        timed!(self, Ownership, self.ownership.enter_scope());
+        timed!(self, Borrow, self.borrow_checker.enter_scope());
     }
 
     fn exit_scope(&mut self) {
@@ -2651,13 +4756,173 @@ impl SemanticAnalyzer {
 
         // Exit scope in all checkers
         self.scope_stack.exit();
-        self.ownership.exit_scope();
-        self.borrow_checker.exit_scope();
+        timed!(self, Ownership, self.ownership.exit_scope());
+        timed!(self, Borrow, self.borrow_checker.exit_scope());
         self.scope_depth -= 1;
     }
 
+    /// drop moves its argument and ends the borrow held by a dropped reference;
+    /// take and swap write through `&mut` places, which must be mutable and unborrowed;
+    /// weak reads its argument without borrowing it, so the owner can still be dropped
+    fn analyze_mem_intrinsic(
+        &mut self,
+        name: &str,
+        arguments: &[Expression],
+        span: crate::lexer::Span,
+    ) -> ZyraResult<ZyraType> {
+        let location = || Some(SourceLocation::from_span(&span));
+        let expected = if name == "swap" { 2 } else { 1 };
+        if arguments.len() != expected {
+            return Err(ZyraError::type_error(
+                &format!(
+                    "Function '{}' expects {} argument(s), got {}",
+                    name,
+                    expected,
+                    arguments.len()
+                ),
+                location(),
+            ));
+        }
+
+        if name == "weak" {
+            let arg = match &arguments[0] {
+                Expression::Reference {
+                    mutable: false,
+                    value,
+                    ..
+                } => value.as_ref(),
+                arg => arg,
+            };
+            self.analyze_expression(arg)?;
+            return Ok(ZyraType::Struct("Weak".to_string()));
+        }
+
+        if name == "drop" {
+            let arg = &arguments[0];
+            let arg_type = self.analyze_expression(arg)?;
+            if let Expression::Identifier { name: var, .. } = arg {
+                if let Some(reference) = self.references.remove(var) {
+                    timed!(self, Borrow, self.borrow_checker.end_borrow(var));
+                    let still_borrowed = self
+                        .references
+                        .values()
+                        .any(|r| r.source_name == reference.source_name);
+                    if !still_borrowed {
+                        timed!(
+                            self,
+                            Ownership,
+                            self.ownership.release(&reference.source_name)
+                        );
+                    }
+                }
+                if !arg_type.is_copy_type() {
+                    timed!(
+                        self,
+                        Ownership,
+                        self.ownership.move_value(var, "drop", span.line)
+                    )
+                    .map_err(|e| self.ownership_error_to_zyra(e))?;
+                }
+            }
+            return Ok(ZyraType::Void);
+        }
+
+        let mut places = Vec::new();
+        for arg in arguments {
+            let place = match arg {
+                Expression::Reference {
+                    mutable: true,
+                    value,
+                    ..
+                } => match value.as_ref() {
+                    Expression::Identifier { name, .. } => Some((name, value.as_ref())),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let Some((place, place_expr)) = place else {
+                return Err(ZyraError::type_error(
+                    &format!("{} expects a mutable place such as `&mut x`", name),
+                    location(),
+                ));
+            };
+            timed!(self, Ownership, self.ownership.assign(place, span.line))
+                .map_err(|e| ZyraError::ownership_error(&e.to_string(), location()))?;
+            timed!(
+                self,
+                Borrow,
+                self.borrow_checker.can_mutate(place, span.line)
+            )
+            .map_err(|e| ZyraError::ownership_error(&e.to_string(), location()))?;
+            places.push((place, self.analyze_expression(place_expr)?));
+        }
+
+        if let [(a, a_type), (b, b_type)] = places.as_slice() {
+            if a == b {
+                return Err(ZyraError::type_error(
+                    &format!("swap needs two different variables, got '{}' twice", a),
+                    location(),
+                ));
+            }
+            if !self.type_accepts(a_type, b_type)
+                && !self.type_accepts(b_type, a_type)
+                && !matches!(a_type, ZyraType::Unknown)
+                && !matches!(b_type, ZyraType::Unknown)
+            {
+                return Err(ZyraError::type_error(
+                    &format!(
+                        "swap needs values of the same type, got {} and {}",
+                        a_type.display_name(),
+                        b_type.display_name()
+                    ),
+                    location(),
+                ));
+            }
+            return Ok(ZyraType::Void);
+        }
+        Ok(places.pop().map(|(_, t)| t).unwrap_or(ZyraType::Unknown))
+    }
+
+    /// The error at the line of the offending use, with a label on the line
+    /// that led to it
     fn ownership_error_to_zyra(&self, err: OwnershipError) -> ZyraError {
-        ZyraError::ownership_error(&err.to_string(), None)
+        let at = |line| SourceLocation::new("", line, 0);
+        let error = ZyraError::ownership_error(&err.to_string(), Some(at(err.line())));
+        match err {
+            OwnershipError::UsedAfterMove {
+                moved_to, moved_at, ..
+            } if moved_to.is_empty() || moved_to == "drop" => {
+                error.with_label(at(moved_at), "value moved here")
+            }
+            OwnershipError::UsedAfterMove {
+                moved_to, moved_at, ..
+            } => error.with_label(
+                at(moved_at),
+                &format!("value moved into '{}' here", moved_to),
+            ),
+            OwnershipError::AlreadyDefined { original_line, .. } => {
+                error.with_label(at(original_line), "first defined here")
+            }
+            OwnershipError::AssignToImmutable { defined_at, .. } => {
+                error.with_label(at(defined_at), "defined here without 'mut'")
+            }
+            _ => error,
+        }
+    }
+
+    /// The error at `location`, with a label on the line a used value was
+    /// moved on
+    fn borrow_error_to_zyra(err: BorrowError, location: Option<SourceLocation>) -> ZyraError {
+        let error = ZyraError::ownership_error(&err.to_string(), location);
+        match err {
+            BorrowError::UseAfterMove {
+                moved_to, moved_at, ..
+            } => error.with_label(
+                SourceLocation::new("", moved_at, 0),
+                &format!("value moved into '{}' here", moved_to),
+            ),
+            _ => error,
+        }
     }
 
     /// Check if a return expression contains a dangling reference
@@ -2681,7 +4946,7 @@ impl SemanticAnalyzer {
                                         name,
                                         symbol.origin.display_name()
                                     ),
-                                    Some(SourceLocation::new("", span.line, span.column)),
+                                    Some(SourceLocation::from_span(span)),
                                 ));
                             }
                             ValueOrigin::Param | ValueOrigin::Global => {
@@ -2704,7 +4969,7 @@ impl SemanticAnalyzer {
                                  note: `{}` borrows from local variable `{}`",
                                 ref_info.source_name, name, ref_info.source_name
                             ),
-                            Some(SourceLocation::new("", span.line, span.column)),
+                            Some(SourceLocation::from_span(span)),
                         ));
                     }
                 }
@@ -2858,3 +5123,549 @@ impl Default for SemanticAnalyzer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn analyze(source: &str) -> ZyraResult<()> {
+        let tokens = Lexer::new(source, "test.zr").tokenize()?;
+        let program = Parser::new(tokens).parse()?;
+        SemanticAnalyzer::new().analyze(&program)
+    }
+
+    const DIR: &str = "enum Dir { North, South, East(i32) }\n";
+
+    #[test]
+    fn test_match_reports_missing_variants() {
+        let err = analyze(&format!(
+            "{}func f(d: Dir) -> i32 {{ match d {{ Dir::North => 1, Dir::East(x) if x > 0 => x, }} }}\nfunc main() {{}}",
+            DIR
+        ))
+        .unwrap_err();
+        assert!(err.message.contains("missing variant(s) South, East"));
+    }
+
+    #[test]
+    fn test_match_covering_all_variants() {
+        analyze(&format!(
+            "{}func f(d: Dir) -> i32 {{ match d {{ Dir::North => 1, Dir::South => 2, Dir::East(x) => x, }} }}\nfunc main() {{}}",
+            DIR
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn test_or_patterns_cover_variants_and_bind_alike() {
+        analyze(&format!(
+            "{}func f(d: Dir) -> i32 {{ match d {{ Dir::North | Dir::South => 1, Dir::East(x) => x, }} }}\nfunc main() {{}}",
+            DIR
+        ))
+        .unwrap();
+        let err = analyze(&format!(
+            "{}func f(d: Dir) -> i32 {{ match d {{ Dir::North | Dir::East(_) => 1, }} }}\nfunc main() {{}}",
+            DIR
+        ))
+        .unwrap_err();
+        assert!(err.message.contains("missing variant(s) South"));
+
+        let err = analyze(
+            "enum E { A(i32), B(i32) }\nfunc f(e: E) -> i32 { match e { E::A(x) | E::B(y) => 1, } }\nfunc main() {}",
+        )
+        .unwrap_err();
+        assert!(err.message.contains("must bind the same variables"));
+        let err =
+            analyze("func f(n: i32) -> i32 { match n { 5..5 => 1, _ => 0, } }\nfunc main() {}")
+                .unwrap_err();
+        assert!(err.message.contains("range pattern is empty"));
+    }
+
+    #[test]
+    fn test_nested_payload_patterns_cover_variants() {
+        let shape = "enum Shape { Circle(i32), Square(i32) }\nstruct Point { x: i32, y: i32 }\n";
+        for arms in [
+            "Some(Shape::Circle(r)) => r, Some(Shape::Square(_)) => 0, None => -1,",
+            "Some(Shape::Circle(_) | Shape::Square(_)) => 0, None => -1,",
+        ] {
+            analyze(&format!(
+                "{shape}func f(s: Option<Shape>) -> i32 {{ match s {{ {arms} }} }}\nfunc main() {{}}"
+            ))
+            .unwrap();
+        }
+        analyze(&format!(
+            "{shape}func f(p: Option<Point>) -> i32 {{ match p {{ Some(Point {{ x, y }}) => x + y, None => 0, }} }}\n\
+             func g(t: Option<(i32, i32)>) -> i32 {{ match t {{ Some((a, b)) => a + b, None => 0, }} }}\nfunc main() {{}}"
+        ))
+        .unwrap();
+
+        let err = analyze(&format!(
+            "{shape}func f(s: Option<Shape>) -> i32 {{ match s {{ Some(Shape::Circle(r)) => r, None => -1, }} }}\nfunc main() {{}}"
+        ))
+        .unwrap_err();
+        assert!(
+            err.message.contains("missing variant(s) Some"),
+            "{}",
+            err.message
+        );
+    }
+
+    #[test]
+    fn test_tuple_types_are_checked() {
+        analyze("func main() { let (a, b): (i32, String) = (1, \"x\"); let c: i32 = a; println(b); println(c); }")
+            .unwrap();
+        let err = analyze("func main() { let t = (1, 2); println(t.2); }").unwrap_err();
+        assert!(err.message.contains("out of bounds for a tuple of 2"));
+        let err = analyze("func main() { let (a, b) = (1, 2, 3); }").unwrap_err();
+        assert!(err.message.contains("Expected a tuple of 2 element(s)"));
+        let err = analyze("func main() { let t = (1, 2); let (1, x) = t; }").unwrap_err();
+        assert!(err.message.contains("can fail to match"));
+    }
+
+    #[test]
+    fn test_struct_let_patterns_bind_typed_fields() {
+        let point = "struct Point { x: i32, y: i32 }\nstruct Named { name: String }\n";
+        analyze(&format!(
+            "{point}func main() {{ let p = Point {{ x: 1, y: 2 }}; let Point {{ x, y: _ }} = &p; let a: i32 = x; println(a + p.y); }}"
+        ))
+        .unwrap();
+        let err = analyze(&format!(
+            "{point}func main() {{ let p = Point {{ x: 1, y: 2 }}; let Point {{ x, z }} = p; }}"
+        ))
+        .unwrap_err();
+        assert!(err.message.contains("'Point' has no field named 'z'"));
+        let err = analyze(&format!(
+            "{point}func main() {{ let n = Named {{ name: \"n\" }}; let Named {{ name }} = n; println(n.name); }}"
+        ))
+        .unwrap_err();
+        assert!(err.message.contains("was moved"));
+    }
+
+    #[test]
+    fn test_constants_are_checked() {
+        analyze("func area() -> f64 { SIDE * SIDE }\nconst SIDE: f64 = 2.5;\nconst BIG = 1 << 20;\nfunc main() { let a: f64 = area(); println(a); println(BIG); }")
+            .unwrap();
+        let err = analyze("func f() -> i32 { 1 }\nconst A = f();\nfunc main() { }").unwrap_err();
+        assert!(err.message.contains("must be known at compile time"));
+        let err = analyze("const A = 1;\nfunc main() { A += 1; }").unwrap_err();
+        assert!(err.message.contains("Cannot assign to constant 'A'"));
+        let err = analyze("const A = 1;\nfunc main() { let A = 2; }").unwrap_err();
+        assert!(err.message.contains("already declared as a constant"));
+        let err = analyze(
+            "const A = 1;\nfunc bump(x: &mut i32) { }\nfunc main() { let r = &A; bump(&mut A); }",
+        )
+        .unwrap_err();
+        assert!(err
+            .message
+            .contains("Cannot borrow constant 'A' as mutable"));
+        let err = analyze("static mut TOP: i32 = 6;\nfunc main() { match 6 { TOP => println(1), _ => println(2) } }")
+            .unwrap_err();
+        assert!(
+            err.message.contains("'TOP' is not an enum variant"),
+            "{}",
+            err.message
+        );
+    }
+
+    #[test]
+    fn test_string_methods_are_typed() {
+        analyze("func main() { let s = \"a,b\"; let parts: Vec<String> = s.split(\",\"); let n: i32 = s.trim().len(); let up: String = s.to_upper(); let cs: Vec<char> = s.chars(); let p: Option<i64> = s.parse_int(); }")
+            .unwrap();
+        let err =
+            analyze("func main() { let s = \"abc\"; let n: i32 = s.to_upper(); }").unwrap_err();
+        assert!(err.message.contains("Type mismatch"));
+        let err = analyze("func main() { let s = \"abc\"; s.contains(1); }").unwrap_err();
+        assert!(err.message.contains("String::contains"), "{}", err.message);
+        let err = analyze("func main() { let s = \"abc\"; s.shout(); }").unwrap_err();
+        assert!(err.message.contains("String has no method 'shout'"));
+    }
+
+    #[test]
+    fn test_static_globals_are_checked_and_warned_about() {
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer
+            .analyze(&parse(
+                "static mut SCORE: i32 = 0;
+func add(n: i32) { SCORE += n; SCORE = SCORE * 2; }
+func main() { add(1); println(SCORE); }",
+            ))
+            .unwrap();
+        let lints: Vec<&str> = analyzer.warnings().iter().map(|w| w.lint).collect();
+        assert_eq!(lints, vec!["static_mut", "static_mut"]);
+        assert!(analyzer.warnings()[1].message.contains("`main` uses"));
+
+        let err = analyze(
+            "static mut SCORE: i32 = 0;
+func main() { SCORE = \"high\"; }",
+        )
+        .unwrap_err();
+        assert!(err.message.contains("expected i32, found String"));
+        let err = analyze(
+            "static mut SCORE = 0;
+func main() { let r = &SCORE; }",
+        )
+        .unwrap_err();
+        assert!(err.message.contains("Cannot borrow the global 'SCORE'"));
+        let err = analyze(
+            "static SCORE = 0;
+func main() { }",
+        )
+        .unwrap_err();
+        assert!(err.message.contains("declared with 'static mut'"));
+    }
+
+    #[test]
+    fn test_drop_ends_borrow_and_moves() {
+        analyze("import std::mem;\nfunc main() { let mut s = 1; let r = &mut s; drop(r); s = 2; }")
+            .unwrap();
+        let err = analyze("import std::mem;\nfunc main() { let v = \"a\"; drop(v); println(v); }")
+            .unwrap_err();
+        assert!(err.message.contains("was moved"));
+    }
+
+    #[test]
+    fn test_weak_does_not_borrow() {
+        analyze(
+            "import std::mem;\nstruct N { v: i32 }\nfunc main() { let a = N { v: 1 }; let w = weak(&a); drop(a); println(w.upgrade()); }",
+        )
+        .unwrap();
+        let err = analyze(
+            "import std::mem;\nstruct N { v: i32 }\nfunc main() { let a = N { v: 1 }; drop(a); let w = weak(&a); }",
+        )
+        .unwrap_err();
+        assert!(err.message.contains("was moved"));
+    }
+
+    #[test]
+    fn test_constant_array_index_is_bounds_checked() {
+        let err =
+            analyze("func main() { let a: [i32; 3] = [1, 2, 3]; println(a[3]); }").unwrap_err();
+        assert!(err
+            .message
+            .contains("Index 3 is out of bounds for array of length 3"));
+        let err = analyze("func main() { let mut a = [1, 2]; a[-1] = 5; }").unwrap_err();
+        assert!(err.message.contains("Index -1 is out of bounds"));
+        analyze("func main() { let a = [1, 2, 3]; let i = 5; println(a[2] + a[i]); }").unwrap();
+    }
+
+    #[test]
+    fn test_map_keys_and_values_are_typed() {
+        analyze(
+            "func main() { let mut m = map{\"a\": 1}; m.insert(\"b\", 2); let n: Map<int, bool> = map{}; println(n.get(1)); }",
+        )
+        .unwrap();
+        let err =
+            analyze("func main() { let mut m = map{\"a\": 1}; m.insert(1, 2); }").unwrap_err();
+        assert!(err.message.contains("Map::insert expects String"));
+        let err = analyze("func main() { let m = map{1: 1, 2: true}; }").unwrap_err();
+        assert!(err.message.contains("Map value has type Bool"));
+        let err = analyze("func main() { let m = map{\"a\": 1}; m.clear(); }").unwrap_err();
+        assert!(err.message.contains("immutable variable 'm'"));
+    }
+
+    #[test]
+    fn test_set_elements_are_typed_and_hashable() {
+        analyze("func main() { let mut s = set{1, 2}; s.insert(3); println(s.union(set{4})); }")
+            .unwrap();
+        let err = analyze("func main() { let s = set{1}; println(s.union(5)); }").unwrap_err();
+        assert!(err.message.contains("Set::union expects Set<i32>"));
+        let err = analyze("func main() { let s = set{vec[1]}; }").unwrap_err();
+        assert!(err.message.contains("cannot be a set element"));
+    }
+
+    #[test]
+    fn test_closure_calls_are_checked() {
+        analyze(
+            "func apply(f: Fn(Float) -> Float, x: Float) -> Float { return f(x); }\nfunc main() { println(apply(|x| x * 2.0, 1.5)); }",
+        )
+        .unwrap();
+        let err = analyze("func main() { let add = |a: i32, b: i32| a + b; add(1); }").unwrap_err();
+        assert!(err.message.contains("expects 2 argument(s), got 1"));
+        let err = analyze("func apply(f: Fn(i32) -> i32) -> i32 { return f(1); }\nfunc main() { apply(|a, b| a); }").unwrap_err();
+        assert!(err.message.contains("argument 1 expects |i32| -> i32"));
+    }
+
+    #[test]
+    fn test_swap_requires_mutable_places() {
+        let err = analyze(
+            "import std::mem;\nfunc main() { let a = 1; let mut b = 2; swap(&mut a, &mut b); }",
+        )
+        .unwrap_err();
+        assert!(err.message.contains("immutable variable 'a'"));
+        let err = analyze(
+            "import std::mem;\nfunc main() { let mut a = 1; let mut b = 2; swap(a, &mut b); }",
+        )
+        .unwrap_err();
+        assert!(err.message.contains("mutable place"));
+    }
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source, "test.zr").tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    #[test]
+    fn test_numeric_mismatch_suggests_a_cast() {
+        let source =
+            "func main() {\n    let a: i32 = 5;\n    let b: i64 = 10;\n    let c = b * (a - 1);\n}";
+        let err = analyze(source).unwrap_err();
+        assert!(err.message.contains("between i64 and i32"));
+        let fixed = Edit::apply_all(source, &err.fix);
+        assert!(fixed.contains("let c = b * (a - 1) as i64;"));
+        analyze(&fixed).unwrap();
+
+        // The narrower side is the one promoted
+        let source = "func main() { let a: i32 = 5; let x: f64 = 1.5; let y = a + x; }";
+        let fixed = Edit::apply_all(source, &analyze(source).unwrap_err().fix);
+        assert!(fixed.contains("let y = a as f64 + x;"));
+        analyze(&fixed).unwrap();
+    }
+
+    #[test]
+    fn test_annotation_argument_and_return_mismatches_suggest_a_cast() {
+        let mut source = "func half(x: f32) -> f32 { return x / 2.0; }\n\
+                          func total(n: i32) -> f64 { return n * 2; }\n\
+                          func main() { let a: i32 = 3; let b: f64 = a; let c = half(a + 1); }"
+            .to_string();
+        while let Err(err) = analyze(&source) {
+            assert!(
+                err.suggestion.as_ref().unwrap().contains("`as "),
+                "{}",
+                err.message
+            );
+            source = Edit::apply_all(&source, &err.fix);
+        }
+        assert!(source.contains("return (n * 2) as f64;"), "{}", source);
+        assert!(source.contains("let b: f64 = a as f64;"));
+        assert!(source.contains("half((a + 1) as f32)"));
+    }
+
+    #[test]
+    fn test_literal_argument_checks_can_be_skipped() {
+        let source = "import std::game;\nfunc main() { let up = key_pressed(\"up\"); }";
+        let err = analyze(source).unwrap_err();
+        assert_eq!(err.message, "key_pressed does not know the key 'up'");
+        assert_eq!(
+            err.location.as_ref().map(|l| (l.line, l.column)),
+            Some((2, 36))
+        );
+
+        SemanticAnalyzer::new()
+            .without_arg_checks(&[ArgCheck::Key])
+            .analyze(&parse(source))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_chained_method_calls_are_checked_on_their_receiver() {
+        let prelude = "struct B { w: i32 }\nimpl B {\n    func width(self, w: i32) -> B { B { w: w } }\n    func area(self) -> i32 { self.w * self.w }\n}\nfunc width(a: i32, b: i32) -> i32 { a + b }\nfunc make() -> B { B { w: 1 } }\n";
+        analyze(&format!(
+            "{}func main() {{ let a: i32 = make().width(3).width(4).area(); }}",
+            prelude
+        ))
+        .unwrap();
+
+        let err = analyze(&format!(
+            "{}func main() {{ let a = make().width(3).width(\"4\"); }}",
+            prelude
+        ))
+        .unwrap_err();
+        assert!(err
+            .message
+            .contains("'B::width' argument 1 expects i32, got String"));
+        let err = analyze(&format!(
+            "{}func main() {{ let a: String = make().width(3).area(); }}",
+            prelude
+        ))
+        .unwrap_err();
+        assert!(err.message.contains("expected String, found i32"));
+
+        // Methods the previous call's type doesn't have
+        let err = analyze(&format!(
+            "{}func main() {{ let a = make().area().foo(); }}",
+            prelude
+        ))
+        .unwrap_err();
+        assert!(err.message.contains("i32 has no method 'foo'"));
+        let err = analyze(&format!(
+            "{}func main() {{ let a = make().width(3).height(4); }}",
+            prelude
+        ))
+        .unwrap_err();
+        assert!(err.message.contains("B has no method 'height'"));
+        // Trait methods count, wherever the impl is
+        analyze(&format!(
+            "{}func main() {{ let s: String = make().name(); }}\ntrait Named {{\n    func name(self) -> String;\n}}\nimpl Named for B {{\n    func name(self) -> String {{ \"b\" }}\n}}",
+            prelude
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn test_literals_take_their_type_from_context() {
+        analyze(
+            "func f(b: u8) -> i64 { return 5000000000; }\n\
+             func main() { let x: u8 = 250; let y = 5 + x; let z = f(7) * 2; let n = -128i8; }",
+        )
+        .unwrap();
+
+        let err = analyze("func main() { let x: u8 = 5; let y = x + 300; }").unwrap_err();
+        assert!(err
+            .message
+            .contains("Literal 300 does not fit in u8 (0 to 255)"));
+        let err = analyze("func main() { let c = 128i8; }").unwrap_err();
+        assert!(err.message.contains("does not fit in i8"));
+        // A suffix wins over the context
+        let err = analyze("func main() { let x: u8 = 5i64; }").unwrap_err();
+        assert!(err.message.contains("expected u8, found i64"));
+    }
+
+    #[test]
+    fn test_bitwise_operands_must_be_integers() {
+        analyze("func main() { let a = 255; let b = (a << 8) | 3 ^ !0; let s = b >> 2; }").unwrap();
+
+        let err = analyze("func main() { let x = 1.5 & 2; }").unwrap_err();
+        assert!(err.message.contains("Left side of '&' must be an integer"));
+
+        // Masks need matching widths, shift amounts don't
+        let err =
+            analyze("func main() { let a = 7 as u8; let b = 3; let m = a & b; }").unwrap_err();
+        assert!(err.message.contains("between u8 and i32"));
+        analyze("func main() { let a = 7 as u8; let s = a << 2; }").unwrap();
+    }
+
+    #[test]
+    fn test_lossy_arithmetic_is_warned_about() {
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer
+            .analyze(&parse(
+                "func main() {\n    let a = 0.1 + 0.2;\n    let _same = a == 0.3;\n    let _half = 7 / 2;\n    let _avg = (7 + 2) as f64;\n    let _mean = (10 / 4) as f64;\n    let n = 3;\n    let _third = (10 / n) as f64;\n}",
+            ))
+            .unwrap();
+        let lints: Vec<&str> = analyzer.warnings().iter().map(|w| w.lint).collect();
+        assert_eq!(
+            lints,
+            vec!["float_eq", "int_division", "int_division", "int_division"]
+        );
+        assert_eq!(analyzer.warnings()[0].location.as_ref().unwrap().line, 3);
+
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer
+            .analyze(&parse(
+                "func main() { let _x = 8 / 2; let _y = 2.0 / 3.0; }",
+            ))
+            .unwrap();
+        assert!(analyzer.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_independent_errors_are_reported_together() {
+        let source = "func a() -> i64 {\n    let x: i64 = \"s\";\n    return x + 1;\n}\n\
+                      func main() {\n    let y = missing;\n    let ok: bool = 5;\n}";
+        let err = SemanticAnalyzer::new().analyze(&parse(source)).unwrap_err();
+        // The failed `let x` still declares x, so `x + 1` adds no error
+        let messages: Vec<&str> = err.all().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages.len(), 3, "{:?}", messages);
+        assert!(messages[0].contains("expected i64, found String"));
+        assert!(messages[1].contains("'missing'"));
+        assert!(messages[2].contains("expected Bool"));
+
+        let err = SemanticAnalyzer::new()
+            .with_max_errors(2)
+            .analyze(&parse(source))
+            .unwrap_err();
+        let kinds: Vec<&str> = err.all().map(|e| e.kind.as_str()).collect();
+        assert_eq!(kinds, ["TypeError", "OwnershipError", "TooManyErrors"]);
+    }
+
+    #[test]
+    fn test_lints_find_unused_unreachable_and_shadowed_code() {
+        let source = "import std::game;\nimport std::math;\nimport std::time;\n\
+                      func pick(n: i32) -> i32 {\n    let unused = 1;\n    let kept = n * 2;\n\
+                      if n > 0 {\n        let kept = 3;\n        return kept;\n    }\n\
+                      return abs(kept);\n    println(\"never\");\n}\n\
+                      func main() {\n    let _ignored = pick(2);\n    let win = Window(10, 10, \"w\");\n}";
+        let found = |analyzer: &SemanticAnalyzer| {
+            analyzer
+                .warnings()
+                .iter()
+                .map(|w| (w.lint, w.location.as_ref().unwrap().line))
+                .collect::<Vec<_>>()
+        };
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&parse(source)).unwrap();
+        assert_eq!(
+            found(&analyzer),
+            [
+                ("unused_import", 3),
+                ("unused_variable", 5),
+                ("shadowed_variable", 8),
+                ("unreachable_code", 12),
+            ]
+        );
+        assert!(analyzer.warnings()[2]
+            .message
+            .contains("declared on line 6"));
+        assert!(analyzer
+            .warnings()
+            .iter()
+            .all(|w| w.severity == Severity::Warning));
+
+        let mut analyzer = SemanticAnalyzer::new().with_denied_warnings();
+        analyzer.analyze(&parse(source)).unwrap();
+        assert!(analyzer
+            .warnings()
+            .iter()
+            .all(|w| w.severity == Severity::Error));
+    }
+
+    #[test]
+    fn test_reanalyze_checks_only_the_edited_function() {
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer
+            .analyze(&parse(
+                "func add(a: i32, b: i32) -> i32 { return a + b; }\nfunc main() { let x = add(1, 2); }",
+            ))
+            .unwrap();
+
+        let broken = parse("func add(a: i32, b: i32) -> i32 { return c; }");
+        assert!(analyzer.reanalyze(&broken.statements).is_err());
+
+        // Same signature: nothing else needs re-checking
+        let fixed = parse("func add(a: i32, b: i32) -> i32 { return b + a; }");
+        assert!(analyzer.reanalyze(&fixed.statements).unwrap().is_empty());
+
+        // New signature: main calls add and is reported as stale
+        let changed = parse("func add(a: i32) -> i32 { return a; }");
+        assert_eq!(
+            analyzer.reanalyze(&changed.statements).unwrap(),
+            vec!["main"]
+        );
+        let main = parse("func main() { let x = add(1, 2); }");
+        assert!(analyzer.reanalyze(&main.statements).is_err());
+    }
+
+    #[test]
+    fn test_reanalyze_rejects_top_level_code() {
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&parse("func main() {}")).unwrap();
+        let err = analyzer
+            .reanalyze(&parse("let x = 1;").statements)
+            .unwrap_err();
+        assert!(err.message.contains("Top-level variable 'x'"));
+    }
+
+    #[test]
+    fn test_if_branches_must_agree() {
+        let err = analyze("func main() { let x = if true { 1 } else { \"one\" }; }").unwrap_err();
+        assert!(err.message.contains("If branches have incompatible types"));
+
+        assert!(analyze("func main() { let x = if true { 1 } else { 2 }; }").is_ok());
+        // A branch without a value leaves nothing to compare
+        assert!(analyze(
+            "func f(a: bool) -> i32 { if a { 1 } else { return 2; } }\nfunc main() {}"
+        )
+        .is_ok());
+    }
+}
@@ -34,11 +34,14 @@ pub struct Binding {
 
 /// Ownership checker tracks variable ownership through scopes
 pub struct OwnershipChecker {
-    bindings: HashMap<String, Binding>,
+    /// Bindings by name; shadowing ones are pushed on top, so the last is the
+    /// innermost binding in scope (bindings are removed when their scope exits)
+    bindings: HashMap<String, Vec<Binding>>,
     scope_depth: usize,
-    scope_id: usize,         // Current unique scope ID
-    next_scope_id: usize,    // Counter for generating unique scope IDs
-    scope_stack: Vec<usize>, // Stack of scope IDs for tracking nested scopes
+    scope_id: usize,      // Current unique scope ID
+    next_scope_id: usize, // Counter for generating unique scope IDs
+    /// Open scopes with the names each defines, innermost last
+    scope_stack: Vec<(usize, Vec<String>)>,
 }
 
 impl OwnershipChecker {
@@ -48,7 +51,7 @@ impl OwnershipChecker {
             scope_depth: 0,
             scope_id: 0,
             next_scope_id: 1,
-            scope_stack: vec![0], // Root scope
+            scope_stack: vec![(0, Vec::new())], // Root scope
         }
     }
 
@@ -57,87 +60,80 @@ impl OwnershipChecker {
         self.scope_depth += 1;
         self.scope_id = self.next_scope_id;
         self.next_scope_id += 1;
-        self.scope_stack.push(self.scope_id);
+        self.scope_stack.push((self.scope_id, Vec::new()));
     }
 
     /// Exit current scope, dropping all bindings in this scope
     pub fn exit_scope(&mut self) -> Vec<String> {
-        let current_scope_id = self.scope_id;
-        let dropped: Vec<_> = self
-            .bindings
-            .iter()
-            .filter(|(_, b)| b.scope_id == current_scope_id)
-            .map(|(name, _)| name.clone())
-            .collect();
+        let dropped = self
+            .scope_stack
+            .pop()
+            .map(|(_, names)| names)
+            .unwrap_or_default();
 
         for name in &dropped {
-            self.bindings.remove(name);
+            if let Some(shadowed) = self.bindings.get_mut(name) {
+                shadowed.pop();
+                if shadowed.is_empty() {
+                    self.bindings.remove(name);
+                }
+            }
         }
 
         self.scope_depth -= 1;
-        self.scope_stack.pop();
         // Restore previous scope ID
-        self.scope_id = *self.scope_stack.last().unwrap_or(&0);
+        self.scope_id = self.scope_stack.last().map_or(0, |(id, _)| *id);
         dropped
     }
 
-    /// Define a new binding with mangled name for scoped uniqueness
+    /// Define a new binding in the current scope
     pub fn define(&mut self, name: &str, mutable: bool, line: usize) -> Result<(), OwnershipError> {
-        // Create a scoped key that includes the scope_id for uniqueness
-        let scoped_key = format!("{}@{}", name, self.scope_id);
-
         // Check if the SAME variable already exists in the SAME scope
-        if let Some(existing) = self.bindings.get(&scoped_key) {
-            return Err(OwnershipError::AlreadyDefined {
-                name: name.to_string(),
-                original_line: existing.defined_at,
-                duplicate_line: line,
-            });
+        // An inner scope may shadow a variable of an outer one
+        if let Some(existing) = self.find_binding(name) {
+            if existing.scope_id == self.scope_id {
+                return Err(OwnershipError::AlreadyDefined {
+                    name: name.to_string(),
+                    original_line: existing.defined_at,
+                    duplicate_line: line,
+                });
+            }
         }
 
-        // Also check the plain name for shadowing in outer scope
-        // This allows inner scope to shadow outer scope variables
-
-        self.bindings.insert(
-            scoped_key,
-            Binding {
+        self.bindings
+            .entry(name.to_string())
+            .or_default()
+            .push(Binding {
                 name: name.to_string(),
                 mutable,
                 ownership: OwnershipState::Owned,
                 defined_at: line,
                 scope_depth: self.scope_depth,
                 scope_id: self.scope_id,
-            },
-        );
+            });
+        if let Some((_, names)) = self.scope_stack.last_mut() {
+            names.push(name.to_string());
+        }
 
         Ok(())
     }
 
-    /// Find the scoped key for a binding, searching from current scope up to root
-    fn find_binding_key(&self, name: &str) -> Option<String> {
-        // Search from current scope up to root
-        for &scope_id in self.scope_stack.iter().rev() {
-            let scoped_key = format!("{}@{}", name, scope_id);
-            if self.bindings.contains_key(&scoped_key) {
-                return Some(scoped_key);
-            }
-        }
-        None
+    /// The innermost binding of a name
+    fn find_binding(&self, name: &str) -> Option<&Binding> {
+        self.bindings.get(name).and_then(|shadowed| shadowed.last())
+    }
+
+    fn find_binding_mut(&mut self, name: &str) -> Option<&mut Binding> {
+        self.bindings
+            .get_mut(name)
+            .and_then(|shadowed| shadowed.last_mut())
     }
 
     /// Use a binding (read access)
     pub fn use_binding(&self, name: &str, line: usize) -> Result<&Binding, OwnershipError> {
         // Search for binding in scope hierarchy
-        let key = self
-            .find_binding_key(name)
-            .ok_or_else(|| OwnershipError::NotDefined {
-                name: name.to_string(),
-                at_line: line,
-            })?;
-
         let binding = self
-            .bindings
-            .get(&key)
+            .find_binding(name)
             .ok_or_else(|| OwnershipError::NotDefined {
                 name: name.to_string(),
                 at_line: line,
@@ -169,14 +165,12 @@ impl OwnershipChecker {
             });
         }
 
-        // Mark as moved - use scoped key
-        if let Some(key) = self.find_binding_key(from) {
-            if let Some(b) = self.bindings.get_mut(&key) {
-                b.ownership = OwnershipState::Moved {
-                    to: to.to_string(),
-                    at_line: line,
-                };
-            }
+        // Mark as moved
+        if let Some(b) = self.find_binding_mut(from) {
+            b.ownership = OwnershipState::Moved {
+                to: to.to_string(),
+                at_line: line,
+            };
         }
 
         Ok(())
@@ -184,16 +178,8 @@ impl OwnershipChecker {
 
     /// Assign to a mutable binding
     pub fn assign(&mut self, name: &str, line: usize) -> Result<(), OwnershipError> {
-        let key = self
-            .find_binding_key(name)
-            .ok_or_else(|| OwnershipError::NotDefined {
-                name: name.to_string(),
-                at_line: line,
-            })?;
-
         let binding = self
-            .bindings
-            .get(&key)
+            .find_binding(name)
             .ok_or_else(|| OwnershipError::NotDefined {
                 name: name.to_string(),
                 at_line: line,
@@ -226,16 +212,8 @@ impl OwnershipChecker {
         borrower: &str,
         line: usize,
     ) -> Result<(), OwnershipError> {
-        let key = self
-            .find_binding_key(name)
-            .ok_or_else(|| OwnershipError::NotDefined {
-                name: name.to_string(),
-                at_line: line,
-            })?;
-
         let binding = self
-            .bindings
-            .get_mut(&key)
+            .find_binding_mut(name)
             .ok_or_else(|| OwnershipError::NotDefined {
                 name: name.to_string(),
                 at_line: line,
@@ -276,16 +254,8 @@ impl OwnershipChecker {
         borrower: &str,
         line: usize,
     ) -> Result<(), OwnershipError> {
-        let key = self
-            .find_binding_key(name)
-            .ok_or_else(|| OwnershipError::NotDefined {
-                name: name.to_string(),
-                at_line: line,
-            })?;
-
         let binding = self
-            .bindings
-            .get_mut(&key)
+            .find_binding_mut(name)
             .ok_or_else(|| OwnershipError::NotDefined {
                 name: name.to_string(),
                 at_line: line,
@@ -324,10 +294,20 @@ impl OwnershipChecker {
         }
     }
 
+    /// End all borrows of a binding before its scope does (e.g. the reference was dropped)
+    pub fn release(&mut self, name: &str) {
+        if let Some(b) = self.find_binding_mut(name) {
+            if let OwnershipState::Borrowed { .. } | OwnershipState::MutablyBorrowed { .. } =
+                &b.ownership
+            {
+                b.ownership = OwnershipState::Owned;
+            }
+        }
+    }
+
     /// Get binding info if it exists
     pub fn get(&self, name: &str) -> Option<&Binding> {
-        self.find_binding_key(name)
-            .and_then(|key| self.bindings.get(&key))
+        self.find_binding(name)
     }
 }
 
@@ -386,6 +366,24 @@ pub enum OwnershipError {
     },
 }
 
+impl OwnershipError {
+    /// Line the offending use is on
+    pub fn line(&self) -> usize {
+        match self {
+            OwnershipError::NotDefined { at_line, .. }
+            | OwnershipError::MovedWhileBorrowed { at_line, .. }
+            | OwnershipError::AssignToImmutable { at_line, .. }
+            | OwnershipError::AssignWhileBorrowed { at_line, .. }
+            | OwnershipError::BorrowWhileMutablyBorrowed { at_line, .. }
+            | OwnershipError::MutBorrowOfImmutable { at_line, .. }
+            | OwnershipError::MutBorrowWhileBorrowed { at_line, .. }
+            | OwnershipError::MutBorrowWhileMutablyBorrowed { at_line, .. } => *at_line,
+            OwnershipError::AlreadyDefined { duplicate_line, .. } => *duplicate_line,
+            OwnershipError::UsedAfterMove { used_at, .. } => *used_at,
+        }
+    }
+}
+
 impl std::fmt::Display for OwnershipError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,111 @@
+//! Analysis profiling for Zyra
+//!
+//! Times the parts of semantic analysis for `zyra check --profile-analysis`.
+//! The ownership, borrow and lifetime checkers run interleaved with type
+//! checking, so their calls are timed one by one and typing gets the rest.
+
+use std::time::Duration;
+
+/// A part of semantic analysis that is timed separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pass {
+    /// Collecting type definitions and function signatures
+    Signatures,
+    /// Type checking and everything else in the statement walk
+    Typing,
+    Ownership,
+    Borrow,
+    Lifetime,
+}
+
+impl Pass {
+    pub const ALL: [Pass; 5] = [
+        Pass::Signatures,
+        Pass::Typing,
+        Pass::Ownership,
+        Pass::Borrow,
+        Pass::Lifetime,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Pass::Signatures => "signatures",
+            Pass::Typing => "typing",
+            Pass::Ownership => "ownership",
+            Pass::Borrow => "borrow",
+            Pass::Lifetime => "lifetime",
+        }
+    }
+}
+
+/// Time spent per pass and per top-level item
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisProfile {
+    passes: [Duration; 5],
+    items: Vec<(String, Duration)>,
+}
+
+impl AnalysisProfile {
+    pub fn add(&mut self, pass: Pass, elapsed: Duration) {
+        self.passes[pass as usize] += elapsed;
+    }
+
+    pub fn pass(&self, pass: Pass) -> Duration {
+        self.passes[pass as usize]
+    }
+
+    /// Record a top-level item, e.g. `func main`. The statement walk counts
+    /// as typing, minus what the checkers spent inside it.
+    pub(crate) fn add_item(&mut self, name: String, elapsed: Duration, checkers: Duration) {
+        self.add(Pass::Typing, elapsed.saturating_sub(checkers));
+        self.items.push((name, elapsed));
+    }
+
+    /// Time spent in the ownership, borrow and lifetime checkers so far
+    pub(crate) fn checkers(&self) -> Duration {
+        self.pass(Pass::Ownership) + self.pass(Pass::Borrow) + self.pass(Pass::Lifetime)
+    }
+
+    pub fn total(&self) -> Duration {
+        self.passes.iter().sum()
+    }
+
+    /// The `count` slowest top-level items, slowest first
+    pub fn slowest_items(&self, count: usize) -> Vec<(&str, Duration)> {
+        let mut items: Vec<(&str, Duration)> = self
+            .items
+            .iter()
+            .map(|(name, elapsed)| (name.as_str(), *elapsed))
+            .collect();
+        items.sort_by_key(|&(_, elapsed)| std::cmp::Reverse(elapsed));
+        items.truncate(count);
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_items_count_as_typing_without_checker_time() {
+        let mut profile = AnalysisProfile::default();
+        profile.add(Pass::Ownership, Duration::from_millis(2));
+        profile.add_item(
+            "func a".to_string(),
+            Duration::from_millis(5),
+            profile.checkers(),
+        );
+        profile.add_item(
+            "func b".to_string(),
+            Duration::from_millis(9),
+            Duration::ZERO,
+        );
+        assert_eq!(profile.pass(Pass::Typing), Duration::from_millis(12));
+        assert_eq!(profile.total(), Duration::from_millis(14));
+        assert_eq!(
+            profile.slowest_items(1),
+            vec![("func b", Duration::from_millis(9))]
+        );
+    }
+}
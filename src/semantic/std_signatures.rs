@@ -0,0 +1,1156 @@
+//! Standard library signatures for semantic analysis
+//!
+//! Every `import std::...` registers the module's functions with the
+//! analyzer. The signatures are built once per process and cloned from
+//! these tables, instead of being rebuilt by every import in every analysis.
+
+use std::collections::HashMap;
+
+use super::{FunctionSignature, ZyraType};
+
+/// Modules with signatures in this table
+const MODULES: &[&str] = &[
+    "std::math",
+    "std::io",
+    "std::time",
+    "std::string",
+    "std::fs",
+    "std::env",
+    "std::list",
+    "std::process",
+    "std::thread",
+    "std::mem",
+    "std::core",
+    "std::ai",
+    "std::debug",
+    "std::test",
+    "std::cli",
+    "std::serde",
+    "std::json",
+    "std::random",
+    "std::net",
+    "std::audio",
+    "std::game",
+];
+
+lazy_static::lazy_static! {
+    static ref SIGNATURES: HashMap<&'static str, Vec<FunctionSignature>> = MODULES
+        .iter()
+        .map(|&module| {
+            let signatures = module_functions(module)
+                .into_iter()
+                .map(|(name, params, return_type)| FunctionSignature {
+                    name: name.to_string(),
+                    params: params
+                        .into_iter()
+                        .map(|(n, t)| (n.to_string(), t))
+                        .collect(),
+                    return_type,
+                    lifetimes: vec![],
+                    has_mut_self: false,
+                })
+                .collect();
+            (module, signatures)
+        })
+        .collect();
+}
+
+/// Signatures of the functions a std module exports, empty for unknown modules
+pub fn module_signatures(module_name: &str) -> &'static [FunctionSignature] {
+    SIGNATURES
+        .get(module_name)
+        .map(|signatures| signatures.as_slice())
+        .unwrap_or(&[])
+}
+
+type FunctionTable = Vec<(&'static str, Vec<(&'static str, ZyraType)>, ZyraType)>;
+
+fn module_functions(module_name: &str) -> FunctionTable {
+    let vec2 = ZyraType::Struct("Vec2".to_string());
+    let vec3 = ZyraType::Struct("Vec3".to_string());
+    let tween = ZyraType::Struct("Tween".to_string());
+    let stopwatch = ZyraType::Struct("Stopwatch".to_string());
+    let window = ZyraType::Struct("Window".to_string());
+    match module_name {
+        "std::math" => vec![
+            // Basic math - polymorphic (accepts int or float)
+            ("abs", vec![("x", ZyraType::Unknown)], ZyraType::Unknown),
+            ("sqrt", vec![("x", ZyraType::Unknown)], ZyraType::F64),
+            (
+                "pow",
+                vec![("base", ZyraType::Unknown), ("exp", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            ("sin", vec![("x", ZyraType::Unknown)], ZyraType::F64),
+            ("cos", vec![("x", ZyraType::Unknown)], ZyraType::F64),
+            ("tan", vec![("x", ZyraType::Unknown)], ZyraType::F64),
+            (
+                "min",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "max",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            ("floor", vec![("x", ZyraType::Unknown)], ZyraType::I64),
+            ("ceil", vec![("x", ZyraType::Unknown)], ZyraType::I64),
+            ("round", vec![("x", ZyraType::Unknown)], ZyraType::I64),
+            (
+                "random",
+                vec![("min", ZyraType::I64), ("max", ZyraType::I64)],
+                ZyraType::I64,
+            ),
+            (
+                "lerp",
+                vec![
+                    ("a", ZyraType::Unknown),
+                    ("b", ZyraType::Unknown),
+                    ("t", ZyraType::Unknown),
+                ],
+                ZyraType::F64,
+            ),
+            (
+                "clamp",
+                vec![
+                    ("x", ZyraType::Unknown),
+                    ("min", ZyraType::Unknown),
+                    ("max", ZyraType::Unknown),
+                ],
+                ZyraType::Unknown,
+            ),
+            ("pi", vec![], ZyraType::F64),
+            ("e", vec![], ZyraType::F64),
+            // Integer arithmetic with the overflow behavior in the name
+            (
+                "wrapping_add",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "wrapping_sub",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "wrapping_mul",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "saturating_add",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "saturating_sub",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "saturating_mul",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "checked_add",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Option(Box::new(ZyraType::Unknown)),
+            ),
+            (
+                "checked_sub",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Option(Box::new(ZyraType::Unknown)),
+            ),
+            (
+                "checked_mul",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Option(Box::new(ZyraType::Unknown)),
+            ),
+            // Noise
+            (
+                "noise2d",
+                vec![
+                    ("x", ZyraType::Unknown),
+                    ("y", ZyraType::Unknown),
+                    ("seed", ZyraType::Unknown),
+                ],
+                ZyraType::F64,
+            ),
+            (
+                "octave_noise2d",
+                vec![
+                    ("x", ZyraType::Unknown),
+                    ("y", ZyraType::Unknown),
+                    ("seed", ZyraType::Unknown),
+                    ("octaves", ZyraType::Unknown),
+                    ("persistence", ZyraType::Unknown),
+                ],
+                ZyraType::F64,
+            ),
+            // Vectors
+            (
+                "vec2",
+                vec![("x", ZyraType::Unknown), ("y", ZyraType::Unknown)],
+                vec2.clone(),
+            ),
+            (
+                "vec2_add",
+                vec![("a", vec2.clone()), ("b", vec2.clone())],
+                vec2.clone(),
+            ),
+            (
+                "vec2_sub",
+                vec![("a", vec2.clone()), ("b", vec2.clone())],
+                vec2.clone(),
+            ),
+            (
+                "vec2_scale",
+                vec![("v", vec2.clone()), ("s", ZyraType::Unknown)],
+                vec2.clone(),
+            ),
+            (
+                "vec2_dot",
+                vec![("a", vec2.clone()), ("b", vec2.clone())],
+                ZyraType::F64,
+            ),
+            ("vec2_length", vec![("v", vec2.clone())], ZyraType::F64),
+            ("vec2_normalize", vec![("v", vec2.clone())], vec2.clone()),
+            (
+                "vec2_distance",
+                vec![("a", vec2.clone()), ("b", vec2.clone())],
+                ZyraType::F64,
+            ),
+            (
+                "vec2_lerp",
+                vec![
+                    ("a", vec2.clone()),
+                    ("b", vec2.clone()),
+                    ("t", ZyraType::Unknown),
+                ],
+                vec2.clone(),
+            ),
+            (
+                "vec2_rotate",
+                vec![("v", vec2.clone()), ("angle", ZyraType::Unknown)],
+                vec2.clone(),
+            ),
+            ("vec2_angle", vec![("v", vec2.clone())], ZyraType::F64),
+            (
+                "vec2_from_angle",
+                vec![("angle", ZyraType::Unknown), ("length", ZyraType::Unknown)],
+                vec2.clone(),
+            ),
+            (
+                "vec3",
+                vec![
+                    ("x", ZyraType::Unknown),
+                    ("y", ZyraType::Unknown),
+                    ("z", ZyraType::Unknown),
+                ],
+                vec3.clone(),
+            ),
+            (
+                "vec3_add",
+                vec![("a", vec3.clone()), ("b", vec3.clone())],
+                vec3.clone(),
+            ),
+            (
+                "vec3_sub",
+                vec![("a", vec3.clone()), ("b", vec3.clone())],
+                vec3.clone(),
+            ),
+            (
+                "vec3_scale",
+                vec![("v", vec3.clone()), ("s", ZyraType::Unknown)],
+                vec3.clone(),
+            ),
+            (
+                "vec3_dot",
+                vec![("a", vec3.clone()), ("b", vec3.clone())],
+                ZyraType::F64,
+            ),
+            (
+                "vec3_cross",
+                vec![("a", vec3.clone()), ("b", vec3.clone())],
+                vec3.clone(),
+            ),
+            ("vec3_length", vec![("v", vec3.clone())], ZyraType::F64),
+            ("vec3_normalize", vec![("v", vec3.clone())], vec3.clone()),
+            (
+                "vec3_distance",
+                vec![("a", vec3.clone()), ("b", vec3.clone())],
+                ZyraType::F64,
+            ),
+            (
+                "vec3_lerp",
+                vec![
+                    ("a", vec3.clone()),
+                    ("b", vec3.clone()),
+                    ("t", ZyraType::Unknown),
+                ],
+                vec3.clone(),
+            ),
+            (
+                "vec3_rotate",
+                vec![
+                    ("v", vec3.clone()),
+                    ("axis", vec3.clone()),
+                    ("angle", ZyraType::Unknown),
+                ],
+                vec3,
+            ),
+        ],
+        "std::io" => vec![
+            ("print", vec![("value", ZyraType::Unknown)], ZyraType::Void),
+            (
+                "println",
+                vec![("value", ZyraType::Unknown)],
+                ZyraType::Void,
+            ),
+            ("eprint", vec![("value", ZyraType::Unknown)], ZyraType::Void),
+            (
+                "eprintln",
+                vec![("value", ZyraType::Unknown)],
+                ZyraType::Void,
+            ),
+            ("input", vec![], ZyraType::String),
+        ],
+        "std::time" => vec![
+            ("now", vec![], ZyraType::I64),
+            ("now_secs", vec![], ZyraType::F64),
+            ("sleep", vec![("ms", ZyraType::I32)], ZyraType::Void),
+            ("sleep_precise", vec![("ms", ZyraType::F64)], ZyraType::Void),
+            (
+                "sleep_until",
+                vec![("timestamp", ZyraType::F64)],
+                ZyraType::Void,
+            ),
+            ("perf_counter", vec![], ZyraType::F64),
+            ("Stopwatch", vec![], stopwatch.clone()),
+            (
+                "stopwatch_start",
+                vec![("stopwatch", stopwatch.clone())],
+                ZyraType::Void,
+            ),
+            (
+                "stopwatch_lap",
+                vec![("stopwatch", stopwatch.clone())],
+                ZyraType::F64,
+            ),
+            (
+                "stopwatch_elapsed",
+                vec![("stopwatch", stopwatch.clone())],
+                ZyraType::F64,
+            ),
+            (
+                "budget",
+                vec![("ms", ZyraType::Unknown), ("section", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            ("monotonic_ms", vec![], ZyraType::I64),
+            ("instant_now", vec![], ZyraType::I64),
+            (
+                "instant_elapsed",
+                vec![("id", ZyraType::I64)],
+                ZyraType::I64,
+            ),
+            ("delta_time", vec![], ZyraType::F32),
+            ("fps", vec![], ZyraType::F32),
+        ],
+        "std::string" => vec![
+            ("string_len", vec![("s", ZyraType::String)], ZyraType::I32),
+            ("byte_len", vec![("s", ZyraType::String)], ZyraType::I32),
+            (
+                "char_at",
+                vec![("s", ZyraType::String), ("index", ZyraType::I64)],
+                ZyraType::Option(Box::new(ZyraType::Char)),
+            ),
+            (
+                "substring",
+                vec![
+                    ("s", ZyraType::String),
+                    ("start", ZyraType::I64),
+                    ("end", ZyraType::I64),
+                ],
+                ZyraType::String,
+            ),
+            ("to_upper", vec![("s", ZyraType::String)], ZyraType::String),
+            ("to_lower", vec![("s", ZyraType::String)], ZyraType::String),
+            ("trim", vec![("s", ZyraType::String)], ZyraType::String),
+            (
+                "contains",
+                vec![("s", ZyraType::String), ("sub", ZyraType::String)],
+                ZyraType::Bool,
+            ),
+            (
+                "replace",
+                vec![
+                    ("s", ZyraType::String),
+                    ("from", ZyraType::String),
+                    ("to", ZyraType::String),
+                ],
+                ZyraType::String,
+            ),
+            (
+                "split",
+                vec![("s", ZyraType::String), ("delim", ZyraType::String)],
+                ZyraType::Vec(Box::new(ZyraType::String)),
+            ),
+            // Numeric parsing
+            ("parse_int", vec![("s", ZyraType::String)], ZyraType::I64),
+            ("parse_float", vec![("s", ZyraType::String)], ZyraType::F64),
+            // String validation
+            ("is_numeric", vec![("s", ZyraType::String)], ZyraType::Bool),
+            ("is_digit", vec![("s", ZyraType::String)], ZyraType::Bool),
+            ("is_alpha", vec![("s", ZyraType::String)], ZyraType::Bool),
+            (
+                "is_alphanumeric",
+                vec![("s", ZyraType::String)],
+                ZyraType::Bool,
+            ),
+            // Type casting functions
+            ("to_i32", vec![("s", ZyraType::String)], ZyraType::I32),
+            ("to_i64", vec![("s", ZyraType::String)], ZyraType::I64),
+            ("to_f32", vec![("s", ZyraType::String)], ZyraType::F32),
+            ("to_f64", vec![("s", ZyraType::String)], ZyraType::F64),
+        ],
+        "std::fs" => vec![
+            (
+                "read_file",
+                vec![("path", ZyraType::String)],
+                ZyraType::String,
+            ),
+            (
+                "write_file",
+                vec![("path", ZyraType::String), ("content", ZyraType::String)],
+                ZyraType::Bool,
+            ),
+            (
+                "file_exists",
+                vec![("path", ZyraType::String)],
+                ZyraType::Bool,
+            ),
+            ("is_file", vec![("path", ZyraType::String)], ZyraType::Bool),
+            ("is_dir", vec![("path", ZyraType::String)], ZyraType::Bool),
+            (
+                "list_dir",
+                vec![("path", ZyraType::String)],
+                ZyraType::Vec(Box::new(ZyraType::String)),
+            ),
+            (
+                "read_file_bytes",
+                vec![("path", ZyraType::String)],
+                ZyraType::Vec(Box::new(ZyraType::I64)),
+            ),
+            (
+                "write_file_bytes",
+                vec![("path", ZyraType::String), ("bytes", ZyraType::Unknown)],
+                ZyraType::Bool,
+            ),
+            ("current_dir", vec![], ZyraType::String),
+        ],
+        "std::env" => vec![
+            ("args", vec![], ZyraType::Vec(Box::new(ZyraType::String))),
+            ("args_count", vec![], ZyraType::I64),
+            (
+                "env_var",
+                vec![("name", ZyraType::String)],
+                ZyraType::String,
+            ),
+            (
+                "env_int",
+                vec![("name", ZyraType::String), ("default", ZyraType::Unknown)],
+                ZyraType::I64,
+            ),
+            (
+                "env_bool",
+                vec![("name", ZyraType::String), ("default", ZyraType::Bool)],
+                ZyraType::Bool,
+            ),
+            (
+                "load_dotenv",
+                vec![("path", ZyraType::String)],
+                ZyraType::I64,
+            ),
+            ("os_name", vec![], ZyraType::String),
+            ("os_arch", vec![], ZyraType::String),
+            ("is_windows", vec![], ZyraType::Bool),
+            ("is_linux", vec![], ZyraType::Bool),
+            ("temp_dir", vec![], ZyraType::String),
+            ("pid", vec![], ZyraType::I64),
+        ],
+        "std::list" => vec![
+            // LinkedList - memory-safe doubly linked list
+            ("list_new", vec![], ZyraType::I64),
+            (
+                "list_push_front",
+                vec![("list", ZyraType::I64), ("value", ZyraType::Unknown)],
+                ZyraType::Bool,
+            ),
+            (
+                "list_push_back",
+                vec![("list", ZyraType::I64), ("value", ZyraType::Unknown)],
+                ZyraType::Bool,
+            ),
+            (
+                "list_pop_front",
+                vec![("list", ZyraType::I64)],
+                ZyraType::Unknown,
+            ),
+            (
+                "list_pop_back",
+                vec![("list", ZyraType::I64)],
+                ZyraType::Unknown,
+            ),
+            (
+                "list_get",
+                vec![("list", ZyraType::I64), ("index", ZyraType::I64)],
+                ZyraType::Unknown,
+            ),
+            (
+                "list_set",
+                vec![
+                    ("list", ZyraType::I64),
+                    ("index", ZyraType::I64),
+                    ("value", ZyraType::Unknown),
+                ],
+                ZyraType::Bool,
+            ),
+            ("list_len", vec![("list", ZyraType::I64)], ZyraType::I64),
+            (
+                "list_is_empty",
+                vec![("list", ZyraType::I64)],
+                ZyraType::Bool,
+            ),
+            (
+                "list_to_array",
+                vec![("list", ZyraType::I64)],
+                ZyraType::Vec(Box::new(ZyraType::Unknown)),
+            ),
+            ("list_clear", vec![("list", ZyraType::I64)], ZyraType::Bool),
+            ("list_delete", vec![("list", ZyraType::I64)], ZyraType::Bool),
+        ],
+        "std::process" => vec![
+            ("exit", vec![("code", ZyraType::I64)], ZyraType::Void),
+            (
+                "set_exit_code",
+                vec![("code", ZyraType::I64)],
+                ZyraType::Void,
+            ),
+            (
+                "on_panic",
+                vec![("handler", ZyraType::Unknown)],
+                ZyraType::Void,
+            ),
+        ],
+        "std::thread" => vec![
+            ("thread_sleep", vec![("ms", ZyraType::I64)], ZyraType::Void),
+            ("thread_yield", vec![], ZyraType::Void),
+            ("thread_id", vec![], ZyraType::I64),
+            ("cpu_cores", vec![], ZyraType::I64),
+        ],
+        "std::mem" => vec![
+            ("size_of", vec![("value", ZyraType::Unknown)], ZyraType::I64),
+            ("drop", vec![("value", ZyraType::Unknown)], ZyraType::Void),
+            (
+                "freeze",
+                vec![("value", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "is_frozen",
+                vec![(
+                    "value",
+                    ZyraType::Reference {
+                        lifetime: None,
+                        mutable: false,
+                        inner: Box::new(ZyraType::Unknown),
+                    },
+                )],
+                ZyraType::Bool,
+            ),
+            (
+                "take",
+                vec![("place", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "swap",
+                vec![("a", ZyraType::Unknown), ("b", ZyraType::Unknown)],
+                ZyraType::Void,
+            ),
+            (
+                "weak",
+                vec![(
+                    "value",
+                    ZyraType::Reference {
+                        lifetime: None,
+                        mutable: false,
+                        inner: Box::new(ZyraType::Unknown),
+                    },
+                )],
+                ZyraType::Struct("Weak".to_string()),
+            ),
+            (
+                "type_of",
+                vec![("value", ZyraType::Unknown)],
+                ZyraType::String,
+            ),
+        ],
+        "std::core" => vec![
+            (
+                "assert",
+                vec![("condition", ZyraType::Bool), ("message", ZyraType::String)],
+                ZyraType::Void,
+            ),
+            (
+                "assert_eq",
+                vec![("left", ZyraType::Unknown), ("right", ZyraType::Unknown)],
+                ZyraType::Void,
+            ),
+            (
+                "assert_ne",
+                vec![("left", ZyraType::Unknown), ("right", ZyraType::Unknown)],
+                ZyraType::Void,
+            ),
+            ("panic", vec![("message", ZyraType::String)], ZyraType::Void),
+            (
+                "is_none",
+                vec![("value", ZyraType::Unknown)],
+                ZyraType::Bool,
+            ),
+            (
+                "is_some",
+                vec![("value", ZyraType::Unknown)],
+                ZyraType::Bool,
+            ),
+            (
+                "unwrap",
+                vec![("value", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+        ],
+        "std::ai" => vec![
+            (
+                "astar",
+                vec![
+                    ("grid", ZyraType::Unknown),
+                    ("start", ZyraType::Unknown),
+                    ("goal", ZyraType::Unknown),
+                    ("passable", ZyraType::Unknown),
+                ],
+                ZyraType::Vec(Box::new(ZyraType::Unknown)),
+            ),
+            (
+                "bfs",
+                vec![
+                    ("grid", ZyraType::Unknown),
+                    ("start", ZyraType::Unknown),
+                    ("goal", ZyraType::Unknown),
+                    ("passable", ZyraType::Unknown),
+                ],
+                ZyraType::Vec(Box::new(ZyraType::Unknown)),
+            ),
+        ],
+        "std::debug" => vec![
+            ("watch", vec![("path", ZyraType::String)], ZyraType::Void),
+            ("dump_watches", vec![], ZyraType::Void),
+        ],
+        "std::test" => vec![
+            (
+                "forall",
+                vec![
+                    ("generator", ZyraType::Unknown),
+                    ("property", ZyraType::Unknown),
+                ],
+                ZyraType::Void,
+            ),
+            (
+                "gen_int",
+                vec![("min", ZyraType::I64), ("max", ZyraType::I64)],
+                ZyraType::Unknown,
+            ),
+            (
+                "gen_string",
+                vec![("max_len", ZyraType::I64)],
+                ZyraType::Unknown,
+            ),
+            (
+                "gen_vec",
+                vec![("element", ZyraType::Unknown), ("max_len", ZyraType::I64)],
+                ZyraType::Unknown,
+            ),
+        ],
+        "std::i18n" => vec![
+            ("load", vec![("path", ZyraType::String)], ZyraType::String),
+            (
+                "tr",
+                vec![("key", ZyraType::String), ("args", ZyraType::Unknown)],
+                ZyraType::String,
+            ),
+            (
+                "set_language",
+                vec![("code", ZyraType::String)],
+                ZyraType::Bool,
+            ),
+            ("language", vec![], ZyraType::String),
+        ],
+        "std::cli" => vec![(
+            "parse",
+            vec![("spec", ZyraType::Unknown)],
+            ZyraType::Object(HashMap::new()),
+        )],
+        "std::serde" => vec![
+            (
+                "to_bytes",
+                vec![("value", ZyraType::Unknown)],
+                ZyraType::Vec(Box::new(ZyraType::I64)),
+            ),
+            (
+                "from_bytes",
+                vec![("bytes", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "validate",
+                vec![("value", ZyraType::Unknown), ("schema", ZyraType::Unknown)],
+                ZyraType::Vec(Box::new(ZyraType::Unknown)),
+            ),
+        ],
+        "std::json" => vec![
+            (
+                "json_parse",
+                vec![("text", ZyraType::String)],
+                ZyraType::Object(HashMap::new()),
+            ),
+            (
+                "json_stringify",
+                vec![("value", ZyraType::Unknown)],
+                ZyraType::String,
+            ),
+        ],
+        "std::random" => vec![
+            ("seed", vec![("n", ZyraType::I64)], ZyraType::Void),
+            (
+                "random",
+                vec![("min", ZyraType::I64), ("max", ZyraType::I64)],
+                ZyraType::I64,
+            ),
+            ("random_f32", vec![], ZyraType::F32),
+            ("random_float", vec![], ZyraType::F64),
+            (
+                "shuffle",
+                vec![("items", ZyraType::Unknown)],
+                ZyraType::Unknown,
+            ),
+            (
+                "choice",
+                vec![("items", ZyraType::Unknown)],
+                ZyraType::Option(Box::new(ZyraType::Unknown)),
+            ),
+        ],
+        "std::net" => {
+            let result = |ok_type| ZyraType::Result {
+                ok_type: Box::new(ok_type),
+                err_type: Box::new(ZyraType::String),
+            };
+            vec![
+                (
+                    "tcp_connect",
+                    vec![("host", ZyraType::String), ("port", ZyraType::I64)],
+                    result(ZyraType::I64),
+                ),
+                (
+                    "tcp_send",
+                    vec![("socket", ZyraType::I64), ("text", ZyraType::String)],
+                    result(ZyraType::I64),
+                ),
+                (
+                    "tcp_recv",
+                    vec![("socket", ZyraType::I64), ("max_bytes", ZyraType::I64)],
+                    result(ZyraType::String),
+                ),
+                ("tcp_close", vec![("socket", ZyraType::I64)], ZyraType::Bool),
+                (
+                    "http_get",
+                    vec![("url", ZyraType::String)],
+                    result(ZyraType::String),
+                ),
+                ("net_timeout", vec![("ms", ZyraType::I64)], ZyraType::Void),
+            ]
+        }
+        "std::audio" => vec![
+            (
+                "load_sound",
+                vec![("path", ZyraType::String)],
+                ZyraType::I64,
+            ),
+            ("play_sound", vec![("sound", ZyraType::I64)], ZyraType::Bool),
+            (
+                "play_music",
+                vec![("path", ZyraType::String), ("looping", ZyraType::Bool)],
+                ZyraType::Bool,
+            ),
+            ("stop_music", vec![], ZyraType::Void),
+            (
+                "set_volume",
+                vec![("volume", ZyraType::F64)],
+                ZyraType::Void,
+            ),
+        ],
+        "std::game" => vec![
+            (
+                "Window",
+                vec![
+                    ("width", ZyraType::I32),
+                    ("height", ZyraType::I32),
+                    ("title", ZyraType::String),
+                ],
+                window.clone(),
+            ),
+            (
+                "window_is_open",
+                vec![("window", window.clone())],
+                ZyraType::Bool,
+            ),
+            (
+                "window_set_title",
+                vec![("window", window.clone()), ("title", ZyraType::String)],
+                window.clone(),
+            ),
+            (
+                "window_set_size",
+                vec![
+                    ("window", window.clone()),
+                    ("width", ZyraType::I32),
+                    ("height", ZyraType::I32),
+                ],
+                window.clone(),
+            ),
+            ("is_open", vec![], ZyraType::Bool),
+            ("clear", vec![], ZyraType::Void),
+            ("display", vec![], ZyraType::Void),
+            (
+                "key_pressed",
+                vec![("key", ZyraType::String)],
+                ZyraType::Bool,
+            ),
+            (
+                "draw_rect",
+                vec![
+                    ("x", ZyraType::I32),
+                    ("y", ZyraType::I32),
+                    ("w", ZyraType::I32),
+                    ("h", ZyraType::I32),
+                ],
+                ZyraType::Void,
+            ),
+            (
+                "draw_rect_color",
+                vec![
+                    ("x", ZyraType::I32),
+                    ("y", ZyraType::I32),
+                    ("w", ZyraType::I32),
+                    ("h", ZyraType::I32),
+                    ("color", ZyraType::I32),
+                ],
+                ZyraType::Void,
+            ),
+            (
+                "draw_digit",
+                vec![
+                    ("x", ZyraType::I32),
+                    ("y", ZyraType::I32),
+                    ("digit", ZyraType::I32),
+                    ("color", ZyraType::I32),
+                ],
+                ZyraType::I32,
+            ),
+            (
+                "draw_number",
+                vec![
+                    ("x", ZyraType::I32),
+                    ("y", ZyraType::I32),
+                    ("num", ZyraType::I32),
+                    ("scale", ZyraType::I32),
+                ],
+                ZyraType::Void,
+            ),
+            (
+                "draw_text_win",
+                vec![
+                    ("x", ZyraType::I32),
+                    ("y", ZyraType::I32),
+                    ("scale", ZyraType::I32),
+                ],
+                ZyraType::Void,
+            ),
+            (
+                "draw_text_lose",
+                vec![
+                    ("x", ZyraType::I32),
+                    ("y", ZyraType::I32),
+                    ("scale", ZyraType::I32),
+                ],
+                ZyraType::Void,
+            ),
+            // Sprites
+            (
+                "load_sprite",
+                vec![("path", ZyraType::String)],
+                ZyraType::I64,
+            ),
+            (
+                "draw_sprite",
+                vec![
+                    ("id", ZyraType::I64),
+                    ("x", ZyraType::I32),
+                    ("y", ZyraType::I32),
+                ],
+                ZyraType::Void,
+            ),
+            (
+                "draw_sprite_scaled",
+                vec![
+                    ("id", ZyraType::I64),
+                    ("x", ZyraType::I32),
+                    ("y", ZyraType::I32),
+                    ("scale", ZyraType::I32),
+                ],
+                ZyraType::Void,
+            ),
+            // Render targets
+            (
+                "create_canvas",
+                vec![("w", ZyraType::I32), ("h", ZyraType::I32)],
+                ZyraType::I64,
+            ),
+            (
+                "draw_to",
+                vec![("canvas", ZyraType::I64), ("draw", ZyraType::Unknown)],
+                ZyraType::Void,
+            ),
+            (
+                "draw_canvas",
+                vec![
+                    ("canvas", ZyraType::I64),
+                    ("x", ZyraType::I32),
+                    ("y", ZyraType::I32),
+                    ("scale", ZyraType::Unknown),
+                ],
+                ZyraType::Void,
+            ),
+            (
+                "game_loop",
+                vec![
+                    ("update", ZyraType::Unknown),
+                    ("render", ZyraType::Unknown),
+                    ("fps", ZyraType::Unknown),
+                ],
+                ZyraType::Void,
+            ),
+            // Camera
+            (
+                "set_camera",
+                vec![("x", ZyraType::Unknown), ("y", ZyraType::Unknown)],
+                ZyraType::Void,
+            ),
+            (
+                "set_zoom",
+                vec![("zoom", ZyraType::Unknown)],
+                ZyraType::Void,
+            ),
+            (
+                "screen_to_world",
+                vec![("x", ZyraType::Unknown), ("y", ZyraType::Unknown)],
+                vec2.clone(),
+            ),
+            // Collision
+            (
+                "rect_overlaps",
+                vec![
+                    ("x1", ZyraType::Unknown),
+                    ("y1", ZyraType::Unknown),
+                    ("w1", ZyraType::Unknown),
+                    ("h1", ZyraType::Unknown),
+                    ("x2", ZyraType::Unknown),
+                    ("y2", ZyraType::Unknown),
+                    ("w2", ZyraType::Unknown),
+                    ("h2", ZyraType::Unknown),
+                ],
+                ZyraType::Bool,
+            ),
+            (
+                "circle_overlaps",
+                vec![
+                    ("x1", ZyraType::Unknown),
+                    ("y1", ZyraType::Unknown),
+                    ("r1", ZyraType::Unknown),
+                    ("x2", ZyraType::Unknown),
+                    ("y2", ZyraType::Unknown),
+                    ("r2", ZyraType::Unknown),
+                ],
+                ZyraType::Bool,
+            ),
+            (
+                "point_in_rect",
+                vec![
+                    ("px", ZyraType::Unknown),
+                    ("py", ZyraType::Unknown),
+                    ("x", ZyraType::Unknown),
+                    ("y", ZyraType::Unknown),
+                    ("w", ZyraType::Unknown),
+                    ("h", ZyraType::Unknown),
+                ],
+                ZyraType::Bool,
+            ),
+            // Icons
+            (
+                "set_window_icon",
+                vec![("path", ZyraType::String)],
+                ZyraType::Bool,
+            ),
+            ("is_icon_supported", vec![], ZyraType::Bool),
+            ("hex_color", vec![("hex", ZyraType::String)], ZyraType::I32),
+            // Easing & tweens
+            (
+                "ease",
+                vec![("kind", ZyraType::String), ("t", ZyraType::Unknown)],
+                ZyraType::F64,
+            ),
+            (
+                "Tween",
+                vec![
+                    ("start", ZyraType::Unknown),
+                    ("end", ZyraType::Unknown),
+                    ("duration", ZyraType::Unknown),
+                    ("kind", ZyraType::String),
+                ],
+                tween.clone(),
+            ),
+            (
+                "tween_update",
+                vec![("tween", tween.clone()), ("dt", ZyraType::Unknown)],
+                tween.clone(),
+            ),
+            ("tween_value", vec![("tween", tween.clone())], ZyraType::F64),
+            ("tween_done", vec![("tween", tween.clone())], ZyraType::Bool),
+            ("tween_reset", vec![("tween", tween.clone())], tween),
+            // Text & mouse
+            (
+                "draw_text",
+                vec![
+                    ("x", ZyraType::Unknown),
+                    ("y", ZyraType::Unknown),
+                    ("text", ZyraType::String),
+                    ("color", ZyraType::Unknown),
+                    ("scale", ZyraType::Unknown),
+                ],
+                ZyraType::Void,
+            ),
+            ("mouse_x", vec![], ZyraType::I64),
+            ("mouse_y", vec![], ZyraType::I64),
+            ("mouse_down", vec![], ZyraType::Bool),
+            ("mouse_clicked", vec![], ZyraType::Bool),
+            // Headless windows
+            (
+                "simulate_key",
+                vec![("key", ZyraType::String), ("down", ZyraType::Bool)],
+                ZyraType::Bool,
+            ),
+            (
+                "simulate_mouse",
+                vec![
+                    ("x", ZyraType::I64),
+                    ("y", ZyraType::I64),
+                    ("down", ZyraType::Bool),
+                ],
+                ZyraType::Void,
+            ),
+            (
+                "simulate_text",
+                vec![("text", ZyraType::String)],
+                ZyraType::Void,
+            ),
+            (
+                "pixel_at",
+                vec![("x", ZyraType::I64), ("y", ZyraType::I64)],
+                ZyraType::I64,
+            ),
+            ("is_headless", vec![], ZyraType::Bool),
+            (
+                "HeadlessWindow",
+                vec![
+                    ("width", ZyraType::I32),
+                    ("height", ZyraType::I32),
+                    ("title", ZyraType::String),
+                ],
+                window,
+            ),
+            (
+                "draw_calls",
+                vec![],
+                ZyraType::Vec(Box::new(ZyraType::String)),
+            ),
+            // Immediate-mode UI
+            (
+                "ui_button",
+                vec![
+                    ("x", ZyraType::Unknown),
+                    ("y", ZyraType::Unknown),
+                    ("label", ZyraType::String),
+                ],
+                ZyraType::Bool,
+            ),
+            (
+                "ui_slider",
+                vec![
+                    ("x", ZyraType::Unknown),
+                    ("y", ZyraType::Unknown),
+                    ("width", ZyraType::Unknown),
+                    ("value", ZyraType::Unknown),
+                    ("min", ZyraType::Unknown),
+                    ("max", ZyraType::Unknown),
+                ],
+                ZyraType::F64,
+            ),
+            (
+                "ui_checkbox",
+                vec![
+                    ("x", ZyraType::Unknown),
+                    ("y", ZyraType::Unknown),
+                    ("label", ZyraType::String),
+                    ("checked", ZyraType::Bool),
+                ],
+                ZyraType::Bool,
+            ),
+            (
+                "ui_text_input",
+                vec![
+                    ("x", ZyraType::Unknown),
+                    ("y", ZyraType::Unknown),
+                    ("width", ZyraType::Unknown),
+                    ("text", ZyraType::String),
+                ],
+                ZyraType::String,
+            ),
+        ],
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signatures_are_built_once() {
+        let first = module_signatures("std::math");
+        let second = module_signatures("std::math");
+        assert!(std::ptr::eq(first, second));
+        assert!(first.iter().any(|sig| sig.name == "sqrt"));
+        assert!(module_signatures("std::nope").is_empty());
+    }
+}
@@ -31,6 +31,12 @@ pub enum ZyraType {
         elem: Box<ZyraType>,
         size: usize,
     },
+    Map {
+        key: Box<ZyraType>,
+        value: Box<ZyraType>,
+    },
+    Set(Box<ZyraType>),
+    Tuple(Vec<ZyraType>),
 
     // Legacy/Complex
     Object(HashMap<String, ZyraType>),
@@ -107,6 +113,22 @@ impl ZyraType {
                 size: *size,
             },
             ast::Type::List(inner) => ZyraType::Vec(Box::new(Self::from_ast_type(inner))),
+            ast::Type::Option(inner) => ZyraType::Option(Box::new(Self::from_ast_type(inner))),
+            ast::Type::Map(key, value) => ZyraType::Map {
+                key: Box::new(Self::from_ast_type(key)),
+                value: Box::new(Self::from_ast_type(value)),
+            },
+            ast::Type::Set(inner) => ZyraType::Set(Box::new(Self::from_ast_type(inner))),
+            ast::Type::Tuple(elements) => {
+                ZyraType::Tuple(elements.iter().map(Self::from_ast_type).collect())
+            }
+            ast::Type::Function {
+                params,
+                return_type,
+            } => ZyraType::Closure {
+                params: params.iter().map(Self::from_ast_type).collect(),
+                return_type: Box::new(Self::from_ast_type(return_type)),
+            },
 
             ast::Type::Object => ZyraType::Object(HashMap::new()),
 
@@ -199,9 +221,40 @@ impl ZyraType {
         matches!(self, ZyraType::F32 | ZyraType::F64)
     }
 
+    /// Whether every value of this type converts exactly to `target` with `as`
+    pub fn widens_to(&self, target: &ZyraType) -> bool {
+        use ZyraType::*;
+        matches!(
+            (self, target),
+            (I8, I32 | I64 | F32 | F64)
+                | (I32, I64 | F64)
+                | (U8, U32 | U64 | I32 | I64 | F32 | F64)
+                | (U32, U64 | I64 | F64)
+                | (F32, F64)
+        )
+    }
+
+    /// Smallest and largest value of an integer type
+    pub fn int_range(&self) -> Option<(i128, i128)> {
+        match self {
+            ZyraType::I8 => Some((i8::MIN as i128, i8::MAX as i128)),
+            ZyraType::I32 => Some((i32::MIN as i128, i32::MAX as i128)),
+            ZyraType::I64 => Some((i64::MIN as i128, i64::MAX as i128)),
+            ZyraType::U8 => Some((0, u8::MAX as i128)),
+            ZyraType::U32 => Some((0, u32::MAX as i128)),
+            ZyraType::U64 => Some((0, u64::MAX as i128)),
+            _ => None,
+        }
+    }
+
     /// Returns true if this type is a Copy type (stack-only, never refcounted).
     /// Copy types are passed by value and can be used multiple times without move.
+    /// The built-in Vec2/Vec3 and std::game Tween values are Copy as well, and
+    /// so are tuples of Copy types.
     pub fn is_copy_type(&self) -> bool {
+        if let ZyraType::Tuple(elements) = self {
+            return elements.iter().all(ZyraType::is_copy_type);
+        }
         matches!(
             self,
             ZyraType::I8
@@ -216,7 +269,14 @@ impl ZyraType {
                 | ZyraType::Char
                 | ZyraType::Void
                 | ZyraType::Never
-        )
+        ) || self.is_vector_type()
+            || matches!(self, ZyraType::Struct(name) if name == "Tween")
+    }
+
+    /// Returns true for the built-in std::math vector types (Vec2, Vec3).
+    /// These are small value types, so they are Copy like the numeric primitives.
+    pub fn is_vector_type(&self) -> bool {
+        matches!(self, ZyraType::Struct(name) if name == "Vec2" || name == "Vec3")
     }
 
     /// Returns true if this type is a Reference type (heap-only, always refcounted).
@@ -227,6 +287,8 @@ impl ZyraType {
             ZyraType::String
                 | ZyraType::Vec(_)
                 | ZyraType::Array { .. }
+                | ZyraType::Map { .. }
+                | ZyraType::Set(_)
                 | ZyraType::Object(_)
                 | ZyraType::Struct(_)
                 | ZyraType::Enum(_)
@@ -287,6 +349,27 @@ impl ZyraType {
 
             // Collections
             (ZyraType::Vec(a), ZyraType::Vec(b)) => a.is_compatible(b),
+            (ZyraType::Map { key: ka, value: va }, ZyraType::Map { key: kb, value: vb }) => {
+                ka.is_compatible(kb) && va.is_compatible(vb)
+            }
+            (ZyraType::Set(a), ZyraType::Set(b)) => a.is_compatible(b),
+            (ZyraType::Tuple(a), ZyraType::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_compatible(b))
+            }
+            (
+                ZyraType::Closure {
+                    params: pa,
+                    return_type: ra,
+                },
+                ZyraType::Closure {
+                    params: pb,
+                    return_type: rb,
+                },
+            ) => {
+                pa.len() == pb.len()
+                    && pa.iter().zip(pb).all(|(a, b)| b.is_compatible(a))
+                    && ra.is_compatible(rb)
+            }
             (ZyraType::Array { elem: a, size: sa }, ZyraType::Array { elem: b, size: sb }) => {
                 sa == sb && a.is_compatible(b)
             }
@@ -400,6 +483,18 @@ impl ZyraType {
             ZyraType::String => "String".to_string(),
 
             ZyraType::Vec(inner) => format!("Vec<{}>", inner.display_name()),
+            ZyraType::Map { key, value } => {
+                format!("Map<{}, {}>", key.display_name(), value.display_name())
+            }
+            ZyraType::Set(inner) => format!("Set<{}>", inner.display_name()),
+            ZyraType::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.display_name()).collect();
+                if elements.len() == 1 {
+                    format!("({},)", elements[0])
+                } else {
+                    format!("({})", elements.join(", "))
+                }
+            }
             ZyraType::Array { elem, size } => format!("[{}; {}]", elem.display_name(), size),
 
             ZyraType::Object(_) => "Object".to_string(),
@@ -488,6 +583,13 @@ impl ZyraType {
 
             // Collections: strict element type AND size matching
             (ZyraType::Vec(a), ZyraType::Vec(b)) => a.is_compatible_strict(b),
+            (ZyraType::Map { key: ka, value: va }, ZyraType::Map { key: kb, value: vb }) => {
+                ka.is_compatible_strict(kb) && va.is_compatible_strict(vb)
+            }
+            (ZyraType::Set(a), ZyraType::Set(b)) => a.is_compatible_strict(b),
+            (ZyraType::Tuple(a), ZyraType::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_compatible_strict(b))
+            }
             (ZyraType::Array { elem: a, size: sa }, ZyraType::Array { elem: b, size: sb }) => {
                 sa == sb && a.is_compatible_strict(b)
             }
@@ -0,0 +1,227 @@
+//! AI module for Zyra standard library
+//!
+//! Provides grid pathfinding for game AI:
+//! - astar: shortest path with a Manhattan heuristic
+//! - bfs: shortest path by breadth-first search
+//!
+//! Both search 4-directionally and expand neighbours in a fixed order
+//! (right, down, left, up), so the same grid always yields the same path.
+
+use crate::compiler::bytecode::Value;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+/// A grid cell coordinate (x = column, y = row)
+pub type Point = (i64, i64);
+
+/// Neighbour expansion order - fixed for deterministic results
+const DIRECTIONS: [Point; 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+// ===== Search =====
+
+/// Find the shortest path from start to goal with A*
+/// `passable[y][x]` tells whether a cell can be walked on.
+/// Returns the path including start and goal, or None if unreachable.
+pub fn astar(passable: &[Vec<bool>], start: Point, goal: Point) -> Option<Vec<Point>> {
+    if !is_open(passable, start) || !is_open(passable, goal) {
+        return None;
+    }
+
+    let heuristic = |p: Point| (p.0 - goal.0).abs() + (p.1 - goal.1).abs();
+
+    // Ordered by (f, h, insertion order) so ties break the same way every run
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Point, Point> = HashMap::new();
+    let mut cost: HashMap<Point, i64> = HashMap::new();
+    let mut seq = 0u64;
+
+    cost.insert(start, 0);
+    open.push(Reverse((heuristic(start), heuristic(start), seq, start)));
+
+    while let Some(Reverse((_, _, _, current))) = open.pop() {
+        if current == goal {
+            return Some(reconstruct(&came_from, start, goal));
+        }
+
+        let current_cost = cost[&current];
+        for next in neighbours(passable, current) {
+            let next_cost = current_cost + 1;
+            let improved = match cost.get(&next) {
+                Some(&known) => next_cost < known,
+                None => true,
+            };
+            if improved {
+                cost.insert(next, next_cost);
+                came_from.insert(next, current);
+                seq += 1;
+                let h = heuristic(next);
+                open.push(Reverse((next_cost + h, h, seq, next)));
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the shortest path from start to goal with breadth-first search
+/// Returns the path including start and goal, or None if unreachable.
+pub fn bfs(passable: &[Vec<bool>], start: Point, goal: Point) -> Option<Vec<Point>> {
+    if !is_open(passable, start) || !is_open(passable, goal) {
+        return None;
+    }
+
+    let mut queue = VecDeque::new();
+    let mut came_from: HashMap<Point, Point> = HashMap::new();
+    queue.push_back(start);
+    came_from.insert(start, start);
+
+    while let Some(current) = queue.pop_front() {
+        if current == goal {
+            return Some(reconstruct(&came_from, start, goal));
+        }
+        for next in neighbours(passable, current) {
+            if let Entry::Vacant(entry) = came_from.entry(next) {
+                entry.insert(current);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+fn is_open(passable: &[Vec<bool>], (x, y): Point) -> bool {
+    if x < 0 || y < 0 {
+        return false;
+    }
+    passable
+        .get(y as usize)
+        .and_then(|row| row.get(x as usize))
+        .copied()
+        .unwrap_or(false)
+}
+
+fn neighbours(passable: &[Vec<bool>], (x, y): Point) -> Vec<Point> {
+    DIRECTIONS
+        .iter()
+        .map(|(dx, dy)| (x + dx, y + dy))
+        .filter(|&p| is_open(passable, p))
+        .collect()
+}
+
+fn reconstruct(came_from: &HashMap<Point, Point>, start: Point, goal: Point) -> Vec<Point> {
+    let mut path = vec![goal];
+    let mut current = goal;
+    while current != start {
+        current = came_from[&current];
+        path.push(current);
+    }
+    path.reverse();
+    path
+}
+
+// ===== Value Conversion =====
+
+/// Read a point from `[x, y]`, `vec[x, y]`, or an object with `x`/`y` fields (e.g. Vec2)
+pub fn point_from_value(v: &Value) -> Option<Point> {
+    match v {
+        Value::Array(items) | Value::Vec(items) | Value::List(items) if items.len() >= 2 => {
+            Some((coord(&items[0])?, coord(&items[1])?))
+        }
+        Value::Object(fields) => Some((coord(fields.get("x")?)?, coord(fields.get("y")?)?)),
+        _ => None,
+    }
+}
+
+/// Convert a path to a Vec of `[x, y]` points (empty if there is no path)
+pub fn path_to_value(path: Option<Vec<Point>>) -> Value {
+    Value::Vec(
+        path.unwrap_or_default()
+            .into_iter()
+            .map(|(x, y)| Value::Array(vec![Value::Int(x), Value::Int(y)]))
+            .collect(),
+    )
+}
+
+/// Get the rows of a grid value (`[[...], [...]]` or a Vec of Vecs)
+pub fn grid_rows(grid: &Value) -> Option<Vec<Vec<Value>>> {
+    match grid {
+        Value::Array(rows) | Value::Vec(rows) | Value::List(rows) => rows
+            .iter()
+            .map(|row| match row {
+                Value::Array(cells) | Value::Vec(cells) | Value::List(cells) => Some(cells.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Default passability when no callback is given: nonzero integers, true and
+/// None are walls; 0, false and any other value are floor
+pub fn default_passable(cell: &Value) -> bool {
+    match cell {
+        Value::Int(n) | Value::I64(n) => *n == 0,
+        Value::I32(n) => *n == 0,
+        Value::Bool(b) => !*b,
+        Value::None => false,
+        _ => true,
+    }
+}
+
+fn coord(v: &Value) -> Option<i64> {
+    match v {
+        Value::Int(n) | Value::I64(n) => Some(*n),
+        Value::I32(n) => Some(*n as i64),
+        Value::Float(f) | Value::F64(f) => Some(f.round() as i64),
+        Value::F32(f) => Some(f.round() as i64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse a grid where '#' is a wall
+    fn grid(rows: &[&str]) -> Vec<Vec<bool>> {
+        rows.iter()
+            .map(|row| row.chars().map(|c| c != '#').collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_astar_around_wall() {
+        let g = grid(&["...", ".#.", "..."]);
+        let path = astar(&g, (0, 0), (2, 2)).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path, vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_bfs_matches_astar_length() {
+        let g = grid(&["....#", ".##.#", "...#.", "#...."]);
+        let a = astar(&g, (0, 0), (4, 3)).unwrap();
+        let b = bfs(&g, (0, 0), (4, 3)).unwrap();
+        assert_eq!(a.len(), b.len());
+        assert_eq!(b, bfs(&g, (0, 0), (4, 3)).unwrap());
+    }
+
+    #[test]
+    fn test_default_passable_treats_zero_as_floor() {
+        assert!(default_passable(&Value::Int(0)));
+        assert!(default_passable(&Value::Bool(false)));
+        assert!(!default_passable(&Value::Int(1)));
+        assert!(!default_passable(&Value::Bool(true)));
+        assert!(!default_passable(&Value::None));
+    }
+
+    #[test]
+    fn test_unreachable_goal() {
+        let g = grid(&[".#.", "##.", "..."]);
+        assert!(astar(&g, (0, 0), (2, 2)).is_none());
+        assert!(bfs(&g, (0, 0), (2, 2)).is_none());
+        assert!(bfs(&g, (0, 0), (5, 5)).is_none());
+    }
+}
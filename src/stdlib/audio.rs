@@ -0,0 +1,240 @@
+//! Audio module for Zyra standard library
+//!
+//! Sound effects and background music:
+//! - load_sound(path): sound id, 0 if the file is missing or not WAV/Ogg Vorbis
+//! - play_sound(id): play a loaded sound once, over whatever else is playing
+//! - play_music(path, looping): replace the music track
+//! - stop_music()
+//! - set_volume(v): master volume, from 0.0 (silent) to 1.0
+//!
+//! Sound goes out through rodio with the `audio` feature, which needs the
+//! system's audio libraries (ALSA on Linux) to build. Without the feature, in
+//! headless runs and on machines without an output device, playback is
+//! silent but every call returns what it would otherwise, so a game behaves
+//! the same either way.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Audio state: loaded sounds and what is playing
+struct AudioState {
+    sounds: HashMap<usize, Arc<[u8]>>, // Encoded sound files by ID
+    next_sound_id: usize,
+    volume: f32,
+    /// Path of the music track playing, if any
+    music: Option<String>,
+    /// Output device, opened by the first sound played; None inside if
+    /// there is no device to open
+    #[cfg(feature = "audio")]
+    output: Option<Option<output::Output>>,
+}
+
+impl AudioState {
+    fn new() -> Self {
+        Self {
+            sounds: HashMap::new(),
+            next_sound_id: 1,
+            volume: 1.0,
+            music: None,
+            #[cfg(feature = "audio")]
+            output: None,
+        }
+    }
+}
+
+thread_local! {
+    static AUDIO_STATE: RefCell<AudioState> = RefCell::new(AudioState::new());
+}
+
+/// Run `f` with the output device and the volume, opening the device first
+/// if needed. Does nothing in headless runs or without a device
+#[cfg(feature = "audio")]
+fn with_output(f: impl FnOnce(&mut output::Output, f32)) {
+    if super::game::is_headless() {
+        return;
+    }
+    AUDIO_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let volume = state.volume;
+        if let Some(output) = state.output.get_or_insert_with(output::Output::open) {
+            f(output, volume);
+        }
+    })
+}
+
+/// Read a sound file, if it holds a format we can play
+fn read_sound(path: &str) -> Option<Arc<[u8]>> {
+    let bytes = std::fs::read(path).ok()?;
+    let is_wav = bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WAVE";
+    let is_ogg = bytes.starts_with(b"OggS");
+    (is_wav || is_ogg).then(|| bytes.into())
+}
+
+/// Load a sound effect
+pub fn load_sound(path: &str) -> i64 {
+    let Some(sound) = read_sound(path) else {
+        return 0; // Return 0 on failure
+    };
+
+    AUDIO_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let id = state.next_sound_id;
+        state.sounds.insert(id, sound);
+        state.next_sound_id += 1;
+        id as i64
+    })
+}
+
+/// Play a loaded sound once; false for an unknown id
+pub fn play_sound(sound_id: i64) -> bool {
+    let sound = AUDIO_STATE.with(|state| state.borrow().sounds.get(&(sound_id as usize)).cloned());
+    let Some(sound) = sound else {
+        return false;
+    };
+    #[cfg(feature = "audio")]
+    with_output(|output, volume| output.play_sound(sound, volume));
+    #[cfg(not(feature = "audio"))]
+    let _ = sound;
+    true
+}
+
+/// Start a music track in place of the current one; false if the file can't
+/// be played, in which case the current track keeps playing
+pub fn play_music(path: &str, looping: bool) -> bool {
+    let Some(track) = read_sound(path) else {
+        return false;
+    };
+
+    AUDIO_STATE.with(|state| state.borrow_mut().music = Some(path.to_string()));
+    #[cfg(feature = "audio")]
+    with_output(|output, volume| output.play_music(track, looping, volume));
+    #[cfg(not(feature = "audio"))]
+    let _ = (track, looping);
+    true
+}
+
+/// Stop the music track, if one is playing
+pub fn stop_music() {
+    AUDIO_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.music = None;
+        #[cfg(feature = "audio")]
+        if let Some(Some(output)) = state.output.as_mut() {
+            output.stop_music();
+        }
+    })
+}
+
+/// Set the volume of music and of sounds played from now on
+pub fn set_volume(volume: f64) {
+    AUDIO_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.volume = volume.clamp(0.0, 1.0) as f32;
+        #[cfg(feature = "audio")]
+        if let Some(Some(output)) = &state.output {
+            output.set_volume(state.volume);
+        }
+    })
+}
+
+#[cfg(feature = "audio")]
+mod output {
+    use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    /// The default output device, kept open while the program runs
+    pub struct Output {
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        music: Option<Sink>,
+    }
+
+    impl Output {
+        pub fn open() -> Option<Self> {
+            let (stream, handle) = OutputStream::try_default().ok()?;
+            Some(Self {
+                _stream: stream,
+                handle,
+                music: None,
+            })
+        }
+
+        pub fn play_sound(&self, sound: Arc<[u8]>, volume: f32) {
+            if let Ok(source) = Decoder::new(Cursor::new(sound)) {
+                let _ = self
+                    .handle
+                    .play_raw(source.amplify(volume).convert_samples());
+            }
+        }
+
+        pub fn play_music(&mut self, track: Arc<[u8]>, looping: bool, volume: f32) {
+            self.stop_music();
+            let Ok(sink) = Sink::try_new(&self.handle) else {
+                return;
+            };
+            let track = Cursor::new(track);
+            if looping {
+                match Decoder::new_looped(track) {
+                    Ok(source) => sink.append(source),
+                    Err(_) => return,
+                }
+            } else {
+                match Decoder::new(track) {
+                    Ok(source) => sink.append(source),
+                    Err(_) => return,
+                }
+            }
+            sink.set_volume(volume);
+            self.music = Some(sink);
+        }
+
+        pub fn stop_music(&mut self) {
+            if let Some(sink) = self.music.take() {
+                sink.stop();
+            }
+        }
+
+        pub fn set_volume(&self, volume: f32) {
+            if let Some(sink) = &self.music {
+                sink.set_volume(volume);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sounds_are_handles_like_sprites() {
+        let path = std::env::temp_dir().join(format!("zyra_audio_{}.wav", std::process::id()));
+        let mut wav = b"RIFF\x24\x00\x00\x00WAVEfmt ".to_vec();
+        wav.extend_from_slice(&[16, 0, 0, 0, 1, 0, 1, 0, 0x44, 0xAC, 0, 0, 0x88, 0x58, 1, 0]);
+        wav.extend_from_slice(&[2, 0, 16, 0]);
+        wav.extend_from_slice(b"data\x00\x00\x00\x00");
+        std::fs::write(&path, &wav).unwrap();
+        let path = path.to_str().unwrap();
+
+        let first = load_sound(path);
+        assert!(first > 0);
+        assert_eq!(load_sound(path), first + 1);
+        assert_eq!(load_sound("no/such.wav"), 0);
+        assert_eq!(load_sound("Cargo.toml"), 0);
+        assert!(play_sound(first));
+        assert!(!play_sound(999));
+
+        assert!(play_music(path, true));
+        assert!(!play_music("Cargo.toml", false));
+        let music = || AUDIO_STATE.with(|state| state.borrow().music.clone());
+        assert_eq!(music().as_deref(), Some(path));
+        stop_music();
+        assert_eq!(music(), None);
+
+        set_volume(3.0);
+        assert_eq!(AUDIO_STATE.with(|state| state.borrow().volume), 1.0);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,75 @@
+//! Capabilities for Zyra standard library
+//!
+//! A project lists what its program may touch outside the VM in zyra.toml:
+//! `needs = ["fs", "audio"]`. Once a project has a `needs` list, the VM
+//! refuses calls into the parts of the standard library it doesn't name.
+//! Projects without one keep every capability.
+
+/// Something outside the VM a program can be granted access to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// Reading and writing files and directories
+    Fs,
+    /// Network connections
+    Net,
+    /// Sound output
+    Audio,
+}
+
+impl Capability {
+    pub const ALL: &'static [Capability] = &[Capability::Fs, Capability::Net, Capability::Audio];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| c.as_str() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Capability::Fs => "fs",
+            Capability::Net => "net",
+            Capability::Audio => "audio",
+        }
+    }
+
+    /// Capability a standard library function needs, if any
+    pub fn required_by(function: &str) -> Option<Self> {
+        let name = function.split("::").last().unwrap_or(function);
+        match name {
+            "read_file" | "write_file" | "file_exists" | "is_file" | "is_dir" | "list_dir"
+            | "read_file_bytes" | "write_file_bytes" | "current_dir" | "load_dotenv" => {
+                Some(Capability::Fs)
+            }
+            "tcp_connect" | "tcp_send" | "tcp_recv" | "tcp_close" | "http_get" | "net_timeout" => {
+                Some(Capability::Net)
+            }
+            "load_sound" | "play_sound" | "play_music" | "stop_music" | "set_volume" => {
+                Some(Capability::Audio)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_of_functions() {
+        assert_eq!(Capability::parse("fs"), Some(Capability::Fs));
+        assert_eq!(Capability::parse("disk"), None);
+        assert_eq!(
+            Capability::required_by("std::fs::read_file"),
+            Some(Capability::Fs)
+        );
+        assert_eq!(
+            Capability::required_by("net::http_get"),
+            Some(Capability::Net)
+        );
+        assert_eq!(
+            Capability::required_by("std::audio::play_sound"),
+            Some(Capability::Audio)
+        );
+        assert_eq!(Capability::required_by("println"), None);
+    }
+}
@@ -0,0 +1,408 @@
+//! Command-line argument parsing for Zyra standard library
+//!
+//! `cli::parse(spec)` turns a declarative spec (an array of strings) into an
+//! options object, one field per flag or positional:
+//!
+//! ```text
+//! "--verbose, -v: Print more output"    bool flag (false unless given)
+//! "--count=3, -c: Number of runs"       option; the default's type (int/float/string) is kept
+//! "--name=: Player name"                string option with an empty default
+//! "input: File to read"                 required positional
+//! "output?: File to write"              optional positional ("" if absent)
+//! "extra...: More files"                remaining positionals as a Vec
+//! ```
+//!
+//! Field names drop the leading dashes and use `_` for `-` (`--dry-run` → `dry_run`).
+//! `--help`/`-h` prints the generated usage and exits with code 0; invalid arguments
+//! print the error plus usage to stderr and exit with code 2.
+//!
+//! `parse` is only dispatched when called as `cli::parse` / `std::cli::parse`,
+//! so user functions named `parse` keep working.
+
+use crate::compiler::bytecode::{OrderedMap, Value};
+use crate::error::{ZyraError, ZyraResult};
+use std::path::Path;
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    /// The running script's name and the arguments given after its path
+    static ref SCRIPT_ARGS: Mutex<(String, Vec<String>)> =
+        Mutex::new(("zyra".to_string(), Vec::new()));
+}
+
+/// A declared `--flag` or `--option=default`
+#[derive(Debug)]
+struct OptionSpec {
+    long: String,
+    short: Option<char>,
+    /// None for bool flags; otherwise the default, whose type values are parsed as
+    default: Option<Value>,
+    help: String,
+}
+
+/// A declared positional argument
+#[derive(Debug)]
+struct PositionalSpec {
+    name: String,
+    optional: bool,
+    rest: bool,
+    help: String,
+}
+
+#[derive(Debug, Default)]
+struct Spec {
+    options: Vec<OptionSpec>,
+    positionals: Vec<PositionalSpec>,
+}
+
+/// Why argument parsing stopped early
+#[derive(Debug, PartialEq)]
+enum Stop {
+    Help,
+    Error(String),
+}
+
+/// Parse this program's arguments against a spec; may exit the process (see module docs)
+pub fn parse(spec: &Value) -> ZyraResult<Value> {
+    let lines: Vec<String> = match spec {
+        Value::Array(items) | Value::Vec(items) | Value::List(items) => {
+            items.iter().map(|v| v.to_string()).collect()
+        }
+        _ => {
+            return Err(ZyraError::new(
+                "CliError",
+                "cli::parse expects an array of spec strings",
+                None,
+            ))
+        }
+    };
+    let spec = parse_spec(&lines).map_err(|e| ZyraError::new("CliError", &e, None))?;
+
+    let (program, args) = script_args();
+    match parse_args(&spec, &args) {
+        Ok(options) => Ok(Value::Object(options)),
+        Err(Stop::Help) => {
+            print!("{}", help_text(&spec, &program));
+            std::process::exit(0);
+        }
+        Err(Stop::Error(msg)) => {
+            eprintln!("error: {}\n", msg);
+            eprint!("{}", help_text(&spec, &program));
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Record the script being run and the arguments after its path, so `parse`
+/// sees `zyra run game.zr --fast` as ("game", ["--fast"]). zyra's own flags
+/// are left out by the caller.
+pub fn set_script_args(script: &str, args: Vec<String>) {
+    let program = Path::new(script)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| script.to_string());
+    *SCRIPT_ARGS.lock().unwrap() = (program, args);
+}
+
+/// The running script's name and the arguments after it
+fn script_args() -> (String, Vec<String>) {
+    SCRIPT_ARGS.lock().unwrap().clone()
+}
+
+// ===== Spec =====
+
+fn parse_spec(lines: &[String]) -> Result<Spec, String> {
+    let mut spec = Spec::default();
+    for line in lines {
+        let (names, help) = match line.split_once(": ") {
+            Some((names, help)) => (names, help.trim().to_string()),
+            None => (line.trim_end_matches(':'), String::new()),
+        };
+
+        let mut long = None;
+        let mut short = None;
+        let mut default = None;
+        let mut positional = None;
+        for token in names.split(',').map(str::trim) {
+            if let Some(name) = token.strip_prefix("--") {
+                let (name, value) = match name.split_once('=') {
+                    Some((name, value)) => (name, Some(typed_default(value))),
+                    None => (name, None),
+                };
+                long = Some(name.to_string());
+                default = value;
+            } else if let Some(name) = token.strip_prefix('-') {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => short = Some(c),
+                    _ => return Err(format!("short flag '{}' must be one character", token)),
+                }
+            } else if !token.is_empty() {
+                positional = Some(token.to_string());
+            }
+        }
+
+        match (long, positional) {
+            (Some(long), None) => spec.options.push(OptionSpec {
+                long,
+                short,
+                default,
+                help,
+            }),
+            (None, Some(name)) if short.is_none() => {
+                let (name, rest) = match name.strip_suffix("...") {
+                    Some(name) => (name.to_string(), true),
+                    None => (name, false),
+                };
+                let (name, optional) = match name.strip_suffix('?') {
+                    Some(name) => (name.to_string(), true),
+                    None => (name, false),
+                };
+                if spec.positionals.last().is_some_and(|p| p.rest) {
+                    return Err(format!("positional '{}' follows a '...' positional", name));
+                }
+                spec.positionals.push(PositionalSpec {
+                    name,
+                    optional,
+                    rest,
+                    help,
+                });
+            }
+            _ => return Err(format!("invalid spec entry '{}'", line)),
+        }
+    }
+    Ok(spec)
+}
+
+/// A default keeps its literal's type: `3` is an int, `0.5` a float, anything else a string
+fn typed_default(text: &str) -> Value {
+    if let Ok(n) = text.parse::<i64>() {
+        Value::Int(n)
+    } else if let Ok(f) = text.parse::<f64>() {
+        Value::Float(f)
+    } else {
+        Value::String(text.to_string())
+    }
+}
+
+fn field_name(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+// ===== Parsing =====
+
+fn parse_args(spec: &Spec, args: &[String]) -> Result<OrderedMap<String, Value>, Stop> {
+    let mut options = OrderedMap::new();
+    for opt in &spec.options {
+        let value = opt.default.clone().unwrap_or(Value::Bool(false));
+        options.insert(field_name(&opt.long), value);
+    }
+
+    let mut positionals = Vec::new();
+    let mut iter = args.iter();
+    let mut only_positionals = false;
+    while let Some(arg) = iter.next() {
+        if only_positionals || arg == "-" || !arg.starts_with('-') {
+            positionals.push(arg.clone());
+            continue;
+        }
+        if arg == "--" {
+            only_positionals = true;
+            continue;
+        }
+        if arg == "--help" || arg == "-h" {
+            return Err(Stop::Help);
+        }
+
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        let opt = spec
+            .options
+            .iter()
+            .find(|o| match flag.strip_prefix("--") {
+                Some(long) => o.long == long,
+                None => flag.len() == 2 && o.short == flag.chars().nth(1),
+            })
+            .ok_or_else(|| Stop::Error(format!("unknown option '{}'", flag)))?;
+
+        let value = match &opt.default {
+            None if inline.is_some() => {
+                return Err(Stop::Error(format!("'{}' does not take a value", flag)))
+            }
+            None => Value::Bool(true),
+            Some(default) => {
+                let text = match inline {
+                    Some(text) => text,
+                    None => iter
+                        .next()
+                        .cloned()
+                        .ok_or_else(|| Stop::Error(format!("'{}' needs a value", flag)))?,
+                };
+                convert(&text, default).ok_or_else(|| {
+                    Stop::Error(format!("invalid value '{}' for '{}'", text, flag))
+                })?
+            }
+        };
+        options.insert(field_name(&opt.long), value);
+    }
+
+    let mut given = positionals.into_iter();
+    for pos in &spec.positionals {
+        let value = if pos.rest {
+            Value::Vec(given.by_ref().map(Value::String).collect())
+        } else {
+            match given.next() {
+                Some(arg) => Value::String(arg),
+                None if pos.optional => Value::String(String::new()),
+                None => return Err(Stop::Error(format!("missing argument <{}>", pos.name))),
+            }
+        };
+        options.insert(field_name(&pos.name), value);
+    }
+    if let Some(extra) = given.next() {
+        return Err(Stop::Error(format!("unexpected argument '{}'", extra)));
+    }
+
+    Ok(options)
+}
+
+/// Parse an option value as the same type as its default
+fn convert(text: &str, default: &Value) -> Option<Value> {
+    match default {
+        Value::Int(_) => text.parse().ok().map(Value::Int),
+        Value::Float(_) => text.parse().ok().map(Value::Float),
+        _ => Some(Value::String(text.to_string())),
+    }
+}
+
+// ===== Help =====
+
+fn help_text(spec: &Spec, program: &str) -> String {
+    let mut usage = format!("Usage: {}", program);
+    if !spec.options.is_empty() {
+        usage.push_str(" [options]");
+    }
+    for pos in &spec.positionals {
+        usage.push(' ');
+        usage.push_str(&match (pos.optional, pos.rest) {
+            (_, true) => format!("[{}...]", pos.name),
+            (true, false) => format!("[{}]", pos.name),
+            (false, false) => format!("<{}>", pos.name),
+        });
+    }
+
+    let mut rows: Vec<(String, String)> = spec
+        .positionals
+        .iter()
+        .map(|p| (p.name.clone(), p.help.clone()))
+        .collect();
+    let arguments = rows.len();
+    for opt in &spec.options {
+        let mut names = match opt.short {
+            Some(c) => format!("-{}, --{}", c, opt.long),
+            None => format!("    --{}", opt.long),
+        };
+        let mut help = opt.help.clone();
+        if let Some(default) = &opt.default {
+            names.push_str(&format!(" <{}>", field_name(&opt.long).to_uppercase()));
+            if default.to_string().is_empty() {
+                help = help.trim().to_string();
+            } else {
+                help = format!("{} (default: {})", help, default)
+                    .trim()
+                    .to_string();
+            }
+        }
+        rows.push((names, help));
+    }
+    rows.push(("-h, --help".to_string(), "Show this help".to_string()));
+
+    let width = rows.iter().map(|(names, _)| names.len()).max().unwrap_or(0);
+    let mut out = format!("{}\n", usage);
+    for (i, (names, help)) in rows.iter().enumerate() {
+        if i == 0 && arguments > 0 {
+            out.push_str("\nArguments:\n");
+        } else if i == arguments {
+            out.push_str("\nOptions:\n");
+        }
+        out.push_str(format!("  {:width$}  {}", names, help, width = width).trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(lines: &[&str]) -> Spec {
+        parse_spec(&lines.iter().map(|l| l.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_script_args_are_the_ones_after_the_script() {
+        set_script_args("games/pong.zr", args(&["--fast"]));
+        assert_eq!(
+            script_args(),
+            ("pong".to_string(), vec!["--fast".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_flags_options_and_positionals() {
+        let spec = spec(&[
+            "--verbose, -v: Print more",
+            "--count=3, -c: Runs",
+            "--dry-run",
+            "input: File",
+            "rest...: More",
+        ]);
+        let opts =
+            parse_args(&spec, &args(&["-v", "a.txt", "--count=7", "b", "--", "-c"])).unwrap();
+        assert_eq!(opts["verbose"], Value::Bool(true));
+        assert_eq!(opts["dry_run"], Value::Bool(false));
+        assert_eq!(opts["count"], Value::Int(7));
+        assert_eq!(opts["input"], Value::String("a.txt".to_string()));
+        assert_eq!(
+            opts["rest"],
+            Value::Vec(vec![Value::String("b".into()), Value::String("-c".into())])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_and_help() {
+        let spec = spec(&["--count=3, -c", "input"]);
+        let err = |list: &[&str]| match parse_args(&spec, &args(list)) {
+            Err(Stop::Error(msg)) => msg,
+            other => panic!("expected error, got {:?}", other),
+        };
+        assert_eq!(err(&["x", "--nope"]), "unknown option '--nope'");
+        assert_eq!(err(&["x", "-c", "many"]), "invalid value 'many' for '-c'");
+        assert_eq!(err(&["x", "-c"]), "'-c' needs a value");
+        assert_eq!(err(&[]), "missing argument <input>");
+        assert_eq!(err(&["x", "y"]), "unexpected argument 'y'");
+        assert_eq!(parse_args(&spec, &args(&["-h"])).unwrap_err(), Stop::Help);
+        assert!(parse_spec(&["-ab".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_help_text() {
+        let spec = spec(&[
+            "--verbose, -v: Print more",
+            "--count=3: Runs",
+            "input?: File",
+        ]);
+        assert_eq!(
+            help_text(&spec, "tool"),
+            "Usage: tool [options] [input]\n\
+             \nArguments:\n  input                File\n\
+             \nOptions:\n  -v, --verbose        Print more\n      --count <COUNT>  Runs (default: 3)\n  -h, --help           Show this help\n"
+        );
+    }
+}
@@ -1,7 +1,7 @@
 //! Core module for Zyra standard library
 //!
 //! Provides foundational types and functions:
-//! - assert() / panic() for runtime checks
+//! - assert() / assert_eq() / assert_ne() / panic() for runtime checks
 //! - Option<T> / Result<T,E> type helpers
 //! - Type introspection utilities
 
@@ -21,6 +21,129 @@ pub fn assert_true(condition: bool, message: &str) -> ZyraResult<Value> {
     }
 }
 
+/// Compare two fully resolved values for assert_eq (`expect_equal`) or assert_ne.
+/// A failure shows both values with their types, and where they start to differ.
+pub fn assert_compare(left: &Value, right: &Value, expect_equal: bool) -> ZyraResult<Value> {
+    if deep_equal(left, right) == expect_equal {
+        return Ok(Value::None);
+    }
+
+    let (name, headline) = if expect_equal {
+        ("assert_eq", "values are not equal")
+    } else {
+        ("assert_ne", "values are equal")
+    };
+    let mut message = format!(
+        "{} failed: {}\n  left:  {}\n  right: {}",
+        name,
+        headline,
+        describe(left),
+        describe(right)
+    );
+    if let Some(difference) = first_difference(left, right) {
+        message.push_str(&format!("\n  {}", difference));
+    }
+    Err(ZyraError::new("AssertionError", &message, None))
+}
+
+/// Structural equality of resolved values: lists element by element, structs field by field
+fn deep_equal(left: &Value, right: &Value) -> bool {
+    let mut pending = vec![(left, right)];
+    while let Some((a, b)) = pending.pop() {
+        match (a, b) {
+            (
+                Value::Array(x) | Value::Vec(x) | Value::List(x),
+                Value::Array(y) | Value::Vec(y) | Value::List(y),
+            ) => {
+                if x.len() != y.len() {
+                    return false;
+                }
+                pending.extend(x.iter().zip(y));
+            }
+            (Value::Object(x), Value::Object(y)) => {
+                if x.len() != y.len() {
+                    return false;
+                }
+                for (key, value) in x {
+                    match y.get(key) {
+                        Some(other) => pending.push((value, other)),
+                        None => return false,
+                    }
+                }
+            }
+            (Value::Set(x), Value::Set(y)) => {
+                if x != y {
+                    return false;
+                }
+            }
+            (Value::Map(x), Value::Map(y)) => {
+                if x.len() != y.len() {
+                    return false;
+                }
+                for (key, value) in x {
+                    match y.get(key) {
+                        Some(other) => pending.push((value, other)),
+                        None => return false,
+                    }
+                }
+            }
+            (Value::Some(x), Value::Some(y))
+            | (Value::Ok(x), Value::Ok(y))
+            | (Value::Err(x), Value::Err(y)) => pending.push((x, y)),
+            _ => {
+                if !matches!(a.eq(b), Value::Bool(true)) {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+/// A value followed by its type, with strings quoted so whitespace shows.
+/// Structs list their fields in name order so both sides line up.
+pub fn describe(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{:?} (String)", s),
+        Value::Object(fields) => {
+            let mut names: Vec<&String> = fields.keys().filter(|k| *k != "_type").collect();
+            names.sort();
+            let body: Vec<String> = names
+                .iter()
+                .map(|name| format!("{}: {}", name, fields[*name]))
+                .collect();
+            match fields.get("_type") {
+                Some(Value::String(type_name)) => {
+                    format!("{} {{ {} }} ({})", type_name, body.join(", "), type_name)
+                }
+                _ => format!("{{ {} }} (Object)", body.join(", ")),
+            }
+        }
+        other => format!("{} ({})", other, other.type_name()),
+    }
+}
+
+/// Where two unequal strings or lists first differ
+fn first_difference(left: &Value, right: &Value) -> Option<String> {
+    match (left, right) {
+        (Value::String(a), Value::String(b)) => {
+            let index = a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count();
+            Some(format!("first difference at character {}", index))
+        }
+        (
+            Value::Array(a) | Value::Vec(a) | Value::List(a),
+            Value::Array(b) | Value::Vec(b) | Value::List(b),
+        ) => match a.iter().zip(b).position(|(x, y)| !deep_equal(x, y)) {
+            Some(index) => Some(format!("first difference at element {}", index)),
+            None if a.len() != b.len() => {
+                Some(format!("lengths differ: {} vs {}", a.len(), b.len()))
+            }
+            None => None,
+        },
+        _ => None,
+    }
+}
+
 /// Panic with a message - halts execution
 pub fn panic(message: &str) -> ZyraResult<Value> {
     Err(ZyraError::new(
@@ -75,6 +198,8 @@ pub fn type_of(value: &Value) -> String {
         Value::Ok(_) => "Ok".to_string(),
         Value::Err(_) => "Err".to_string(),
         Value::Object(_) => "Object".to_string(),
+        Value::Map(_) => "Map".to_string(),
+        Value::Set(_) => "Set".to_string(),
         Value::Reference { .. } => "Reference".to_string(),
         Value::Function { .. } => "Function".to_string(),
         Value::Window(_) => "Window".to_string(),
@@ -206,3 +331,37 @@ where
         other => f(other),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assert_eq_compares_structurally() {
+        let list = |items: &[i64]| Value::Array(items.iter().map(|&i| Value::Int(i)).collect());
+        assert!(assert_compare(&list(&[1, 2]), &list(&[1, 2]), true).is_ok());
+        assert!(assert_compare(&list(&[1, 2]), &list(&[1, 2]), false).is_err());
+
+        let err = assert_compare(&list(&[1, 2, 3]), &list(&[1, 5, 3]), true).unwrap_err();
+        assert!(err.message.contains("left:  [1, 2, 3] (Array)"));
+        assert!(err.message.contains("first difference at element 1"));
+    }
+
+    #[test]
+    fn test_assert_eq_shows_structs_and_strings() {
+        let point = |x: i64| {
+            let mut fields = crate::compiler::bytecode::OrderedMap::new();
+            fields.insert("_type".to_string(), Value::String("Point".to_string()));
+            fields.insert("y".to_string(), Value::Int(0));
+            fields.insert("x".to_string(), Value::Int(x));
+            Value::Object(fields)
+        };
+        let err = assert_compare(&point(1), &point(2), true).unwrap_err();
+        assert!(err.message.contains("right: Point { x: 2, y: 0 } (Point)"));
+
+        let text = |s: &str| Value::String(s.to_string());
+        let err = assert_compare(&text("abc"), &text("abd"), true).unwrap_err();
+        assert!(err.message.contains("\"abd\" (String)"));
+        assert!(err.message.contains("first difference at character 2"));
+    }
+}
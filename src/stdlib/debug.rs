@@ -0,0 +1,89 @@
+//! Debug module for Zyra standard library (`std::debug`)
+//!
+//! `watch("player.hp")` records a variable's value, or a field path into it,
+//! on every loop iteration (each frame of a game loop). The last `HISTORY`
+//! samples are kept in a ring buffer and shown when the program stops with an
+//! error, or printed on demand with `dump_watches()`.
+
+use std::collections::VecDeque;
+
+/// Samples kept per watch
+pub const HISTORY: usize = 32;
+
+/// A watched variable path and its most recent values
+pub struct Watch {
+    /// Variable name followed by field names, e.g. ["player", "hp"]
+    pub path: Vec<String>,
+    /// (iteration, value) pairs, oldest first
+    samples: VecDeque<(u64, String)>,
+}
+
+impl Watch {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path
+                .split('.')
+                .map(|part| part.trim().to_string())
+                .collect(),
+            samples: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.path.join(".")
+    }
+
+    /// Record a value, dropping the oldest sample once the buffer is full
+    pub fn record(&mut self, iteration: u64, value: String) {
+        if self.samples.len() == HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((iteration, value));
+    }
+
+    /// One line: the watched path, the iterations covered and the values in order
+    pub fn summary(&self) -> String {
+        match (self.samples.front(), self.samples.back()) {
+            (Some((first, _)), Some((last, _))) => {
+                let values: Vec<&str> = self.samples.iter().map(|(_, v)| v.as_str()).collect();
+                format!(
+                    "watch {}, iterations {}-{}: {}",
+                    self.name(),
+                    first,
+                    last,
+                    values.join(", ")
+                )
+            }
+            _ => format!("watch {}: no samples (never in scope)", self.name()),
+        }
+    }
+}
+
+/// Summaries of all watches, one per line
+pub fn dump(watches: &[Watch]) -> String {
+    watches
+        .iter()
+        .map(Watch::summary)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_keeps_only_recent_samples() {
+        let mut watch = Watch::new("player.hp");
+        assert_eq!(
+            watch.summary(),
+            "watch player.hp: no samples (never in scope)"
+        );
+        for i in 0..HISTORY as u64 + 3 {
+            watch.record(i, i.to_string());
+        }
+        let summary = watch.summary();
+        assert!(summary.starts_with("watch player.hp, iterations 3-34: 3, 4, "));
+        assert!(summary.ends_with(", 33, 34"));
+    }
+}
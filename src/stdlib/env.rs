@@ -2,10 +2,11 @@
 //!
 //! Provides access to:
 //! - Command line arguments
-//! - Environment variables
+//! - Environment variables (including `.env` files and typed getters)
 //! - System information
 
 use crate::compiler::bytecode::Value;
+use crate::error::{ZyraError, ZyraResult};
 
 /// Get command line arguments
 pub fn args() -> Value {
@@ -34,6 +35,97 @@ pub fn env_var(name: &str) -> Value {
     }
 }
 
+/// Get an environment variable as an integer, or `default` if unset or not a number
+pub fn env_int(name: &str, default: i64) -> i64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+/// Get an environment variable as a bool, or `default` if unset or unrecognized
+/// Accepts 1/true/yes/on and 0/false/no/off (case-insensitive).
+pub fn env_bool(name: &str, default: bool) -> bool {
+    match std::env::var(name) {
+        Ok(v) => match v.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => true,
+            "0" | "false" | "no" | "off" => false,
+            _ => default,
+        },
+        Err(_) => default,
+    }
+}
+
+/// Load `KEY=VALUE` lines from a .env file into the process environment
+/// Variables that are already set keep their value, and a missing file loads nothing.
+/// Returns the number of variables set.
+pub fn load_dotenv(path: &str) -> ZyraResult<i64> {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(ZyraError::new(
+                "FileError",
+                &format!("Failed to read '{}': {}", path, e),
+                None,
+            ))
+        }
+    };
+    let pairs = parse_dotenv(&source).map_err(|(line, msg)| {
+        ZyraError::new("EnvError", &format!("{}:{}: {}", path, line, msg), None)
+    })?;
+
+    let mut loaded = 0;
+    for (key, value) in pairs {
+        if std::env::var_os(&key).is_none() {
+            std::env::set_var(key, value);
+            loaded += 1;
+        }
+    }
+    Ok(loaded)
+}
+
+/// Parse .env lines; errors carry the 1-based line number
+/// Supports `#` comments, an optional `export ` prefix, double-quoted values with
+/// `\n`/`\"` escapes, single-quoted literal values, and trailing ` #` comments.
+fn parse_dotenv(source: &str) -> Result<Vec<(String, String)>, (usize, String)> {
+    let mut pairs = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| (i + 1, format!("expected 'KEY=VALUE', found '{}'", line)))?;
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err((i + 1, format!("invalid variable name '{}'", key)));
+        }
+
+        let value = value.trim();
+        let value = if let Some(rest) = value.strip_prefix('"') {
+            let end = rest
+                .rfind('"')
+                .ok_or_else(|| (i + 1, "unterminated double quote".to_string()))?;
+            rest[..end].replace("\\n", "\n").replace("\\\"", "\"")
+        } else if let Some(rest) = value.strip_prefix('\'') {
+            let end = rest
+                .rfind('\'')
+                .ok_or_else(|| (i + 1, "unterminated single quote".to_string()))?;
+            rest[..end].to_string()
+        } else {
+            match value.find(" #") {
+                Some(comment) => value[..comment].trim_end().to_string(),
+                None => value.to_string(),
+            }
+        };
+        pairs.push((key.to_string(), value));
+    }
+    Ok(pairs)
+}
+
 /// Set an environment variable (for current process)
 pub fn set_env_var(name: &str, value: &str) {
     std::env::set_var(name, value);
@@ -109,3 +201,35 @@ pub fn is_linux() -> bool {
 pub fn is_macos() -> bool {
     cfg!(target_os = "macos")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let source = "# settings\nexport PORT=8080\nNAME = \"Zyra \\\"game\\\"\"\nRAW='a\\nb'\nDEBUG=on # local only\n";
+        let pairs = parse_dotenv(source).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("PORT".to_string(), "8080".to_string()),
+                ("NAME".to_string(), "Zyra \"game\"".to_string()),
+                ("RAW".to_string(), "a\\nb".to_string()),
+                ("DEBUG".to_string(), "on".to_string()),
+            ]
+        );
+        assert_eq!(parse_dotenv("A=1\nnot a pair").unwrap_err().0, 2);
+        assert_eq!(parse_dotenv("X=\"open").unwrap_err().0, 1);
+    }
+
+    #[test]
+    fn test_typed_getters() {
+        std::env::set_var("ZYRA_TEST_ENV_INT", " 42 ");
+        std::env::set_var("ZYRA_TEST_ENV_BOOL", "Yes");
+        assert_eq!(env_int("ZYRA_TEST_ENV_INT", 0), 42);
+        assert_eq!(env_int("ZYRA_TEST_ENV_BOOL", 7), 7);
+        assert!(env_bool("ZYRA_TEST_ENV_BOOL", false));
+        assert!(!env_bool("ZYRA_TEST_ENV_MISSING", false));
+    }
+}
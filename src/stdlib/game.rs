@@ -1,109 +1,207 @@
 //! Game module for Zyra standard library
 //!
-//! Provides window creation, drawing, and input handling for 2D games
-
-use crate::compiler::bytecode::{Value, WindowState};
-use minifb::{Key, Window, WindowOptions};
+//! Provides window creation, drawing, input handling, and easing/tweens for 2D games
+//!
+//! Rectangles, sprites and canvases are drawn in world coordinates, which the
+//! camera (`set_camera`, `set_zoom`) maps to the window. Text, numbers and the
+//! UI widgets are drawn in window coordinates, so a HUD stays put while the
+//! world scrolls. Drawing into a canvas ignores the camera.
+
+use super::render::{Backend, Key, Renderer};
+use crate::compiler::bytecode::{OrderedMap, Value, WindowState};
+use crate::error::{ZyraError, ZyraResult};
 use std::collections::HashMap;
 use std::path::Path;
 
-/// Sprite data: RGBA pixels converted to u32 for minifb
+/// Sprite data: RGBA pixels converted to u32 (ARGB), like the window buffer
 #[derive(Clone)]
 pub struct Sprite {
-    pub pixels: Vec<u32>, // ARGB format for minifb
+    pub pixels: Vec<u32>, // ARGB format
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Off-screen render target: drawn into with `draw_to`, onto the screen with `draw_canvas`
+pub struct Canvas {
+    pub pixels: Vec<u32>, // RGB, like the window buffer
     pub width: usize,
     pub height: usize,
 }
 
+/// View onto the game world
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// World point shown at the window's top-left corner
+    pub x: f64,
+    pub y: f64,
+    /// Window pixels per world unit
+    pub zoom: f64,
+}
+
+impl Camera {
+    /// Window pixel a world point lands on
+    pub fn to_screen(&self, x: i64, y: i64) -> (i64, i64) {
+        (
+            ((x as f64 - self.x) * self.zoom).floor() as i64,
+            ((y as f64 - self.y) * self.zoom).floor() as i64,
+        )
+    }
+
+    /// World point under a window pixel
+    pub fn to_world(&self, x: f64, y: f64) -> (f64, f64) {
+        (x / self.zoom + self.x, y / self.zoom + self.y)
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+        }
+    }
+}
+
+/// Seconds each headless frame lasts, whatever the real time taken
+pub const HEADLESS_DELTA: f64 = 1.0 / 60.0;
+
+/// Frames a headless window stays open unless told otherwise
+pub const HEADLESS_FRAME_LIMIT: u64 = 600;
+
+/// Keys reported by `key_pressed`, and accepted by `simulate_key`
+const KEY_MAPPINGS: [(&str, Key); 15] = [
+    ("W", Key::W),
+    ("w", Key::W),
+    ("A", Key::A),
+    ("a", Key::A),
+    ("S", Key::S),
+    ("s", Key::S),
+    ("D", Key::D),
+    ("d", Key::D),
+    ("Up", Key::Up),
+    ("Down", Key::Down),
+    ("Left", Key::Left),
+    ("Right", Key::Right),
+    ("Space", Key::Space),
+    ("Enter", Key::Enter),
+    ("Escape", Key::Escape),
+];
+
 /// Global game state
 pub struct GameState {
-    pub window: Option<Window>,
+    /// Where frames go, while a window is open
+    pub renderer: Option<Box<dyn Renderer>>,
+    /// Backend the next window is opened with
+    pub backend: Backend,
     pub buffer: Vec<u32>,
     pub width: usize,
     pub height: usize,
     pub keys_pressed: HashMap<String, bool>,
     pub running: bool,
     pub sprites: HashMap<usize, Sprite>, // Sprite storage by ID
+    /// Decoded texture atlases and their manifests, by atlas path
+    pub atlases: HashMap<String, (Sprite, Vec<crate::assets::AtlasEntry>)>,
     pub next_sprite_id: usize,
+    pub mouse_x: i64,
+    pub mouse_y: i64,
+    pub mouse_down: bool,
+    pub mouse_clicked: bool,    // Pressed this frame (was up last frame)
+    pub typed_chars: Vec<char>, // Text typed this frame ('\u{8}' = backspace)
+    pub canvases: HashMap<usize, Canvas>, // Canvas storage by ID
+    pub next_canvas_id: usize,
+    /// Canvases being drawn into, innermost last. While one is active its pixels
+    /// are swapped into `buffer`, so every drawing function targets it
+    pub targets: Vec<usize>,
+    pub camera: Camera,
 }
 
 impl GameState {
     pub fn new() -> Self {
         Self {
-            window: None,
+            renderer: None,
+            backend: Backend::from_env(),
             buffer: Vec::new(),
             width: 0,
             height: 0,
             keys_pressed: HashMap::new(),
             running: false,
             sprites: HashMap::new(),
+            atlases: HashMap::new(),
             next_sprite_id: 1,
+            mouse_x: 0,
+            mouse_y: 0,
+            mouse_down: false,
+            mouse_clicked: false,
+            typed_chars: Vec::new(),
+            canvases: HashMap::new(),
+            next_canvas_id: 1,
+            targets: Vec::new(),
+            camera: Camera::default(),
         }
     }
 
     /// Create a new window
     pub fn create_window(&mut self, width: usize, height: usize, title: &str) -> bool {
-        let options = WindowOptions {
-            resize: false,
-            scale: minifb::Scale::X1,
-            ..WindowOptions::default()
-        };
-
-        match Window::new(title, width, height, options) {
-            Ok(win) => {
-                self.window = Some(win);
+        match self.backend.open(width, height, title) {
+            Some(renderer) => {
+                self.renderer = Some(renderer);
                 self.buffer = vec![0; width * height];
                 self.width = width;
                 self.height = height;
                 self.running = true;
                 true
             }
-            Err(_) => false,
+            None => false,
         }
     }
 
     /// Check if window is still open
     pub fn is_open(&mut self) -> bool {
-        if let Some(ref window) = self.window {
-            window.is_open() && !window.is_key_down(Key::Escape)
-        } else {
-            false
+        match &self.renderer {
+            Some(renderer) => self.running && renderer.is_open(),
+            None => false,
         }
     }
 
+    fn is_key_down(&self, key: Key) -> bool {
+        self.renderer
+            .as_ref()
+            .is_some_and(|renderer| renderer.is_key_down(key))
+    }
+
     /// Update key states
     pub fn update_keys(&mut self) {
-        if let Some(ref window) = self.window {
-            // Clear previous state
-            self.keys_pressed.clear();
-
-            // Check common game keys
-            let key_mappings = [
-                ("W", Key::W),
-                ("w", Key::W),
-                ("A", Key::A),
-                ("a", Key::A),
-                ("S", Key::S),
-                ("s", Key::S),
-                ("D", Key::D),
-                ("d", Key::D),
-                ("Up", Key::Up),
-                ("Down", Key::Down),
-                ("Left", Key::Left),
-                ("Right", Key::Right),
-                ("Space", Key::Space),
-                ("Enter", Key::Enter),
-                ("Escape", Key::Escape),
-            ];
-
-            for (name, key) in key_mappings.iter() {
-                if window.is_key_down(*key) {
-                    self.keys_pressed.insert(name.to_string(), true);
-                }
+        self.keys_pressed.clear();
+        for (name, key) in KEY_MAPPINGS.iter() {
+            if self.is_key_down(*key) {
+                self.keys_pressed.insert(name.to_string(), true);
             }
         }
     }
 
+    /// Update mouse position and button state
+    pub fn update_mouse(&mut self) {
+        let Some(renderer) = &self.renderer else {
+            return;
+        };
+        if let Some((mx, my)) = renderer.mouse_pos() {
+            self.mouse_x = mx;
+            self.mouse_y = my;
+        }
+        let down = renderer.mouse_down();
+        self.mouse_clicked = down && !self.mouse_down;
+        self.mouse_down = down;
+    }
+
+    /// Collect characters typed since the last frame (for text input)
+    pub fn update_typed_chars(&mut self) {
+        self.typed_chars = match &mut self.renderer {
+            Some(renderer) => renderer.take_typed_chars(),
+            None => Vec::new(),
+        };
+    }
+
     /// Check if a key is pressed
     pub fn is_key_pressed(&self, key: &str) -> bool {
         self.keys_pressed.get(key).copied().unwrap_or(false)
@@ -119,33 +217,56 @@ impl GameState {
         self.buffer.fill(color);
     }
 
-    /// Draw a filled rectangle
+    /// Draw a filled rectangle in window pixels, clipped to the window
     pub fn draw_rect(&mut self, x: i64, y: i64, w: i64, h: i64, color: u32) {
-        let x = x.max(0) as usize;
-        let y = y.max(0) as usize;
-        let w = w.max(0) as usize;
-        let h = h.max(0) as usize;
-
-        for dy in 0..h {
-            for dx in 0..w {
-                let px = x + dx;
-                let py = y + dy;
-                if px < self.width && py < self.height {
-                    self.buffer[py * self.width + px] = color;
-                }
-            }
+        let x0 = x.clamp(0, self.width as i64) as usize;
+        let x1 = x.saturating_add(w.max(0)).clamp(0, self.width as i64) as usize;
+        let y0 = y.clamp(0, self.height as i64) as usize;
+        let y1 = y.saturating_add(h.max(0)).clamp(0, self.height as i64) as usize;
+
+        for py in y0..y1 {
+            self.buffer[py * self.width + x0..py * self.width + x1].fill(color);
+        }
+    }
+
+    /// Camera drawing goes through: none while drawing into a canvas
+    fn view(&self) -> Camera {
+        if self.targets.is_empty() {
+            self.camera
+        } else {
+            Camera::default()
+        }
+    }
+
+    /// Draw a filled rectangle in world coordinates
+    pub fn draw_world_rect(&mut self, x: i64, y: i64, w: i64, h: i64, color: u32) {
+        let view = self.view();
+        let (x0, y0) = view.to_screen(x, y);
+        let (x1, y1) = view.to_screen(x.saturating_add(w), y.saturating_add(h));
+        self.draw_rect(x0, y0, x1 - x0, y1 - y0, color);
+    }
+
+    /// Swap a canvas's pixels with the current drawing buffer
+    fn swap_target(&mut self, id: usize) {
+        if let Some(canvas) = self.canvases.get_mut(&id) {
+            std::mem::swap(&mut self.buffer, &mut canvas.pixels);
+            std::mem::swap(&mut self.width, &mut canvas.width);
+            std::mem::swap(&mut self.height, &mut canvas.height);
         }
     }
 
     /// Display the buffer to the window
     pub fn display(&mut self) {
-        if let Some(ref mut window) = self.window {
-            window
-                .update_with_buffer(&self.buffer, self.width, self.height)
-                .ok();
-            // Update key states after display
-            self.update_keys();
+        match &mut self.renderer {
+            Some(renderer) if self.running => {
+                renderer.present(&self.buffer, self.width, self.height)
+            }
+            _ => return,
         }
+        // Update key states after display
+        self.update_keys();
+        self.update_mouse();
+        self.update_typed_chars();
     }
 }
 
@@ -155,7 +276,7 @@ impl Default for GameState {
     }
 }
 
-// Thread-local game state (Window is not Send/Sync so we use thread_local instead of lazy_static)
+// Thread-local game state (renderers aren't Send/Sync so we use thread_local instead of lazy_static)
 thread_local! {
     pub static GAME_STATE: std::cell::RefCell<GameState> = std::cell::RefCell::new(GameState::new());
 }
@@ -185,6 +306,15 @@ pub fn create_window(width: i64, height: i64, title: &str) -> Value {
     })
 }
 
+/// Create a window that is always simulated, like one in a `--headless` run.
+/// From then on every window the program opens is headless
+pub fn create_headless_window(width: i64, height: i64, title: &str) -> Value {
+    if !is_headless() {
+        set_headless(HEADLESS_FRAME_LIMIT);
+    }
+    create_window(width, height, title)
+}
+
 /// Try to set the default Zyra window icon
 /// Windows: looks for zyra.ico in installed and development paths
 /// Linux/Other: looks for zyra.png or zyra.ico
@@ -289,6 +419,72 @@ pub fn window_is_open() -> bool {
     GAME_STATE.with(|state| state.borrow_mut().is_open())
 }
 
+/// Change the window's title. Returns the window so calls can be chained:
+/// `win.set_title("Pong").set_size(800, 600)`
+pub fn window_set_title(window: &Value, title: &str) -> Value {
+    GAME_STATE.with(|state| {
+        if let Some(renderer) = &mut state.borrow_mut().renderer {
+            renderer.set_title(title);
+        }
+    });
+    match window {
+        Value::Window(win) => Value::Window(WindowState {
+            title: title.to_string(),
+            ..win.clone()
+        }),
+        other => other.clone(),
+    }
+}
+
+/// Change the window's size, returning the resized window, or None if the
+/// window had to close
+pub fn window_set_size(window: &Value, width: i64, height: i64) -> Value {
+    let Value::Window(win) = window else {
+        return window.clone();
+    };
+    let (w, h) = (width.max(1) as usize, height.max(1) as usize);
+    let resized = GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let resized = match &mut state.renderer {
+            Some(renderer) => renderer.resize(w, h),
+            None => false,
+        };
+        if resized {
+            state.buffer = vec![0; w * h];
+            state.width = w;
+            state.height = h;
+        } else {
+            state.renderer = None;
+        }
+        resized
+    });
+    if !resized {
+        return Value::None;
+    }
+    Value::Window(WindowState {
+        width: w,
+        height: h,
+        ..win.clone()
+    })
+}
+
+/// Color written as `#rrggbb` or `#rgb`, as the 0xRRGGBB value drawing takes
+pub fn parse_color(text: &str) -> Option<u32> {
+    let digits = text.strip_prefix('#')?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match digits.len() {
+        6 => u32::from_str_radix(digits, 16).ok(),
+        // Each digit is doubled: #f80 is #ff8800
+        3 => digits.chars().try_fold(0, |color, c| {
+            let n = c.to_digit(16)?;
+            Some(color << 8 | n << 4 | n)
+        }),
+        _ => None,
+    }
+}
+
 /// Check if a key is pressed
 pub fn key_pressed(key: &str) -> bool {
     GAME_STATE.with(|state| state.borrow().is_key_pressed(key))
@@ -297,24 +493,112 @@ pub fn key_pressed(key: &str) -> bool {
 /// Clear the screen
 pub fn clear() {
     GAME_STATE.with(|state| {
-        state.borrow_mut().clear();
+        let mut state = state.borrow_mut();
+        state.clear();
+        if let Some(headless) = state.renderer.as_mut().and_then(|r| r.as_software()) {
+            headless.draw_calls.clear();
+        }
     })
 }
 
-/// Draw a rectangle (default white color)
-pub fn draw_rect(x: i64, y: i64, w: i64, h: i64) {
+/// Log a draw call in a headless window, for `draw_calls` to read back
+fn record_draw(call: impl FnOnce() -> String) {
     GAME_STATE.with(|state| {
-        state.borrow_mut().draw_rect(x, y, w, h, 0xFFFFFF); // White
+        let mut state = state.borrow_mut();
+        if let Some(headless) = state.renderer.as_mut().and_then(|r| r.as_software()) {
+            headless.draw_calls.push(call());
+        }
+    })
+}
+
+/// Draw calls made since the screen was last cleared, in the order they were
+/// made; empty unless the window is headless
+pub fn draw_calls() -> Vec<String> {
+    GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        match state.renderer.as_mut().and_then(|r| r.as_software()) {
+            Some(headless) => headless.draw_calls.clone(),
+            None => Vec::new(),
+        }
     })
 }
 
+/// A color as draw calls are logged: `#rrggbb`
+fn color_name(color: u32) -> String {
+    format!("#{:06x}", color & 0xFFFFFF)
+}
+
+/// Draw a rectangle (default white color)
+pub fn draw_rect(x: i64, y: i64, w: i64, h: i64) {
+    draw_rect_color(x, y, w, h, 0xFFFFFF); // White
+}
+
 /// Draw a rectangle with specific color
 pub fn draw_rect_color(x: i64, y: i64, w: i64, h: i64, color: u32) {
+    record_draw(|| format!("rect({}, {}, {}, {}, {})", x, y, w, h, color_name(color)));
+    GAME_STATE.with(|state| {
+        state.borrow_mut().draw_world_rect(x, y, w, h, color);
+    })
+}
+
+/// Draw a rectangle in window pixels, whatever the camera
+pub fn draw_screen_rect(x: i64, y: i64, w: i64, h: i64, color: u32) {
+    record_draw(|| format!("rect({}, {}, {}, {}, {})", x, y, w, h, color_name(color)));
     GAME_STATE.with(|state| {
         state.borrow_mut().draw_rect(x, y, w, h, color);
     })
 }
 
+/// Show the world from (x, y) at the window's top-left corner
+pub fn set_camera(x: f64, y: f64) {
+    GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.camera.x = x;
+        state.camera.y = y;
+    })
+}
+
+/// Magnify the world: 2.0 draws everything twice as large
+pub fn set_zoom(zoom: f64) -> ZyraResult<()> {
+    if !(zoom > 0.0 && zoom.is_finite()) {
+        return Err(ZyraError::runtime_error(&format!(
+            "set_zoom: zoom must be positive, got {}",
+            zoom
+        )));
+    }
+    GAME_STATE.with(|state| state.borrow_mut().camera.zoom = zoom);
+    Ok(())
+}
+
+/// World point under a window pixel, e.g. the mouse, as a Vec2
+pub fn screen_to_world(x: f64, y: f64) -> Value {
+    let (x, y) = GAME_STATE.with(|state| state.borrow().camera.to_world(x, y));
+    super::math::vec2_new(x, y)
+}
+
+/// Current mouse position (x, y) in window pixels
+pub fn mouse_pos() -> (i64, i64) {
+    GAME_STATE.with(|state| {
+        let state = state.borrow();
+        (state.mouse_x, state.mouse_y)
+    })
+}
+
+/// Check if the left mouse button is held
+pub fn mouse_down() -> bool {
+    GAME_STATE.with(|state| state.borrow().mouse_down)
+}
+
+/// Check if the left mouse button was pressed this frame
+pub fn mouse_clicked() -> bool {
+    GAME_STATE.with(|state| state.borrow().mouse_clicked)
+}
+
+/// Characters typed this frame ('\u{8}' = backspace)
+pub fn typed_chars() -> Vec<char> {
+    GAME_STATE.with(|state| state.borrow().typed_chars.clone())
+}
+
 /// Display the frame
 pub fn display() {
     GAME_STATE.with(|state| {
@@ -322,9 +606,78 @@ pub fn display() {
     })
 }
 
+/// Simulate windows from now on: no OS window, scripted input and a fixed
+/// delta time. Each window closes itself after `frame_limit` frames
+pub fn set_headless(frame_limit: u64) {
+    GAME_STATE.with(|state| state.borrow_mut().backend = Backend::Software { frame_limit })
+}
+
+/// Whether windows are simulated
+pub fn is_headless() -> bool {
+    GAME_STATE.with(|state| matches!(state.borrow().backend, Backend::Software { .. }))
+}
+
+/// Whether `key_pressed` and `simulate_key` know a key name
+pub fn is_key_name(name: &str) -> bool {
+    KEY_MAPPINGS.iter().any(|(n, _)| *n == name)
+}
+
+/// Hold or release a key in a headless window; seen from the next frame.
+/// Returns false for key names `key_pressed` doesn't know
+pub fn simulate_key(name: &str, down: bool) -> bool {
+    let Some(&(_, key)) = KEY_MAPPINGS.iter().find(|(n, _)| *n == name) else {
+        return false;
+    };
+    GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if let Some(headless) = state.renderer.as_mut().and_then(|r| r.as_software()) {
+            if down {
+                headless.keys.insert(key);
+            } else {
+                headless.keys.remove(&key);
+            }
+        }
+    });
+    true
+}
+
+/// Move the mouse and set the left button in a headless window
+pub fn simulate_mouse(x: i64, y: i64, down: bool) {
+    GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if let Some(headless) = state.renderer.as_mut().and_then(|r| r.as_software()) {
+            headless.mouse_x = x;
+            headless.mouse_y = y;
+            headless.mouse_down = down;
+        }
+    })
+}
+
+/// Type text into a headless window; it arrives as `typed_chars` next frame
+pub fn simulate_text(text: &str) {
+    GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if let Some(headless) = state.renderer.as_mut().and_then(|r| r.as_software()) {
+            headless.text.extend(text.chars());
+        }
+    })
+}
+
+/// Color of a pixel on the screen, or -1 outside it
+pub fn pixel_at(x: i64, y: i64) -> i64 {
+    GAME_STATE.with(|state| {
+        let state = state.borrow();
+        if x < 0 || y < 0 || x as usize >= state.width || y as usize >= state.height {
+            return -1;
+        }
+        state.buffer[y as usize * state.width + x as usize] as i64
+    })
+}
+
 /// Draw a single digit (0-9) using 5x7 pixel font
 /// Returns the width drawn (6 pixels including spacing)
 pub fn draw_digit(x: i64, y: i64, digit: i64, color: u32) {
+    record_draw(|| format!("digit({}, {}, {}, {})", x, y, digit, color_name(color)));
     // 5x7 bitmap font for digits 0-9
     // Each digit is 5 pixels wide, 7 pixels tall
     let patterns: [[u8; 7]; 10] = [
@@ -390,6 +743,16 @@ pub fn draw_digit(x: i64, y: i64, digit: i64, color: u32) {
 /// Draw a number (multiple digits) at position
 /// Scale: 1 = 5x7 pixels per digit, 2 = 10x14, etc.
 pub fn draw_number(x: i64, y: i64, num: i64, color: u32, scale: i64) {
+    record_draw(|| {
+        format!(
+            "number({}, {}, {}, {}, {})",
+            x,
+            y,
+            num,
+            color_name(color),
+            scale
+        )
+    });
     let num_str = num.abs().to_string();
     let mut offset = 0i64;
 
@@ -466,6 +829,7 @@ fn draw_digit_scaled(x: i64, y: i64, digit: i64, color: u32, scale: i64) {
 
 /// Draw text "WIN" at position (for victory screen)
 pub fn draw_text_win(x: i64, y: i64, color: u32, scale: i64) {
+    record_draw(|| format!("win({}, {}, {}, {})", x, y, color_name(color), scale));
     // W pattern
     let w_pattern: [u8; 7] = [
         0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b11011, 0b10001,
@@ -486,6 +850,7 @@ pub fn draw_text_win(x: i64, y: i64, color: u32, scale: i64) {
 
 /// Draw text "LOSE" at position
 pub fn draw_text_lose(x: i64, y: i64, color: u32, scale: i64) {
+    record_draw(|| format!("lose({}, {}, {}, {})", x, y, color_name(color), scale));
     // L pattern
     let l_pattern: [u8; 7] = [
         0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111,
@@ -509,6 +874,164 @@ pub fn draw_text_lose(x: i64, y: i64, color: u32, scale: i64) {
     draw_char_pattern(x + 18 * scale, y, &e_pattern, color, scale);
 }
 
+/// Draw a line of text with the built-in 5x7 font
+/// Letters are drawn uppercase; unsupported characters are skipped.
+/// Each character advances 6 * scale pixels.
+pub fn draw_text(x: i64, y: i64, text: &str, color: u32, scale: i64) {
+    record_draw(|| {
+        format!(
+            "text({}, {}, {:?}, {}, {})",
+            x,
+            y,
+            text,
+            color_name(color),
+            scale
+        )
+    });
+    for (i, ch) in text.chars().enumerate() {
+        if let Some(pattern) = glyph(ch) {
+            draw_char_pattern(x + i as i64 * 6 * scale, y, &pattern, color, scale);
+        }
+    }
+}
+
+/// Width in pixels of text drawn with `draw_text`
+pub fn text_width(text: &str, scale: i64) -> i64 {
+    text.chars().count() as i64 * 6 * scale
+}
+
+/// 5x7 bitmap for a character (letters are case-insensitive)
+fn glyph(ch: char) -> Option<[u8; 7]> {
+    let pattern = match ch.to_ascii_uppercase() {
+        'A' => [
+            0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+        'B' => [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110,
+        ],
+        'C' => [
+            0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110,
+        ],
+        'D' => [
+            0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110,
+        ],
+        'E' => [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111,
+        ],
+        'F' => [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+        'G' => [
+            0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111,
+        ],
+        'H' => [
+            0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+        'I' => [
+            0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+        'J' => [
+            0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100,
+        ],
+        'K' => [
+            0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001,
+        ],
+        'L' => [
+            0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111,
+        ],
+        'M' => [
+            0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001,
+        ],
+        'N' => [
+            0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001, 0b10001,
+        ],
+        'O' => [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+        'P' => [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+        'Q' => [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101,
+        ],
+        'R' => [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001,
+        ],
+        'S' => [
+            0b01110, 0b10001, 0b10000, 0b01110, 0b00001, 0b10001, 0b01110,
+        ],
+        'T' => [
+            0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100,
+        ],
+        'U' => [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+        'V' => [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100,
+        ],
+        'W' => [
+            0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b11011, 0b10001,
+        ],
+        'X' => [
+            0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001,
+        ],
+        'Y' => [
+            0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100,
+        ],
+        'Z' => [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111,
+        ],
+        '0' => [
+            0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110,
+        ],
+        '1' => [
+            0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+        '2' => [
+            0b01110, 0b10001, 0b00001, 0b00110, 0b01000, 0b10000, 0b11111,
+        ],
+        '3' => [
+            0b01110, 0b10001, 0b00001, 0b00110, 0b00001, 0b10001, 0b01110,
+        ],
+        '4' => [
+            0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010,
+        ],
+        '5' => [
+            0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110,
+        ],
+        '6' => [
+            0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110,
+        ],
+        '7' => [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000,
+        ],
+        '8' => [
+            0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110,
+        ],
+        '9' => [
+            0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100,
+        ],
+        ' ' => [0; 7],
+        '.' => [0, 0, 0, 0, 0, 0b01100, 0b01100],
+        ',' => [0, 0, 0, 0, 0b01100, 0b00100, 0b01000],
+        ':' => [0, 0b01100, 0b01100, 0, 0b01100, 0b01100, 0],
+        '!' => [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0, 0b00100],
+        '?' => [0b01110, 0b10001, 0b00001, 0b00110, 0b00100, 0, 0b00100],
+        '-' => [0, 0, 0, 0b11111, 0, 0, 0],
+        '+' => [0, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0],
+        '/' => [
+            0b00001, 0b00010, 0b00010, 0b00100, 0b01000, 0b01000, 0b10000,
+        ],
+        '%' => [
+            0b11001, 0b11010, 0b00010, 0b00100, 0b01000, 0b01011, 0b10011,
+        ],
+        '_' => [0, 0, 0, 0, 0, 0, 0b11111],
+        '|' => [0b00100; 7],
+        _ => return None,
+    };
+    Some(pattern)
+}
+
 /// Helper to draw a character pattern
 fn draw_char_pattern(x: i64, y: i64, pattern: &[u8; 7], color: u32, scale: i64) {
     GAME_STATE.with(|state| {
@@ -525,46 +1048,82 @@ fn draw_char_pattern(x: i64, y: i64, pattern: &[u8; 7], color: u32, scale: i64)
     });
 }
 
-/// Load a sprite from an image file (PNG, JPEG, etc.)
-/// Returns sprite ID on success, 0 on failure
-pub fn load_sprite(path: &str) -> i64 {
-    use image::GenericImageView;
-
-    let img_result = image::open(Path::new(path));
-
-    match img_result {
-        Ok(img) => {
-            let (width, height) = img.dimensions();
-            let rgba = img.to_rgba8();
-
-            // Convert RGBA to ARGB (minifb format)
-            let mut pixels: Vec<u32> = Vec::with_capacity((width * height) as usize);
-            for pixel in rgba.pixels() {
-                let r = pixel[0] as u32;
-                let g = pixel[1] as u32;
-                let b = pixel[2] as u32;
-                let a = pixel[3] as u32;
-                // ARGB format for minifb
-                let argb = (a << 24) | (r << 16) | (g << 8) | b;
-                pixels.push(argb);
-            }
+/// Convert an image to a sprite (RGBA to the window buffer's ARGB)
+fn sprite_from_image(img: &image::DynamicImage) -> Sprite {
+    let rgba = img.to_rgba8();
+    let pixels = rgba
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b, a] = pixel.0.map(|c| c as u32);
+            (a << 24) | (r << 16) | (g << 8) | b
+        })
+        .collect();
+    Sprite {
+        pixels,
+        width: rgba.width() as usize,
+        height: rgba.height() as usize,
+    }
+}
 
-            let sprite = Sprite {
-                pixels,
-                width: width as usize,
-                height: height as usize,
-            };
+/// Cut a named sprite out of an atlas built by `zyra assets atlas`. The atlas
+/// image and its manifest are read once and kept for later sprites
+fn atlas_sprite(atlas_path: &str, name: &str) -> Option<Sprite> {
+    let cached = GAME_STATE.with(|state| state.borrow().atlases.contains_key(atlas_path));
+    if !cached {
+        let manifest_path = crate::assets::manifest_path(Path::new(atlas_path));
+        let manifest = std::fs::read_to_string(manifest_path).ok()?;
+        let entries = crate::assets::parse_manifest(&manifest).ok()?;
+        let atlas = sprite_from_image(&image::open(atlas_path).ok()?);
+        GAME_STATE.with(|state| {
+            state
+                .borrow_mut()
+                .atlases
+                .insert(atlas_path.to_string(), (atlas, entries))
+        });
+    }
 
-            GAME_STATE.with(|state| {
-                let mut state = state.borrow_mut();
-                let id = state.next_sprite_id;
-                state.sprites.insert(id, sprite);
-                state.next_sprite_id += 1;
-                id as i64
-            })
+    GAME_STATE.with(|state| {
+        let state = state.borrow();
+        let (atlas, entries) = state.atlases.get(atlas_path)?;
+        let entry = entries.iter().find(|entry| entry.name == name)?;
+        let (x, y) = (entry.x as usize, entry.y as usize);
+        let (width, height) = (entry.width as usize, entry.height as usize);
+        if x + width > atlas.width || y + height > atlas.height {
+            return None;
         }
-        Err(_) => 0, // Return 0 on failure
-    }
+        let pixels = (y..y + height)
+            .flat_map(|row| &atlas.pixels[row * atlas.width + x..row * atlas.width + x + width])
+            .copied()
+            .collect();
+        Some(Sprite {
+            pixels,
+            width,
+            height,
+        })
+    })
+}
+
+/// Load a sprite from an image file (PNG, JPEG, etc.), or from a texture
+/// atlas with `"atlas.png#name"`.
+/// Returns sprite ID on success, 0 on failure
+pub fn load_sprite(path: &str) -> i64 {
+    let sprite = match path.rsplit_once('#') {
+        Some((atlas_path, name)) => atlas_sprite(atlas_path, name),
+        None => image::open(Path::new(path))
+            .ok()
+            .map(|img| sprite_from_image(&img)),
+    };
+    let Some(sprite) = sprite else {
+        return 0; // Return 0 on failure
+    };
+
+    GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let id = state.next_sprite_id;
+        state.sprites.insert(id, sprite);
+        state.next_sprite_id += 1;
+        id as i64
+    })
 }
 
 /// Draw a sprite at position (x, y)
@@ -574,6 +1133,7 @@ pub fn draw_sprite(sprite_id: i64, x: i64, y: i64) {
 
 /// Draw a sprite with scaling
 pub fn draw_sprite_scaled(sprite_id: i64, x: i64, y: i64, scale: i64) {
+    record_draw(|| format!("sprite({}, {}, {}, {})", sprite_id, x, y, scale));
     // First, get the sprite data (clone it to avoid borrow conflicts)
     let sprite_data = GAME_STATE.with(|state| {
         let state = state.borrow();
@@ -582,46 +1142,173 @@ pub fn draw_sprite_scaled(sprite_id: i64, x: i64, y: i64, scale: i64) {
 
     // Now draw using the cloned sprite data
     if let Some(sprite) = sprite_data {
-        let scale = scale.max(1) as usize;
-
         GAME_STATE.with(|state| {
             let mut state = state.borrow_mut();
+            let view = state.view();
             let width = state.width;
-            let height = state.height;
-
-            for sy in 0..sprite.height {
-                for sx in 0..sprite.width {
-                    let pixel = sprite.pixels[sy * sprite.width + sx];
-
-                    // Skip fully transparent pixels (alpha = 0)
-                    if (pixel >> 24) == 0 {
-                        continue;
-                    }
-
-                    // Draw scaled pixel
-                    let dest_x = x + (sx * scale) as i64;
-                    let dest_y = y + (sy * scale) as i64;
-
-                    // Convert ARGB to RGB for drawing
-                    let rgb = pixel & 0x00FFFFFF;
-
-                    for dy in 0..scale {
-                        for dx in 0..scale {
-                            let px = dest_x + dx as i64;
-                            let py = dest_y + dy as i64;
-
-                            if px >= 0 && py >= 0 && (px as usize) < width && (py as usize) < height
-                            {
-                                state.buffer[(py as usize) * width + (px as usize)] = rgb;
-                            }
-                        }
-                    }
-                }
-            }
+            blit(
+                &mut state.buffer,
+                width,
+                (&sprite.pixels, sprite.width),
+                view.to_screen(x, y),
+                scale.max(1) as f64 * view.zoom,
+                // Skip fully transparent pixels, and convert ARGB to RGB
+                |pixel| (pixel >> 24 != 0).then_some(pixel & 0x00FFFFFF),
+            );
         });
     }
 }
 
+/// Copy `pixels`, rows of `pixels_width`, to (x, y) of a buffer `width`
+/// pixels wide, scaled with nearest-neighbor sampling so pixel art stays
+/// sharp. `color` gives the color each pixel is drawn in, None to skip it
+fn blit(
+    buffer: &mut [u32],
+    width: usize,
+    (pixels, pixels_width): (&[u32], usize),
+    (x, y): (i64, i64),
+    scale: f64,
+    color: impl Fn(u32) -> Option<u32>,
+) {
+    if pixels.is_empty() || pixels_width == 0 || width == 0 {
+        return;
+    }
+    let pixels_height = pixels.len() / pixels_width;
+    let height = buffer.len() / width;
+    let dest_w = (pixels_width as f64 * scale).round() as i64;
+    let dest_h = (pixels_height as f64 * scale).round() as i64;
+    // Only the part of the scaled image that lands inside the buffer is visited
+    for dy in (-y).max(0)..dest_h.min(height as i64 - y) {
+        let sy = ((dy as f64 / scale) as usize).min(pixels_height - 1);
+        let row = (y + dy) as usize * width;
+        for dx in (-x).max(0)..dest_w.min(width as i64 - x) {
+            let sx = ((dx as f64 / scale) as usize).min(pixels_width - 1);
+            if let Some(color) = color(pixels[sy * pixels_width + sx]) {
+                buffer[row + (x + dx) as usize] = color;
+            }
+        }
+    }
+}
+
+// ============================================
+// RENDER TARGETS
+// ============================================
+
+/// Largest width or height an off-screen canvas may have
+pub const MAX_CANVAS_SIZE: i64 = 8192;
+
+fn canvas_error(message: &str) -> ZyraError {
+    ZyraError::runtime_error(message)
+}
+
+/// Create a black off-screen canvas, returning its ID
+pub fn create_canvas(width: i64, height: i64) -> ZyraResult<i64> {
+    if width <= 0 || height <= 0 {
+        return Err(canvas_error(&format!(
+            "create_canvas: size must be positive, got {}x{}",
+            width, height
+        )));
+    }
+    if width > MAX_CANVAS_SIZE || height > MAX_CANVAS_SIZE {
+        return Err(canvas_error(&format!(
+            "create_canvas: size {}x{} is larger than the {}x{} limit",
+            width, height, MAX_CANVAS_SIZE, MAX_CANVAS_SIZE
+        )));
+    }
+    let (width, height) = (width as usize, height as usize);
+    let pixels = width.checked_mul(height).ok_or_else(|| {
+        canvas_error(&format!(
+            "create_canvas: size {}x{} is too large",
+            width, height
+        ))
+    })?;
+    let canvas = Canvas {
+        pixels: vec![0; pixels],
+        width,
+        height,
+    };
+    Ok(GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let id = state.next_canvas_id;
+        state.canvases.insert(id, canvas);
+        state.next_canvas_id += 1;
+        id as i64
+    }))
+}
+
+/// Make a canvas the target of all drawing until `end_draw_to`
+pub fn begin_draw_to(canvas_id: i64) -> ZyraResult<()> {
+    GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let id = canvas_id as usize;
+        if !state.canvases.contains_key(&id) {
+            return Err(canvas_error(&format!(
+                "draw_to: no canvas with ID {}",
+                canvas_id
+            )));
+        }
+        if state.targets.contains(&id) {
+            return Err(canvas_error(&format!(
+                "draw_to: canvas {} is already being drawn into",
+                canvas_id
+            )));
+        }
+        state.swap_target(id);
+        state.targets.push(id);
+        Ok(())
+    })
+}
+
+/// Draw into the previous target again (the window, or an outer canvas)
+pub fn end_draw_to() {
+    GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if let Some(id) = state.targets.pop() {
+            state.swap_target(id);
+        }
+    })
+}
+
+/// Draw a canvas at (x, y), scaled with nearest-neighbor sampling so pixel art stays sharp
+pub fn draw_canvas(canvas_id: i64, x: i64, y: i64, scale: f64) -> ZyraResult<()> {
+    if !(scale > 0.0 && scale.is_finite()) {
+        return Err(canvas_error(&format!(
+            "draw_canvas: scale must be positive, got {}",
+            scale
+        )));
+    }
+    record_draw(|| format!("canvas({}, {}, {}, {:?})", canvas_id, x, y, scale));
+    GAME_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let id = canvas_id as usize;
+        if state.targets.contains(&id) {
+            return Err(canvas_error(&format!(
+                "draw_canvas: canvas {} can't be drawn while drawing into it",
+                canvas_id
+            )));
+        }
+        let view = state.view();
+        let GameState {
+            canvases,
+            buffer,
+            width,
+            ..
+        } = &mut *state;
+        let canvas = canvases.get(&id).ok_or_else(|| {
+            canvas_error(&format!("draw_canvas: no canvas with ID {}", canvas_id))
+        })?;
+        blit(
+            buffer,
+            *width,
+            (&canvas.pixels, canvas.width),
+            view.to_screen(x, y),
+            scale * view.zoom,
+            Some,
+        );
+        Ok(())
+    })
+}
+
 // ============================================
 // WINDOW ICON FUNCTION
 // ============================================
@@ -630,46 +1317,406 @@ pub fn draw_sprite_scaled(sprite_id: i64, x: i64, y: i64, scale: i64) {
 /// On Windows: expects .ico file
 /// On Linux/macOS/Wayland: Not supported (minifb Icon API not available on Wayland)
 pub fn set_window_icon(path: &str) -> bool {
-    #[cfg(target_os = "windows")]
-    {
-        use minifb::Icon;
-        use std::str::FromStr;
+    GAME_STATE.with(|state| match &mut state.borrow_mut().renderer {
+        Some(renderer) => renderer.set_icon(path),
+        None => false,
+    })
+}
 
-        GAME_STATE.with(|state| {
-            let mut state = state.borrow_mut();
-            if let Some(ref mut window) = state.window {
-                // Windows requires .ico file
-                match Icon::from_str(path) {
-                    Ok(icon) => {
-                        window.set_icon(icon);
-                        true
-                    }
-                    Err(_) => false,
-                }
-            } else {
-                false
-            }
+/// Check if window icon is supported on this platform
+pub fn is_icon_supported() -> bool {
+    cfg!(all(target_os = "windows", feature = "window"))
+}
+
+// ============================================
+// COLLISION
+// ============================================
+// Shapes that only touch along an edge don't overlap, so an object moved
+// flush against a wall stops colliding with it.
+
+/// Whether two rectangles, each given as (x, y, width, height), overlap
+pub fn rect_overlaps(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> bool {
+    a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+}
+
+/// Whether two circles, each given as (x, y, radius), overlap
+pub fn circle_overlaps(a: (f64, f64, f64), b: (f64, f64, f64)) -> bool {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    dx * dx + dy * dy < (a.2 + b.2) * (a.2 + b.2)
+}
+
+/// Whether a point lies in a rectangle; its left and top edges are inside
+pub fn point_in_rect(x: f64, y: f64, rect: (f64, f64, f64, f64)) -> bool {
+    rect.0 <= x && x < rect.0 + rect.2 && rect.1 <= y && y < rect.1 + rect.3
+}
+
+// ============================================
+// FIXED TIMESTEP
+// ============================================
+
+/// Longest frame `FixedStep` catches up on; time past this after a stall is
+/// dropped, so a slow frame doesn't queue up enough updates to slow the next
+const MAX_FRAME_TIME: f64 = 0.25;
+
+/// Clock of `game_loop`: turns the time each frame took into a whole number
+/// of fixed updates, carrying what's left over into the next frame
+pub struct FixedStep {
+    step: f64,
+    accumulator: f64,
+}
+
+impl FixedStep {
+    /// A clock running `fps` updates per second
+    pub fn new(fps: f64) -> ZyraResult<Self> {
+        if !(fps > 0.0 && fps.is_finite()) {
+            return Err(ZyraError::runtime_error(&format!(
+                "game_loop: fps must be positive, got {}",
+                fps
+            )));
+        }
+        Ok(Self {
+            step: 1.0 / fps,
+            accumulator: 0.0,
         })
     }
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        // Linux (X11/Wayland) and macOS: Icon API not reliably available
-        // minifb's Icon::from_argb doesn't compile on Wayland backend
-        let _ = path;
-        false
+    /// Seconds each update covers
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Updates due after a frame that took `delta` seconds
+    pub fn advance(&mut self, delta: f64) -> u32 {
+        self.accumulator += delta.min(MAX_FRAME_TIME);
+        let mut updates = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            updates += 1;
+        }
+        updates
     }
 }
 
-/// Check if window icon is supported on this platform
-pub fn is_icon_supported() -> bool {
-    #[cfg(target_os = "windows")]
-    {
-        true
+// ============================================
+// EASING & TWEENS
+// ============================================
+
+/// Apply an easing curve to t (clamped to 0..1)
+/// Kinds: linear, quad, cubic, elastic, bounce - each with `_in`, `_out`,
+/// or `_in_out` suffix. A bare name means `_in_out`; unknown kinds are linear.
+pub fn ease(kind: &str, t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    let (curve, mode) = match kind.split_once('_') {
+        Some((curve, mode)) => (curve, mode),
+        None => (kind, "in_out"),
+    };
+
+    let ease_in: fn(f64) -> f64 = match curve {
+        "quad" => |t| t * t,
+        "cubic" => |t| t * t * t,
+        "elastic" => elastic_in,
+        "bounce" => |t| 1.0 - bounce_out(1.0 - t),
+        _ => return t,
+    };
+
+    match mode {
+        "in" => ease_in(t),
+        "out" => 1.0 - ease_in(1.0 - t),
+        _ if t < 0.5 => ease_in(2.0 * t) / 2.0,
+        _ => 1.0 - ease_in(2.0 - 2.0 * t) / 2.0,
     }
+}
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        false
+fn elastic_in(t: f64) -> f64 {
+    if t <= 0.0 || t >= 1.0 {
+        return t;
+    }
+    let c4 = (2.0 * std::f64::consts::PI) / 3.0;
+    -(2f64.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * c4).sin()
+}
+
+fn bounce_out(t: f64) -> f64 {
+    const N1: f64 = 7.5625;
+    const D1: f64 = 2.75;
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+/// Create a Tween from start to end over duration seconds
+pub fn tween_new(start: f64, end: f64, duration: f64, kind: &str) -> Value {
+    let mut map = OrderedMap::new();
+    map.insert("_type".to_string(), Value::String("Tween".to_string()));
+    map.insert("start".to_string(), Value::Float(start));
+    map.insert("end".to_string(), Value::Float(end));
+    map.insert("duration".to_string(), Value::Float(duration.max(0.0)));
+    map.insert("elapsed".to_string(), Value::Float(0.0));
+    map.insert("kind".to_string(), Value::String(kind.to_string()));
+    Value::Object(map)
+}
+
+/// Advance a Tween by delta time (seconds), returning the updated Tween
+pub fn tween_update(tween: &Value, dt: f64) -> Value {
+    let mut tween = tween.clone();
+    if let Value::Object(map) = &mut tween {
+        let duration = tween_field(map, "duration");
+        let elapsed = (tween_field(map, "elapsed") + dt).clamp(0.0, duration);
+        map.insert("elapsed".to_string(), Value::Float(elapsed));
+    }
+    tween
+}
+
+/// Current eased value of a Tween
+pub fn tween_value(tween: &Value) -> f64 {
+    match tween {
+        Value::Object(map) => {
+            let start = tween_field(map, "start");
+            let end = tween_field(map, "end");
+            let kind = match map.get("kind") {
+                Some(Value::String(kind)) => kind.as_str(),
+                _ => "linear",
+            };
+            start + (end - start) * ease(kind, tween_progress(map))
+        }
+        _ => 0.0,
+    }
+}
+
+/// Check whether a Tween has reached its end
+pub fn tween_done(tween: &Value) -> bool {
+    match tween {
+        Value::Object(map) => tween_progress(map) >= 1.0,
+        _ => true,
+    }
+}
+
+/// Restart a Tween from the beginning
+pub fn tween_reset(tween: &Value) -> Value {
+    let mut tween = tween.clone();
+    if let Value::Object(map) = &mut tween {
+        map.insert("elapsed".to_string(), Value::Float(0.0));
+    }
+    tween
+}
+
+fn tween_progress(map: &OrderedMap<String, Value>) -> f64 {
+    let duration = tween_field(map, "duration");
+    if duration <= 0.0 {
+        1.0
+    } else {
+        (tween_field(map, "elapsed") / duration).clamp(0.0, 1.0)
+    }
+}
+
+fn tween_field(map: &OrderedMap<String, Value>, key: &str) -> f64 {
+    match map.get(key) {
+        Some(Value::Float(f)) | Some(Value::F64(f)) => *f,
+        Some(Value::F32(f)) => *f as f64,
+        Some(Value::Int(n)) | Some(Value::I64(n)) => *n as f64,
+        Some(Value::I32(n)) => *n as f64,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canvas_size_is_limited() {
+        assert!(create_canvas(0, 10).is_err());
+        assert!(create_canvas(MAX_CANVAS_SIZE + 1, 1).is_err());
+        assert!(create_canvas(i64::MAX, i64::MAX).is_err());
+        assert!(create_canvas(MAX_CANVAS_SIZE, 1).is_ok());
+    }
+
+    #[test]
+    fn test_canvas_is_drawn_into_and_scaled_onto_screen() {
+        GAME_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            state.buffer = vec![0; 8 * 8];
+            state.width = 8;
+            state.height = 8;
+        });
+        let canvas = create_canvas(2, 2).unwrap();
+        begin_draw_to(canvas).unwrap();
+        assert!(begin_draw_to(canvas).is_err());
+        draw_rect_color(1, 0, 1, 1, 0xFF0000);
+        end_draw_to();
+
+        draw_canvas(canvas, 4, 4, 3.0).unwrap();
+        GAME_STATE.with(|state| {
+            let state = state.borrow();
+            assert_eq!(state.width, 8);
+            // The red pixel covers columns 7..9 of rows 4..6, clipped at the edge
+            assert_eq!(state.buffer[4 * 8 + 7], 0xFF0000);
+            assert_eq!(state.buffer[6 * 8 + 7], 0xFF0000);
+            assert_eq!(state.buffer[4 * 8 + 6], 0);
+            assert_eq!(state.buffer[7 * 8 + 7], 0);
+        });
+    }
+
+    #[test]
+    fn test_camera_moves_and_zooms_the_world() {
+        GAME_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            state.buffer = vec![0; 8 * 8];
+            state.width = 8;
+            state.height = 8;
+        });
+        set_camera(10.0, 20.0);
+        set_zoom(2.0).unwrap();
+        assert!(set_zoom(0.0).is_err());
+        // World (11, 21)..(12, 22) lands on pixels (2, 2)..(4, 4)
+        draw_rect_color(11, 21, 1, 1, 0xFF0000);
+        // Window-space drawing ignores the camera
+        draw_screen_rect(0, 0, 1, 1, 0x00FF00);
+        let canvas = create_canvas(1, 1).unwrap();
+        begin_draw_to(canvas).unwrap();
+        draw_rect_color(0, 0, 1, 1, 0x0000FF);
+        end_draw_to();
+        draw_canvas(canvas, 13, 20, 1.0).unwrap();
+
+        GAME_STATE.with(|state| {
+            let state = state.borrow();
+            let pixel = |x: usize, y: usize| state.buffer[y * 8 + x];
+            assert_eq!(pixel(2, 2), 0xFF0000);
+            assert_eq!(pixel(3, 3), 0xFF0000);
+            assert_eq!(pixel(4, 4), 0);
+            assert_eq!(pixel(0, 0), 0x00FF00);
+            assert_eq!(pixel(6, 0), 0x0000FF);
+            assert_eq!(pixel(7, 1), 0x0000FF);
+        });
+        assert_eq!(
+            screen_to_world(4.0, 6.0),
+            super::super::math::vec2_new(12.0, 23.0)
+        );
+    }
+
+    #[test]
+    fn test_headless_window_runs_scripted_frames() {
+        set_headless(3);
+        assert!(matches!(create_window(4, 4, "test"), Value::Window(_)));
+        assert!(simulate_key("Space", true));
+        assert!(!simulate_key("F13", true));
+        simulate_text("ok");
+        draw_rect_color(1, 1, 1, 1, 0x00FF00);
+        display();
+        assert!(key_pressed("Space") && !key_pressed("Up"));
+        assert_eq!(typed_chars(), vec!['o', 'k']);
+        assert_eq!(pixel_at(1, 1), 0x00FF00);
+        assert_eq!(pixel_at(4, 0), -1);
+        assert_eq!(crate::stdlib::time::delta_time(), HEADLESS_DELTA);
+
+        display();
+        assert!(typed_chars().is_empty());
+        assert!(window_is_open());
+        display();
+        assert!(!window_is_open());
+    }
+
+    #[test]
+    fn test_headless_window_logs_draw_calls() {
+        assert!(matches!(
+            create_headless_window(8, 8, "test"),
+            Value::Window(_)
+        ));
+        assert!(is_headless());
+        draw_rect(1, 2, 3, 4);
+        draw_text(0, 0, "Hi", 0xFF8800, 1);
+        draw_text_win(0, 0, 0x00FF00, 2);
+        assert_eq!(
+            draw_calls(),
+            vec![
+                "rect(1, 2, 3, 4, #ffffff)".to_string(),
+                "text(0, 0, \"Hi\", #ff8800, 1)".to_string(),
+                "win(0, 0, #00ff00, 2)".to_string(),
+            ]
+        );
+        display();
+        assert_eq!(draw_calls().len(), 3);
+        clear();
+        assert!(draw_calls().is_empty());
+    }
+
+    #[test]
+    fn test_headless_window_builder_methods() {
+        set_headless(1);
+        let window = create_window(4, 4, "test");
+        let window = window_set_size(&window_set_title(&window, "Pong"), 8, 2);
+        let Value::Window(state) = &window else {
+            panic!("expected a window, got {:?}", window);
+        };
+        assert_eq!((state.width, state.height), (8, 2));
+        assert_eq!(state.title, "Pong");
+        draw_rect_color(7, 1, 1, 1, 0xFF0000);
+        assert_eq!(pixel_at(7, 1), 0xFF0000);
+        assert_eq!(pixel_at(0, 2), -1);
+    }
+
+    #[test]
+    fn test_color_strings() {
+        assert_eq!(parse_color("#ff8800"), Some(0xFF8800));
+        assert_eq!(parse_color("#F80"), Some(0xFF8800));
+        assert_eq!(parse_color("ff8800"), None);
+        assert_eq!(parse_color("#ff880"), None);
+        assert_eq!(parse_color("#+1234a"), None);
+    }
+
+    #[test]
+    fn test_collisions_need_more_than_touching() {
+        let wall = (10.0, 0.0, 5.0, 20.0);
+        assert!(rect_overlaps((0.0, 0.0, 11.0, 1.0), wall));
+        assert!(!rect_overlaps((0.0, 0.0, 10.0, 1.0), wall));
+        assert!(!rect_overlaps((12.0, 20.0, 1.0, 1.0), wall));
+
+        assert!(circle_overlaps((0.0, 0.0, 2.0), (3.0, 4.0, 3.5)));
+        assert!(!circle_overlaps((0.0, 0.0, 2.0), (3.0, 4.0, 3.0)));
+
+        assert!(point_in_rect(10.0, 0.0, wall));
+        assert!(!point_in_rect(15.0, 5.0, wall));
+    }
+
+    #[test]
+    fn test_fixed_step_carries_leftover_time() {
+        let mut clock = FixedStep::new(50.0).unwrap();
+        assert_eq!(clock.step(), 0.02);
+        assert_eq!(clock.advance(0.01), 0);
+        assert_eq!(clock.advance(0.015), 1);
+        assert_eq!(clock.advance(0.05), 2);
+        // A long stall only catches up MAX_FRAME_TIME worth of updates
+        assert_eq!(clock.advance(30.0), 13);
+        assert!(FixedStep::new(0.0).is_err());
+    }
+
+    #[test]
+    fn test_ease_endpoints() {
+        for kind in ["linear", "quad", "cubic_in", "elastic_out", "bounce_in_out"] {
+            assert!(ease(kind, 0.0).abs() < 1e-9, "{} at 0", kind);
+            assert!((ease(kind, 1.0) - 1.0).abs() < 1e-9, "{} at 1", kind);
+        }
+        assert_eq!(ease("quad_in", 0.5), 0.25);
+        assert_eq!(ease("quad_out", 0.5), 0.75);
+    }
+
+    #[test]
+    fn test_tween_with_delta_time() {
+        let mut tween = tween_new(10.0, 20.0, 2.0, "linear");
+        tween = tween_update(&tween, 0.5);
+        assert_eq!(tween_value(&tween), 12.5);
+        assert!(!tween_done(&tween));
+        tween = tween_update(&tween, 5.0);
+        assert_eq!(tween_value(&tween), 20.0);
+        assert!(tween_done(&tween));
+        assert_eq!(tween_value(&tween_reset(&tween)), 10.0);
     }
 }
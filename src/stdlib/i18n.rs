@@ -0,0 +1,180 @@
+//! Localization module for Zyra standard library
+//!
+//! Provides key → string tables, one file per language:
+//! - i18n::load(path): load a table; the language code is the file stem (`lang/fr.txt` → "fr")
+//! - tr(key, args): translate a key and fill `{0}`, `{1}`, ... (or `{}` in order) from args
+//! - set_language(code), language(): switch or query the current language
+//!
+//! Table files hold one `key = value` pair per line. Blank lines and lines
+//! starting with `#` are ignored, and `\n` in a value becomes a newline.
+//! A key missing from the current language falls back to the first loaded
+//! language, then to the key itself.
+//!
+//! `load` is only dispatched when called as `i18n::load` / `std::i18n::load`,
+//! so user functions named `load` keep working.
+
+use crate::compiler::bytecode::Value;
+use crate::error::{ZyraError, ZyraResult};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Default)]
+struct I18nState {
+    tables: HashMap<String, HashMap<String, String>>,
+    current: Option<String>,
+    fallback: Option<String>,
+}
+
+thread_local! {
+    static I18N: RefCell<I18nState> = RefCell::new(I18nState::default());
+}
+
+/// Load a translation table from a file; returns its language code
+/// The first loaded language becomes both the current and the fallback language.
+pub fn load(path: &str) -> ZyraResult<Value> {
+    let source = fs::read_to_string(path).map_err(|e| {
+        ZyraError::new(
+            "FileError",
+            &format!("Failed to read translation file '{}': {}", path, e),
+            None,
+        )
+    })?;
+    let table = parse_table(&source).map_err(|(line, msg)| {
+        ZyraError::new("I18nError", &format!("{}:{}: {}", path, line, msg), None)
+    })?;
+
+    let language = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+
+    I18N.with(|state| {
+        let mut state = state.borrow_mut();
+        state.tables.insert(language.clone(), table);
+        if state.current.is_none() {
+            state.current = Some(language.clone());
+        }
+        if state.fallback.is_none() {
+            state.fallback = Some(language.clone());
+        }
+    });
+
+    Ok(Value::String(language))
+}
+
+/// Switch the current language; returns false if no table was loaded for it
+pub fn set_language(code: &str) -> bool {
+    I18N.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.tables.contains_key(code) {
+            state.current = Some(code.to_string());
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Current language code ("" if nothing is loaded)
+pub fn language() -> String {
+    I18N.with(|state| state.borrow().current.clone().unwrap_or_default())
+}
+
+/// Translate a key and substitute placeholders with args
+pub fn tr(key: &str, args: &[Value]) -> String {
+    let template = I18N.with(|state| {
+        let state = state.borrow();
+        [&state.current, &state.fallback]
+            .iter()
+            .filter_map(|lang| state.tables.get(lang.as_ref()?))
+            .find_map(|table| table.get(key).cloned())
+    });
+
+    // Accept tr("key", [a, b]) as well as tr("key", a)
+    let args: Vec<String> = match args {
+        [Value::Array(items)] | [Value::Vec(items)] | [Value::List(items)] => {
+            items.iter().map(|v| v.to_string()).collect()
+        }
+        _ => args.iter().map(|v| v.to_string()).collect(),
+    };
+
+    substitute(template.as_deref().unwrap_or(key), &args)
+}
+
+/// Parse `key = value` lines; errors carry the 1-based line number
+fn parse_table(source: &str) -> Result<HashMap<String, String>, (usize, String)> {
+    let mut table = HashMap::new();
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| (i + 1, format!("expected 'key = value', found '{}'", line)))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err((i + 1, "missing key before '='".to_string()));
+        }
+        table.insert(key.to_string(), value.trim().replace("\\n", "\n"));
+    }
+    Ok(table)
+}
+
+/// Replace `{N}` with args[N] and `{}` with the next arg in order
+/// Placeholders without a matching argument are left as-is.
+fn substitute(template: &str, args: &[String]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            rest = &rest[open..];
+            break;
+        };
+
+        let inner = &after[..close];
+        let index = if inner.is_empty() {
+            next += 1;
+            Some(next - 1)
+        } else {
+            inner.parse::<usize>().ok()
+        };
+
+        match index.and_then(|i| args.get(i)) {
+            Some(arg) => out.push_str(arg),
+            None => out.push_str(&rest[open..open + close + 2]),
+        }
+        rest = &after[close + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_table() {
+        let table = parse_table("# menu\ngreeting = Hello, {0}!\n\nmulti = a\\nb\n").unwrap();
+        assert_eq!(table["greeting"], "Hello, {0}!");
+        assert_eq!(table["multi"], "a\nb");
+        assert_eq!(parse_table("ok = 1\nbroken").unwrap_err().0, 2);
+    }
+
+    #[test]
+    fn test_substitute_placeholders() {
+        let args = vec!["Ana".to_string(), "3".to_string()];
+        assert_eq!(substitute("{0} has {1} lives", &args), "Ana has 3 lives");
+        assert_eq!(substitute("{} has {}", &args), "Ana has 3");
+        assert_eq!(substitute("{1}/{0} {5} {x}", &args), "3/Ana {5} {x}");
+        assert_eq!(substitute("open { brace", &args), "open { brace");
+    }
+}
@@ -1,17 +1,56 @@
 //! IO module for Zyra standard library
 
 use crate::compiler::bytecode::Value;
+use std::cell::RefCell;
 use std::io::{self, Write};
 
+thread_local! {
+    /// Program output collected by `capture` instead of going to stdout
+    static CAPTURED: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, collecting everything the program prints to stdout and returning it
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let previous = CAPTURED.with(|c| c.replace(Some(String::new())));
+    let result = f();
+    let output = CAPTURED.with(|c| c.replace(previous)).unwrap_or_default();
+    (result, output)
+}
+
+/// Write program output to stdout, or to the capture buffer while one is active
+pub fn write_stdout(text: &str) {
+    let captured = CAPTURED.with(|c| match c.borrow_mut().as_mut() {
+        Some(buffer) => {
+            buffer.push_str(text);
+            true
+        }
+        None => false,
+    });
+    if !captured {
+        print!("{}", text);
+        io::stdout().flush().ok();
+    }
+}
+
 /// Print a value to stdout
 pub fn print(value: &Value) {
-    print!("{}", value);
-    io::stdout().flush().ok();
+    write_stdout(&value.to_string());
 }
 
 /// Print a value to stdout with newline
 pub fn println(value: &Value) {
-    println!("{}", value);
+    write_stdout(&format!("{}\n", value));
+}
+
+/// Print a value to stderr
+pub fn eprint(value: &Value) {
+    eprint!("{}", value);
+    io::stderr().flush().ok();
+}
+
+/// Print a value to stderr with newline
+pub fn eprintln(value: &Value) {
+    eprintln!("{}", value);
 }
 
 /// Read a line from stdin
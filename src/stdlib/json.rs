@@ -0,0 +1,424 @@
+//! JSON module for Zyra standard library
+//!
+//! Reads and writes JSON text for config and save files:
+//! - json_parse(text): objects become objects, arrays Vecs, `null` None,
+//!   whole numbers ints and other numbers floats
+//! - json_stringify(value): compact JSON text for any value
+//!
+//! Objects keep their keys in the order they were written, so parsing and
+//! stringifying gives back the same text apart from whitespace. Malformed
+//! input is reported with the line and column of the problem.
+
+use crate::compiler::bytecode::{MapKey, OrderedMap, Value};
+
+// ===== Parsing =====
+
+/// Parse JSON text into a value
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut parser = JsonParser {
+        chars: text.chars().collect(),
+        pos: 0,
+    };
+    parser.skip_whitespace();
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("unexpected text after the JSON value"));
+    }
+    Ok(value)
+}
+
+/// Arrays and objects nested deeper than this are rejected instead of
+/// overflowing the stack
+const MAX_DEPTH: usize = 512;
+
+struct JsonParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsonParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// An error message ending with the line and column of the current position
+    fn error(&self, message: &str) -> String {
+        let before = &self.chars[..self.pos.min(self.chars.len())];
+        let line = before.iter().filter(|&&c| c == '\n').count() + 1;
+        let column = before.iter().rev().take_while(|&&c| c != '\n').count() + 1;
+        format!("{} at line {}, column {}", message, line, column)
+    }
+
+    fn expect(&mut self, expected: char, message: &str) -> Result<(), String> {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("JSON is nested too deeply"));
+        }
+        match self.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => self.string().map(Value::String),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.keyword("true", Value::Bool(true)),
+            Some('f') => self.keyword("false", Value::Bool(false)),
+            Some('n') => self.keyword("null", Value::None),
+            Some(c) => Err(self.error(&format!("unexpected character '{}'", c))),
+            None => Err(self.error("unexpected end of input, expected a value")),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        let end = self.pos + word.chars().count();
+        if end <= self.chars.len() && self.chars[self.pos..end].iter().copied().eq(word.chars()) {
+            self.pos = end;
+            Ok(value)
+        } else {
+            Err(self.error("unknown word, expected true, false or null"))
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, String> {
+        self.pos += 1; // Consume {
+        let mut fields = OrderedMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a quoted key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':', "expected ':' after the key")?;
+            self.skip_whitespace();
+            let value = self.value(depth + 1)?;
+            fields.insert(key, value);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}' in object")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, String> {
+        self.pos += 1; // Consume [
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Value::Vec(items));
+        }
+        loop {
+            self.skip_whitespace();
+            items.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Value::Vec(items));
+                }
+                _ => return Err(self.error("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1; // Consume opening quote
+        let mut s = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            match c {
+                '"' => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                '\\' => {
+                    self.pos += 1;
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.pos += 1;
+                            s.push(self.unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.error("invalid escape in string")),
+                    };
+                    self.pos += 1;
+                    s.push(escaped);
+                }
+                c if (c as u32) < 0x20 => {
+                    return Err(self.error("control character in string, escape it"))
+                }
+                c => {
+                    self.pos += 1;
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    /// The character of a `\uXXXX` escape after its `\u`, joining a
+    /// surrogate pair written as two escapes
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.peek() != Some('\\') || self.chars.get(self.pos + 1) != Some(&'u') {
+                return Err(self.error("unpaired surrogate in \\u escape"));
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate in \\u escape"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let end = self.pos + 4;
+        let digits: String = self.chars[self.pos..end.min(self.chars.len())]
+            .iter()
+            .collect();
+        match u32::from_str_radix(&digits, 16) {
+            Ok(code) if digits.len() == 4 && digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                self.pos = end;
+                Ok(code)
+            }
+            _ => Err(self.error("expected four hex digits after \\u")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some('0') => self.pos += 1,
+            Some('1'..='9') => self.digits(),
+            _ => return Err(self.error("expected a digit")),
+        }
+        let mut is_float = false;
+        if self.peek() == Some('.') {
+            is_float = true;
+            self.pos += 1;
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("expected a digit after '.'"));
+            }
+            self.digits();
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            is_float = true;
+            self.pos += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.pos += 1;
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("expected a digit in the exponent"));
+            }
+            self.digits();
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        // Whole numbers too big for an int are kept as floats
+        match text.parse::<i64>() {
+            Ok(n) if !is_float => Ok(Value::Int(n)),
+            _ => Ok(Value::Float(text.parse().unwrap_or(f64::NAN))),
+        }
+    }
+
+    fn digits(&mut self) {
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.pos += 1;
+        }
+    }
+}
+
+// ===== Stringifying =====
+
+/// Write a value as compact JSON. Heap references must be resolved and
+/// enums expanded by the caller. Structs become objects without their type
+/// name, enum variants their name (`"Hero"`) or `{"Npc": payload}`, Options
+/// their content or `null`, Results `{"Ok": v}` or `{"Err": e}`, and maps
+/// objects with their keys as strings; values with no JSON form
+/// (functions, windows, NaN) are written as `null`.
+pub fn stringify(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Int(n) | Value::I64(n) => out.push_str(&n.to_string()),
+        Value::I8(n) => out.push_str(&n.to_string()),
+        Value::I32(n) => out.push_str(&n.to_string()),
+        Value::U8(n) => out.push_str(&n.to_string()),
+        Value::U32(n) => out.push_str(&n.to_string()),
+        Value::U64(n) => out.push_str(&n.to_string()),
+        Value::Float(f) | Value::F64(f) => write_float(out, *f),
+        Value::F32(f) => write_float(out, *f as f64),
+        Value::String(s) => write_string(out, s),
+        Value::Char(c) => write_string(out, &c.to_string()),
+        Value::Vec(items) | Value::List(items) | Value::Array(items) | Value::Tuple(items) => {
+            write_items(out, items)
+        }
+        Value::Set(items) => {
+            let items: Vec<Value> = items.iter().map(MapKey::to_value).collect();
+            write_items(out, &items)
+        }
+        // Enums arrive expanded, as `{_type: "Kind::Variant", _data: payload}`
+        Value::Object(fields) => match fields.get("_type") {
+            Some(Value::String(name)) if name.contains("::") => {
+                let variant = name.rsplit("::").next().unwrap_or(name);
+                match fields.get("_data") {
+                    Some(data) => write_object(out, [(variant, data)]),
+                    None => write_string(out, variant),
+                }
+            }
+            _ => write_object(
+                out,
+                fields
+                    .iter()
+                    .filter(|(key, _)| key.as_str() != "_type")
+                    .map(|(key, value)| (key.as_str(), value)),
+            ),
+        },
+        Value::Map(entries) => {
+            out.push('{');
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                match key.to_value() {
+                    Value::String(s) => write_string(out, &s),
+                    other => write_string(out, &other.to_string()),
+                }
+                out.push(':');
+                write_value(out, value);
+            }
+            out.push('}');
+        }
+        Value::Some(inner) => write_value(out, inner),
+        Value::Ok(inner) => write_object(out, [("Ok", inner.as_ref())]),
+        Value::Err(inner) => write_object(out, [("Err", inner.as_ref())]),
+        _ => out.push_str("null"),
+    }
+}
+
+fn write_items(out: &mut String, items: &[Value]) {
+    out.push('[');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_value(out, item);
+    }
+    out.push(']');
+}
+
+fn write_object<'a>(out: &mut String, fields: impl IntoIterator<Item = (&'a str, &'a Value)>) {
+    out.push('{');
+    for (i, (key, value)) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_string(out, key);
+        out.push(':');
+        write_value(out, value);
+    }
+    out.push('}');
+}
+
+/// Floats keep a fractional part, so they read back as floats
+fn write_float(out: &mut String, f: f64) {
+    if !f.is_finite() {
+        out.push_str("null");
+    } else if f.fract() == 0.0 && f.abs() < 1e16 {
+        out.push_str(&format!("{:.1}", f));
+    } else {
+        out.push_str(&f.to_string());
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_keeps_nesting_and_key_order() {
+        let text = r#"{"name":"Ada \"A\" é😀","level":3,"pos":[1.5,-2.0,1e3],"tags":[],"boss":null,"inv":{"z":true,"a":[{"id":7}]}}"#;
+        let value = parse(text).unwrap();
+        let Value::Object(fields) = &value else {
+            panic!("expected an object");
+        };
+        assert!(matches!(fields.get("level"), Some(Value::Int(3))));
+        assert!(matches!(fields.get("name"), Some(Value::String(s)) if s == "Ada \"A\" é😀"));
+        assert_eq!(
+            stringify(&value),
+            r#"{"name":"Ada \"A\" é😀","level":3,"pos":[1.5,-2.0,1000.0],"tags":[],"boss":null,"inv":{"z":true,"a":[{"id":7}]}}"#
+        );
+    }
+
+    #[test]
+    fn test_malformed_input_reports_line_and_column() {
+        let err = parse("{\n  \"a\": 1,\n  \"b\" 2\n}").unwrap_err();
+        assert_eq!(err, "expected ':' after the key at line 3, column 7");
+        assert!(parse("[1, 2,]").unwrap_err().contains("column 7"));
+        assert!(parse("[1] x").unwrap_err().contains("after the JSON value"));
+        assert!(parse("\"open").unwrap_err().contains("unterminated string"));
+    }
+}
@@ -0,0 +1,126 @@
+//! Map module for Zyra standard library
+//!
+//! Methods of the built-in `Map<K, V>` type, created with `map{"a": 1}`:
+//! - get, insert, remove, contains_key
+//! - keys, values
+//! - len, is_empty, clear
+//!
+//! Keys are integers, strings, bools or chars (see `MapKey`).
+
+use crate::compiler::bytecode::{MapKey, Value};
+use crate::error::{ZyraError, ZyraResult};
+
+/// Whether a built-in method changes the map it is called on
+pub fn mutates(method: &str) -> bool {
+    matches!(method, "insert" | "remove" | "clear")
+}
+
+fn key_of(method: &str, value: Option<&Value>) -> ZyraResult<MapKey> {
+    let value = value.unwrap_or(&Value::None);
+    MapKey::from_value(value).ok_or_else(|| {
+        ZyraError::new(
+            "TypeError",
+            &format!(
+                "Map::{} expects an integer, string, bool or char key, got {}",
+                method,
+                value.type_name()
+            ),
+            None,
+        )
+    })
+}
+
+/// Call a built-in method on a Map (`m.insert(k, v)`), changing it in place.
+/// Returns None for methods that aren't built in
+pub fn call_method(map: &mut Value, method: &str, args: &[Value]) -> ZyraResult<Option<Value>> {
+    let Value::Map(entries) = map else {
+        return Err(ZyraError::new("TypeError", "Expected a Map", None));
+    };
+    let option = |value: Option<Value>| value.map(Value::some).unwrap_or(Value::None);
+    let result = match method {
+        "get" => option(entries.get(&key_of(method, args.first())?).cloned()),
+        "insert" => {
+            let key = key_of(method, args.first())?;
+            entries.insert(key, args.get(1).cloned().unwrap_or(Value::None));
+            Value::None
+        }
+        "remove" => option(entries.remove(&key_of(method, args.first())?)),
+        "contains_key" => Value::Bool(entries.contains_key(&key_of(method, args.first())?)),
+        "keys" => Value::Vec(entries.keys().map(MapKey::to_value).collect()),
+        "values" => Value::Vec(entries.values().cloned().collect()),
+        "len" | "length" => Value::Int(entries.len() as i64),
+        "is_empty" => Value::Bool(entries.is_empty()),
+        "clear" => {
+            entries.clear();
+            Value::None
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::bytecode::OrderedMap;
+
+    #[test]
+    fn test_map_methods_change_entries_in_place() {
+        let mut map = Value::Map(OrderedMap::new());
+        let call = |map: &mut Value, method: &str, args: &[Value]| {
+            call_method(map, method, args).unwrap().unwrap()
+        };
+        call(
+            &mut map,
+            "insert",
+            &[Value::String("a".into()), Value::Int(1)],
+        );
+        call(
+            &mut map,
+            "insert",
+            &[Value::String("a".into()), Value::Int(2)],
+        );
+        assert_eq!(call(&mut map, "len", &[]), Value::Int(1));
+        assert_eq!(
+            call(&mut map, "get", &[Value::String("a".into())]),
+            Value::some(Value::Int(2))
+        );
+        assert_eq!(
+            call(&mut map, "remove", &[Value::String("a".into())]),
+            Value::some(Value::Int(2))
+        );
+        assert_eq!(
+            call(&mut map, "get", &[Value::String("a".into())]),
+            Value::None
+        );
+        assert!(call_method(&mut map, "get", &[Value::Float(1.5)]).is_err());
+    }
+
+    #[test]
+    fn test_map_keeps_insertion_order() {
+        let mut map = Value::Map(OrderedMap::new());
+        for key in ["b", "a", "c"] {
+            call_method(
+                &mut map,
+                "insert",
+                &[Value::String(key.into()), Value::Int(0)],
+            )
+            .unwrap();
+        }
+        call_method(&mut map, "remove", &[Value::String("a".into())]).unwrap();
+        call_method(
+            &mut map,
+            "insert",
+            &[Value::String("a".into()), Value::Int(1)],
+        )
+        .unwrap();
+        assert_eq!(map.to_string(), "{b: 0, c: 0, a: 1}");
+        assert_eq!(
+            call_method(&mut map, "keys", &[])
+                .unwrap()
+                .unwrap()
+                .to_string(),
+            "[b, c, a]"
+        );
+    }
+}
@@ -2,13 +2,16 @@
 //!
 //! Provides mathematical operations:
 //! - Basic: abs, min, max, clamp
+//! - Overflow: wrapping_add, saturating_add, checked_add (and _sub, _mul)
 //! - Rounding: floor, ceil, round
 //! - Powers: sqrt, pow, exp, log
 //! - Trig: sin, cos, tan, atan2
 //! - Vectors: Vec2, Vec3 operations
 //! - Interpolation: lerp, smoothstep
+//! - Noise: noise2d, octave_noise2d (seeded Perlin noise)
 
-use crate::compiler::bytecode::Value;
+use crate::compiler::bytecode::{OrderedMap, Value};
+use crate::compiler::OverflowMode;
 
 // ===== Basic Math =====
 
@@ -52,6 +55,33 @@ pub fn clamp(value: &Value, min_v: &Value, max_v: &Value) -> Value {
     }
 }
 
+/// `wrapping_add`, `saturating_mul`, `checked_sub` and the like: integer
+/// arithmetic with the overflow behavior in the name, whatever the program's
+/// overflow setting. `checked_*` returns Some(result), or None on overflow.
+pub fn int_op(name: &str, a: &Value, b: &Value) -> Value {
+    let Some((behavior, op)) = name.split_once('_') else {
+        return Value::None;
+    };
+    let op = match op {
+        "add" => '+',
+        "sub" => '-',
+        "mul" => '*',
+        _ => return Value::None,
+    };
+    let mode = match behavior {
+        "wrapping" => OverflowMode::Wrap,
+        "saturating" => OverflowMode::Saturate,
+        "checked" => OverflowMode::Error,
+        _ => return Value::None,
+    };
+    let (a, b) = Value::unify_numeric(a.clone(), b.clone());
+    match a.int_arith(op, &b, mode) {
+        Some(Ok(result)) if mode == OverflowMode::Error => Value::Some(Box::new(result)),
+        Some(Ok(result)) => result,
+        Some(Err(_)) | None => Value::None,
+    }
+}
+
 /// Sign of a value (-1, 0, or 1)
 pub fn sign(value: &Value) -> Value {
     match value {
@@ -299,27 +329,115 @@ pub fn tau() -> Value {
     Value::Float(std::f64::consts::TAU)
 }
 
-// ===== Random =====
+// ===== Noise =====
 
-/// Generate a random integer between min and max (inclusive)
-pub fn random(min_val: i64, max_val: i64) -> Value {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let seed = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .subsec_nanos() as i64;
-    let range = max_val - min_val + 1;
-    Value::Int(min_val + (seed.abs() % range))
+/// Deterministic pseudo-random generator (SplitMix64) - same seed, same sequence
+pub struct SeededRng {
+    state: u64,
 }
 
-/// Generate a random float between 0 and 1
-pub fn random_float() -> Value {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let seed = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .subsec_nanos();
-    Value::Float((seed as f64) / (u32::MAX as f64))
+impl SeededRng {
+    pub fn new(seed: i64) -> Self {
+        Self { state: seed as u64 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random float in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Build the Perlin permutation table for a seed (Fisher-Yates shuffle, doubled to avoid wrapping)
+fn permutation(seed: i64) -> [u8; 512] {
+    let mut base: [u8; 256] = [0; 256];
+    for (i, slot) in base.iter_mut().enumerate() {
+        *slot = i as u8;
+    }
+    let mut rng = SeededRng::new(seed);
+    for i in (1..256).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        base.swap(i, j);
+    }
+
+    let mut perm = [0; 512];
+    for (i, slot) in perm.iter_mut().enumerate() {
+        *slot = base[i & 255];
+    }
+    perm
+}
+
+/// Dot product of one of 8 gradient directions with (x, y)
+fn gradient(hash: u8, x: f64, y: f64) -> f64 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+fn perlin(perm: &[u8; 512], x: f64, y: f64) -> f64 {
+    let xi = (x.floor() as i64 & 255) as usize;
+    let yi = (y.floor() as i64 & 255) as usize;
+    let xf = x - x.floor();
+    let yf = y - y.floor();
+
+    let fade = |t: f64| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let u = fade(xf);
+    let v = fade(yf);
+
+    let aa = perm[perm[xi] as usize + yi];
+    let ab = perm[perm[xi] as usize + yi + 1];
+    let ba = perm[perm[xi + 1] as usize + yi];
+    let bb = perm[perm[xi + 1] as usize + yi + 1];
+
+    let x1 = lerp(gradient(aa, xf, yf), gradient(ba, xf - 1.0, yf), u);
+    let x2 = lerp(
+        gradient(ab, xf, yf - 1.0),
+        gradient(bb, xf - 1.0, yf - 1.0),
+        u,
+    );
+    lerp(x1, x2, v).clamp(-1.0, 1.0)
+}
+
+/// 2D Perlin noise in [-1, 1]; the same (x, y, seed) always gives the same value
+pub fn noise2d(x: f64, y: f64, seed: i64) -> f64 {
+    perlin(&permutation(seed), x, y)
+}
+
+/// Octaves past this add nothing visible: the frequency has outgrown f64 precision
+pub const MAX_OCTAVES: i64 = 32;
+
+/// Layered (fractal) 2D noise in [-1, 1] for terrain and clouds
+/// Each octave doubles the frequency and scales the amplitude by `persistence`.
+/// `octaves` is clamped to 1..=MAX_OCTAVES.
+pub fn octave_noise2d(x: f64, y: f64, seed: i64, octaves: i64, persistence: f64) -> f64 {
+    let perm = permutation(seed);
+    let mut total = 0.0;
+    let mut frequency = 1.0;
+    let mut amplitude = 1.0;
+    let mut max_amplitude = 0.0;
+
+    for _ in 0..octaves.clamp(1, MAX_OCTAVES) {
+        total += perlin(&perm, x * frequency, y * frequency) * amplitude;
+        max_amplitude += amplitude;
+        frequency *= 2.0;
+        amplitude *= persistence;
+    }
+
+    total / max_amplitude
 }
 
 // ===== Interpolation =====
@@ -374,7 +492,7 @@ pub fn smootherstep(edge0: f64, edge1: f64, x: f64) -> f64 {
 
 /// Create a Vec2 struct
 pub fn vec2_new(x: f64, y: f64) -> Value {
-    let mut map = std::collections::HashMap::new();
+    let mut map = OrderedMap::new();
     map.insert("_type".to_string(), Value::String("Vec2".to_string()));
     map.insert("x".to_string(), Value::Float(x));
     map.insert("y".to_string(), Value::Float(y));
@@ -447,6 +565,13 @@ pub fn vec2_from_angle(angle: f64, length: f64) -> Value {
     vec2_new(angle.cos() * length, angle.sin() * length)
 }
 
+/// Rotate Vec2 counter-clockwise by angle in radians
+pub fn vec2_rotate(v: &Value, angle: f64) -> Value {
+    let (x, y) = extract_vec2(v);
+    let (sin, cos) = angle.sin_cos();
+    vec2_new(x * cos - y * sin, x * sin + y * cos)
+}
+
 fn extract_vec2(v: &Value) -> (f64, f64) {
     if let Value::Object(map) = v {
         let x = map.get("x").map(|v| extract_float(v)).unwrap_or(0.0);
@@ -460,7 +585,7 @@ fn extract_vec2(v: &Value) -> (f64, f64) {
 
 /// Create a Vec3 struct
 pub fn vec3_new(x: f64, y: f64, z: f64) -> Value {
-    let mut map = std::collections::HashMap::new();
+    let mut map = OrderedMap::new();
     map.insert("_type".to_string(), Value::String("Vec3".to_string()));
     map.insert("x".to_string(), Value::Float(x));
     map.insert("y".to_string(), Value::Float(y));
@@ -530,6 +655,20 @@ pub fn vec3_lerp(a: &Value, b: &Value, t: f64) -> Value {
     vec3_new(lerp(ax, bx, t), lerp(ay, by, t), lerp(az, bz, t))
 }
 
+/// Rotate Vec3 around an axis by angle in radians (Rodrigues' formula)
+pub fn vec3_rotate(v: &Value, axis: &Value, angle: f64) -> Value {
+    let (x, y, z) = extract_vec3(v);
+    let (kx, ky, kz) = extract_vec3(&vec3_normalize(axis));
+    let (sin, cos) = angle.sin_cos();
+    let dot = kx * x + ky * y + kz * z;
+    let (cx, cy, cz) = (ky * z - kz * y, kz * x - kx * z, kx * y - ky * x);
+    vec3_new(
+        x * cos + cx * sin + kx * dot * (1.0 - cos),
+        y * cos + cy * sin + ky * dot * (1.0 - cos),
+        z * cos + cz * sin + kz * dot * (1.0 - cos),
+    )
+}
+
 fn extract_vec3(v: &Value) -> (f64, f64, f64) {
     if let Value::Object(map) = v {
         let x = map.get("x").map(|v| extract_float(v)).unwrap_or(0.0);
@@ -540,10 +679,180 @@ fn extract_vec3(v: &Value) -> (f64, f64, f64) {
     (0.0, 0.0, 0.0)
 }
 
-fn extract_float(v: &Value) -> f64 {
+// ===== Vector Operators =====
+
+/// Get the vector type name ("Vec2" or "Vec3") of a value, if any
+pub fn vector_type(v: &Value) -> Option<&str> {
+    if let Value::Object(map) = v {
+        if let Some(Value::String(t)) = map.get("_type") {
+            if t == "Vec2" || t == "Vec3" {
+                return Some(t.as_str());
+            }
+        }
+    }
+    None
+}
+
+/// Apply an arithmetic operator to vectors: `v + v`, `v - v`, `v * s`, `s * v`, `v / s`
+pub fn vector_arith(op: char, a: &Value, b: &Value) -> Option<Value> {
+    match (vector_type(a), vector_type(b)) {
+        (Some("Vec2"), Some("Vec2")) => match op {
+            '+' => Some(vec2_add(a, b)),
+            '-' => Some(vec2_sub(a, b)),
+            _ => None,
+        },
+        (Some("Vec3"), Some("Vec3")) => match op {
+            '+' => Some(vec3_add(a, b)),
+            '-' => Some(vec3_sub(a, b)),
+            _ => None,
+        },
+        (Some(kind), None) => {
+            let s = scalar(b)?;
+            let factor = match op {
+                '*' => s,
+                '/' if s != 0.0 => 1.0 / s,
+                _ => return None,
+            };
+            Some(vector_scale(kind, a, factor))
+        }
+        (None, Some(kind)) if op == '*' => Some(vector_scale(kind, b, scalar(a)?)),
+        _ => None,
+    }
+}
+
+/// Negate a vector
+pub fn vector_neg(v: &Value) -> Option<Value> {
+    vector_type(v).map(|kind| vector_scale(kind, v, -1.0))
+}
+
+fn vector_scale(kind: &str, v: &Value, s: f64) -> Value {
+    if kind == "Vec3" {
+        vec3_scale(v, s)
+    } else {
+        vec2_scale(v, s)
+    }
+}
+
+fn scalar(v: &Value) -> Option<f64> {
     match v {
-        Value::Float(f) => *f,
-        Value::Int(i) => *i as f64,
-        _ => 0.0,
+        Value::Int(n) | Value::I64(n) => Some(*n as f64),
+        Value::I32(n) => Some(*n as f64),
+        Value::Float(f) | Value::F64(f) => Some(*f),
+        Value::F32(f) => Some(*f as f64),
+        _ => None,
+    }
+}
+
+fn extract_float(v: &Value) -> f64 {
+    scalar(v).unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_noise_is_deterministic() {
+        assert_eq!(noise2d(1.3, 4.7, 42), noise2d(1.3, 4.7, 42));
+        assert_ne!(noise2d(1.3, 4.7, 42), noise2d(1.3, 4.7, 43));
+        // Lattice points are always zero
+        assert_eq!(noise2d(3.0, 5.0, 7), 0.0);
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_vec2_math() {
+        let v = vec2_new(3.0, 4.0);
+        assert_eq!(vec2_len(&v), 5.0);
+        let (x, y) = extract_vec2(&vec2_normalize(&v));
+        assert!(close(x, 0.6) && close(y, 0.8));
+        assert_eq!(vec2_dot(&v, &vec2_new(2.0, -1.0)), 2.0);
+        assert_eq!(vec2_distance(&v, &vec2_new(0.0, 0.0)), 5.0);
+        assert_eq!(
+            extract_vec2(&vec2_lerp(&v, &vec2_new(5.0, 8.0), 0.5)),
+            (4.0, 6.0)
+        );
+        // The zero vector stays zero instead of becoming NaN
+        assert_eq!(
+            extract_vec2(&vec2_normalize(&vec2_new(0.0, 0.0))),
+            (0.0, 0.0)
+        );
+
+        let (x, y) = extract_vec2(&vec2_rotate(&vec2_new(1.0, 0.0), PI / 2.0));
+        assert!(close(x, 0.0) && close(y, 1.0));
+        assert!(close(vec2_angle(&vec2_new(0.0, 2.0)), PI / 2.0));
+    }
+
+    #[test]
+    fn test_vec3_math() {
+        let x_axis = vec3_new(1.0, 0.0, 0.0);
+        let y_axis = vec3_new(0.0, 1.0, 0.0);
+        assert_eq!(extract_vec3(&vec3_cross(&x_axis, &y_axis)), (0.0, 0.0, 1.0));
+        assert_eq!(vec3_dot(&x_axis, &y_axis), 0.0);
+        assert_eq!(vec3_len(&vec3_new(2.0, 3.0, 6.0)), 7.0);
+        assert_eq!(vec3_distance(&x_axis, &y_axis), 2f64.sqrt());
+
+        // A quarter turn around z takes x to y, whatever the axis length
+        let (x, y, z) = extract_vec3(&vec3_rotate(&x_axis, &vec3_new(0.0, 0.0, 5.0), PI / 2.0));
+        assert!(close(x, 0.0) && close(y, 1.0) && close(z, 0.0));
+    }
+
+    #[test]
+    fn test_vector_operators() {
+        let a = vec2_new(1.0, 2.0);
+        let b = vec2_new(3.0, 5.0);
+        assert_eq!(a.add(&b), Some(vec2_new(4.0, 7.0)));
+        assert_eq!(b.sub(&a), Some(vec2_new(2.0, 3.0)));
+        assert_eq!(a.mul(&Value::Int(2)), Some(vec2_new(2.0, 4.0)));
+        assert_eq!(Value::Float(0.5).mul(&b), Some(vec2_new(1.5, 2.5)));
+        assert_eq!(b.div(&Value::Int(2)), Some(vec2_new(1.5, 2.5)));
+        assert_eq!(a.neg(), Some(vec2_new(-1.0, -2.0)));
+
+        let c = vec3_new(1.0, 2.0, 3.0);
+        assert_eq!(c.add(&c), Some(vec3_new(2.0, 4.0, 6.0)));
+        assert_eq!(c.mul(&Value::Float(-1.0)), c.neg());
+
+        // Mixed kinds, vector products and division by zero have no meaning
+        assert_eq!(a.add(&c), None);
+        assert_eq!(a.mul(&b), None);
+        assert_eq!(a.div(&Value::Int(0)), None);
+        assert_eq!(Value::Int(2).div(&a), None);
+    }
+
+    #[test]
+    fn test_int_ops_pick_their_own_overflow_behavior() {
+        let max = Value::I32(i32::MAX);
+        assert_eq!(
+            int_op("wrapping_add", &max, &Value::Int(1)),
+            Value::I32(i32::MIN)
+        );
+        assert_eq!(int_op("saturating_mul", &max, &Value::I32(2)), max);
+        assert_eq!(int_op("checked_add", &max, &Value::I32(1)), Value::None);
+        assert_eq!(
+            int_op("checked_sub", &Value::U8(3), &Value::U8(1)),
+            Value::Some(Box::new(Value::U8(2)))
+        );
+        assert_eq!(
+            int_op("wrapping_add", &Value::Float(1.0), &max),
+            Value::None
+        );
+    }
+
+    #[test]
+    fn test_octave_noise_in_range() {
+        for i in 0..100 {
+            let x = i as f64 * 0.37;
+            let n = octave_noise2d(x, x * 0.5, 9, 5, 0.5);
+            assert!((-1.0..=1.0).contains(&n));
+        }
+        // A huge octave count is clamped instead of looping for ever
+        assert_eq!(
+            octave_noise2d(0.3, 0.7, 9, i64::MAX, 0.5),
+            octave_noise2d(0.3, 0.7, 9, MAX_OCTAVES, 0.5)
+        );
     }
 }
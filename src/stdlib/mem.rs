@@ -6,7 +6,7 @@
 //! - memory statistics
 //! - reference counting helpers
 
-use crate::compiler::bytecode::Value;
+use crate::compiler::bytecode::{OrderedMap, Value};
 use std::collections::HashMap;
 use std::mem;
 
@@ -105,7 +105,7 @@ pub fn memory_usage() -> Value {
     // These are approximations since Rust doesn't expose exact heap usage easily
     let heap_estimate = 0i64; // Would need custom allocator to track
 
-    let mut map = HashMap::new();
+    let mut map = OrderedMap::new();
     map.insert(
         "_type".to_string(),
         Value::String("MemoryStats".to_string()),
@@ -133,9 +133,48 @@ pub fn swap(a: &mut Value, b: &mut Value) {
     std::mem::swap(a, b);
 }
 
-/// Take value and replace with None
+/// Take value and replace it with the default for its type
 pub fn take(value: &mut Value) -> Value {
-    std::mem::replace(value, Value::None)
+    let default = default_like(value);
+    std::mem::replace(value, default)
+}
+
+/// Empty value of the same type: zero, "", false, an empty collection,
+/// or a struct of defaulted fields. Options and anything else become None.
+pub fn default_like(value: &Value) -> Value {
+    match value {
+        Value::I8(_) => Value::I8(0),
+        Value::I32(_) => Value::I32(0),
+        Value::I64(_) => Value::I64(0),
+        Value::Int(_) => Value::Int(0),
+        Value::U8(_) => Value::U8(0),
+        Value::U32(_) => Value::U32(0),
+        Value::U64(_) => Value::U64(0),
+        Value::F32(_) => Value::F32(0.0),
+        Value::F64(_) => Value::F64(0.0),
+        Value::Float(_) => Value::Float(0.0),
+        Value::Bool(_) => Value::Bool(false),
+        Value::Char(_) => Value::Char('\0'),
+        Value::String(_) => Value::String(String::new()),
+        Value::Vec(_) => Value::Vec(Vec::new()),
+        Value::List(_) => Value::List(Vec::new()),
+        Value::Array(_) => Value::Array(Vec::new()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, field)| {
+                    // Keep the type tag so the result is still the same struct
+                    let value = if name == "_type" {
+                        field.clone()
+                    } else {
+                        default_like(field)
+                    };
+                    (name.clone(), value)
+                })
+                .collect(),
+        ),
+        _ => Value::None,
+    }
 }
 
 /// Replace value and return old value
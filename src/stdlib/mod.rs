@@ -2,25 +2,52 @@
 //!
 //! Built-in functions exposed to Zyra programs
 
+pub mod ai;
+pub mod audio;
+pub mod capability;
+pub mod cli;
 pub mod core;
+pub mod debug;
 pub mod env;
 pub mod fs;
 pub mod game;
+pub mod i18n;
 pub mod io;
+pub mod json;
 pub mod linkedlist;
+pub mod map;
 pub mod math;
 pub mod mem;
+pub mod net;
 pub mod process;
+pub mod random;
+pub mod render;
+pub mod serde;
+pub mod set;
 pub mod string;
 pub mod sync;
+pub mod test;
 pub mod thread;
 pub mod time;
+pub mod ui;
 pub mod vec;
 
-use crate::compiler::bytecode::Value;
-use crate::error::ZyraResult;
+use crate::compiler::bytecode::{Value, MAX_VALUE_DEPTH};
+use crate::error::{ZyraError, ZyraResult};
 // VM is no longer needed here - stdlib functions use global state
 
+/// A value just built around others, or an error if that nested it too deep
+/// to drop or clone without overflowing the host stack
+pub fn check_nesting(value: Value) -> ZyraResult<Value> {
+    if value.exceeds_max_depth() {
+        return Err(ZyraError::runtime_error(&format!(
+            "Value is nested more than {} levels deep",
+            MAX_VALUE_DEPTH
+        )));
+    }
+    Ok(value)
+}
+
 /// Standard library dispatcher
 pub struct StdLib {
     // Reserved for future state
@@ -44,12 +71,61 @@ impl StdLib {
         }
     }
 
+    /// Extract f64 from any numeric Value type
+    fn to_f64(v: &Value) -> Option<f64> {
+        match v {
+            Value::Float(f) | Value::F64(f) => Some(*f),
+            Value::F32(f) => Some(*f as f64),
+            _ => Self::to_i64(v).map(|n| n as f64),
+        }
+    }
+
+    /// Get a string argument, or "" if missing or not a string
+    fn str_arg(args: &[Value], index: usize) -> &str {
+        match args.get(index) {
+            Some(Value::String(s)) => s.as_str(),
+            _ => "",
+        }
+    }
+
+    /// Call a method on a built-in struct type (e.g. `v.length()` on a Vec2)
+    /// The receiver is passed as the first argument
+    pub fn call_method(
+        &self,
+        type_name: &str,
+        method: &str,
+        args: &[Value],
+    ) -> ZyraResult<Option<Value>> {
+        match type_name {
+            "Vec2" | "Vec3" => self.call(&format!("{}_{}", type_name.to_lowercase(), method), args),
+            "Tween" => self.call(&format!("tween_{}", method), args),
+            "Stopwatch" => self.call(&format!("stopwatch_{}", method), args),
+            "Window" => self.call(&format!("window_{}", method), args),
+            _ => Ok(None),
+        }
+    }
+
     /// Call a standard library function
     pub fn call(&self, name: &str, args: &[Value]) -> ZyraResult<Option<Value>> {
         // Handle qualified names by using the leaf name (e.g. std::math::abs -> abs)
         // This relies on the semantic analyzer to ensure correct module usage
         let func_name = name.split("::").last().unwrap_or(name);
 
+        // Integers of a declared type reach the library as plain ints, except
+        // where the type decides the result (`wrapping_add` on an i32 wraps at
+        // the i32 bounds, `type_of` names it)
+        let plain_args: Vec<Value>;
+        let keeps_types = func_name.starts_with("wrapping_")
+            || func_name.starts_with("saturating_")
+            || func_name.starts_with("checked_")
+            || matches!(func_name, "type_of" | "size_of");
+        let args = if !keeps_types && args.iter().any(Value::is_sized_int) {
+            plain_args = args.iter().cloned().map(Value::into_plain_int).collect();
+            &plain_args
+        } else {
+            args
+        };
+
         match func_name {
             // IO functions
             "print" => {
@@ -62,7 +138,21 @@ impl StdLib {
                 if let Some(value) = args.first() {
                     io::println(value);
                 } else {
-                    println!();
+                    io::write_stdout("\n");
+                }
+                Ok(Some(Value::None))
+            }
+            "eprint" => {
+                if let Some(value) = args.first() {
+                    io::eprint(value);
+                }
+                Ok(Some(Value::None))
+            }
+            "eprintln" => {
+                if let Some(value) = args.first() {
+                    io::eprintln(value);
+                } else {
+                    eprintln!();
                 }
                 Ok(Some(Value::None))
             }
@@ -125,23 +215,6 @@ impl StdLib {
                     Ok(Some(Value::None))
                 }
             }
-            "random" => {
-                let min = args
-                    .get(0)
-                    .and_then(|v| match v {
-                        Value::Int(n) => Some(*n),
-                        _ => None,
-                    })
-                    .unwrap_or(0);
-                let max = args
-                    .get(1)
-                    .and_then(|v| match v {
-                        Value::Int(n) => Some(*n),
-                        _ => None,
-                    })
-                    .unwrap_or(100);
-                Ok(Some(math::random(min, max)))
-            }
             "sin" => {
                 if let Some(value) = args.first() {
                     Ok(Some(math::sin(value)))
@@ -177,6 +250,28 @@ impl StdLib {
                 time::sleep(ms);
                 Ok(Some(Value::None))
             }
+            "sleep_precise" => {
+                time::sleep_precise(args.first().and_then(Self::to_f64).unwrap_or(0.0))
+                    .map_err(|e| ZyraError::runtime_error(&format!("sleep_precise: {}", e)))?;
+                Ok(Some(Value::None))
+            }
+            "sleep_until" => {
+                time::sleep_until(args.first().and_then(Self::to_f64).unwrap_or(0.0))
+                    .map_err(|e| ZyraError::runtime_error(&format!("sleep_until: {}", e)))?;
+                Ok(Some(Value::None))
+            }
+            "perf_counter" => Ok(Some(Value::Float(time::perf_counter()))),
+            "Stopwatch" => Ok(Some(time::stopwatch_new())),
+            "stopwatch_start" if !args.is_empty() => {
+                time::stopwatch_start(&args[0]);
+                Ok(Some(Value::None))
+            }
+            "stopwatch_lap" if !args.is_empty() => {
+                Ok(Some(Value::Float(time::stopwatch_lap(&args[0]))))
+            }
+            "stopwatch_elapsed" if !args.is_empty() => {
+                Ok(Some(Value::Float(time::stopwatch_elapsed(&args[0]))))
+            }
 
             // Game functions - Window constructor
             "Window" => {
@@ -191,6 +286,143 @@ impl StdLib {
                     .unwrap_or_else(|| "Zyra Window".to_string());
                 Ok(Some(game::create_window(width, height, &title)))
             }
+            "HeadlessWindow" => {
+                let width = args.first().and_then(Self::to_i64).unwrap_or(800);
+                let height = args.get(1).and_then(Self::to_i64).unwrap_or(600);
+                Ok(Some(game::create_headless_window(
+                    width,
+                    height,
+                    Self::str_arg(args, 2),
+                )))
+            }
+
+            "window_is_open" => Ok(Some(Value::Bool(game::window_is_open()))),
+            "window_set_title" if !args.is_empty() => Ok(Some(game::window_set_title(
+                &args[0],
+                Self::str_arg(args, 1),
+            ))),
+            "window_set_size" if !args.is_empty() => {
+                let width = args.get(1).and_then(Self::to_i64).unwrap_or(800);
+                let height = args.get(2).and_then(Self::to_i64).unwrap_or(600);
+                Ok(Some(game::window_set_size(&args[0], width, height)))
+            }
+
+            // Easing & tweens
+            "hex_color" => match game::parse_color(Self::str_arg(args, 0)) {
+                Some(color) => Ok(Some(Value::Int(color as i64))),
+                None => Err(ZyraError::runtime_error(&format!(
+                    "'{}' is not a color: expected #rrggbb or #rgb",
+                    Self::str_arg(args, 0)
+                ))),
+            },
+            "ease" => {
+                let kind = match args.first() {
+                    Some(Value::String(kind)) => kind.as_str(),
+                    _ => "linear",
+                };
+                let t = args.get(1).and_then(Self::to_f64).unwrap_or(0.0);
+                Ok(Some(Value::Float(game::ease(kind, t))))
+            }
+            "Tween" => {
+                let start = args.first().and_then(Self::to_f64).unwrap_or(0.0);
+                let end = args.get(1).and_then(Self::to_f64).unwrap_or(1.0);
+                let duration = args.get(2).and_then(Self::to_f64).unwrap_or(1.0);
+                let kind = match args.get(3) {
+                    Some(Value::String(kind)) => kind.as_str(),
+                    _ => "linear",
+                };
+                Ok(Some(game::tween_new(start, end, duration, kind)))
+            }
+            "tween_update" if !args.is_empty() => {
+                let dt = args.get(1).and_then(Self::to_f64).unwrap_or(0.0);
+                Ok(Some(game::tween_update(&args[0], dt)))
+            }
+            "tween_value" if !args.is_empty() => {
+                Ok(Some(Value::Float(game::tween_value(&args[0]))))
+            }
+            "tween_done" if !args.is_empty() => Ok(Some(Value::Bool(game::tween_done(&args[0])))),
+            "tween_reset" if !args.is_empty() => Ok(Some(game::tween_reset(&args[0]))),
+
+            // Text & mouse
+            "draw_text" | "draw.text" => {
+                let x = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let y = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                let color = args.get(3).and_then(Self::to_i64).unwrap_or(0xFFFFFF);
+                let scale = args.get(4).and_then(Self::to_i64).unwrap_or(2);
+                game::draw_text(x, y, Self::str_arg(args, 2), color as u32, scale);
+                Ok(Some(Value::None))
+            }
+            "mouse_x" => Ok(Some(Value::Int(game::mouse_pos().0))),
+            "mouse_y" => Ok(Some(Value::Int(game::mouse_pos().1))),
+            "mouse_down" => Ok(Some(Value::Bool(game::mouse_down()))),
+            "mouse_clicked" => Ok(Some(Value::Bool(game::mouse_clicked()))),
+
+            // Headless windows: scripted input and reading the screen back
+            "simulate_key" => {
+                let down = matches!(args.get(1), Some(Value::Bool(true)));
+                Ok(Some(Value::Bool(game::simulate_key(
+                    Self::str_arg(args, 0),
+                    down,
+                ))))
+            }
+            "simulate_mouse" => {
+                let x = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let y = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                let down = matches!(args.get(2), Some(Value::Bool(true)));
+                game::simulate_mouse(x, y, down);
+                Ok(Some(Value::None))
+            }
+            "simulate_text" => {
+                game::simulate_text(Self::str_arg(args, 0));
+                Ok(Some(Value::None))
+            }
+            "pixel_at" => {
+                let x = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let y = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                Ok(Some(Value::Int(game::pixel_at(x, y))))
+            }
+            "is_headless" => Ok(Some(Value::Bool(game::is_headless()))),
+            "draw_calls" => Ok(Some(Value::Vec(
+                game::draw_calls().into_iter().map(Value::String).collect(),
+            ))),
+
+            // Immediate-mode UI
+            "ui_button" => {
+                let x = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let y = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                Ok(Some(Value::Bool(ui::button(x, y, Self::str_arg(args, 2)))))
+            }
+            "ui_slider" => {
+                let x = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let y = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                let width = args.get(2).and_then(Self::to_i64).unwrap_or(100);
+                let value = args.get(3).and_then(Self::to_f64).unwrap_or(0.0);
+                let min = args.get(4).and_then(Self::to_f64).unwrap_or(0.0);
+                let max = args.get(5).and_then(Self::to_f64).unwrap_or(1.0);
+                Ok(Some(Value::Float(ui::slider(x, y, width, value, min, max))))
+            }
+            "ui_checkbox" => {
+                let x = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let y = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                let checked = matches!(args.get(3), Some(Value::Bool(true)));
+                Ok(Some(Value::Bool(ui::checkbox(
+                    x,
+                    y,
+                    Self::str_arg(args, 2),
+                    checked,
+                ))))
+            }
+            "ui_text_input" => {
+                let x = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let y = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                let width = args.get(2).and_then(Self::to_i64).unwrap_or(200);
+                Ok(Some(Value::String(ui::text_input(
+                    x,
+                    y,
+                    width,
+                    Self::str_arg(args, 3),
+                ))))
+            }
 
             // Window methods (called on window objects)
             "win.is_open" | "is_open" => Ok(Some(Value::Bool(game::window_is_open()))),
@@ -261,6 +493,47 @@ impl StdLib {
                 Ok(Some(Value::None))
             }
 
+            // Collision
+            "rect_overlaps" => {
+                let n = |i| args.get(i).and_then(Self::to_f64).unwrap_or(0.0);
+                Ok(Some(Value::Bool(game::rect_overlaps(
+                    (n(0), n(1), n(2), n(3)),
+                    (n(4), n(5), n(6), n(7)),
+                ))))
+            }
+            "circle_overlaps" => {
+                let n = |i| args.get(i).and_then(Self::to_f64).unwrap_or(0.0);
+                Ok(Some(Value::Bool(game::circle_overlaps(
+                    (n(0), n(1), n(2)),
+                    (n(3), n(4), n(5)),
+                ))))
+            }
+            "point_in_rect" => {
+                let n = |i| args.get(i).and_then(Self::to_f64).unwrap_or(0.0);
+                Ok(Some(Value::Bool(game::point_in_rect(
+                    n(0),
+                    n(1),
+                    (n(2), n(3), n(4), n(5)),
+                ))))
+            }
+
+            // Camera
+            "set_camera" => {
+                let x = args.first().and_then(Self::to_f64).unwrap_or(0.0);
+                let y = args.get(1).and_then(Self::to_f64).unwrap_or(0.0);
+                game::set_camera(x, y);
+                Ok(Some(Value::None))
+            }
+            "set_zoom" => {
+                game::set_zoom(args.first().and_then(Self::to_f64).unwrap_or(1.0))?;
+                Ok(Some(Value::None))
+            }
+            "screen_to_world" => {
+                let x = args.first().and_then(Self::to_f64).unwrap_or(0.0);
+                let y = args.get(1).and_then(Self::to_f64).unwrap_or(0.0);
+                Ok(Some(game::screen_to_world(x, y)))
+            }
+
             // Draw a number at position with scale
             "draw_number" | "draw.number" => {
                 let x = args
@@ -384,6 +657,21 @@ impl StdLib {
                 Ok(Some(Value::None))
             }
 
+            // Render targets (draw_to runs a closure, so it lives in the VM)
+            "create_canvas" => {
+                let w = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let h = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                Ok(Some(Value::Int(game::create_canvas(w, h)?)))
+            }
+            "draw_canvas" => {
+                let id = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let x = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                let y = args.get(2).and_then(Self::to_i64).unwrap_or(0);
+                let scale = args.get(3).and_then(Self::to_f64).unwrap_or(1.0);
+                game::draw_canvas(id, x, y, scale)?;
+                Ok(Some(Value::None))
+            }
+
             // Window icon
             "set_icon" | "set_window_icon" => {
                 if let Some(Value::String(path)) = args.first() {
@@ -398,8 +686,11 @@ impl StdLib {
             "len" | "length" => {
                 if let Some(value) = args.first() {
                     match value {
-                        Value::String(s) => Ok(Some(Value::Int(s.len() as i64))),
-                        Value::List(items) => Ok(Some(Value::Int(items.len() as i64))),
+                        Value::String(s) => Ok(Some(Value::Int(string::string_len(s)))),
+                        Value::List(items)
+                        | Value::Vec(items)
+                        | Value::Array(items)
+                        | Value::Tuple(items) => Ok(Some(Value::Int(items.len() as i64))),
                         _ => Ok(Some(Value::Int(0))),
                     }
                 } else {
@@ -411,7 +702,7 @@ impl StdLib {
             _ if name.ends_with(".len") || name.ends_with(".length") => {
                 if let Some(value) = args.first() {
                     match value {
-                        Value::String(s) => Ok(Some(Value::Int(s.len() as i64))),
+                        Value::String(s) => Ok(Some(Value::Int(string::string_len(s)))),
                         Value::List(items) => Ok(Some(Value::Int(items.len() as i64))),
                         _ => Ok(Some(Value::Int(0))),
                     }
@@ -480,6 +771,30 @@ impl StdLib {
                     Ok(Some(Value::Int(0)))
                 }
             }
+            "byte_len" => {
+                if let Some(Value::String(s)) = args.first() {
+                    Ok(Some(Value::Int(string::string_byte_len(s))))
+                } else {
+                    Ok(Some(Value::Int(0)))
+                }
+            }
+            "char_at" => {
+                let index = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                if let Some(Value::String(s)) = args.first() {
+                    Ok(Some(string::string_char_at(s, index)))
+                } else {
+                    Ok(Some(Value::None))
+                }
+            }
+            "substring" => {
+                let start = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                let end = args.get(2).and_then(Self::to_i64).unwrap_or(0);
+                if let Some(Value::String(s)) = args.first() {
+                    Ok(Some(Value::String(string::string_slice(s, start, end))))
+                } else {
+                    Ok(Some(Value::String(String::new())))
+                }
+            }
             "to_upper" => {
                 if let Some(Value::String(s)) = args.first() {
                     Ok(Some(Value::String(string::string_to_upper(s))))
@@ -590,12 +905,134 @@ impl StdLib {
                 }
             }
             "e" => Ok(Some(math::e())),
+            "wrapping_add" | "wrapping_sub" | "wrapping_mul" | "saturating_add"
+            | "saturating_sub" | "saturating_mul" | "checked_add" | "checked_sub"
+            | "checked_mul" => {
+                if args.len() >= 2 {
+                    Ok(Some(math::int_op(name, &args[0], &args[1])))
+                } else {
+                    Ok(Some(Value::None))
+                }
+            }
             "tau" => Ok(Some(math::tau())),
-            "random_float" => Ok(Some(math::random_float())),
+            "noise2d" => {
+                let x = args.first().and_then(Self::to_f64).unwrap_or(0.0);
+                let y = args.get(1).and_then(Self::to_f64).unwrap_or(0.0);
+                let seed = args.get(2).and_then(Self::to_i64).unwrap_or(0);
+                Ok(Some(Value::Float(math::noise2d(x, y, seed))))
+            }
+            "octave_noise2d" => {
+                let x = args.first().and_then(Self::to_f64).unwrap_or(0.0);
+                let y = args.get(1).and_then(Self::to_f64).unwrap_or(0.0);
+                let seed = args.get(2).and_then(Self::to_i64).unwrap_or(0);
+                let octaves = args.get(3).and_then(Self::to_i64).unwrap_or(4);
+                let persistence = args.get(4).and_then(Self::to_f64).unwrap_or(0.5);
+                Ok(Some(Value::Float(math::octave_noise2d(
+                    x,
+                    y,
+                    seed,
+                    octaves,
+                    persistence,
+                ))))
+            }
+
+            // Math - Vec2
+            "vec2" => {
+                let x = args.first().and_then(Self::to_f64).unwrap_or(0.0);
+                let y = args.get(1).and_then(Self::to_f64).unwrap_or(0.0);
+                Ok(Some(math::vec2_new(x, y)))
+            }
+            "vec2_add" | "vec2_sub" | "vec2_dot" | "vec2_distance" if args.len() >= 2 => {
+                Ok(Some(match func_name {
+                    "vec2_add" => math::vec2_add(&args[0], &args[1]),
+                    "vec2_sub" => math::vec2_sub(&args[0], &args[1]),
+                    "vec2_dot" => Value::Float(math::vec2_dot(&args[0], &args[1])),
+                    _ => Value::Float(math::vec2_distance(&args[0], &args[1])),
+                }))
+            }
+            "vec2_scale" | "vec2_rotate" if args.len() >= 2 => {
+                let s = Self::to_f64(&args[1]).unwrap_or(0.0);
+                if func_name == "vec2_scale" {
+                    Ok(Some(math::vec2_scale(&args[0], s)))
+                } else {
+                    Ok(Some(math::vec2_rotate(&args[0], s)))
+                }
+            }
+            "vec2_length" | "vec2_len" => match args.first() {
+                Some(v) => Ok(Some(Value::Float(math::vec2_len(v)))),
+                None => Ok(Some(Value::None)),
+            },
+            "vec2_normalize" => match args.first() {
+                Some(v) => Ok(Some(math::vec2_normalize(v))),
+                None => Ok(Some(Value::None)),
+            },
+            "vec2_angle" => match args.first() {
+                Some(v) => Ok(Some(Value::Float(math::vec2_angle(v)))),
+                None => Ok(Some(Value::None)),
+            },
+            "vec2_lerp" if args.len() >= 3 => {
+                let t = Self::to_f64(&args[2]).unwrap_or(0.0);
+                Ok(Some(math::vec2_lerp(&args[0], &args[1], t)))
+            }
+            "vec2_from_angle" => {
+                let angle = args.first().and_then(Self::to_f64).unwrap_or(0.0);
+                let length = args.get(1).and_then(Self::to_f64).unwrap_or(1.0);
+                Ok(Some(math::vec2_from_angle(angle, length)))
+            }
+
+            // Math - Vec3
+            "vec3" => {
+                let x = args.first().and_then(Self::to_f64).unwrap_or(0.0);
+                let y = args.get(1).and_then(Self::to_f64).unwrap_or(0.0);
+                let z = args.get(2).and_then(Self::to_f64).unwrap_or(0.0);
+                Ok(Some(math::vec3_new(x, y, z)))
+            }
+            "vec3_add" | "vec3_sub" | "vec3_dot" | "vec3_cross" | "vec3_distance"
+                if args.len() >= 2 =>
+            {
+                Ok(Some(match func_name {
+                    "vec3_add" => math::vec3_add(&args[0], &args[1]),
+                    "vec3_sub" => math::vec3_sub(&args[0], &args[1]),
+                    "vec3_dot" => Value::Float(math::vec3_dot(&args[0], &args[1])),
+                    "vec3_cross" => math::vec3_cross(&args[0], &args[1]),
+                    _ => Value::Float(math::vec3_distance(&args[0], &args[1])),
+                }))
+            }
+            "vec3_scale" if args.len() >= 2 => {
+                let s = Self::to_f64(&args[1]).unwrap_or(0.0);
+                Ok(Some(math::vec3_scale(&args[0], s)))
+            }
+            "vec3_length" | "vec3_len" => match args.first() {
+                Some(v) => Ok(Some(Value::Float(math::vec3_len(v)))),
+                None => Ok(Some(Value::None)),
+            },
+            "vec3_normalize" => match args.first() {
+                Some(v) => Ok(Some(math::vec3_normalize(v))),
+                None => Ok(Some(Value::None)),
+            },
+            "vec3_lerp" if args.len() >= 3 => {
+                let t = Self::to_f64(&args[2]).unwrap_or(0.0);
+                Ok(Some(math::vec3_lerp(&args[0], &args[1], t)))
+            }
+            "vec3_rotate" if args.len() >= 3 => {
+                let angle = Self::to_f64(&args[2]).unwrap_or(0.0);
+                Ok(Some(math::vec3_rotate(&args[0], &args[1], angle)))
+            }
 
             // Time - New functions
             "now_secs" => Ok(Some(time::now_secs())),
             "monotonic_ms" => Ok(Some(Value::Int(time::monotonic_ms()))),
+            // Localization (bare `load` is left to user functions)
+            "load" if name.contains("i18n") => i18n::load(Self::str_arg(args, 0)).map(Some),
+            "tr" => Ok(Some(Value::String(i18n::tr(
+                Self::str_arg(args, 0),
+                args.get(1..).unwrap_or(&[]),
+            )))),
+            "set_language" => Ok(Some(Value::Bool(i18n::set_language(Self::str_arg(
+                args, 0,
+            ))))),
+            "language" => Ok(Some(Value::String(i18n::language()))),
+
             "instant_now" => Ok(Some(time::instant_now())),
             "instant_elapsed" => {
                 if let Some(Value::Int(id)) = args.first() {
@@ -652,8 +1089,115 @@ impl StdLib {
                     Ok(Some(Value::Array(Vec::new())))
                 }
             }
+            "read_file_bytes" => {
+                if let Some(Value::String(path)) = args.first() {
+                    fs::read_file_bytes(path).map(Some)
+                } else {
+                    Ok(Some(Value::None))
+                }
+            }
+            "write_file_bytes" => {
+                if let (Some(Value::String(path)), Some(bytes)) = (args.first(), args.get(1)) {
+                    fs::write_file_bytes(path, bytes).map(Some)
+                } else {
+                    Ok(Some(Value::Bool(false)))
+                }
+            }
             "current_dir" => fs::current_dir().map(Some),
 
+            // Binary serialization (to_bytes needs heap access and lives in the VM)
+            "from_bytes" => {
+                let bytes = args
+                    .first()
+                    .and_then(serde::value_to_bytes)
+                    .ok_or_else(|| {
+                        ZyraError::new(
+                            "SerdeError",
+                            "from_bytes expects an array of byte values (0-255)",
+                            None,
+                        )
+                    })?;
+                serde::decode(&bytes)
+                    .map(Some)
+                    .map_err(|e| ZyraError::new("SerdeError", &e, None))
+            }
+
+            // JSON
+            "json_parse" => match args.first() {
+                Some(Value::String(text)) => json::parse(text)
+                    .map(Some)
+                    .map_err(|e| ZyraError::new("JsonError", &format!("json_parse: {}", e), None)),
+                _ => Err(ZyraError::new(
+                    "JsonError",
+                    "json_parse expects a String of JSON text",
+                    None,
+                )),
+            },
+            "json_stringify" => Ok(Some(Value::String(json::stringify(
+                args.first().unwrap_or(&Value::None),
+            )))),
+
+            // Networking
+            "tcp_connect" => {
+                let port = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                Ok(Some(net::tcp_connect(Self::str_arg(args, 0), port)))
+            }
+            "tcp_send" => {
+                let socket = args.first().and_then(Self::to_i64).unwrap_or(0);
+                Ok(Some(net::tcp_send(socket, Self::str_arg(args, 1))))
+            }
+            "tcp_recv" => {
+                let socket = args.first().and_then(Self::to_i64).unwrap_or(0);
+                let max = args.get(1).and_then(Self::to_i64).unwrap_or(4096);
+                Ok(Some(net::tcp_recv(socket, max)))
+            }
+            "tcp_close" => {
+                let socket = args.first().and_then(Self::to_i64).unwrap_or(0);
+                Ok(Some(net::tcp_close(socket)))
+            }
+            "http_get" => Ok(Some(net::http_get(Self::str_arg(args, 0)))),
+            "net_timeout" => {
+                net::set_timeout(args.first().and_then(Self::to_i64).unwrap_or(5000));
+                Ok(Some(Value::None))
+            }
+
+            // Audio
+            "load_sound" => Ok(Some(Value::Int(audio::load_sound(Self::str_arg(args, 0))))),
+            "play_sound" => {
+                let id = args.first().and_then(Self::to_i64).unwrap_or(0);
+                Ok(Some(Value::Bool(audio::play_sound(id))))
+            }
+            "play_music" => {
+                let looping = matches!(args.get(1), Some(Value::Bool(true)));
+                Ok(Some(Value::Bool(audio::play_music(
+                    Self::str_arg(args, 0),
+                    looping,
+                ))))
+            }
+            "stop_music" => {
+                audio::stop_music();
+                Ok(Some(Value::None))
+            }
+            "set_volume" => {
+                audio::set_volume(args.first().and_then(Self::to_f64).unwrap_or(1.0));
+                Ok(Some(Value::None))
+            }
+
+            // Property-test generators (forall calls a closure and lives in the VM)
+            "gen_int" => {
+                let min = args.first().and_then(Self::to_i64).unwrap_or(i64::MIN);
+                let max = args.get(1).and_then(Self::to_i64).unwrap_or(i64::MAX);
+                test::gen_int(min, max).map(Some)
+            }
+            "gen_string" => {
+                let max_len = args.first().and_then(Self::to_i64).unwrap_or(16);
+                Ok(Some(test::gen_string(max_len)))
+            }
+            "gen_vec" => {
+                let max_len = args.get(1).and_then(Self::to_i64).unwrap_or(16);
+                test::gen_vec(args.first().unwrap_or(&Value::None), max_len).map(Some)
+            }
+
             // Environment functions
             "args" => Ok(Some(env::args())),
             "args_count" => Ok(Some(Value::Int(env::args_count()))),
@@ -664,6 +1208,27 @@ impl StdLib {
                     Ok(Some(Value::None))
                 }
             }
+            "env_int" => {
+                let default = args.get(1).and_then(Self::to_i64).unwrap_or(0);
+                Ok(Some(Value::Int(env::env_int(
+                    Self::str_arg(args, 0),
+                    default,
+                ))))
+            }
+            "env_bool" => {
+                let default = matches!(args.get(1), Some(Value::Bool(true)));
+                Ok(Some(Value::Bool(env::env_bool(
+                    Self::str_arg(args, 0),
+                    default,
+                ))))
+            }
+            "load_dotenv" => {
+                let path = match args.first() {
+                    Some(Value::String(path)) => path.as_str(),
+                    _ => ".env",
+                };
+                env::load_dotenv(path).map(|n| Some(Value::Int(n)))
+            }
             "os_name" => Ok(Some(env::os_name())),
             "os_arch" => Ok(Some(env::os_arch())),
             "is_windows" => Ok(Some(Value::Bool(env::is_windows()))),
@@ -865,21 +1430,21 @@ impl StdLib {
             // Option/Result constructors
             "Some" => {
                 if let Some(value) = args.first() {
-                    Ok(Some(Value::some(value.clone())))
+                    Ok(Some(check_nesting(Value::some(value.clone()))?))
                 } else {
                     Ok(Some(Value::None))
                 }
             }
             "Ok" => {
                 if let Some(value) = args.first() {
-                    Ok(Some(Value::ok(value.clone())))
+                    Ok(Some(check_nesting(Value::ok(value.clone()))?))
                 } else {
                     Ok(Some(Value::ok(Value::None)))
                 }
             }
             "Err" | "Error" => {
                 if let Some(value) = args.first() {
-                    Ok(Some(Value::err(value.clone())))
+                    Ok(Some(check_nesting(Value::err(value.clone()))?))
                 } else {
                     Ok(Some(Value::err(Value::None)))
                 }
@@ -0,0 +1,307 @@
+//! Network module for Zyra standard library
+//!
+//! Plain TCP and HTTP, enough for leaderboards and multiplayer prototypes:
+//! - tcp_connect(host, port): Ok(socket id)
+//! - tcp_send(socket, text): Ok(bytes sent)
+//! - tcp_recv(socket, max_bytes): Ok(text), "" once the other side closed
+//! - tcp_close(socket): false if the socket was already closed
+//! - http_get(url): Ok(body) of a 2xx response
+//! - net_timeout(ms): limit for connecting, sending and receiving (5000 by default)
+//!
+//! Nothing here panics: failures, timeouts included, come back as Err with a
+//! message. Only `http://` URLs are supported, as there is no TLS.
+
+use crate::compiler::bytecode::Value;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Largest read `tcp_recv` does at once
+const MAX_RECV: usize = 1 << 20;
+
+thread_local! {
+    /// Open sockets, keyed by the id handed to the program
+    static SOCKETS: RefCell<HashMap<i64, TcpStream>> = RefCell::new(HashMap::new());
+    static NEXT_SOCKET_ID: Cell<i64> = const { Cell::new(1) };
+    static TIMEOUT: Cell<Duration> = const { Cell::new(Duration::from_millis(5000)) };
+}
+
+/// Set the timeout for later connects, sends and receives
+pub fn set_timeout(ms: i64) {
+    TIMEOUT.with(|t| t.set(Duration::from_millis(ms.max(1) as u64)));
+}
+
+fn timeout() -> Duration {
+    TIMEOUT.with(Cell::get)
+}
+
+/// Describe an I/O error, naming timeouts as such
+fn describe(action: &str, error: std::io::Error) -> String {
+    match error.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut => {
+            format!("{} timed out after {}ms", action, timeout().as_millis())
+        }
+        _ => format!("{} failed: {}", action, error),
+    }
+}
+
+fn open(host: &str, port: i64) -> Result<TcpStream, String> {
+    let port = u16::try_from(port)
+        .ok()
+        .filter(|&p| p != 0)
+        .ok_or_else(|| format!("Invalid port {}, expected 1 to 65535", port))?;
+    let addresses = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Cannot resolve '{}': {}", host, e))?;
+
+    let mut last_error = format!("Cannot resolve '{}'", host);
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, timeout()) {
+            Ok(stream) => {
+                let _ = stream.set_read_timeout(Some(timeout()));
+                let _ = stream.set_write_timeout(Some(timeout()));
+                return Ok(stream);
+            }
+            Err(e) => last_error = describe(&format!("Connecting to {}:{}", host, port), e),
+        }
+    }
+    Err(last_error)
+}
+
+fn with_socket<T>(
+    id: i64,
+    f: impl FnOnce(&mut TcpStream) -> Result<T, String>,
+) -> Result<T, String> {
+    SOCKETS.with(|sockets| match sockets.borrow_mut().get_mut(&id) {
+        Some(stream) => f(stream),
+        None => Err(format!("Socket {} is not open", id)),
+    })
+}
+
+fn to_result(result: Result<Value, String>) -> Value {
+    match result {
+        Ok(value) => Value::ok(value),
+        Err(message) => Value::err(Value::String(message)),
+    }
+}
+
+// ===== TCP =====
+
+/// Open a TCP connection
+pub fn tcp_connect(host: &str, port: i64) -> Value {
+    to_result(open(host, port).map(|stream| {
+        let id = NEXT_SOCKET_ID.with(|next| next.replace(next.get() + 1));
+        SOCKETS.with(|sockets| sockets.borrow_mut().insert(id, stream));
+        Value::Int(id)
+    }))
+}
+
+/// Send all of `text` over a socket
+pub fn tcp_send(id: i64, text: &str) -> Value {
+    to_result(with_socket(id, |stream| {
+        stream
+            .write_all(text.as_bytes())
+            .map(|_| Value::Int(text.len() as i64))
+            .map_err(|e| describe("tcp_send", e))
+    }))
+}
+
+/// Receive up to `max` bytes as text
+pub fn tcp_recv(id: i64, max: i64) -> Value {
+    to_result(with_socket(id, |stream| {
+        let mut buffer = vec![0; max.clamp(1, MAX_RECV as i64) as usize];
+        let n = stream
+            .read(&mut buffer)
+            .map_err(|e| describe("tcp_recv", e))?;
+        Ok(Value::String(
+            String::from_utf8_lossy(&buffer[..n]).into_owned(),
+        ))
+    }))
+}
+
+/// Close a socket; false if it wasn't open
+pub fn tcp_close(id: i64) -> Value {
+    Value::Bool(SOCKETS.with(|sockets| sockets.borrow_mut().remove(&id).is_some()))
+}
+
+// ===== HTTP =====
+
+/// GET a URL and return the body of a successful response
+pub fn http_get(url: &str) -> Value {
+    to_result(fetch(url).map(Value::String))
+}
+
+fn fetch(url: &str) -> Result<String, String> {
+    let (host, port, path) = parse_url(url)?;
+    let mut stream = open(&host, port)?;
+    let host_header = match port {
+        80 => host,
+        _ => format!("{}:{}", host, port),
+    };
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: zyra\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+        path, host_header
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| describe("http_get", e))?;
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .map_err(|e| describe("http_get", e))?;
+    parse_response(&response)
+}
+
+/// Split an `http://host[:port][/path]` URL
+fn parse_url(url: &str) -> Result<(String, i64, String), String> {
+    let rest = match url.split_once("://") {
+        Some(("http", rest)) => rest,
+        Some(("https", _)) => {
+            return Err(format!(
+                "Cannot fetch '{}': https is not supported, only http",
+                url
+            ))
+        }
+        _ => return Err(format!("Invalid URL '{}', expected http://host/path", url)),
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|_| format!("Invalid port in URL '{}'", url))?,
+        ),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("Invalid URL '{}': no host", url));
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+/// Body of a raw HTTP response, or the status as an error if it isn't 2xx
+fn parse_response(response: &[u8]) -> Result<String, String> {
+    let split = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or("Malformed HTTP response: no end of headers")?;
+    let head = String::from_utf8_lossy(&response[..split]);
+    let body = &response[split + 4..];
+
+    let mut lines = head.lines();
+    let status_line = lines.next().unwrap_or_default();
+    let mut parts = status_line.splitn(3, ' ');
+    let status: u16 = match (parts.next(), parts.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/") => code
+            .parse()
+            .map_err(|_| format!("Malformed HTTP status line '{}'", status_line))?,
+        _ => return Err(format!("Malformed HTTP status line '{}'", status_line)),
+    };
+    if !(200..300).contains(&status) {
+        return Err(format!(
+            "HTTP {}",
+            status_line.split_once(' ').map_or("", |(_, rest)| rest)
+        ));
+    }
+
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    let body = if chunked {
+        decode_chunked(body)?
+    } else {
+        body.to_vec()
+    };
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let malformed = || "Malformed chunked HTTP body".to_string();
+    let mut out = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(malformed)?;
+        let size_text = String::from_utf8_lossy(&body[..line_end]);
+        let size_text = size_text.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_text, 16).map_err(|_| malformed())?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(out);
+        }
+        // Sizes come from the server, so a huge one must not overflow
+        let chunk = body.get(..size).ok_or_else(malformed)?;
+        out.extend_from_slice(chunk);
+        body = size
+            .checked_add(2)
+            .and_then(|end| body.get(end..))
+            .ok_or_else(malformed)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_urls_and_responses() {
+        assert_eq!(
+            parse_url("http://example.com:8080/scores?top=10"),
+            Ok((
+                "example.com".to_string(),
+                8080,
+                "/scores?top=10".to_string()
+            ))
+        );
+        assert!(parse_url("https://example.com")
+            .unwrap_err()
+            .contains("https"));
+
+        let chunked = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n";
+        assert_eq!(parse_response(chunked), Ok("Wikipedia".to_string()));
+        let oversized = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nFFFFFFFFFFFFFFFF\r\nWiki\r\n0\r\n\r\n";
+        assert_eq!(
+            parse_response(oversized),
+            Err("Malformed chunked HTTP body".to_string())
+        );
+        let missing = b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n";
+        assert_eq!(
+            parse_response(missing),
+            Err("HTTP 404 Not Found".to_string())
+        );
+    }
+
+    #[test]
+    fn test_http_get_and_tcp_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 512];
+            let n = stream.read(&mut request).unwrap();
+            assert!(request[..n].starts_with(b"GET /top HTTP/1.1\r\n"));
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello")
+                .unwrap();
+        });
+        assert_eq!(
+            http_get(&format!("http://127.0.0.1:{}/top", port)),
+            Value::ok(Value::String("hello".to_string()))
+        );
+        server.join().unwrap();
+
+        assert!(matches!(tcp_connect("127.0.0.1", 70000), Value::Err(_)));
+        assert!(matches!(tcp_send(999, "x"), Value::Err(_)));
+        assert_eq!(tcp_close(999), Value::Bool(false));
+    }
+}
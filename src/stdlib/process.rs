@@ -5,9 +5,8 @@
 //! - spawn child processes
 //! - execute commands
 
-use crate::compiler::bytecode::Value;
+use crate::compiler::bytecode::{OrderedMap, Value};
 use crate::error::{ZyraError, ZyraResult};
-use std::collections::HashMap;
 use std::process::{Command, Stdio};
 
 /// Exit the program with a status code
@@ -40,7 +39,7 @@ pub fn exec(command: &str, args: &[String]) -> ZyraResult<Value> {
             let code = output.status.code().unwrap_or(-1) as i64;
             let success = output.status.success();
 
-            let mut map = HashMap::new();
+            let mut map = OrderedMap::new();
             map.insert(
                 "_type".to_string(),
                 Value::String("ProcessResult".to_string()),
@@ -80,7 +79,7 @@ pub fn shell(command: &str) -> ZyraResult<Value> {
             let code = output.status.code().unwrap_or(-1) as i64;
             let success = output.status.success();
 
-            let mut map = HashMap::new();
+            let mut map = OrderedMap::new();
             map.insert(
                 "_type".to_string(),
                 Value::String("ProcessResult".to_string()),
@@ -103,7 +102,7 @@ pub fn shell(command: &str) -> ZyraResult<Value> {
 pub fn spawn(command: &str, args: &[String]) -> ZyraResult<Value> {
     match Command::new(command).args(args).spawn() {
         Ok(child) => {
-            let mut map = HashMap::new();
+            let mut map = OrderedMap::new();
             map.insert("_type".to_string(), Value::String("Process".to_string()));
             map.insert("id".to_string(), Value::Int(child.id() as i64));
             map.insert("command".to_string(), Value::String(command.to_string()));
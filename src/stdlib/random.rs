@@ -0,0 +1,83 @@
+//! Random module for Zyra standard library
+//!
+//! Every VM owns one deterministic generator (SplitMix64, `math::SeededRng`)
+//! that all of these draw from:
+//! - seed(n): restart the sequence, so a run seeded the same way draws the
+//!   same numbers and a replay reproduces exactly
+//! - random(min, max): int in min..=max
+//! - random_f32(), random_float(): float in [0, 1)
+//! - shuffle(v): a shuffled copy of a Vec or array
+//! - choice(v): Some(random element), or None when empty
+//!
+//! Until `seed` is called the generator starts from the clock.
+
+use super::math::SeededRng;
+use crate::compiler::bytecode::Value;
+
+/// Seed for a generator nobody seeded: different on every run
+pub fn clock_seed() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as i64)
+        .unwrap_or(0)
+}
+
+/// Uniform int in `min..=max`; the bounds may come in either order
+pub fn range(rng: &mut SeededRng, min: i64, max: i64) -> i64 {
+    let (min, max) = if min <= max { (min, max) } else { (max, min) };
+    let span = (max as i128 - min as i128 + 1) as u128;
+    if span > u64::MAX as u128 {
+        return rng.next_u64() as i64;
+    }
+    (min as i128 + below(rng, span as u64) as i128) as i64
+}
+
+/// Uniform in `0..n` without modulo bias; `n` must be non-zero
+pub fn below(rng: &mut SeededRng, n: u64) -> u64 {
+    let zone = u64::MAX - u64::MAX % n;
+    loop {
+        let x = rng.next_u64();
+        if x < zone {
+            return x % n;
+        }
+    }
+}
+
+/// Shuffle in place (Fisher-Yates)
+pub fn shuffle(rng: &mut SeededRng, items: &mut [Value]) {
+    for i in (1..items.len()).rev() {
+        let j = below(rng, i as u64 + 1) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Some(random element), or None for an empty slice
+pub fn choice(rng: &mut SeededRng, items: &[Value]) -> Value {
+    if items.is_empty() {
+        return Value::None;
+    }
+    let i = below(rng, items.len() as u64) as usize;
+    Value::some(items[i].clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_draws() {
+        let draw = |seed| {
+            let mut rng = SeededRng::new(seed);
+            let mut items: Vec<Value> = (0..10).map(Value::Int).collect();
+            shuffle(&mut rng, &mut items);
+            (range(&mut rng, 1, 6), choice(&mut rng, &items), items)
+        };
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42).2, draw(43).2);
+
+        let mut rng = SeededRng::new(7);
+        assert!((0..1000).all(|_| (-3..=3).contains(&range(&mut rng, 3, -3))));
+        assert_eq!(choice(&mut rng, &[]), Value::None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Display backends for std::game
+//!
+//! std::game draws into a plain pixel buffer and hands every finished frame to
+//! a `Renderer`, which also reports the player's input. `WindowRenderer` shows
+//! frames in an OS window through minifb; it is built with the `window`
+//! feature, on by default. `SoftwareRenderer` keeps frames in memory and plays
+//! back scripted input: it serves `--headless` runs and `zyra test`, and every
+//! window of a build without the `window` feature, so the crate builds on
+//! servers without a display stack. Supporting a new platform means adding a
+//! `Renderer`; the std::game functions stay as they are.
+
+mod software;
+#[cfg(feature = "window")]
+mod window;
+
+pub use software::SoftwareRenderer;
+#[cfg(feature = "window")]
+pub use window::WindowRenderer;
+
+/// Keys std::game reports, whatever the backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    W,
+    A,
+    S,
+    D,
+    Up,
+    Down,
+    Left,
+    Right,
+    Space,
+    Enter,
+    Escape,
+}
+
+/// A place finished frames are shown, with the input of whoever watches it
+pub trait Renderer {
+    /// Whether the window is still open; pressing Escape closes it
+    fn is_open(&self) -> bool;
+
+    fn is_key_down(&self, key: Key) -> bool;
+
+    /// Mouse position in window pixels, if the backend knows it
+    fn mouse_pos(&self) -> Option<(i64, i64)>;
+
+    /// Whether the left mouse button is held
+    fn mouse_down(&self) -> bool;
+
+    /// Characters typed since the last frame ('\u{8}' = backspace)
+    fn take_typed_chars(&mut self) -> Vec<char>;
+
+    /// Show a finished frame and advance the frame clock
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize);
+
+    fn set_title(&mut self, title: &str);
+
+    /// Change the window's size; false if the window had to close
+    fn resize(&mut self, width: usize, height: usize) -> bool;
+
+    /// Set the window icon from a file; false where icons aren't supported
+    fn set_icon(&mut self, _path: &str) -> bool {
+        false
+    }
+
+    /// This renderer as a software one, whose input can be scripted
+    fn as_software(&mut self) -> Option<&mut SoftwareRenderer> {
+        None
+    }
+}
+
+/// Environment variable that makes every window headless, e.g. on CI
+/// machines without a display: `ZYRA_HEADLESS=1`
+pub const HEADLESS_ENV: &str = "ZYRA_HEADLESS";
+
+/// Backend new windows are opened with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// An OS window, or a software one in builds without the `window` feature
+    Window,
+    /// Frames kept in memory; each window closes after `frame_limit` frames
+    Software { frame_limit: u64 },
+}
+
+impl Backend {
+    /// Backend a program starts with: software if `ZYRA_HEADLESS` is set to
+    /// anything but "" or "0", windows otherwise
+    pub fn from_env() -> Self {
+        match std::env::var(HEADLESS_ENV) {
+            Ok(value) if !matches!(value.trim(), "" | "0") => Backend::Software {
+                frame_limit: super::game::HEADLESS_FRAME_LIMIT,
+            },
+            _ => Backend::Window,
+        }
+    }
+
+    /// Open a window of this backend, None if the OS refuses one
+    pub fn open(self, width: usize, height: usize, title: &str) -> Option<Box<dyn Renderer>> {
+        match self {
+            #[cfg(feature = "window")]
+            Backend::Window => WindowRenderer::open(width, height, title)
+                .map(|renderer| Box::new(renderer) as Box<dyn Renderer>),
+            #[cfg(not(feature = "window"))]
+            Backend::Window => {
+                let _ = (width, height, title);
+                Some(Box::new(SoftwareRenderer::new(
+                    super::game::HEADLESS_FRAME_LIMIT,
+                )))
+            }
+            Backend::Software { frame_limit } => Some(Box::new(SoftwareRenderer::new(frame_limit))),
+        }
+    }
+}
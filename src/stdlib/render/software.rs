@@ -0,0 +1,95 @@
+//! Software renderer: frames stay in memory and input is scripted
+
+use std::collections::HashSet;
+
+use super::{Key, Renderer};
+use crate::stdlib::game::HEADLESS_DELTA;
+use crate::stdlib::time;
+
+/// Simulated display and input, used instead of an OS window by
+/// `zyra run --headless` and `zyra test`
+pub struct SoftwareRenderer {
+    /// The window reports itself closed after this many frames
+    pub frame_limit: u64,
+    /// Frames displayed since the window was created
+    pub frames: u64,
+    pub keys: HashSet<Key>,
+    pub mouse_x: i64,
+    pub mouse_y: i64,
+    pub mouse_down: bool,
+    /// Text delivered as typed characters on the next frame
+    pub text: Vec<char>,
+    /// Draw calls since the screen was last cleared, like `rect(0, 0, 8, 8, #ffffff)`
+    pub draw_calls: Vec<String>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(frame_limit: u64) -> Self {
+        Self {
+            frame_limit,
+            frames: 0,
+            keys: HashSet::new(),
+            mouse_x: 0,
+            mouse_y: 0,
+            mouse_down: false,
+            text: Vec::new(),
+            draw_calls: Vec::new(),
+        }
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn is_open(&self) -> bool {
+        self.frames < self.frame_limit && !self.keys.contains(&Key::Escape)
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.keys.contains(&key)
+    }
+
+    fn mouse_pos(&self) -> Option<(i64, i64)> {
+        Some((self.mouse_x, self.mouse_y))
+    }
+
+    fn mouse_down(&self) -> bool {
+        self.mouse_down
+    }
+
+    fn take_typed_chars(&mut self) -> Vec<char> {
+        std::mem::take(&mut self.text)
+    }
+
+    fn present(&mut self, _buffer: &[u32], _width: usize, _height: usize) {
+        // Nothing to show: just advance the clock by a fixed step, so a
+        // headless run behaves the same on every machine
+        self.frames += 1;
+        time::frame_tick_fixed(HEADLESS_DELTA);
+    }
+
+    fn set_title(&mut self, _title: &str) {}
+
+    fn resize(&mut self, _width: usize, _height: usize) -> bool {
+        true
+    }
+
+    fn as_software(&mut self) -> Option<&mut SoftwareRenderer> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_software_window_closes_after_its_frames() {
+        let mut renderer: Box<dyn Renderer> = Box::new(SoftwareRenderer::new(2));
+        renderer.present(&[0; 4], 2, 2);
+        assert!(renderer.is_open());
+        renderer.as_software().unwrap().keys.insert(Key::Escape);
+        assert!(!renderer.is_open());
+        renderer.as_software().unwrap().keys.clear();
+        renderer.present(&[0; 4], 2, 2);
+        assert!(!renderer.is_open());
+    }
+}
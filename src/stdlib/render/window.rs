@@ -0,0 +1,176 @@
+//! Window renderer: frames are shown in an OS window through minifb
+
+use minifb::{KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+
+use super::{Key, Renderer};
+use crate::stdlib::time;
+
+/// An OS window
+pub struct WindowRenderer {
+    window: Window,
+    title: String,
+}
+
+impl WindowRenderer {
+    /// Open a window, None if the OS refuses one
+    pub fn open(width: usize, height: usize, title: &str) -> Option<Self> {
+        Some(Self {
+            window: Self::new_window(width, height, title)?,
+            title: title.to_string(),
+        })
+    }
+
+    fn new_window(width: usize, height: usize, title: &str) -> Option<Window> {
+        let options = WindowOptions {
+            resize: false,
+            scale: minifb::Scale::X1,
+            ..WindowOptions::default()
+        };
+        Window::new(title, width, height, options).ok()
+    }
+}
+
+impl Renderer for WindowRenderer {
+    fn is_open(&self) -> bool {
+        self.window.is_open() && !self.window.is_key_down(minifb::Key::Escape)
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.window.is_key_down(minifb_key(key))
+    }
+
+    fn mouse_pos(&self) -> Option<(i64, i64)> {
+        self.window
+            .get_mouse_pos(MouseMode::Clamp)
+            .map(|(x, y)| (x as i64, y as i64))
+    }
+
+    fn mouse_down(&self) -> bool {
+        self.window.get_mouse_down(MouseButton::Left)
+    }
+
+    fn take_typed_chars(&mut self) -> Vec<char> {
+        let shift = self.window.is_key_down(minifb::Key::LeftShift)
+            || self.window.is_key_down(minifb::Key::RightShift);
+        self.window
+            .get_keys_pressed(KeyRepeat::Yes)
+            .into_iter()
+            .filter_map(|key| key_to_char(key, shift))
+            .collect()
+    }
+
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        self.window.update_with_buffer(buffer, width, height).ok();
+        time::frame_tick(self.window.is_active());
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+        self.title = title.to_string();
+    }
+
+    /// minifb windows can't be resized from code, so the window is closed
+    /// and reopened
+    fn resize(&mut self, width: usize, height: usize) -> bool {
+        match Self::new_window(width, height, &self.title) {
+            Some(window) => {
+                self.window = window;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// On Windows: expects .ico file
+    /// On Linux/macOS/Wayland: Not supported (minifb Icon API not available on Wayland)
+    fn set_icon(&mut self, path: &str) -> bool {
+        #[cfg(target_os = "windows")]
+        {
+            use minifb::Icon;
+            use std::str::FromStr;
+
+            // Windows requires .ico file
+            match Icon::from_str(path) {
+                Ok(icon) => {
+                    self.window.set_icon(icon);
+                    true
+                }
+                Err(_) => false,
+            }
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            // Linux (X11/Wayland) and macOS: Icon API not reliably available
+            // minifb's Icon::from_argb doesn't compile on Wayland backend
+            let _ = path;
+            false
+        }
+    }
+}
+
+fn minifb_key(key: Key) -> minifb::Key {
+    match key {
+        Key::W => minifb::Key::W,
+        Key::A => minifb::Key::A,
+        Key::S => minifb::Key::S,
+        Key::D => minifb::Key::D,
+        Key::Up => minifb::Key::Up,
+        Key::Down => minifb::Key::Down,
+        Key::Left => minifb::Key::Left,
+        Key::Right => minifb::Key::Right,
+        Key::Space => minifb::Key::Space,
+        Key::Enter => minifb::Key::Enter,
+        Key::Escape => minifb::Key::Escape,
+    }
+}
+
+/// Map a key press to the character it types
+fn key_to_char(key: minifb::Key, shift: bool) -> Option<char> {
+    use minifb::Key;
+    let c = match key {
+        Key::A => 'a',
+        Key::B => 'b',
+        Key::C => 'c',
+        Key::D => 'd',
+        Key::E => 'e',
+        Key::F => 'f',
+        Key::G => 'g',
+        Key::H => 'h',
+        Key::I => 'i',
+        Key::J => 'j',
+        Key::K => 'k',
+        Key::L => 'l',
+        Key::M => 'm',
+        Key::N => 'n',
+        Key::O => 'o',
+        Key::P => 'p',
+        Key::Q => 'q',
+        Key::R => 'r',
+        Key::S => 's',
+        Key::T => 't',
+        Key::U => 'u',
+        Key::V => 'v',
+        Key::W => 'w',
+        Key::X => 'x',
+        Key::Y => 'y',
+        Key::Z => 'z',
+        Key::Key0 => '0',
+        Key::Key1 => '1',
+        Key::Key2 => '2',
+        Key::Key3 => '3',
+        Key::Key4 => '4',
+        Key::Key5 => '5',
+        Key::Key6 => '6',
+        Key::Key7 => '7',
+        Key::Key8 => '8',
+        Key::Key9 => '9',
+        Key::Space => ' ',
+        Key::Minus => '-',
+        Key::Period => '.',
+        Key::Comma => ',',
+        Key::Backspace => return Some('\u{8}'),
+        _ => return None,
+    };
+    Some(if shift { c.to_ascii_uppercase() } else { c })
+}
@@ -0,0 +1,589 @@
+//! Binary serialization module for Zyra standard library
+//!
+//! Provides a compact, versioned encoding for save files and network packets:
+//! - to_bytes(value): encode a value as an array of byte ints (0..=255)
+//! - from_bytes(bytes): decode bytes produced by to_bytes
+//! - validate(value, Schema): check a loaded object against a struct definition
+//!
+//! Layout: `ZSER` magic, one version byte, then the encoded value.
+//! Lengths and counts are LEB128 varints. Every struct field is stored as
+//! name + length-prefixed value, so a decoder that meets a field whose value
+//! it cannot read (e.g. a tag added by a newer version) skips just that field.
+//! Fields the reading program does not declare are kept in the object and
+//! ignored by it, and trailing bytes after the value are ignored.
+//! Data nested more than 128 values deep is rejected as corrupt.
+//!
+//! Functions, closures, and windows have no stable encoding and are written as None.
+
+use crate::compiler::bytecode::{OrderedMap, Value};
+use crate::semantic::ZyraType;
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 4] = b"ZSER";
+
+/// Current format version
+pub const FORMAT_VERSION: u8 = 1;
+
+// Value tags
+const TAG_NONE: u8 = 0x00;
+const TAG_BOOL: u8 = 0x01;
+const TAG_INT: u8 = 0x02;
+const TAG_FLOAT: u8 = 0x03;
+const TAG_STRING: u8 = 0x04;
+const TAG_CHAR: u8 = 0x05;
+const TAG_VEC: u8 = 0x06;
+const TAG_ARRAY: u8 = 0x07;
+const TAG_OBJECT: u8 = 0x08;
+const TAG_SOME: u8 = 0x09;
+const TAG_OK: u8 = 0x0A;
+const TAG_ERR: u8 = 0x0B;
+const TAG_I8: u8 = 0x0C;
+const TAG_I32: u8 = 0x0D;
+const TAG_U8: u8 = 0x0E;
+const TAG_U32: u8 = 0x0F;
+const TAG_U64: u8 = 0x10;
+const TAG_F32: u8 = 0x11;
+
+// ===== Encoding =====
+
+/// Encode a value with the format header
+/// Heap references must be resolved by the caller; any left over encode as None.
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::with_capacity(64);
+    out.extend_from_slice(MAGIC);
+    out.push(FORMAT_VERSION);
+    write_value(&mut out, value);
+    out
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Bool(b) => {
+            out.push(TAG_BOOL);
+            out.push(*b as u8);
+        }
+        Value::Int(n) | Value::I64(n) => {
+            out.push(TAG_INT);
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+        Value::Float(f) | Value::F64(f) => {
+            out.push(TAG_FLOAT);
+            out.extend_from_slice(&f.to_le_bytes());
+        }
+        Value::String(s) => {
+            out.push(TAG_STRING);
+            write_str(out, s);
+        }
+        Value::Char(c) => {
+            out.push(TAG_CHAR);
+            out.extend_from_slice(&(*c as u32).to_le_bytes());
+        }
+        Value::Vec(items) | Value::List(items) => {
+            out.push(TAG_VEC);
+            write_items(out, items);
+        }
+        Value::Array(items) => {
+            out.push(TAG_ARRAY);
+            write_items(out, items);
+        }
+        Value::Object(fields) => {
+            out.push(TAG_OBJECT);
+            // Sorted so the same value always produces the same bytes
+            let mut names: Vec<&String> = fields.keys().collect();
+            names.sort();
+            write_varint(out, names.len() as u64);
+            let mut field = Vec::new();
+            for name in names {
+                field.clear();
+                write_value(&mut field, &fields[name]);
+                write_str(out, name);
+                write_varint(out, field.len() as u64);
+                out.extend_from_slice(&field);
+            }
+        }
+        Value::Some(inner) => {
+            out.push(TAG_SOME);
+            write_value(out, inner);
+        }
+        Value::Ok(inner) => {
+            out.push(TAG_OK);
+            write_value(out, inner);
+        }
+        Value::Err(inner) => {
+            out.push(TAG_ERR);
+            write_value(out, inner);
+        }
+        Value::I8(n) => {
+            out.push(TAG_I8);
+            out.push(*n as u8);
+        }
+        Value::I32(n) => {
+            out.push(TAG_I32);
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+        Value::U8(n) => {
+            out.push(TAG_U8);
+            out.push(*n);
+        }
+        Value::U32(n) => {
+            out.push(TAG_U32);
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+        Value::U64(n) => {
+            out.push(TAG_U64);
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+        Value::F32(f) => {
+            out.push(TAG_F32);
+            out.extend_from_slice(&f.to_le_bytes());
+        }
+        _ => out.push(TAG_NONE),
+    }
+}
+
+fn write_items(out: &mut Vec<u8>, items: &[Value]) {
+    write_varint(out, items.len() as u64);
+    for item in items {
+        write_value(out, item);
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_varint(out, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    loop {
+        let byte = (n & 0x7F) as u8;
+        n >>= 7;
+        if n == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+// ===== Decoding =====
+
+/// Decode bytes produced by `encode`
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    if bytes.len() < 5 || &bytes[..4] != MAGIC {
+        return Err("not Zyra serialized data (missing 'ZSER' header)".to_string());
+    }
+    let version = bytes[4];
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(format!(
+            "unsupported format version {} (this build reads up to {})",
+            version, FORMAT_VERSION
+        ));
+    }
+    let mut reader = Reader {
+        bytes,
+        pos: MAGIC.len() + 1,
+    };
+    reader.value(0)
+}
+
+/// Values nested deeper than this are rejected instead of overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+/// Start of the error for a tag this version does not know, the one error a
+/// struct field may be skipped for
+const UNKNOWN_TAG: &str = "unknown value tag";
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| format!("unexpected end of data at byte {}", self.pos))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            n |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(format!("varint too long at byte {}", self.pos))
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        let n = self.varint()? as usize;
+        // Every element takes at least one byte, so larger counts are corrupt
+        if n > self.bytes.len() - self.pos {
+            return Err(format!("length {} exceeds remaining data", n));
+        }
+        Ok(n)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| "invalid UTF-8 string".to_string())
+    }
+
+    fn items(&mut self, depth: usize) -> Result<Vec<Value>, String> {
+        let count = self.len()?;
+        (0..count).map(|_| self.value(depth + 1)).collect()
+    }
+
+    /// Struct fields, each length-prefixed so a value of an unknown type skips just that field
+    fn object(&mut self, depth: usize) -> Result<Value, String> {
+        let count = self.len()?;
+        let mut fields = OrderedMap::with_capacity(count);
+        for _ in 0..count {
+            let name = self.string()?;
+            let len = self.len()?;
+            let mut field = Reader {
+                bytes: self.take(len)?,
+                pos: 0,
+            };
+            // Unknown-field tolerance: skip values written by a newer version,
+            // but not ones that are corrupt or nested too deeply
+            match field.value(depth + 1) {
+                Ok(value) => {
+                    fields.insert(name, value);
+                }
+                Err(e) if e.starts_with(UNKNOWN_TAG) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Value::Object(fields))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("value nested too deeply at byte {}", self.pos));
+        }
+        let tag = self.byte()?;
+        Ok(match tag {
+            TAG_NONE => Value::None,
+            TAG_BOOL => Value::Bool(self.byte()? != 0),
+            TAG_INT => Value::Int(i64::from_le_bytes(self.array()?)),
+            TAG_FLOAT => Value::Float(f64::from_le_bytes(self.array()?)),
+            TAG_STRING => Value::String(self.string()?),
+            TAG_CHAR => {
+                let code = u32::from_le_bytes(self.array()?);
+                Value::Char(char::from_u32(code).ok_or("invalid char code")?)
+            }
+            TAG_VEC => Value::Vec(self.items(depth)?),
+            TAG_ARRAY => Value::Array(self.items(depth)?),
+            TAG_OBJECT => self.object(depth)?,
+            TAG_SOME => Value::Some(Box::new(self.value(depth + 1)?)),
+            TAG_OK => Value::Ok(Box::new(self.value(depth + 1)?)),
+            TAG_ERR => Value::Err(Box::new(self.value(depth + 1)?)),
+            TAG_I8 => Value::I8(self.byte()? as i8),
+            TAG_I32 => Value::I32(i32::from_le_bytes(self.array()?)),
+            TAG_U8 => Value::U8(self.byte()?),
+            TAG_U32 => Value::U32(u32::from_le_bytes(self.array()?)),
+            TAG_U64 => Value::U64(u64::from_le_bytes(self.array()?)),
+            TAG_F32 => Value::F32(f32::from_le_bytes(self.array()?)),
+            other => {
+                return Err(format!(
+                    "{} 0x{:02X} at byte {}",
+                    UNKNOWN_TAG,
+                    other,
+                    self.pos - 1
+                ))
+            }
+        })
+    }
+}
+
+// ===== Schema Validation =====
+
+/// Struct layouts by type name, as recorded in the bytecode
+pub type StructLayouts = HashMap<String, Vec<(String, ZyraType)>>;
+
+/// Check a loaded object against a struct definition
+/// Returns a Vec with one SchemaError object per missing, extra, or mismatched
+/// field (empty if the value matches). Heap references must be resolved by the caller.
+pub fn validate(value: &Value, schema: &str, structs: &StructLayouts) -> Value {
+    let mut errors = Vec::new();
+    check_struct(value, schema, structs, "", &mut errors);
+    Value::Vec(errors)
+}
+
+fn check_struct(
+    value: &Value,
+    schema: &str,
+    structs: &StructLayouts,
+    path: &str,
+    errors: &mut Vec<Value>,
+) {
+    let (Value::Object(fields), Some(layout)) = (value, structs.get(schema)) else {
+        errors.push(schema_error("mismatch", path, schema, value.type_name()));
+        return;
+    };
+
+    for (name, ty) in layout {
+        let field_path = join_path(path, name);
+        match fields.get(name) {
+            Some(v) => check_type(v, ty, structs, &field_path, errors),
+            None => errors.push(schema_error("missing", &field_path, &ty.display_name(), "")),
+        }
+    }
+
+    let mut extra: Vec<&String> = fields
+        .keys()
+        .filter(|k| *k != "_type" && !layout.iter().any(|(name, _)| name == *k))
+        .collect();
+    extra.sort();
+    for name in extra {
+        let found = fields[name].type_name();
+        errors.push(schema_error("extra", &join_path(path, name), "", found));
+    }
+}
+
+fn check_type(
+    value: &Value,
+    ty: &ZyraType,
+    structs: &StructLayouts,
+    path: &str,
+    errors: &mut Vec<Value>,
+) {
+    let int = match value {
+        Value::Int(n) | Value::I64(n) => Some(*n as i128),
+        Value::I8(n) => Some(*n as i128),
+        Value::I32(n) => Some(*n as i128),
+        Value::U8(n) => Some(*n as i128),
+        Value::U32(n) => Some(*n as i128),
+        Value::U64(n) => Some(*n as i128),
+        _ => None,
+    };
+
+    let ok = match ty {
+        ZyraType::I8 => int.is_some_and(|n| i8::try_from(n).is_ok()),
+        ZyraType::I32 => int.is_some_and(|n| i32::try_from(n).is_ok()),
+        ZyraType::I64 => int.is_some_and(|n| i64::try_from(n).is_ok()),
+        ZyraType::U8 => int.is_some_and(|n| u8::try_from(n).is_ok()),
+        ZyraType::U32 => int.is_some_and(|n| u32::try_from(n).is_ok()),
+        ZyraType::U64 => int.is_some_and(|n| u64::try_from(n).is_ok()),
+        ZyraType::F32 | ZyraType::F64 => {
+            matches!(value, Value::Float(_) | Value::F64(_) | Value::F32(_))
+        }
+        ZyraType::Bool => matches!(value, Value::Bool(_)),
+        ZyraType::Char => matches!(value, Value::Char(_)),
+        ZyraType::String => matches!(value, Value::String(_)),
+        ZyraType::Vec(elem) | ZyraType::Array { elem, .. } => match value {
+            Value::Vec(items) | Value::List(items) | Value::Array(items) => {
+                if let ZyraType::Array { size, .. } = ty {
+                    if items.len() != *size {
+                        let found = format!("{} elements", items.len());
+                        errors.push(schema_error("mismatch", path, &ty.display_name(), &found));
+                    }
+                }
+                for (i, item) in items.iter().enumerate() {
+                    check_type(item, elem, structs, &format!("{}[{}]", path, i), errors);
+                }
+                true
+            }
+            _ => false,
+        },
+        ZyraType::Struct(name) if structs.contains_key(name) => {
+            check_struct(value, name, structs, path, errors);
+            true
+        }
+        ZyraType::Reference { inner, .. } => {
+            check_type(value, inner, structs, path, errors);
+            true
+        }
+        // Enums, Object, and other types accept any value
+        _ => true,
+    };
+
+    if !ok {
+        errors.push(schema_error(
+            "mismatch",
+            path,
+            &ty.display_name(),
+            value.type_name(),
+        ));
+    }
+}
+
+fn join_path(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+/// Build a SchemaError object: kind is "missing", "extra", or "mismatch"
+fn schema_error(kind: &str, field: &str, expected: &str, found: &str) -> Value {
+    let mut fields = OrderedMap::new();
+    fields.insert(
+        "_type".to_string(),
+        Value::String("SchemaError".to_string()),
+    );
+    fields.insert("kind".to_string(), Value::String(kind.to_string()));
+    fields.insert("field".to_string(), Value::String(field.to_string()));
+    fields.insert("expected".to_string(), Value::String(expected.to_string()));
+    fields.insert("found".to_string(), Value::String(found.to_string()));
+    Value::Object(fields)
+}
+
+// ===== Value Conversion =====
+
+/// Convert bytes to an array of byte ints (same shape as fs::read_file_bytes)
+pub fn bytes_to_value(bytes: Vec<u8>) -> Value {
+    Value::Array(bytes.into_iter().map(|b| Value::Int(b as i64)).collect())
+}
+
+/// Read bytes from an array/Vec of ints; None if any element is not a byte
+pub fn value_to_bytes(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::Array(items) | Value::Vec(items) | Value::List(items) => items
+            .iter()
+            .map(|v| match v {
+                Value::Int(n) | Value::I64(n) => u8::try_from(*n).ok(),
+                Value::I32(n) => u8::try_from(*n).ok(),
+                Value::U8(n) => Some(*n),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player() -> Value {
+        let mut fields = OrderedMap::new();
+        fields.insert("_type".to_string(), Value::String("Player".to_string()));
+        fields.insert("name".to_string(), Value::String("Ana".to_string()));
+        fields.insert("hp".to_string(), Value::Int(-3));
+        fields.insert(
+            "pos".to_string(),
+            Value::Array(vec![Value::Float(1.5), Value::F32(2.0)]),
+        );
+        fields.insert(
+            "items".to_string(),
+            Value::Vec(vec![Value::Some(Box::new(Value::Char('é'))), Value::None]),
+        );
+        Value::Object(fields)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bytes = encode(&player());
+        assert_eq!(&bytes[..5], b"ZSER\x01");
+        assert_eq!(decode(&bytes).unwrap(), player());
+        assert_eq!(value_to_bytes(&bytes_to_value(bytes.clone())), Some(bytes));
+    }
+
+    #[test]
+    fn test_skips_unknown_field_value() {
+        let mut bytes = encode(&player());
+        // Replace the first field's value tag ("_type" sorts first) with an unknown tag
+        let tag_pos = 5 + 1 + 1 + 1 + "_type".len() + 1;
+        assert_eq!(bytes[tag_pos], TAG_STRING);
+        bytes[tag_pos] = 0xEE;
+
+        let Value::Object(fields) = decode(&bytes).unwrap() else {
+            panic!("expected object");
+        };
+        assert!(!fields.contains_key("_type"));
+        assert_eq!(fields["hp"], Value::Int(-3));
+    }
+
+    #[test]
+    fn test_validate_reports_field_errors() {
+        let mut structs = StructLayouts::new();
+        structs.insert(
+            "Player".to_string(),
+            vec![
+                ("name".to_string(), ZyraType::String),
+                ("hp".to_string(), ZyraType::U8),
+                (
+                    "pos".to_string(),
+                    ZyraType::Array {
+                        elem: Box::new(ZyraType::F64),
+                        size: 2,
+                    },
+                ),
+                ("level".to_string(), ZyraType::I32),
+            ],
+        );
+
+        let Value::Vec(errors) = validate(&player(), "Player", &structs) else {
+            panic!("expected a Vec of errors");
+        };
+        let summary: Vec<String> = errors
+            .iter()
+            .map(|e| match e {
+                Value::Object(f) => format!("{} {}", f["kind"], f["field"]),
+                _ => String::new(),
+            })
+            .collect();
+        assert_eq!(summary, ["mismatch hp", "missing level", "extra items"]);
+
+        structs.get_mut("Player").unwrap().truncate(3);
+        structs.get_mut("Player").unwrap().remove(1);
+        let Value::Vec(errors) = validate(&player(), "Player", &structs) else {
+            panic!("expected a Vec of errors");
+        };
+        assert_eq!(errors.len(), 2); // extra hp, extra items
+    }
+
+    #[test]
+    fn test_rejects_bad_header() {
+        assert!(decode(b"ZSER").is_err());
+        assert!(decode(b"JSON\x01\x00").is_err());
+        assert!(decode(b"ZSER\x09\x00").is_err());
+        assert!(decode(b"ZSER\x01\x06\x05").is_err());
+    }
+
+    #[test]
+    fn test_rejects_hostile_nesting() {
+        let mut bytes = b"ZSER\x01".to_vec();
+        bytes.extend_from_slice(&[TAG_SOME; 20_000]);
+        bytes.push(TAG_NONE);
+        assert!(decode(&bytes).unwrap_err().contains("nested too deeply"));
+
+        let mut bytes = b"ZSER\x01".to_vec();
+        bytes.extend_from_slice(&[TAG_SOME; MAX_DEPTH]);
+        bytes.push(TAG_NONE);
+        assert!(decode(&bytes).is_ok());
+
+        // Nested struct fields: the field too deep fails the whole decode
+        let mut value = vec![TAG_NONE];
+        for _ in 0..MAX_DEPTH + 1 {
+            let mut object = vec![TAG_OBJECT, 1];
+            write_str(&mut object, "a");
+            write_varint(&mut object, value.len() as u64);
+            object.extend(value);
+            value = object;
+        }
+        let mut bytes = b"ZSER\x01".to_vec();
+        bytes.extend(value);
+        assert!(decode(&bytes).unwrap_err().contains("nested too deeply"));
+    }
+}
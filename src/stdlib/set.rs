@@ -0,0 +1,123 @@
+//! Set module for Zyra standard library
+//!
+//! Methods of the built-in `Set<T>` type, created with `set{1, 2}`:
+//! - insert, remove, contains
+//! - union, intersection, difference
+//! - len, is_empty, clear, to_vec
+//!
+//! Elements are hashed like map keys (see `MapKey`), so lookups don't scan.
+
+use crate::compiler::bytecode::{MapKey, Value};
+use crate::error::{ZyraError, ZyraResult};
+
+/// Whether a built-in method changes the set it is called on
+pub fn mutates(method: &str) -> bool {
+    matches!(method, "insert" | "remove" | "clear")
+}
+
+fn element_of(method: &str, value: Option<&Value>) -> ZyraResult<MapKey> {
+    let value = value.unwrap_or(&Value::None);
+    MapKey::from_value(value).ok_or_else(|| {
+        ZyraError::new(
+            "TypeError",
+            &format!(
+                "Set::{} expects an integer, string, bool or char, got {}",
+                method,
+                value.type_name()
+            ),
+            None,
+        )
+    })
+}
+
+/// Call a built-in method on a Set (`s.insert(x)`), changing it in place.
+/// Returns None for methods that aren't built in
+pub fn call_method(set: &mut Value, method: &str, args: &[Value]) -> ZyraResult<Option<Value>> {
+    let Value::Set(elements) = set else {
+        return Err(ZyraError::new("TypeError", "Expected a Set", None));
+    };
+    let other = || match args.first() {
+        Some(Value::Set(other)) => Ok(other),
+        _ => Err(ZyraError::new(
+            "TypeError",
+            &format!("Set::{} expects another Set", method),
+            None,
+        )),
+    };
+    let result = match method {
+        // insert and remove report whether the set changed
+        "insert" => Value::Bool(elements.insert(element_of(method, args.first())?)),
+        "remove" => Value::Bool(elements.remove(&element_of(method, args.first())?)),
+        "contains" => Value::Bool(elements.contains(&element_of(method, args.first())?)),
+        // Results keep this set's order, followed by what the other one adds
+        "union" => Value::Set(elements.iter().chain(other()?.iter()).cloned().collect()),
+        "intersection" => {
+            let other = other()?;
+            Value::Set(
+                elements
+                    .iter()
+                    .filter(|e| other.contains(*e))
+                    .cloned()
+                    .collect(),
+            )
+        }
+        "difference" => {
+            let other = other()?;
+            Value::Set(
+                elements
+                    .iter()
+                    .filter(|e| !other.contains(*e))
+                    .cloned()
+                    .collect(),
+            )
+        }
+        "to_vec" => Value::Vec(elements.iter().map(MapKey::to_value).collect()),
+        "len" | "length" => Value::Int(elements.len() as i64),
+        "is_empty" => Value::Bool(elements.is_empty()),
+        "clear" => {
+            elements.clear();
+            Value::None
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_of(items: &[i64]) -> Value {
+        Value::Set(items.iter().map(|&n| MapKey::Int(n)).collect())
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let mut set = set_of(&[1, 2]);
+        let call = |set: &mut Value, method: &str, args: &[Value]| {
+            call_method(set, method, args).unwrap().unwrap()
+        };
+        assert_eq!(
+            call(&mut set, "insert", &[Value::Int(3)]),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            call(&mut set, "insert", &[Value::Int(3)]),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            call(&mut set, "contains", &[Value::I32(2)]),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            call(&mut set, "remove", &[Value::Int(1)]),
+            Value::Bool(true)
+        );
+
+        let other = [set_of(&[3, 4])];
+        assert_eq!(call(&mut set, "union", &other), set_of(&[2, 3, 4]));
+        assert_eq!(call(&mut set, "intersection", &other), set_of(&[3]));
+        assert_eq!(call(&mut set, "difference", &other), set_of(&[2]));
+        assert!(call_method(&mut set, "union", &[Value::Int(1)]).is_err());
+    }
+}
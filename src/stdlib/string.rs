@@ -1,14 +1,17 @@
 //! String module for Zyra standard library
 //!
-//! Provides string operations with UTF-8 support:
-//! - len, is_empty
-//! - slice, char_at
+//! Provides string operations with UTF-8 support. Lengths and indices count
+//! characters (Unicode scalar values), never bytes, so no operation can cut
+//! a multi-byte character in half; `byte_len` gives the encoded size:
+//! - len, byte_len, is_empty
+//! - slice/substring, char_at
 //! - contains, starts_with, ends_with
 //! - to_upper, to_lower
 //! - trim, split, replace
 //! - parse_int, parse_float
 
 use crate::compiler::bytecode::Value;
+use crate::error::{ZyraError, ZyraResult};
 
 /// Get the length of a string (character count, not bytes)
 pub fn string_len(s: &str) -> i64 {
@@ -25,28 +28,29 @@ pub fn string_is_empty(s: &str) -> bool {
     s.is_empty()
 }
 
-/// Get a character at index (returns None if out of bounds)
+/// The character at a character index, or None if out of bounds
+pub fn string_index(s: &str, index: i64) -> Option<char> {
+    usize::try_from(index).ok().and_then(|i| s.chars().nth(i))
+}
+
+/// Get a character at index as an Option (None if out of bounds)
 pub fn string_char_at(s: &str, index: i64) -> Value {
-    if index >= 0 {
-        if let Some(c) = s.chars().nth(index as usize) {
-            return Value::String(c.to_string());
-        }
+    match string_index(s, index) {
+        Some(c) => Value::some(Value::Char(c)),
+        None => Value::None,
     }
-    Value::None
 }
 
-/// Get a substring (by character indices, not bytes)
+/// Get a substring from `start` up to `end` (by character indices, not
+/// bytes); indices are clamped to the string, so this never fails
 pub fn string_slice(s: &str, start: i64, end: i64) -> String {
-    let chars: Vec<char> = s.chars().collect();
-    let len = chars.len() as i64;
-    let start = start.max(0) as usize;
-    let end = end.min(len) as usize;
-
-    if start <= end && start < chars.len() {
-        chars[start..end].iter().collect()
-    } else {
-        String::new()
-    }
+    let len = string_len(s);
+    let start = start.clamp(0, len) as usize;
+    let end = end.clamp(0, len) as usize;
+    s.chars()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect()
 }
 
 /// Check if string contains a substring
@@ -322,3 +326,76 @@ pub fn string_to_f64(s: &str) -> Value {
         Err(_) => Value::None,
     }
 }
+
+/// Call a built-in method on a string (`name.to_upper()`). Strings are values,
+/// so every method returns a new value and leaves the string alone. Returns
+/// None for methods that aren't built in
+pub fn call_method(s: &str, method: &str, args: &[Value]) -> ZyraResult<Option<Value>> {
+    let text = |position: usize| match args.get(position) {
+        Some(Value::String(text)) => Ok(text.clone()),
+        Some(Value::Char(c)) => Ok(c.to_string()),
+        _ => Err(ZyraError::new(
+            "TypeError",
+            &format!("String::{} expects a string argument", method),
+            None,
+        )),
+    };
+    let int = |position: usize| {
+        args.get(position)
+            .and_then(super::StdLib::to_i64)
+            .ok_or_else(|| {
+                ZyraError::new(
+                    "TypeError",
+                    &format!("String::{} expects an integer index", method),
+                    None,
+                )
+            })
+    };
+    let strings = |parts: Vec<&str>| {
+        Value::Vec(
+            parts
+                .into_iter()
+                .map(|part| Value::String(part.to_string()))
+                .collect(),
+        )
+    };
+    let result = match method {
+        "len" | "length" => Value::Int(string_len(s)),
+        "byte_len" => Value::Int(string_byte_len(s)),
+        "is_empty" => Value::Bool(string_is_empty(s)),
+        "char_at" => string_char_at(s, int(0)?),
+        "substring" => Value::String(string_slice(s, int(0)?, int(1)?)),
+        "to_upper" => Value::String(string_to_upper(s)),
+        "to_lower" => Value::String(string_to_lower(s)),
+        "trim" => Value::String(string_trim(s)),
+        "trim_start" => Value::String(string_trim_start(s)),
+        "trim_end" => Value::String(string_trim_end(s)),
+        "reverse" => Value::String(string_reverse(s)),
+        "contains" => Value::Bool(string_contains(s, &text(0)?)),
+        "starts_with" => Value::Bool(string_starts_with(s, &text(0)?)),
+        "ends_with" => Value::Bool(string_ends_with(s, &text(0)?)),
+        "index_of" => Value::Int(string_index_of(s, &text(0)?)),
+        "replace" => Value::String(string_replace(s, &text(0)?, &text(1)?)),
+        "repeat" => {
+            let count = args.first().and_then(super::StdLib::to_i64).unwrap_or(0);
+            Value::String(string_repeat(s, count))
+        }
+        "split" => strings(s.split(text(0)?.as_str()).collect()),
+        "lines" => strings(s.lines().collect()),
+        "chars" => Value::Vec(s.chars().map(Value::Char).collect()),
+        // Option results, unlike the free functions, so a failed parse can be matched
+        "parse_int" | "parse_float" => {
+            let parsed = if method == "parse_int" {
+                string_parse_int(s)
+            } else {
+                string_parse_float(s)
+            };
+            match parsed {
+                Value::None => Value::None,
+                value => Value::some(value),
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
@@ -0,0 +1,237 @@
+//! Property testing for Zyra standard library (`std::test`)
+//!
+//! Generators describe random inputs: `gen_int(min, max)`, `gen_string(max_len)`
+//! and `gen_vec(element, max_len)`. `forall(generator, property)` runs in the VM,
+//! since it calls a closure: it checks the property on `CASES` generated values
+//! and shrinks the first failing one to a simpler counterexample.
+
+use super::math::SeededRng;
+use super::random;
+use crate::compiler::bytecode::{OrderedMap, Value};
+use crate::error::{ZyraError, ZyraResult};
+
+/// Generated values checked per `forall`
+pub const CASES: usize = 100;
+
+/// Upper bound on shrinking steps, so a slow property can't hang a test run
+pub const MAX_SHRINK_STEPS: usize = 1000;
+
+/// Fixed seed: a failing property fails the same way on every run
+pub const SEED: i64 = 0x5eed_2a7a_0f1e_1d05;
+
+/// Characters generated strings are made of, including a space and non-ASCII ones
+const ALPHABET: &[char] = &[
+    'a', 'b', 'c', 'x', 'y', 'z', 'A', 'Z', '0', '1', '9', ' ', '_', '-', '!', 'é', 'ß', '✓',
+];
+
+fn generator(kind: &str, params: Vec<(&str, Value)>) -> Value {
+    let mut fields = OrderedMap::new();
+    fields.insert("_type".to_string(), Value::String("Generator".to_string()));
+    fields.insert("kind".to_string(), Value::String(kind.to_string()));
+    for (name, value) in params {
+        fields.insert(name.to_string(), value);
+    }
+    Value::Object(fields)
+}
+
+/// Integers in `min..=max`
+pub fn gen_int(min: i64, max: i64) -> ZyraResult<Value> {
+    if min > max {
+        return Err(ZyraError::runtime_error(&format!(
+            "gen_int: min {} is greater than max {}",
+            min, max
+        )));
+    }
+    Ok(generator(
+        "int",
+        vec![("min", Value::Int(min)), ("max", Value::Int(max))],
+    ))
+}
+
+/// Strings of up to `max_len` characters
+pub fn gen_string(max_len: i64) -> Value {
+    generator("string", vec![("max_len", Value::Int(max_len.max(0)))])
+}
+
+/// Vecs of up to `max_len` values drawn from another generator
+pub fn gen_vec(element: &Value, max_len: i64) -> ZyraResult<Value> {
+    kind(element)?;
+    Ok(generator(
+        "vec",
+        vec![
+            ("element", element.clone()),
+            ("max_len", Value::Int(max_len.max(0))),
+        ],
+    ))
+}
+
+fn kind(gen: &Value) -> ZyraResult<&str> {
+    match gen {
+        Value::Object(fields) => match (fields.get("_type"), fields.get("kind")) {
+            (Some(Value::String(t)), Some(Value::String(kind))) if t == "Generator" => Ok(kind),
+            _ => Err(not_a_generator()),
+        },
+        _ => Err(not_a_generator()),
+    }
+}
+
+fn not_a_generator() -> ZyraError {
+    ZyraError::runtime_error("expected a generator (gen_int, gen_string or gen_vec)")
+}
+
+fn field<'a>(gen: &'a Value, name: &str) -> &'a Value {
+    match gen {
+        Value::Object(fields) => fields.get(name).unwrap_or(&Value::None),
+        _ => &Value::None,
+    }
+}
+
+fn int_field(gen: &Value, name: &str) -> i64 {
+    match field(gen, name) {
+        Value::Int(n) => *n,
+        _ => 0,
+    }
+}
+
+/// Draw a value from a generator
+pub fn generate(gen: &Value, rng: &mut SeededRng) -> ZyraResult<Value> {
+    match kind(gen)? {
+        "int" => {
+            let (min, max) = (int_field(gen, "min"), int_field(gen, "max"));
+            // One case in eight is an edge value: those are where bugs live
+            if random::below(rng, 8) == 0 {
+                let edges: Vec<i64> = [min, max, 0, 1, -1]
+                    .into_iter()
+                    .filter(|n| (min..=max).contains(n))
+                    .collect();
+                return Ok(Value::Int(
+                    edges[random::below(rng, edges.len() as u64) as usize],
+                ));
+            }
+            Ok(Value::Int(random::range(rng, min, max)))
+        }
+        "string" => {
+            let len = random::range(rng, 0, int_field(gen, "max_len"));
+            let text: String = (0..len)
+                .map(|_| ALPHABET[random::below(rng, ALPHABET.len() as u64) as usize])
+                .collect();
+            Ok(Value::String(text))
+        }
+        "vec" => {
+            let len = random::range(rng, 0, int_field(gen, "max_len"));
+            let element = field(gen, "element");
+            let items = (0..len)
+                .map(|_| generate(element, rng))
+                .collect::<ZyraResult<Vec<Value>>>()?;
+            Ok(Value::Vec(items))
+        }
+        other => Err(ZyraError::runtime_error(&format!(
+            "unknown generator kind '{}'",
+            other
+        ))),
+    }
+}
+
+/// Simpler values the generator could also have produced, simplest first
+pub fn shrink(gen: &Value, value: &Value) -> Vec<Value> {
+    match (kind(gen).unwrap_or(""), value) {
+        ("int", Value::Int(n)) => {
+            let n = *n;
+            // Shrink toward 0, or toward the bound nearest to it
+            let target = 0.clamp(int_field(gen, "min"), int_field(gen, "max"));
+            // The target itself, then halving the distance to it: n - d/2, n - d/4, ...
+            let distance = n as i128 - target as i128;
+            let mut candidates = vec![target];
+            let mut step = distance / 2;
+            while step != 0 {
+                candidates.push((n as i128 - step) as i64);
+                step /= 2;
+            }
+            candidates
+                .into_iter()
+                .filter(|&c| c != n)
+                .map(Value::Int)
+                .collect()
+        }
+        ("string", Value::String(s)) => {
+            let chars: Vec<char> = s.chars().collect();
+            shrink_sequence(&chars, |_| Vec::new())
+                .into_iter()
+                .map(|chars| Value::String(chars.into_iter().collect()))
+                .collect()
+        }
+        ("vec", Value::Vec(items)) => {
+            let element = field(gen, "element");
+            shrink_sequence(items, |item| shrink(element, item))
+                .into_iter()
+                .map(Value::Vec)
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Empty, each half, each item removed, then each item shrunk in place
+fn shrink_sequence<T: Clone>(items: &[T], shrink_item: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let mut candidates = vec![Vec::new()];
+    let half = items.len() / 2;
+    if half > 0 {
+        candidates.push(items[..half].to_vec());
+        candidates.push(items[half..].to_vec());
+    }
+    for i in 0..items.len() {
+        let mut fewer = items.to_vec();
+        fewer.remove(i);
+        candidates.push(fewer);
+    }
+    for (i, item) in items.iter().enumerate() {
+        for simpler in shrink_item(item) {
+            let mut changed = items.to_vec();
+            changed[i] = simpler;
+            candidates.push(changed);
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_values_respect_bounds() {
+        let ints = gen_int(-5, 5).unwrap();
+        let vecs = gen_vec(&gen_string(3), 4).unwrap();
+        let mut rng = SeededRng::new(SEED);
+        for _ in 0..CASES {
+            match generate(&ints, &mut rng).unwrap() {
+                Value::Int(n) => assert!((-5..=5).contains(&n)),
+                other => panic!("expected an int, got {}", other),
+            }
+            match generate(&vecs, &mut rng).unwrap() {
+                Value::Vec(items) => {
+                    assert!(items.len() <= 4);
+                    for item in items {
+                        assert!(matches!(item, Value::String(s) if s.chars().count() <= 3));
+                    }
+                }
+                other => panic!("expected a vec, got {}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_shrinking_moves_toward_simplest_value() {
+        let ints = gen_int(10, 100).unwrap();
+        assert_eq!(shrink(&ints, &Value::Int(50))[0], Value::Int(10));
+        assert!(shrink(&ints, &Value::Int(10)).is_empty());
+
+        let vecs = gen_vec(&gen_int(-9, 9).unwrap(), 5).unwrap();
+        let candidates = shrink(&vecs, &Value::Vec(vec![Value::Int(3), Value::Int(-4)]));
+        assert_eq!(candidates[0], Value::Vec(Vec::new()));
+        assert!(candidates.contains(&Value::Vec(vec![Value::Int(0), Value::Int(-4)])));
+    }
+}
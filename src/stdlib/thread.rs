@@ -6,7 +6,7 @@
 //! - thread-local storage
 //! - thread info
 
-use crate::compiler::bytecode::Value;
+use crate::compiler::bytecode::{OrderedMap, Value};
 use std::collections::HashMap;
 use std::sync::Mutex;
 use std::thread;
@@ -104,7 +104,7 @@ pub fn spawn_thread(callback_name: &str) -> Value {
     // Store thread info for later joining
     THREAD_RESULTS.lock().unwrap().insert(thread_id, None);
 
-    let mut map = std::collections::HashMap::new();
+    let mut map = OrderedMap::new();
     map.insert("_type".to_string(), Value::String("Thread".to_string()));
     map.insert("id".to_string(), Value::Int(thread_id as i64));
     map.insert(
@@ -139,7 +139,7 @@ pub fn thread_info() -> Value {
     let name = current_thread_name();
     let cores = available_parallelism();
 
-    let mut map = std::collections::HashMap::new();
+    let mut map = OrderedMap::new();
     map.insert("_type".to_string(), Value::String("ThreadInfo".to_string()));
     map.insert("id".to_string(), Value::Int(id as i64));
     map.insert("name".to_string(), name);
@@ -4,9 +4,11 @@
 //! - Instant type for monotonic timing
 //! - Duration helpers
 //! - sleep, now
+//! - High-resolution perf_counter and precise sleeping
+//! - Stopwatch and frame-budget helpers
 //! - Frame timing for games
 
-use crate::compiler::bytecode::Value;
+use crate::compiler::bytecode::{OrderedMap, Value};
 use std::sync::Mutex;
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
@@ -16,8 +18,58 @@ lazy_static::lazy_static! {
     static ref INSTANTS: Mutex<Vec<Instant>> = Mutex::new(Vec::new());
     static ref START_TIME: Instant = Instant::now();
     static ref LAST_FRAME_TIME: Mutex<Instant> = Mutex::new(Instant::now());
+    static ref FRAME_CLOCK: Mutex<FrameClock> = Mutex::new(FrameClock::new());
 }
 
+thread_local! {
+    /// Stopwatch state, indexed by handle
+    static STOPWATCHES: std::cell::RefCell<Stopwatches> = std::cell::RefCell::new(Stopwatches::default());
+}
+
+/// Stopwatches kept at once; handles are plain values that can't say when
+/// they are gone, so past this the least recently used slot is reused
+pub const MAX_STOPWATCHES: usize = 1024;
+
+#[derive(Default)]
+struct Stopwatches {
+    slots: Vec<StopwatchSlot>,
+    /// Bumped on every use, to find the least recently used slot
+    clock: u64,
+}
+
+struct StopwatchSlot {
+    started: Instant,
+    last_lap: Instant,
+    /// Which stopwatch holds the slot, so a handle to an evicted one reads 0
+    generation: i64,
+    used: u64,
+}
+
+impl Stopwatches {
+    /// The slot a handle refers to, if its stopwatch still holds it
+    fn get(&mut self, stopwatch: &Value) -> Option<&mut StopwatchSlot> {
+        let Value::Object(map) = stopwatch else {
+            return None;
+        };
+        let (Some(Value::Int(id)), Some(Value::Int(generation))) =
+            (map.get("id"), map.get("generation"))
+        else {
+            return None;
+        };
+        self.clock += 1;
+        let slot = self.slots.get_mut(usize::try_from(*id).ok()?)?;
+        if slot.generation != *generation {
+            return None;
+        }
+        slot.used = self.clock;
+        Some(slot)
+    }
+}
+
+/// How close to a deadline the OS sleep stops and busy-waiting takes over.
+/// Scheduler wakeups are often 1-2ms late, which is most of a 60 FPS frame budget.
+const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
 /// Get current time in milliseconds since epoch
 pub fn now() -> Value {
     let duration = SystemTime::now()
@@ -80,43 +132,268 @@ pub fn sleep(ms: i64) {
 }
 
 /// Sleep for a number of seconds
-pub fn sleep_secs(secs: f64) {
+pub fn sleep_secs(secs: f64) -> Result<(), String> {
     if secs > 0.0 {
-        thread::sleep(Duration::from_secs_f64(secs));
+        thread::sleep(Duration::try_from_secs_f64(secs).map_err(|_| too_long(secs))?);
+    }
+    Ok(())
+}
+
+/// The instant `secs` seconds after `start`, or an error if no clock can reach it
+fn instant_after(start: Instant, secs: f64) -> Result<Instant, String> {
+    Duration::try_from_secs_f64(secs)
+        .ok()
+        .and_then(|duration| start.checked_add(duration))
+        .ok_or_else(|| too_long(secs))
+}
+
+fn too_long(secs: f64) -> String {
+    format!("{} seconds is too long to wait", secs)
+}
+
+/// High-resolution monotonic clock in seconds since program start
+pub fn perf_counter() -> f64 {
+    START_TIME.elapsed().as_secs_f64()
+}
+
+/// Block until `deadline`: sleep most of the way, then spin for the last stretch
+fn wait_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline <= now {
+        return;
+    }
+    let remaining = deadline - now;
+    if remaining > SPIN_MARGIN {
+        thread::sleep(remaining - SPIN_MARGIN);
+    }
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
+
+/// Sleep for a (fractional) number of milliseconds with sub-millisecond accuracy
+pub fn sleep_precise(ms: f64) -> Result<(), String> {
+    if ms > 0.0 {
+        wait_until(instant_after(Instant::now(), ms / 1000.0)?);
+    }
+    Ok(())
+}
+
+/// Sleep until a perf_counter() timestamp; returns immediately if it has passed
+pub fn sleep_until(timestamp: f64) -> Result<(), String> {
+    if timestamp.is_finite() && timestamp > 0.0 {
+        wait_until(instant_after(*START_TIME, timestamp)?);
+    }
+    Ok(())
+}
+
+/// Create a running Stopwatch; its state lives in a table keyed by the handle
+pub fn stopwatch_new() -> Value {
+    let now = Instant::now();
+    let (id, generation) = STOPWATCHES.with(|stopwatches| {
+        let mut stopwatches = stopwatches.borrow_mut();
+        stopwatches.clock += 1;
+        let used = stopwatches.clock;
+        let slot = StopwatchSlot {
+            started: now,
+            last_lap: now,
+            generation: 0,
+            used,
+        };
+        if stopwatches.slots.len() < MAX_STOPWATCHES {
+            stopwatches.slots.push(slot);
+            return (stopwatches.slots.len() - 1, 0);
+        }
+        let (id, oldest) = stopwatches
+            .slots
+            .iter_mut()
+            .enumerate()
+            .min_by_key(|(_, slot)| slot.used)
+            .expect("the table is full");
+        let generation = oldest.generation + 1;
+        *oldest = StopwatchSlot { generation, ..slot };
+        (id, generation)
+    });
+    let mut map = OrderedMap::new();
+    map.insert("_type".to_string(), Value::String("Stopwatch".to_string()));
+    map.insert("id".to_string(), Value::Int(id as i64));
+    map.insert("generation".to_string(), Value::Int(generation));
+    Value::Object(map)
+}
+
+/// Restart a Stopwatch from zero
+pub fn stopwatch_start(stopwatch: &Value) {
+    STOPWATCHES.with(|stopwatches| {
+        if let Some(slot) = stopwatches.borrow_mut().get(stopwatch) {
+            let now = Instant::now();
+            slot.started = now;
+            slot.last_lap = now;
+        }
+    });
+}
+
+/// Milliseconds since the previous lap (or the start), beginning a new lap
+pub fn stopwatch_lap(stopwatch: &Value) -> f64 {
+    STOPWATCHES.with(
+        |stopwatches| match stopwatches.borrow_mut().get(stopwatch) {
+            Some(slot) => {
+                let now = Instant::now();
+                let lap = now.duration_since(slot.last_lap);
+                slot.last_lap = now;
+                lap.as_secs_f64() * 1000.0
+            }
+            None => 0.0,
+        },
+    )
+}
+
+/// Milliseconds since the Stopwatch was started
+pub fn stopwatch_elapsed(stopwatch: &Value) -> f64 {
+    STOPWATCHES.with(|stopwatches| {
+        stopwatches
+            .borrow_mut()
+            .get(stopwatch)
+            .map(|slot| slot.started.elapsed().as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    })
+}
+
+/// Warning for a section that ran over its budget, or None if it fit
+pub fn budget_warning(budget_ms: f64, elapsed: Duration) -> Option<String> {
+    let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+    if elapsed_ms > budget_ms {
+        Some(format!(
+            "warning: section took {:.2}ms, over its {}ms budget by {:.2}ms",
+            elapsed_ms,
+            budget_ms,
+            elapsed_ms - budget_ms
+        ))
+    } else {
+        None
+    }
+}
+
+/// Longest frame `delta_time` reports, in seconds. A stall (loading, a dragged
+/// window) then slows the game down instead of teleporting everything
+pub const MAX_DELTA: f64 = 0.25;
+
+/// How often the `fps` average is refreshed, in seconds
+const FPS_WINDOW: f64 = 0.5;
+
+/// Frame timing, advanced once per displayed frame by the game window
+pub struct FrameClock {
+    last: Option<Instant>,
+    delta: f64,
+    frames: u64,
+    fps: f64,
+    window_start: Option<Instant>,
+    window_frames: u32,
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        Self {
+            last: None,
+            delta: 0.0,
+            frames: 0,
+            fps: 0.0,
+            window_start: None,
+            window_frames: 0,
+        }
+    }
+
+    /// Record a displayed frame. While the window is unfocused the clock is
+    /// paused: delta is 0, and the first frame back doesn't count the pause
+    pub fn tick(&mut self, now: Instant, focused: bool) {
+        self.frames += 1;
+        if !focused {
+            self.last = None;
+            self.delta = 0.0;
+            self.window_start = None;
+            return;
+        }
+        self.delta = self
+            .last
+            .map_or(0.0, |last| now.duration_since(last).as_secs_f64())
+            .min(MAX_DELTA);
+        self.last = Some(now);
+
+        let start = *self.window_start.get_or_insert(now);
+        self.window_frames += 1;
+        let elapsed = now.duration_since(start).as_secs_f64();
+        if elapsed >= FPS_WINDOW {
+            self.fps = (self.window_frames - 1) as f64 / elapsed;
+            self.window_start = Some(now);
+            self.window_frames = 1;
+        }
+    }
+
+    /// Record a frame that lasted exactly `delta` seconds, for simulated
+    /// windows where wall-clock time would make runs differ
+    pub fn tick_fixed(&mut self, delta: f64) {
+        self.frames += 1;
+        self.last = None;
+        self.window_start = None;
+        self.delta = delta;
+        self.fps = 1.0 / delta;
+    }
+
+    /// Seconds between the last two displayed frames
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+
+    /// Frames per second, averaged over the last half second
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    /// Frames displayed so far
+    pub fn frames(&self) -> u64 {
+        self.frames
     }
 }
 
-/// Get delta time since last frame (in seconds)
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Advance the frame clock; called by the game window after each displayed frame
+pub fn frame_tick(focused: bool) {
+    FRAME_CLOCK.lock().unwrap().tick(Instant::now(), focused);
+}
+
+/// Advance the frame clock by a fixed step; called by headless windows
+pub fn frame_tick_fixed(delta: f64) {
+    FRAME_CLOCK.lock().unwrap().tick_fixed(delta);
+}
+
+/// Get delta time since last frame (in seconds). Without a game window it is
+/// the time since the previous call
 pub fn delta_time() -> f64 {
+    let clock = FRAME_CLOCK.lock().unwrap();
+    if clock.frames() > 0 {
+        return clock.delta();
+    }
     let mut last = LAST_FRAME_TIME.lock().unwrap();
     let now = Instant::now();
-    let delta = now.duration_since(*last).as_secs_f64();
+    let delta = now.duration_since(*last).as_secs_f64().min(MAX_DELTA);
     *last = now;
     delta
 }
 
-/// Mark frame start (for delta time tracking)
-pub fn frame_start() {
-    let mut last = LAST_FRAME_TIME.lock().unwrap();
-    *last = Instant::now();
-}
-
-/// Get frames per second (based on last frame time)
+/// Get frames per second of the game window (0 before the first half second)
 pub fn fps() -> f64 {
-    let last = LAST_FRAME_TIME.lock().unwrap();
-    let elapsed = last.elapsed().as_secs_f64();
-    if elapsed > 0.0 {
-        1.0 / elapsed
-    } else {
-        60.0 // default
-    }
+    FRAME_CLOCK.lock().unwrap().fps()
 }
 
 // Duration utilities
 
 /// Create a Duration from milliseconds
 pub fn duration_from_ms(ms: i64) -> Value {
-    let mut map = std::collections::HashMap::new();
+    let mut map = OrderedMap::new();
     map.insert("_type".to_string(), Value::String("Duration".to_string()));
     map.insert("ms".to_string(), Value::Int(ms));
     map.insert("secs".to_string(), Value::Float(ms as f64 / 1000.0));
@@ -125,7 +402,7 @@ pub fn duration_from_ms(ms: i64) -> Value {
 
 /// Create a Duration from seconds
 pub fn duration_from_secs(secs: f64) -> Value {
-    let mut map = std::collections::HashMap::new();
+    let mut map = OrderedMap::new();
     map.insert("_type".to_string(), Value::String("Duration".to_string()));
     map.insert("ms".to_string(), Value::Int((secs * 1000.0) as i64));
     map.insert("secs".to_string(), Value::Float(secs));
@@ -158,10 +435,7 @@ impl FrameTimer {
 
     /// Wait to maintain target FPS
     pub fn wait(&mut self) {
-        let elapsed = self.last_frame.elapsed();
-        if elapsed < self.frame_duration {
-            thread::sleep(self.frame_duration - elapsed);
-        }
+        wait_until(self.last_frame + self.frame_duration);
     }
 
     /// Get current FPS
@@ -207,3 +481,85 @@ pub fn format_duration(ms: i64) -> String {
         format!("{}h {}m", hours, mins)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sleep_until_past_returns_immediately() {
+        let start = Instant::now();
+        sleep_until(0.0).unwrap();
+        sleep_until(perf_counter() - 1.0).unwrap();
+        assert!(start.elapsed() < Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_stopwatch_laps() {
+        let stopwatch = stopwatch_new();
+        sleep_precise(2.0).unwrap();
+        let lap = stopwatch_lap(&stopwatch);
+        assert!(lap >= 2.0);
+        assert!(stopwatch_lap(&stopwatch) < lap);
+        assert!(stopwatch_elapsed(&stopwatch) >= lap);
+    }
+
+    #[test]
+    fn test_stopwatch_slots_are_reused() {
+        let kept = stopwatch_new();
+        let evicted = stopwatch_new();
+        for _ in 0..MAX_STOPWATCHES - 1 {
+            stopwatch_lap(&kept);
+            stopwatch_new();
+        }
+        STOPWATCHES
+            .with(|stopwatches| assert_eq!(stopwatches.borrow().slots.len(), MAX_STOPWATCHES));
+        // The one in use survives; the idle one's slot went to a newer stopwatch
+        sleep_precise(1.0).unwrap();
+        assert!(stopwatch_elapsed(&kept) >= 1.0);
+        assert_eq!(stopwatch_elapsed(&evicted), 0.0);
+    }
+
+    #[test]
+    fn test_budget_warning() {
+        assert!(budget_warning(16.0, Duration::from_millis(10)).is_none());
+        let warning = budget_warning(16.0, Duration::from_millis(20)).unwrap();
+        assert!(warning.contains("20.00ms, over its 16ms budget by 4.00ms"));
+    }
+
+    #[test]
+    fn test_frame_clock_pauses_while_unfocused() {
+        let mut clock = FrameClock::new();
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        for frame in 0..=30 {
+            clock.tick(at(frame * 20), true);
+        }
+        assert!((clock.delta() - 0.02).abs() < 1e-9);
+        assert!((clock.fps() - 50.0).abs() < 1e-6);
+
+        clock.tick(at(700), false);
+        assert_eq!(clock.delta(), 0.0);
+        // Back in focus after a long pause: no jump, then normal frames again
+        clock.tick(at(5000), true);
+        assert_eq!(clock.delta(), 0.0);
+        clock.tick(at(5016), true);
+        assert!((clock.delta() - 0.016).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sleep_until_reaches_deadline() {
+        let deadline = perf_counter() + 0.005;
+        sleep_until(deadline).unwrap();
+        assert!(perf_counter() >= deadline);
+    }
+
+    #[test]
+    fn test_sleeping_too_long_is_an_error() {
+        let err = sleep_precise(f64::INFINITY).unwrap_err();
+        assert!(err.contains("too long to wait"), "{}", err);
+        assert!(sleep_until(1e300).is_err());
+        assert!(sleep_secs(1e20).is_err());
+        sleep_precise(f64::NAN).unwrap();
+    }
+}
@@ -0,0 +1,213 @@
+//! Immediate-mode UI widgets for Zyra standard library
+//!
+//! Widgets are drawn and hit-tested every frame with the std::game
+//! draw/text/mouse APIs and return their interaction result directly:
+//! - ui_button: true on the frame it is clicked
+//! - ui_slider: the (possibly dragged) value
+//! - ui_checkbox: the (possibly toggled) checked state
+//! - ui_text_input: the (possibly edited) text
+//!
+//! A widget is identified by its kind and position, so keep positions
+//! stable between frames while dragging a slider or typing.
+
+use super::game;
+use std::cell::RefCell;
+
+const TEXT_SCALE: i64 = 2;
+const TEXT_HEIGHT: i64 = 7 * TEXT_SCALE;
+const PADDING: i64 = 6;
+const WIDGET_HEIGHT: i64 = TEXT_HEIGHT + 2 * PADDING;
+
+const COLOR_IDLE: u32 = 0x3A3A4A;
+const COLOR_HOVER: u32 = 0x50506A;
+const COLOR_ACTIVE: u32 = 0x6A6A9A;
+const COLOR_ACCENT: u32 = 0x4FA3FF;
+const COLOR_TEXT: u32 = 0xFFFFFF;
+
+/// Widget ids that own the mouse or keyboard across frames
+#[derive(Default)]
+struct UiState {
+    /// Slider currently being dragged
+    active: Option<WidgetId>,
+    /// Text input receiving typed characters
+    focused: Option<WidgetId>,
+}
+
+type WidgetId = (&'static str, i64, i64);
+
+thread_local! {
+    static UI_STATE: RefCell<UiState> = RefCell::new(UiState::default());
+}
+
+// ===== Widgets =====
+
+/// Draw a button sized to its label; returns true on the frame it is clicked
+pub fn button(x: i64, y: i64, label: &str) -> bool {
+    let w = game::text_width(label, TEXT_SCALE) + 2 * PADDING;
+    let hovered = mouse_over(x, y, w, WIDGET_HEIGHT);
+
+    let color = match (hovered, game::mouse_down()) {
+        (true, true) => COLOR_ACTIVE,
+        (true, false) => COLOR_HOVER,
+        _ => COLOR_IDLE,
+    };
+    game::draw_screen_rect(x, y, w, WIDGET_HEIGHT, color);
+    game::draw_text(x + PADDING, y + PADDING, label, COLOR_TEXT, TEXT_SCALE);
+
+    hovered && game::mouse_clicked()
+}
+
+/// Draw a horizontal slider; returns the value after this frame's dragging
+pub fn slider(x: i64, y: i64, width: i64, value: f64, min: f64, max: f64) -> f64 {
+    let id = ("slider", x, y);
+    let width = width.max(1);
+
+    let dragging = UI_STATE.with(|ui| {
+        let mut ui = ui.borrow_mut();
+        if game::mouse_clicked() && mouse_over(x, y, width, WIDGET_HEIGHT) {
+            ui.active = Some(id);
+        } else if !game::mouse_down() && ui.active == Some(id) {
+            ui.active = None;
+        }
+        ui.active == Some(id)
+    });
+
+    let value = if dragging {
+        slider_value(game::mouse_pos().0, x, width, min, max)
+    } else {
+        value.clamp(min.min(max), min.max(max))
+    };
+
+    let t = if max == min {
+        0.0
+    } else {
+        (value - min) / (max - min)
+    };
+    let knob_x = x + (t * width as f64) as i64;
+    game::draw_screen_rect(x, y + WIDGET_HEIGHT / 2 - 2, width, 4, COLOR_IDLE);
+    game::draw_screen_rect(x, y + WIDGET_HEIGHT / 2 - 2, knob_x - x, 4, COLOR_ACCENT);
+    let knob_color = if dragging { COLOR_ACTIVE } else { COLOR_HOVER };
+    game::draw_screen_rect(knob_x - 4, y, 8, WIDGET_HEIGHT, knob_color);
+
+    value
+}
+
+/// Draw a checkbox with a label; returns the checked state after this frame's click
+pub fn checkbox(x: i64, y: i64, label: &str, checked: bool) -> bool {
+    let box_size = TEXT_HEIGHT + 4;
+    let w = box_size + PADDING + game::text_width(label, TEXT_SCALE);
+    let hovered = mouse_over(x, y, w, box_size);
+    let checked = if hovered && game::mouse_clicked() {
+        !checked
+    } else {
+        checked
+    };
+
+    let color = if hovered { COLOR_HOVER } else { COLOR_IDLE };
+    game::draw_screen_rect(x, y, box_size, box_size, color);
+    if checked {
+        game::draw_screen_rect(x + 4, y + 4, box_size - 8, box_size - 8, COLOR_ACCENT);
+    }
+    game::draw_text(x + box_size + PADDING, y + 2, label, COLOR_TEXT, TEXT_SCALE);
+
+    checked
+}
+
+/// Draw a single-line text field; click to focus, click elsewhere to unfocus.
+/// Returns the text after applying this frame's typing.
+pub fn text_input(x: i64, y: i64, width: i64, text: &str) -> String {
+    let id = ("text_input", x, y);
+    let hovered = mouse_over(x, y, width, WIDGET_HEIGHT);
+
+    let focused = UI_STATE.with(|ui| {
+        let mut ui = ui.borrow_mut();
+        if game::mouse_clicked() {
+            if hovered {
+                ui.focused = Some(id);
+            } else if ui.focused == Some(id) {
+                ui.focused = None;
+            }
+        }
+        ui.focused == Some(id)
+    });
+
+    let text = if focused {
+        apply_typed(text, &game::typed_chars())
+    } else {
+        text.to_string()
+    };
+
+    let border = if focused { COLOR_ACCENT } else { COLOR_HOVER };
+    game::draw_screen_rect(x, y, width, WIDGET_HEIGHT, border);
+    game::draw_screen_rect(x + 2, y + 2, width - 4, WIDGET_HEIGHT - 4, COLOR_IDLE);
+
+    // Show the end of the text when it is wider than the field
+    let char_width = game::text_width("M", TEXT_SCALE);
+    let max_chars = ((width - 2 * PADDING) / char_width - 1).max(0) as usize;
+    let count = text.chars().count();
+    let visible: String = text.chars().skip(count.saturating_sub(max_chars)).collect();
+    let shown = if focused {
+        format!("{}_", visible)
+    } else {
+        visible
+    };
+    game::draw_text(x + PADDING, y + PADDING, &shown, COLOR_TEXT, TEXT_SCALE);
+
+    text
+}
+
+// ===== Helpers =====
+
+fn mouse_over(x: i64, y: i64, w: i64, h: i64) -> bool {
+    let (mx, my) = game::mouse_pos();
+    contains(mx, my, x, y, w, h)
+}
+
+fn contains(px: i64, py: i64, x: i64, y: i64, w: i64, h: i64) -> bool {
+    px >= x && px < x + w && py >= y && py < y + h
+}
+
+/// Map a mouse x position on the slider track to a value in min..=max
+fn slider_value(mouse_x: i64, x: i64, width: i64, min: f64, max: f64) -> f64 {
+    let t = ((mouse_x - x) as f64 / width as f64).clamp(0.0, 1.0);
+    min + (max - min) * t
+}
+
+/// Apply typed characters to text ('\u{8}' deletes the last character)
+fn apply_typed(text: &str, typed: &[char]) -> String {
+    let mut text = text.to_string();
+    for &c in typed {
+        if c == '\u{8}' {
+            text.pop();
+        } else {
+            text.push(c);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains_edges() {
+        assert!(contains(10, 10, 10, 10, 5, 5));
+        assert!(contains(14, 14, 10, 10, 5, 5));
+        assert!(!contains(15, 10, 10, 10, 5, 5));
+        assert!(!contains(9, 12, 10, 10, 5, 5));
+    }
+
+    #[test]
+    fn test_slider_value_clamps() {
+        assert_eq!(slider_value(50, 0, 100, 0.0, 10.0), 5.0);
+        assert_eq!(slider_value(-20, 0, 100, 0.0, 10.0), 0.0);
+        assert_eq!(slider_value(500, 0, 100, 0.0, 10.0), 10.0);
+    }
+
+    #[test]
+    fn test_apply_typed_backspace() {
+        assert_eq!(apply_typed("ab", &['c', '\u{8}', '\u{8}', 'z']), "az");
+        assert_eq!(apply_typed("", &['\u{8}']), "");
+    }
+}
@@ -22,26 +22,26 @@ pub fn vec_with_capacity(capacity: i64) -> Value {
 
 /// Push a value to the end of the vector
 pub fn vec_push(arr: &mut Value, value: Value) -> ZyraResult<()> {
-    if let Value::Array(ref mut vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         vec.push(value);
         Ok(())
     } else {
-        Err(ZyraError::new("TypeError", "Expected an Array", None))
+        Err(ZyraError::new("TypeError", "Expected a Vec or array", None))
     }
 }
 
 /// Pop a value from the end of the vector
 pub fn vec_pop(arr: &mut Value) -> ZyraResult<Value> {
-    if let Value::Array(ref mut vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         Ok(vec.pop().unwrap_or(Value::None))
     } else {
-        Err(ZyraError::new("TypeError", "Expected an Array", None))
+        Err(ZyraError::new("TypeError", "Expected a Vec or array", None))
     }
 }
 
 /// Get the length of the vector
 pub fn vec_len(arr: &Value) -> i64 {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         vec.len() as i64
     } else {
         0
@@ -50,7 +50,7 @@ pub fn vec_len(arr: &Value) -> i64 {
 
 /// Check if the vector is empty
 pub fn vec_is_empty(arr: &Value) -> bool {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         vec.is_empty()
     } else {
         true
@@ -59,17 +59,17 @@ pub fn vec_is_empty(arr: &Value) -> bool {
 
 /// Clear all elements from the vector
 pub fn vec_clear(arr: &mut Value) -> ZyraResult<()> {
-    if let Value::Array(ref mut vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         vec.clear();
         Ok(())
     } else {
-        Err(ZyraError::new("TypeError", "Expected an Array", None))
+        Err(ZyraError::new("TypeError", "Expected a Vec or array", None))
     }
 }
 
 /// Get a value at index (returns None if out of bounds)
 pub fn vec_get(arr: &Value, index: i64) -> Value {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         if index >= 0 && (index as usize) < vec.len() {
             vec[index as usize].clone()
         } else {
@@ -82,7 +82,7 @@ pub fn vec_get(arr: &Value, index: i64) -> Value {
 
 /// Set a value at index
 pub fn vec_set(arr: &mut Value, index: i64, value: Value) -> ZyraResult<()> {
-    if let Value::Array(ref mut vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         if index >= 0 && (index as usize) < vec.len() {
             vec[index as usize] = value;
             Ok(())
@@ -94,13 +94,13 @@ pub fn vec_set(arr: &mut Value, index: i64, value: Value) -> ZyraResult<()> {
             ))
         }
     } else {
-        Err(ZyraError::new("TypeError", "Expected an Array", None))
+        Err(ZyraError::new("TypeError", "Expected a Vec or array", None))
     }
 }
 
 /// Insert a value at index (shifts elements right)
 pub fn vec_insert(arr: &mut Value, index: i64, value: Value) -> ZyraResult<()> {
-    if let Value::Array(ref mut vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         if index >= 0 && (index as usize) <= vec.len() {
             vec.insert(index as usize, value);
             Ok(())
@@ -112,13 +112,13 @@ pub fn vec_insert(arr: &mut Value, index: i64, value: Value) -> ZyraResult<()> {
             ))
         }
     } else {
-        Err(ZyraError::new("TypeError", "Expected an Array", None))
+        Err(ZyraError::new("TypeError", "Expected a Vec or array", None))
     }
 }
 
 /// Remove a value at index (shifts elements left)
 pub fn vec_remove(arr: &mut Value, index: i64) -> ZyraResult<Value> {
-    if let Value::Array(ref mut vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         if index >= 0 && (index as usize) < vec.len() {
             Ok(vec.remove(index as usize))
         } else {
@@ -129,13 +129,13 @@ pub fn vec_remove(arr: &mut Value, index: i64) -> ZyraResult<Value> {
             ))
         }
     } else {
-        Err(ZyraError::new("TypeError", "Expected an Array", None))
+        Err(ZyraError::new("TypeError", "Expected a Vec or array", None))
     }
 }
 
 /// Get the first element (or None)
 pub fn vec_first(arr: &Value) -> Value {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         vec.first().cloned().unwrap_or(Value::None)
     } else {
         Value::None
@@ -144,7 +144,7 @@ pub fn vec_first(arr: &Value) -> Value {
 
 /// Get the last element (or None)
 pub fn vec_last(arr: &Value) -> Value {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         vec.last().cloned().unwrap_or(Value::None)
     } else {
         Value::None
@@ -153,7 +153,7 @@ pub fn vec_last(arr: &Value) -> Value {
 
 /// Check if vector contains a value
 pub fn vec_contains(arr: &Value, value: &Value) -> bool {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         for item in vec {
             if values_equal(item, value) {
                 return true;
@@ -165,7 +165,7 @@ pub fn vec_contains(arr: &Value, value: &Value) -> bool {
 
 /// Find index of a value (returns -1 if not found)
 pub fn vec_index_of(arr: &Value, value: &Value) -> i64 {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         for (i, item) in vec.iter().enumerate() {
             if values_equal(item, value) {
                 return i as i64;
@@ -177,17 +177,50 @@ pub fn vec_index_of(arr: &Value, value: &Value) -> i64 {
 
 /// Reverse the vector in place
 pub fn vec_reverse(arr: &mut Value) -> ZyraResult<()> {
-    if let Value::Array(ref mut vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         vec.reverse();
         Ok(())
     } else {
-        Err(ZyraError::new("TypeError", "Expected an Array", None))
+        Err(ZyraError::new("TypeError", "Expected a Vec or array", None))
     }
 }
 
+/// Sort the vector in place: numbers by value, strings alphabetically
+pub fn vec_sort(arr: &mut Value) -> ZyraResult<()> {
+    let (Value::Array(vec) | Value::Vec(vec)) = arr else {
+        return Err(ZyraError::new("TypeError", "Expected a Vec or array", None));
+    };
+    let key = |v: &Value| match v {
+        Value::String(_) | Value::Bool(_) => None,
+        _ => super::StdLib::to_f64(v),
+    };
+    let all_ints = vec.iter().all(|v| super::StdLib::to_i64(v).is_some());
+    let all_numbers = vec.iter().all(|v| key(v).is_some());
+    let all_strings = vec.iter().all(|v| matches!(v, Value::String(_)));
+    let all_bools = vec.iter().all(|v| matches!(v, Value::Bool(_)));
+    if all_ints {
+        vec.sort_by_key(|v| super::StdLib::to_i64(v).unwrap_or(0));
+    } else if all_numbers {
+        vec.sort_by(|a, b| key(a).unwrap_or(0.0).total_cmp(&key(b).unwrap_or(0.0)));
+    } else if all_strings || all_bools {
+        vec.sort_by(|a, b| match (a, b) {
+            (Value::String(x), Value::String(y)) => x.cmp(y),
+            (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+            _ => std::cmp::Ordering::Equal,
+        });
+    } else {
+        return Err(ZyraError::new(
+            "TypeError",
+            "sort needs all elements to be numbers, all strings or all bools",
+            None,
+        ));
+    }
+    Ok(())
+}
+
 /// Clone a vector
 pub fn vec_clone(arr: &Value) -> Value {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         Value::Array(vec.clone())
     } else {
         Value::Array(Vec::new())
@@ -208,7 +241,7 @@ pub fn vec_concat(arr1: &Value, arr2: &Value) -> Value {
 
 /// Slice a vector (returns new vector)
 pub fn vec_slice(arr: &Value, start: i64, end: i64) -> Value {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         let len = vec.len() as i64;
         let start = start.max(0) as usize;
         let end = end.min(len) as usize;
@@ -224,7 +257,7 @@ pub fn vec_slice(arr: &Value, start: i64, end: i64) -> Value {
 
 /// Join vector elements into a string
 pub fn vec_join(arr: &Value, separator: &str) -> String {
-    if let Value::Array(vec) = arr {
+    if let Value::Array(vec) | Value::Vec(vec) = arr {
         let parts: Vec<String> = vec.iter().map(|v| value_to_string(v)).collect();
         parts.join(separator)
     } else {
@@ -232,6 +265,67 @@ pub fn vec_join(arr: &Value, separator: &str) -> String {
     }
 }
 
+/// Whether a Vec method changes the Vec it's called on
+pub fn mutates(method: &str) -> bool {
+    matches!(
+        method,
+        "push" | "pop" | "insert" | "remove" | "clear" | "sort" | "reverse"
+    )
+}
+
+/// Call a built-in method on a Vec (`v.push(x)`), changing it in place.
+/// Returns None for methods that aren't built in, such as the closure-taking
+/// ones (map, filter, ...) the VM runs itself
+pub fn call_method(vec: &mut Value, method: &str, args: &[Value]) -> ZyraResult<Option<Value>> {
+    let index = |position: usize| match args.get(position) {
+        Some(value) => super::StdLib::to_i64(value).ok_or_else(|| {
+            ZyraError::new(
+                "TypeError",
+                &format!("Vec::{} expects an integer index", method),
+                None,
+            )
+        }),
+        None => Err(ZyraError::new(
+            "TypeError",
+            &format!("Vec::{} expects an index", method),
+            None,
+        )),
+    };
+    let arg = |position: usize| args.get(position).cloned().unwrap_or(Value::None);
+    let result = match method {
+        "push" => {
+            vec_push(vec, arg(0))?;
+            Value::None
+        }
+        "pop" => match vec_pop(vec)? {
+            Value::None => Value::None,
+            value => Value::some(value),
+        },
+        "insert" => {
+            vec_insert(vec, index(0)?, arg(1))?;
+            Value::None
+        }
+        "remove" => vec_remove(vec, index(0)?)?,
+        "clear" => {
+            vec_clear(vec)?;
+            Value::None
+        }
+        "sort" => {
+            vec_sort(vec)?;
+            Value::None
+        }
+        "reverse" => {
+            vec_reverse(vec)?;
+            Value::None
+        }
+        "contains" => Value::Bool(vec_contains(vec, &arg(0))),
+        "len" | "length" => Value::Int(vec_len(vec)),
+        "is_empty" => Value::Bool(vec_is_empty(vec)),
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
+
 // Helper: compare two values for equality
 fn values_equal(a: &Value, b: &Value) -> bool {
     match (a, b) {
@@ -240,7 +334,7 @@ fn values_equal(a: &Value, b: &Value) -> bool {
         (Value::Bool(x), Value::Bool(y)) => x == y,
         (Value::String(x), Value::String(y)) => x == y,
         (Value::None, Value::None) => true,
-        _ => false,
+        _ => matches!(a.eq(b), Value::Bool(true)),
     }
 }
 
@@ -255,3 +349,35 @@ fn value_to_string(v: &Value) -> String {
         _ => "[complex]".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_methods_change_the_vec_in_place() {
+        let mut v = Value::Vec(vec![Value::Int(3), Value::Int(1)]);
+        call_method(&mut v, "push", &[Value::Int(2)]).unwrap();
+        call_method(&mut v, "insert", &[Value::Int(0), Value::Int(9)]).unwrap();
+        call_method(&mut v, "sort", &[]).unwrap();
+        assert_eq!(
+            v,
+            Value::Vec(vec![
+                Value::Int(1),
+                Value::Int(2),
+                Value::Int(3),
+                Value::Int(9)
+            ])
+        );
+        let popped = call_method(&mut v, "pop", &[]).unwrap();
+        assert_eq!(popped, Some(Value::some(Value::Int(9))));
+        let removed = call_method(&mut v, "remove", &[Value::Int(0)]).unwrap();
+        assert_eq!(removed, Some(Value::Int(1)));
+        assert_eq!(
+            call_method(&mut v, "contains", &[Value::Int(3)]).unwrap(),
+            Some(Value::Bool(true))
+        );
+        assert!(call_method(&mut v, "remove", &[Value::Int(5)]).is_err());
+        assert_eq!(call_method(&mut v, "map", &[]).unwrap(), None);
+    }
+}
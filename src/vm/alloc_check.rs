@@ -0,0 +1,121 @@
+//! Allocation checks for `zyra run --assert-no-alloc-in <region>`
+//!
+//! A game that creates Vecs, structs or closures on every frame makes work
+//! for the heap sixty times a second, which shows up as stutter. With a
+//! region set, the VM records each heap allocation made inside it together
+//! with the instruction that made it, and the run reports them by source
+//! line once the program ends.
+//!
+//! The region is either `frame`, the code between two `display()` calls, or
+//! the name of a function, covering everything it calls. The first frame is
+//! a warm-up where games build their state, and allocations after the last
+//! `display()` belong to no finished frame, so neither is reported.
+
+use crate::compiler::bytecode::OrderedMap;
+use crate::compiler::Bytecode;
+use crate::error::{SourceLocation, ZyraError};
+
+/// Code whose heap allocations are reported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Region {
+    /// From one `display()` to the next
+    Frame,
+    /// While a function of this name is running
+    Function(String),
+}
+
+impl Region {
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "frame" => Region::Frame,
+            name => Region::Function(name.to_string()),
+        }
+    }
+}
+
+/// Allocations seen inside a region
+#[derive(Debug, Clone)]
+pub struct AllocCheck {
+    pub region: Region,
+    /// Frames displayed so far
+    frames: u64,
+    /// Allocations of the frame being drawn, kept until it is displayed
+    pending: Vec<(usize, &'static str)>,
+    /// Instruction address and kind of each allocation site, with its count
+    sites: OrderedMap<(usize, &'static str), u64>,
+}
+
+impl AllocCheck {
+    pub fn new(region: Region) -> Self {
+        Self {
+            region,
+            frames: 0,
+            pending: Vec::new(),
+            sites: OrderedMap::new(),
+        }
+    }
+
+    /// Record an allocation of a `kind` value by the instruction at `address`
+    pub fn record(&mut self, address: usize, kind: &'static str) {
+        match self.region {
+            Region::Frame => self.pending.push((address, kind)),
+            Region::Function(_) => self.count((address, kind)),
+        }
+    }
+
+    /// A frame was displayed: keep its allocations unless it was the first
+    pub fn end_frame(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        if self.frames > 0 {
+            for site in pending {
+                self.count(site);
+            }
+        }
+        self.frames += 1;
+    }
+
+    fn count(&mut self, site: (usize, &'static str)) {
+        let seen = self.sites.get(&site).copied().unwrap_or(0);
+        self.sites.insert(site, seen + 1);
+    }
+
+    /// One error per allocation site, in the order they were first hit
+    pub fn report(&self, bytecode: &Bytecode) -> Vec<ZyraError> {
+        let (inside, checked, help) = match &self.region {
+            Region::Frame => (
+                "inside a frame".to_string(),
+                format!(" ({} frames checked)", self.frames.saturating_sub(1)),
+                "Create the value once before the game loop and reuse it, clearing it where needed",
+            ),
+            Region::Function(name) => (
+                format!("inside {}()", name),
+                String::new(),
+                "Create the value once and pass it in, so every call reuses it",
+            ),
+        };
+        self.sites
+            .iter()
+            .map(|(&(address, kind), &count)| {
+                let location = bytecode
+                    .line_at(address)
+                    .map(|line| SourceLocation::new(&bytecode.source_file, line, 0));
+                ZyraError::new(
+                    "AllocationError",
+                    &format!(
+                        "{} allocated {} {}{}",
+                        kind,
+                        inside,
+                        if count == 1 {
+                            "once".to_string()
+                        } else {
+                            format!("{} times", count)
+                        },
+                        checked
+                    ),
+                    location,
+                )
+                .with_suggestion(help)
+            })
+            .collect()
+    }
+}
@@ -21,6 +21,8 @@ pub struct HeapObject {
     pub data: Value,
     /// Reference count - freed when reaches 0
     pub ref_count: usize,
+    /// Set by freeze(); the VM rejects mutation of frozen objects
+    pub frozen: bool,
 }
 
 impl HeapObject {
@@ -28,6 +30,7 @@ impl HeapObject {
         Self {
             data,
             ref_count: 1, // Start with count of 1 (creator owns it)
+            frozen: false,
         }
     }
 }
@@ -39,6 +42,8 @@ pub struct Heap {
     objects: Vec<Option<HeapObject>>,
     /// Free list for reusing slots
     free_list: Vec<HeapId>,
+    /// Per-slot generation, bumped on free so weak handles can detect reuse
+    generations: Vec<u64>,
 }
 
 impl Heap {
@@ -46,6 +51,7 @@ impl Heap {
         Self {
             objects: Vec::new(),
             free_list: Vec::new(),
+            generations: Vec::new(),
         }
     }
 
@@ -62,6 +68,7 @@ impl Heap {
             // Allocate new slot
             let id = self.objects.len();
             self.objects.push(Some(obj));
+            self.generations.push(0);
             id
         }
     }
@@ -124,44 +131,79 @@ impl Heap {
         }
     }
 
-    /// Free a heap object and add slot to free list
-    /// Drops fields depth-first, decrements refcounts of referenced fields
+    /// Free a heap object and add slot to free list.
+    /// Objects whose last reference was held by a freed field are freed from a
+    /// worklist, so long chains of structs do not recurse on the host stack.
     fn free(&mut self, id: HeapId) -> ZyraResult<()> {
-        // Get the object to free
-        let obj = self
-            .objects
-            .get_mut(id)
-            .ok_or_else(|| ZyraError::runtime_error(&format!("Free on invalid heap id: {}", id)))?;
-
-        if let Some(heap_obj) = obj.take() {
-            // Drop fields depth-first if it's an object
-            if let Value::Object(fields) = &heap_obj.data {
-                // Collect ref ids to decrement (avoid borrow issues)
-                let mut refs_to_dec: Vec<HeapId> = Vec::new();
-                for value in fields.values() {
-                    if let Value::Ref(ref_id) = value {
-                        refs_to_dec.push(*ref_id);
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            // Get the object to free
+            let obj = self.objects.get_mut(id).ok_or_else(|| {
+                ZyraError::runtime_error(&format!("Free on invalid heap id: {}", id))
+            })?;
+            let Some(heap_obj) = obj.take() else {
+                continue;
+            };
+
+            // Drop fields if it's an object or struct, and items of a Vec or Map
+            let fields: Vec<&Value> = match &heap_obj.data {
+                Value::Object(fields) => fields.values().collect(),
+                Value::Map(entries) => entries.values().collect(),
+                Value::Struct { fields, .. } => fields.iter().collect(),
+                Value::Vec(items)
+                | Value::Array(items)
+                | Value::List(items)
+                | Value::Tuple(items) => items.iter().collect(),
+                _ => Vec::new(),
+            };
+            for value in fields {
+                if let Value::Ref(ref_id) = value {
+                    // Ignore invalid ids (defensive)
+                    if let Some(child) = self.get_mut(*ref_id) {
+                        child.ref_count = child.ref_count.saturating_sub(1);
+                        if child.ref_count == 0 {
+                            pending.push(*ref_id);
+                        }
                     }
                 }
-                // Now decrement refs (may trigger recursive free)
-                for ref_id in refs_to_dec {
-                    // Ignore errors for now (defensive)
-                    let _ = self.dec_ref(ref_id);
-                }
             }
 
             // Add slot to free list
+            self.generations[id] = self.generations[id].wrapping_add(1);
             self.free_list.push(id);
         }
 
         Ok(())
     }
 
+    /// Generation of a live object; a weak handle is valid while this matches
+    pub fn generation(&self, id: HeapId) -> Option<u64> {
+        self.get(id).map(|_| self.generations[id])
+    }
+
     /// Get the reference count for an object
     pub fn ref_count(&self, id: HeapId) -> Option<usize> {
         self.get(id).map(|obj| obj.ref_count)
     }
 
+    /// Mark an object and everything reachable from it as read-only
+    pub fn freeze(&mut self, id: HeapId) {
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            if let Some(obj) = self.get_mut(id) {
+                if !obj.frozen {
+                    obj.frozen = true;
+                    collect_refs(&obj.data, &mut pending);
+                }
+            }
+        }
+    }
+
+    /// Whether an object has been frozen
+    pub fn is_frozen(&self, id: HeapId) -> bool {
+        self.get(id).is_some_and(|obj| obj.frozen)
+    }
+
     /// Check if &mut self is valid (ref_count == 1)
     /// Panics if ref_count > 1 as per runtime enforcement
     pub fn check_exclusive_borrow(&self, id: HeapId) -> ZyraResult<()> {
@@ -197,6 +239,30 @@ impl Heap {
     }
 }
 
+/// Heap ids referenced from inside a value, including nested collections
+fn collect_refs(value: &Value, refs: &mut Vec<HeapId>) {
+    let mut pending = vec![value];
+    while let Some(value) = pending.pop() {
+        match value {
+            Value::Ref(id) => refs.push(*id),
+            Value::Object(fields) => pending.extend(fields.values()),
+            Value::Map(entries) => pending.extend(entries.values()),
+            Value::Closure { env, .. } => pending.extend(env.iter().map(|(_, value)| value)),
+            Value::Array(items)
+            | Value::Vec(items)
+            | Value::List(items)
+            | Value::Tuple(items)
+            | Value::Struct { fields: items, .. } => pending.extend(items),
+            Value::Some(inner) | Value::Ok(inner) | Value::Err(inner) => pending.push(inner),
+            Value::Enum {
+                payload: Some(inner),
+                ..
+            } => pending.push(inner),
+            _ => {}
+        }
+    }
+}
+
 impl Default for Heap {
     fn default() -> Self {
         Self::new()
@@ -244,4 +310,51 @@ mod tests {
         let id2 = heap.alloc(Value::I32(2));
         assert_eq!(id1, id2); // Should reuse the slot
     }
+
+    #[test]
+    fn test_freeze_reaches_children() {
+        let mut heap = Heap::new();
+        let child = heap.alloc(Value::I32(1));
+        let mut fields = crate::compiler::bytecode::OrderedMap::new();
+        fields.insert("child".to_string(), Value::Ref(child));
+        let parent = heap.alloc(Value::Object(fields));
+        let other = heap.alloc(Value::I32(2));
+
+        heap.freeze(parent);
+        assert!(heap.is_frozen(parent));
+        assert!(heap.is_frozen(child));
+        assert!(!heap.is_frozen(other));
+    }
+
+    #[test]
+    fn test_generation_changes_on_reuse() {
+        let mut heap = Heap::new();
+        let id = heap.alloc(Value::I32(1));
+        let generation = heap.generation(id);
+        let _ = heap.dec_ref(id);
+        assert_eq!(heap.generation(id), None);
+
+        let reused = heap.alloc(Value::I32(2));
+        assert_eq!(reused, id);
+        assert_ne!(heap.generation(reused), generation);
+    }
+
+    #[test]
+    fn test_free_long_chain() {
+        // Each struct holds the only reference to the next one
+        let mut heap = Heap::new();
+        let mut next = Value::None;
+        for _ in 0..200_000 {
+            let id = heap.alloc(Value::Struct {
+                type_id: 0,
+                fields: vec![next],
+            });
+            next = Value::Ref(id);
+        }
+        if let Value::Ref(head) = next {
+            assert!(heap.dec_ref(head).unwrap());
+        }
+        assert!(heap.get(0).is_none());
+        assert_eq!(heap.free_list.len(), 200_000);
+    }
 }
@@ -2,12 +2,19 @@
 //!
 //! Stack-based bytecode interpreter with scope management
 
+pub mod alloc_check;
 pub mod heap;
 pub mod value;
 
-use crate::compiler::{Bytecode, FunctionDef, Instruction};
-use crate::error::{ZyraError, ZyraResult};
-use crate::stdlib::StdLib;
+use crate::compiler::bytecode::{str_hash, MapKey, OrderedMap, OrderedSet, MAX_VALUE_DEPTH};
+use crate::compiler::{Bytecode, FunctionDef, Instruction, OverflowMode};
+use crate::error::{SourceLocation, TraceFrame, ZyraError, ZyraResult};
+use crate::stdlib::capability::Capability;
+use crate::stdlib::{
+    ai, check_nesting, cli, core, debug, game, map, math, mem, random, serde, set, string, test,
+    time, vec, StdLib,
+};
+use alloc_check::{AllocCheck, Region};
 pub use heap::{Heap, HeapId, HeapObject};
 pub use value::Value;
 
@@ -16,9 +23,12 @@ use std::collections::HashMap;
 /// Call stack frame
 #[derive(Debug, Clone)]
 struct CallFrame {
-    // function_name: String,
+    function_name: String,
     return_address: usize,
     base_pointer: usize,
+    /// Heap closure being called; its captured variables live in the scope
+    /// at `base_pointer` and are saved back into it on return
+    closure: Option<HeapId>,
 }
 
 /// Scope for variable storage
@@ -40,12 +50,35 @@ pub struct VM {
     stack: Vec<Value>,
     call_stack: Vec<CallFrame>,
     scopes: Vec<Scope>,
+    /// `static mut` globals, kept apart from the scopes so no local can hide them
+    globals: HashMap<String, Value>,
     ip: usize,
     stdlib: StdLib,
     halted: bool,
     main_called: bool, // Track if main() was already called
+    /// Function the program was started from (main, or a test)
+    entry: String,
     /// Heap for reference-counted objects (structs, enums, vecs, strings)
     heap: Heap,
+    /// Status the process should exit with, set by process::set_exit_code
+    exit_code: i32,
+    /// Closure registered with process::on_panic, called with the error message
+    panic_hook: Option<Value>,
+    /// True once the panic hook has reported a failure
+    panic_reported: bool,
+    /// Instructions executed so far, and how many are allowed (None = no limit)
+    steps: u64,
+    step_limit: Option<u64>,
+    /// Variables registered with debug::watch, sampled on every loop iteration
+    watches: Vec<debug::Watch>,
+    /// Generator behind std::random, restarted by `seed`
+    rng: math::SeededRng,
+    /// Loop iterations run so far (counted only while something is watched)
+    iterations: u64,
+    /// Capabilities granted by the project's `needs` (None = all of them)
+    capabilities: Option<Vec<Capability>>,
+    /// Heap allocations recorded by --assert-no-alloc-in
+    alloc_check: Option<AllocCheck>,
 }
 
 impl VM {
@@ -54,51 +87,171 @@ impl VM {
             stack: Vec::new(),
             call_stack: Vec::new(),
             scopes: vec![Scope::new()],
+            globals: HashMap::new(),
             ip: 0,
             stdlib: StdLib::new(),
             halted: false,
             main_called: false,
+            entry: "main".to_string(),
             heap: Heap::new(),
+            exit_code: 0,
+            panic_hook: None,
+            panic_reported: false,
+            steps: 0,
+            step_limit: None,
+            watches: Vec::new(),
+            rng: math::SeededRng::new(random::clock_seed()),
+            iterations: 0,
+            capabilities: None,
+            alloc_check: None,
+        }
+    }
+
+    /// Stop with an error after `limit` instructions, so a runaway loop in
+    /// untrusted code (e.g. a tutorial exercise) can't hang the caller
+    pub fn with_step_limit(mut self, limit: u64) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// Grant only these capabilities: standard library calls needing any
+    /// other one fail with a CapabilityError
+    pub fn with_capabilities(mut self, capabilities: Vec<Capability>) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Refuse a standard library call that needs a capability the program
+    /// wasn't granted
+    fn check_capability(&self, name: &str, bytecode: &Bytecode) -> ZyraResult<()> {
+        let Some(granted) = &self.capabilities else {
+            return Ok(());
+        };
+        if bytecode.functions.contains_key(name) {
+            return Ok(());
+        }
+        match Capability::required_by(name) {
+            Some(needed) if !granted.contains(&needed) => Err(ZyraError::new(
+                "CapabilityError",
+                &format!(
+                    "'{}' needs the \"{}\" capability, which this project doesn't declare. \
+                     Add it to needs in zyra.toml",
+                    name,
+                    needed.as_str()
+                ),
+                None,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Record the heap allocations made inside `region`, read back with
+    /// `alloc_report` after the run
+    pub fn with_alloc_check(mut self, region: Region) -> Self {
+        self.alloc_check = Some(AllocCheck::new(region));
+        self
+    }
+
+    /// Allocations found inside the checked region, one error per source site
+    pub fn alloc_report(&self, bytecode: &Bytecode) -> Vec<ZyraError> {
+        self.alloc_check
+            .as_ref()
+            .map(|check| check.report(bytecode))
+            .unwrap_or_default()
+    }
+
+    /// Put a value on the heap, noting it for the allocation check
+    fn alloc(&mut self, value: Value) -> HeapId {
+        if let Some(check) = &mut self.alloc_check {
+            let inside = match &check.region {
+                Region::Frame => true,
+                Region::Function(name) => {
+                    self.entry == *name || self.call_stack.iter().any(|f| f.function_name == *name)
+                }
+            };
+            if inside {
+                check.record(self.ip.saturating_sub(1), value.type_name());
+            }
+        }
+        self.heap.alloc(value)
+    }
+
+    /// Exit status requested by the program (0 unless set_exit_code was called)
+    pub fn exit_code(&self) -> i32 {
+        self.exit_code
+    }
+
+    /// True if a runtime error was already reported by the program's panic hook
+    pub fn panic_reported(&self) -> bool {
+        self.panic_reported
+    }
+
+    /// Hand a runtime error to the panic hook, if one is registered.
+    /// The hook is removed first so a failure inside it can't recurse.
+    fn report_panic(&mut self, error: &ZyraError, bytecode: &Bytecode) {
+        if let Some(hook) = self.panic_hook.take() {
+            self.stack.clear();
+            self.call_stack.clear();
+            self.halted = false;
+            let message = Value::String(error.message.clone());
+            match self.call_closure_with_value(&hook, vec![message], bytecode) {
+                Ok(_) => self.panic_reported = true,
+                Err(e) => eprintln!("{}", e),
+            }
         }
     }
 
     /// Run bytecode program
     /// IMPORTANT: Only main() is executed - no code outside functions runs
     pub fn run(&mut self, bytecode: &Bytecode) -> ZyraResult<Option<Value>> {
-        self.ip = 0;
-        self.halted = false;
-
         // *** MAIN-ONLY EXECUTION ***
         // Programs must have a main() function as the entry point.
         // No code outside functions is executed - stack starts clean from main().
-
-        if let Some(main_func) = bytecode.functions.get("main") {
+        match bytecode.functions.get("main") {
             // Verify main has no parameters (valid entry point)
-            if !main_func.params.is_empty() {
-                return Err(ZyraError::runtime_error(
-                    "main() function must not have parameters.",
-                ));
-            }
+            Some(main_func) if !main_func.params.is_empty() => Err(ZyraError::runtime_error(
+                "main() function must not have parameters.",
+            )),
+            Some(_) => self.run_function(bytecode, "main"),
+            // No main function found - error
+            None => Err(ZyraError::runtime_error(
+                "No 'main' function found. Programs must have a 'func main() { ... }' as entry point.",
+            )),
+        }
+    }
+
+    /// Run a parameterless function as the entry point, e.g. main or a single test
+    pub fn run_function(&mut self, bytecode: &Bytecode, name: &str) -> ZyraResult<Option<Value>> {
+        let func = bytecode
+            .functions
+            .get(name)
+            .ok_or_else(|| ZyraError::runtime_error(&format!("Function '{}' not found", name)))?;
 
-            // Mark main as called and execute it
-            self.main_called = true;
+        self.ip = 0;
+        self.halted = false;
+        self.globals = bytecode.globals.iter().cloned().collect();
 
-            // Set up main execution WITHOUT pushing a CallFrame
-            // This way when main() returns, call_stack is empty and halted gets set to true
-            self.scopes.push(Scope::new()); // Enter main's scope
-            self.ip = main_func.start_address;
+        // Mark main as called and execute it
+        self.main_called = true;
+        self.entry = name.to_string();
+
+        // Set up the entry execution WITHOUT pushing a CallFrame
+        // This way when it returns, call_stack is empty and halted gets set to true
+        self.scopes.push(Scope::new()); // Enter the entry function's scope
+        self.ip = func.start_address;
 
-            // Execute instructions starting from main's body
-            while self.ip < bytecode.instructions.len() && !self.halted {
-                let instruction = bytecode.instructions[self.ip].clone();
-                self.ip += 1;
-                self.execute_instruction(&instruction, bytecode)?;
+        // Execute instructions starting from the entry function's body
+        while self.ip < bytecode.instructions.len() && !self.halted {
+            let instruction = bytecode.instructions[self.ip].clone();
+            self.ip += 1;
+            if let Err(e) = self.execute_instruction(&instruction, bytecode) {
+                let mut e = self.locate_error(e, bytecode);
+                if !self.watches.is_empty() {
+                    e.message = format!("{}\n{}", e.message, debug::dump(&self.watches));
+                }
+                self.report_panic(&e, bytecode);
+                return Err(e);
             }
-        } else {
-            // No main function found - error
-            return Err(ZyraError::runtime_error(
-                "No 'main' function found. Programs must have a 'func main() { ... }' as entry point.",
-            ));
         }
 
         // Return top of stack if any
@@ -109,11 +262,101 @@ impl VM {
         }
     }
 
+    /// Attach the failing instruction's source line and a backtrace of the call stack
+    fn locate_error(&self, mut error: ZyraError, bytecode: &Bytecode) -> ZyraError {
+        if error.location.is_some() {
+            return error;
+        }
+        let at = |addr: usize| {
+            bytecode
+                .line_at(addr)
+                .map(|line| SourceLocation::new(&bytecode.source_file, line, 0))
+        };
+        error.location = at(self.ip.saturating_sub(1));
+
+        // The entry runs without a frame; each frame's caller is paused at its return address
+        let functions = std::iter::once(self.entry.as_str())
+            .chain(self.call_stack.iter().map(|f| f.function_name.as_str()));
+        let addresses = self
+            .call_stack
+            .iter()
+            .map(|f| f.return_address)
+            .chain(std::iter::once(self.ip));
+        let mut trace: Vec<TraceFrame> = functions
+            .zip(addresses)
+            .map(|(function, addr)| TraceFrame {
+                function: if function.starts_with("__closure_") {
+                    "<closure>".to_string()
+                } else {
+                    function.to_string()
+                },
+                location: at(addr.saturating_sub(1)),
+            })
+            .collect();
+        trace.reverse();
+        error.trace = trace;
+        error
+    }
+
+    /// Run one REPL entry, keeping its variables alive for the next entry
+    /// `entry` must be a parameterless function; the scopes it leaves behind
+    /// are merged into the session scope instead of being dropped.
+    pub fn run_repl(&mut self, bytecode: &Bytecode, entry: &str) -> ZyraResult<Option<Value>> {
+        let func = bytecode.functions.get(entry).ok_or_else(|| {
+            ZyraError::runtime_error(&format!("REPL entry '{}' not found", entry))
+        })?;
+
+        self.stack.clear();
+        self.call_stack.clear();
+        self.halted = false;
+        self.ip = func.start_address;
+
+        let session_depth = self.scopes.len();
+        let mut result = Ok(());
+        while self.ip < bytecode.instructions.len() && !self.halted {
+            let instruction = bytecode.instructions[self.ip].clone();
+            self.ip += 1;
+            result = self.execute_instruction(&instruction, bytecode);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        // A failed entry may stop inside a callee, so only keep bindings on success.
+        // Outer scopes are merged first so inner bindings win.
+        let leftover: Vec<Scope> = self.scopes.drain(session_depth..).collect();
+        for scope in leftover {
+            for (name, value) in scope.variables {
+                let replaced = if result.is_ok() {
+                    self.scopes[session_depth - 1].variables.insert(name, value)
+                } else {
+                    Some(value)
+                };
+                if let Some(Value::Ref(heap_id)) = replaced {
+                    let _ = self.heap.dec_ref(heap_id);
+                }
+            }
+        }
+        self.call_stack.clear();
+
+        result?;
+        Ok(self.stack.pop().filter(|v| !matches!(v, Value::None)))
+    }
+
     fn execute_instruction(
         &mut self,
         instruction: &Instruction,
         bytecode: &Bytecode,
     ) -> ZyraResult<()> {
+        if let Some(limit) = self.step_limit {
+            self.steps += 1;
+            if self.steps > limit {
+                return Err(ZyraError::runtime_error(&format!(
+                    "Program stopped after {} instructions (is there an endless loop?)",
+                    limit
+                )));
+            }
+        }
         match instruction {
             Instruction::LoadConst(value) => {
                 self.stack.push(value.clone());
@@ -128,90 +371,65 @@ impl VM {
             }
 
             Instruction::StoreVar(name) => {
-                let value = self.pop()?;
+                let mut value = self.pop()?;
+                // Vecs and Maps built outside a literal (stdlib results, map, filter)
+                // move to the heap too, so their methods change the variable's value
+                if let Value::Vec(_) | Value::Map(_) | Value::Set(_) = value {
+                    value = Value::Ref(self.alloc(value));
+                }
                 // set_variable handles ref counting: decrements old value's ref if Ref
                 self.set_variable(name, value);
             }
 
-            Instruction::Pop => {
-                // Gracefully handle empty stack (e.g., after void function calls)
-                if let Some(val) = self.stack.pop() {
-                    if let Value::Ref(heap_id) = val {
-                        let _ = self.heap.dec_ref(heap_id);
-                    }
-                }
-            }
-
-            // Arithmetic
-            Instruction::Add => {
-                let b = self.pop()?;
-                let a = self.pop()?;
-                let result = a.add(&b).ok_or_else(|| {
-                    ZyraError::runtime_error(&format!(
-                        "Cannot add {} and {}",
-                        a.type_name(),
-                        b.type_name()
-                    ))
+            Instruction::LoadGlobal(name) => {
+                let value = self.globals.get(name).cloned().ok_or_else(|| {
+                    ZyraError::runtime_error(&format!("Global '{}' is not defined", name))
                 })?;
-
-                // Cleanup operands
-                if let Value::Ref(id) = a {
-                    let _ = self.heap.dec_ref(id);
-                }
-                if let Value::Ref(id) = b {
-                    let _ = self.heap.dec_ref(id);
+                if let Value::Ref(heap_id) = value {
+                    let _ = self.heap.inc_ref(heap_id);
                 }
-
-                self.stack.push(result);
+                self.stack.push(value);
             }
 
-            Instruction::Sub => {
-                let b = self.pop()?;
-                let a = self.pop()?;
-                let result = a.sub(&b).ok_or_else(|| {
-                    ZyraError::runtime_error(&format!(
-                        "Cannot subtract {} from {}",
-                        b.type_name(),
-                        a.type_name()
-                    ))
-                })?;
-
-                // Cleanup operands
-                if let Value::Ref(id) = a {
-                    let _ = self.heap.dec_ref(id);
+            Instruction::StoreGlobal(name) => {
+                let mut value = self.pop()?;
+                if let Value::Vec(_) | Value::Map(_) | Value::Set(_) = value {
+                    value = Value::Ref(self.alloc(value));
                 }
-                if let Value::Ref(id) = b {
-                    let _ = self.heap.dec_ref(id);
+                if let Some(Value::Ref(heap_id)) = self.globals.insert(name.clone(), value) {
+                    let _ = self.heap.dec_ref(heap_id);
                 }
-
-                self.stack.push(result);
             }
 
-            Instruction::Mul => {
-                let b = self.pop()?;
-                let a = self.pop()?;
-                let result = a.mul(&b).ok_or_else(|| {
-                    ZyraError::runtime_error(&format!(
-                        "Cannot multiply {} and {}",
-                        a.type_name(),
-                        b.type_name()
-                    ))
-                })?;
-
-                // Cleanup operands
-                if let Value::Ref(id) = a {
-                    let _ = self.heap.dec_ref(id);
-                }
-                if let Value::Ref(id) = b {
-                    let _ = self.heap.dec_ref(id);
+            Instruction::Pop => {
+                // Gracefully handle empty stack (e.g., after void function calls)
+                if let Some(val) = self.stack.pop() {
+                    if let Value::Ref(heap_id) = val {
+                        let _ = self.heap.dec_ref(heap_id);
+                    }
                 }
+            }
 
-                self.stack.push(result);
+            // Arithmetic
+            Instruction::Add => self.arith('+', OverflowMode::Wrap)?,
+            Instruction::Sub => self.arith('-', OverflowMode::Wrap)?,
+            Instruction::Mul => self.arith('*', OverflowMode::Wrap)?,
+            Instruction::CheckedAdd => self.arith('+', OverflowMode::Error)?,
+            Instruction::CheckedSub => self.arith('-', OverflowMode::Error)?,
+            Instruction::CheckedMul => self.arith('*', OverflowMode::Error)?,
+            Instruction::SaturatingAdd => self.arith('+', OverflowMode::Saturate)?,
+            Instruction::SaturatingSub => self.arith('-', OverflowMode::Saturate)?,
+            Instruction::SaturatingMul => self.arith('*', OverflowMode::Saturate)?,
+            Instruction::Narrow(type_name, mode) => {
+                let value = self.pop()?;
+                let narrowed = value
+                    .narrow(type_name, *mode)
+                    .map_err(|message| ZyraError::overflow_error(&message))?;
+                self.stack.push(narrowed);
             }
 
             Instruction::Div => {
-                let b = self.pop()?;
-                let a = self.pop()?;
+                let (a, b) = self.pop_operands()?;
                 let result = a.div(&b).ok_or_else(|| {
                     ZyraError::runtime_error("Division error (possibly division by zero)")
                 })?;
@@ -228,8 +446,7 @@ impl VM {
             }
 
             Instruction::Mod => {
-                let b = self.pop()?;
-                let a = self.pop()?;
+                let (a, b) = self.pop_operands()?;
                 let result = a
                     .modulo(&b)
                     .ok_or_else(|| ZyraError::runtime_error("Modulo error"))?;
@@ -261,8 +478,7 @@ impl VM {
 
             // Comparison
             Instruction::Eq => {
-                let b = self.pop()?;
-                let a = self.pop()?;
+                let (a, b) = self.pop_operands()?;
                 self.stack.push(a.eq(&b));
 
                 // Cleanup operands
@@ -275,8 +491,7 @@ impl VM {
             }
 
             Instruction::Neq => {
-                let b = self.pop()?;
-                let a = self.pop()?;
+                let (a, b) = self.pop_operands()?;
                 let eq = a.eq(&b);
                 self.stack.push(eq.not());
 
@@ -290,8 +505,7 @@ impl VM {
             }
 
             Instruction::Lt => {
-                let b = self.pop()?;
-                let a = self.pop()?;
+                let (a, b) = self.pop_operands()?;
                 let result = a.lt(&b).ok_or_else(|| {
                     ZyraError::runtime_error(&format!(
                         "Cannot compare {} and {}",
@@ -312,8 +526,7 @@ impl VM {
             }
 
             Instruction::Lte => {
-                let b = self.pop()?;
-                let a = self.pop()?;
+                let (a, b) = self.pop_operands()?;
                 let result = a.lte(&b).ok_or_else(|| {
                     ZyraError::runtime_error(&format!(
                         "Cannot compare {} and {}",
@@ -334,8 +547,7 @@ impl VM {
             }
 
             Instruction::Gt => {
-                let b = self.pop()?;
-                let a = self.pop()?;
+                let (a, b) = self.pop_operands()?;
                 let result = a.gt(&b).ok_or_else(|| {
                     ZyraError::runtime_error(&format!(
                         "Cannot compare {} and {}",
@@ -356,8 +568,7 @@ impl VM {
             }
 
             Instruction::Gte => {
-                let b = self.pop()?;
-                let a = self.pop()?;
+                let (a, b) = self.pop_operands()?;
                 let result = a.gte(&b).ok_or_else(|| {
                     ZyraError::runtime_error(&format!(
                         "Cannot compare {} and {}",
@@ -406,6 +617,12 @@ impl VM {
                 }
             }
 
+            Instruction::BitAnd => self.bitwise("&")?,
+            Instruction::BitOr => self.bitwise("|")?,
+            Instruction::BitXor => self.bitwise("^")?,
+            Instruction::Shl => self.bitwise("<<")?,
+            Instruction::Shr => self.bitwise(">>")?,
+
             Instruction::Not => {
                 let a = self.pop()?;
                 self.stack.push(a.not());
@@ -418,6 +635,11 @@ impl VM {
 
             // Control flow
             Instruction::Jump(address) => {
+                // A backward jump starts the next loop iteration
+                if *address < self.ip && !self.watches.is_empty() {
+                    self.iterations += 1;
+                    self.sample_watches(bytecode);
+                }
                 self.ip = *address;
             }
 
@@ -428,7 +650,30 @@ impl VM {
                 }
             }
 
+            Instruction::SwitchStr { cases, default } => {
+                if let Value::String(s) = self.pop()? {
+                    let hash = str_hash(&s);
+                    let first = cases.partition_point(|(h, _, _)| *h < hash);
+                    self.ip = cases[first..]
+                        .iter()
+                        .take_while(|(h, _, _)| *h == hash)
+                        .find(|(_, case, _)| *case == s)
+                        .map_or(*default, |(_, _, addr)| *addr);
+                }
+            }
+
             Instruction::Call(name, arg_count) => {
+                self.check_capability(name, bytecode)?;
+
+                // game::display() finishes a frame
+                if let Some(check) = &mut self.alloc_check {
+                    if name.rsplit("::").next() == Some("display")
+                        && !bytecode.functions.contains_key(name)
+                    {
+                        check.end_frame();
+                    }
+                }
+
                 // Collect arguments
                 let mut args = Vec::new();
                 for _ in 0..*arg_count {
@@ -436,6 +681,15 @@ impl VM {
                 }
                 args.reverse();
 
+                // vec_map and friends take a Vec's items, not its heap reference
+                if name.starts_with("vec_") {
+                    if let Some(Value::Ref(id)) = args.first() {
+                        if let Some(items @ Value::Vec(_)) = self.heap.get_value(*id) {
+                            args[0] = items.clone();
+                        }
+                    }
+                }
+
                 // Handle higher-order functions that need closure invocation
                 match name.as_str() {
                     "vec_map" => {
@@ -646,9 +900,414 @@ impl VM {
                             ));
                         }
                     }
+                    "astar" | "bfs" | "ai::astar" | "ai::bfs" | "std::ai::astar"
+                    | "std::ai::bfs" => {
+                        // astar(grid, start, goal, passable_fn) -> Vec of [x, y] points
+                        // The closure decides per cell value whether it can be walked on
+                        let rows = args.first().and_then(ai::grid_rows).ok_or_else(|| {
+                            ZyraError::runtime_error(&format!(
+                                "{}: first argument must be a grid (array of rows)",
+                                name
+                            ))
+                        })?;
+                        let (start, goal) = match (
+                            args.get(1).and_then(ai::point_from_value),
+                            args.get(2).and_then(ai::point_from_value),
+                        ) {
+                            (Some(start), Some(goal)) => (start, goal),
+                            _ => {
+                                return Err(ZyraError::runtime_error(&format!(
+                                    "{}: start and goal must be [x, y] points",
+                                    name
+                                )))
+                            }
+                        };
+
+                        let mut passable = Vec::with_capacity(rows.len());
+                        for row in rows {
+                            let mut open_row = Vec::with_capacity(row.len());
+                            for cell in row {
+                                let open = match args.get(3) {
+                                    Some(closure) => self
+                                        .call_closure_with_value(closure, vec![cell], bytecode)?
+                                        .is_truthy(),
+                                    None => ai::default_passable(&cell),
+                                };
+                                open_row.push(open);
+                            }
+                            passable.push(open_row);
+                        }
+
+                        let path = if name.ends_with("astar") {
+                            ai::astar(&passable, start, goal)
+                        } else {
+                            ai::bfs(&passable, start, goal)
+                        };
+                        self.stack.push(ai::path_to_value(path));
+                    }
+                    "forall" | "test::forall" | "std::test::forall"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // forall(generator, property): property returns false or errors to fail
+                        match (args.first(), args.get(1)) {
+                            (Some(gen), Some(property)) if self.is_closure(property) => {
+                                self.forall(gen, property, bytecode)?
+                            }
+                            _ => {
+                                return Err(ZyraError::runtime_error(
+                                    "forall expects a generator and a closure taking one value",
+                                ))
+                            }
+                        }
+                        self.stack.push(Value::None);
+                    }
+                    "draw_to" | "game::draw_to" | "std::game::draw_to"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // draw_to(canvas, closure): everything the closure draws lands on the canvas
+                        let (canvas, closure) =
+                            match (args.first(), args.get(1)) {
+                                (Some(Value::Int(canvas) | Value::I64(canvas)), Some(closure))
+                                    if self.is_closure(closure) =>
+                                {
+                                    (*canvas, closure)
+                                }
+                                _ => return Err(ZyraError::runtime_error(
+                                    "draw_to expects a canvas and a closure taking no arguments",
+                                )),
+                            };
+                        game::begin_draw_to(canvas)?;
+                        let result = self.call_closure_with_value(closure, Vec::new(), bytecode);
+                        game::end_draw_to();
+                        result?;
+                        self.stack.push(Value::None);
+                    }
+                    "game_loop" | "game::game_loop" | "std::game::game_loop"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // game_loop(update, render, fps): fixed-step updates until the window closes
+                        let fps = match args.get(2) {
+                            Some(Value::Float(f) | Value::F64(f)) => Some(*f),
+                            Some(Value::Int(n) | Value::I64(n)) => Some(*n as f64),
+                            Some(Value::I32(n)) => Some(*n as f64),
+                            None => Some(60.0),
+                            _ => None,
+                        };
+                        match (args.first(), args.get(1), fps) {
+                            (Some(update), Some(render), Some(fps))
+                                if self.is_closure(update) && self.is_closure(render) =>
+                            {
+                                self.game_loop(update, render, fps, bytecode)?
+                            }
+                            _ => return Err(ZyraError::runtime_error(
+                                "game_loop expects an update closure taking the step in seconds, \
+                                 a render closure and a frame rate",
+                            )),
+                        }
+                        self.stack.push(Value::None);
+                    }
+                    "watch" | "debug::watch" | "std::debug::watch"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // watch("player.hp"): record the value on every loop iteration
+                        let path = match args.first() {
+                            Some(Value::String(path)) if !path.trim().is_empty() => path.clone(),
+                            _ => {
+                                return Err(ZyraError::runtime_error(
+                                    "watch expects a variable name such as \"player.hp\"",
+                                ))
+                            }
+                        };
+                        if !self.watches.iter().any(|w| w.name() == path) {
+                            let mut watch = debug::Watch::new(&path);
+                            if let Some(value) = self.watch_value(&watch.path, bytecode) {
+                                watch.record(self.iterations, value);
+                            }
+                            self.watches.push(watch);
+                        }
+                        self.stack.push(Value::None);
+                    }
+                    "dump_watches" | "debug::dump_watches" | "std::debug::dump_watches"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        crate::stdlib::io::write_stdout(&format!(
+                            "{}\n",
+                            debug::dump(&self.watches)
+                        ));
+                        self.stack.push(Value::None);
+                    }
+                    "to_bytes" | "serde::to_bytes" | "std::serde::to_bytes" => {
+                        // Structs live on the heap, so resolve refs before encoding
+                        let value =
+                            self.resolve_deep(args.first().unwrap_or(&Value::None), bytecode)?;
+                        let value = self.expand_enums(&value, bytecode)?;
+                        self.stack
+                            .push(serde::bytes_to_value(serde::encode(&value)));
+                    }
+                    "seed" | "random::seed" | "std::random::seed"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        let seed = match args.first() {
+                            Some(value) => self.value_to_i64(value)?,
+                            None => 0,
+                        };
+                        self.rng = math::SeededRng::new(seed);
+                        self.stack.push(Value::None);
+                    }
+                    "random"
+                    | "math::random"
+                    | "std::math::random"
+                    | "random::random"
+                    | "std::random::random"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        let min = match args.first() {
+                            Some(value) => self.value_to_i64(value)?,
+                            None => 0,
+                        };
+                        let max = match args.get(1) {
+                            Some(value) => self.value_to_i64(value)?,
+                            None => 100,
+                        };
+                        let n = random::range(&mut self.rng, min, max);
+                        self.stack.push(Value::Int(n));
+                    }
+                    "random_f32" | "random::random_f32" | "std::random::random_f32"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        let f = self.rng.next_f64() as f32;
+                        // Rounding up to 1.0 would break the [0, 1) promise
+                        self.stack.push(Value::F32(if f < 1.0 { f } else { 0.0 }));
+                    }
+                    "random_float" | "random::random_float" | "std::random::random_float"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        self.stack.push(Value::Float(self.rng.next_f64()));
+                    }
+                    "shuffle" | "random::shuffle" | "std::random::shuffle"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // A Vec comes back as a new Vec, an array as an array
+                        let shuffled = match args.first() {
+                            Some(Value::Ref(id)) => match self.heap.get_value(*id) {
+                                Some(Value::Vec(items)) => {
+                                    let mut items = items.clone();
+                                    random::shuffle(&mut self.rng, &mut items);
+                                    Value::Ref(self.alloc(Value::Vec(items)))
+                                }
+                                _ => {
+                                    return Err(ZyraError::runtime_error(
+                                        "shuffle expects a Vec or an array",
+                                    ))
+                                }
+                            },
+                            Some(Value::Vec(items)) => {
+                                let mut items = items.clone();
+                                random::shuffle(&mut self.rng, &mut items);
+                                Value::Vec(items)
+                            }
+                            Some(Value::Array(items)) => {
+                                let mut items = items.clone();
+                                random::shuffle(&mut self.rng, &mut items);
+                                Value::Array(items)
+                            }
+                            _ => {
+                                return Err(ZyraError::runtime_error(
+                                    "shuffle expects a Vec or an array",
+                                ))
+                            }
+                        };
+                        self.stack.push(shuffled);
+                    }
+                    "choice" | "random::choice" | "std::random::choice"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        let items = match args.first() {
+                            Some(Value::Ref(id)) => match self.heap.get_value(*id) {
+                                Some(Value::Vec(items)) => items,
+                                _ => {
+                                    return Err(ZyraError::runtime_error(
+                                        "choice expects a Vec or an array",
+                                    ))
+                                }
+                            },
+                            Some(Value::Vec(items) | Value::Array(items)) => items,
+                            _ => {
+                                return Err(ZyraError::runtime_error(
+                                    "choice expects a Vec or an array",
+                                ))
+                            }
+                        };
+                        let picked = random::choice(&mut self.rng, items);
+                        self.stack.push(picked);
+                    }
+                    "set_exit_code" | "process::set_exit_code" | "std::process::set_exit_code" => {
+                        let code = match args.first() {
+                            Some(code) => self.value_to_i64(code)?,
+                            None => 0,
+                        };
+                        // Statuses wrap at 256 on Unix, so 256 would report success
+                        if !(0..=255).contains(&code) {
+                            return Err(ZyraError::runtime_error(&format!(
+                                "set_exit_code expects a status from 0 to 255, got {}",
+                                code
+                            )));
+                        }
+                        self.exit_code = code as i32;
+                        self.stack.push(Value::None);
+                    }
+                    "on_panic" | "process::on_panic" | "std::process::on_panic" => {
+                        match args.into_iter().next() {
+                            Some(hook) if self.is_closure(&hook) => self.panic_hook = Some(hook),
+                            _ => {
+                                return Err(ZyraError::runtime_error(
+                                    "on_panic expects a closure taking the error message",
+                                ))
+                            }
+                        }
+                        self.stack.push(Value::None);
+                    }
+                    "drop" | "mem::drop" | "std::mem::drop"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // The compiler clears the variable afterwards; this releases the argument
+                        if let Some(Value::Ref(id)) = args.first() {
+                            let _ = self.heap.dec_ref(*id);
+                        }
+                        self.stack.push(Value::None);
+                    }
+                    "take" | "mem::take" | "std::mem::take"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // Produces the default left behind; the compiler keeps the taken value
+                        let taken = match args.first() {
+                            Some(value @ Value::Ref(id)) => {
+                                let resolved = self.resolve_deep(value, bytecode)?;
+                                let _ = self.heap.dec_ref(*id);
+                                resolved
+                            }
+                            Some(value) => value.clone(),
+                            None => Value::None,
+                        };
+                        self.stack.push(mem::default_like(&taken));
+                    }
+                    "freeze" | "mem::freeze" | "std::mem::freeze"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // Only heap objects can be shared; other values are copied on use
+                        let value = args.into_iter().next().unwrap_or(Value::None);
+                        if let Value::Ref(id) = &value {
+                            self.heap.freeze(*id);
+                        }
+                        self.stack.push(value);
+                    }
+                    "is_frozen" | "mem::is_frozen" | "std::mem::is_frozen"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        let frozen = matches!(args.first(), Some(Value::Ref(id)) if self.heap.is_frozen(*id));
+                        self.stack.push(Value::Bool(frozen));
+                    }
+                    "weak" | "mem::weak" | "std::mem::weak"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // The handle records the slot and its generation but holds no count
+                        let weak = match args.first() {
+                            Some(Value::Ref(id)) => {
+                                let generation = self.heap.generation(*id).unwrap_or(0);
+                                let _ = self.heap.dec_ref(*id);
+                                let mut fields = OrderedMap::new();
+                                fields
+                                    .insert("_type".to_string(), Value::String("Weak".to_string()));
+                                fields.insert("id".to_string(), Value::Int(*id as i64));
+                                fields.insert(
+                                    "generation".to_string(),
+                                    Value::Int(generation as i64),
+                                );
+                                Value::Object(fields)
+                            }
+                            other => {
+                                return Err(ZyraError::runtime_error(&format!(
+                                    "weak expects a heap value such as a struct, found {}",
+                                    other.map(Value::type_name).unwrap_or("nothing")
+                                )))
+                            }
+                        };
+                        self.stack.push(weak);
+                    }
+                    "budget" | "time::budget" | "std::time::budget"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // budget(ms, closure): run the section, warn on stderr if it overruns
+                        let budget_ms = match args.first() {
+                            Some(ms) => self.value_to_f64(ms)?,
+                            None => 0.0,
+                        };
+                        let section = args.get(1).cloned().unwrap_or(Value::None);
+                        let start = std::time::Instant::now();
+                        let result =
+                            self.call_closure_with_value(&section, Vec::new(), bytecode)?;
+                        if let Some(warning) = time::budget_warning(budget_ms, start.elapsed()) {
+                            eprintln!("{}", warning);
+                        }
+                        self.stack.push(result);
+                    }
+                    "cli::parse" | "std::cli::parse" => {
+                        // The spec array lives on the heap
+                        let spec =
+                            self.resolve_deep(args.first().unwrap_or(&Value::None), bytecode)?;
+                        self.stack.push(cli::parse(&spec)?);
+                    }
+                    "assert_eq" | "assert_ne" if !bytecode.functions.contains_key(name) => {
+                        // Compare what the arguments point to, not heap ids
+                        let mut sides = Vec::new();
+                        for arg in args.iter().take(2) {
+                            let value = self.resolve_deep(arg, bytecode)?;
+                            sides.push(self.expand_enums(&value, bytecode)?);
+                        }
+                        sides.resize(2, Value::None);
+                        core::assert_compare(&sides[0], &sides[1], name == "assert_eq")?;
+                        self.stack.push(Value::None);
+                    }
+                    "validate" | "serde::validate" | "std::serde::validate"
+                        if !bytecode.functions.contains_key(name) =>
+                    {
+                        // validate(value, Schema) -> Vec of SchemaError (empty if valid)
+                        let schema = match args.get(1) {
+                            Some(Value::String(schema)) => schema.clone(),
+                            _ => {
+                                return Err(ZyraError::runtime_error(
+                                    "validate: second argument must be a struct type",
+                                ))
+                            }
+                        };
+                        let value =
+                            self.resolve_deep(args.first().unwrap_or(&Value::None), bytecode)?;
+                        let value = self.expand_enums(&value, bytecode)?;
+                        self.stack
+                            .push(serde::validate(&value, &schema, &bytecode.structs));
+                    }
                     _ => {
-                        // Check for built-in functions first
-                        if let Some(result) = self.stdlib.call(name, &args)? {
+                        // The program's own functions shadow built-in ones. Built-ins
+                        // see heap values resolved and enums as tagged objects
+                        let user_function = bytecode.functions.contains_key(name);
+                        let result = if user_function {
+                            None
+                        } else if args.iter().any(Self::contains_ref) {
+                            let resolved: Vec<Value> = args
+                                .iter()
+                                .map(|arg| self.copy_value(arg, bytecode, true, true))
+                                .collect::<ZyraResult<_>>()?;
+                            self.stdlib.call(name, &resolved)?
+                        } else if args.iter().any(Self::contains_enum) {
+                            let expanded: Vec<Value> = args
+                                .iter()
+                                .map(|arg| self.expand_enums(arg, bytecode))
+                                .collect::<ZyraResult<_>>()?;
+                            self.stdlib.call(name, &expanded)?
+                        } else {
+                            self.stdlib.call(name, &args)?
+                        };
+                        if let Some(result) = result {
                             self.stack.push(result);
                         } else if let Some(func) = bytecode.functions.get(name) {
                             // User-defined function
@@ -698,20 +1357,15 @@ impl VM {
                                 )));
                             }
                         } else if let Ok(closure_val) = self.get_variable(name) {
-                            // Check if it's a closure variable
-                            match closure_val {
-                                Value::Closure { .. } => {
-                                    let result =
-                                        self.call_closure_with_value(&closure_val, args, bytecode)?;
-                                    self.stack.push(result);
-                                }
-                                _ => {
-                                    return Err(ZyraError::runtime_error(&format!(
-                                        "Variable '{}' is not callable (type: {})",
-                                        name,
-                                        closure_val.type_name()
-                                    )));
-                                }
+                            // Check if it's a closure variable; its Return pushes the result
+                            if self.is_closure(&closure_val) {
+                                self.call_closure(&closure_val, args, bytecode)?;
+                            } else {
+                                return Err(ZyraError::runtime_error(&format!(
+                                    "Variable '{}' is not callable (type: {})",
+                                    name,
+                                    closure_val.type_name()
+                                )));
                             }
                         } else {
                             return Err(ZyraError::runtime_error(&format!(
@@ -736,9 +1390,110 @@ impl VM {
                 // Pop the receiver (first argument is the struct)
                 let receiver = self.pop()?;
 
+                // ===== BUILT-IN VEC METHODS =====
+                // push, pop, sort, ... change a heap Vec in place; the closure-taking
+                // methods below run on a copy of its items
+                let receiver = match receiver {
+                    Value::Ref(id) if matches!(self.heap.get_value(id), Some(Value::Vec(_))) => {
+                        if vec::mutates(method_name) && self.heap.is_frozen(id) {
+                            return Err(ZyraError::runtime_error(&format!(
+                                "Cannot call '{}' on a frozen Vec",
+                                method_name
+                            )));
+                        }
+                        if let Some(items) = self.heap.get_value_mut(id) {
+                            if let Some(result) = vec::call_method(items, method_name, &args)? {
+                                self.stack.push(result);
+                                return Ok(());
+                            }
+                        }
+                        self.heap.get_value(id).cloned().unwrap_or(Value::None)
+                    }
+                    mut items @ Value::Vec(_) => {
+                        if let Some(result) = vec::call_method(&mut items, method_name, &args)? {
+                            self.stack.push(result);
+                            return Ok(());
+                        }
+                        items
+                    }
+                    other => other,
+                };
+
+                // ===== BUILT-IN MAP AND SET METHODS =====
+                let is_collection =
+                    |value: Option<&Value>| matches!(value, Some(Value::Map(_) | Value::Set(_)));
+                let receiver = match receiver {
+                    Value::Ref(id) if is_collection(self.heap.get_value(id)) => {
+                        let kind = self.heap.get_value(id).map_or("", Value::type_name);
+                        let mutates = match kind {
+                            "Set" => set::mutates(method_name),
+                            _ => map::mutates(method_name),
+                        };
+                        if mutates && self.heap.is_frozen(id) {
+                            return Err(ZyraError::runtime_error(&format!(
+                                "Cannot call '{}' on a frozen {}",
+                                method_name, kind
+                            )));
+                        }
+                        // union and friends take another Set, which is on the heap too
+                        let args: Vec<Value> = args
+                            .iter()
+                            .map(|arg| match arg {
+                                Value::Ref(arg_id) if *arg_id != id => {
+                                    match self.heap.get_value(*arg_id) {
+                                        Some(set @ Value::Set(_)) => set.clone(),
+                                        _ => arg.clone(),
+                                    }
+                                }
+                                Value::Ref(_) => {
+                                    self.heap.get_value(id).cloned().unwrap_or(Value::None)
+                                }
+                                _ => arg.clone(),
+                            })
+                            .collect();
+                        if let Some(collection) = self.heap.get_value_mut(id) {
+                            if let Some(result) =
+                                Self::collection_method(collection, method_name, &args)?
+                            {
+                                self.stack.push(result);
+                                return Ok(());
+                            }
+                        }
+                        self.heap.get_value(id).cloned().unwrap_or(Value::None)
+                    }
+                    mut collection @ (Value::Map(_) | Value::Set(_)) => {
+                        if let Some(result) =
+                            Self::collection_method(&mut collection, method_name, &args)?
+                        {
+                            self.stack.push(result);
+                            return Ok(());
+                        }
+                        collection
+                    }
+                    other => other,
+                };
+
+                // ===== BUILT-IN STRING METHODS =====
+                if let Value::String(s) = &receiver {
+                    if let Some(result) = string::call_method(s, method_name, &args)? {
+                        self.stack.push(result);
+                        return Ok(());
+                    }
+                    return Err(ZyraError::runtime_error(&format!(
+                        "Unknown method: '{}' on type 'String'",
+                        method_name
+                    )));
+                }
+
                 // ===== ARRAY/VEC HOF METHODS =====
                 // Handle method calls on Array and Vec types (map, filter, fold, etc.)
                 match (&receiver, method_name.as_str()) {
+                    (Value::Object(fields), "upgrade") if matches!(fields.get("_type"), Some(Value::String(t)) if t == "Weak") =>
+                    {
+                        let upgraded = self.upgrade_weak(fields);
+                        self.stack.push(upgraded);
+                        return Ok(());
+                    }
                     (Value::Array(arr), "map") | (Value::Vec(arr), "map") => {
                         if args.is_empty() {
                             return Err(ZyraError::runtime_error(
@@ -883,21 +1638,7 @@ impl VM {
                 let type_name_opt = match &receiver {
                     Value::Ref(heap_id) => {
                         // Dereference from heap
-                        if let Some(heap_obj) = self.heap.get(*heap_id) {
-                            if let Value::Object(fields) = &heap_obj.data {
-                                fields.get("_type").and_then(|v| {
-                                    if let Value::String(s) = v {
-                                        Some(s.clone())
-                                    } else {
-                                        None
-                                    }
-                                })
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
+                        self.heap_type_name(*heap_id, bytecode)
                     }
                     Value::Object(fields) => fields.get("_type").and_then(|v| {
                         if let Value::String(s) = v {
@@ -906,6 +1647,11 @@ impl VM {
                             None
                         }
                     }),
+                    Value::Enum { type_id, .. } => bytecode
+                        .enums
+                        .get(*type_id as usize)
+                        .map(|(name, _)| name.clone()),
+                    Value::Window(_) => Some("Window".to_string()),
                     _ => None,
                 };
 
@@ -950,12 +1696,14 @@ impl VM {
                         // Search for any function matching the pattern <* as Type>::method
                         let trait_method_suffix = format!(" as {}>::{}", type_name, method_name);
 
+                        // Pick the lowest name so the choice doesn't depend on map order
                         let trait_func = bytecode
                             .functions
                             .iter()
-                            .find(|(name, _)| {
+                            .filter(|(name, _)| {
                                 name.starts_with('<') && name.ends_with(&trait_method_suffix)
                             })
+                            .min_by(|(a, _), (b, _)| a.cmp(b))
                             .map(|(_, func)| func);
 
                         if let Some(func) = trait_func {
@@ -964,10 +1712,20 @@ impl VM {
                             all_args.extend(args);
                             self.call_function(func, all_args)?;
                         } else {
-                            return Err(ZyraError::runtime_error(&format!(
-                                "Unknown method: '{}' on type '{}'. No inherent or trait implementation found.",
-                                method_name, type_name
-                            )));
+                            // Built-in struct types (Vec2, Vec3) get their methods from the stdlib
+                            let mut all_args = vec![receiver.clone()];
+                            all_args.extend(args);
+                            if let Some(result) =
+                                self.stdlib
+                                    .call_method(&type_name, method_name, &all_args)?
+                            {
+                                self.stack.push(result);
+                            } else {
+                                return Err(ZyraError::runtime_error(&format!(
+                                    "Unknown method: '{}' on type '{}'. No inherent or trait implementation found.",
+                                    method_name, type_name
+                                )));
+                            }
                         }
                     }
                 } else {
@@ -992,15 +1750,7 @@ impl VM {
 
                 if let Some(frame) = self.call_stack.pop() {
                     // Restore scope: Pop all scopes up to base_pointer
-                    while self.scopes.len() > frame.base_pointer {
-                        if let Some(scope) = self.scopes.pop() {
-                            for (_, value) in scope.variables {
-                                if let Value::Ref(heap_id) = value {
-                                    let _ = self.heap.dec_ref(heap_id);
-                                }
-                            }
-                        }
-                    }
+                    self.leave_frame(&frame);
                     self.ip = frame.return_address;
                     self.stack.push(return_value);
                 } else {
@@ -1011,13 +1761,22 @@ impl VM {
             }
 
             // Data structures
-            Instruction::MakeList(count) => {
+            Instruction::MakeArray(count) => {
                 let mut elements = Vec::new();
                 for _ in 0..*count {
                     elements.push(self.pop()?);
                 }
                 elements.reverse();
-                self.stack.push(Value::Array(elements));
+                self.stack.push(check_nesting(Value::Array(elements))?);
+            }
+
+            Instruction::MakeTuple(count) => {
+                let mut elements = Vec::new();
+                for _ in 0..*count {
+                    elements.push(self.pop()?);
+                }
+                elements.reverse();
+                self.stack.push(check_nesting(Value::Tuple(elements))?);
             }
 
             Instruction::MakeVec(count) => {
@@ -1026,11 +1785,55 @@ impl VM {
                     elements.push(self.pop()?);
                 }
                 elements.reverse();
-                self.stack.push(Value::Vec(elements));
+                // Vecs live on the heap so methods like push change them in place
+                let heap_id = self.alloc(Value::Vec(elements));
+                self.stack.push(Value::Ref(heap_id));
+            }
+
+            Instruction::MakeMap(count) => {
+                let mut entries = OrderedMap::new();
+                let mut pairs = Vec::new();
+                for _ in 0..*count {
+                    let value = self.pop()?;
+                    let key = self.pop()?;
+                    pairs.push((key, value));
+                }
+                // Later entries win over earlier ones with the same key
+                for (key, value) in pairs.into_iter().rev() {
+                    let map_key = MapKey::from_value(&key).ok_or_else(|| {
+                        ZyraError::runtime_error(&format!(
+                            "Map keys must be integers, strings, bools or chars, got {}",
+                            key.type_name()
+                        ))
+                    })?;
+                    entries.insert(map_key, value);
+                }
+                let heap_id = self.alloc(Value::Map(entries));
+                self.stack.push(Value::Ref(heap_id));
+            }
+
+            Instruction::MakeSet(count) => {
+                let mut popped = Vec::new();
+                for _ in 0..*count {
+                    popped.push(self.pop()?);
+                }
+                // Elements keep the order they were written in
+                let mut elements = OrderedSet::new();
+                for element in popped.into_iter().rev() {
+                    let key = MapKey::from_value(&element).ok_or_else(|| {
+                        ZyraError::runtime_error(&format!(
+                            "Set elements must be integers, strings, bools or chars, got {}",
+                            element.type_name()
+                        ))
+                    })?;
+                    elements.insert(key);
+                }
+                let heap_id = self.alloc(Value::Set(elements));
+                self.stack.push(Value::Ref(heap_id));
             }
 
             Instruction::MakeObject(count) => {
-                let mut fields = HashMap::new();
+                let mut fields = OrderedMap::new();
                 for _ in 0..*count {
                     let value = self.pop()?;
                     let key = self.pop()?;
@@ -1039,7 +1842,7 @@ impl VM {
                     }
                 }
                 // Allocate object on heap and push reference
-                let heap_id = self.heap.alloc(Value::Object(fields));
+                let heap_id = self.alloc(Value::Object(fields));
                 self.stack.push(Value::Ref(heap_id));
             }
 
@@ -1056,6 +1859,20 @@ impl VM {
                             if let Value::Object(fields) = &heap_obj.data {
                                 let value = fields.get(field).cloned().unwrap_or(Value::None);
                                 self.stack.push(value);
+                            } else if let Value::Struct { type_id, fields } = &heap_obj.data {
+                                let value = if field == "_type" {
+                                    bytecode
+                                        .struct_layout(*type_id)
+                                        .map_or(Value::None, |(name, _)| {
+                                            Value::String(name.to_string())
+                                        })
+                                } else {
+                                    bytecode
+                                        .field_index(*type_id, field)
+                                        .and_then(|idx| fields.get(idx as usize).cloned())
+                                        .unwrap_or(Value::None)
+                                };
+                                self.stack.push(value);
                             } else {
                                 return Err(ZyraError::runtime_error(&format!(
                                     "Cannot access field '{}' on non-object heap value",
@@ -1069,6 +1886,19 @@ impl VM {
                             )));
                         }
                     }
+                    Value::Enum {
+                        type_id,
+                        variant_id,
+                        payload,
+                    } => match field.as_str() {
+                        "_type" => self.stack.push(Value::String(
+                            bytecode
+                                .variant_name(type_id, variant_id)
+                                .unwrap_or_default(),
+                        )),
+                        "_data" => self.stack.push(payload.map_or(Value::None, |p| *p)),
+                        _ => self.stack.push(Value::None),
+                    },
                     Value::Window(state) => {
                         // Window method access
                         match field.as_str() {
@@ -1079,13 +1909,28 @@ impl VM {
                         }
                     }
                     Value::String(s) => match field.as_str() {
-                        "len" => self.stack.push(Value::Int(s.len() as i64)),
+                        "len" => self.stack.push(Value::Int(string::string_len(&s))),
                         _ => self.stack.push(Value::None),
                     },
                     Value::Array(l) | Value::Vec(l) => match field.as_str() {
                         "len" | "length" => self.stack.push(Value::Int(l.len() as i64)),
                         _ => self.stack.push(Value::None),
                     },
+                    // Option and Result expose the same _type/_data fields as enum
+                    // variants, so match patterns can test and destructure them
+                    Value::Some(_) | Value::None | Value::Ok(_) | Value::Err(_) => {
+                        let (type_name, data) = match obj {
+                            Value::Some(data) => ("Option::Some", *data),
+                            Value::Ok(data) => ("Result::Ok", *data),
+                            Value::Err(data) => ("Result::Err", *data),
+                            _ => ("Option::None", Value::None),
+                        };
+                        match field.as_str() {
+                            "_type" => self.stack.push(Value::String(type_name.to_string())),
+                            "_data" => self.stack.push(data),
+                            _ => self.stack.push(Value::None),
+                        }
+                    }
                     _ => {
                         return Err(ZyraError::runtime_error(&format!(
                             "Cannot access field '{}' on {}",
@@ -1102,12 +1947,39 @@ impl VM {
                 let value = self.pop()?;
                 match obj {
                     Value::Ref(heap_id) => {
+                        if self.heap.is_frozen(heap_id) {
+                            let type_name = self
+                                .heap_type_name(heap_id, bytecode)
+                                .unwrap_or_else(|| "object".to_string());
+                            return Err(ZyraError::runtime_error(&format!(
+                                "Cannot assign to field '{}' of a frozen {}",
+                                field, type_name
+                            )));
+                        }
+                        let slot = match self.heap.get_value(heap_id) {
+                            Some(Value::Struct { type_id, .. }) => {
+                                bytecode.field_index(*type_id, field)
+                            }
+                            _ => None,
+                        };
+                        let new_id = match value {
+                            Value::Ref(id) => Some(id),
+                            _ => None,
+                        };
                         if let Some(heap_obj) = self.heap.get_mut(heap_id) {
-                            if let Value::Object(ref mut fields) = heap_obj.data {
-                                if let Some(old_val) = fields.insert(field.clone(), value) {
-                                    if let Value::Ref(old_id) = old_val {
-                                        let _ = self.heap.dec_ref(old_id);
-                                    }
+                            let old_val = match heap_obj.data {
+                                Value::Object(ref mut fields) => {
+                                    fields.insert(field.clone(), value)
+                                }
+                                Value::Struct { ref mut fields, .. } => slot
+                                    .and_then(|idx| fields.get_mut(idx as usize))
+                                    .map(|place| std::mem::replace(place, value)),
+                                _ => None,
+                            };
+                            // Writing a field's own value back keeps its count
+                            if let Some(Value::Ref(old_id)) = old_val {
+                                if new_id != Some(old_id) {
+                                    let _ = self.heap.dec_ref(old_id);
                                 }
                             }
                         }
@@ -1124,12 +1996,100 @@ impl VM {
                 }
             }
 
+            Instruction::MakeStruct { type_id, slots } => {
+                let field_count = bytecode
+                    .struct_layout(*type_id)
+                    .map_or(slots.len(), |(_, layout)| layout.len());
+                let mut fields = vec![Value::None; field_count];
+                for &slot in slots.iter().rev() {
+                    let value = self.pop()?;
+                    if let Some(place) = fields.get_mut(slot as usize) {
+                        *place = value;
+                    }
+                }
+                let heap_id = self.alloc(Value::Struct {
+                    type_id: *type_id,
+                    fields,
+                });
+                self.stack.push(Value::Ref(heap_id));
+            }
+
+            Instruction::GetFieldIdx(type_id, idx) => {
+                let obj = self.pop()?;
+                if let Value::Ref(heap_id) = obj {
+                    if let Some(Value::Struct { type_id: t, fields }) = self.heap.get_value(heap_id)
+                    {
+                        if t == type_id {
+                            let value = fields.get(*idx as usize).cloned().unwrap_or(Value::None);
+                            self.stack.push(value);
+                            return Ok(());
+                        }
+                    }
+                }
+                // Not the struct the compiler expected: look the field up by name
+                self.stack.push(obj);
+                let field = Self::layout_field_name(bytecode, *type_id, *idx)?;
+                return self.execute_instruction(&Instruction::GetField(field), bytecode);
+            }
+
+            Instruction::SetFieldIdx(type_id, idx) => {
+                let obj = self.pop()?;
+                if let Value::Ref(heap_id) = obj {
+                    let is_match = matches!(
+                        self.heap.get_value(heap_id),
+                        Some(Value::Struct { type_id: t, .. }) if t == type_id
+                    );
+                    if is_match && !self.heap.is_frozen(heap_id) {
+                        let value = self.pop()?;
+                        let new_id = match value {
+                            Value::Ref(id) => Some(id),
+                            _ => None,
+                        };
+                        let mut old_val = None;
+                        if let Some(heap_obj) = self.heap.get_mut(heap_id) {
+                            if let Value::Struct { ref mut fields, .. } = heap_obj.data {
+                                if let Some(place) = fields.get_mut(*idx as usize) {
+                                    old_val = Some(std::mem::replace(place, value));
+                                }
+                            }
+                        }
+                        if let Some(Value::Ref(old_id)) = old_val {
+                            if new_id != Some(old_id) {
+                                let _ = self.heap.dec_ref(old_id);
+                            }
+                        }
+                        self.stack.push(Value::Ref(heap_id));
+                        return Ok(());
+                    }
+                }
+                // Frozen or unexpected values take the by-name path and its errors
+                self.stack.push(obj);
+                let field = Self::layout_field_name(bytecode, *type_id, *idx)?;
+                return self.execute_instruction(&Instruction::SetField(field), bytecode);
+            }
+
             Instruction::GetIndex => {
-                let index = self.pop()?;
+                let index = self.pop()?.into_plain_int();
                 let obj = self.pop()?;
 
+                if let (Value::Ref(id), Value::Int(i)) = (&obj, &index) {
+                    if let Some(Value::Vec(list)) = self.heap.get_value(*id) {
+                        let item = list.get(*i as usize).cloned().ok_or_else(|| {
+                            ZyraError::runtime_error(&format!(
+                                "Index {} out of bounds for Vec of length {}",
+                                i,
+                                list.len()
+                            ))
+                        })?;
+                        self.stack.push(item);
+                        return Ok(());
+                    }
+                }
+
                 match (&obj, &index) {
-                    (Value::Array(list), Value::Int(i)) | (Value::Vec(list), Value::Int(i)) => {
+                    (Value::Array(list), Value::Int(i))
+                    | (Value::Vec(list), Value::Int(i))
+                    | (Value::Tuple(list), Value::Int(i)) => {
                         let idx = *i as usize;
                         if idx < list.len() {
                             self.stack.push(list[idx].clone());
@@ -1141,19 +2101,17 @@ impl VM {
                             )));
                         }
                     }
-                    (Value::String(s), Value::Int(i)) => {
-                        let idx = *i as usize;
-                        if idx < s.len() {
-                            self.stack
-                                .push(Value::String(s.chars().nth(idx).unwrap().to_string()));
-                        } else {
+                    // Strings are indexed by character, like len counts them
+                    (Value::String(s), Value::Int(i)) => match string::string_index(s, *i) {
+                        Some(c) => self.stack.push(Value::Char(c)),
+                        None => {
                             return Err(ZyraError::runtime_error(&format!(
                                 "Index {} out of bounds for string of length {}",
                                 i,
-                                s.len()
+                                string::string_len(s)
                             )));
                         }
-                    }
+                    },
                     _ => {
                         return Err(ZyraError::runtime_error(&format!(
                             "Cannot index {} with {}",
@@ -1165,20 +2123,43 @@ impl VM {
             }
 
             Instruction::SetIndex => {
-                let index = self.pop()?;
+                let index = self.pop()?.into_plain_int();
                 let mut obj = self.pop()?;
                 let value = self.pop()?;
 
-                if let (Value::Array(ref mut list), Value::Int(i)) = (&mut obj, &index) {
-                    let idx = *i as usize;
-                    if idx < list.len() {
+                if let (Value::Ref(id), Value::Int(i)) = (&obj, &index) {
+                    if self.heap.is_frozen(*id) {
+                        return Err(ZyraError::runtime_error(
+                            "Cannot assign to an element of a frozen Vec",
+                        ));
+                    }
+                    if let Some(Value::Vec(list)) = self.heap.get_value_mut(*id) {
+                        let idx = *i as usize;
+                        if idx >= list.len() {
+                            return Err(ZyraError::runtime_error(&format!(
+                                "Index {} out of bounds for Vec of length {}",
+                                i,
+                                list.len()
+                            )));
+                        }
                         list[idx] = value;
+                        self.stack.push(obj);
+                        return Ok(());
                     }
-                } else if let (Value::Vec(ref mut list), Value::Int(i)) = (&mut obj, &index) {
+                }
+
+                if let (Value::Array(list), Value::Int(i)) | (Value::Vec(list), Value::Int(i)) =
+                    (&mut obj, &index)
+                {
                     let idx = *i as usize;
-                    if idx < list.len() {
-                        list[idx] = value;
+                    if idx >= list.len() {
+                        return Err(ZyraError::runtime_error(&format!(
+                            "Index {} out of bounds for array of length {}",
+                            i,
+                            list.len()
+                        )));
                     }
+                    list[idx] = value;
                 }
                 self.stack.push(obj);
             }
@@ -1201,7 +2182,7 @@ impl VM {
 
             Instruction::Print => {
                 let value = self.pop()?;
-                println!("{}", value);
+                crate::stdlib::io::println(&value);
                 if let Value::Ref(id) = value {
                     let _ = self.heap.dec_ref(id);
                 }
@@ -1262,6 +2243,44 @@ impl VM {
                 }
             }
 
+            Instruction::MakeEnum(type_id, variant_id) => {
+                let payload = self.pop()?;
+                self.stack.push(check_nesting(Value::Enum {
+                    type_id: *type_id,
+                    variant_id: *variant_id,
+                    payload: Some(Box::new(payload)),
+                })?);
+            }
+
+            Instruction::IsVariant(type_id, variant_id) => {
+                let value = self.pop()?;
+                let is_variant = match &value {
+                    Value::Enum {
+                        type_id: t,
+                        variant_id: v,
+                        ..
+                    } => t == type_id && v == variant_id,
+                    // Enums decoded by the stdlib arrive as tagged objects
+                    Value::Object(_) | Value::Ref(_) => {
+                        let tag = match &value {
+                            Value::Object(fields) => match fields.get("_type") {
+                                Some(Value::String(t)) => Some(t.clone()),
+                                _ => None,
+                            },
+                            Value::Ref(id) => self.heap_type_name(*id, bytecode),
+                            _ => None,
+                        };
+                        let name = bytecode.variant_name(*type_id, *variant_id);
+                        matches!((tag, name), (Some(t), Some(n)) if t == n)
+                    }
+                    _ => false,
+                };
+                if let Value::Ref(id) = value {
+                    let _ = self.heap.dec_ref(id);
+                }
+                self.stack.push(Value::Bool(is_variant));
+            }
+
             Instruction::StrContains => {
                 // Check if string contains substring: [haystack, needle] => bool
                 let needle = self.pop()?;
@@ -1286,13 +2305,40 @@ impl VM {
             Instruction::MakeClosure {
                 func_name,
                 param_count,
+                captures,
             } => {
-                // Create a closure value that references the compiled function
+                // Copy the captured variables into the closure's environment.
+                // Names that aren't variables here (functions, say) are skipped
+                let mut env = Vec::new();
+                for name in captures {
+                    if let Ok(value) = self.get_variable(name) {
+                        if let Value::Ref(heap_id) = value {
+                            let _ = self.heap.inc_ref(heap_id);
+                        }
+                        env.push((name.clone(), value));
+                    }
+                }
                 let closure = Value::Closure {
                     func_name: func_name.clone(),
                     param_count: *param_count,
+                    env,
                 };
-                self.stack.push(closure);
+                let heap_id = self.alloc(closure);
+                self.stack.push(Value::Ref(heap_id));
+            }
+
+            Instruction::CallValue(arg_count) => {
+                let mut args = Vec::new();
+                for _ in 0..*arg_count {
+                    args.push(self.pop()?);
+                }
+                args.reverse();
+                let callee = self.pop()?;
+                self.call_closure(&callee, args, bytecode)?;
+                // The call frame holds its own reference to the closure
+                if let Value::Ref(heap_id) = callee {
+                    let _ = self.heap.dec_ref(heap_id);
+                }
             }
         }
 
@@ -1302,9 +2348,10 @@ impl VM {
     fn call_function(&mut self, func: &FunctionDef, args: Vec<Value>) -> ZyraResult<()> {
         // Push call frame
         self.call_stack.push(CallFrame {
-            // function_name: func.name.clone(),
+            function_name: func.name.clone(),
             return_address: self.ip,
             base_pointer: self.scopes.len(),
+            closure: None,
         });
 
         // Push arguments onto stack in reverse order so StoreVar can pop them
@@ -1319,6 +2366,111 @@ impl VM {
         Ok(())
     }
 
+    /// True for a closure, or a heap reference to one
+    fn is_closure(&self, value: &Value) -> bool {
+        match value {
+            Value::Ref(heap_id) => {
+                matches!(self.heap.get_value(*heap_id), Some(Value::Closure { .. }))
+            }
+            other => matches!(other, Value::Closure { .. }),
+        }
+    }
+
+    /// Start a call of a closure (or a heap reference to one): push its frame,
+    /// a scope holding its captured variables, and the arguments, then jump
+    /// to its code. The closure's Return pushes the result
+    fn call_closure(
+        &mut self,
+        callee: &Value,
+        args: Vec<Value>,
+        bytecode: &Bytecode,
+    ) -> ZyraResult<()> {
+        let heap_id = match callee {
+            Value::Ref(heap_id) => Some(*heap_id),
+            _ => None,
+        };
+        let closure = match heap_id {
+            Some(heap_id) => self.heap.get_value(heap_id).unwrap_or(&Value::None),
+            None => callee,
+        };
+        let Value::Closure {
+            func_name,
+            param_count,
+            env,
+        } = closure
+        else {
+            return Err(ZyraError::runtime_error(&format!(
+                "Value of type {} is not callable",
+                closure.type_name()
+            )));
+        };
+
+        // Verify argument count
+        if args.len() != *param_count {
+            return Err(ZyraError::runtime_error(&format!(
+                "Closure expected {} arguments, got {}",
+                param_count,
+                args.len()
+            )));
+        }
+        let func = bytecode.functions.get(func_name).ok_or_else(|| {
+            ZyraError::runtime_error(&format!("Closure function '{}' not found", func_name))
+        })?;
+
+        let mut captured = Scope::new();
+        captured.variables.extend(env.iter().cloned());
+        self.call_function(func, args)?;
+        for value in captured.variables.values() {
+            if let Value::Ref(id) = value {
+                let _ = self.heap.inc_ref(*id);
+            }
+        }
+        self.scopes.push(captured);
+        if let Some(heap_id) = heap_id {
+            // Keep the closure alive until the call returns
+            let _ = self.heap.inc_ref(heap_id);
+            if let Some(frame) = self.call_stack.last_mut() {
+                frame.closure = Some(heap_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the scopes of a returning call. A closure's captured variables
+    /// are saved back into it first, so changes to them persist between calls
+    fn leave_frame(&mut self, frame: &CallFrame) {
+        if let Some(heap_id) = frame.closure {
+            let mut captured = match self.scopes.get_mut(frame.base_pointer) {
+                Some(scope) => std::mem::take(&mut scope.variables),
+                None => HashMap::new(),
+            };
+            let mut released = Vec::new();
+            if let Some(Value::Closure { env, .. }) = self.heap.get_value_mut(heap_id) {
+                for (name, value) in env.iter_mut() {
+                    if let Some(current) = captured.remove(name) {
+                        released.push(std::mem::replace(value, current));
+                    }
+                }
+            }
+            released.extend(captured.into_values());
+            for value in released {
+                if let Value::Ref(id) = value {
+                    let _ = self.heap.dec_ref(id);
+                }
+            }
+            let _ = self.heap.dec_ref(heap_id);
+        }
+        while self.scopes.len() > frame.base_pointer {
+            if let Some(scope) = self.scopes.pop() {
+                for (_, value) in scope.variables {
+                    if let Value::Ref(heap_id) = value {
+                        let _ = self.heap.dec_ref(heap_id);
+                    }
+                }
+            }
+        }
+    }
+
     /// Call a closure with given arguments and return the result
     /// This is used for higher-order functions like map, filter, fold
     fn call_closure_with_value(
@@ -1327,70 +2479,515 @@ impl VM {
         args: Vec<Value>,
         bytecode: &Bytecode,
     ) -> ZyraResult<Value> {
-        if let Value::Closure {
-            func_name,
-            param_count,
-        } = closure
-        {
-            // Verify argument count
-            if args.len() != *param_count {
-                return Err(ZyraError::runtime_error(&format!(
-                    "Closure expected {} arguments, got {}",
-                    param_count,
-                    args.len()
-                )));
+        // Save state
+        let saved_ip = self.ip;
+        let saved_stack_len = self.stack.len();
+        let saved_depth = self.call_stack.len();
+
+        // Call the closure
+        self.call_closure(closure, args, bytecode)?;
+
+        // Execute until return
+        while self.ip < bytecode.instructions.len() && !self.halted {
+            let instr = &bytecode.instructions[self.ip];
+            self.ip += 1;
+
+            // Only the closure's own Return ends the call; returns from
+            // functions it calls are executed normally
+            if matches!(instr, Instruction::Return) && self.call_stack.len() == saved_depth + 1 {
+                if let Some(frame) = self.call_stack.pop() {
+                    // Get return value from stack
+                    let return_value = if self.stack.len() > saved_stack_len {
+                        self.pop()?
+                    } else {
+                        Value::None
+                    };
+
+                    // Restore state
+                    self.ip = saved_ip;
+                    self.leave_frame(&frame);
+
+                    return Ok(return_value);
+                }
             }
 
-            // Look up the closure function
-            if let Some(func) = bytecode.functions.get(func_name) {
-                // Save state
-                let saved_ip = self.ip;
-                let saved_stack_len = self.stack.len();
+            self.execute_instruction(instr, bytecode)?;
+        }
 
-                // Call the closure
-                self.call_function(func, args)?;
+        // If we get here without returning, return None
+        Ok(Value::None)
+    }
 
-                // Execute until return
-                while self.ip < bytecode.instructions.len() && !self.halted {
-                    let instr = &bytecode.instructions[self.ip];
-                    self.ip += 1;
+    /// Check a property on generated values, shrinking the first counterexample
+    fn forall(&mut self, gen: &Value, property: &Value, bytecode: &Bytecode) -> ZyraResult<()> {
+        let mut rng = math::SeededRng::new(test::SEED);
+        for case in 1..=test::CASES {
+            let value = test::generate(gen, &mut rng)?;
+            let Err(cause) = self.check_property(property, &value, bytecode) else {
+                continue;
+            };
+
+            // Greedily take the first simpler value that still fails
+            let (mut smallest, mut cause, mut steps) = (value.clone(), cause, 0);
+            'shrinking: while steps < test::MAX_SHRINK_STEPS {
+                for candidate in test::shrink(gen, &smallest) {
+                    if let Err(candidate_cause) =
+                        self.check_property(property, &candidate, bytecode)
+                    {
+                        smallest = candidate;
+                        cause = candidate_cause;
+                        steps += 1;
+                        continue 'shrinking;
+                    }
+                }
+                break;
+            }
 
-                    // Check for Return instruction
-                    if matches!(instr, Instruction::Return) {
-                        if let Some(frame) = self.call_stack.pop() {
-                            // Get return value from stack
-                            let return_value = if self.stack.len() > saved_stack_len {
-                                self.pop()?
-                            } else {
-                                Value::None
-                            };
+            let mut message = format!(
+                "Property failed on case {} of {}\n  counterexample: {}",
+                case,
+                test::CASES,
+                core::describe(&smallest)
+            );
+            if steps > 0 {
+                message.push_str(&format!(
+                    " (shrunk from {} in {} steps)",
+                    core::describe(&value),
+                    steps
+                ));
+            }
+            message.push_str(&format!("\n  cause: {}", cause));
+            return Err(ZyraError::new("PropertyError", &message, None));
+        }
+        Ok(())
+    }
 
-                            // Restore state
-                            self.ip = saved_ip;
+    /// Call `update` `fps` times per second of game time, each time with the
+    /// step in seconds, and `render` once per frame, until the window closes.
+    /// A slow frame is made up for with extra updates before the next render,
+    /// so the game runs at the same speed whatever the frame rate
+    fn game_loop(
+        &mut self,
+        update: &Value,
+        render: &Value,
+        fps: f64,
+        bytecode: &Bytecode,
+    ) -> ZyraResult<()> {
+        let mut clock = game::FixedStep::new(fps)?;
+        if !game::window_is_open() {
+            return Err(ZyraError::runtime_error(
+                "game_loop needs a window: create one with Window(width, height, title) first",
+            ));
+        }
+        // The first frame shows the game after one update
+        let mut updates = 1;
+        while game::window_is_open() && !self.halted {
+            let frame_start = time::perf_counter();
+            for _ in 0..updates {
+                self.call_closure_with_value(update, vec![Value::Float(clock.step())], bytecode)?;
+            }
+            self.call_closure_with_value(render, Vec::new(), bytecode)?;
+            if let Some(check) = &mut self.alloc_check {
+                check.end_frame();
+            }
+            game::display();
+            // Don't render faster than the game updates; headless frames
+            // take no real time
+            if !game::is_headless() {
+                time::sleep_until(frame_start + clock.step())
+                    .map_err(|e| ZyraError::runtime_error(&format!("game loop: {}", e)))?;
+            }
+            updates = clock.advance(time::delta_time());
+        }
+        Ok(())
+    }
 
-                            // Clean up any leftover stack values
-                            while self.scopes.len() > frame.base_pointer {
-                                self.scopes.pop();
-                            }
+    /// Run a property on one value; Err describes why it failed. The VM is put
+    /// back as it was, so a failing case doesn't leave frames behind
+    fn check_property(
+        &mut self,
+        property: &Value,
+        value: &Value,
+        bytecode: &Bytecode,
+    ) -> Result<(), String> {
+        let (ip, stack_len, depth, scopes) = (
+            self.ip,
+            self.stack.len(),
+            self.call_stack.len(),
+            self.scopes.len(),
+        );
+        match self.call_closure_with_value(property, vec![value.clone()], bytecode) {
+            Ok(Value::Bool(false)) => Err("property returned false".to_string()),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                self.ip = ip;
+                self.stack.truncate(stack_len);
+                self.call_stack.truncate(depth);
+                self.scopes.truncate(scopes);
+                Err(e.message.clone())
+            }
+        }
+    }
+
+    /// Some(reference) if a Weak handle's object is still alive, otherwise None
+    fn upgrade_weak(&mut self, weak: &OrderedMap<String, Value>) -> Value {
+        let field = |name: &str| match weak.get(name) {
+            Some(Value::Int(n)) => u64::try_from(*n).ok(),
+            _ => None,
+        };
+        match (field("id"), field("generation")) {
+            (Some(id), Some(generation))
+                if self.heap.generation(id as HeapId) == Some(generation) =>
+            {
+                let _ = self.heap.inc_ref(id as HeapId);
+                Value::some(Value::Ref(id as HeapId))
+            }
+            _ => Value::None,
+        }
+    }
+
+    /// Type name of the struct or `_type`-tagged object stored at a heap slot
+    fn heap_type_name(&self, heap_id: HeapId, bytecode: &Bytecode) -> Option<String> {
+        match self.heap.get_value(heap_id)? {
+            Value::Object(fields) => match fields.get("_type") {
+                Some(Value::String(t)) => Some(t.clone()),
+                _ => None,
+            },
+            Value::Struct { type_id, .. } => bytecode
+                .struct_layout(*type_id)
+                .map(|(name, _)| name.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Field name behind an indexed field access, for the by-name fallback
+    fn layout_field_name(bytecode: &Bytecode, type_id: u32, idx: u32) -> ZyraResult<String> {
+        bytecode
+            .struct_layout(type_id)
+            .and_then(|(_, fields)| fields.get(idx as usize))
+            .map(|(name, _)| name.clone())
+            .ok_or_else(|| {
+                ZyraError::runtime_error(&format!(
+                    "Invalid struct field index {} for type id {}",
+                    idx, type_id
+                ))
+            })
+    }
 
-                            return Ok(return_value);
+    /// Current value of a watched path, formatted; None while it isn't in scope
+    fn watch_value(&self, path: &[String], bytecode: &Bytecode) -> Option<String> {
+        let (variable, fields) = path.split_first()?;
+        let value = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.variables.get(variable))?;
+        let mut value = self.copy_value(value, bytecode, true, true).ok()?;
+        for field in fields {
+            value = match value {
+                Value::Object(mut object) => object.remove(field)?,
+                _ => return None,
+            };
+        }
+        Some(value.to_string())
+    }
+
+    fn sample_watches(&mut self, bytecode: &Bytecode) {
+        let mut watches = std::mem::take(&mut self.watches);
+        for watch in &mut watches {
+            if let Some(value) = self.watch_value(&watch.path, bytecode) {
+                watch.record(self.iterations, value);
+            }
+        }
+        self.watches = watches;
+    }
+
+    /// Copy a value with every heap reference replaced by the referenced data.
+    /// Structs become `_type`-tagged objects, the form stdlib functions understand.
+    fn resolve_deep(&self, value: &Value, bytecode: &Bytecode) -> ZyraResult<Value> {
+        self.copy_value(value, bytecode, true, false)
+    }
+
+    /// Copy a value with compact enums turned into `{_type: "Enum::Variant", _data}`
+    /// objects, the form stdlib functions (printing, serde) understand
+    fn expand_enums(&self, value: &Value, bytecode: &Bytecode) -> ZyraResult<Value> {
+        self.copy_value(value, bytecode, false, true)
+    }
+
+    /// Copy a value, optionally resolving heap references and expanding enums.
+    /// Works from a worklist so deep values cannot overflow the host stack; values
+    /// nested deeper than `MAX_VALUE_DEPTH` (including reference cycles) are errors.
+    fn copy_value(
+        &self,
+        value: &Value,
+        bytecode: &Bytecode,
+        resolve_refs: bool,
+        expand_enums: bool,
+    ) -> ZyraResult<Value> {
+        /// Container to rebuild once its children have been copied
+        enum Build {
+            Vec(usize),
+            List(usize),
+            Array(usize),
+            Tuple(usize),
+            Object(Vec<String>),
+            Map(Vec<MapKey>),
+            Some,
+            Ok,
+            Err,
+            Enum(u32, u32),
+        }
+        enum Task<'a> {
+            Copy(&'a Value, usize),
+            Push(Value),
+            Build(Build),
+        }
+
+        let mut tasks = vec![Task::Copy(value, 0)];
+        let mut done: Vec<Value> = Vec::new();
+        while let Some(task) = tasks.pop() {
+            let (value, depth) = match task {
+                Task::Copy(value, depth) => (value, depth),
+                Task::Push(value) => {
+                    done.push(value);
+                    continue;
+                }
+                Task::Build(build) => {
+                    let built = match build {
+                        Build::Vec(n) => Value::Vec(done.split_off(done.len() - n)),
+                        Build::List(n) => Value::List(done.split_off(done.len() - n)),
+                        Build::Array(n) => Value::Array(done.split_off(done.len() - n)),
+                        Build::Tuple(n) => Value::Tuple(done.split_off(done.len() - n)),
+                        Build::Object(keys) => {
+                            let values = done.split_off(done.len() - keys.len());
+                            Value::Object(keys.into_iter().zip(values).collect())
+                        }
+                        Build::Map(keys) => {
+                            let values = done.split_off(done.len() - keys.len());
+                            Value::Map(keys.into_iter().zip(values).collect())
                         }
+                        Build::Some => Value::some(done.pop().unwrap_or(Value::None)),
+                        Build::Ok => Value::ok(done.pop().unwrap_or(Value::None)),
+                        Build::Err => Value::err(done.pop().unwrap_or(Value::None)),
+                        Build::Enum(type_id, variant_id) => Value::Enum {
+                            type_id,
+                            variant_id,
+                            payload: done.pop().map(Box::new),
+                        },
+                    };
+                    done.push(built);
+                    continue;
+                }
+            };
+            // Also stops reference cycles, which would otherwise copy forever
+            if depth > MAX_VALUE_DEPTH {
+                return Err(ZyraError::runtime_error(&format!(
+                    "Value is nested more than {} levels deep (or contains itself)",
+                    MAX_VALUE_DEPTH
+                )));
+            }
+            let depth = depth + 1;
+            // Children are pushed in reverse so they are copied in order
+            match value {
+                Value::Ref(id) if resolve_refs => match self.heap.get(*id) {
+                    Some(obj) => tasks.push(Task::Copy(&obj.data, depth)),
+                    None => done.push(Value::None),
+                },
+                Value::Struct { type_id, fields } if resolve_refs => {
+                    let mut keys = vec!["_type".to_string()];
+                    if let Some((name, layout)) = bytecode.struct_layout(*type_id) {
+                        keys.extend(layout.iter().map(|(field, _)| field.clone()));
+                        tasks.push(Task::Build(Build::Object(keys)));
+                        for field in fields.iter().take(layout.len()).rev() {
+                            tasks.push(Task::Copy(field, depth));
+                        }
+                        tasks.push(Task::Push(Value::String(name.to_string())));
+                    } else {
+                        done.push(Value::Object(OrderedMap::new()));
                     }
-
-                    self.execute_instruction(instr, bytecode)?;
                 }
+                Value::Vec(items)
+                | Value::List(items)
+                | Value::Array(items)
+                | Value::Tuple(items) => {
+                    let build = match value {
+                        Value::Vec(_) => Build::Vec(items.len()),
+                        Value::List(_) => Build::List(items.len()),
+                        Value::Tuple(_) => Build::Tuple(items.len()),
+                        _ => Build::Array(items.len()),
+                    };
+                    tasks.push(Task::Build(build));
+                    tasks.extend(items.iter().rev().map(|item| Task::Copy(item, depth)));
+                }
+                Value::Object(fields) => {
+                    let (keys, values): (Vec<_>, Vec<_>) = fields.iter().unzip();
+                    tasks.push(Task::Build(Build::Object(
+                        keys.into_iter().cloned().collect(),
+                    )));
+                    tasks.extend(values.into_iter().rev().map(|v| Task::Copy(v, depth)));
+                }
+                Value::Map(entries) => {
+                    let (keys, values): (Vec<_>, Vec<_>) = entries.iter().unzip();
+                    tasks.push(Task::Build(Build::Map(keys.into_iter().cloned().collect())));
+                    tasks.extend(values.into_iter().rev().map(|v| Task::Copy(v, depth)));
+                }
+                Value::Some(inner) | Value::Ok(inner) | Value::Err(inner) => {
+                    let build = match value {
+                        Value::Some(_) => Build::Some,
+                        Value::Ok(_) => Build::Ok,
+                        _ => Build::Err,
+                    };
+                    tasks.push(Task::Build(build));
+                    tasks.push(Task::Copy(inner, depth));
+                }
+                Value::Enum {
+                    type_id,
+                    variant_id,
+                    payload,
+                } if expand_enums => {
+                    let name = bytecode.variant_name(*type_id, *variant_id);
+                    let mut keys = vec!["_type".to_string()];
+                    if payload.is_some() {
+                        keys.push("_data".to_string());
+                    }
+                    tasks.push(Task::Build(Build::Object(keys)));
+                    if let Some(payload) = payload {
+                        tasks.push(Task::Copy(payload, depth));
+                    }
+                    tasks.push(Task::Push(Value::String(name.unwrap_or_default())));
+                }
+                Value::Enum {
+                    type_id,
+                    variant_id,
+                    payload: Some(payload),
+                } => {
+                    tasks.push(Task::Build(Build::Enum(*type_id, *variant_id)));
+                    tasks.push(Task::Copy(payload, depth));
+                }
+                other => done.push(other.clone()),
+            }
+        }
+        Ok(done.pop().unwrap_or(Value::None))
+    }
 
-                // If we get here without returning, return None
-                Ok(Value::None)
-            } else {
-                Err(ZyraError::runtime_error(&format!(
-                    "Closure function '{}' not found",
-                    func_name
-                )))
+    /// Run a built-in Map or Set method, changing the collection in place
+    fn collection_method(
+        collection: &mut Value,
+        method: &str,
+        args: &[Value],
+    ) -> ZyraResult<Option<Value>> {
+        match collection {
+            Value::Map(_) => map::call_method(collection, method, args),
+            Value::Set(_) => set::call_method(collection, method, args),
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether a value holds a compact enum anywhere inside it
+    /// True if a value holds a heap reference anywhere inside it
+    fn contains_ref(value: &Value) -> bool {
+        let mut pending = vec![value];
+        while let Some(value) = pending.pop() {
+            match value {
+                Value::Ref(_) => return true,
+                Value::Vec(items)
+                | Value::List(items)
+                | Value::Array(items)
+                | Value::Tuple(items) => pending.extend(items),
+                Value::Object(fields) => pending.extend(fields.values()),
+                Value::Map(entries) => pending.extend(entries.values()),
+                Value::Closure { env, .. } => pending.extend(env.iter().map(|(_, value)| value)),
+                Value::Some(inner) | Value::Ok(inner) | Value::Err(inner) => pending.push(inner),
+                _ => {}
             }
-        } else {
-            Err(ZyraError::runtime_error("Expected a closure value"))
         }
+        false
+    }
+
+    fn contains_enum(value: &Value) -> bool {
+        let mut pending = vec![value];
+        while let Some(value) = pending.pop() {
+            match value {
+                Value::Enum { .. } => return true,
+                Value::Vec(items)
+                | Value::List(items)
+                | Value::Array(items)
+                | Value::Tuple(items) => pending.extend(items),
+                Value::Object(fields) => pending.extend(fields.values()),
+                Value::Map(entries) => pending.extend(entries.values()),
+                Value::Closure { env, .. } => pending.extend(env.iter().map(|(_, value)| value)),
+                Value::Some(inner) | Value::Ok(inner) | Value::Err(inner) => pending.push(inner),
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Pop two operands and push their sum, difference or product (`op`),
+    /// with integer overflow handled as `mode` says
+    fn arith(&mut self, op: char, mode: OverflowMode) -> ZyraResult<()> {
+        let (a, b) = self.pop_operands()?;
+        let result = match a.int_arith(op, &b, mode) {
+            Some(result) => result.map_err(|message| ZyraError::overflow_error(&message))?,
+            None => {
+                let result = match op {
+                    '+' => a.add(&b),
+                    '-' => a.sub(&b),
+                    _ => a.mul(&b),
+                };
+                result.ok_or_else(|| {
+                    ZyraError::runtime_error(&match op {
+                        '+' => format!("Cannot add {} and {}", a.type_name(), b.type_name()),
+                        '-' => format!("Cannot subtract {} from {}", b.type_name(), a.type_name()),
+                        _ => format!("Cannot multiply {} and {}", a.type_name(), b.type_name()),
+                    })
+                })?
+            }
+        };
+
+        // Cleanup operands
+        if let Value::Ref(id) = a {
+            let _ = self.heap.dec_ref(id);
+        }
+        if let Value::Ref(id) = b {
+            let _ = self.heap.dec_ref(id);
+        }
+
+        self.stack.push(result);
+        Ok(())
+    }
+
+    /// Pop two integers and push the result of the bitwise operator `op`
+    fn bitwise(&mut self, op: &str) -> ZyraResult<()> {
+        // The shift amount doesn't lend its type to the shifted value
+        let (a, b) = if op == "<<" || op == ">>" {
+            let b = self.pop()?;
+            (self.pop()?, b)
+        } else {
+            self.pop_operands()?
+        };
+        let result = a
+            .bitwise(op, &b)
+            .ok_or_else(|| {
+                format!(
+                    "Cannot apply '{}' to {} and {}",
+                    op,
+                    a.type_name(),
+                    b.type_name()
+                )
+            })
+            .and_then(|result| result)
+            .map_err(|message| ZyraError::runtime_error(&message))?;
+        self.stack.push(result);
+        Ok(())
+    }
+
+    /// Pop the two operands of a binary operator, left one first, with an
+    /// untyped number given the sized type of the other side
+    fn pop_operands(&mut self) -> ZyraResult<(Value, Value)> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        Ok(Value::unify_numeric(a, b))
     }
 
     fn pop(&mut self) -> ZyraResult<Value> {
@@ -1468,77 +3065,23 @@ impl VM {
 
     /// Cast a value to a target type at runtime
     fn cast_value(&self, value: Value, target_type: &str) -> ZyraResult<Value> {
-        match target_type {
-            // Integer casts
-            "i8" => {
-                let n = self.value_to_i64(&value)?;
-                Ok(Value::I8(n as i8))
-            }
-            "i32" => {
-                let n = self.value_to_i64(&value)?;
-                Ok(Value::I32(n as i32))
-            }
-            "i64" | "Int" => {
-                let n = self.value_to_i64(&value)?;
-                Ok(Value::I64(n))
-            }
-            // Unsigned integer casts
-            "u8" => {
-                let n = self.value_to_i64(&value)?;
-                Ok(Value::U8(n as u8))
-            }
-            "u32" => {
-                let n = self.value_to_i64(&value)?;
-                Ok(Value::U32(n as u32))
-            }
-            "u64" => {
-                let n = self.value_to_i64(&value)?;
-                Ok(Value::U64(n as u64))
-            }
-            // Float casts
-            "f32" => {
-                let n = self.value_to_f64(&value)?;
-                Ok(Value::F32(n as f32))
-            }
-            "f64" | "Float" => {
-                let n = self.value_to_f64(&value)?;
-                Ok(Value::Float(n))
-            }
-            // Same type - return as-is
-            _ => Ok(value),
-        }
+        value
+            .cast(target_type)
+            .map_err(|message| ZyraError::runtime_error(&message))
     }
 
     /// Helper to extract i64 from any numeric value
     fn value_to_i64(&self, value: &Value) -> ZyraResult<i64> {
-        match value {
-            Value::I8(n) => Ok(*n as i64),
-            Value::I32(n) => Ok(*n as i64),
-            Value::I64(n) | Value::Int(n) => Ok(*n),
-            Value::U8(n) => Ok(*n as i64),
-            Value::U32(n) => Ok(*n as i64),
-            Value::U64(n) => Ok(*n as i64),
-            Value::F32(n) => Ok(*n as i64),
-            Value::F64(n) | Value::Float(n) => Ok(*n as i64),
-            Value::Bool(b) => Ok(if *b { 1 } else { 0 }),
-            Value::Char(c) => Ok(*c as i64),
-            _ => Err(ZyraError::runtime_error("Cannot cast value to integer")),
-        }
+        value
+            .as_i64()
+            .ok_or_else(|| ZyraError::runtime_error("Cannot cast value to integer"))
     }
 
     /// Helper to extract f64 from any numeric value
     fn value_to_f64(&self, value: &Value) -> ZyraResult<f64> {
-        match value {
-            Value::I8(n) => Ok(*n as f64),
-            Value::I32(n) => Ok(*n as f64),
-            Value::I64(n) | Value::Int(n) => Ok(*n as f64),
-            Value::U8(n) => Ok(*n as f64),
-            Value::U32(n) => Ok(*n as f64),
-            Value::U64(n) => Ok(*n as f64),
-            Value::F32(n) => Ok(*n as f64),
-            Value::F64(n) | Value::Float(n) => Ok(*n),
-            _ => Err(ZyraError::runtime_error("Cannot cast value to float")),
-        }
+        value
+            .as_f64()
+            .ok_or_else(|| ZyraError::runtime_error("Cannot cast value to float"))
     }
 }
 
@@ -1547,3 +3090,568 @@ impl Default for VM {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn run(source: &str) -> ZyraResult<Option<Value>> {
+        run_with(source, OverflowMode::default())
+    }
+
+    fn run_with(source: &str, overflow: OverflowMode) -> ZyraResult<Option<Value>> {
+        let tokens = Lexer::new(source, "test.zr").tokenize()?;
+        let program = Parser::new(tokens).parse()?;
+        let mut bytecode = Compiler::new().with_overflow(overflow).compile(&program)?;
+        bytecode.source_file = "test.zr".to_string();
+        VM::new().run(&bytecode)
+    }
+
+    #[test]
+    fn test_runtime_error_has_backtrace() {
+        let err = run("func inner(n: i32) -> i32 {\n    return 10 / n;\n}\n\
+                       func outer() -> i32 {\n    return inner(0);\n}\n\
+                       func main() {\n    outer();\n}")
+        .unwrap_err();
+        let location = err.location.as_ref().unwrap();
+        assert_eq!((location.file.as_str(), location.line), ("test.zr", 2));
+        let frames: Vec<_> = err
+            .trace
+            .iter()
+            .map(|f| (f.function.as_str(), f.location.as_ref().map(|l| l.line)))
+            .collect();
+        assert_eq!(
+            frames,
+            vec![("inner", Some(2)), ("outer", Some(5)), ("main", Some(8))]
+        );
+    }
+
+    #[test]
+    fn test_struct_fields_by_index_and_name() {
+        // `q` comes from a call, so its field accesses go by name
+        let result = run("struct Point { x: i32, y: i32 }\n\
+                          func same(p: Point) -> Point {\n    return p;\n}\n\
+                          func main() -> i32 {\n    let mut p = Point { y: 2, x: 1 };\n    p.y = p.x + p.y;\n\
+                          let mut q = same(p);\n    q.x = q.x + 10;\n    return p.x + q.y;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(14)));
+    }
+
+    #[test]
+    fn test_enum_payloads_through_fields() {
+        let result = run("enum Shape { Circle(i32), Empty }\n\
+                          struct Holder { shape: Shape, n: i32 }\n\
+                          func main() -> i32 {\n    let h = Holder { shape: Shape::Circle(5), n: 2 };\n\
+                          let shapes = [Shape::Empty, Shape::Circle(30)];\n\
+                          if shapes[0]._type == \"Shape::Empty\" {\n        return h.shape._data * h.n + shapes[1]._data;\n    }\n\
+                          return 0;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(40)));
+    }
+
+    #[test]
+    fn test_assignment_through_nested_places() {
+        let result = run("struct Item { count: i32 }\n\
+                          struct Bag { items: Vec<Item>, sizes: Vec<i32> }\n\
+                          func main() -> i32 {\n    let mut bag = Bag { items: [Item { count: 1 }, Item { count: 2 }], sizes: [0, 0] };\n\
+                          bag.items[1].count += 40;\n    bag.sizes[0] = 7;\n\
+                          let mut cube = [[[0, 0], [0, 0]], [[0, 0], [0, 0]]];\n    cube[1][0][1] = 100;\n\
+                          return bag.items[1].count + bag.sizes[0] + cube[1][0][1] + cube[0][0][1];\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(149)));
+    }
+
+    #[test]
+    fn test_string_match_dispatch_keeps_arm_order() {
+        let source = "func code(cmd: String, admin: bool) -> i32 {\n    match cmd {\n\
+                      \"start\" => 1,\n        \"quit\" if admin => 2,\n        other if other == \"help\" => 3,\n\
+                      \"quit\" => 4,\n        \"help\" => 5,\n        _ => 0,\n    }\n}\n\
+                      func main() -> i32 {\n    return code(\"start\", false) + code(\"quit\", true) * 10\n\
+                      + code(\"quit\", false) * 100 + code(\"help\", false) * 1000 + code(\"zzz\", false) * 10000;\n}";
+        let tokens = Lexer::new(source, "test.zr").tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let compiled = Compiler::new().compile(&program).unwrap();
+        assert!(compiled
+            .instructions
+            .iter()
+            .any(|i| matches!(i, Instruction::SwitchStr { cases, .. } if cases.len() == 3)));
+
+        let bytecode = Bytecode::deserialize(&compiled.serialize()).unwrap();
+        let result = VM::new().run(&bytecode).unwrap();
+        assert_eq!(result, Some(Value::I32(3421)));
+    }
+
+    #[test]
+    fn test_compound_assignment_evaluates_place_once() {
+        let result = run("struct Counter { picks: i32, hp: i32 }\n\
+                          impl Counter {\n    func pick(&mut self) -> i32 {\n        self.picks += 1;\n        return 1;\n    }\n}\n\
+                          func main() -> i32 {\n    let mut c = Counter { picks: 0, hp: 5 };\n    let mut v = [10, 20, 30];\n\
+                          v[c.pick()] *= 3;\n    c.hp -= 2;\n    return v[1] + c.picks * 100 + c.hp;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(163)));
+    }
+
+    #[test]
+    fn test_self_referencing_struct_reports_error() {
+        let err = run("struct Node { value: i32, next: i32 }\n\
+                       func main() {\n    let mut n = Node { value: 1, next: 0 };\n\
+                       n.next = n;\n    to_bytes(n);\n}")
+        .unwrap_err();
+        assert!(err.message.contains("nested more than"), "{}", err.message);
+    }
+
+    #[test]
+    fn test_closures_capture_their_environment() {
+        let result = run("func make_counter(start: i32) -> Fn() -> i32 {\n\
+                          let mut count = start;\n    move || {\n        count = count + 1;\n        count\n    }\n}\n\
+                          func apply(f: Fn(i32) -> i32, x: i32) -> i32 {\n    return f(x);\n}\n\
+                          func main() -> i32 {\n    let next = make_counter(10);\n    next();\n\
+                          let step = next();\n    return apply(|x| x * step, 2) + make_counter(0)();\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(25)));
+    }
+
+    #[test]
+    fn test_match_nested_patterns_and_guards() {
+        let result = run("enum Shape { Circle(i32), Square(i32) }\n\
+                          struct Point { x: i32, y: i32 }\n\
+                          func size(s: Shape) -> i32 {\n    match s {\n        Shape::Circle(r) if r > 10 => 1000,\n\
+                          Shape::Circle(r) => r,\n        Shape::Square(5) => 50,\n        Shape::Square(w) => w * w,\n    }\n}\n\
+                          func axis(p: Point) -> i32 {\n    match p {\n        Point { x: 0, y } => y,\n        _ => -1,\n    }\n}\n\
+                          func main() -> i32 {\n    let o = Some(Some(7));\n    let inner = match o {\n        Some(None) => 0,\n\
+                          Some(Some(n)) => n,\n        None => 0,\n    };\n\
+                          return size(Shape::Circle(20)) + size(Shape::Circle(2)) + size(Shape::Square(5))\n\
+                          + size(Shape::Square(3)) + axis(Point { x: 0, y: 100 }) + axis(Point { x: 1, y: 0 }) + inner;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(1000 + 2 + 50 + 9 + 100 - 1 + 7)));
+    }
+
+    #[test]
+    fn test_tuples_build_index_and_destructure() {
+        let result = run("func divmod(a: i32, b: i32) -> (i32, i32) {\n    (a / b, a % b)\n}\n\
+                          func main() -> i32 {\n    let (q, mut r) = divmod(17, 5);\n    r += 10;\n\
+                          let nested = (1, (2, 3));\n    let same = (1, \"a\") == (1, \"a\");\n\
+                          let hit = match nested {\n        (1, (x, 3)) => x,\n        _ => 0,\n    };\n\
+                          if same {\n        return q * 1000 + r * 10 + nested.1.1 + hit * 100;\n    }\n    return -1;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(3000 + 120 + 3 + 200)));
+        assert_eq!(
+            Value::Tuple(vec![Value::Int(1), Value::String("a".to_string())]).to_string(),
+            "(1, a)"
+        );
+        assert_eq!(Value::Tuple(vec![Value::Int(1)]).to_string(), "(1,)");
+    }
+
+    #[test]
+    fn test_constants_are_folded() {
+        let result = run("const WIDTH = 320;\nconst HEIGHT = WIDTH * 3 / 4;\nconst MASK: u8 = 0x0F;\n\
+                          const SCALE: f64 = WIDTH as f64 / 2.0;\n\
+                          func main() -> i32 {\n    let add = |x: i32| x + HEIGHT;\n\
+                          if MASK as i32 == 15 && SCALE > 159.0 {\n        return add(WIDTH);\n    }\n    return 0;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::Int(560)));
+        let err = run("const A: i32 = 7 % (2 - 2);\nfunc main() { }").unwrap_err();
+        assert!(err.message.contains("Division by zero in a constant"));
+        // Constants are folded in their declared type
+        for source in [
+            "const A: i32 = 2147483647 * 2;\nfunc main() { }",
+            "const C = 2000000000;\nconst A: i32 = C + C;\nfunc main() { }",
+        ] {
+            let err = run(source).unwrap_err();
+            assert!(
+                err.message.contains("does not fit in i32"),
+                "{}",
+                err.message
+            );
+        }
+        let result = run("const A: u8 = 200 + 55;\nconst B: i32 = -2147483648;\n\
+                          func main() -> Bool { A == 255 && B < 0 }")
+        .unwrap();
+        assert_eq!(result, Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_string_methods() {
+        let result = run("func main() -> i32 {\n    let words = \" Zyra,lang \".trim().split(\",\");\n\
+                          let first = words[0].to_lower();\n    let chars = \"abc\".chars();\n\
+                          if first.starts_with(\"zy\") && chars[2] == 'c' && words[1].contains(\"an\") {\n\
+                          let n = match \"12\".parse_int() {\n            Some(n) => n,\n            None => -1,\n        };\n\
+                          return n + \"héllo\".len();\n    }\n\
+                          return 0;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(17)));
+    }
+
+    #[test]
+    fn test_strings_are_indexed_by_character() {
+        let result = run("func main() -> i32 {\n    let s = \"naïve café\";\n\
+                          if s[2] == 'ï' && s.char_at(9) == Some('é') && s.substring(6, 50) == \"café\" {\n\
+                          return s.len() * 100 + s.byte_len();\n    }\n    return 0;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(1012)));
+        let err = run("func main() { let s = \"héllo\"; println(s[5]); }").unwrap_err();
+        assert!(err
+            .message
+            .contains("Index 5 out of bounds for string of length 5"));
+    }
+
+    #[test]
+    fn test_seeded_random_replays_exactly() {
+        let result = run("import std::random;\n\
+                          func draw() -> i64 {\n    let deck = shuffle(vec[1, 2, 3, 4, 5, 6, 7, 8]);\n\
+                          let pick = match choice(&deck) {\n        Some(n) => n,\n        None => 0,\n    };\n\
+                          return deck[0] * 100 + pick * 10 + random(1, 6);\n}\n\
+                          func main() -> bool {\n    seed(7);\n    let first = draw();\n\
+                          seed(7);\n    return draw() == first && random_f32() < 1.0;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_json_round_trips_heap_values() {
+        let result = run(r###"import std::json;
+enum Kind { Hero, Npc(i32) }
+struct Save { name: String, inv: Vec<String>, kind: Kind }
+func main() -> i32 {
+    let save = Save { name: "Bo", inv: vec["a", "bc"], kind: Kind::Npc(3) };
+    let text = json_stringify(&save);
+    let back = json_parse(&text);
+    if text == r#"{"name":"Bo","inv":["a","bc"],"kind":{"Npc":3}}"# {
+        return back.kind.Npc * 10 + back.inv[1].len();
+    }
+    return 0;
+}"###)
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(32)));
+    }
+
+    #[test]
+    fn test_game_loop_runs_fixed_steps_per_frame() {
+        game::set_headless(4);
+        let result = run("import std::game;
+struct Count { updates: i32, frames: i32 }
+func main() -> i32 {
+    let win = Window(8, 8, \"loop\");
+    let mut count = Count { updates: 0, frames: 0 };
+    game_loop(|dt: float| { count.updates = count.updates + 1; }, || {
+        count.frames = count.frames + 1;
+    }, 120);
+    return count.updates * 10 + count.frames;
+}")
+        .unwrap();
+        // One update before the first frame, then two per 1/60 s frame
+        assert_eq!(result, Some(Value::I32(74)));
+        assert!(run("func main() { game_loop(1, 2, 60); }").is_err());
+    }
+
+    #[test]
+    fn test_values_nested_too_deep_are_errors() {
+        let err = run("func main() {
+    let mut v = None;
+    for i in 0..20000 {
+        v = Some(v);
+    }
+}")
+        .unwrap_err();
+        assert!(err.message.contains("nested more than"), "{}", err.message);
+        let result = run("func main() -> i32 {
+    let mut v = None;
+    for i in 0..500 {
+        v = Some(v);
+    }
+    return 1;
+}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(1)));
+    }
+
+    #[test]
+    fn test_static_globals_keep_their_value_across_calls() {
+        let result = run("const BONUS = 5;
+static mut SCORE: i32 = BONUS * 2;
+                          func add(n: i32) {
+    SCORE += n;
+}
+                          func main() -> i32 {
+    add(3);
+    add(4);
+    let SCORE_TWICE = SCORE * 2;
+    return SCORE_TWICE;
+}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(34)));
+    }
+
+    #[test]
+    fn test_struct_destructuring_let() {
+        let result = run("struct Point { x: i32, y: i32 }\nstruct Line { from: Point, to: Point }\n\
+                          func main() -> i32 {\n    let line = Line { from: Point { x: 1, y: 2 }, to: Point { x: 3, y: 4 } };\n\
+                          let Line { from: Point { x, y }, to } = line;\n    let Point { x: tx, .. } = &to;\n\
+                          return x * 1000 + y * 100 + tx * 10 + to.y;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(1234)));
+    }
+
+    #[test]
+    fn test_match_ranges_and_or_patterns() {
+        let result = run("enum Shape { Circle(i32), Square(i32), Dot }\n\
+                          func size(s: Shape) -> i32 {\n    match s {\n        Shape::Circle(x) | Shape::Square(x) => x,\n\
+                          Shape::Dot => 0,\n    }\n}\n\
+                          func digits(n: i32) -> i32 {\n    match n {\n        -9..=-1 | 0..10 => 1,\n        10..=99 => 2,\n        _ => 3,\n    }\n}\n\
+                          func kind(c: char) -> i32 {\n    match c {\n        'a' | 'e' | 'i' | 'o' | 'u' => 1,\n\
+                          'a'..='z' => 2,\n        _ => 3,\n    }\n}\n\
+                          func main() -> i32 {\n    return digits(-4) + digits(9) * 10 + digits(10) * 100 + digits(100) * 1000\n\
+                          + kind('e') * 10000 + kind('x') * 100000 + kind('Z') * 1000000\n\
+                          + size(Shape::Square(3)) * 10000000;\n}")
+        .unwrap();
+        assert_eq!(result, Some(Value::I32(33_213_211)));
+    }
+
+    #[test]
+    fn test_undeclared_capabilities_are_refused() {
+        let tokens = Lexer::new(
+            "func main() -> bool {\n    return file_exists(\"zyra.toml\");\n}",
+            "test.zr",
+        )
+        .tokenize()
+        .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let bytecode = Compiler::new().compile(&program).unwrap();
+
+        let err = VM::new()
+            .with_capabilities(vec![Capability::Audio])
+            .run(&bytecode)
+            .unwrap_err();
+        assert_eq!(err.kind, "CapabilityError");
+        assert!(err.message.contains("\"fs\" capability"), "{}", err.message);
+        assert_eq!(err.location.as_ref().map(|l| l.line), Some(2));
+        let mut granted = VM::new().with_capabilities(vec![Capability::Fs]);
+        assert!(granted.run(&bytecode).is_ok());
+    }
+
+    #[test]
+    fn test_overflow_modes() {
+        let source =
+            "func main() -> Int {\n    let big = 9223372036854775807;\n    return big + 1;\n}";
+        let min = run_with(source, OverflowMode::Wrap).unwrap();
+        assert_eq!(min, Some(Value::Int(i64::MIN)));
+        let max = run_with(source, OverflowMode::Saturate).unwrap();
+        assert_eq!(max, Some(Value::Int(i64::MAX)));
+        let err = run_with(source, OverflowMode::Error).unwrap_err();
+        assert_eq!(err.kind, "OverflowError");
+        assert!(
+            err.message.contains("does not fit in Int"),
+            "{}",
+            err.message
+        );
+        assert_eq!(err.location.as_ref().map(|l| l.line), Some(3));
+
+        // An i32 overflows at the i32 bounds, whatever the mode
+        let source = "func main() -> i32 {\n    let big: i32 = 2147483647;\n    return big + 1;\n}";
+        let min = run_with(source, OverflowMode::Wrap).unwrap();
+        assert_eq!(min, Some(Value::I32(i32::MIN)));
+        let max = run_with(source, OverflowMode::Saturate).unwrap();
+        assert_eq!(max, Some(Value::I32(i32::MAX)));
+        let err = run_with(source, OverflowMode::Error).unwrap_err();
+        assert!(
+            err.message.contains("2147483647 + 1 does not fit in i32"),
+            "{}",
+            err.message
+        );
+        let source = "import std::math;\nfunc main() -> i32 {\n    let big: i32 = 2147483647;\n\
+                      return wrapping_add(big, 1);\n}";
+        let wrapped = run_with(source, OverflowMode::Error).unwrap();
+        assert_eq!(wrapped, Some(Value::I32(i32::MIN)));
+
+        // ...including arithmetic on unannotated variables assigned to an i32
+        let source = "func main() -> i32 {\n    let x = 2000000000;\n    let w: i32 = x + x;\n\
+                      return w;\n}";
+        let wrapped = run_with(source, OverflowMode::Wrap).unwrap();
+        assert_eq!(
+            wrapped,
+            Some(Value::I32(2000000000i32.wrapping_add(2000000000)))
+        );
+        let err = run_with(source, OverflowMode::Error).unwrap_err();
+        assert!(
+            err.message
+                .contains("2000000000 + 2000000000 does not fit in i32"),
+            "{}",
+            err.message
+        );
+
+        // Dividing the minimum by -1 is an error rather than a crash
+        for op in ["/", "%"] {
+            let source = format!(
+                "func main() -> Int {{\n    let min = 0 - 9223372036854775807 - 1;\n\
+                 return min {} (0 - 1);\n}}",
+                op
+            );
+            let err = run_with(&source, OverflowMode::Wrap).unwrap_err();
+            assert_eq!(err.kind, "RuntimeError", "{}", err.message);
+        }
+    }
+
+    #[test]
+    fn test_declared_integers_work_with_the_library() {
+        let source = "import std::math;\nfunc add(a: i32, b: i32) -> i32 {\n    return a + b;\n}\n\
+                      func main() -> Int {\n    let n: i32 = 2;\n    assert_eq(add(1, 2), 3);\n\
+                      assert(vec[3, 1, 2].contains(n));\n    assert(set{1, 2}.contains(n));\n\
+                      return abs(0 - n) * 100 + max(n, 7) * 10 + pow(n, 3);\n}";
+        assert_eq!(run(source).unwrap(), Some(Value::Int(278)));
+    }
+
+    #[test]
+    fn test_user_functions_shadow_library_names() {
+        let source = "func tr(n: Int) -> Int {\n    return n * 2;\n}\n\
+                      func main() -> Int {\n    return tr(21);\n}";
+        assert_eq!(run(source).unwrap(), Some(Value::Int(42)));
+        let source = "import std::i18n;\nfunc language() -> String {\n    return \"mine\";\n}\n\
+                      func main() -> String {\n    return language();\n}";
+        assert_eq!(
+            run(source).unwrap(),
+            Some(Value::String("mine".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_set_literals_keep_their_order() {
+        let source = "func main() -> Int {\n    let v = set{3, 1, 2, 3}.to_vec();\n\
+                      return v.len() * 1000 + v[0] * 100 + v[1] * 10 + v[2];\n}";
+        assert_eq!(run(source).unwrap(), Some(Value::Int(3312)));
+    }
+
+    #[test]
+    fn test_set_exit_code_and_on_panic() {
+        let run_vm = |body: &str| {
+            let source = format!("import std::process;\nfunc main() {{\n{}\n}}", body);
+            let tokens = Lexer::new(&source, "test.zr").tokenize().unwrap();
+            let program = Parser::new(tokens).parse().unwrap();
+            let bytecode = Compiler::new().compile(&program).unwrap();
+            let mut vm = VM::new();
+            let result = vm.run(&bytecode);
+            (vm, result)
+        };
+
+        let (vm, result) = run_vm("set_exit_code(3);");
+        assert!(result.is_ok());
+        assert_eq!(vm.exit_code(), 3);
+
+        for code in ["256", "-1"] {
+            let (vm, result) = run_vm(&format!("set_exit_code({});", code));
+            assert!(result.unwrap_err().message.contains("from 0 to 255"));
+            assert_eq!(vm.exit_code(), 0);
+        }
+
+        // The hook gets the message and may still choose the exit status
+        let (vm, result) = run_vm(
+            "on_panic(|message| {\n    if message.contains(\"by zero\") { set_exit_code(7); }\n});\n\
+             let zero = 0;\nprintln(1 / zero);",
+        );
+        assert!(result.is_err());
+        assert!(vm.panic_reported());
+        assert_eq!(vm.exit_code(), 7);
+
+        let (vm, result) = run_vm("on_panic(5);");
+        assert!(result.is_err());
+        assert!(!vm.panic_reported());
+    }
+
+    #[test]
+    fn test_weak_handles_do_not_keep_values_alive() {
+        let program = |before_upgrade: &str| {
+            format!(
+                "import std::mem;\nstruct Node {{ v: Int }}\nfunc main() -> Int {{\n\
+                 let a = Node {{ v: 7 }};\n    let w = weak(&a);\n    {}\n\
+                 return match w.upgrade() {{ Some(n) => n.v, None => -1 }};\n}}",
+                before_upgrade
+            )
+        };
+        assert_eq!(run(&program("")).unwrap(), Some(Value::Int(7)));
+        assert_eq!(run(&program("drop(a);")).unwrap(), Some(Value::Int(-1)));
+    }
+
+    #[test]
+    fn test_chained_method_calls() {
+        let source = "struct B { w: Int, h: Int }\nimpl B {\n    func width(self, w: Int) -> B { B { w: w, h: self.h } }\n    func height(self, h: Int) -> B { B { w: self.w, h: h } }\n    func area(self) -> Int { self.w * self.h }\n}\nfunc make() -> B { B { w: 1, h: 1 } }\nfunc main() -> Int {\n    let parts = vec[make().width(2)];\n    return make().width(3).height(4).area() + parts[0].height(5).area();\n}";
+        assert_eq!(run(source).unwrap(), Some(Value::Int(22)));
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        let source = "func main() -> Int {\n    let packed = (255 << 16) | (128 << 8) | 7;\n    return (packed >> 8) & 255 ^ 1;\n}";
+        assert_eq!(run(source).unwrap(), Some(Value::Int(129)));
+        let source = "func main() -> u8 {\n    let b = 250 as u8;\n    return !b;\n}";
+        assert_eq!(run(source).unwrap(), Some(Value::U8(5)));
+
+        let source = "func main() -> Int {\n    let n = 64;\n    return 1 << n;\n}";
+        let err = run(source).unwrap_err();
+        assert!(err.message.contains("Cannot shift"), "{}", err.message);
+    }
+
+    #[test]
+    fn test_sized_integer_literals() {
+        let source = "func main() -> i8 {\n    return -128i8;\n}";
+        assert_eq!(run(source).unwrap(), Some(Value::I8(-128)));
+        let source = "func main() -> u8 {\n    let x: u8 = 250;\n    return x + 5;\n}";
+        assert_eq!(run(source).unwrap(), Some(Value::U8(255)));
+
+        let source = "func main() -> u8 {\n    let x: u8 = 250;\n    return x + 6;\n}";
+        let err = run(source).unwrap_err();
+        assert!(
+            err.message.contains("does not fit in u8"),
+            "{}",
+            err.message
+        );
+
+        // Literal arguments and operands take the type their context declares
+        let source = "func add(b: u8) -> u8 {\n    return b + 250;\n}\n\
+                      func main() -> u8 {\n    return add(10);\n}";
+        let err = run(source).unwrap_err();
+        assert!(
+            err.message.contains("10 + 250 does not fit in u8"),
+            "{}",
+            err.message
+        );
+        let source = "struct Pixel { level: u8 }\n\
+                      func main() -> u8 {\n    let mut p = Pixel { level: 200 };\n    p.level += 55;\n    return p.level;\n}";
+        assert_eq!(run(source).unwrap(), Some(Value::U8(255)));
+    }
+
+    #[test]
+    fn test_allocations_inside_the_checked_region_are_reported() {
+        // The first frame is warm-up and the Vec built after the loop
+        // belongs to no frame, so only the struct in the loop is reported
+        let source = "struct P { x: i32 }\n\
+                      func fresh(n: i32) -> Vec<i32> {\n    return vec[n];\n}\n\
+                      func main() {\n    let keep = vec[1];\n    let mut i = 0;\n\
+                      while i < 4 {\n        let p = P { x: i };\n        i = i + 1;\n        display();\n    }\n\
+                      let after = fresh(i);\n}";
+        let tokens = Lexer::new(source, "test.zr").tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let mut bytecode = Compiler::new().compile(&program).unwrap();
+        bytecode.source_file = "test.zr".to_string();
+        let sites = |region: &str| {
+            let mut vm = VM::new().with_alloc_check(Region::parse(region));
+            vm.run(&bytecode).unwrap();
+            vm.alloc_report(&bytecode)
+                .iter()
+                .map(|e| (e.location.as_ref().unwrap().line, e.message.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sites("frame"),
+            [(
+                9,
+                "Struct allocated inside a frame 3 times (3 frames checked)".to_string()
+            )]
+        );
+        assert_eq!(
+            sites("fresh"),
+            [(3, "Vec allocated inside fresh() once".to_string())]
+        );
+        assert!(VM::new().alloc_report(&bytecode).is_empty());
+    }
+}
@@ -1,8 +1,194 @@
 //! Runtime values for Zyra VM
 
 pub use crate::compiler::bytecode::{Value, WindowState};
+use crate::compiler::OverflowMode;
+use crate::stdlib::math;
 
 impl Value {
+    /// Give an untyped `Int` or `Float` operand the sized type of the other
+    /// one. Literals and unannotated values are untyped at run time, while
+    /// `as` produces sized values; the semantic pass has already checked
+    /// that both sides have the same type.
+    pub fn unify_numeric(a: Value, b: Value) -> (Value, Value) {
+        fn adopt(untyped: &Value, sized: &Value) -> Option<Value> {
+            Some(match (untyped, sized) {
+                (Value::Int(n), Value::I8(_)) => Value::I8(*n as i8),
+                (Value::Int(n), Value::I32(_)) => Value::I32(*n as i32),
+                (Value::Int(n), Value::I64(_)) => Value::I64(*n),
+                (Value::Int(n), Value::U8(_)) => Value::U8(*n as u8),
+                (Value::Int(n), Value::U32(_)) => Value::U32(*n as u32),
+                (Value::Int(n), Value::U64(_)) => Value::U64(*n as u64),
+                (Value::Float(x), Value::F32(_)) => Value::F32(*x as f32),
+                (Value::Float(x), Value::F64(_)) => Value::F64(*x),
+                _ => return None,
+            })
+        }
+        match (adopt(&a, &b), adopt(&b, &a)) {
+            (Some(a), _) => (a, b),
+            (_, Some(b)) => (a, b),
+            _ => (a, b),
+        }
+    }
+
+    /// Integer `+`, `-` or `*` (`op`) on two operands of the same integer type,
+    /// handling overflow as `mode` says. Returns None for other operands, and an
+    /// error message for an overflow in `OverflowMode::Error`.
+    pub fn int_arith(
+        &self,
+        op: char,
+        other: &Value,
+        mode: OverflowMode,
+    ) -> Option<Result<Value, String>> {
+        macro_rules! arith {
+            ($a:expr, $b:expr, $variant:path, $type_name:literal) => {{
+                let (a, b) = ($a, $b);
+                let (wrapped, overflowed) = match op {
+                    '+' => a.overflowing_add(b),
+                    '-' => a.overflowing_sub(b),
+                    _ => a.overflowing_mul(b),
+                };
+                Some(match (overflowed, mode) {
+                    (false, _) | (true, OverflowMode::Wrap) => Ok($variant(wrapped)),
+                    (true, OverflowMode::Saturate) => Ok($variant(match op {
+                        '+' => a.saturating_add(b),
+                        '-' => a.saturating_sub(b),
+                        _ => a.saturating_mul(b),
+                    })),
+                    (true, OverflowMode::Error) => Err(format!(
+                        "Integer overflow: {} {} {} does not fit in {}",
+                        a, op, b, $type_name
+                    )),
+                })
+            }};
+        }
+        match (self, other) {
+            (Value::I8(a), Value::I8(b)) => arith!(*a, *b, Value::I8, "i8"),
+            (Value::I32(a), Value::I32(b)) => arith!(*a, *b, Value::I32, "i32"),
+            (Value::I64(a), Value::I64(b)) => arith!(*a, *b, Value::I64, "i64"),
+            (Value::Int(a), Value::Int(b)) => arith!(*a, *b, Value::Int, "Int"),
+            (Value::U8(a), Value::U8(b)) => arith!(*a, *b, Value::U8, "u8"),
+            (Value::U32(a), Value::U32(b)) => arith!(*a, *b, Value::U32, "u32"),
+            (Value::U64(a), Value::U64(b)) => arith!(*a, *b, Value::U64, "u64"),
+            _ => None,
+        }
+    }
+
+    /// A plain `Int` given the declared integer type `type_name`, with a value
+    /// outside that type's range handled as `mode` says. Other values are
+    /// returned as they are.
+    pub fn narrow(&self, type_name: &str, mode: OverflowMode) -> Result<Value, String> {
+        let Value::Int(n) = *self else {
+            return Ok(self.clone());
+        };
+        let (min, max): (i128, i128) = match type_name {
+            "i8" => (i8::MIN.into(), i8::MAX.into()),
+            "i32" => (i32::MIN.into(), i32::MAX.into()),
+            "u8" => (0, u8::MAX.into()),
+            "u32" => (0, u32::MAX.into()),
+            "u64" => (0, u64::MAX.into()),
+            _ => (i64::MIN.into(), i64::MAX.into()),
+        };
+        let n = match (mode, i128::from(n)) {
+            (_, value) if (min..=max).contains(&value) => n,
+            (OverflowMode::Wrap, _) => n,
+            (OverflowMode::Saturate, value) => value.clamp(min, max) as i64,
+            (OverflowMode::Error, _) => {
+                return Err(format!(
+                    "Integer overflow: {} does not fit in {}",
+                    n, type_name
+                ))
+            }
+        };
+        Value::Int(n).cast(type_name)
+    }
+
+    /// The value converted by `as` to the type named `target_type`, or an
+    /// error message when it isn't a number
+    pub fn cast(&self, target_type: &str) -> Result<Value, String> {
+        let int = || self.as_i64().ok_or("Cannot cast value to integer");
+        let float = || self.as_f64().ok_or("Cannot cast value to float");
+        Ok(match target_type {
+            // Integer casts
+            "i8" => Value::I8(int()? as i8),
+            "i32" => Value::I32(int()? as i32),
+            "i64" | "Int" => Value::I64(int()?),
+            // Unsigned integer casts
+            "u8" => Value::U8(int()? as u8),
+            "u32" => Value::U32(int()? as u32),
+            "u64" => Value::U64(int()? as u64),
+            // Float casts
+            "f32" => Value::F32(float()? as f32),
+            "f64" | "Float" => Value::Float(float()?),
+            // Same type - return as-is
+            _ => self.clone(),
+        })
+    }
+
+    /// Whether this is an integer of a declared type (`i32`, `u8`...) rather
+    /// than a plain `Int`
+    pub fn is_sized_int(&self) -> bool {
+        matches!(
+            self,
+            Value::I8(_)
+                | Value::I32(_)
+                | Value::I64(_)
+                | Value::U8(_)
+                | Value::U32(_)
+                | Value::U64(_)
+        )
+    }
+
+    /// An integer of any type as a plain `Int`, which is what indexes are;
+    /// other values are returned as they are
+    pub fn into_plain_int(self) -> Value {
+        match self.is_sized_int() {
+            true => Value::Int(self.as_i64().unwrap_or_default()),
+            false => self,
+        }
+    }
+
+    /// The value of an integer of any type, wide enough for all of them
+    fn integer(&self) -> Option<i128> {
+        match self {
+            Value::Int(n) => Some(*n as i128),
+            Value::U64(n) => Some(*n as i128),
+            other if other.is_sized_int() => other.as_i64().map(i128::from),
+            _ => None,
+        }
+    }
+
+    /// A number, bool or char as an i64
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::I8(n) => Some(*n as i64),
+            Value::I32(n) => Some(*n as i64),
+            Value::I64(n) | Value::Int(n) => Some(*n),
+            Value::U8(n) => Some(*n as i64),
+            Value::U32(n) => Some(*n as i64),
+            Value::U64(n) => Some(*n as i64),
+            Value::F32(n) => Some(*n as i64),
+            Value::F64(n) | Value::Float(n) => Some(*n as i64),
+            Value::Bool(b) => Some(if *b { 1 } else { 0 }),
+            Value::Char(c) => Some(*c as i64),
+            _ => None,
+        }
+    }
+
+    /// A number as an f64
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::I8(n) => Some(*n as f64),
+            Value::I32(n) => Some(*n as f64),
+            Value::I64(n) | Value::Int(n) => Some(*n as f64),
+            Value::U8(n) => Some(*n as f64),
+            Value::U32(n) => Some(*n as f64),
+            Value::U64(n) => Some(*n as f64),
+            Value::F32(n) => Some(*n as f64),
+            Value::F64(n) | Value::Float(n) => Some(*n),
+            _ => None,
+        }
+    }
+
     /// Perform addition
     pub fn add(&self, other: &Value) -> Option<Value> {
         match (self, other) {
@@ -47,6 +233,9 @@ impl Value {
             (Value::F64(a), Value::Float(b)) => Some(Value::F64(a + b)),
             (Value::Float(a), Value::F64(b)) => Some(Value::Float(a + b)),
 
+            // Vec2/Vec3 component-wise addition
+            (Value::Object(_), _) => math::vector_arith('+', self, other),
+
             _ => None,
         }
     }
@@ -75,6 +264,9 @@ impl Value {
             (Value::I32(a), Value::I64(b)) => Some(Value::I64((*a as i64).wrapping_sub(*b))),
             (Value::I64(a), Value::I32(b)) => Some(Value::I64(a.wrapping_sub(*b as i64))),
 
+            // Vec2/Vec3 component-wise subtraction
+            (Value::Object(_), _) => math::vector_arith('-', self, other),
+
             _ => None,
         }
     }
@@ -93,6 +285,9 @@ impl Value {
             (Value::I32(a), Value::F32(b)) => Some(Value::F32(*a as f32 * b)),
             (Value::F32(a), Value::I32(b)) => Some(Value::F32(a * *b as f32)),
 
+            // Vec2/Vec3 scaling
+            (Value::Object(_), _) | (_, Value::Object(_)) => math::vector_arith('*', self, other),
+
             _ => None,
         }
     }
@@ -100,16 +295,19 @@ impl Value {
     /// Perform division
     pub fn div(&self, other: &Value) -> Option<Value> {
         match (self, other) {
-            (Value::Int(a), Value::Int(b)) if *b != 0 => Some(Value::Int(a / b)),
+            (Value::Int(a), Value::Int(b)) => a.checked_div(*b).map(Value::Int),
             (Value::Float(a), Value::Float(b)) if *b != 0.0 => Some(Value::Float(a / b)),
 
-            (Value::I32(a), Value::I32(b)) if *b != 0 => Some(Value::I32(a / b)),
+            (Value::I32(a), Value::I32(b)) => a.checked_div(*b).map(Value::I32),
             (Value::F32(a), Value::F32(b)) if *b != 0.0 => Some(Value::F32(a / b)),
-            (Value::I64(a), Value::I64(b)) if *b != 0 => Some(Value::I64(a / b)),
+            (Value::I64(a), Value::I64(b)) => a.checked_div(*b).map(Value::I64),
 
             (Value::I32(a), Value::F32(b)) if *b != 0.0 => Some(Value::F32(*a as f32 / b)),
             (Value::F32(a), Value::I32(b)) if *b != 0 => Some(Value::F32(a / *b as f32)),
 
+            // Vec2/Vec3 scaling
+            (Value::Object(_), _) => math::vector_arith('/', self, other),
+
             _ => None,
         }
     }
@@ -117,9 +315,9 @@ impl Value {
     /// Perform modulo
     pub fn modulo(&self, other: &Value) -> Option<Value> {
         match (self, other) {
-            (Value::Int(a), Value::Int(b)) if *b != 0 => Some(Value::Int(a % b)),
-            (Value::I32(a), Value::I32(b)) if *b != 0 => Some(Value::I32(a % b)),
-            (Value::I64(a), Value::I64(b)) if *b != 0 => Some(Value::I64(a % b)),
+            (Value::Int(a), Value::Int(b)) => a.checked_rem(*b).map(Value::Int),
+            (Value::I32(a), Value::I32(b)) => a.checked_rem(*b).map(Value::I32),
+            (Value::I64(a), Value::I64(b)) => a.checked_rem(*b).map(Value::I64),
             _ => None,
         }
     }
@@ -130,35 +328,146 @@ impl Value {
             Value::Int(a) => Some(Value::Int(-a)),
             Value::Float(a) => Some(Value::Float(-a)),
 
+            Value::I8(a) => a.checked_neg().map(Value::I8),
             Value::I32(a) => Some(Value::I32(-a)),
             Value::I64(a) => Some(Value::I64(-a)),
             Value::F32(a) => Some(Value::F32(-a)),
             Value::F64(a) => Some(Value::F64(-a)),
+            Value::Object(_) => math::vector_neg(self),
             _ => None,
         }
     }
 
-    /// Perform logical not
+    /// Perform logical not, or bitwise not of an integer
     pub fn not(&self) -> Value {
-        Value::Bool(!self.is_truthy())
+        match self {
+            Value::I8(n) => Value::I8(!n),
+            Value::I32(n) => Value::I32(!n),
+            Value::I64(n) => Value::I64(!n),
+            Value::Int(n) => Value::Int(!n),
+            Value::U8(n) => Value::U8(!n),
+            Value::U32(n) => Value::U32(!n),
+            Value::U64(n) => Value::U64(!n),
+            _ => Value::Bool(!self.is_truthy()),
+        }
     }
 
-    /// Equality comparison
+    /// Integer `&`, `|`, `^`, `<<` or `>>` (`op`). `&`, `|` and `^` take two
+    /// operands of the same type; a shift amount may be any integer. Returns
+    /// None for other operands, and an error message for a shift by a negative
+    /// amount or by the type's width or more.
+    pub fn bitwise(&self, op: &str, other: &Value) -> Option<Result<Value, String>> {
+        macro_rules! bits {
+            ($a:expr, $variant:path, $type:ty, $type_name:literal) => {{
+                let a = *$a;
+                if op == "<<" || op == ">>" {
+                    let amount = other.shift_amount()?;
+                    Some(
+                        u32::try_from(amount)
+                            .ok()
+                            .filter(|&n| n < <$type>::BITS)
+                            .map(|n| $variant(if op == "<<" { a << n } else { a >> n }))
+                            .ok_or_else(|| {
+                                format!("Cannot shift {} by {} bits", $type_name, amount)
+                            }),
+                    )
+                } else {
+                    let $variant(b) = other else {
+                        return None;
+                    };
+                    Some(Ok($variant(match op {
+                        "&" => a & b,
+                        "|" => a | b,
+                        _ => a ^ b,
+                    })))
+                }
+            }};
+        }
+        match self {
+            Value::I8(a) => bits!(a, Value::I8, i8, "i8"),
+            Value::I32(a) => bits!(a, Value::I32, i32, "i32"),
+            Value::I64(a) => bits!(a, Value::I64, i64, "i64"),
+            Value::Int(a) => bits!(a, Value::Int, i64, "Int"),
+            Value::U8(a) => bits!(a, Value::U8, u8, "u8"),
+            Value::U32(a) => bits!(a, Value::U32, u32, "u32"),
+            Value::U64(a) => bits!(a, Value::U64, u64, "u64"),
+            _ => None,
+        }
+    }
+
+    /// An integer as a shift amount
+    fn shift_amount(&self) -> Option<i64> {
+        match self {
+            Value::I8(n) => Some(*n as i64),
+            Value::I32(n) => Some(*n as i64),
+            Value::I64(n) | Value::Int(n) => Some(*n),
+            Value::U8(n) => Some(*n as i64),
+            Value::U32(n) => Some(*n as i64),
+            Value::U64(n) => Some(i64::try_from(*n).unwrap_or(i64::MAX)),
+            _ => None,
+        }
+    }
+
+    /// Equality comparison.
+    /// Enum payloads are compared from a worklist, so deeply nested values
+    /// cannot overflow the host stack.
     pub fn eq(&self, other: &Value) -> Value {
-        Value::Bool(match (self, other) {
-            (Value::Int(a), Value::Int(b)) => a == b,
-            (Value::Float(a), Value::Float(b)) => (a - b).abs() < f64::EPSILON,
-
-            (Value::I32(a), Value::I32(b)) => a == b,
-            (Value::I64(a), Value::I64(b)) => a == b,
-            (Value::F32(a), Value::F32(b)) => (a - b).abs() < f32::EPSILON,
-
-            (Value::Bool(a), Value::Bool(b)) => a == b,
-            (Value::String(a), Value::String(b)) => a == b,
-            (Value::Char(a), Value::Char(b)) => a == b,
-            (Value::None, Value::None) => true,
-            _ => false,
-        })
+        let mut pending = vec![(self, other)];
+        while let Some((a, b)) = pending.pop() {
+            let equal = match (a, b) {
+                // Integers are equal by value whatever their types, so an
+                // `i32` compares equal to the plain `Int` a library returned
+                (a, b) if a.integer().is_some() && b.integer().is_some() => {
+                    a.integer() == b.integer()
+                }
+                (Value::Float(a), Value::Float(b)) => (a - b).abs() < f64::EPSILON,
+
+                (Value::F32(a), Value::F32(b)) => (a - b).abs() < f32::EPSILON,
+
+                (Value::Bool(a), Value::Bool(b)) => a == b,
+                (Value::String(a), Value::String(b)) => a == b,
+                (Value::Char(a), Value::Char(b)) => a == b,
+                (Value::None, Value::None) => true,
+                (Value::Some(a), Value::Some(b))
+                | (Value::Ok(a), Value::Ok(b))
+                | (Value::Err(a), Value::Err(b)) => {
+                    pending.push((a, b));
+                    true
+                }
+                (Value::Tuple(a), Value::Tuple(b)) => {
+                    pending.extend(a.iter().zip(b));
+                    a.len() == b.len()
+                }
+                (
+                    Value::Enum {
+                        type_id: ta,
+                        variant_id: va,
+                        payload: pa,
+                    },
+                    Value::Enum {
+                        type_id: tb,
+                        variant_id: vb,
+                        payload: pb,
+                    },
+                ) => {
+                    ta == tb
+                        && va == vb
+                        && match (pa, pb) {
+                            (Some(a), Some(b)) => {
+                                pending.push((a, b));
+                                true
+                            }
+                            (None, None) => true,
+                            _ => false,
+                        }
+                }
+                _ => false,
+            };
+            if !equal {
+                return Value::Bool(false);
+            }
+        }
+        Value::Bool(true)
     }
 
     /// Less than comparison
@@ -170,6 +479,7 @@ impl Value {
             (Value::I32(a), Value::I32(b)) => Some(Value::Bool(a < b)),
             (Value::I64(a), Value::I64(b)) => Some(Value::Bool(a < b)),
             (Value::F32(a), Value::F32(b)) => Some(Value::Bool(a < b)),
+            (Value::Char(a), Value::Char(b)) => Some(Value::Bool(a < b)),
             (Value::String(a), Value::String(b)) => Some(Value::Bool(a < b)),
 
             // None comparisons: treat None as 0 for numeric comparisons
@@ -189,6 +499,7 @@ impl Value {
             (Value::I32(a), Value::I32(b)) => Some(Value::Bool(a <= b)),
             (Value::I64(a), Value::I64(b)) => Some(Value::Bool(a <= b)),
             (Value::F32(a), Value::F32(b)) => Some(Value::Bool(a <= b)),
+            (Value::Char(a), Value::Char(b)) => Some(Value::Bool(a <= b)),
 
             // None comparisons
             (Value::None, Value::Int(b)) => Some(Value::Bool(&0 <= b)),
@@ -207,6 +518,7 @@ impl Value {
             (Value::I32(a), Value::I32(b)) => Some(Value::Bool(a > b)),
             (Value::I64(a), Value::I64(b)) => Some(Value::Bool(a > b)),
             (Value::F32(a), Value::F32(b)) => Some(Value::Bool(a > b)),
+            (Value::Char(a), Value::Char(b)) => Some(Value::Bool(a > b)),
 
             // None comparisons: treat None as 0 for numeric comparisons
             (Value::None, Value::Int(b)) => Some(Value::Bool(&0 > b)),
@@ -225,6 +537,7 @@ impl Value {
             (Value::I32(a), Value::I32(b)) => Some(Value::Bool(a >= b)),
             (Value::I64(a), Value::I64(b)) => Some(Value::Bool(a >= b)),
             (Value::F32(a), Value::F32(b)) => Some(Value::Bool(a >= b)),
+            (Value::Char(a), Value::Char(b)) => Some(Value::Bool(a >= b)),
 
             // None comparisons
             (Value::None, Value::Int(b)) => Some(Value::Bool(&0 >= b)),